@@ -0,0 +1,79 @@
+//! 最高分，保存为 JSON，创下新纪录时立即写入，下次启动时恢复。
+
+use std::{fs, io, path::PathBuf};
+
+use color_eyre::{eyre::WrapErr, Result};
+use ratatui_common::files;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Saved {
+    best: u32,
+}
+
+#[derive(Debug)]
+pub struct BestScore {
+    path: PathBuf,
+    best: u32,
+}
+
+impl BestScore {
+    /// 读取记录文件。文件还不存在时最高分是 0。
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let saved: Saved = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .wrap_err_with(|| format!("parsing {} failed", path.display()))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Saved::default(),
+            Err(err) => {
+                return Err(err).wrap_err_with(|| format!("reading {} failed", path.display()))
+            }
+        };
+        Ok(Self {
+            path,
+            best: saved.best,
+        })
+    }
+
+    pub fn get(&self) -> u32 {
+        self.best
+    }
+
+    /// 分数超过最高分时更新并写入文件。
+    pub fn record(&mut self, score: u32) -> Result<()> {
+        if score <= self.best {
+            return Ok(());
+        }
+        self.best = score;
+        self.save()
+    }
+
+    /// 保存最高分。
+    fn save(&self) -> Result<()> {
+        serde_json::to_vec(&Saved { best: self.best })
+            .map_err(io::Error::from)
+            .and_then(|bytes| files::write_atomic(&self.path, bytes))
+            .wrap_err_with(|| format!("writing {} failed", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    #[test]
+    fn persist_best_score() {
+        let dir = env::temp_dir().join(format!("2048-demo-best-{}", process::id()));
+        let path = dir.join("best.json");
+
+        let mut best = BestScore::load(path.clone()).unwrap();
+        assert_eq!(best.get(), 0);
+        best.record(120).unwrap();
+        best.record(80).unwrap();
+
+        let best = BestScore::load(path).unwrap();
+        assert_eq!(best.get(), 120);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
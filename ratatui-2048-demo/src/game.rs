@@ -0,0 +1,215 @@
+//! 2048 的规则：所有方块朝一个方向滑动，相同的两个方块相撞时合并成一个，每次移动最多合并一次。
+//! 移动之后在随机的空位上出现一个新方块（90% 是 2，10% 是 4）。
+//!
+//! 每次移动都返回每个方块从哪里滑到哪里，界面据此播放滑动动画。
+
+use rand::{seq::IteratorRandom, Rng};
+
+pub const SIZE: usize = 4;
+
+/// 第一次出现这个方块时获胜，之后可以继续玩。
+pub const GOAL: u32 = 2048;
+
+/// `[行][列]`，0 是空位。
+pub type Grid = [[u32; SIZE]; SIZE];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// 一个方块在一次移动中的路径，合并的两个方块滑到同一个位置。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slide {
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+    /// 移动之前的值。
+    pub value: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Move {
+    pub slides: Vec<Slide>,
+    /// 合并后得到的方块的位置。
+    pub merged: Vec<(usize, usize)>,
+    pub gained: u32,
+    /// 移动之后出现的新方块的位置。
+    pub spawned: Option<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub grid: Grid,
+    pub score: u32,
+}
+
+impl Game {
+    /// 有两个方块的新棋盘。
+    pub fn new(rng: &mut impl Rng) -> Self {
+        let mut game = Self::from_grid([[0; SIZE]; SIZE]);
+        game.spawn(rng);
+        game.spawn(rng);
+        game
+    }
+
+    pub fn from_grid(grid: Grid) -> Self {
+        Self { grid, score: 0 }
+    }
+
+    pub fn max_tile(&self) -> u32 {
+        self.grid.iter().flatten().copied().max().unwrap_or(0)
+    }
+
+    /// 没有空位，相邻的方块也都不相同。
+    pub fn is_over(&self) -> bool {
+        let grid = &self.grid;
+        (0..SIZE).all(|row| {
+            (0..SIZE).all(|col| {
+                let value = grid[row][col];
+                value != 0
+                    && (col + 1 == SIZE || grid[row][col + 1] != value)
+                    && (row + 1 == SIZE || grid[row + 1][col] != value)
+            })
+        })
+    }
+
+    fn spawn(&mut self, rng: &mut impl Rng) -> Option<(usize, usize)> {
+        let (row, col) = (0..SIZE)
+            .flat_map(|row| (0..SIZE).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.grid[row][col] == 0)
+            .choose(rng)?;
+        self.grid[row][col] = if rng.gen_bool(0.9) { 2 } else { 4 };
+        Some((row, col))
+    }
+
+    /// 朝 `direction` 移动，没有任何方块能移动时返回 `None`，也不会出现新方块。
+    pub fn slide(&mut self, direction: Direction, rng: &mut impl Rng) -> Option<Move> {
+        let mut slides = Vec::new();
+        let mut merged = Vec::new();
+        let mut gained = 0;
+        let mut moved = false;
+        for line in 0..SIZE {
+            // 从滑向的那一边开始排列的位置。
+            let cells: [(usize, usize); SIZE] = std::array::from_fn(|i| match direction {
+                Direction::Up => (i, line),
+                Direction::Down => (SIZE - 1 - i, line),
+                Direction::Left => (line, i),
+                Direction::Right => (line, SIZE - 1 - i),
+            });
+            // 排好的方块，以及它是否已经合并过。
+            let mut packed: Vec<(u32, bool)> = Vec::new();
+            for &(row, col) in &cells {
+                let value = self.grid[row][col];
+                if value == 0 {
+                    continue;
+                }
+                let merge = matches!(packed.last(), Some(&(last, false)) if last == value);
+                if merge {
+                    *packed.last_mut().unwrap() = (value * 2, true);
+                    gained += value * 2;
+                } else {
+                    packed.push((value, false));
+                }
+                let to = cells[packed.len() - 1];
+                if merge {
+                    merged.push(to);
+                }
+                moved |= to != (row, col);
+                slides.push(Slide {
+                    from: (row, col),
+                    to,
+                    value,
+                });
+            }
+            for (i, &(row, col)) in cells.iter().enumerate() {
+                self.grid[row][col] = packed.get(i).map_or(0, |&(value, _)| value);
+            }
+        }
+        if !moved {
+            return None;
+        }
+        self.score += gained;
+        Some(Move {
+            slides,
+            merged,
+            gained,
+            spawned: self.spawn(rng),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn tiles(game: &Game) -> usize {
+        game.grid
+            .iter()
+            .flatten()
+            .filter(|&&value| value != 0)
+            .count()
+    }
+
+    /// 向左移动第一行，返回移动后的第一行和得分。
+    fn slide_row(row: [u32; SIZE]) -> Option<([u32; SIZE], u32)> {
+        let mut game = Game::from_grid([row, [0; SIZE], [0; SIZE], [0; SIZE]]);
+        let step = game.slide(Direction::Left, &mut StdRng::seed_from_u64(0))?;
+        Some((game.grid[0], step.gained))
+    }
+
+    #[test]
+    fn merge_once_per_move() {
+        assert_eq!(slide_row([2, 2, 2, 2]), Some(([4, 4, 0, 0], 8)));
+        assert_eq!(slide_row([2, 2, 4, 0]), Some(([4, 4, 0, 0], 4)));
+        assert_eq!(slide_row([4, 0, 4, 8]), Some(([8, 8, 0, 0], 8)));
+        assert_eq!(slide_row([0, 0, 0, 2]), Some(([2, 0, 0, 0], 0)));
+        assert_eq!(slide_row([2, 4, 8, 0]), None);
+    }
+
+    #[test]
+    fn slides_for_animation() {
+        let mut game = Game::from_grid([[0, 0, 0, 0], [2, 0, 0, 0], [2, 0, 0, 0], [4, 0, 0, 0]]);
+        let step = game
+            .slide(Direction::Down, &mut StdRng::seed_from_u64(0))
+            .unwrap();
+        assert_eq!(
+            step.slides,
+            [
+                Slide {
+                    from: (3, 0),
+                    to: (3, 0),
+                    value: 4
+                },
+                Slide {
+                    from: (2, 0),
+                    to: (2, 0),
+                    value: 2
+                },
+                Slide {
+                    from: (1, 0),
+                    to: (2, 0),
+                    value: 2
+                },
+            ]
+        );
+        assert_eq!(step.merged, [(2, 0)]);
+        assert_eq!((game.grid[2][0], game.grid[3][0], game.score), (4, 4, 4));
+        let (row, col) = step.spawned.unwrap();
+        assert!(matches!(game.grid[row][col], 2 | 4));
+        assert_eq!(tiles(&game), 3);
+    }
+
+    #[test]
+    fn game_over() {
+        let mut game = Game::from_grid([[2, 4, 2, 4], [4, 2, 4, 2], [2, 4, 2, 4], [4, 2, 4, 2]]);
+        assert!(game.is_over());
+        game.grid[3][3] = 4;
+        assert!(!game.is_over());
+        assert_eq!(tiles(&Game::new(&mut StdRng::seed_from_u64(0))), 2);
+    }
+}
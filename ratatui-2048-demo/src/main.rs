@@ -0,0 +1,405 @@
+//! 2048 演示：用方向键移动方块，相同的方块合并，凑出 2048。规则见 `game` 模块。
+//!
+//! 每次移动后方块从原来的位置滑到新的位置。动画不需要单独的线程：动画进行时主循环每一帧都
+//! 按经过的时间插值出方块的位置再绘制，动画结束后恢复为只在按键时重绘。减少动态效果时
+//! 直接显示移动后的棋盘。最高分保存在数据目录中的 `best.json`。
+//!
+//! 按键：方向键或 `h` / `j` / `k` / `l` 移动，`u` 撤销，`n` 重新开始，`q` 退出。
+
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use clap::Parser;
+use color_eyre::{eyre::eyre, Result};
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use directories::ProjectDirs;
+use rand::{rngs::StdRng, SeedableRng};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Paragraph},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    motion::Motion,
+    terminal,
+};
+
+use crate::{
+    best::BestScore,
+    game::{Direction, Game, Slide, GOAL, SIZE},
+};
+
+mod best;
+mod game;
+
+const APPLICATION: &str = "ratatui-2048-demo";
+
+/// 动画进行时两帧之间的间隔。
+const FRAME: Duration = Duration::from_millis(16);
+/// 没有动画时等待按键的最长时间。
+const IDLE: Duration = Duration::from_secs(1);
+const SLIDE_DURATION: Duration = Duration::from_millis(120);
+
+/// 最多能撤销的步数。
+const UNDO_LIMIT: usize = 100;
+
+/// 方块的大小，以及相邻两个方块之间的距离。
+const TILE_WIDTH: u16 = 8;
+const TILE_HEIGHT: u16 = 3;
+const PITCH_X: u16 = TILE_WIDTH + 1;
+const PITCH_Y: u16 = TILE_HEIGHT + 1;
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 最高分记录，默认是数据目录中的 `best.json`
+    #[arg(long, value_name = "FILE")]
+    best: Option<PathBuf>,
+    /// 减少动态效果：不播放滑动动画
+    #[arg(long)]
+    reduced_motion: bool,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let path = cli
+        .best
+        .or_else(|| {
+            let project = ProjectDirs::from("", "", APPLICATION)?;
+            Some(project.data_dir().join("best.json"))
+        })
+        .ok_or_else(|| eyre!("no data directory, pass --best"))?;
+    let best = BestScore::load(path)?;
+    let motion = Motion {
+        reduced: cli.reduced_motion,
+    };
+
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result =
+        App::new(best, motion, StdRng::from_entropy()).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+fn tile_style(value: u32) -> Style {
+    let (bg, fg) = match value {
+        2 => (Color::White, Color::Black),
+        4 => (Color::LightYellow, Color::Black),
+        8 => (Color::Yellow, Color::Black),
+        16 => (Color::LightRed, Color::Black),
+        32 => (Color::Red, Color::White),
+        64 => (Color::Magenta, Color::White),
+        128 => (Color::LightMagenta, Color::Black),
+        256 => (Color::LightBlue, Color::Black),
+        512 => (Color::Blue, Color::White),
+        1024 => (Color::Cyan, Color::Black),
+        2048 => (Color::Green, Color::Black),
+        _ => (Color::DarkGray, Color::White),
+    };
+    Style::new().bg(bg).fg(fg).bold()
+}
+
+/// 在 `(x, y)` 绘制一个方块，值为 0 时是空位。
+fn render_tile(value: u32, x: u16, y: u16, area: Rect, buf: &mut Buffer) {
+    let tile = Rect::new(x, y, TILE_WIDTH, TILE_HEIGHT).intersection(area);
+    if value == 0 {
+        buf.set_style(tile, Style::new().bg(Color::Indexed(236)));
+        return;
+    }
+    buf.set_style(tile, tile_style(value));
+    let text = value.to_string();
+    let offset = TILE_WIDTH.saturating_sub(text.len() as u16) / 2;
+    let middle = Rect::new(x + offset, y + TILE_HEIGHT / 2, text.len() as u16, 1);
+    Line::from(text).render(middle.intersection(area), buf);
+}
+
+/// 一次移动的滑动动画。
+struct Animation {
+    slides: Vec<Slide>,
+    started: Instant,
+    duration: Duration,
+}
+
+impl Animation {
+    /// 0.0 到 1.0，先快后慢。
+    fn progress(&self, now: Instant) -> f64 {
+        let t = (now - self.started).as_secs_f64() / self.duration.as_secs_f64();
+        let t = t.min(1.0);
+        1.0 - (1.0 - t) * (1.0 - t)
+    }
+}
+
+struct App {
+    game: Game,
+    /// 之前的棋盘，最后一个是上一步。
+    undo: Vec<Game>,
+    best: BestScore,
+    motion: Motion,
+    rng: StdRng,
+    animation: Option<Animation>,
+    /// 已经凑出过 2048，不再提示。
+    won: bool,
+    /// 最近一次操作的结果，`Err` 显示为红色。
+    message: Option<Result<String, String>>,
+    exit: bool,
+}
+
+impl App {
+    fn new(best: BestScore, motion: Motion, mut rng: StdRng) -> Self {
+        Self {
+            game: Game::new(&mut rng),
+            undo: Vec::new(),
+            best,
+            motion,
+            rng,
+            animation: None,
+            won: false,
+            message: None,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            let now = Instant::now();
+            self.tick(now);
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut(), now))?;
+            let timeout = if self.animation.is_some() {
+                FRAME
+            } else {
+                IDLE
+            };
+            if events.poll(timeout)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key.code, Instant::now());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 结束已经播放完的动画。
+    fn tick(&mut self, now: Instant) {
+        if self
+            .animation
+            .as_ref()
+            .is_some_and(|animation| animation.progress(now) >= 1.0)
+        {
+            self.animation = None;
+        }
+    }
+
+    fn handle_key(&mut self, code: KeyCode, now: Instant) {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
+            KeyCode::Up | KeyCode::Char('k') => self.slide(Direction::Up, now),
+            KeyCode::Down | KeyCode::Char('j') => self.slide(Direction::Down, now),
+            KeyCode::Left | KeyCode::Char('h') => self.slide(Direction::Left, now),
+            KeyCode::Right | KeyCode::Char('l') => self.slide(Direction::Right, now),
+            KeyCode::Char('u') => self.undo(),
+            KeyCode::Char('n') => {
+                self.game = Game::new(&mut self.rng);
+                self.undo.clear();
+                self.animation = None;
+                self.won = false;
+                self.message = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn slide(&mut self, direction: Direction, now: Instant) {
+        let before = self.game.clone();
+        // 上一次的动画还没有播放完时直接开始新的动画，棋盘已经是上一步移动之后的样子。
+        let Some(step) = self.game.slide(direction, &mut self.rng) else {
+            return;
+        };
+        self.undo.push(before);
+        if self.undo.len() > UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+        self.animation = self
+            .motion
+            .animate(SLIDE_DURATION)
+            .map(|duration| Animation {
+                slides: step.slides,
+                started: now,
+                duration,
+            });
+        self.message = None;
+        if let Err(error) = self.best.record(self.game.score) {
+            self.message = Some(Err(format!("{error:#}")));
+        } else if !self.won && self.game.max_tile() >= GOAL {
+            self.won = true;
+            self.message = Some(Ok(format!(
+                "You made {GOAL}! Keep going for a higher score"
+            )));
+        }
+    }
+
+    fn undo(&mut self) {
+        if let Some(game) = self.undo.pop() {
+            self.game = game;
+            self.animation = None;
+            self.message = None;
+        }
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        match &self.message {
+            Some(Ok(message)) => Line::from(message.clone().green()),
+            Some(Err(error)) => Line::from(error.clone().red()),
+            None if self.game.is_over() => {
+                Line::from("No more moves. Undo <U> or start a new game <N>".red())
+            }
+            None => Line::from("Move the tiles with the arrow keys".dim()),
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, now: Instant) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let width = PITCH_X * SIZE as u16 + 1 + 2;
+        let height = PITCH_Y * SIZE as u16 + 1 + 2;
+        let board_area = Rect {
+            x: main.x + main.width.saturating_sub(width) / 2,
+            y: main.y + main.height.saturating_sub(height) / 2,
+            width: width.min(main.width),
+            height: height.min(main.height),
+        };
+        let scores = format!(" Score {}  Best {} ", self.game.score, self.best.get());
+        let block = Block::bordered()
+            .title(" 2048 ".bold())
+            .title(Line::from(scores).right_aligned())
+            .title_bottom(Line::from(" Undo <U> New <N> Quit <Q> ").centered());
+        let inner = block.inner(board_area);
+        block.render(board_area, buf);
+
+        let origin = (inner.x + 1, inner.y + 1);
+        let position = |(row, col): (usize, usize)| {
+            (
+                origin.0 + col as u16 * PITCH_X,
+                origin.1 + row as u16 * PITCH_Y,
+            )
+        };
+        for row in 0..SIZE {
+            for col in 0..SIZE {
+                let (x, y) = position((row, col));
+                render_tile(0, x, y, inner, buf);
+            }
+        }
+        match &self.animation {
+            Some(animation) if animation.progress(now) < 1.0 => {
+                let t = animation.progress(now);
+                let lerp = |from: u16, to: u16| {
+                    (f64::from(from) + (f64::from(to) - f64::from(from)) * t).round() as u16
+                };
+                for slide in &animation.slides {
+                    let (from_x, from_y) = position(slide.from);
+                    let (to_x, to_y) = position(slide.to);
+                    render_tile(
+                        slide.value,
+                        lerp(from_x, to_x),
+                        lerp(from_y, to_y),
+                        inner,
+                        buf,
+                    );
+                }
+            }
+            _ => {
+                for row in 0..SIZE {
+                    for col in 0..SIZE {
+                        let (x, y) = position((row, col));
+                        render_tile(self.game.grid[row][col], x, y, inner, buf);
+                    }
+                }
+            }
+        }
+
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn app(name: &str, motion: Motion) -> (App, PathBuf) {
+        let dir = env::temp_dir().join(format!("2048-demo-{name}-{}", process::id()));
+        let best = BestScore::load(dir.join("best.json")).unwrap();
+        let mut app = App::new(best, motion, StdRng::seed_from_u64(0));
+        app.game = Game::from_grid([[2, 2, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 4]]);
+        (app, dir)
+    }
+
+    fn rows(app: &App, width: u16, height: u16, now: Instant) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf, now))
+    }
+
+    #[test]
+    fn slide_animation() {
+        let (mut app, dir) = app("animation", Motion::default());
+        let start = Instant::now();
+        app.handle_key(KeyCode::Right, start);
+        assert_eq!(app.game.grid[0][3], 4);
+        assert_eq!(app.game.score, 4);
+        assert_eq!(app.best.get(), 4);
+
+        // 动画进行到一半时，第一行的两个 2 还在路上。
+        let half = start + Duration::from_millis(35);
+        let t = app.animation.as_ref().unwrap().progress(half);
+        assert!((0.4..0.6).contains(&t), "{t}");
+        let middle = rows(&app, 41, 20, half);
+        assert!(!middle[3].contains('4'));
+        assert_eq!(middle[3].matches('2').count(), 2);
+
+        let end = start + SLIDE_DURATION;
+        app.tick(end);
+        assert!(app.animation.is_none());
+        let rows = rows(&app, 41, 20, end);
+        assert_eq!(
+            rows[0],
+            format!(" ┌ 2048 {} Score 4  Best 4 ┐ ", "─".repeat(14))
+        );
+        assert!(rows[3].ends_with("│                               4     │ "));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn undo_and_reduced_motion() {
+        let (mut app, dir) = app("undo", Motion::reduced());
+        let now = Instant::now();
+        let before = app.game.clone();
+        app.handle_key(KeyCode::Left, now);
+        assert!(app.animation.is_none());
+        assert_eq!(app.game.grid[0][0], 4);
+
+        // 不能移动时不算一步。
+        app.game.grid = [[2, 4, 2, 4], [4, 2, 4, 2], [2, 4, 2, 4], [4, 2, 4, 2]];
+        app.handle_key(KeyCode::Up, now);
+        assert_eq!(app.undo.len(), 1);
+        assert_eq!(
+            app.status_line().to_string(),
+            "No more moves. Undo <U> or start a new game <N>"
+        );
+
+        app.handle_key(KeyCode::Char('u'), now);
+        assert_eq!(app.game, before);
+        app.handle_key(KeyCode::Char('u'), now);
+        assert_eq!(app.game, before);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,256 @@
+//! 网络带宽监视演示：后台线程定时采样各个网络接口的收发字节数，表格中列出当前速率和累计流量，
+//! 下方用两条迷你折线图显示所选接口最近一段时间的接收和发送速率。
+//!
+//! 按键：`Up` / `Down` 选择接口，`q` 退出。
+
+use std::{
+    collections::VecDeque,
+    sync::mpsc::{self, Receiver},
+    time::Duration,
+};
+
+use clap::Parser;
+use color_eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, RenderDirection, Row, Sparkline, Table, TableState},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+};
+
+use crate::{
+    monitor::{Interface, Monitor},
+    sampler::Sample,
+};
+
+mod monitor;
+mod sampler;
+
+/// 等待按键的最长时间，之后检查有没有新的采样。
+const REFRESH: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// 采样间隔（毫秒）。
+    #[arg(long, default_value_t = 1000)]
+    interval: u64,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let (sender, samples) = mpsc::channel();
+    sampler::spawn(Duration::from_millis(cli.interval.max(1)), sender);
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new(samples).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+struct App {
+    monitor: Monitor,
+    samples: Receiver<Sample>,
+    table: TableState,
+    exit: bool,
+}
+
+impl App {
+    fn new(samples: Receiver<Sample>) -> Self {
+        Self {
+            monitor: Monitor::default(),
+            samples,
+            table: TableState::default().with_selected(Some(0)),
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            self.receive();
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut()))?;
+            if events.poll(REFRESH)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key.code);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self) {
+        while let Ok(sample) = self.samples.try_recv() {
+            self.monitor.record(sample);
+        }
+        // 接口消失后选择不能超出列表。
+        let last = self.monitor.interfaces().len().saturating_sub(1);
+        let selected = self.table.selected().unwrap_or(0);
+        self.table.select(Some(selected.min(last)));
+    }
+
+    fn handle_key(&mut self, code: KeyCode) {
+        let selected = self.table.selected().unwrap_or(0);
+        let last = self.monitor.interfaces().len().saturating_sub(1);
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
+            KeyCode::Up => self.table.select(Some(selected.saturating_sub(1))),
+            KeyCode::Down => self.table.select(Some((selected + 1).min(last))),
+            _ => {}
+        }
+    }
+
+    fn selected(&self) -> Option<(&String, &Interface)> {
+        self.monitor.interfaces().iter().nth(self.table.selected()?)
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let interfaces = self.monitor.interfaces();
+        let table_height = interfaces.len().max(1) as u16 + 3;
+        let [table_area, rx_area, tx_area] = Layout::vertical([
+            Constraint::Length(table_height),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+
+        let rows: Vec<Row> = interfaces
+            .iter()
+            .map(|(name, interface)| {
+                Row::new([
+                    name.clone(),
+                    format!("{}/s", format_bytes(interface.rx_rate())),
+                    format!("{}/s", format_bytes(interface.tx_rate())),
+                    format_bytes(interface.rx_total),
+                    format_bytes(interface.tx_total),
+                ])
+            })
+            .collect();
+        let widths = [
+            Constraint::Fill(1),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(10),
+            Constraint::Length(10),
+        ];
+        let table = Table::new(rows, widths)
+            .header(Row::new(["Interface", "RX", "TX", "RX total", "TX total"]).bold())
+            .highlight_style(Style::new().reversed())
+            .block(
+                Block::bordered()
+                    .title(" Bandwidth ".bold())
+                    .title_bottom(Line::from(" Select <Up>/<Down> Quit <Q> ").centered()),
+            );
+        StatefulWidget::render(table, table_area, buf, &mut self.table);
+
+        let (name, interface) = match self.selected() {
+            Some((name, interface)) => (name.as_str(), interface.clone()),
+            None => ("-", Interface::default()),
+        };
+        history_chart(&interface.rx, "RX", name, Color::Green, rx_area, buf);
+        history_chart(&interface.tx, "TX", name, Color::Blue, tx_area, buf);
+    }
+}
+
+/// 一个方向的速率历史，最新的值在右侧，标题显示当前值和可见范围内的峰值。
+fn history_chart(
+    history: &VecDeque<u64>,
+    label: &str,
+    name: &str,
+    color: Color,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let width = area.width.saturating_sub(2) as usize;
+    let data: Vec<u64> = history.iter().rev().take(width).copied().collect();
+    let current = data.first().copied().unwrap_or(0);
+    let peak = data.iter().copied().max().unwrap_or(0);
+    let title = format!(
+        " {label} {name}  {}/s  peak {}/s ",
+        format_bytes(current),
+        format_bytes(peak)
+    );
+    Sparkline::default()
+        .data(&data)
+        .direction(RenderDirection::RightToLeft)
+        .style(Style::new().fg(color))
+        .block(Block::bordered().title(title))
+        .render(area, buf);
+}
+
+/// 用二进制前缀显示字节数，例如 `1.5 KiB`。
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    #[test]
+    fn render_interfaces() {
+        let (sender, samples) = mpsc::channel();
+        let mut app = App::new(samples);
+        let start = Instant::now();
+        for (seconds, eth0, lo) in [(0, 0, 0), (1, 2048, 100), (2, 4096, 300)] {
+            let totals = [
+                ("eth0".to_string(), (eth0, 10 * seconds)),
+                ("lo".to_string(), (lo, lo)),
+            ];
+            sender
+                .send(Sample {
+                    at: start + Duration::from_secs(seconds),
+                    totals: totals.into_iter().collect(),
+                })
+                .unwrap();
+        }
+        app.receive();
+        app.handle_key(KeyCode::Down);
+        let rows = rows(&mut app, 70, 13);
+        assert_eq!(
+            &rows[..5],
+            [
+                "┌ Bandwidth ─────────────────────────────────────────────────────────┐",
+                "│Interface            RX           TX           RX total   TX total  │",
+                "│eth0                 2.0 KiB/s    10 B/s       4.0 KiB    20 B      │",
+                "│lo                   200 B/s      200 B/s      300 B      300 B     │",
+                "└─────────────────── Select <Up>/<Down> Quit <Q> ────────────────────┘",
+            ]
+        );
+        assert!(rows[5].starts_with("┌ RX lo  200 B/s  peak 200 B/s "));
+        assert!(rows[9].starts_with("┌ TX lo  200 B/s  peak 200 B/s "));
+    }
+
+    #[test]
+    fn bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(3 << 20), "3.0 MiB");
+    }
+}
@@ -0,0 +1,124 @@
+//! 根据相邻两次采样计算每个接口的速率，并保留最近一段时间的历史。
+
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::sampler::Sample;
+
+/// 每个接口保留的历史点数。
+pub const HISTORY: usize = 240;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Interface {
+    pub rx_total: u64,
+    pub tx_total: u64,
+    /// 每秒接收和发送的字节数，最新的在最后。
+    pub rx: VecDeque<u64>,
+    pub tx: VecDeque<u64>,
+}
+
+impl Interface {
+    pub fn rx_rate(&self) -> u64 {
+        self.rx.back().copied().unwrap_or(0)
+    }
+
+    pub fn tx_rate(&self) -> u64 {
+        self.tx.back().copied().unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Monitor {
+    interfaces: BTreeMap<String, Interface>,
+    previous: Option<Sample>,
+}
+
+impl Monitor {
+    pub fn interfaces(&self) -> &BTreeMap<String, Interface> {
+        &self.interfaces
+    }
+
+    /// 记录一次采样。第一次采样只有累计值，从第二次开始才有速率。
+    pub fn record(&mut self, sample: Sample) {
+        self.interfaces
+            .retain(|name, _| sample.totals.contains_key(name));
+        for (name, &(rx_total, tx_total)) in &sample.totals {
+            let interface = self.interfaces.entry(name.clone()).or_default();
+            interface.rx_total = rx_total;
+            interface.tx_total = tx_total;
+            let Some(previous) = &self.previous else {
+                continue;
+            };
+            let Some(&(rx_before, tx_before)) = previous.totals.get(name) else {
+                continue;
+            };
+            let seconds = sample.at.duration_since(previous.at).as_secs_f64();
+            if seconds <= 0.0 {
+                continue;
+            }
+            // 计数器被重置（例如接口重新启用）时差值为负，按 0 计算。
+            let rate = |now: u64, before: u64| (now.saturating_sub(before) as f64 / seconds) as u64;
+            push(&mut interface.rx, rate(rx_total, rx_before));
+            push(&mut interface.tx, rate(tx_total, tx_before));
+        }
+        self.previous = Some(sample);
+    }
+}
+
+fn push(history: &mut VecDeque<u64>, value: u64) {
+    if history.len() == HISTORY {
+        history.pop_front();
+    }
+    history.push_back(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    fn sample(at: Instant, totals: &[(&str, u64, u64)]) -> Sample {
+        Sample {
+            at,
+            totals: totals
+                .iter()
+                .map(|&(name, rx, tx)| (name.to_string(), (rx, tx)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn rates_from_totals() {
+        let start = Instant::now();
+        let mut monitor = Monitor::default();
+        monitor.record(sample(start, &[("eth0", 1000, 500)]));
+        assert_eq!(monitor.interfaces()["eth0"].rx_rate(), 0);
+        assert!(monitor.interfaces()["eth0"].rx.is_empty());
+
+        let later = start + Duration::from_secs(2);
+        monitor.record(sample(later, &[("eth0", 5000, 700), ("wlan0", 10, 10)]));
+        let eth0 = &monitor.interfaces()["eth0"];
+        assert_eq!((eth0.rx_rate(), eth0.tx_rate()), (2000, 100));
+        assert_eq!((eth0.rx_total, eth0.tx_total), (5000, 700));
+        // 新出现的接口还没有速率。
+        assert!(monitor.interfaces()["wlan0"].rx.is_empty());
+
+        // 计数器重置，消失的接口被移除。
+        monitor.record(sample(later + Duration::from_secs(1), &[("wlan0", 0, 30)]));
+        let wlan0 = &monitor.interfaces()["wlan0"];
+        assert_eq!((wlan0.rx_rate(), wlan0.tx_rate()), (0, 20));
+        assert!(!monitor.interfaces().contains_key("eth0"));
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let start = Instant::now();
+        let mut monitor = Monitor::default();
+        for i in 0..=HISTORY as u64 + 10 {
+            monitor.record(sample(start + Duration::from_secs(i), &[("lo", i * 10, 0)]));
+        }
+        let lo = &monitor.interfaces()["lo"];
+        assert_eq!(lo.rx.len(), HISTORY);
+        assert!(lo.rx.iter().all(|&rate| rate == 10));
+    }
+}
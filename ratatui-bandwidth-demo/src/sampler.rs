@@ -0,0 +1,45 @@
+//! 在后台线程中定时读取各个网络接口的累计收发字节数。
+
+use std::{
+    collections::BTreeMap,
+    sync::mpsc::Sender,
+    thread,
+    time::{Duration, Instant},
+};
+
+use sysinfo::Networks;
+
+/// 某一时刻各个接口的累计接收和发送字节数。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sample {
+    pub at: Instant,
+    pub totals: BTreeMap<String, (u64, u64)>,
+}
+
+/// 每隔 `interval` 采样一次，直到接收端被丢弃。
+pub fn spawn(interval: Duration, samples: Sender<Sample>) {
+    thread::spawn(move || {
+        let mut networks = Networks::new_with_refreshed_list();
+        loop {
+            let totals = networks
+                .iter()
+                .map(|(name, data)| {
+                    (
+                        name.clone(),
+                        (data.total_received(), data.total_transmitted()),
+                    )
+                })
+                .collect();
+            let sample = Sample {
+                at: Instant::now(),
+                totals,
+            };
+            if samples.send(sample).is_err() {
+                break;
+            }
+            thread::sleep(interval);
+            // 同时发现新出现的接口，去掉已经消失的接口。
+            networks.refresh(true);
+        }
+    });
+}
@@ -0,0 +1,763 @@
+//! 国际象棋规则：局面、合法着法和标准代数记法（SAN）。
+//!
+//! 合法着法先按棋子的走法生成，再去掉走完后己方王被将军的着法。王车易位表示为王走两格，
+//! 和 UCI 协议一致。
+
+use std::fmt;
+
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Color {
+    White,
+    Black,
+}
+
+impl Color {
+    pub fn opponent(self) -> Self {
+        match self {
+            Self::White => Self::Black,
+            Self::Black => Self::White,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::White => "White",
+            Self::Black => "Black",
+        }
+    }
+
+    /// 兵前进的方向。
+    fn forward(self) -> i8 {
+        match self {
+            Self::White => 1,
+            Self::Black => -1,
+        }
+    }
+
+    /// 己方的底线。
+    fn back_rank(self) -> u8 {
+        match self {
+            Self::White => 0,
+            Self::Black => 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King,
+}
+
+impl Kind {
+    /// 升变可以选择的棋子。
+    pub const PROMOTIONS: [Self; 4] = [Self::Queen, Self::Rook, Self::Bishop, Self::Knight];
+
+    /// SAN 和 FEN 中的大写字母，兵是 `P`。
+    pub fn letter(self) -> char {
+        match self {
+            Self::Pawn => 'P',
+            Self::Knight => 'N',
+            Self::Bishop => 'B',
+            Self::Rook => 'R',
+            Self::Queen => 'Q',
+            Self::King => 'K',
+        }
+    }
+
+    pub fn from_letter(letter: char) -> Option<Self> {
+        Some(match letter.to_ascii_uppercase() {
+            'P' => Self::Pawn,
+            'N' => Self::Knight,
+            'B' => Self::Bishop,
+            'R' => Self::Rook,
+            'Q' => Self::Queen,
+            'K' => Self::King,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Piece {
+    pub color: Color,
+    pub kind: Kind,
+}
+
+impl Piece {
+    /// 实心的棋子符号，黑白两方用颜色区分，在深色和浅色的格子上都看得清。
+    pub fn symbol(self) -> char {
+        match self.kind {
+            Kind::Pawn => '♟',
+            Kind::Knight => '♞',
+            Kind::Bishop => '♝',
+            Kind::Rook => '♜',
+            Kind::Queen => '♛',
+            Kind::King => '♚',
+        }
+    }
+}
+
+/// 格子的编号：`rank * 8 + file`，a1 是 0，h8 是 63。
+pub type Square = u8;
+
+pub fn square(file: u8, rank: u8) -> Square {
+    rank * 8 + file
+}
+
+pub fn file_of(square: Square) -> u8 {
+    square % 8
+}
+
+pub fn rank_of(square: Square) -> u8 {
+    square / 8
+}
+
+pub fn square_name(square: Square) -> String {
+    format!(
+        "{}{}",
+        char::from(b'a' + file_of(square)),
+        rank_of(square) + 1
+    )
+}
+
+pub fn parse_square(name: &str) -> Option<Square> {
+    let &[file @ b'a'..=b'h', rank @ b'1'..=b'8'] = name.as_bytes() else {
+        return None;
+    };
+    Some(square(file - b'a', rank - b'1'))
+}
+
+/// 从 `from` 走 `(files, ranks)` 到达的格子，出了棋盘时为 `None`。
+fn offset(from: Square, files: i8, ranks: i8) -> Option<Square> {
+    let file = file_of(from) as i8 + files;
+    let rank = rank_of(from) as i8 + ranks;
+    ((0..8).contains(&file) && (0..8).contains(&rank)).then(|| square(file as u8, rank as u8))
+}
+
+const KNIGHT_STEPS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const KING_STEPS: [(i8, i8); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+];
+const ROOK_DIRECTIONS: [(i8, i8); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub from: Square,
+    pub to: Square,
+    pub promotion: Option<Kind>,
+}
+
+impl Move {
+    pub fn new(from: Square, to: Square) -> Self {
+        Self {
+            from,
+            to,
+            promotion: None,
+        }
+    }
+
+    /// UCI 格式，例如 `e2e4`、`e7e8q`。
+    pub fn parse_uci(text: &str) -> Option<Self> {
+        let from = parse_square(text.get(0..2)?)?;
+        let to = parse_square(text.get(2..4)?)?;
+        let promotion = match text.get(4..)? {
+            "" => None,
+            letter => {
+                let mut chars = letter.chars();
+                let kind = chars.next().and_then(Kind::from_letter)?;
+                if chars.next().is_some() || !Kind::PROMOTIONS.contains(&kind) {
+                    return None;
+                }
+                Some(kind)
+            }
+        };
+        Some(Self {
+            from,
+            to,
+            promotion,
+        })
+    }
+}
+
+/// UCI 格式。
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", square_name(self.from), square_name(self.to))?;
+        if let Some(kind) = self.promotion {
+            write!(f, "{}", kind.letter().to_ascii_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+/// 王车易位的权利。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Castling {
+    pub white_king_side: bool,
+    pub white_queen_side: bool,
+    pub black_king_side: bool,
+    pub black_queen_side: bool,
+}
+
+impl Castling {
+    fn king_side(&self, color: Color) -> bool {
+        match color {
+            Color::White => self.white_king_side,
+            Color::Black => self.black_king_side,
+        }
+    }
+
+    fn queen_side(&self, color: Color) -> bool {
+        match color {
+            Color::White => self.white_queen_side,
+            Color::Black => self.black_queen_side,
+        }
+    }
+
+    /// 王或车离开原位、或者车在原位被吃掉后失去对应的权利。
+    fn touch(&mut self, square: Square) {
+        match square {
+            0 => self.white_queen_side = false,
+            4 => (self.white_king_side, self.white_queen_side) = (false, false),
+            7 => self.white_king_side = false,
+            56 => self.black_queen_side = false,
+            60 => (self.black_king_side, self.black_queen_side) = (false, false),
+            63 => self.black_king_side = false,
+            _ => {}
+        }
+    }
+}
+
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    board: [Option<Piece>; 64],
+    pub turn: Color,
+    pub castling: Castling,
+    /// 上一步兵走了两格时越过的格子。
+    pub en_passant: Option<Square>,
+    /// 距离上一次吃子或走兵的半回合数，用于五十回合规则。
+    pub halfmove_clock: u32,
+    pub fullmove: u32,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::from_fen(START_FEN).unwrap()
+    }
+}
+
+impl Position {
+    /// 读取 FEN。最后两个字段（半回合计数和回合数）可以省略。
+    pub fn from_fen(fen: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        let &[placement, turn, castling, en_passant, ref rest @ ..] = &fields[..] else {
+            return Err(format!("invalid FEN {fen:?}"));
+        };
+        let mut board = [None; 64];
+        let ranks: Vec<&str> = placement.split('/').collect();
+        if ranks.len() != 8 {
+            return Err("a FEN board needs 8 ranks".into());
+        }
+        for (index, row) in ranks.iter().enumerate() {
+            let rank = 7 - index as u8;
+            let mut file = 0;
+            for c in row.chars() {
+                if let Some(empty) = c.to_digit(10) {
+                    file += empty as u8;
+                    continue;
+                }
+                let kind = Kind::from_letter(c).ok_or_else(|| format!("invalid piece {c:?}"))?;
+                let color = if c.is_ascii_uppercase() {
+                    Color::White
+                } else {
+                    Color::Black
+                };
+                if file >= 8 {
+                    return Err(format!("rank {} has more than 8 squares", rank + 1));
+                }
+                board[usize::from(square(file, rank))] = Some(Piece { color, kind });
+                file += 1;
+            }
+            if file != 8 {
+                return Err(format!("rank {} does not have 8 squares", rank + 1));
+            }
+        }
+        let turn = match turn {
+            "w" => Color::White,
+            "b" => Color::Black,
+            _ => return Err(format!("invalid side to move {turn:?}")),
+        };
+        let mut rights = Castling::default();
+        for c in castling.chars().filter(|&c| c != '-') {
+            match c {
+                'K' => rights.white_king_side = true,
+                'Q' => rights.white_queen_side = true,
+                'k' => rights.black_king_side = true,
+                'q' => rights.black_queen_side = true,
+                _ => return Err(format!("invalid castling rights {castling:?}")),
+            }
+        }
+        let en_passant = match en_passant {
+            "-" => None,
+            name => Some(parse_square(name).ok_or_else(|| format!("invalid square {name:?}"))?),
+        };
+        let number = |index: usize, default: u32| {
+            rest.get(index).map_or(Ok(default), |text| {
+                text.parse()
+                    .map_err(|_| format!("invalid move number {text:?}"))
+            })
+        };
+        let mut position = Self {
+            board,
+            turn,
+            castling: rights,
+            en_passant,
+            halfmove_clock: number(0, 0)?,
+            fullmove: number(1, 1)?,
+        };
+        for color in [Color::White, Color::Black] {
+            let kings = (0..64)
+                .filter(|&square| {
+                    position.piece(square)
+                        == Some(Piece {
+                            color,
+                            kind: Kind::King,
+                        })
+                })
+                .count();
+            if kings != 1 {
+                return Err(format!("{} needs exactly one king", color.name()));
+            }
+        }
+        // 王或车不在原位时去掉对应的权利，否则没有车也能易位。
+        for (square, color, kind) in [
+            (0, Color::White, Kind::Rook),
+            (4, Color::White, Kind::King),
+            (7, Color::White, Kind::Rook),
+            (56, Color::Black, Kind::Rook),
+            (60, Color::Black, Kind::King),
+            (63, Color::Black, Kind::Rook),
+        ] {
+            if position.piece(square) != Some(Piece { color, kind }) {
+                position.castling.touch(square);
+            }
+        }
+        Ok(position)
+    }
+
+    pub fn piece(&self, square: Square) -> Option<Piece> {
+        self.board[usize::from(square)]
+    }
+
+    /// 重复局面只比较棋子、轮到哪一方和双方的权利，不比较计数。
+    pub fn same_as(&self, other: &Self) -> bool {
+        self.board == other.board
+            && self.turn == other.turn
+            && self.castling == other.castling
+            && self.en_passant == other.en_passant
+    }
+
+    fn king(&self, color: Color) -> Square {
+        (0..64)
+            .find(|&square| {
+                self.piece(square)
+                    == Some(Piece {
+                        color,
+                        kind: Kind::King,
+                    })
+            })
+            .expect("both sides have a king")
+    }
+
+    /// `square` 是否受到 `by` 一方的攻击。
+    pub fn is_attacked(&self, square: Square, by: Color) -> bool {
+        let is = |target: Option<Square>, kinds: &[Kind]| {
+            target
+                .and_then(|target| self.piece(target))
+                .is_some_and(|piece| piece.color == by && kinds.contains(&piece.kind))
+        };
+        // 兵从斜后方攻击。
+        let back = -by.forward();
+        if is(offset(square, -1, back), &[Kind::Pawn]) || is(offset(square, 1, back), &[Kind::Pawn])
+        {
+            return true;
+        }
+        if KNIGHT_STEPS
+            .iter()
+            .any(|&(files, ranks)| is(offset(square, files, ranks), &[Kind::Knight]))
+            || KING_STEPS
+                .iter()
+                .any(|&(files, ranks)| is(offset(square, files, ranks), &[Kind::King]))
+        {
+            return true;
+        }
+        let slides = |directions: &[(i8, i8)], kinds: &[Kind]| {
+            directions.iter().any(|&(files, ranks)| {
+                let mut current = square;
+                while let Some(next) = offset(current, files, ranks) {
+                    if self.piece(next).is_some() {
+                        return is(Some(next), kinds);
+                    }
+                    current = next;
+                }
+                false
+            })
+        };
+        slides(&ROOK_DIRECTIONS, &[Kind::Rook, Kind::Queen])
+            || slides(&BISHOP_DIRECTIONS, &[Kind::Bishop, Kind::Queen])
+    }
+
+    pub fn in_check(&self) -> bool {
+        self.is_attacked(self.king(self.turn), self.turn.opponent())
+    }
+
+    /// 不考虑王是否被将军的着法。
+    fn pseudo_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        let turn = self.turn;
+        let free = |square: Square| self.piece(square).is_none();
+        let enemy = |square: Square| self.piece(square).is_some_and(|piece| piece.color != turn);
+        for from in 0..64 {
+            let Some(piece) = self.piece(from).filter(|piece| piece.color == turn) else {
+                continue;
+            };
+            match piece.kind {
+                Kind::Pawn => {
+                    let forward = turn.forward();
+                    let last_rank = turn.opponent().back_rank();
+                    let mut push = |to: Square| {
+                        if rank_of(to) == last_rank {
+                            moves.extend(Kind::PROMOTIONS.map(|kind| Move {
+                                from,
+                                to,
+                                promotion: Some(kind),
+                            }));
+                        } else {
+                            moves.push(Move::new(from, to));
+                        }
+                    };
+                    if let Some(one) = offset(from, 0, forward).filter(|&to| free(to)) {
+                        push(one);
+                        let start_rank = (turn.back_rank() as i8 + forward) as u8;
+                        if let Some(two) = offset(one, 0, forward)
+                            .filter(|&to| rank_of(from) == start_rank && free(to))
+                        {
+                            push(two);
+                        }
+                    }
+                    for files in [-1, 1] {
+                        if let Some(to) = offset(from, files, forward)
+                            .filter(|&to| enemy(to) || Some(to) == self.en_passant)
+                        {
+                            push(to);
+                        }
+                    }
+                }
+                Kind::Knight | Kind::King => {
+                    let steps = if piece.kind == Kind::Knight {
+                        &KNIGHT_STEPS
+                    } else {
+                        &KING_STEPS
+                    };
+                    for &(files, ranks) in steps {
+                        if let Some(to) = offset(from, files, ranks).filter(|&to| !self.own(to)) {
+                            moves.push(Move::new(from, to));
+                        }
+                    }
+                }
+                Kind::Bishop | Kind::Rook | Kind::Queen => {
+                    let directions: &[(i8, i8)] = match piece.kind {
+                        Kind::Bishop => &BISHOP_DIRECTIONS,
+                        Kind::Rook => &ROOK_DIRECTIONS,
+                        _ => &KING_STEPS,
+                    };
+                    for &(files, ranks) in directions {
+                        let mut current = from;
+                        while let Some(to) = offset(current, files, ranks) {
+                            if self.own(to) {
+                                break;
+                            }
+                            moves.push(Move::new(from, to));
+                            if enemy(to) {
+                                break;
+                            }
+                            current = to;
+                        }
+                    }
+                }
+            }
+        }
+        self.castling_moves(&mut moves);
+        moves
+    }
+
+    fn own(&self, square: Square) -> bool {
+        self.piece(square)
+            .is_some_and(|piece| piece.color == self.turn)
+    }
+
+    /// 王和车之间没有棋子，王不在被将军的状态，经过和到达的格子也不受攻击。
+    fn castling_moves(&self, moves: &mut Vec<Move>) {
+        let turn = self.turn;
+        let rank = turn.back_rank();
+        let king = square(4, rank);
+        if self.piece(king).map(|piece| piece.kind) != Some(Kind::King) {
+            return;
+        }
+        let safe = |file: u8| !self.is_attacked(square(file, rank), turn.opponent());
+        let empty = |files: &[u8]| {
+            files
+                .iter()
+                .all(|&file| self.piece(square(file, rank)).is_none())
+        };
+        if self.castling.king_side(turn) && empty(&[5, 6]) && [4, 5, 6].into_iter().all(safe) {
+            moves.push(Move::new(king, square(6, rank)));
+        }
+        if self.castling.queen_side(turn) && empty(&[1, 2, 3]) && [4, 3, 2].into_iter().all(safe) {
+            moves.push(Move::new(king, square(2, rank)));
+        }
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.pseudo_moves()
+            .into_iter()
+            .filter(|&mv| {
+                let next = self.play(mv);
+                !next.is_attacked(next.king(self.turn), next.turn)
+            })
+            .collect()
+    }
+
+    pub fn is_legal(&self, mv: Move) -> bool {
+        self.legal_moves().contains(&mv)
+    }
+
+    fn is_capture(&self, mv: Move) -> bool {
+        self.piece(mv.to).is_some()
+            || (self.piece(mv.from).map(|piece| piece.kind) == Some(Kind::Pawn)
+                && Some(mv.to) == self.en_passant)
+    }
+
+    /// 走完 `mv` 后的局面，`mv` 需要是合法着法。
+    pub fn play(&self, mv: Move) -> Self {
+        let mut next = *self;
+        let piece = self.piece(mv.from).expect("a move starts from a piece");
+        let capture = self.is_capture(mv);
+        next.board[usize::from(mv.from)] = None;
+        if piece.kind == Kind::Pawn && Some(mv.to) == self.en_passant {
+            // 吃过路兵时被吃的兵在到达格的后面。
+            let captured = offset(mv.to, 0, -self.turn.forward()).unwrap();
+            next.board[usize::from(captured)] = None;
+        }
+        if piece.kind == Kind::King && file_of(mv.from).abs_diff(file_of(mv.to)) == 2 {
+            let rank = rank_of(mv.from);
+            let (rook_from, rook_to) = if file_of(mv.to) == 6 { (7, 5) } else { (0, 3) };
+            next.board[usize::from(square(rook_to, rank))] =
+                next.board[usize::from(square(rook_from, rank))].take();
+        }
+        next.board[usize::from(mv.to)] = Some(Piece {
+            kind: mv.promotion.unwrap_or(piece.kind),
+            ..piece
+        });
+        next.castling.touch(mv.from);
+        next.castling.touch(mv.to);
+        next.en_passant = (piece.kind == Kind::Pawn
+            && rank_of(mv.from).abs_diff(rank_of(mv.to)) == 2)
+            .then(|| offset(mv.from, 0, self.turn.forward()).unwrap());
+        next.halfmove_clock = if piece.kind == Kind::Pawn || capture {
+            0
+        } else {
+            self.halfmove_clock + 1
+        };
+        if self.turn == Color::Black {
+            next.fullmove += 1;
+        }
+        next.turn = self.turn.opponent();
+        next
+    }
+
+    /// 标准代数记法，例如 `Nf3`、`exd5`、`O-O`、`e8=Q+`。
+    pub fn san(&self, mv: Move) -> String {
+        let piece = self.piece(mv.from).expect("a move starts from a piece");
+        let mut san = String::new();
+        if piece.kind == Kind::King && file_of(mv.from).abs_diff(file_of(mv.to)) == 2 {
+            san.push_str(if file_of(mv.to) == 6 { "O-O" } else { "O-O-O" });
+        } else if piece.kind == Kind::Pawn {
+            if self.is_capture(mv) {
+                san.push(char::from(b'a' + file_of(mv.from)));
+                san.push('x');
+            }
+            san.push_str(&square_name(mv.to));
+            if let Some(kind) = mv.promotion {
+                san.push('=');
+                san.push(kind.letter());
+            }
+        } else {
+            san.push(piece.kind.letter());
+            // 同种的其他棋子也能走到这里时，先用列区分，不行再用行，都不行时两个都写。
+            let others: Vec<Square> = self
+                .legal_moves()
+                .into_iter()
+                .filter(|other| {
+                    other.to == mv.to
+                        && other.from != mv.from
+                        && self.piece(other.from) == Some(piece)
+                })
+                .map(|other| other.from)
+                .collect();
+            if !others.is_empty() {
+                let file = char::from(b'a' + file_of(mv.from));
+                let rank = char::from(b'1' + rank_of(mv.from));
+                if others
+                    .iter()
+                    .all(|&other| file_of(other) != file_of(mv.from))
+                {
+                    san.push(file);
+                } else if others
+                    .iter()
+                    .all(|&other| rank_of(other) != rank_of(mv.from))
+                {
+                    san.push(rank);
+                } else {
+                    san.push(file);
+                    san.push(rank);
+                }
+            }
+            if self.is_capture(mv) {
+                san.push('x');
+            }
+            san.push_str(&square_name(mv.to));
+        }
+        let next = self.play(mv);
+        if next.in_check() {
+            san.push(if next.legal_moves().is_empty() {
+                '#'
+            } else {
+                '+'
+            });
+        }
+        san
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(position: Position, moves: &[&str]) -> Position {
+        moves.iter().fold(position, |position, text| {
+            let mv = Move::parse_uci(text).unwrap();
+            assert!(position.is_legal(mv), "{text} is not legal");
+            position.play(mv)
+        })
+    }
+
+    /// 区分各种着法的标准测试局面，着法数从 perft 表中来。
+    fn perft(position: &Position, depth: u32) -> usize {
+        if depth == 0 {
+            return 1;
+        }
+        position
+            .legal_moves()
+            .into_iter()
+            .map(|mv| perft(&position.play(mv), depth - 1))
+            .sum()
+    }
+
+    #[test]
+    fn move_counts() {
+        assert_eq!(perft(&Position::default(), 3), 8902);
+        let kiwipete =
+            Position::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -")
+                .unwrap();
+        assert_eq!(perft(&kiwipete, 1), 48);
+        assert_eq!(perft(&kiwipete, 2), 2039);
+        // 有吃过路兵和将军的残局。
+        let endgame = Position::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap();
+        assert_eq!(perft(&endgame, 3), 2812);
+    }
+
+    #[test]
+    fn special_moves() {
+        let position = play(
+            Position::default(),
+            &[
+                "e2e4", "g8f6", "e4e5", "d7d5", "e5d6", "e7d6", "f1c4", "f8e7", "g1f3",
+            ],
+        );
+        // 吃过路兵后 d5 的兵没有了。
+        assert_eq!(position.piece(parse_square("d5").unwrap()), None);
+        let castled = play(position, &["e8g8"]);
+        assert_eq!(
+            castled
+                .piece(parse_square("f8").unwrap())
+                .map(|piece| piece.kind),
+            Some(Kind::Rook)
+        );
+        assert!(!castled.castling.black_king_side && !castled.castling.black_queen_side);
+        assert!(castled.castling.white_king_side);
+
+        // 只有王的局面中写着的易位权利不算数。
+        let kings = Position::from_fen("4k3/8/8/8/8/8/8/4K3 w KQkq - 0 1").unwrap();
+        assert_eq!(kings.castling, Castling::default());
+        assert!(!kings.is_legal(Move::parse_uci("e1g1").unwrap()));
+        assert!(!kings.is_legal(Move::parse_uci("e1c1").unwrap()));
+        // 车的颜色不对也不行。
+        let rooks = Position::from_fen("r3k2R/8/8/8/8/8/8/R3K2r w KQkq - 0 1").unwrap();
+        assert!(rooks.castling.white_queen_side && rooks.castling.black_queen_side);
+        assert!(!rooks.castling.white_king_side && !rooks.castling.black_king_side);
+
+        let promotion = Position::from_fen("8/P6k/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        assert_eq!(promotion.legal_moves().len(), 3 + 4);
+        let mv = Move::parse_uci("a7a8n").unwrap();
+        assert_eq!(mv.to_string(), "a7a8n");
+        assert_eq!(promotion.san(mv), "a8=N");
+        assert_eq!(Move::parse_uci("a7a8k"), None);
+    }
+
+    #[test]
+    fn san_and_mate() {
+        let position = Position::default();
+        assert_eq!(position.san(Move::parse_uci("g1f3").unwrap()), "Nf3");
+        // 两个马都能到 d2 时写出列。
+        let knights = Position::from_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1").unwrap();
+        assert_eq!(knights.san(Move::parse_uci("b1d2").unwrap()), "Nbd2");
+        let rooks = Position::from_fen("4k3/8/R7/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        assert_eq!(rooks.san(Move::parse_uci("a1a3").unwrap()), "R1a3");
+
+        let mated = play(position, &["f2f3", "e7e5", "g2g4"]);
+        let mate = Move::parse_uci("d8h4").unwrap();
+        assert_eq!(mated.san(mate), "Qh4#");
+        let mated = mated.play(mate);
+        assert!(mated.in_check() && mated.legal_moves().is_empty());
+
+        assert!(Position::from_fen("8/8/8/8/8/8/8/8 w - -").is_err());
+        assert!(Position::from_fen("8/8/8 w - -").is_err());
+    }
+}
@@ -0,0 +1,163 @@
+//! 通过 UCI 协议和外部引擎（例如 Stockfish）对弈。
+//!
+//! 引擎是一个子进程：界面线程把命令发到通道里，由写线程写入引擎的标准输入；读线程逐行读取
+//! 引擎的输出，把关心的几种回复解析成 [`Update`] 通过标准库的通道送回。关闭命令通道后
+//! 写线程发送 `quit` 并等待引擎退出。
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+    process::{Command, Stdio},
+    sync::mpsc,
+    thread,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    /// 以兵为单位的百分之一，从轮到走棋的一方看。
+    Centipawns(i32),
+    /// 几步之内将死，负数表示被将死。
+    Mate(i32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Update {
+    /// `id name`。
+    Name(String),
+    /// 搜索过程中的深度和评分。
+    Info { depth: u32, score: Score },
+    /// 搜索结束，UCI 格式的着法。没有着法可走时引擎回复 `(none)`。
+    BestMove(String),
+    /// 引擎退出或者无法读取它的输出。
+    Exited(String),
+}
+
+/// 解析引擎输出的一行，不关心的行返回 `None`。
+pub fn parse_line(line: &str) -> Option<Update> {
+    let mut words = line.split_whitespace();
+    match words.next()? {
+        "id" if words.next() == Some("name") => {
+            Some(Update::Name(words.collect::<Vec<_>>().join(" ")))
+        }
+        "bestmove" => Some(Update::BestMove(words.next()?.to_string())),
+        "info" => {
+            let (mut depth, mut score) = (None, None);
+            while let Some(word) = words.next() {
+                match word {
+                    "depth" => depth = words.next()?.parse().ok(),
+                    "score" => {
+                        let kind = words.next()?;
+                        let value = words.next()?.parse().ok()?;
+                        score = match kind {
+                            "cp" => Some(Score::Centipawns(value)),
+                            "mate" => Some(Score::Mate(value)),
+                            _ => None,
+                        };
+                    }
+                    // 后面是主要变化，不再有需要的字段。
+                    "pv" | "string" => break,
+                    _ => {}
+                }
+            }
+            Some(Update::Info {
+                depth: depth?,
+                score: score?,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// 启动引擎，返回发送命令的一端。先发送 `uci` 和 `isready` 完成握手。
+pub fn spawn(program: &Path, updates: mpsc::Sender<Update>) -> io::Result<mpsc::Sender<String>> {
+    let mut child = Command::new(program)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let stdout = child.stdout.take().expect("stdout is piped");
+
+    let (commands, received) = mpsc::channel::<String>();
+    thread::spawn(move || {
+        for command in ["uci".to_string(), "isready".to_string()]
+            .into_iter()
+            .chain(received)
+        {
+            if writeln!(stdin, "{command}")
+                .and_then(|()| stdin.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+        let _ = writeln!(stdin, "quit");
+        drop(stdin);
+        let _ = child.wait();
+    });
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(error) => {
+                    let _ = updates.send(Update::Exited(error.to_string()));
+                    return;
+                }
+            };
+            if let Some(update) = parse_line(&line) {
+                if updates.send(update).is_err() {
+                    return;
+                }
+            }
+        }
+        let _ = updates.send(Update::Exited("the engine exited".into()));
+    });
+    Ok(commands)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_engine_output() {
+        assert_eq!(
+            parse_line("id name Stockfish 16"),
+            Some(Update::Name("Stockfish 16".into()))
+        );
+        assert_eq!(parse_line("id author the Stockfish developers"), None);
+        assert_eq!(
+            parse_line("info depth 12 seldepth 15 multipv 1 score cp -35 nodes 1000 pv e7e5 g1f3"),
+            Some(Update::Info {
+                depth: 12,
+                score: Score::Centipawns(-35)
+            })
+        );
+        assert_eq!(
+            parse_line("info depth 20 score mate 3 pv h5f7"),
+            Some(Update::Info {
+                depth: 20,
+                score: Score::Mate(3)
+            })
+        );
+        assert_eq!(parse_line("info string NNUE evaluation enabled"), None);
+        assert_eq!(
+            parse_line("bestmove e2e4 ponder e7e5"),
+            Some(Update::BestMove("e2e4".into()))
+        );
+        assert_eq!(parse_line("uciok"), None);
+    }
+
+    #[test]
+    fn talk_to_a_process() {
+        // 用 `cat` 代替引擎：发送的命令原样回来，其中 `bestmove` 会被解析。
+        let (sender, updates) = mpsc::channel();
+        let Ok(commands) = spawn(Path::new("cat"), sender) else {
+            return;
+        };
+        commands.send("bestmove e2e4".into()).unwrap();
+        assert_eq!(updates.recv(), Ok(Update::BestMove("e2e4".into())));
+        drop(commands);
+        assert!(matches!(updates.recv(), Ok(Update::Exited(_))));
+    }
+}
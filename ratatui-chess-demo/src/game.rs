@@ -0,0 +1,290 @@
+//! 一盘棋：起始局面、走过的着法和之后的每个局面，可以悔棋、判断结果和导出 PGN。
+
+use crate::chess::{Color, Move, Position};
+
+/// 五十回合规则：双方各走 50 步没有吃子也没有走兵。
+const FIFTY_MOVES: u32 = 100;
+
+/// PGN 着法部分每行的最大宽度。
+const PGN_WIDTH: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Checkmate { winner: Color },
+    Stalemate,
+    FiftyMoves,
+    Repetition,
+}
+
+impl Outcome {
+    /// PGN 中的结果。
+    pub fn result(self) -> &'static str {
+        match self {
+            Self::Checkmate {
+                winner: Color::White,
+            } => "1-0",
+            Self::Checkmate {
+                winner: Color::Black,
+            } => "0-1",
+            _ => "1/2-1/2",
+        }
+    }
+
+    pub fn describe(self) -> String {
+        match self {
+            Self::Checkmate { winner } => format!("Checkmate, {} wins", winner.name()),
+            Self::Stalemate => "Draw by stalemate".into(),
+            Self::FiftyMoves => "Draw by the fifty-move rule".into(),
+            Self::Repetition => "Draw by threefold repetition".into(),
+        }
+    }
+}
+
+/// PGN 的标签。
+#[derive(Debug, Clone)]
+pub struct Tags {
+    pub white: String,
+    pub black: String,
+    /// `YYYY.MM.DD`。
+    pub date: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Game {
+    /// 不是从标准开局开始时的 FEN。
+    fen: Option<String>,
+    /// 第一个是起始局面，最后一个是当前局面。
+    positions: Vec<Position>,
+    moves: Vec<Move>,
+    sans: Vec<String>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self {
+            fen: None,
+            positions: vec![Position::default()],
+            moves: Vec::new(),
+            sans: Vec::new(),
+        }
+    }
+}
+
+impl Game {
+    pub fn from_fen(fen: &str) -> Result<Self, String> {
+        Ok(Self {
+            fen: Some(fen.trim().to_string()),
+            positions: vec![Position::from_fen(fen)?],
+            ..Self::default()
+        })
+    }
+
+    pub fn position(&self) -> &Position {
+        self.positions.last().unwrap()
+    }
+
+    pub fn last_move(&self) -> Option<Move> {
+        self.moves.last().copied()
+    }
+
+    pub fn play(&mut self, mv: Move) -> Result<(), String> {
+        if self.outcome().is_some() {
+            return Err("the game is over".into());
+        }
+        let position = *self.position();
+        if !position.is_legal(mv) {
+            return Err(format!("{mv} is not a legal move"));
+        }
+        self.sans.push(position.san(mv));
+        self.moves.push(mv);
+        self.positions.push(position.play(mv));
+        Ok(())
+    }
+
+    pub fn undo(&mut self) -> Option<Move> {
+        let mv = self.moves.pop()?;
+        self.sans.pop();
+        self.positions.pop();
+        Some(mv)
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        let position = self.position();
+        if position.legal_moves().is_empty() {
+            return Some(if position.in_check() {
+                Outcome::Checkmate {
+                    winner: position.turn.opponent(),
+                }
+            } else {
+                Outcome::Stalemate
+            });
+        }
+        if position.halfmove_clock >= FIFTY_MOVES {
+            return Some(Outcome::FiftyMoves);
+        }
+        let repeated = self
+            .positions
+            .iter()
+            .filter(|other| other.same_as(position))
+            .count();
+        (repeated >= 3).then_some(Outcome::Repetition)
+    }
+
+    /// 着法列表，每一项是回合数和双方的着法。从黑方开始时第一项白方的着法为 `None`。
+    pub fn numbered_moves(&self) -> Vec<(u32, Option<&str>, Option<&str>)> {
+        let start = &self.positions[0];
+        let mut sans = self.sans.iter().map(String::as_str);
+        let mut rows = Vec::new();
+        let mut number = start.fullmove;
+        if start.turn == Color::Black {
+            if let Some(black) = sans.next() {
+                rows.push((number, None, Some(black)));
+            }
+            number += 1;
+        }
+        while let Some(white) = sans.next() {
+            rows.push((number, Some(white), sans.next()));
+            number += 1;
+        }
+        rows
+    }
+
+    /// UCI 的 `position` 命令。
+    pub fn uci_position(&self) -> String {
+        let mut command = match &self.fen {
+            Some(fen) => format!("position fen {fen}"),
+            None => "position startpos".to_string(),
+        };
+        if !self.moves.is_empty() {
+            command.push_str(" moves");
+            for mv in &self.moves {
+                command.push_str(&format!(" {mv}"));
+            }
+        }
+        command
+    }
+
+    pub fn pgn(&self, tags: &Tags) -> String {
+        let result = self.outcome().map_or("*", Outcome::result);
+        let mut pgn = String::new();
+        let mut tag = |name: &str, value: &str| {
+            pgn.push_str(&format!("[{name} \"{}\"]\n", value.replace('"', "'")));
+        };
+        tag("Event", "Casual game");
+        tag("Site", "ratatui-chess-demo");
+        tag("Date", &tags.date);
+        tag("Round", "-");
+        tag("White", &tags.white);
+        tag("Black", &tags.black);
+        tag("Result", result);
+        if let Some(fen) = &self.fen {
+            tag("SetUp", "1");
+            tag("FEN", fen);
+        }
+        pgn.push('\n');
+
+        let mut tokens = Vec::new();
+        for (number, white, black) in self.numbered_moves() {
+            match white {
+                Some(white) => tokens.push(format!("{number}. {white}")),
+                None => tokens.push(format!("{number}...")),
+            }
+            tokens.extend(black.map(str::to_string));
+        }
+        tokens.push(result.to_string());
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > PGN_WIDTH {
+                pgn.push_str(&line);
+                pgn.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        pgn.push_str(&line);
+        pgn.push('\n');
+        pgn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(game: &mut Game, moves: &[&str]) {
+        for text in moves {
+            game.play(Move::parse_uci(text).unwrap()).unwrap();
+        }
+    }
+
+    fn tags() -> Tags {
+        Tags {
+            white: "Player".into(),
+            black: "Engine".into(),
+            date: "2026.10.14".into(),
+        }
+    }
+
+    #[test]
+    fn scholars_mate_pgn() {
+        let mut game = Game::default();
+        play(
+            &mut game,
+            &["e2e4", "e7e5", "f1c4", "b8c6", "d1h5", "g8f6", "h5f7"],
+        );
+        assert_eq!(
+            game.outcome(),
+            Some(Outcome::Checkmate {
+                winner: Color::White
+            })
+        );
+        assert_eq!(
+            game.play(Move::parse_uci("e8f7").unwrap()).unwrap_err(),
+            "the game is over"
+        );
+        assert_eq!(
+            game.pgn(&tags()),
+            "[Event \"Casual game\"]\n[Site \"ratatui-chess-demo\"]\n[Date \"2026.10.14\"]\n\
+             [Round \"-\"]\n[White \"Player\"]\n[Black \"Engine\"]\n[Result \"1-0\"]\n\n\
+             1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0\n"
+        );
+        assert_eq!(
+            game.uci_position(),
+            "position startpos moves e2e4 e7e5 f1c4 b8c6 d1h5 g8f6 h5f7"
+        );
+
+        assert_eq!(game.undo(), Some(Move::parse_uci("h5f7").unwrap()));
+        assert_eq!(game.outcome(), None);
+        assert_eq!(
+            game.numbered_moves().last(),
+            Some(&(3, Some("Qh5"), Some("Nf6")))
+        );
+    }
+
+    #[test]
+    fn draws_and_custom_start() {
+        let mut game = Game::default();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        play(&mut game, &shuffle);
+        assert_eq!(game.outcome(), None);
+        play(&mut game, &shuffle);
+        assert_eq!(game.outcome(), Some(Outcome::Repetition));
+
+        let mut game = Game::from_fen("7k/8/8/8/8/8/Q7/K7 b - - 10 40").unwrap();
+        play(&mut game, &["h8h7"]);
+        assert!(game.play(Move::parse_uci("a1b1").unwrap()).is_ok());
+        assert_eq!(
+            game.numbered_moves(),
+            [(40, None, Some("Kh7")), (41, Some("Kb1"), None)]
+        );
+        let pgn = game.pgn(&tags());
+        assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"7k/8/8/8/8/8/Q7/K7 b - - 10 40\"]\n"));
+        assert!(pgn.ends_with("\n40... Kh7 41. Kb1 *\n"));
+
+        let stalemate = Game::from_fen("7k/5Q2/8/8/8/8/8/K7 b - - 0 1").unwrap();
+        assert_eq!(stalemate.outcome(), Some(Outcome::Stalemate));
+    }
+}
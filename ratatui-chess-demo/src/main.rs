@@ -0,0 +1,690 @@
+//! 国际象棋演示：两个人轮流走棋，或者通过 UCI 协议和外部引擎对弈（`--engine stockfish`）。
+//!
+//! 规则见 `chess` 模块，只能走合法的着法。右边是着法列表，可以把这盘棋导出为 PGN。
+//! 和引擎下棋时悔棋会一直退到轮到人走为止，引擎还在思考时它的结果被丢弃。
+//!
+//! 按键：方向键或 `h` / `j` / `k` / `l` 移动光标，`Enter` / `Space` 选择棋子再选择目标格，
+//! `Esc` 取消选择，`u` 悔棋，`f` 翻转棋盘，`w` 导出 PGN，`n` 重新开始，`q` 退出。
+
+use std::{fs, path::PathBuf, sync::mpsc, time::Duration};
+
+use chrono::{Local, NaiveDate};
+use clap::Parser;
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Paragraph},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+};
+
+use crate::{
+    chess::{file_of, rank_of, square, Color as Side, Kind, Move, Square},
+    engine::{Score, Update},
+    game::{Game, Tags},
+};
+
+mod chess;
+mod engine;
+mod game;
+
+/// 等待按键的最长时间，之后读取引擎的回复。
+const REFRESH: Duration = Duration::from_millis(50);
+
+/// 棋盘的宽度：行号和 8 个 3 列宽的格子。
+const BOARD_WIDTH: u16 = 2 + 8 * 3;
+
+const LIGHT_SQUARE: Color = Color::Rgb(240, 217, 181);
+const DARK_SQUARE: Color = Color::Rgb(181, 136, 99);
+const LAST_MOVE: Color = Color::Rgb(205, 210, 106);
+const SELECTED: Color = Color::Rgb(120, 180, 90);
+const CURSOR: Color = Color::Rgb(100, 150, 230);
+const CHECK: Color = Color::Rgb(220, 90, 80);
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 起始局面的 FEN，默认是标准开局
+    #[arg(long)]
+    fen: Option<String>,
+    /// UCI 引擎的可执行文件，例如 `stockfish`；不给出时双方都由人来走
+    #[arg(long, value_name = "PROGRAM")]
+    engine: Option<PathBuf>,
+    /// 引擎执哪一方
+    #[arg(long, value_enum, default_value_t = Side::Black)]
+    engine_color: Side,
+    /// 引擎每步思考的时间（毫秒）
+    #[arg(long, default_value_t = 1000)]
+    movetime: u64,
+    /// 导出 PGN 的文件
+    #[arg(long, value_name = "FILE", default_value = "game.pgn")]
+    pgn: PathBuf,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let game = match &cli.fen {
+        Some(fen) => Game::from_fen(fen).map_err(|error| eyre!("invalid --fen: {error}"))?,
+        None => Game::default(),
+    };
+    let opponent = match &cli.engine {
+        Some(program) => {
+            let (sender, updates) = mpsc::channel();
+            let commands = engine::spawn(program, sender)
+                .wrap_err_with(|| format!("starting {} failed", program.display()))?;
+            let name = program
+                .file_stem()
+                .map_or("Engine".into(), |stem| stem.to_string_lossy().into_owned());
+            Some(Opponent::new(
+                name,
+                cli.engine_color,
+                cli.movetime,
+                commands,
+                updates,
+            ))
+        }
+        None => None,
+    };
+
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let today = Local::now().date_naive();
+    let mut app = App::new(game, opponent, cli.pgn, today);
+    let result = app.run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+/// 引擎一方的状态。
+struct Opponent {
+    name: String,
+    color: Side,
+    movetime: u64,
+    commands: mpsc::Sender<String>,
+    updates: mpsc::Receiver<Update>,
+    thinking: bool,
+    /// 悔棋或重新开始时被取消的搜索数，它们的结果还会送回来，需要丢弃。
+    stale: usize,
+    /// 当前搜索的深度和评分。
+    info: Option<(u32, Score)>,
+}
+
+impl Opponent {
+    fn new(
+        name: String,
+        color: Side,
+        movetime: u64,
+        commands: mpsc::Sender<String>,
+        updates: mpsc::Receiver<Update>,
+    ) -> Self {
+        Self {
+            name,
+            color,
+            movetime,
+            commands,
+            updates,
+            thinking: false,
+            stale: 0,
+            info: None,
+        }
+    }
+
+    /// 白方看来的评分，例如 `+0.35`、`-M3`。
+    fn evaluation(&self) -> Option<String> {
+        let (depth, score) = self.info?;
+        let sign = if self.color == Side::White { 1 } else { -1 };
+        let score = match score {
+            Score::Centipawns(cp) => format!("{:+.2}", f64::from(cp * sign) / 100.0),
+            Score::Mate(moves) if moves * sign > 0 => format!("+M{}", moves.abs()),
+            Score::Mate(moves) => format!("-M{}", moves.abs()),
+        };
+        Some(format!("depth {depth} eval {score}"))
+    }
+}
+
+struct App {
+    /// 重新开始时恢复到的对局。
+    start: Game,
+    game: Game,
+    cursor: Square,
+    selected: Option<Square>,
+    /// 等待选择升变棋子的着法。
+    promotion: Option<Move>,
+    flipped: bool,
+    opponent: Option<Opponent>,
+    pgn_path: PathBuf,
+    today: NaiveDate,
+    /// 最近一次操作的结果，`Err` 显示为红色。
+    message: Option<Result<String, String>>,
+    exit: bool,
+}
+
+impl App {
+    fn new(game: Game, opponent: Option<Opponent>, pgn_path: PathBuf, today: NaiveDate) -> Self {
+        // 引擎执白时让黑方在下面。
+        let flipped = opponent
+            .as_ref()
+            .is_some_and(|opponent| opponent.color == Side::White);
+        let mut app = Self {
+            start: game.clone(),
+            game,
+            cursor: square(4, 1),
+            selected: None,
+            promotion: None,
+            flipped,
+            opponent,
+            pgn_path,
+            today,
+            message: None,
+            exit: false,
+        };
+        app.send(&["ucinewgame".to_string()]);
+        app.think();
+        app
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            self.receive();
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut()))?;
+            if events.poll(REFRESH)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key.code);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 发送命令给引擎。引擎已经退出时改为双方都由人来走。
+    fn send(&mut self, commands: &[String]) {
+        let Some(opponent) = &self.opponent else {
+            return;
+        };
+        if commands
+            .iter()
+            .any(|command| opponent.commands.send(command.clone()).is_err())
+        {
+            self.message = Some(Err(format!("{} is not running", opponent.name)));
+            self.opponent = None;
+        }
+    }
+
+    /// 轮到引擎时开始搜索。
+    fn think(&mut self) {
+        let Some(opponent) = &self.opponent else {
+            return;
+        };
+        if opponent.thinking
+            || opponent.color != self.game.position().turn
+            || self.game.outcome().is_some()
+        {
+            return;
+        }
+        let commands = [
+            self.game.uci_position(),
+            format!("go movetime {}", opponent.movetime),
+        ];
+        self.send(&commands);
+        if let Some(opponent) = &mut self.opponent {
+            opponent.thinking = true;
+            opponent.info = None;
+        }
+    }
+
+    /// 取消正在进行的搜索。
+    fn cancel(&mut self) {
+        let Some(opponent) = &mut self.opponent else {
+            return;
+        };
+        if opponent.thinking {
+            opponent.thinking = false;
+            opponent.stale += 1;
+            self.send(&["stop".to_string()]);
+        }
+    }
+
+    /// 处理引擎送回的所有回复。
+    fn receive(&mut self) {
+        while let Some(update) = self
+            .opponent
+            .as_ref()
+            .and_then(|opponent| opponent.updates.try_recv().ok())
+        {
+            let Some(opponent) = &mut self.opponent else {
+                return;
+            };
+            match update {
+                Update::Name(name) => opponent.name = name,
+                Update::Info { depth, score } if opponent.thinking => {
+                    opponent.info = Some((depth, score))
+                }
+                Update::Info { .. } => {}
+                Update::BestMove(_) if opponent.stale > 0 => opponent.stale -= 1,
+                Update::BestMove(text) => {
+                    opponent.thinking = false;
+                    let name = opponent.name.clone();
+                    let played = Move::parse_uci(&text)
+                        .ok_or_else(|| format!("{name} sent an invalid move {text:?}"))
+                        .and_then(|mv| self.game.play(mv));
+                    self.message = played.err().map(Err);
+                }
+                Update::Exited(reason) => {
+                    self.message = Some(Err(format!("{}: {reason}", opponent.name)));
+                    self.opponent = None;
+                }
+            }
+        }
+    }
+
+    fn human_to_move(&self) -> bool {
+        self.opponent
+            .as_ref()
+            .is_none_or(|opponent| opponent.color != self.game.position().turn)
+    }
+
+    fn handle_key(&mut self, code: KeyCode) {
+        if let Some(mv) = self.promotion {
+            let kind = match code {
+                KeyCode::Char('q') | KeyCode::Enter => Some(Kind::Queen),
+                KeyCode::Char('r') => Some(Kind::Rook),
+                KeyCode::Char('b') => Some(Kind::Bishop),
+                KeyCode::Char('n') => Some(Kind::Knight),
+                _ => None,
+            };
+            if kind.is_some() || code == KeyCode::Esc {
+                self.promotion = None;
+            }
+            if let Some(kind) = kind {
+                self.play(Move {
+                    promotion: Some(kind),
+                    ..mv
+                });
+            }
+            return;
+        }
+        // 翻转棋盘后屏幕上的方向和棋盘上的方向相反。
+        let sign = if self.flipped { -1 } else { 1 };
+        match code {
+            KeyCode::Char('q') => self.exit = true,
+            KeyCode::Esc => self.selected = None,
+            KeyCode::Left | KeyCode::Char('h') => self.step(-sign, 0),
+            KeyCode::Right | KeyCode::Char('l') => self.step(sign, 0),
+            KeyCode::Up | KeyCode::Char('k') => self.step(0, sign),
+            KeyCode::Down | KeyCode::Char('j') => self.step(0, -sign),
+            KeyCode::Enter | KeyCode::Char(' ') => self.choose(),
+            KeyCode::Char('u') => self.undo(),
+            KeyCode::Char('f') => self.flipped = !self.flipped,
+            KeyCode::Char('w') => self.export(),
+            KeyCode::Char('n') => {
+                self.cancel();
+                self.game = self.start.clone();
+                self.selected = None;
+                self.message = None;
+                self.send(&["ucinewgame".to_string()]);
+                self.think();
+            }
+            _ => {}
+        }
+    }
+
+    fn step(&mut self, files: i8, ranks: i8) {
+        let file = (file_of(self.cursor) as i8 + files).clamp(0, 7) as u8;
+        let rank = (rank_of(self.cursor) as i8 + ranks).clamp(0, 7) as u8;
+        self.cursor = square(file, rank);
+    }
+
+    /// 先选择己方的棋子，再选择目标格。
+    fn choose(&mut self) {
+        if !self.human_to_move() || self.game.outcome().is_some() {
+            return;
+        }
+        let position = self.game.position();
+        let own = position
+            .piece(self.cursor)
+            .is_some_and(|piece| piece.color == position.turn);
+        match self.selected {
+            Some(from) if from == self.cursor => self.selected = None,
+            _ if own => self.selected = Some(self.cursor),
+            None => {}
+            Some(from) => {
+                let moves: Vec<Move> = position
+                    .legal_moves()
+                    .into_iter()
+                    .filter(|mv| mv.from == from && mv.to == self.cursor)
+                    .collect();
+                match moves.first() {
+                    None => self.message = Some(Err("That move is not legal".into())),
+                    Some(&mv) if mv.promotion.is_some() => {
+                        self.promotion = Some(Move {
+                            promotion: None,
+                            ..mv
+                        })
+                    }
+                    Some(&mv) => self.play(mv),
+                }
+            }
+        }
+    }
+
+    fn play(&mut self, mv: Move) {
+        self.selected = None;
+        self.message = self.game.play(mv).err().map(Err);
+        self.think();
+    }
+
+    /// 和引擎下棋时一直退到轮到人走为止。
+    fn undo(&mut self) {
+        self.cancel();
+        self.selected = None;
+        if self.game.undo().is_none() {
+            return;
+        }
+        while !self.human_to_move() && self.game.undo().is_some() {}
+        self.message = None;
+        self.think();
+    }
+
+    fn tags(&self) -> Tags {
+        let name = |side: Side| match &self.opponent {
+            Some(opponent) if opponent.color == side => opponent.name.clone(),
+            _ => "Player".to_string(),
+        };
+        Tags {
+            white: name(Side::White),
+            black: name(Side::Black),
+            date: self.today.format("%Y.%m.%d").to_string(),
+        }
+    }
+
+    fn export(&mut self) {
+        let path = self.pgn_path.display();
+        self.message = Some(
+            fs::write(&self.pgn_path, self.game.pgn(&self.tags()))
+                .map(|()| format!("Saved the game to {path}"))
+                .map_err(|error| format!("writing {path} failed: {error}")),
+        );
+    }
+
+    fn square_style(&self, square: Square, targets: &[Square]) -> Style {
+        let position = self.game.position();
+        let piece = position.piece(square);
+        let light = (file_of(square) + rank_of(square)) % 2 == 1;
+        let last = self
+            .game
+            .last_move()
+            .is_some_and(|mv| mv.from == square || mv.to == square);
+        let checked = position.in_check()
+            && piece.is_some_and(|piece| piece.kind == Kind::King && piece.color == position.turn);
+        let background = if square == self.cursor {
+            CURSOR
+        } else if Some(square) == self.selected {
+            SELECTED
+        } else if checked || (targets.contains(&square) && piece.is_some()) {
+            CHECK
+        } else if last {
+            LAST_MOVE
+        } else if light {
+            LIGHT_SQUARE
+        } else {
+            DARK_SQUARE
+        };
+        let foreground = match piece.map(|piece| piece.color) {
+            Some(Side::White) => Color::White,
+            _ => Color::Black,
+        };
+        Style::new().fg(foreground).bg(background).bold()
+    }
+
+    fn board_lines(&self) -> Vec<Line<'static>> {
+        let position = self.game.position();
+        let targets: Vec<Square> = self.selected.map_or_else(Vec::new, |from| {
+            position
+                .legal_moves()
+                .into_iter()
+                .filter(|mv| mv.from == from)
+                .map(|mv| mv.to)
+                .collect()
+        });
+        let order = |index: u8| if self.flipped { index } else { 7 - index };
+        let mut lines: Vec<Line> = (0..8)
+            .map(|row| {
+                let rank = order(row);
+                let mut spans = vec![format!("{} ", rank + 1).dim()];
+                for column in 0..8 {
+                    let square = square(7 - order(column), rank);
+                    let symbol = match position.piece(square) {
+                        Some(piece) => piece.symbol(),
+                        None if targets.contains(&square) => '•',
+                        None => ' ',
+                    };
+                    spans.push(Span::styled(
+                        format!(" {symbol} "),
+                        self.square_style(square, &targets),
+                    ));
+                }
+                Line::from(spans)
+            })
+            .collect();
+        let files: String = (0..8)
+            .map(|column| format!(" {} ", char::from(b'a' + 7 - order(column))))
+            .collect();
+        lines.push(Line::from(format!("  {files}").dim()));
+        lines
+    }
+
+    fn move_lines(&self, height: usize) -> Vec<Line<'static>> {
+        let rows = self.game.numbered_moves();
+        let skip = rows.len().saturating_sub(height);
+        rows.into_iter()
+            .skip(skip)
+            .map(|(number, white, black)| {
+                Line::from(format!(
+                    "{number:>3}. {:<8}{}",
+                    white.unwrap_or("…"),
+                    black.unwrap_or("")
+                ))
+            })
+            .collect()
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        if let Some(message) = &self.message {
+            return match message {
+                Ok(message) => Line::from(message.clone().green()),
+                Err(error) => Line::from(error.clone().red()),
+            };
+        }
+        if self.promotion.is_some() {
+            return Line::from("Promote to Queen <Q> Rook <R> Bishop <B> Knight <N>".yellow());
+        }
+        if let Some(outcome) = self.game.outcome() {
+            return Line::from(outcome.describe().bold());
+        }
+        let position = self.game.position();
+        let mut spans = vec![format!("{} to move", position.turn.name()).into()];
+        if position.in_check() {
+            spans.push(" (check)".light_red());
+        }
+        if let Some(opponent) = self.opponent.as_ref().filter(|opponent| opponent.thinking) {
+            spans.push(format!("  {} is thinking", opponent.name).dim());
+            if let Some(evaluation) = opponent.evaluation() {
+                spans.push(format!(", {evaluation}").dim());
+            }
+        }
+        Line::from(spans)
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let players = self.tags();
+        let block = Block::bordered()
+            .title(" Chess ".bold())
+            .title(Line::from(format!(" {} vs {} ", players.white, players.black)).right_aligned())
+            .title_bottom(
+                Line::from(" Move <Enter> Undo <U> Flip <F> Save PGN <W> New <N> Quit <Q> ")
+                    .centered(),
+            );
+        let inner = block.inner(main);
+        block.render(main, buf);
+        let [board, _, moves] = Layout::horizontal([
+            Constraint::Length(BOARD_WIDTH),
+            Constraint::Length(3),
+            Constraint::Fill(1),
+        ])
+        .areas(inner);
+        Paragraph::new(self.board_lines()).render(board, buf);
+        Paragraph::new(self.move_lines(usize::from(moves.height))).render(moves, buf);
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use ratatui_common::testing;
+
+    use super::*;
+    use crate::chess::parse_square;
+
+    fn app(opponent: Option<Opponent>) -> App {
+        let path = env::temp_dir().join(format!("chess-demo-{}.pgn", process::id()));
+        let today = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+        App::new(Game::default(), opponent, path, today)
+    }
+
+    fn rows(app: &App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    /// 把光标移到 `name` 并按 `Enter`。
+    fn click(app: &mut App, name: &str) {
+        app.cursor = parse_square(name).unwrap();
+        app.handle_key(KeyCode::Enter);
+    }
+
+    #[test]
+    fn play_moves_with_the_cursor() {
+        let mut app = app(None);
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.selected, parse_square("e2"));
+        for _ in 0..2 {
+            app.handle_key(KeyCode::Up);
+        }
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.game.uci_position(), "position startpos moves e2e4");
+
+        // 不能走到非法的格子，也不能选择对方的棋子。
+        click(&mut app, "e4");
+        assert_eq!(app.selected, None);
+        click(&mut app, "g8");
+        click(&mut app, "g5");
+        assert_eq!(app.message, Some(Err("That move is not legal".into())));
+        click(&mut app, "f6");
+
+        let screen = rows(&app, 70, 13);
+        assert_eq!(
+            screen[0],
+            format!("┌ Chess {} Player vs Player ┐", "─".repeat(43))
+        );
+        assert!(screen[1].starts_with("│8  ♜  ♞  ♝  ♛  ♚  ♝     ♜      1. e4      Nf6   "));
+        assert!(screen[9].starts_with("│   a  b  c  d  e  f  g  h   "));
+        assert_eq!(screen[12].trim_end(), "White to move");
+
+        app.handle_key(KeyCode::Char('f'));
+        let flipped = rows(&app, 70, 13);
+        assert!(flipped[1].starts_with("│1  ♜  ♞  ♝  ♚  ♛  ♝  ♞  ♜"));
+        assert!(flipped[9].starts_with("│   h  g  f  e  d  c  b  a"));
+    }
+
+    #[test]
+    fn promotion_and_pgn_export() {
+        let game = Game::from_fen("8/P6k/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        let today = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+        let path = env::temp_dir().join(format!("chess-demo-promotion-{}.pgn", process::id()));
+        let mut app = App::new(game, None, path.clone(), today);
+        click(&mut app, "a7");
+        click(&mut app, "a8");
+        assert!(app.promotion.is_some());
+        assert_eq!(
+            app.status_line().to_string(),
+            "Promote to Queen <Q> Rook <R> Bishop <B> Knight <N>"
+        );
+        app.handle_key(KeyCode::Char('n'));
+        assert_eq!(app.game.last_move().unwrap().promotion, Some(Kind::Knight));
+
+        app.handle_key(KeyCode::Char('w'));
+        let pgn = fs::read_to_string(&path).unwrap();
+        assert!(pgn.contains("[Date \"2026.10.14\"]"));
+        assert!(pgn.ends_with("\n1. a8=N *\n"));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn engine_opponent() {
+        let (commands, sent) = mpsc::channel();
+        let (replies, updates) = mpsc::channel();
+        let opponent = Opponent::new("Fish".into(), Side::Black, 100, commands, updates);
+        let mut app = app(Some(opponent));
+        assert_eq!(sent.try_recv().as_deref(), Ok("ucinewgame"));
+
+        click(&mut app, "e2");
+        click(&mut app, "e4");
+        let received: Vec<String> = sent.try_iter().collect();
+        assert_eq!(
+            received,
+            ["position startpos moves e2e4", "go movetime 100"]
+        );
+        // 引擎思考时不能走棋。
+        click(&mut app, "d2");
+        assert_eq!(app.selected, None);
+
+        replies
+            .send(Update::Info {
+                depth: 8,
+                score: Score::Centipawns(-20),
+            })
+            .unwrap();
+        app.receive();
+        assert_eq!(
+            app.status_line().to_string(),
+            "Black to move  Fish is thinking, depth 8 eval +0.20"
+        );
+        replies.send(Update::BestMove("e7e5".into())).unwrap();
+        app.receive();
+        assert_eq!(app.game.uci_position(), "position startpos moves e2e4 e7e5");
+
+        // 引擎思考时悔棋：取消搜索，丢弃随后送回的结果。
+        click(&mut app, "g1");
+        click(&mut app, "f3");
+        sent.try_iter().count();
+        app.handle_key(KeyCode::Char('u'));
+        assert_eq!(sent.try_recv().as_deref(), Ok("stop"));
+        assert_eq!(app.game.last_move(), Move::parse_uci("e7e5"));
+        replies.send(Update::BestMove("b8c6".into())).unwrap();
+        app.receive();
+        assert_eq!(app.game.last_move(), Move::parse_uci("e7e5"));
+
+        app.handle_key(KeyCode::Char('u'));
+        assert_eq!(app.game.last_move(), None);
+
+        replies.send(Update::Exited("crashed".into())).unwrap();
+        app.receive();
+        assert!(app.opponent.is_none());
+        assert_eq!(app.message, Some(Err("Fish: crashed".into())));
+    }
+}
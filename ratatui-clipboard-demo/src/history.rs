@@ -0,0 +1,180 @@
+//! 剪贴板历史：最近复制的在最前面，同样的内容只保留一条。
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub text: String,
+    /// 最近一次复制的时间，Unix 时间戳（秒）。
+    pub copied: u64,
+}
+
+impl Entry {
+    /// 列表中显示的一行：换行显示成 `↵`，连续的空白合成一个空格。
+    pub fn preview(&self) -> String {
+        let mut preview = String::new();
+        for (index, line) in self.text.trim().lines().enumerate() {
+            if index > 0 {
+                preview.push_str(" ↵ ");
+            }
+            preview.push_str(&line.split_whitespace().collect::<Vec<_>>().join(" "));
+        }
+        preview
+    }
+
+    pub fn matches(&self, query: &str) -> bool {
+        self.text.to_lowercase().contains(&query.to_lowercase())
+    }
+}
+
+/// 记录一次复制的结果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recorded {
+    Added,
+    /// 已经在历史中，移到了最前面。
+    Moved,
+    /// 和最近的一条相同，或者只有空白。
+    Unchanged,
+    /// 超过了大小限制，没有记录。
+    TooLarge,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct History {
+    entries: Vec<Entry>,
+    /// 最多保留的条数，超出时丢弃最旧的。
+    max_entries: usize,
+    /// 一条的最大字节数。
+    max_bytes: usize,
+}
+
+impl History {
+    /// 读取的条目超过限制时只保留最近的。
+    pub fn new(mut entries: Vec<Entry>, max_entries: usize, max_bytes: usize) -> Self {
+        entries.retain(|entry| entry.text.len() <= max_bytes);
+        entries.truncate(max_entries);
+        Self {
+            entries,
+            max_entries,
+            max_bytes,
+        }
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    pub fn record(&mut self, text: String, now: u64) -> Recorded {
+        if text.trim().is_empty() || self.entries.first().is_some_and(|entry| entry.text == text) {
+            return Recorded::Unchanged;
+        }
+        if text.len() > self.max_bytes {
+            return Recorded::TooLarge;
+        }
+        let recorded = match self.entries.iter().position(|entry| entry.text == text) {
+            Some(index) => {
+                self.entries.remove(index);
+                Recorded::Moved
+            }
+            None => Recorded::Added,
+        };
+        self.entries.insert(0, Entry { text, copied: now });
+        self.entries.truncate(self.max_entries);
+        recorded
+    }
+
+    pub fn remove(&mut self, index: usize) -> Entry {
+        self.entries.remove(index)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// 复制的时间距离现在多久，例如 `5m`、`3h`。
+pub fn ago(copied: u64, now: u64) -> String {
+    let seconds = now.saturating_sub(copied);
+    match seconds {
+        0..=59 => "now".into(),
+        60..=3599 => format!("{}m", seconds / 60),
+        3600..=86_399 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86_400),
+    }
+}
+
+/// 字节数的可读形式，例如 `64 KiB`。
+pub fn size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{bytes} B")
+    } else if bytes < 1024 * 1024 {
+        format!("{} KiB", bytes / 1024)
+    } else {
+        format!("{} MiB", bytes / (1024 * 1024))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(history: &History) -> Vec<&str> {
+        history
+            .entries()
+            .iter()
+            .map(|entry| entry.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn record_copies() {
+        let mut history = History::new(Vec::new(), 3, 16);
+        assert_eq!(history.record("one".into(), 10), Recorded::Added);
+        assert_eq!(history.record("one".into(), 11), Recorded::Unchanged);
+        assert_eq!(history.record("  \n".into(), 12), Recorded::Unchanged);
+        assert_eq!(history.record("two".into(), 13), Recorded::Added);
+        assert_eq!(history.record("x".repeat(17), 14), Recorded::TooLarge);
+        assert_eq!(history.record("one".into(), 15), Recorded::Moved);
+        assert_eq!(texts(&history), ["one", "two"]);
+        assert_eq!(history.entries()[0].copied, 15);
+
+        history.record("three".into(), 16);
+        history.record("four".into(), 17);
+        assert_eq!(texts(&history), ["four", "three", "one"]);
+
+        let entries = vec![
+            Entry {
+                text: "a".into(),
+                copied: 3,
+            },
+            Entry {
+                text: "b".repeat(20),
+                copied: 2,
+            },
+            Entry {
+                text: "c".into(),
+                copied: 1,
+            },
+        ];
+        assert_eq!(texts(&History::new(entries, 1, 16)), ["a"]);
+    }
+
+    #[test]
+    fn previews() {
+        let entry = Entry {
+            text: "  fn main() {\n    println!();\n}\n".into(),
+            copied: 0,
+        };
+        assert_eq!(entry.preview(), "fn main() { ↵ println!(); ↵ }");
+        assert!(entry.matches("PRINTLN"));
+        assert_eq!(ago(100, 159), "now");
+        assert_eq!(ago(100, 400), "5m");
+        assert_eq!(ago(0, 7200), "2h");
+        assert_eq!(ago(0, 3 * 86_400), "3d");
+        assert_eq!(size(65_536), "64 KiB");
+    }
+}
@@ -0,0 +1,576 @@
+//! 剪贴板历史演示：定时读取系统剪贴板，把每次复制的内容记到可以搜索的历史列表中，选中一条按
+//! `Enter` 再次复制。
+//!
+//! 读取剪贴板用各个平台的命令（见 `watch` 模块），复制则和其他演示一样用 OSC 52，由终端写入剪贴板。
+//! 历史最多保留 `--max-entries` 条，超过 `--max-bytes` 的内容不记录；历史保存在数据目录中的
+//! `history.json`（见 `store` 模块），每次变化后立即写入。复制密码之类的内容前可以按 `p` 暂停记录。
+//!
+//! 按键：`/` 搜索，`Enter` 复制，`x` 删除，`C` 清空，`p` 暂停或继续记录，`q` 退出。
+
+use std::{
+    env, io,
+    path::PathBuf,
+    sync::mpsc::{self, Receiver},
+    time::{Duration, SystemTime},
+};
+
+use clap::Parser;
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use directories::ProjectDirs;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, List, ListState, Paragraph, Wrap},
+};
+use ratatui_common::{
+    clipboard,
+    events::{EventSource, TerminalEvents},
+    terminal,
+    text_input::Input,
+};
+
+use crate::{
+    history::{ago, size, History, Recorded},
+    store::Store,
+    watch::Update,
+};
+
+mod history;
+mod store;
+mod watch;
+
+const APPLICATION: &str = "ratatui-clipboard-demo";
+
+/// 读取剪贴板的间隔。
+const POLL: Duration = Duration::from_millis(500);
+
+/// 等待按键的最长时间，之后处理剪贴板的变化。
+const REFRESH: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 保存历史的文件，默认是数据目录中的 `history.json`
+    #[arg(long, value_name = "FILE")]
+    history: Option<PathBuf>,
+    /// 读取剪贴板的命令，例如 "xsel --clipboard --output"，默认按平台选择
+    #[arg(long, value_name = "COMMAND")]
+    paste_command: Option<String>,
+    /// 最多保留的条数
+    #[arg(long, default_value_t = 200)]
+    max_entries: usize,
+    /// 一条的最大字节数，更大的内容不记录
+    #[arg(long, default_value_t = 64 * 1024)]
+    max_bytes: usize,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let path = cli
+        .history
+        .or_else(|| {
+            let project = ProjectDirs::from("", "", APPLICATION)?;
+            Some(project.data_dir().join("history.json"))
+        })
+        .ok_or_else(|| eyre!("no data directory, pass --history"))?;
+    let store = Store::new(path);
+    let history = History::new(store.load()?, cli.max_entries, cli.max_bytes);
+    let command = match cli.paste_command {
+        Some(command) => Some(command.split_whitespace().map(str::to_string).collect()),
+        None => watch::detect(|name| env::var(name).ok()),
+    };
+    let (sender, updates) = mpsc::channel();
+    match command {
+        Some(command) => watch::spawn(command, POLL, sender),
+        None => {
+            let _ = sender.send(Update::Failed(
+                "no clipboard command found, pass --paste-command".into(),
+            ));
+        }
+    }
+
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new(history, store, updates).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+fn count(count: usize) -> String {
+    match count {
+        1 => "1 entry".into(),
+        count => format!("{count} entries"),
+    }
+}
+
+/// 确认后执行的操作。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Confirm {
+    Delete(usize),
+    Clear,
+}
+
+struct App {
+    history: History,
+    store: Store,
+    updates: Receiver<Update>,
+    search: Input,
+    searching: bool,
+    list: ListState,
+    /// 暂停时不记录剪贴板的变化。
+    paused: bool,
+    /// 读取剪贴板的错误，下一次读取成功时清除。
+    paste_error: Option<String>,
+    /// 下一次绘制前写到终端的文本。
+    clipboard: Option<String>,
+    confirm: Option<Confirm>,
+    /// 显示在状态栏中的提示或错误，下一次按键时清除。
+    message: Option<Result<String, String>>,
+    /// 当前时间，Unix 时间戳（秒），用于显示复制了多久。
+    now: u64,
+    exit: bool,
+}
+
+impl App {
+    fn new(history: History, store: Store, updates: Receiver<Update>) -> Self {
+        let mut app = Self {
+            history,
+            store,
+            updates,
+            search: Input::default(),
+            searching: false,
+            list: ListState::default(),
+            paused: false,
+            paste_error: None,
+            clipboard: None,
+            confirm: None,
+            message: None,
+            now: now(),
+            exit: false,
+        };
+        app.select(0);
+        app
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            while let Ok(update) = self.updates.try_recv() {
+                self.update(update);
+            }
+            if let Some(text) = self.clipboard.take() {
+                clipboard::copy(&mut io::stdout(), &text)
+                    .wrap_err_with(|| format!("failed to copy {text:?}"))?;
+            }
+            self.now = now();
+            terminal.draw(|frame| frame.render_widget(&mut *self, frame.size()))?;
+            if events.poll(REFRESH)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, update: Update) {
+        match update {
+            Update::Copied(text) => {
+                self.paste_error = None;
+                if !self.paused {
+                    self.record(text);
+                }
+            }
+            Update::Failed(error) => self.paste_error = Some(error),
+        }
+    }
+
+    /// 记录一次复制，选择停留在原来选中的那一条上。
+    fn record(&mut self, text: String) {
+        let selected = self
+            .selected()
+            .map(|index| self.history.entries()[index].text.clone());
+        let bytes = text.len();
+        match self.history.record(text, self.now) {
+            Recorded::Added | Recorded::Moved => {
+                let row = selected.and_then(|selected| {
+                    self.visible()
+                        .iter()
+                        .position(|&index| self.history.entries()[index].text == selected)
+                });
+                self.select(row.unwrap_or(0));
+                self.save();
+            }
+            Recorded::TooLarge => {
+                self.message = Some(Err(format!(
+                    "Skipped a copy of {}, the limit is {}",
+                    size(bytes),
+                    size(self.history.max_bytes())
+                )));
+            }
+            Recorded::Unchanged => {}
+        }
+    }
+
+    fn save(&mut self) {
+        if let Err(error) = self.store.save(self.history.entries()) {
+            self.message = Some(Err(format!("{error:#}")));
+        }
+    }
+
+    /// 搜索到的条目的位置。
+    fn visible(&self) -> Vec<usize> {
+        let entries = self.history.entries();
+        (0..entries.len())
+            .filter(|&index| entries[index].matches(self.search.value()))
+            .collect()
+    }
+
+    fn select(&mut self, row: usize) {
+        let last = self.visible().len().checked_sub(1);
+        self.list.select(last.map(|last| row.min(last)));
+    }
+
+    fn selected(&self) -> Option<usize> {
+        self.visible().get(self.list.selected()?).copied()
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        self.message = None;
+        if let Some(confirm) = self.confirm.take() {
+            if key.code == KeyCode::Char('y') {
+                match confirm {
+                    Confirm::Delete(index) => {
+                        self.history.remove(index);
+                        self.message = Some(Ok("Deleted the entry".into()));
+                    }
+                    Confirm::Clear => {
+                        self.history.clear();
+                        self.message = Some(Ok("Cleared the history".into()));
+                    }
+                }
+                self.select(self.list.selected().unwrap_or(0));
+                self.save();
+            }
+            return;
+        }
+        if self.searching {
+            match key.code {
+                KeyCode::Esc => {
+                    self.searching = false;
+                    self.search = Input::default();
+                    self.select(0);
+                }
+                KeyCode::Enter | KeyCode::Down | KeyCode::Tab => self.searching = false,
+                _ => {
+                    if self.search.handle_key_event(key) {
+                        self.select(0);
+                    }
+                }
+            }
+            return;
+        }
+        let row = self.list.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Char('q') => self.exit = true,
+            // 有搜索条件时 `Esc` 先清除搜索。
+            KeyCode::Esc if !self.search.value().is_empty() => {
+                self.search = Input::default();
+                self.select(0);
+            }
+            KeyCode::Esc => self.exit = true,
+            KeyCode::Up | KeyCode::Char('k') => self.select(row.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.select(row + 1),
+            KeyCode::Home => self.select(0),
+            KeyCode::End => self.select(usize::MAX),
+            KeyCode::Char('/') => self.searching = true,
+            KeyCode::Enter => {
+                if let Some(index) = self.selected() {
+                    let text = self.history.entries()[index].text.clone();
+                    self.message = Some(Ok(format!("Copied {}", size(text.len()))));
+                    self.clipboard = Some(text.clone());
+                    // 不等下一次读取，直接移到最前面。暂停时也一样，因为这是历史中已有的内容。
+                    self.search = Input::default();
+                    self.record(text);
+                    self.select(0);
+                }
+            }
+            KeyCode::Char('x') | KeyCode::Delete => {
+                self.confirm = self.selected().map(Confirm::Delete);
+            }
+            KeyCode::Char('C') if !self.history.entries().is_empty() => {
+                self.confirm = Some(Confirm::Clear);
+            }
+            KeyCode::Char('p') => {
+                self.paused = !self.paused;
+                self.message = Some(Ok(if self.paused {
+                    "Paused, copies are not recorded".into()
+                } else {
+                    "Recording copies again".into()
+                }));
+            }
+            _ => {}
+        }
+    }
+
+    fn render_list(&mut self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(" History ")
+            .title_bottom(Line::from(" Search </> Pause <p> Clear <C> ").right_aligned())
+            .border_style(Style::new().green());
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let [search_area, list_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(inner);
+        let style = if self.searching {
+            Style::new().yellow().bold()
+        } else {
+            Style::new().dim()
+        };
+        Paragraph::new(self.search.line("/ ", style)).render(search_area, buf);
+
+        let visible = self.visible();
+        if visible.is_empty() {
+            let text = if self.history.entries().is_empty() {
+                "Nothing copied yet"
+            } else {
+                "No entries match"
+            };
+            Paragraph::new(text.dim())
+                .wrap(Wrap { trim: true })
+                .render(list_area, buf);
+            return;
+        }
+        let entries = self.history.entries();
+        let items: Vec<Line> = visible
+            .iter()
+            .map(|&index| {
+                let entry = &entries[index];
+                Line::from(vec![
+                    format!("{:>3} ", ago(entry.copied, self.now)).dim(),
+                    entry.preview().into(),
+                ])
+            })
+            .collect();
+        StatefulWidget::render(
+            List::new(items).highlight_style(Style::new().reversed()),
+            list_area,
+            buf,
+            &mut self.list,
+        );
+    }
+
+    fn render_entry(&self, area: Rect, buf: &mut Buffer) {
+        let mut block = Block::bordered()
+            .title_bottom(Line::from(" Copy <Enter> Delete <x> Quit <q> ").right_aligned());
+        let Some(entry) = self.selected().map(|index| &self.history.entries()[index]) else {
+            block.render(area, buf);
+            return;
+        };
+        block = block.title(format!(
+            " {}, copied {} ",
+            size(entry.text.len()),
+            match ago(entry.copied, self.now).as_str() {
+                "now" => "just now".to_string(),
+                ago => format!("{ago} ago"),
+            }
+        ));
+        Paragraph::new(entry.text.as_str())
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(area, buf);
+    }
+
+    fn status_line(&self) -> Line<'_> {
+        match self.confirm {
+            Some(Confirm::Delete(_)) => {
+                return Line::from("Delete this entry? <Y>/<N>").red().bold()
+            }
+            Some(Confirm::Clear) => {
+                return Line::from(format!(
+                    "Delete all {}? <Y>/<N>",
+                    count(self.history.entries().len())
+                ))
+                .red()
+                .bold()
+            }
+            None => {}
+        }
+        match (&self.message, &self.paste_error) {
+            (Some(Ok(message)), _) => Line::from(message.as_str()),
+            (Some(Err(error)), _) | (None, Some(error)) => Line::from(error.as_str().red()),
+            (None, None) => {
+                let visible = self.visible().len();
+                let total = self.history.entries().len();
+                let mut text = if visible == total {
+                    count(total)
+                } else {
+                    format!("{visible} of {} match", count(total))
+                };
+                if self.paused {
+                    text.push_str(", paused");
+                }
+                Line::from(text.dim())
+            }
+        }
+    }
+}
+
+impl Widget for &mut App {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let [list_area, entry_area] =
+            Layout::horizontal([Constraint::Percentage(45), Constraint::Fill(1)]).areas(main);
+        self.render_list(list_area, buf);
+        self.render_entry(entry_area, buf);
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, process};
+
+    use ratatui_common::testing;
+
+    use super::*;
+    use crate::history::Entry;
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    fn app(name: &str) -> (App, PathBuf) {
+        let dir = env::temp_dir().join(format!("clipboard-demo-{name}-{}", process::id()));
+        let entries = vec![
+            Entry {
+                text: "cargo test --workspace".into(),
+                copied: 10_000 - 300,
+            },
+            Entry {
+                text: "fn main() {\n    println!(\"hi\");\n}".into(),
+                copied: 10_000 - 7200,
+            },
+        ];
+        let history = History::new(entries, 3, 40);
+        let (_, updates) = mpsc::channel();
+        let mut app = App::new(history, Store::new(dir.join("history.json")), updates);
+        app.now = 10_000;
+        (app, dir)
+    }
+
+    #[test]
+    fn record_and_render() {
+        let (mut app, dir) = app("record");
+        let screen = rows(&mut app, 80, 8);
+        assert_eq!(
+            screen[0],
+            "┌ History ─────────────────────────┐┌ 22 B, copied 5m ago ─────────────────────┐"
+        );
+        assert_eq!(
+            screen[3],
+            "│ 2h fn main() { ↵ println!(\"hi\"); ││                                          │"
+        );
+        assert_eq!(screen[7].trim_end(), "2 entries");
+
+        app.update(Update::Copied("https://ratatui.rs".into()));
+        app.update(Update::Copied("x".repeat(50)));
+        assert_eq!(
+            app.message,
+            Some(Err("Skipped a copy of 50 B, the limit is 40 B".into()))
+        );
+        app.update(Update::Failed(
+            "xclip failed: Error: target STRING not available".into(),
+        ));
+        // 新的复制排在最前面，选择停留在原来的条目上。
+        let screen = rows(&mut app, 80, 8);
+        assert!(screen[2].starts_with("│now https://ratatui.rs "));
+        assert!(screen[0].contains("┌ 22 B, copied 5m ago ─"));
+        app.handle_key(KeyCode::Up.into());
+        assert_eq!(
+            rows(&mut app, 80, 8)[7].trim_end(),
+            "xclip failed: Error: target STRING not available"
+        );
+        assert_eq!(app.history.entries()[0].text, "https://ratatui.rs");
+        let saved = Store::new(dir.join("history.json")).load().unwrap();
+        assert_eq!(saved, app.history.entries());
+
+        // 暂停时不记录，条数超过限制时丢弃最旧的。
+        app.handle_key(KeyCode::Char('p').into());
+        app.update(Update::Copied("secret".into()));
+        assert_eq!(app.history.entries().len(), 3);
+        app.handle_key(KeyCode::Char('p').into());
+        app.update(Update::Copied("one more".into()));
+        assert_eq!(app.history.entries().len(), 3);
+        assert!(!app
+            .history
+            .entries()
+            .iter()
+            .any(|entry| entry.text.contains("main")));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn search_copy_and_delete() {
+        let (mut app, dir) = app("copy");
+        app.handle_key(KeyCode::Char('/').into());
+        testing::type_text("PRINT", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(app.visible(), [1]);
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(
+            app.clipboard.as_deref(),
+            Some("fn main() {\n    println!(\"hi\");\n}")
+        );
+        assert_eq!(app.message, Some(Ok("Copied 33 B".into())));
+        assert_eq!(app.selected(), Some(0));
+        assert_eq!(app.history.entries()[0].copied, 10_000);
+        assert_eq!(app.search.value(), "");
+
+        // 终端写入剪贴板后，下一次读取到的是同样的内容，不再变化。
+        app.update(Update::Copied(app.clipboard.clone().unwrap()));
+        assert_eq!(app.history.entries().len(), 2);
+
+        app.handle_key(KeyCode::Down.into());
+        app.handle_key(KeyCode::Char('x').into());
+        assert_eq!(
+            rows(&mut app, 80, 8)[7].trim_end(),
+            "Delete this entry? <Y>/<N>"
+        );
+        app.handle_key(KeyCode::Char('y').into());
+        assert_eq!(app.history.entries().len(), 1);
+        assert_eq!(app.selected(), Some(0));
+
+        app.handle_key(KeyCode::Char('C').into());
+        assert_eq!(
+            rows(&mut app, 80, 8)[7].trim_end(),
+            "Delete all 1 entry? <Y>/<N>"
+        );
+        app.handle_key(KeyCode::Char('n').into());
+        assert_eq!(app.history.entries().len(), 1);
+        app.handle_key(KeyCode::Char('C').into());
+        app.handle_key(KeyCode::Char('y').into());
+        assert!(app.history.entries().is_empty());
+        assert!(rows(&mut app, 80, 8)[2].starts_with("│Nothing copied yet"));
+        assert!(Store::new(dir.join("history.json"))
+            .load()
+            .unwrap()
+            .is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,85 @@
+//! 把剪贴板历史保存为 JSON，每次变化后立即写入。
+
+use std::{fs, io, path::PathBuf};
+
+use color_eyre::{eyre::WrapErr, Result};
+use ratatui_common::files;
+use serde::{Deserialize, Serialize};
+
+use crate::history::Entry;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Saved {
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug)]
+pub struct Store {
+    path: PathBuf,
+}
+
+impl Store {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// 读取保存的历史。文件还不存在时为空。
+    pub fn load(&self) -> Result<Vec<Entry>> {
+        let json = match fs::read_to_string(&self.path) {
+            Ok(json) => json,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(err).wrap_err_with(|| format!("reading {} failed", self.path.display()))
+            }
+        };
+        let saved: Saved = serde_json::from_str(&json)
+            .wrap_err_with(|| format!("parsing {} failed", self.path.display()))?;
+        Ok(saved.entries)
+    }
+
+    /// 保存剪贴板历史。
+    pub fn save(&self, entries: &[Entry]) -> Result<()> {
+        let saved = Saved {
+            entries: entries.to_vec(),
+        };
+        serde_json::to_vec_pretty(&saved)
+            .map_err(io::Error::from)
+            .and_then(|bytes| files::write_atomic(&self.path, bytes))
+            .wrap_err_with(|| format!("writing {} failed", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    #[test]
+    fn persist_entries() {
+        let dir = env::temp_dir().join(format!("clipboard-demo-store-{}", process::id()));
+        let store = Store::new(dir.join("history.json"));
+        assert!(store.load().unwrap().is_empty());
+
+        let entries = vec![
+            Entry {
+                text: "cargo run\n".into(),
+                copied: 1_792_000_000,
+            },
+            Entry {
+                text: "你好".into(),
+                copied: 1_791_999_000,
+            },
+        ];
+        store.save(&entries).unwrap();
+        assert_eq!(store.load().unwrap(), entries);
+
+        fs::write(dir.join("history.json"), "{\"entries\": [{\"text\": 1}]}").unwrap();
+        let error = store.load().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("parsing {} failed", dir.join("history.json").display())
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,114 @@
+//! 定时读取系统剪贴板。
+//!
+//! 终端程序不能直接读取剪贴板（大多数终端不允许用 OSC 52 读取），所以调用各个平台读取剪贴板的
+//! 命令，例如 Wayland 的 `wl-paste`、X11 的 `xclip` 和 macOS 的 `pbpaste`。命令在单独的线程中
+//! 运行，剪贴板的内容变化时把新的内容通过标准库的通道送回，命令卡住时界面也不会停止响应。
+
+use std::{process::Command, sync::mpsc, thread, time::Duration};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Update {
+    Copied(String),
+    /// 读取剪贴板的命令失败，例如没有安装。
+    Failed(String),
+}
+
+/// 按照环境变量选择读取剪贴板的命令。
+pub fn detect(var: impl Fn(&str) -> Option<String>) -> Option<Vec<String>> {
+    let command: &[&str] = if cfg!(target_os = "macos") {
+        &["pbpaste"]
+    } else if cfg!(windows) {
+        &["powershell", "-NoProfile", "-Command", "Get-Clipboard -Raw"]
+    } else if var("WAYLAND_DISPLAY").is_some() {
+        &["wl-paste", "--no-newline"]
+    } else if var("DISPLAY").is_some() {
+        &["xclip", "-selection", "clipboard", "-out"]
+    } else {
+        return None;
+    };
+    Some(command.iter().map(|part| part.to_string()).collect())
+}
+
+/// 运行一次命令。剪贴板中不是文本（例如图片）时返回 `None`。
+pub fn read(command: &[String]) -> Result<Option<String>, String> {
+    let (program, args) = command.split_first().ok_or("the paste command is empty")?;
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|error| format!("cannot run {program}: {error}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(match stderr.trim() {
+            "" => format!("{program} failed with {}", output.status),
+            stderr => format!("{program} failed: {stderr}"),
+        });
+    }
+    Ok(String::from_utf8(output.stdout).ok())
+}
+
+/// 每隔 `interval` 读取一次剪贴板，只在内容或错误变化时发送，包括启动时的第一次读取。
+/// 接收的一端关闭后线程退出。
+pub fn spawn(command: Vec<String>, interval: Duration, updates: mpsc::Sender<Update>) {
+    thread::spawn(move || {
+        let mut last = None;
+        loop {
+            let current = match read(&command) {
+                Ok(Some(text)) => Update::Copied(text),
+                Ok(None) => {
+                    thread::sleep(interval);
+                    continue;
+                }
+                Err(error) => Update::Failed(error),
+            };
+            if last.as_ref() != Some(&current) {
+                if updates.send(current.clone()).is_err() {
+                    return;
+                }
+                last = Some(current);
+            }
+            thread::sleep(interval);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(command: &[&str]) -> Vec<String> {
+        command.iter().map(|part| part.to_string()).collect()
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn detect_from_environment() {
+        let wayland = |name: &str| (name == "WAYLAND_DISPLAY").then(|| "wayland-0".to_string());
+        assert_eq!(detect(wayland), Some(args(&["wl-paste", "--no-newline"])));
+        let x11 = |name: &str| (name == "DISPLAY").then(|| ":0".to_string());
+        assert_eq!(
+            detect(x11),
+            Some(args(&["xclip", "-selection", "clipboard", "-out"]))
+        );
+        assert_eq!(detect(|_| None), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn poll_a_command() {
+        assert_eq!(read(&args(&["printf", "a\\nb"])), Ok(Some("a\nb".into())));
+        assert_eq!(read(&args(&["printf", "\\377"])), Ok(None));
+        assert_eq!(
+            read(&args(&["sh", "-c", "echo 'No selection' >&2; exit 1"])),
+            Err("sh failed: No selection".into())
+        );
+        assert!(read(&args(&["no-such-paste-command"]))
+            .unwrap_err()
+            .starts_with("cannot run no-such-paste-command: "));
+
+        // 内容不变时只发送一次。
+        let (sender, receiver) = mpsc::channel();
+        spawn(args(&["echo", "hi"]), Duration::from_millis(5), sender);
+        assert_eq!(receiver.recv(), Ok(Update::Copied("hi\n".into())));
+        assert!(receiver.recv_timeout(Duration::from_millis(50)).is_err());
+    }
+}
@@ -0,0 +1,85 @@
+//! HSL 与 RGB 之间的转换。滑块调节的是整数值，所以这里也用整数保存。
+
+/// 色相 0–359 度，饱和度和亮度 0–100%。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hsl {
+    pub hue: u16,
+    pub saturation: u8,
+    pub lightness: u8,
+}
+
+impl Hsl {
+    pub const fn new(hue: u16, saturation: u8, lightness: u8) -> Self {
+        Self {
+            hue,
+            saturation,
+            lightness,
+        }
+    }
+
+    pub fn to_rgb(self) -> (u8, u8, u8) {
+        let s = f32::from(self.saturation) / 100.0;
+        let l = f32::from(self.lightness) / 100.0;
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let h = f32::from(self.hue % 360) / 60.0;
+        let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = l - chroma / 2.0;
+        let channel = |value: f32| ((value + m) * 255.0).round() as u8;
+        (channel(r), channel(g), channel(b))
+    }
+
+    /// 最接近的整数 HSL 值。灰色的色相为 0。
+    pub fn from_rgb((r, g, b): (u8, u8, u8)) -> Self {
+        let [r, g, b] = [r, g, b].map(|channel| f32::from(channel) / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let l = (max + min) / 2.0;
+        if delta == 0.0 {
+            return Self::new(0, 0, (l * 100.0).round() as u8);
+        }
+        let s = delta / (1.0 - (2.0 * l - 1.0).abs());
+        let h = if max == r {
+            ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            (b - r) / delta + 2.0
+        } else {
+            (r - g) / delta + 4.0
+        };
+        Self::new(
+            (h * 60.0).round() as u16 % 360,
+            (s * 100.0).round() as u8,
+            (l * 100.0).round() as u8,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert() {
+        let cases = [
+            (Hsl::new(0, 100, 50), (255, 0, 0)),
+            (Hsl::new(120, 100, 25), (0, 128, 0)),
+            (Hsl::new(210, 100, 56), (31, 143, 255)),
+            (Hsl::new(300, 50, 75), (223, 159, 223)),
+            (Hsl::new(0, 0, 100), (255, 255, 255)),
+            (Hsl::new(0, 0, 0), (0, 0, 0)),
+        ];
+        for (hsl, rgb) in cases {
+            assert_eq!(hsl.to_rgb(), rgb, "{hsl:?}");
+            assert_eq!(Hsl::from_rgb(rgb), hsl, "{rgb:?}");
+        }
+        assert_eq!(Hsl::from_rgb((30, 144, 255)), Hsl::new(210, 100, 56));
+    }
+}
@@ -0,0 +1,596 @@
+//! 颜色选择器演示：16 色、256 色调色板和用 HSL 滑块调节的真彩色，右侧预览选中的颜色
+//! 及其十六进制、RGB、HSL 和 ANSI 转义序列，并可以复制到剪贴板（OSC 52）。
+//!
+//! 切换调色板时选中最接近当前颜色的颜色，也是 `ratatui_common::capabilities`
+//! 中颜色转换的演示。终端不支持真彩色时颜色按终端的能力近似显示。
+//!
+//! 按键：`Tab` 切换调色板，方向键选择颜色；HSL 中 `Up` / `Down` 选择滑块，
+//! `Left` / `Right` 调节（按住 `Shift` 或用 `PageUp` / `PageDown` 一次调节 10），
+//! `c` 复制十六进制，`a` 复制 ANSI 转义序列，`Esc` 退出。
+
+use std::io;
+
+use color_eyre::{eyre::WrapErr, Result};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Paragraph, Tabs},
+};
+use ratatui_common::{
+    capabilities::{self, Capabilities, ColorSupport},
+    clipboard,
+    events::{EventSource, TerminalEvents},
+    terminal,
+};
+
+use crate::hsl::Hsl;
+
+mod hsl;
+
+/// 16 种 ANSI 颜色的名称，顺序与调色板的编号相同。
+const ANSI_NAMES: [&str; 16] = [
+    "Black",
+    "Red",
+    "Green",
+    "Yellow",
+    "Blue",
+    "Magenta",
+    "Cyan",
+    "White",
+    "Bright Black",
+    "Bright Red",
+    "Bright Green",
+    "Bright Yellow",
+    "Bright Blue",
+    "Bright Magenta",
+    "Bright Cyan",
+    "Bright White",
+];
+
+/// 256 色调色板每行显示的颜色数。
+const INDEXED_COLUMNS: u8 = 16;
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new(Capabilities::detect()).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Palette {
+    Ansi16,
+    Indexed256,
+    TrueColor,
+}
+
+impl Palette {
+    const ALL: [Palette; 3] = [Palette::Ansi16, Palette::Indexed256, Palette::TrueColor];
+
+    fn name(self) -> &'static str {
+        match self {
+            Palette::Ansi16 => "16 colors",
+            Palette::Indexed256 => "256 colors",
+            Palette::TrueColor => "True color (HSL)",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slider {
+    Hue,
+    Saturation,
+    Lightness,
+}
+
+impl Slider {
+    const ALL: [Slider; 3] = [Slider::Hue, Slider::Saturation, Slider::Lightness];
+
+    fn max(self) -> u16 {
+        match self {
+            Slider::Hue => 359,
+            Slider::Saturation | Slider::Lightness => 100,
+        }
+    }
+
+    fn get(self, hsl: Hsl) -> u16 {
+        match self {
+            Slider::Hue => hsl.hue,
+            Slider::Saturation => hsl.saturation.into(),
+            Slider::Lightness => hsl.lightness.into(),
+        }
+    }
+
+    fn set(self, hsl: &mut Hsl, value: u16) {
+        let value = value.min(self.max());
+        match self {
+            Slider::Hue => hsl.hue = value,
+            Slider::Saturation => hsl.saturation = value as u8,
+            Slider::Lightness => hsl.lightness = value as u8,
+        }
+    }
+
+    fn label(self, hsl: Hsl) -> String {
+        match self {
+            Slider::Hue => format!("Hue        {:>3}°", hsl.hue),
+            Slider::Saturation => format!("Saturation {:>3}%", hsl.saturation),
+            Slider::Lightness => format!("Lightness  {:>3}%", hsl.lightness),
+        }
+    }
+}
+
+struct App {
+    capabilities: Capabilities,
+    palette: Palette,
+    ansi: u8,
+    indexed: u8,
+    hsl: Hsl,
+    slider: Slider,
+    /// 等待写到终端的剪贴板内容。
+    clipboard: Option<String>,
+    message: Option<Result<String, String>>,
+    exit: bool,
+}
+
+impl App {
+    fn new(capabilities: Capabilities) -> Self {
+        Self {
+            capabilities,
+            palette: Palette::TrueColor,
+            ansi: 4,
+            indexed: 33,
+            hsl: Hsl::new(210, 100, 56),
+            slider: Slider::Hue,
+            clipboard: None,
+            message: None,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            if let Some(text) = self.clipboard.take() {
+                clipboard::copy(&mut io::stdout(), &text)
+                    .wrap_err_with(|| format!("failed to copy {text:?}"))?;
+            }
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut()))?;
+            if let Event::Key(key) = events.read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 选中的颜色的 RGB 值。
+    fn rgb(&self) -> (u8, u8, u8) {
+        match self.palette {
+            Palette::Ansi16 => capabilities::indexed_to_rgb(self.ansi),
+            Palette::Indexed256 => capabilities::indexed_to_rgb(self.indexed),
+            Palette::TrueColor => self.hsl.to_rgb(),
+        }
+    }
+
+    fn hex(&self) -> String {
+        let (r, g, b) = self.rgb();
+        format!("#{r:02X}{g:02X}{b:02X}")
+    }
+
+    /// 把选中的颜色设为前景色的 SGR 转义序列，`ESC` 写成 `\x1b`。
+    fn ansi_code(&self) -> String {
+        let parameters = match self.palette {
+            Palette::Ansi16 if self.ansi < 8 => (30 + self.ansi).to_string(),
+            Palette::Ansi16 => (90 + self.ansi - 8).to_string(),
+            Palette::Indexed256 => format!("38;5;{}", self.indexed),
+            Palette::TrueColor => {
+                let (r, g, b) = self.rgb();
+                format!("38;2;{r};{g};{b}")
+            }
+        };
+        format!("\\x1b[{parameters}m")
+    }
+
+    /// 切换调色板，选中其中最接近当前颜色的颜色。
+    fn switch(&mut self, palette: Palette) {
+        let (r, g, b) = self.rgb();
+        match palette {
+            Palette::Ansi16 => {
+                self.ansi = (0..16)
+                    .min_by_key(|&index| distance((r, g, b), capabilities::indexed_to_rgb(index)))
+                    .unwrap_or(0);
+            }
+            Palette::Indexed256 => self.indexed = capabilities::rgb_to_256(r, g, b),
+            Palette::TrueColor => self.hsl = Hsl::from_rgb((r, g, b)),
+        }
+        self.palette = palette;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        let position = Palette::ALL
+            .iter()
+            .position(|&p| p == self.palette)
+            .unwrap_or(0);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
+            KeyCode::Tab => self.switch(Palette::ALL[(position + 1) % Palette::ALL.len()]),
+            KeyCode::BackTab => {
+                self.switch(Palette::ALL[(position + Palette::ALL.len() - 1) % Palette::ALL.len()]);
+            }
+            KeyCode::Char('c') => self.copy(self.hex()),
+            KeyCode::Char('a') => self.copy(self.ansi_code()),
+            code => match self.palette {
+                Palette::Ansi16 => self.ansi = step(self.ansi, code, 8, 16),
+                Palette::Indexed256 => {
+                    self.indexed = step(self.indexed, code, INDEXED_COLUMNS, 0);
+                }
+                Palette::TrueColor => self.adjust(code, if shift { 10 } else { 1 }),
+            },
+        }
+    }
+
+    fn adjust(&mut self, code: KeyCode, amount: u16) {
+        let index = Slider::ALL
+            .iter()
+            .position(|&s| s == self.slider)
+            .unwrap_or(0);
+        let value = self.slider.get(self.hsl);
+        let value = match code {
+            KeyCode::Up => {
+                self.slider = Slider::ALL[index.saturating_sub(1)];
+                return;
+            }
+            KeyCode::Down => {
+                self.slider = Slider::ALL[(index + 1).min(Slider::ALL.len() - 1)];
+                return;
+            }
+            KeyCode::Left => value.saturating_sub(amount),
+            KeyCode::Right => value + amount,
+            KeyCode::PageDown => value.saturating_sub(10),
+            KeyCode::PageUp => value + 10,
+            KeyCode::Home => 0,
+            KeyCode::End => self.slider.max(),
+            _ => return,
+        };
+        self.slider.set(&mut self.hsl, value);
+    }
+
+    fn copy(&mut self, text: String) {
+        self.message = Some(Ok(format!("Copied: {text}")));
+        self.clipboard = Some(text);
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let [tabs_area, main, status] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+        let [palette_area, preview_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(28)]).areas(main);
+
+        let selected = Palette::ALL.iter().position(|&p| p == self.palette);
+        Tabs::new(Palette::ALL.map(Palette::name))
+            .select(selected.unwrap_or(0))
+            .highlight_style(Style::new().bold().reversed())
+            .render(tabs_area, buf);
+
+        let block = Block::bordered().title(format!(" {} ", self.palette.name()));
+        let inner = block.inner(palette_area);
+        block.render(palette_area, buf);
+        match self.palette {
+            Palette::Ansi16 => self.render_ansi(inner, buf),
+            Palette::Indexed256 => self.render_indexed(inner, buf),
+            Palette::TrueColor => self.render_sliders(inner, buf),
+        }
+        self.render_preview(preview_area, buf);
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+
+    /// 终端能显示的颜色。
+    fn color(&self, rgb: (u8, u8, u8)) -> Color {
+        self.capabilities
+            .adapt_color(Color::Rgb(rgb.0, rgb.1, rgb.2))
+    }
+
+    /// 以索引颜色显示的色块，不经过 RGB 转换，显示的是终端自己的调色板。
+    fn swatch(&self, area: Rect, buf: &mut Buffer, index: u8, label: &str, selected: bool) {
+        let rgb = capabilities::indexed_to_rgb(index);
+        let color = self.capabilities.adapt_color(Color::Indexed(index));
+        let mut style = Style::new().bg(color).fg(contrast(rgb));
+        if selected {
+            style = style.bold().underlined();
+        }
+        buf.set_style(area, Style::new().bg(color));
+        buf.set_stringn(area.x, area.y, label, area.width as usize, style);
+    }
+
+    fn render_ansi(&self, area: Rect, buf: &mut Buffer) {
+        let width = area.width / 8;
+        for index in 0..16u8 {
+            let x = area.x + u16::from(index % 8) * width;
+            let y = area.y + u16::from(index / 8) * 3;
+            if y + 3 > area.bottom() {
+                break;
+            }
+            let selected = index == self.ansi;
+            let marker = if selected { "▸" } else { " " };
+            // 名称显示在预览中，色块可能太窄。
+            let label = format!("{marker}{index}");
+            self.swatch(Rect::new(x, y, width, 3), buf, index, &label, selected);
+        }
+    }
+
+    fn render_indexed(&self, area: Rect, buf: &mut Buffer) {
+        let width = (area.width / u16::from(INDEXED_COLUMNS)).max(1);
+        for index in 0..=255u8 {
+            let x = area.x + u16::from(index % INDEXED_COLUMNS) * width;
+            let y = area.y + u16::from(index / INDEXED_COLUMNS);
+            if y >= area.bottom() {
+                break;
+            }
+            let selected = index == self.indexed;
+            let label = format!("{}{index:>3}", if selected { "▸" } else { " " });
+            self.swatch(Rect::new(x, y, width, 1), buf, index, &label, selected);
+        }
+    }
+
+    /// 三个滑块，每个滑块的色带显示只改变这一项时的颜色；下面是当前饱和度下的色相 × 亮度图。
+    fn render_sliders(&self, area: Rect, buf: &mut Buffer) {
+        let [sliders, field] =
+            Layout::vertical([Constraint::Length(9), Constraint::Fill(1)]).areas(area);
+        let bar_width = sliders.width.saturating_sub(2);
+        for (row, slider) in Slider::ALL.into_iter().enumerate() {
+            let y = sliders.y + row as u16 * 3;
+            if y + 3 > sliders.bottom() || bar_width == 0 {
+                break;
+            }
+            let style = if slider == self.slider {
+                Style::new().green().bold()
+            } else {
+                Style::new()
+            };
+            buf.set_string(sliders.x + 1, y, slider.label(self.hsl), style);
+            let position =
+                |value: u16| u32::from(value) * u32::from(bar_width - 1) / u32::from(slider.max());
+            for i in 0..bar_width {
+                let value = (u32::from(i) * u32::from(slider.max())
+                    / u32::from(bar_width - 1).max(1)) as u16;
+                let mut hsl = self.hsl;
+                slider.set(&mut hsl, value);
+                buf.get_mut(sliders.x + 1 + i, y + 1)
+                    .set_char(' ')
+                    .set_bg(self.color(hsl.to_rgb()));
+            }
+            let marker = position(slider.get(self.hsl)) as u16;
+            buf.set_string(sliders.x + 1 + marker, y + 2, "▲", style);
+        }
+
+        // 每个单元格用上半块显示两行：前景色是上面一行，背景色是下面一行。
+        let rows = u32::from(field.height) * 2;
+        if field.width == 0 || rows < 2 {
+            return;
+        }
+        for y in 0..field.height {
+            for x in 0..field.width {
+                let hue = (u32::from(x) * 360 / u32::from(field.width)) as u16;
+                let lightness = |row: u32| (100 - row * 100 / (rows - 1)) as u8;
+                let top = Hsl::new(hue, self.hsl.saturation, lightness(u32::from(y) * 2));
+                let bottom = Hsl::new(hue, self.hsl.saturation, lightness(u32::from(y) * 2 + 1));
+                buf.get_mut(field.x + x, field.y + y)
+                    .set_char('▀')
+                    .set_fg(self.color(top.to_rgb()))
+                    .set_bg(self.color(bottom.to_rgb()));
+            }
+        }
+    }
+
+    fn render_preview(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered().title(" Preview ");
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let [swatch, info] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(7)]).areas(inner);
+        let rgb = self.rgb();
+        let color = match self.palette {
+            Palette::Ansi16 => Color::Indexed(self.ansi),
+            Palette::Indexed256 => Color::Indexed(self.indexed),
+            Palette::TrueColor => Color::Rgb(rgb.0, rgb.1, rgb.2),
+        };
+        buf.set_style(
+            swatch,
+            Style::new().bg(self.capabilities.adapt_color(color)),
+        );
+
+        let hsl = Hsl::from_rgb(rgb);
+        let name = match self.palette {
+            Palette::Ansi16 => ANSI_NAMES[usize::from(self.ansi)].to_string(),
+            _ => format!("256: {}", capabilities::rgb_to_256(rgb.0, rgb.1, rgb.2)),
+        };
+        let lines = vec![
+            Line::from(self.hex().bold()),
+            Line::from(format!("rgb({}, {}, {})", rgb.0, rgb.1, rgb.2)),
+            Line::from(format!(
+                "hsl({}, {}%, {}%)",
+                hsl.hue, hsl.saturation, hsl.lightness
+            )),
+            Line::from(self.ansi_code()),
+            Line::from(name.dim()),
+            Line::default(),
+            Line::from("Text sample".fg(self.capabilities.adapt_color(color))),
+        ];
+        Paragraph::new(lines).render(info, buf);
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        if let Some(message) = &self.message {
+            return match message {
+                Ok(text) => Line::from(text.clone()),
+                Err(text) => Line::from(text.clone().red()),
+            };
+        }
+        let support = match self.capabilities.colors {
+            ColorSupport::TrueColor => "",
+            ColorSupport::Indexed256 => "256-color terminal, colors approximated  ",
+            ColorSupport::Ansi16 => "16-color terminal, colors approximated  ",
+        };
+        Line::from(vec![
+            support.yellow(),
+            "Palette <Tab> Hex <c> ANSI <a> Quit <Esc>".into(),
+        ])
+    }
+}
+
+/// 在 `columns` 列的网格中按方向键移动。`len` 为 0 表示 256 项。
+fn step(index: u8, code: KeyCode, columns: u8, len: u16) -> u8 {
+    let len = if len == 0 { 256 } else { len };
+    let index = u16::from(index);
+    let columns = u16::from(columns);
+    let next = match code {
+        KeyCode::Left if index % columns > 0 => index - 1,
+        KeyCode::Right if index % columns + 1 < columns && index + 1 < len => index + 1,
+        KeyCode::Up if index >= columns => index - columns,
+        KeyCode::Down if index + columns < len => index + columns,
+        KeyCode::Home => 0,
+        KeyCode::End => len - 1,
+        _ => index,
+    };
+    next as u8
+}
+
+/// 在这种背景色上容易看清的文字颜色。
+fn contrast((r, g, b): (u8, u8, u8)) -> Color {
+    let luma = 299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b);
+    if luma > 140_000 {
+        Color::Black
+    } else {
+        Color::White
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| u32::from(x.abs_diff(y)).pow(2);
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    #[test]
+    fn adjust_sliders() {
+        let mut app = App::new(Capabilities::default());
+        app.handle_key(KeyCode::Right.into());
+        app.handle_key(KeyEvent::new(KeyCode::Left, KeyModifiers::SHIFT));
+        assert_eq!(app.hsl.hue, 201);
+        app.handle_key(KeyCode::Down.into());
+        app.handle_key(KeyCode::Down.into());
+        app.handle_key(KeyCode::Down.into());
+        assert_eq!(app.slider, Slider::Lightness);
+        app.handle_key(KeyCode::End.into());
+        assert_eq!(app.hsl.lightness, 100);
+        app.handle_key(KeyCode::PageUp.into());
+        assert_eq!(app.hsl.lightness, 100);
+        assert_eq!(app.hex(), "#FFFFFF");
+
+        app.handle_key(KeyCode::PageDown.into());
+        app.handle_key(KeyCode::PageDown.into());
+        app.handle_key(KeyCode::PageDown.into());
+        app.handle_key(KeyCode::PageDown.into());
+        app.handle_key(KeyCode::PageDown.into());
+        assert_eq!(app.rgb(), Hsl::new(201, 100, 50).to_rgb());
+        assert_eq!(app.ansi_code(), "\\x1b[38;2;0;166;255m");
+    }
+
+    #[test]
+    fn switch_palettes() {
+        let mut app = App::new(Capabilities::default());
+        app.hsl = Hsl::new(0, 100, 50);
+        app.handle_key(KeyCode::Tab.into());
+        assert_eq!(app.palette, Palette::Ansi16);
+        assert_eq!(app.ansi, 9);
+        let rows16 = rows(&mut app, 60, 14);
+        assert!(rows16[5].starts_with("│ 8 ▸9  10 11"));
+        assert_eq!(app.ansi_code(), "\\x1b[91m");
+        app.handle_key(KeyCode::Up.into());
+        assert_eq!(app.ansi, 1);
+        assert_eq!(app.ansi_code(), "\\x1b[31m");
+
+        app.handle_key(KeyCode::Tab.into());
+        assert_eq!(app.indexed, 160);
+        assert!(rows(&mut app, 100, 20)[12].contains("▸160"));
+        // 太小时也能绘制。
+        rows(&mut app, 8, 3);
+        assert_eq!(app.ansi_code(), "\\x1b[38;5;160m");
+        app.handle_key(KeyCode::Down.into());
+        assert_eq!(app.indexed, 176);
+        app.handle_key(KeyCode::End.into());
+        app.handle_key(KeyCode::Down.into());
+        app.handle_key(KeyCode::Right.into());
+        assert_eq!(app.indexed, 255);
+
+        app.handle_key(KeyCode::BackTab.into());
+        assert_eq!(app.palette, Palette::Ansi16);
+        assert_eq!(app.ansi, 7);
+        app.handle_key(KeyCode::BackTab.into());
+        assert_eq!(app.palette, Palette::TrueColor);
+        assert_eq!(app.hsl, Hsl::new(0, 0, 90));
+    }
+
+    #[test]
+    fn copy_codes() {
+        let mut app = App::new(Capabilities::default());
+        app.handle_key(KeyCode::Char('c').into());
+        assert_eq!(app.clipboard.as_deref(), Some("#1F8FFF"));
+        assert_eq!(app.message, Some(Ok("Copied: #1F8FFF".into())));
+        app.handle_key(KeyCode::Char('a').into());
+        assert_eq!(app.clipboard.as_deref(), Some("\\x1b[38;2;31;143;255m"));
+    }
+
+    #[test]
+    fn render_preview() {
+        let mut app = App::new(Capabilities {
+            colors: ColorSupport::Indexed256,
+            ..Capabilities::default()
+        });
+        let mut buf = Buffer::empty(Rect::new(0, 0, 60, 14));
+        app.render(buf.area, &mut buf);
+        let rows = rows(&mut app, 60, 14);
+        assert_eq!(
+            rows[0],
+            " 16 colors │ 256 colors │ True color (HSL)                  "
+        );
+        assert_eq!(
+            rows[2],
+            "│ Hue        210°              ││                          │"
+        );
+        assert_eq!(
+            rows[4],
+            "│                ▲             ││                          │"
+        );
+        assert_eq!(
+            rows[5],
+            "│ Saturation 100%              ││#1F8FFF                   │"
+        );
+        assert_eq!(
+            rows[13],
+            "256-color terminal, colors approximated  Palette <Tab> Hex <"
+        );
+        // 真彩色按 256 色显示。
+        assert_eq!(buf.get(40, 2).bg, Color::Indexed(33));
+    }
+}
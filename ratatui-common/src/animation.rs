@@ -0,0 +1,234 @@
+//! 动画：缓动函数、按时间插值的数值和颜色，以及由主循环推进的动画。
+//!
+//! [`Animation`] 记录当前的值、目标值和开始的时刻。[`Animation::animate_to`] 开始过渡，主循环在
+//! 每一帧绘制前调用 [`Animation::tick`] 推进，绘制时用 [`Animation::value`] 取当前的值。有动画在
+//! 播放时主循环最多等待到 [`Animation::next_frame`]。
+//!
+//! 是否播放由 [`Motion`] 决定：减少动态效果时 `animate_to` 直接停在目标值上。
+
+use std::time::{Duration, Instant};
+
+use ratatui::style::Color;
+
+use crate::{capabilities, motion::Motion};
+
+/// 播放动画时两帧之间的间隔，大约每秒 60 帧。
+pub const FRAME: Duration = Duration::from_millis(16);
+
+/// 缓动函数：把经过的时间比例（0 到 1）换算成变化的比例。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// 开始慢，越来越快。
+    EaseIn,
+    /// 开始快，越来越慢，适合出现的东西。
+    EaseOut,
+    /// 两头慢，中间快。
+    EaseInOut,
+}
+
+impl Easing {
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t * t,
+            Self::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Self::EaseInOut if t < 0.5 => 4.0 * t * t * t,
+            Self::EaseInOut => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+        }
+    }
+}
+
+/// 可以在两个值之间插值的类型。
+pub trait Lerp: Copy {
+    /// `t` 为 0 时是 `self`，为 1 时是 `to`。
+    fn lerp(self, to: Self, t: f64) -> Self;
+}
+
+impl Lerp for f64 {
+    fn lerp(self, to: Self, t: f64) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for u16 {
+    fn lerp(self, to: Self, t: f64) -> Self {
+        f64::from(self).lerp(f64::from(to), t).round() as u16
+    }
+}
+
+/// 按 RGB 分量插值，结果是真彩色，需要时由调用方按终端能力降级。`Reset` 这样没有具体颜色的
+/// 值无法插值，在中间直接切换。
+impl Lerp for Color {
+    fn lerp(self, to: Self, t: f64) -> Self {
+        match (to_rgb(self), to_rgb(to)) {
+            (Some((r1, g1, b1)), Some((r2, g2, b2))) => {
+                let channel =
+                    |from: u8, to: u8| f64::from(from).lerp(f64::from(to), t).round() as u8;
+                Color::Rgb(channel(r1, r2), channel(g1, g2), channel(b1, b2))
+            }
+            _ if t < 0.5 => self,
+            _ => to,
+        }
+    }
+}
+
+/// 颜色的 RGB 分量，没有具体颜色（`Reset`）时为 `None`。
+pub fn to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        color => capabilities::to_indexed(color).map(capabilities::indexed_to_rgb),
+    }
+}
+
+/// 从当前的值过渡到目标值的动画。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animation<T> {
+    from: T,
+    to: T,
+    duration: Duration,
+    easing: Easing,
+    /// 开始过渡的时刻，已经停在目标值上时为 `None`。
+    start: Option<Instant>,
+    /// 最近一次 `tick` 时变化的比例，已经缓动过。
+    progress: f64,
+}
+
+impl<T: Lerp> Animation<T> {
+    /// 停在 `value` 上的动画，之后每次过渡持续 `duration`。
+    pub fn new(value: T, duration: Duration, easing: Easing) -> Self {
+        Self {
+            from: value,
+            to: value,
+            duration,
+            easing,
+            start: None,
+            progress: 1.0,
+        }
+    }
+
+    pub fn value(&self) -> T {
+        self.from.lerp(self.to, self.progress)
+    }
+
+    pub fn target(&self) -> T {
+        self.to
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.start.is_some()
+    }
+
+    /// 从当前的值开始过渡到 `to`；减少动态效果时直接停在 `to` 上。
+    pub fn animate_to(&mut self, to: T, motion: Motion, now: Instant) {
+        self.from = self.value();
+        self.to = to;
+        self.progress = 0.0;
+        self.start = Some(now);
+        if motion
+            .animate(self.duration)
+            .is_none_or(|duration| duration.is_zero())
+        {
+            self.set(to);
+        }
+    }
+
+    /// 不经过渡直接停在 `value` 上。
+    pub fn set(&mut self, value: T) {
+        self.from = value;
+        self.to = value;
+        self.start = None;
+        self.progress = 1.0;
+    }
+
+    /// 按 `now` 推进，返回是否还在播放。
+    pub fn tick(&mut self, now: Instant) -> bool {
+        let Some(start) = self.start else {
+            return false;
+        };
+        let elapsed = now.saturating_duration_since(start);
+        if elapsed >= self.duration {
+            self.set(self.to);
+            return false;
+        }
+        self.progress = self
+            .easing
+            .apply(elapsed.as_secs_f64() / self.duration.as_secs_f64());
+        true
+    }
+
+    /// 还在播放时下一帧的时刻。
+    pub fn next_frame(&self, now: Instant) -> Option<Instant> {
+        self.start.map(|_| now + FRAME)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn easing() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+            assert_eq!(easing.apply(2.0), 1.0);
+        }
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+        assert!(Easing::EaseIn.apply(0.5) < 0.5);
+        assert!(Easing::EaseOut.apply(0.5) > 0.5);
+    }
+
+    #[test]
+    fn interpolate_colors() {
+        assert_eq!(
+            Color::Rgb(0, 100, 200).lerp(Color::Rgb(100, 0, 200), 0.5),
+            Color::Rgb(50, 50, 200)
+        );
+        // 索引颜色按调色板中的 RGB 值插值。
+        assert_eq!(
+            Color::Indexed(16).lerp(Color::Indexed(231), 0.5),
+            Color::Rgb(128, 128, 128)
+        );
+        assert_eq!(Color::Reset.lerp(Color::Red, 0.4), Color::Reset);
+        assert_eq!(Color::Reset.lerp(Color::Red, 0.6), Color::Red);
+    }
+
+    #[test]
+    fn tick_towards_the_target() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut animation = Animation::new(0u16, Duration::from_millis(100), Easing::Linear);
+        assert!(!animation.is_running());
+        assert_eq!(animation.next_frame(start), None);
+
+        animation.animate_to(10, Motion::default(), start);
+        assert_eq!(animation.value(), 0);
+        assert_eq!(animation.next_frame(start), Some(start + FRAME));
+        assert!(animation.tick(at(30)));
+        assert_eq!(animation.value(), 3);
+
+        // 中途改变目标时从当前的值开始。
+        animation.animate_to(0, Motion::default(), at(30));
+        assert!(animation.tick(at(80)));
+        assert_eq!(animation.value(), 2);
+        assert!(!animation.tick(at(130)));
+        assert_eq!(animation.value(), 0);
+        assert!(!animation.is_running());
+    }
+
+    #[test]
+    fn reduced_motion_jumps_to_the_target() {
+        let mut animation = Animation::new(0.0, Duration::from_millis(100), Easing::EaseOut);
+        animation.animate_to(1.0, Motion::reduced(), Instant::now());
+        assert!(!animation.is_running());
+        assert_eq!(animation.value(), 1.0);
+    }
+}
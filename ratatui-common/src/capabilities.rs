@@ -0,0 +1,295 @@
+//! 终端能力检测：支持的颜色数量、能否显示 Unicode 字符以及是否支持同步输出。
+//!
+//! 检测只依据环境变量（`COLORTERM`、`TERM`、`LANG` 等），不会向终端发送查询序列，
+//! 因此可以在进入原始模式之前完成。
+
+use std::env;
+
+use ratatui::{style::Color, symbols::border};
+
+/// 终端支持的颜色数量。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorSupport {
+    /// 16 种 ANSI 颜色。
+    Ansi16,
+    /// 256 色调色板。
+    Indexed256,
+    /// 24 位真彩色。
+    TrueColor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub colors: ColorSupport,
+    /// 能否显示边框等 Unicode 符号。
+    pub unicode: bool,
+    /// 是否支持同步输出（DEC 私有模式 2026），即把一整帧作为一次更新显示。
+    pub synchronized_output: bool,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            colors: ColorSupport::TrueColor,
+            unicode: true,
+            synchronized_output: false,
+        }
+    }
+}
+
+/// 已知支持同步输出的终端，按 `TERM_PROGRAM` 匹配。
+const SYNCHRONIZED_TERM_PROGRAMS: [&str; 5] =
+    ["iterm.app", "wezterm", "vscode", "ghostty", "tabby"];
+
+/// 已知支持同步输出的终端，按 `TERM` 的内容匹配。
+const SYNCHRONIZED_TERMS: [&str; 5] = ["kitty", "foot", "alacritty", "contour", "ghostty"];
+
+/// 只使用 ASCII 字符的边框，用于不支持 Unicode 的终端。
+pub const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+impl Capabilities {
+    /// 根据当前进程的环境变量检测。
+    pub fn detect() -> Self {
+        Self::from_env(|name| env::var(name).ok())
+    }
+
+    /// 根据 `var` 提供的环境变量检测，便于测试。
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> Self {
+        let colorterm = var("COLORTERM").unwrap_or_default().to_lowercase();
+        let term = var("TERM").unwrap_or_default().to_lowercase();
+        // Windows Terminal 不设置 COLORTERM，但会设置 WT_SESSION。
+        let colors =
+            if colorterm == "truecolor" || colorterm == "24bit" || var("WT_SESSION").is_some() {
+                ColorSupport::TrueColor
+            } else if term.contains("256color") {
+                ColorSupport::Indexed256
+            } else if cfg!(windows) && term.is_empty() {
+                // 现代 Windows 控制台支持真彩色，但不设置任何相关变量。
+                ColorSupport::TrueColor
+            } else {
+                ColorSupport::Ansi16
+            };
+
+        // 第一个非空的区域设置变量决定字符编码。
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .into_iter()
+            .find_map(|name| var(name).filter(|value| !value.is_empty()));
+        let unicode = match locale {
+            Some(locale) => {
+                let locale = locale.to_lowercase();
+                locale.contains("utf-8") || locale.contains("utf8")
+            }
+            None => cfg!(windows),
+        } && term != "linux";
+
+        let term_program = var("TERM_PROGRAM").unwrap_or_default().to_lowercase();
+        let synchronized_output = var("WT_SESSION").is_some()
+            || SYNCHRONIZED_TERM_PROGRAMS.contains(&term_program.as_str())
+            || SYNCHRONIZED_TERMS.iter().any(|name| term.contains(name));
+
+        Self {
+            colors,
+            unicode,
+            synchronized_output,
+        }
+    }
+
+    /// 把颜色转换为终端能显示的最接近的颜色。
+    pub fn adapt_color(&self, color: Color) -> Color {
+        match (self.colors, color) {
+            (ColorSupport::TrueColor, _) => color,
+            (ColorSupport::Indexed256, Color::Rgb(r, g, b)) => Color::Indexed(rgb_to_256(r, g, b)),
+            (ColorSupport::Ansi16, Color::Rgb(r, g, b)) => nearest_ansi16((r, g, b)),
+            (ColorSupport::Ansi16, Color::Indexed(index)) => nearest_ansi16(indexed_to_rgb(index)),
+            _ => color,
+        }
+    }
+
+    /// 终端能显示的边框：不支持 Unicode 时换成 ASCII 边框。
+    pub fn adapt_border(&self, set: border::Set) -> border::Set {
+        if self.unicode {
+            set
+        } else {
+            ASCII_BORDER
+        }
+    }
+}
+
+/// 6×6×6 颜色立方体中每一级的取值。
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn nearest_level(value: u8) -> usize {
+    CUBE_LEVELS
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, level)| level.abs_diff(value))
+        .map_or(0, |(index, _)| index)
+}
+
+/// 把 RGB 颜色转换为 256 色调色板中最接近的颜色（颜色立方体或灰阶）。
+pub fn rgb_to_256(r: u8, g: u8, b: u8) -> u8 {
+    let (ri, gi, bi) = (nearest_level(r), nearest_level(g), nearest_level(b));
+    let cube = (CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+    let cube_index = 16 + 36 * ri + 6 * gi + bi;
+
+    // 灰阶：232..=255，亮度为 8 + 10 * i。
+    let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let gray_step = (average.saturating_sub(8) / 10).min(23) as u8;
+    let gray = 8 + 10 * gray_step;
+
+    if distance((r, g, b), (gray, gray, gray)) < distance((r, g, b), cube) {
+        232 + gray_step
+    } else {
+        cube_index as u8
+    }
+}
+
+/// 256 色调色板中颜色的近似 RGB 值。前 16 种按 xterm 的默认颜色。
+pub fn indexed_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI16[usize::from(index)].1,
+        16..=231 => {
+            let index = index - 16;
+            (
+                CUBE_LEVELS[usize::from(index / 36)],
+                CUBE_LEVELS[usize::from(index / 6 % 6)],
+                CUBE_LEVELS[usize::from(index % 6)],
+            )
+        }
+        232..=255 => {
+            let gray = 8 + 10 * (index - 232);
+            (gray, gray, gray)
+        }
+    }
+}
+
+/// 颜色在 256 色调色板中的位置：ANSI 颜色是前 16 种，RGB 颜色取最接近的一种。
+/// `Color::Reset` 没有固定的颜色，返回 `None`。
+pub fn to_indexed(color: Color) -> Option<u8> {
+    match color {
+        Color::Reset => None,
+        Color::Indexed(index) => Some(index),
+        Color::Rgb(r, g, b) => Some(rgb_to_256(r, g, b)),
+        color => ANSI16
+            .iter()
+            .position(|(ansi, _)| *ansi == color)
+            .map(|index| index as u8),
+    }
+}
+
+/// 16 种 ANSI 颜色及其典型（xterm）RGB 值。
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+fn nearest_ansi16(rgb: (u8, u8, u8)) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, ansi)| distance(rgb, *ansi))
+        .map_or(Color::Reset, |(color, _)| *color)
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| u32::from(x.abs_diff(y)).pow(2);
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn detect(vars: &[(&str, &str)]) -> Capabilities {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        Capabilities::from_env(|name| vars.get(name).map(|value| value.to_string()))
+    }
+
+    #[test]
+    fn detect_colors() {
+        let truecolor = detect(&[("COLORTERM", "truecolor"), ("TERM", "xterm-256color")]);
+        assert_eq!(truecolor.colors, ColorSupport::TrueColor);
+        let indexed = detect(&[("TERM", "xterm-256color")]);
+        assert_eq!(indexed.colors, ColorSupport::Indexed256);
+        let ansi = detect(&[("TERM", "xterm")]);
+        assert_eq!(ansi.colors, ColorSupport::Ansi16);
+    }
+
+    #[test]
+    fn detect_unicode() {
+        assert!(detect(&[("TERM", "xterm"), ("LANG", "en_US.UTF-8")]).unicode);
+        assert!(!detect(&[("TERM", "xterm"), ("LANG", "C")]).unicode);
+        // LC_ALL 优先于 LANG。
+        assert!(!detect(&[("TERM", "xterm"), ("LC_ALL", "C"), ("LANG", "zh_CN.UTF-8")]).unicode);
+        // Linux 虚拟控制台的字体通常缺少边框字符。
+        assert!(!detect(&[("TERM", "linux"), ("LANG", "en_US.UTF-8")]).unicode);
+    }
+
+    #[test]
+    fn detect_synchronized_output() {
+        assert!(detect(&[("TERM", "xterm-kitty")]).synchronized_output);
+        assert!(
+            detect(&[("TERM", "xterm-256color"), ("TERM_PROGRAM", "WezTerm")]).synchronized_output
+        );
+        assert!(detect(&[("WT_SESSION", "1")]).synchronized_output);
+        assert!(!detect(&[("TERM", "xterm-256color")]).synchronized_output);
+    }
+
+    #[test]
+    fn downgrade_colors() {
+        let indexed = Capabilities {
+            colors: ColorSupport::Indexed256,
+            ..Capabilities::default()
+        };
+        assert_eq!(
+            indexed.adapt_color(Color::Rgb(255, 0, 0)),
+            Color::Indexed(196)
+        );
+        assert_eq!(
+            indexed.adapt_color(Color::Rgb(128, 128, 128)),
+            Color::Indexed(244)
+        );
+        assert_eq!(indexed.adapt_color(Color::Blue), Color::Blue);
+
+        let ansi = Capabilities {
+            colors: ColorSupport::Ansi16,
+            unicode: false,
+            ..Capabilities::default()
+        };
+        assert_eq!(ansi.adapt_color(Color::Rgb(230, 159, 0)), Color::Yellow);
+        assert_eq!(ansi.adapt_color(Color::Indexed(196)), Color::LightRed);
+        assert_eq!(ansi.adapt_border(border::THICK), ASCII_BORDER);
+    }
+
+    #[test]
+    fn palette_index() {
+        assert_eq!(to_indexed(Color::Reset), None);
+        assert_eq!(to_indexed(Color::LightBlue), Some(12));
+        assert_eq!(to_indexed(Color::Indexed(100)), Some(100));
+        assert_eq!(to_indexed(Color::Rgb(255, 0, 0)), Some(196));
+    }
+}
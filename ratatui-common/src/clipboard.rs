@@ -0,0 +1,35 @@
+//! 用 OSC 52 转义序列把文本复制到剪贴板。
+//!
+//! 序列由终端处理，所以通过 SSH 或在容器中运行时也能复制到本机的剪贴板，不需要访问 X11 或
+//! Wayland。不支持 OSC 52 的终端会忽略它。
+
+use std::io::{self, Write};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+/// OSC 52 序列，`c` 表示系统剪贴板。
+pub fn sequence(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", STANDARD.encode(text))
+}
+
+/// 把序列写到 `writer`（通常是终端的标准输出）并刷新。
+pub fn copy(writer: &mut impl Write, text: &str) -> io::Result<()> {
+    writer.write_all(sequence(text).as_bytes())?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn osc52() {
+        assert_eq!(
+            sequence("cargo add ratatui"),
+            "\x1b]52;c;Y2FyZ28gYWRkIHJhdGF0dWk=\x07"
+        );
+        let mut written = Vec::new();
+        copy(&mut written, "hi").unwrap();
+        assert_eq!(written, b"\x1b]52;c;aGk=\x07");
+    }
+}
@@ -0,0 +1,362 @@
+//! 调试控制台：按 F12 打开的覆盖面板，列出最近收到的事件、分派的操作和状态的变化，还可以输入
+//! 调试命令。
+//!
+//! 控制台不了解应用程序的类型，各个演示程序这样接入：
+//!
+//! 1. 收到事件时调用 [`Console::event`]，执行操作时调用 [`Console::message`]；
+//! 2. 处理完事件后调用 [`Console::watch`] 交出状态，控制台对比前后两次的 `{:#?}` 输出，记下变化的
+//!    行；
+//! 3. 每个按键先交给 [`Console::handle_key_event`]，控制台打开时所有按键都由它处理；
+//! 4. 界面画完后把 `&Console` 画在整个区域上，关闭时什么也不画。
+//!
+//! 控制台关闭时也在记录，打开时就能看到之前发生的事情。命令：
+//!
+//! ```text
+//! dump             输出完整的状态
+//! error [MESSAGE]  让应用程序返回一个错误，用来检查错误处理和终端的恢复
+//! clear            清空记录
+//! help             列出命令
+//! ```
+
+use std::collections::VecDeque;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Clear, Paragraph},
+};
+
+use crate::text_input::{History, Input};
+
+/// 打开和关闭控制台的按键。
+pub const TOGGLE: KeyCode = KeyCode::F(12);
+
+/// 最多保留的记录条数。
+const CAPACITY: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// 收到的事件。
+    Event,
+    /// 分派的操作。
+    Message,
+    /// 状态中变化的一行。
+    State,
+    /// 命令的输出。
+    Output,
+    Error,
+}
+
+impl Kind {
+    fn tag(self) -> &'static str {
+        match self {
+            Self::Event => "event",
+            Self::Message => "msg",
+            Self::State => "state",
+            Self::Output => "",
+            Self::Error => "error",
+        }
+    }
+
+    fn style(self) -> Style {
+        match self {
+            Self::Event => Style::new().cyan(),
+            Self::Message => Style::new().green(),
+            Self::State => Style::new().yellow(),
+            Self::Output => Style::new(),
+            Self::Error => Style::new().red(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub kind: Kind,
+    pub text: String,
+}
+
+/// [`Console::handle_key_event`] 的结果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsoleAction {
+    /// 控制台关闭着，按键交给应用程序处理。
+    Ignored,
+    Handled,
+    /// 执行了 `error` 命令，应用程序应当返回这个错误。
+    Error(String),
+}
+
+#[derive(Debug, Default)]
+pub struct Console {
+    open: bool,
+    entries: VecDeque<Entry>,
+    /// 上一次 [`Console::watch`] 时状态的 `{:#?}` 输出。
+    state: Option<String>,
+    input: Input,
+    history: History,
+}
+
+impl Console {
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter()
+    }
+
+    pub fn push(&mut self, kind: Kind, text: impl Into<String>) {
+        if self.entries.len() == CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Entry {
+            kind,
+            text: text.into(),
+        });
+    }
+
+    pub fn event(&mut self, text: impl Into<String>) {
+        self.push(Kind::Event, text);
+    }
+
+    pub fn message(&mut self, text: impl Into<String>) {
+        self.push(Kind::Message, text);
+    }
+
+    /// 记下 `state` 和上一次相比变化的行。第一次调用只保存下来，不算变化。
+    pub fn watch(&mut self, state: &impl std::fmt::Debug) {
+        let current = format!("{state:#?}");
+        let Some(previous) = self.state.replace(current) else {
+            return;
+        };
+        let current = self.state.as_deref().unwrap_or_default();
+        if previous == current {
+            return;
+        }
+        let (before, after): (Vec<&str>, Vec<&str>) =
+            (previous.lines().collect(), current.lines().collect());
+        let mut changes = Vec::new();
+        for index in 0..before.len().max(after.len()) {
+            let (old, new) = (before.get(index), after.get(index));
+            if old == new {
+                continue;
+            }
+            if let Some(old) = old {
+                changes.push(format!("- {}", old.trim()));
+            }
+            if let Some(new) = new {
+                changes.push(format!("+ {}", new.trim()));
+            }
+        }
+        for change in changes {
+            self.push(Kind::State, change);
+        }
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> ConsoleAction {
+        if key_event.code == TOGGLE {
+            self.open = !self.open;
+            return ConsoleAction::Handled;
+        }
+        if !self.open {
+            return ConsoleAction::Ignored;
+        }
+        match key_event.code {
+            KeyCode::Esc => self.open = false,
+            KeyCode::Enter => {
+                let command = std::mem::take(&mut self.input).value().trim().to_string();
+                self.history.push(&command);
+                return self.run(&command);
+            }
+            KeyCode::Up => {
+                if let Some(entry) = self.history.older(self.input.value()) {
+                    self.input = Input::with_value(entry);
+                }
+            }
+            KeyCode::Down => {
+                if let Some(entry) = self.history.newer() {
+                    self.input = Input::with_value(entry);
+                }
+            }
+            _ => {
+                self.input.handle_key_event(key_event);
+            }
+        }
+        ConsoleAction::Handled
+    }
+
+    fn run(&mut self, command: &str) -> ConsoleAction {
+        if command.is_empty() {
+            return ConsoleAction::Handled;
+        }
+        self.push(Kind::Output, format!("> {command}"));
+        let (name, argument) = command
+            .split_once(' ')
+            .map_or((command, ""), |(name, argument)| (name, argument.trim()));
+        match name {
+            "dump" => match self.state.clone() {
+                Some(state) => {
+                    for line in state.lines() {
+                        self.push(Kind::Output, line);
+                    }
+                }
+                None => self.push(Kind::Error, "no state yet"),
+            },
+            "error" => {
+                let message = if argument.is_empty() {
+                    "error forced from the debug console"
+                } else {
+                    argument
+                };
+                return ConsoleAction::Error(message.to_string());
+            }
+            "clear" => self.entries.clear(),
+            "help" => self.push(Kind::Output, "commands: dump, error [message], clear, help"),
+            _ => self.push(Kind::Error, format!("unknown command {name:?}, try help")),
+        }
+        ConsoleAction::Handled
+    }
+
+    /// 控制台在 `area` 中占据的区域：下面一半，至少 5 行。
+    pub fn area(area: Rect) -> Rect {
+        let height = (area.height / 2).max(5).min(area.height);
+        Rect::new(area.x, area.bottom() - height, area.width, height)
+    }
+}
+
+/// 最新的记录在最下面，紧挨着输入行。
+impl Widget for &Console {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if !self.open {
+            return;
+        }
+        let area = Console::area(area);
+        let block = Block::bordered()
+            .title(" Console ")
+            .title_bottom(" <F12> close ");
+        let inner = block.inner(area);
+        let visible = usize::from(inner.height.saturating_sub(1));
+        let skip = self.entries.len().saturating_sub(visible);
+        let mut lines: Vec<Line> = self
+            .entries
+            .iter()
+            .skip(skip)
+            .map(|entry| {
+                Line::from(vec![
+                    format!("{:>5} ", entry.kind.tag()).set_style(entry.kind.style()),
+                    entry.text.as_str().into(),
+                ])
+            })
+            .collect();
+        lines.push(self.input.line("    > ", Style::new().bold()));
+        Clear.render(area, buf);
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::buffer_rows;
+
+    fn type_command(console: &mut Console, command: &str) -> ConsoleAction {
+        for c in command.chars() {
+            console.handle_key_event(KeyCode::Char(c).into());
+        }
+        console.handle_key_event(KeyCode::Enter.into())
+    }
+
+    fn texts(console: &Console) -> Vec<String> {
+        console.entries().map(|entry| entry.text.clone()).collect()
+    }
+
+    #[test]
+    fn record_state_changes() {
+        let mut console = Console::default();
+        console.watch(&(0, "a"));
+        console.watch(&(0, "a"));
+        assert_eq!(console.entries().count(), 0);
+        console.event("Key(Right)");
+        console.message("Increment");
+        console.watch(&(1, "a"));
+        assert_eq!(texts(&console), ["Key(Right)", "Increment", "- 0,", "+ 1,"]);
+        assert_eq!(
+            console
+                .entries()
+                .map(|entry| entry.kind)
+                .collect::<Vec<_>>(),
+            [Kind::Event, Kind::Message, Kind::State, Kind::State]
+        );
+    }
+
+    #[test]
+    fn commands() {
+        let mut console = Console::default();
+        assert_eq!(
+            console.handle_key_event(KeyCode::Char('x').into()),
+            ConsoleAction::Ignored
+        );
+        assert_eq!(
+            console.handle_key_event(TOGGLE.into()),
+            ConsoleAction::Handled
+        );
+        assert!(console.is_open());
+
+        type_command(&mut console, "dump");
+        console.watch(&(2, "b"));
+        type_command(&mut console, "dump");
+        type_command(&mut console, "nope");
+        assert_eq!(
+            texts(&console),
+            [
+                "> dump",
+                "no state yet",
+                "> dump",
+                "(",
+                "    2,",
+                "    \"b\",",
+                ")",
+                "> nope",
+                "unknown command \"nope\", try help",
+            ]
+        );
+        assert_eq!(
+            type_command(&mut console, "error boom"),
+            ConsoleAction::Error("boom".into())
+        );
+
+        // 上键找回刚才的命令。
+        console.handle_key_event(KeyCode::Up.into());
+        assert_eq!(
+            console.handle_key_event(KeyCode::Enter.into()),
+            ConsoleAction::Error("boom".into())
+        );
+        type_command(&mut console, "clear");
+        assert_eq!(console.entries().count(), 0);
+        console.handle_key_event(KeyCode::Esc.into());
+        assert!(!console.is_open());
+    }
+
+    #[test]
+    fn render_latest_entries() {
+        let mut console = Console::default();
+        for index in 0..5 {
+            console.message(format!("message {index}"));
+        }
+        let mut buf = Buffer::empty(Rect::new(0, 0, 24, 10));
+        (&console).render(buf.area, &mut buf);
+        assert_eq!(buf, Buffer::empty(buf.area));
+
+        console.handle_key_event(TOGGLE.into());
+        (&console).render(buf.area, &mut buf);
+        assert_eq!(
+            buffer_rows(&buf)[5..],
+            [
+                "┌ Console ─────────────┐",
+                "│  msg message 3       │",
+                "│  msg message 4       │",
+                "│    >                 │",
+                "└ <F12> close ─────────┘",
+            ]
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! 倒计时控件：显示到目标时刻还剩多少时间，快到时换颜色。
+//!
+//! 剩余时间的秒数向上取整，所以显示 `00:00` 时时间确实到了。控件只显示一行文本，放在进度条旁边
+//! 或者用作标签都可以；需要自己组合时用 [`Countdown::text`] 和 [`Countdown::current_style`]。
+
+use std::time::{Duration, Instant};
+
+use ratatui::prelude::*;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// `04:59`，超过一小时时为 `1:02:03`。
+    #[default]
+    Clock,
+    /// `4m 59s`、`1h 02m`、`42s`，只显示最大的两个单位，第二个为零时省略，例如 `3m`。
+    Compact,
+}
+
+/// 把剩余时间格式化，秒数向上取整。
+pub fn format(remaining: Duration, format: Format) -> String {
+    let mut seconds = remaining.as_secs();
+    if remaining.subsec_nanos() > 0 {
+        seconds += 1;
+    }
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    match format {
+        Format::Clock if hours > 0 => format!("{hours}:{minutes:02}:{seconds:02}"),
+        Format::Clock => format!("{minutes:02}:{seconds:02}"),
+        Format::Compact if hours > 0 && minutes == 0 => format!("{hours}h"),
+        Format::Compact if hours > 0 => format!("{hours}h {minutes:02}m"),
+        Format::Compact if minutes > 0 && seconds == 0 => format!("{minutes}m"),
+        Format::Compact if minutes > 0 => format!("{minutes}m {seconds:02}s"),
+        Format::Compact => format!("{seconds}s"),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Countdown {
+    remaining: Duration,
+    format: Format,
+    style: Style,
+    /// 剩余时间不超过这个时长时使用对应的样式，按时长从小到大排列。
+    thresholds: Vec<(Duration, Style)>,
+    alignment: Alignment,
+}
+
+impl Countdown {
+    pub fn new(remaining: Duration) -> Self {
+        Self {
+            remaining,
+            format: Format::default(),
+            style: Style::new(),
+            thresholds: Vec::new(),
+            alignment: Alignment::Left,
+        }
+    }
+
+    /// 到 `target` 还剩的时间，已经过了时为零。
+    pub fn until(target: Instant, now: Instant) -> Self {
+        Self::new(target.saturating_duration_since(now))
+    }
+
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// 剩余时间不超过 `within` 时改用 `style`，叠加在基本样式上。有多个阈值时使用最小的那个。
+    pub fn threshold(mut self, within: Duration, style: Style) -> Self {
+        let at = self
+            .thresholds
+            .partition_point(|(other, _)| *other < within);
+        self.thresholds.insert(at, (within, style));
+        self
+    }
+
+    pub fn alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.remaining
+    }
+
+    pub fn text(&self) -> String {
+        format(self.remaining, self.format)
+    }
+
+    pub fn current_style(&self) -> Style {
+        self.thresholds
+            .iter()
+            .find(|(within, _)| self.remaining <= *within)
+            .map_or(self.style, |(_, style)| self.style.patch(*style))
+    }
+}
+
+impl Widget for &Countdown {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Line::from(self.text())
+            .style(self.current_style())
+            .alignment(self.alignment)
+            .render(area, buf);
+    }
+}
+
+impl Widget for Countdown {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        (&self).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 渲染出的文本，以及最后一个单元格的前景色和修饰。
+    fn render(countdown: &Countdown, width: u16) -> (String, Color, Modifier) {
+        let mut buf = Buffer::empty(Rect::new(0, 0, width, 1));
+        countdown.render(buf.area, &mut buf);
+        let text = (0..width).map(|x| buf.get(x, 0).symbol()).collect();
+        let cell = buf.get(width - 1, 0);
+        (text, cell.fg, cell.modifier)
+    }
+
+    #[test]
+    fn formats() {
+        let secs = Duration::from_secs;
+        assert_eq!(format(secs(299), Format::Clock), "04:59");
+        assert_eq!(
+            format(Duration::from_millis(299_001), Format::Clock),
+            "05:00"
+        );
+        assert_eq!(format(secs(3723), Format::Clock), "1:02:03");
+        assert_eq!(format(Duration::ZERO, Format::Clock), "00:00");
+        assert_eq!(format(secs(3723), Format::Compact), "1h 02m");
+        assert_eq!(format(secs(299), Format::Compact), "4m 59s");
+        assert_eq!(format(secs(42), Format::Compact), "42s");
+        assert_eq!(format(secs(180), Format::Compact), "3m");
+        assert_eq!(format(secs(7230), Format::Compact), "2h");
+    }
+
+    #[test]
+    fn colors_by_remaining_time() {
+        let countdown = |seconds: u64| {
+            Countdown::new(Duration::from_secs(seconds))
+                .style(Style::new().bold())
+                .threshold(Duration::from_secs(10), Style::new().red())
+                .threshold(Duration::from_secs(60), Style::new().yellow())
+                .alignment(Alignment::Right)
+        };
+        assert_eq!(
+            render(&countdown(1500), 7),
+            ("  25:00".into(), Color::Reset, Modifier::BOLD)
+        );
+        assert_eq!(
+            render(&countdown(60), 7),
+            ("  01:00".into(), Color::Yellow, Modifier::BOLD)
+        );
+        assert_eq!(
+            render(&countdown(9), 7),
+            ("  00:09".into(), Color::Red, Modifier::BOLD)
+        );
+        assert_eq!(
+            render(&countdown(0), 7),
+            ("  00:00".into(), Color::Red, Modifier::BOLD)
+        );
+
+        let now = Instant::now();
+        let target = now + Duration::from_millis(90_500);
+        let countdown = Countdown::until(target, now).format(Format::Compact);
+        assert_eq!(render(&countdown, 8).0, "1m 31s  ");
+        assert_eq!(Countdown::until(now, target).remaining(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,162 @@
+//! 多行文本编辑框，正则表达式演示的样例文本和 git 演示的提交说明共用。
+//!
+//! 和单行输入框（见 [`crate::text_input`]）一样，光标以字符为单位移动。
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Editor {
+    lines: Vec<String>,
+    /// 光标所在的行，以及光标之前的字符数。
+    row: usize,
+    column: usize,
+}
+
+impl Editor {
+    pub fn new(text: &str) -> Self {
+        let lines: Vec<String> = text.split('\n').map(str::to_string).collect();
+        Self {
+            row: lines.len() - 1,
+            column: lines.last().map_or(0, |line| line.chars().count()),
+            lines,
+        }
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.column)
+    }
+
+    fn len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    fn byte_index(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices()
+            .nth(self.column)
+            .map_or(line.len(), |(at, _)| at)
+    }
+
+    /// 每行文本一个 [`Line`]，光标所在的单元格反色显示。需要自己高亮文本时用 [`Editor::lines`]。
+    pub fn styled_lines(&self) -> Vec<Line<'_>> {
+        let cursor_at = self.byte_index();
+        self.lines
+            .iter()
+            .enumerate()
+            .map(|(row, line)| {
+                if row != self.row {
+                    return Line::from(line.as_str());
+                }
+                let (before, after) = line.split_at(cursor_at);
+                let mut chars = after.chars();
+                let under_cursor = chars
+                    .next()
+                    .map_or_else(|| " ".to_string(), |c| c.to_string());
+                Line::from(vec![
+                    before.into(),
+                    under_cursor.reversed(),
+                    chars.as_str().into(),
+                ])
+            })
+            .collect()
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> bool {
+        match key_event.code {
+            KeyCode::Char(c) => {
+                let at = self.byte_index();
+                self.lines[self.row].insert(at, c);
+                self.column += 1;
+            }
+            KeyCode::Enter => {
+                let at = self.byte_index();
+                let rest = self.lines[self.row].split_off(at);
+                self.row += 1;
+                self.lines.insert(self.row, rest);
+                self.column = 0;
+            }
+            KeyCode::Backspace if self.column > 0 => {
+                self.column -= 1;
+                let at = self.byte_index();
+                self.lines[self.row].remove(at);
+            }
+            // 在行首退格时和上一行合并。
+            KeyCode::Backspace if self.row > 0 => {
+                let line = self.lines.remove(self.row);
+                self.row -= 1;
+                self.column = self.len(self.row);
+                self.lines[self.row].push_str(&line);
+            }
+            KeyCode::Left if self.column > 0 => self.column -= 1,
+            KeyCode::Left if self.row > 0 => {
+                self.row -= 1;
+                self.column = self.len(self.row);
+            }
+            KeyCode::Right if self.column < self.len(self.row) => self.column += 1,
+            KeyCode::Right if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.column = 0;
+            }
+            KeyCode::Up if self.row > 0 => {
+                self.row -= 1;
+                self.column = self.column.min(self.len(self.row));
+            }
+            KeyCode::Down if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.column = self.column.min(self.len(self.row));
+            }
+            KeyCode::Home => self.column = 0,
+            KeyCode::End => self.column = self.len(self.row),
+            _ => return false,
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(editor: &mut Editor, codes: &[KeyCode]) {
+        for &code in codes {
+            editor.handle_key_event(code.into());
+        }
+    }
+
+    #[test]
+    fn edit_lines() {
+        let mut editor = Editor::new("ab\ncd");
+        assert_eq!(editor.cursor(), (1, 2));
+        press(&mut editor, &[KeyCode::Home, KeyCode::Backspace]);
+        assert_eq!(editor.text(), "abcd");
+        assert_eq!(editor.cursor(), (0, 2));
+        press(
+            &mut editor,
+            &[KeyCode::Enter, KeyCode::Char('x'), KeyCode::Up],
+        );
+        assert_eq!(editor.lines(), ["ab", "xcd"]);
+        assert_eq!(editor.cursor(), (0, 1));
+        press(&mut editor, &[KeyCode::End, KeyCode::Right]);
+        assert_eq!(editor.cursor(), (1, 0));
+    }
+
+    #[test]
+    fn cursor_cell() {
+        let editor = Editor::new("ab\n中");
+        let lines = editor.styled_lines();
+        assert_eq!(lines[0], Line::from("ab"));
+        assert_eq!(
+            lines[1].spans,
+            [Span::raw("中"), Span::raw(" ").reversed(), Span::raw("")]
+        );
+    }
+}
@@ -0,0 +1,41 @@
+//! 事件来源：各个演示程序的主循环都通过这个特征读取事件。
+//!
+//! 真实终端、录制文件的回放（见 [`crate::recording`]）或测试都可以作为事件来源，
+//! 主循环不需要知道事件从哪里来。
+
+use std::{io, time::Duration};
+
+use crossterm::event;
+
+/// 产生 `E` 类型事件的来源。
+pub trait EventSource<E> {
+    /// 阻塞直到下一个事件到来。
+    fn read(&mut self) -> io::Result<E>;
+
+    /// 在 `timeout` 内等待事件，有事件可以读取时返回 `true`。
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool>;
+}
+
+/// 从真实终端读取事件。
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TerminalEvents;
+
+impl EventSource<event::Event> for TerminalEvents {
+    fn read(&mut self) -> io::Result<event::Event> {
+        event::read()
+    }
+
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        event::poll(timeout)
+    }
+}
+
+impl<E, S: EventSource<E> + ?Sized> EventSource<E> for Box<S> {
+    fn read(&mut self) -> io::Result<E> {
+        (**self).read()
+    }
+
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        (**self).poll(timeout)
+    }
+}
@@ -0,0 +1,38 @@
+//! 保存文件：各个演示程序的状态、历史和数据文件都通过这里写入。
+
+use std::{ffi::OsString, fs, io, path::Path};
+
+/// 把 `bytes` 写入 `path`，需要时创建所在的目录。
+///
+/// 先写入同一目录下的 `<文件名>.tmp` 再重命名，中途失败时原来的文件保持不变，不会留下写了一半的文件。
+pub fn write_atomic(path: &Path, bytes: impl AsRef<[u8]>) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(".tmp");
+    let tmp = path.with_file_name(name);
+    fs::write(&tmp, bytes)?;
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    #[test]
+    fn replace_the_file() {
+        let dir = env::temp_dir().join(format!("ratatui-common-files-{}", process::id()));
+        let path = dir.join("nested").join("state.json");
+        write_atomic(&path, "first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert!(!dir.join("nested").join("state.json.tmp").exists());
+
+        // 目标是目录时重命名失败，返回错误。
+        assert!(write_atomic(&dir.join("nested"), "x").is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
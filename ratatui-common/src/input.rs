@@ -0,0 +1,274 @@
+//! 把远程终端发来的原始字节解析为 crossterm 的按键事件。
+//!
+//! 本地终端的输入由 crossterm 解析，但通过 SSH 或 TCP 连接的客户端只发送字节流。
+//! 这里支持常见的按键：可打印字符（UTF-8）、Ctrl 组合键、方向键、Home/End、翻页、功能键和 Alt 组合键。
+//! 无法识别的转义序列会被丢弃。
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+const ESC: u8 = 0x1b;
+
+/// 有状态的解析器：一个转义序列或 UTF-8 字符可能被拆到两次读取中。
+#[derive(Debug, Default)]
+pub struct InputParser {
+    pending: Vec<u8>,
+    /// 上一个字节是回车，紧跟的换行或 NUL（telnet 的 `\r\n`、`\r\0`）不再产生按键。
+    after_cr: bool,
+}
+
+impl InputParser {
+    /// 解析新收到的字节，返回已经完整的按键。
+    ///
+    /// 单独的 Esc 和转义序列的开头无法区分，所以一次读取末尾的 Esc 按 Esc 键处理：
+    /// 终端一次发送整个转义序列，实际上很少被拆开。
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<Event> {
+        self.pending.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        let mut start = 0;
+        while start < self.pending.len() {
+            let rest = &self.pending[start..];
+            let (consumed, key) = match parse(rest) {
+                Parsed::Key(consumed, key) => (consumed, Some(key)),
+                Parsed::Skip(consumed) => (consumed, None),
+                Parsed::Incomplete if rest[0] == ESC && rest.len() == 1 => {
+                    (1, Some(KeyEvent::from(KeyCode::Esc)))
+                }
+                Parsed::Incomplete => break,
+            };
+            let cr = rest[0] == b'\r';
+            let swallowed = self.after_cr && matches!(rest[0], b'\n' | 0);
+            self.after_cr = cr;
+            if let (Some(key), false) = (key, swallowed) {
+                events.push(Event::Key(key));
+            }
+            start += consumed;
+        }
+        self.pending.drain(..start);
+        events
+    }
+}
+
+enum Parsed {
+    Key(usize, KeyEvent),
+    /// 无法识别的序列，跳过这么多字节。
+    Skip(usize),
+    /// 需要更多字节。
+    Incomplete,
+}
+
+fn key(consumed: usize, code: KeyCode, modifiers: KeyModifiers) -> Parsed {
+    Parsed::Key(consumed, KeyEvent::new(code, modifiers))
+}
+
+fn parse(bytes: &[u8]) -> Parsed {
+    match bytes[0] {
+        b'\r' | b'\n' => key(1, KeyCode::Enter, KeyModifiers::NONE),
+        b'\t' => key(1, KeyCode::Tab, KeyModifiers::NONE),
+        0x7f | 0x08 => key(1, KeyCode::Backspace, KeyModifiers::NONE),
+        0 => Parsed::Skip(1),
+        ESC => parse_escape(bytes),
+        byte @ 0x01..=0x1a => key(
+            1,
+            KeyCode::Char((b'a' + byte - 1) as char),
+            KeyModifiers::CONTROL,
+        ),
+        0x1c..=0x1f => Parsed::Skip(1),
+        _ => match parse_char(bytes) {
+            Some((len, c)) => key(len, KeyCode::Char(c), KeyModifiers::NONE),
+            None if bytes.len() < utf8_len(bytes[0]) => Parsed::Incomplete,
+            None => Parsed::Skip(1),
+        },
+    }
+}
+
+fn utf8_len(first: u8) -> usize {
+    match first {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => 1,
+    }
+}
+
+fn parse_char(bytes: &[u8]) -> Option<(usize, char)> {
+    let len = utf8_len(bytes[0]);
+    let text = std::str::from_utf8(bytes.get(..len)?).ok()?;
+    Some((len, text.chars().next()?))
+}
+
+fn parse_escape(bytes: &[u8]) -> Parsed {
+    match bytes.get(1) {
+        None => Parsed::Incomplete,
+        Some(b'[') => parse_csi(bytes),
+        Some(b'O') => match bytes.get(2) {
+            None => Parsed::Incomplete,
+            Some(&byte) => match final_key(byte) {
+                Some(code) => key(3, code, KeyModifiers::NONE),
+                None => Parsed::Skip(3),
+            },
+        },
+        Some(&ESC) => key(1, KeyCode::Esc, KeyModifiers::NONE),
+        // Alt 组合键：Esc 后面跟着一个字符。
+        Some(_) => match parse(&bytes[1..]) {
+            Parsed::Key(len, mut event) => {
+                event.modifiers |= KeyModifiers::ALT;
+                Parsed::Key(len + 1, event)
+            }
+            Parsed::Skip(len) => Parsed::Skip(len + 1),
+            Parsed::Incomplete => Parsed::Incomplete,
+        },
+    }
+}
+
+/// `ESC [` 或 `ESC O` 之后表示按键的最后一个字节。
+fn final_key(byte: u8) -> Option<KeyCode> {
+    Some(match byte {
+        b'A' => KeyCode::Up,
+        b'B' => KeyCode::Down,
+        b'C' => KeyCode::Right,
+        b'D' => KeyCode::Left,
+        b'H' => KeyCode::Home,
+        b'F' => KeyCode::End,
+        b'P' => KeyCode::F(1),
+        b'Q' => KeyCode::F(2),
+        b'R' => KeyCode::F(3),
+        b'S' => KeyCode::F(4),
+        b'Z' => KeyCode::BackTab,
+        _ => return None,
+    })
+}
+
+/// `ESC [ 参数 最终字节`，参数由数字和分号组成，例如 `ESC [ 1 ; 5 C`（Ctrl+Right）。
+fn parse_csi(bytes: &[u8]) -> Parsed {
+    let Some(end) = bytes[2..]
+        .iter()
+        .position(|byte| (0x40..=0x7e).contains(byte))
+        .map(|index| index + 2)
+    else {
+        return Parsed::Incomplete;
+    };
+    let params: Vec<u16> = String::from_utf8_lossy(&bytes[2..end])
+        .split(';')
+        .map(|param| param.parse().unwrap_or(0))
+        .collect();
+    // 修饰键参数是 1 加上 Shift=1、Alt=2、Ctrl=4 的按位或。
+    let modifiers = match params.get(1) {
+        Some(&value) if value > 1 => {
+            let bits = value - 1;
+            let mut modifiers = KeyModifiers::NONE;
+            if bits & 1 != 0 {
+                modifiers |= KeyModifiers::SHIFT;
+            }
+            if bits & 2 != 0 {
+                modifiers |= KeyModifiers::ALT;
+            }
+            if bits & 4 != 0 {
+                modifiers |= KeyModifiers::CONTROL;
+            }
+            modifiers
+        }
+        _ => KeyModifiers::NONE,
+    };
+    let code = if bytes[end] == b'~' {
+        match params[0] {
+            1 | 7 => KeyCode::Home,
+            2 => KeyCode::Insert,
+            3 => KeyCode::Delete,
+            4 | 8 => KeyCode::End,
+            5 => KeyCode::PageUp,
+            6 => KeyCode::PageDown,
+            11..=15 => KeyCode::F((params[0] - 10) as u8),
+            17..=21 => KeyCode::F((params[0] - 11) as u8),
+            23 | 24 => KeyCode::F((params[0] - 12) as u8),
+            _ => return Parsed::Skip(end + 1),
+        }
+    } else {
+        match final_key(bytes[end]) {
+            Some(code) => code,
+            None => return Parsed::Skip(end + 1),
+        }
+    };
+    let modifiers = if code == KeyCode::BackTab {
+        modifiers | KeyModifiers::SHIFT
+    } else {
+        modifiers
+    };
+    key(end + 1, code, modifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(parser: &mut InputParser, bytes: &[u8]) -> Vec<KeyEvent> {
+        parser
+            .feed(bytes)
+            .into_iter()
+            .map(|event| match event {
+                Event::Key(key) => key,
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect()
+    }
+
+    fn plain(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn characters_and_control_keys() {
+        let mut parser = InputParser::default();
+        assert_eq!(
+            keys(&mut parser, "q你\r\x7f\t\x03".as_bytes()),
+            [
+                plain(KeyCode::Char('q')),
+                plain(KeyCode::Char('你')),
+                plain(KeyCode::Enter),
+                plain(KeyCode::Backspace),
+                plain(KeyCode::Tab),
+                KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            ]
+        );
+        // telnet 把回车发送为 `\r\n` 或 `\r\0`，只算一次 Enter。
+        assert_eq!(
+            keys(&mut parser, b"\r\n\r\0"),
+            [plain(KeyCode::Enter), plain(KeyCode::Enter)]
+        );
+    }
+
+    #[test]
+    fn escape_sequences() {
+        let mut parser = InputParser::default();
+        assert_eq!(
+            keys(
+                &mut parser,
+                b"\x1b[C\x1bOD\x1b[5~\x1b[1;5A\x1b[Z\x1bx\x1b[15~"
+            ),
+            [
+                plain(KeyCode::Right),
+                plain(KeyCode::Left),
+                plain(KeyCode::PageUp),
+                KeyEvent::new(KeyCode::Up, KeyModifiers::CONTROL),
+                KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT),
+                KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT),
+                plain(KeyCode::F(5)),
+            ]
+        );
+        assert_eq!(keys(&mut parser, b"\x1b"), [plain(KeyCode::Esc)]);
+        // 无法识别的序列被丢弃。
+        assert_eq!(keys(&mut parser, b"\x1b[99~a"), [plain(KeyCode::Char('a'))]);
+    }
+
+    #[test]
+    fn split_across_reads() {
+        let mut parser = InputParser::default();
+        assert!(keys(&mut parser, b"\x1b[1;").is_empty());
+        assert_eq!(
+            keys(&mut parser, b"2B"),
+            [KeyEvent::new(KeyCode::Down, KeyModifiers::SHIFT)]
+        );
+        let bytes = "世".as_bytes();
+        assert!(keys(&mut parser, &bytes[..1]).is_empty());
+        assert_eq!(keys(&mut parser, &bytes[1..]), [plain(KeyCode::Char('世'))]);
+    }
+}
@@ -0,0 +1,217 @@
+//! 调试布局用的检查器：打开后在界面上标出每个部件的区域和名称，鼠标移动时显示所指单元格的坐标。
+//!
+//! 绘制时各部件用 [`Inspector::record`] 登记自己的区域，检查器关闭时什么也不做。整个界面画完后
+//! 调用 [`Inspector::render`] 把标记叠加在上面：区域的边上换成醒目的背景色，左上角是名称和
+//! `宽x高+x+y`。`record` 只需要共享引用，可以在 `impl Widget for &App` 这样不修改状态的绘制代码中
+//! 调用。
+//!
+//! 鼠标事件需要终端打开鼠标捕获，应用程序可以只在检查器打开时捕获，平时不影响选择文字。
+
+use std::cell::RefCell;
+
+use crossterm::event::{MouseEvent, MouseEventKind};
+use ratatui::prelude::*;
+
+/// 区域标记依次使用的颜色，相邻的区域容易区分。
+const COLORS: [Color; 6] = [
+    Color::Magenta,
+    Color::Cyan,
+    Color::Yellow,
+    Color::Green,
+    Color::Red,
+    Color::Blue,
+];
+
+/// 登记的一个区域。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub label: String,
+    pub area: Rect,
+}
+
+#[derive(Debug, Default)]
+pub struct Inspector {
+    enabled: bool,
+    /// 这一帧登记的区域，按绘制的顺序，后面的画在前面的上层。
+    regions: RefCell<Vec<Region>>,
+    /// 鼠标所在的单元格。
+    hover: Option<(u16, u16)>,
+}
+
+impl Inspector {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.regions.get_mut().clear();
+        self.hover = None;
+    }
+
+    /// 开始绘制新的一帧，忘掉上一帧登记的区域。
+    pub fn begin_frame(&self) {
+        self.regions.borrow_mut().clear();
+    }
+
+    /// 登记名为 `label` 的部件画在 `area` 中。
+    pub fn record(&self, label: impl Into<String>, area: Rect) {
+        if self.enabled && !area.is_empty() {
+            self.regions.borrow_mut().push(Region {
+                label: label.into(),
+                area,
+            });
+        }
+    }
+
+    pub fn regions(&self) -> Vec<Region> {
+        self.regions.borrow().clone()
+    }
+
+    /// 记下鼠标的位置。检查器打开时所有鼠标事件都由它处理，返回 `true`。
+    pub fn handle_mouse(&mut self, event: &MouseEvent) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if matches!(
+            event.kind,
+            MouseEventKind::Moved | MouseEventKind::Drag(_) | MouseEventKind::Down(_)
+        ) {
+            self.hover = Some((event.column, event.row));
+        }
+        true
+    }
+
+    /// 包含鼠标所在单元格的最上层区域。
+    fn hovered(&self, (x, y): (u16, u16)) -> Option<Region> {
+        self.regions
+            .borrow()
+            .iter()
+            .rev()
+            .find(|region| {
+                let area = region.area;
+                area.left() <= x && x < area.right() && area.top() <= y && y < area.bottom()
+            })
+            .cloned()
+    }
+
+    /// 在已经画好的界面上叠加区域的标记，以及右下角鼠标位置的坐标和所在的区域。
+    pub fn render(&self, buf: &mut Buffer) {
+        if !self.enabled {
+            return;
+        }
+        let bounds = buf.area;
+        for (index, region) in self.regions.borrow().iter().enumerate() {
+            let area = region.area.intersection(bounds);
+            if area.is_empty() {
+                continue;
+            }
+            let style = Style::new().bg(COLORS[index % COLORS.len()]);
+            for x in area.left()..area.right() {
+                buf.get_mut(x, area.top()).set_style(style);
+                buf.get_mut(x, area.bottom() - 1).set_style(style);
+            }
+            for y in area.top()..area.bottom() {
+                buf.get_mut(area.left(), y).set_style(style);
+                buf.get_mut(area.right() - 1, y).set_style(style);
+            }
+            let Rect {
+                x,
+                y,
+                width,
+                height,
+            } = region.area;
+            buf.set_stringn(
+                area.x,
+                area.y,
+                format!("{} {width}x{height}+{x}+{y}", region.label),
+                usize::from(area.width),
+                style.fg(Color::Black),
+            );
+        }
+
+        let Some((x, y)) = self.hover.filter(|&(x, y)| {
+            bounds.left() <= x && x < bounds.right() && bounds.top() <= y && y < bounds.bottom()
+        }) else {
+            return;
+        };
+        buf.get_mut(x, y)
+            .set_style(Style::new().add_modifier(Modifier::REVERSED));
+        let mut text = format!(" {x},{y} ");
+        if let Some(region) = self.hovered((x, y)) {
+            text.push_str(&format!("{} ", region.label));
+        }
+        let width = (text.len() as u16).min(bounds.width);
+        buf.set_stringn(
+            bounds.right() - width,
+            bounds.bottom() - 1,
+            text,
+            usize::from(width),
+            Style::new().black().on_white(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyModifiers, MouseButton};
+
+    use super::*;
+    use crate::testing::buffer_rows;
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn record_only_when_enabled() {
+        let mut inspector = Inspector::default();
+        inspector.record("main", Rect::new(0, 0, 4, 2));
+        assert!(inspector.regions().is_empty());
+        assert!(!inspector.handle_mouse(&mouse(MouseEventKind::Moved, 1, 1)));
+
+        inspector.toggle();
+        inspector.record("main", Rect::new(0, 0, 4, 2));
+        inspector.record("empty", Rect::new(0, 0, 0, 2));
+        assert_eq!(
+            inspector.regions(),
+            [Region {
+                label: "main".into(),
+                area: Rect::new(0, 0, 4, 2)
+            }]
+        );
+        inspector.begin_frame();
+        assert!(inspector.regions().is_empty());
+    }
+
+    #[test]
+    fn outline_regions_and_hovered_cell() {
+        let mut inspector = Inspector::default();
+        inspector.toggle();
+        inspector.record("main", Rect::new(0, 0, 20, 4));
+        inspector.record("value", Rect::new(2, 1, 6, 2));
+        assert!(inspector.handle_mouse(&mouse(MouseEventKind::Moved, 3, 2)));
+        assert!(inspector.handle_mouse(&mouse(MouseEventKind::Up(MouseButton::Left), 0, 0)));
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 20, 4));
+        inspector.render(&mut buf);
+        assert_eq!(
+            buffer_rows(&buf),
+            [
+                "main 20x4+0+0       ",
+                "  value             ",
+                "                    ",
+                "          3,2 value ",
+            ]
+        );
+        assert_eq!(buf.get(19, 1).bg, Color::Magenta);
+        assert_eq!(buf.get(7, 2).bg, Color::Cyan);
+        assert_eq!(buf.get(10, 2).bg, Color::Reset);
+        assert!(buf.get(3, 2).modifier.contains(Modifier::REVERSED));
+    }
+}
@@ -0,0 +1,33 @@
+//! 布局：在区域中放置弹出窗口之类的矩形。
+
+use ratatui::layout::Rect;
+
+/// `area` 中间的矩形，不超过 `area`。
+pub fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn centered_in_area() {
+        assert_eq!(
+            centered(Rect::new(2, 1, 10, 5), 4, 3),
+            Rect::new(5, 2, 4, 3)
+        );
+        // 比 `area` 大的时候缩小到 `area`。
+        assert_eq!(
+            centered(Rect::new(1, 1, 3, 2), 10, 10),
+            Rect::new(1, 1, 3, 2)
+        );
+    }
+}
@@ -0,0 +1,25 @@
+//! 工作区中各个演示程序共享的代码。
+
+pub mod animation;
+pub mod capabilities;
+pub mod clipboard;
+pub mod console;
+pub mod countdown;
+pub mod editor;
+pub mod events;
+pub mod files;
+pub mod input;
+pub mod inspector;
+pub mod layout;
+pub mod motion;
+pub mod plugin;
+pub mod qr;
+pub mod recording;
+pub mod terminal;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod text_input;
+pub mod time_travel;
+pub mod toast;
+pub mod tree;
+pub mod wizard;
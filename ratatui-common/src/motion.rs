@@ -0,0 +1,37 @@
+//! 动画（动态效果）设置。
+//!
+//! 工作区中所有带动画的功能（闪烁、跑马灯、过渡效果等）都必须通过 [`Motion`] 决定是否播放动画。
+//! 启用“减少动态效果”后，动画被替换为立即切换到最终状态。
+
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Motion {
+    /// 为 `true` 时禁用所有动画。
+    pub reduced: bool,
+}
+
+impl Motion {
+    pub const fn reduced() -> Self {
+        Self { reduced: true }
+    }
+
+    /// 返回一段动画应播放的时长。
+    ///
+    /// 减少动态效果时返回 `None`，调用方应直接切换到动画的最终状态。
+    pub fn animate(self, duration: Duration) -> Option<Duration> {
+        (!self.reduced).then_some(duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reduced_motion_skips_animations() {
+        let duration = Duration::from_millis(200);
+        assert_eq!(Motion::default().animate(duration), Some(duration));
+        assert_eq!(Motion::reduced().animate(duration), None);
+    }
+}
@@ -0,0 +1,59 @@
+//! 动态插件接口。
+//!
+//! 插件是一个共享库（`cdylib`），导出 API 版本号和一个创建函数，由宿主程序在启动时通过
+//! `libloading` 加载。每个插件提供一个面板：宿主负责边框和标题，插件在内部区域中绘制，
+//! 并接收面板显示期间的终端事件。
+//!
+//! 插件和宿主之间直接传递 Rust 类型（trait 对象、`Buffer`、crossterm 事件），
+//! 因此插件必须使用相同版本的编译器以及相同版本的 `ratatui` 和 `crossterm` 构建。
+//! 宿主会先检查 [`API_VERSION`]，不一致时拒绝加载。
+//!
+//! 插件用 [`declare_plugin!`](crate::declare_plugin) 导出入口：
+//!
+//! ```ignore
+//! ratatui_common::declare_plugin!(MyPane::default());
+//! ```
+
+use crossterm::event::Event;
+use ratatui::{buffer::Buffer, layout::Rect};
+
+/// 插件接口的版本，接口有不兼容的变化时递增。
+pub const API_VERSION: u32 = 1;
+
+/// 插件导出的 API 版本号的符号名。
+pub const VERSION_SYMBOL: &[u8] = b"RATATUI_PLUGIN_API_VERSION\0";
+
+/// 插件导出的创建函数的符号名。
+pub const CREATE_SYMBOL: &[u8] = b"ratatui_plugin_create\0";
+
+/// 插件创建函数的类型。返回的指针由宿主通过 `Box::from_raw` 取得所有权。
+pub type CreateFn = unsafe extern "C" fn() -> *mut Box<dyn Plugin>;
+
+/// 插件提供的面板。
+pub trait Plugin {
+    /// 面板的名称，显示在面板的标题中。
+    fn name(&self) -> &str;
+
+    /// 在 `area` 中绘制面板内容。
+    fn render(&self, area: Rect, buf: &mut Buffer);
+
+    /// 处理面板显示期间的终端事件。返回 `true` 表示事件已被处理，宿主不再按自己的按键绑定处理。
+    fn handle_event(&mut self, _event: &Event) -> bool {
+        false
+    }
+}
+
+/// 导出插件入口：API 版本号和创建函数。参数是创建插件的表达式。
+#[macro_export]
+macro_rules! declare_plugin {
+    ($constructor:expr) => {
+        #[no_mangle]
+        pub static RATATUI_PLUGIN_API_VERSION: u32 = $crate::plugin::API_VERSION;
+
+        #[no_mangle]
+        pub extern "C" fn ratatui_plugin_create() -> *mut Box<dyn $crate::plugin::Plugin> {
+            let plugin: Box<dyn $crate::plugin::Plugin> = Box::new($constructor);
+            Box::into_raw(Box::new(plugin))
+        }
+    };
+}
@@ -0,0 +1,144 @@
+//! 二维码控件：每个单元格用半块字符显示上下两个模块，所以模块接近正方形。
+//!
+//! 控件选择能放进区域的最大整数倍数并居中显示，四周留出静区。深色模块用黑色，
+//! 浅色用白色，在深色背景的终端中也能扫描。
+
+use qrcode::types::Color as Module;
+pub use qrcode::{types::QrError, EcLevel};
+use ratatui::prelude::*;
+
+/// 四周静区的模块数。标准要求 4 个，终端里空间有限，2 个通常也能扫描。
+pub const QUIET_ZONE: usize = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QrCode {
+    /// 逐行排列，`true` 是深色模块。
+    modules: Vec<bool>,
+    width: usize,
+    version: i16,
+    level: EcLevel,
+}
+
+impl QrCode {
+    /// 用能放下数据的最小版本编码。
+    pub fn new(data: impl AsRef<[u8]>, level: EcLevel) -> Result<Self, QrError> {
+        let code = qrcode::QrCode::with_error_correction_level(data, level)?;
+        let version = match code.version() {
+            qrcode::Version::Normal(version) | qrcode::Version::Micro(version) => version,
+        };
+        Ok(Self {
+            width: code.width(),
+            modules: code
+                .to_colors()
+                .into_iter()
+                .map(|module| module == Module::Dark)
+                .collect(),
+            version,
+            level,
+        })
+    }
+
+    pub fn version(&self) -> i16 {
+        self.version
+    }
+
+    pub fn level(&self) -> EcLevel {
+        self.level
+    }
+
+    /// 每边的模块数，不包括静区。
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// 包括静区的坐标，静区是浅色的。
+    fn dark(&self, x: usize, y: usize) -> bool {
+        let (Some(x), Some(y)) = (x.checked_sub(QUIET_ZONE), y.checked_sub(QUIET_ZONE)) else {
+            return false;
+        };
+        x < self.width && y < self.width && self.modules[y * self.width + x]
+    }
+
+    /// 包括静区的每边模块数。
+    fn total(&self) -> u16 {
+        (self.width + 2 * QUIET_ZONE) as u16
+    }
+
+    /// 在 `area` 中能使用的最大放大倍数，放不下时为 0。
+    pub fn scale(&self, area: Rect) -> u16 {
+        (area.width / self.total()).min(area.height * 2 / self.total())
+    }
+
+    /// 放大 `scale` 倍时占用的列数和行数。
+    pub fn size(&self, scale: u16) -> (u16, u16) {
+        let n = self.total() * scale;
+        (n, n.div_ceil(2))
+    }
+}
+
+impl Widget for &QrCode {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let scale = self.scale(area);
+        if scale == 0 {
+            return;
+        }
+        let (width, height) = self.size(scale);
+        let x0 = area.x + (area.width - width) / 2;
+        let y0 = area.y + (area.height - height) / 2;
+        let scale = usize::from(scale);
+        let style = Style::new().fg(Color::Black).bg(Color::White);
+        for row in 0..height {
+            let top = usize::from(row) * 2 / scale;
+            let bottom = (usize::from(row) * 2 + 1) / scale;
+            for col in 0..width {
+                let x = usize::from(col) / scale;
+                let symbol = match (self.dark(x, top), self.dark(x, bottom)) {
+                    (true, true) => "█",
+                    (true, false) => "▀",
+                    (false, true) => "▄",
+                    (false, false) => " ",
+                };
+                buf.get_mut(x0 + col, y0 + row)
+                    .set_symbol(symbol)
+                    .set_style(style);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::buffer_rows;
+
+    #[test]
+    fn encode() {
+        let code = QrCode::new("hello", EcLevel::M).unwrap();
+        assert_eq!((code.version(), code.width()), (1, 21));
+        assert!(QrCode::new("x".repeat(3000), EcLevel::H).is_err());
+    }
+
+    #[test]
+    fn scale_to_area() {
+        let code = QrCode::new("hello", EcLevel::M).unwrap();
+        assert_eq!(code.scale(Rect::new(0, 0, 80, 24)), 1);
+        assert_eq!(code.scale(Rect::new(0, 0, 80, 25)), 2);
+        assert_eq!(code.scale(Rect::new(0, 0, 24, 24)), 0);
+        assert_eq!(code.size(2), (50, 25));
+    }
+
+    #[test]
+    fn render_half_blocks() {
+        let code = QrCode::new("hello", EcLevel::M).unwrap();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 27, 13));
+        code.render(buf.area, &mut buf);
+        let rows = buffer_rows(&buf);
+        // 居中后左边空出一列。第一行是静区，第二行是定位图案的上边。
+        assert_eq!(&rows[0][..3], "   ");
+        assert!(rows[1].starts_with("   █▀▀▀▀▀█ "));
+        assert!(rows[1].ends_with(" █▀▀▀▀▀█   "));
+        assert!(rows[4].starts_with("   ▀▀▀▀▀▀▀ "));
+        assert_eq!(buf.get(0, 0).bg, Color::Reset);
+        assert_eq!(buf.get(1, 0).bg, Color::White);
+    }
+}
@@ -0,0 +1,351 @@
+//! 事件的录制和回放，用于复现错误报告和演示播放。
+//!
+//! 录制文件每行是一个 JSON 对象，记录事件相对于开始时间的偏移（毫秒）和事件本身：
+//!
+//! ```json
+//! { "at_ms": 0, "event": { "Key": { "code": "Right", "modifiers": "", "kind": "Press", "state": "" } } }
+//! { "at_ms": 500, "event": { "Key": { "code": { "Char": "q" }, "modifiers": "", "kind": "Press", "state": "" } } }
+//! ```
+//!
+//! 每个事件写入后立即刷新，程序崩溃时录制文件也是完整的。回放时也接受旧版本使用的 JSON 数组格式。
+
+use std::{
+    collections::VecDeque,
+    fmt, fs,
+    io::{self, Write},
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::events::EventSource;
+
+/// 录制文件中的一条记录。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEvent<E> {
+    /// 相对于开始录制的偏移，单位为毫秒。
+    pub at_ms: u64,
+    pub event: E,
+}
+
+/// 把 `source` 产生的每个事件连同时间戳写入 `out`，再原样交给调用者。
+#[derive(Debug)]
+pub struct Recorder<S, W> {
+    source: S,
+    out: W,
+    start: Instant,
+}
+
+impl<S, W: Write> Recorder<S, W> {
+    pub fn new(source: S, out: W) -> Self {
+        Self {
+            source,
+            out,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl<S> Recorder<S, fs::File> {
+    /// 录制到文件 `path`，文件已经存在时覆盖。
+    pub fn create(source: S, path: &Path) -> io::Result<Self> {
+        Ok(Self::new(source, fs::File::create(path)?))
+    }
+}
+
+impl<E, S, W> EventSource<E> for Recorder<S, W>
+where
+    E: Serialize,
+    S: EventSource<E>,
+    W: Write,
+{
+    fn read(&mut self) -> io::Result<E> {
+        let event = self.source.read()?;
+        let record = RecordedEvent {
+            at_ms: self.start.elapsed().as_millis() as u64,
+            event: &event,
+        };
+        serde_json::to_writer(&mut self.out, &record)?;
+        self.out.write_all(b"\n")?;
+        self.out.flush()?;
+        Ok(event)
+    }
+
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        self.source.poll(timeout)
+    }
+}
+
+/// 按照录制时的时间间隔依次产生事件，可以加速或减速。
+///
+/// 录制的事件播放完毕后改为读取 `then` 设置的来源（例如真实终端），这样用户可以查看最终状态并正常退出；
+/// 没有设置时 `read` 返回 `UnexpectedEof` 错误。
+pub struct Player<E> {
+    events: VecDeque<RecordedEvent<E>>,
+    start: Option<Instant>,
+    /// 播放速度，2.0 表示两倍速。
+    speed: f64,
+    then: Option<Box<dyn EventSource<E> + Send>>,
+}
+
+impl<E: fmt::Debug> fmt::Debug for Player<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Player")
+            .field("events", &self.events)
+            .field("start", &self.start)
+            .field("speed", &self.speed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<E> Player<E> {
+    pub fn new(events: impl IntoIterator<Item = RecordedEvent<E>>) -> Self {
+        Self {
+            events: events.into_iter().collect(),
+            start: None,
+            speed: 1.0,
+            then: None,
+        }
+    }
+
+    /// 读取录制文件。
+    pub fn from_file(path: &Path) -> io::Result<Self>
+    where
+        E: DeserializeOwned,
+    {
+        let text = fs::read_to_string(path)?;
+        let invalid = |error: serde_json::Error, line: usize| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}:{line}: {error}", path.display()),
+            )
+        };
+        if text.trim_start().starts_with('[') {
+            let events: Vec<RecordedEvent<E>> = serde_json::from_str(&text).map_err(|error| {
+                let line = error.line();
+                invalid(error, line)
+            })?;
+            return Ok(Self::new(events));
+        }
+        let events = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|error| invalid(error, index + 1))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Self::new(events))
+    }
+
+    /// 设置播放速度，必须是有限的正数。
+    pub fn speed(self, speed: f64) -> Self {
+        assert!(
+            speed > 0.0 && speed.is_finite(),
+            "playback speed must be positive"
+        );
+        Self { speed, ..self }
+    }
+
+    /// 播放完毕后改为读取 `source`。
+    pub fn then(self, source: impl EventSource<E> + Send + 'static) -> Self {
+        Self {
+            then: Some(Box::new(source)),
+            ..self
+        }
+    }
+
+    /// 距离下一个事件还要等待的时间。
+    fn next_wait(&mut self) -> Option<Duration> {
+        let at_ms = self.events.front()?.at_ms;
+        // 第一次读取时才开始计时，这样终端初始化的耗时不会压缩第一个事件之前的间隔。
+        let start = *self.start.get_or_insert_with(Instant::now);
+        // 速度极小时超出 `Duration` 的范围，按最长的时间等待。
+        let due = Duration::try_from_secs_f64(at_ms as f64 / 1000.0 / self.speed)
+            .unwrap_or(Duration::MAX);
+        Some(due.saturating_sub(start.elapsed()))
+    }
+}
+
+impl<E> EventSource<E> for Player<E> {
+    fn read(&mut self) -> io::Result<E> {
+        let Some(wait) = self.next_wait() else {
+            return match &mut self.then {
+                Some(source) => source.read(),
+                None => Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "all recorded events have been played",
+                )),
+            };
+        };
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+        Ok(self
+            .events
+            .pop_front()
+            .expect("next_wait saw an event")
+            .event)
+    }
+
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        let Some(wait) = self.next_wait() else {
+            return match &mut self.then {
+                Some(source) => source.poll(timeout),
+                None => {
+                    thread::sleep(timeout);
+                    Ok(false)
+                }
+            };
+        };
+        thread::sleep(wait.min(timeout));
+        Ok(wait <= timeout)
+    }
+}
+
+/// 解析命令行中的播放速度，只接受有限的正数。
+pub fn parse_speed(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(speed) if speed > 0.0 && speed.is_finite() => Ok(speed),
+        Ok(_) => Err("speed must be a positive number".into()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{Event, KeyCode};
+
+    use super::*;
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(code.into())
+    }
+
+    fn recorded(at_ms: u64, event: Event) -> RecordedEvent<Event> {
+        RecordedEvent { at_ms, event }
+    }
+
+    #[test]
+    fn parse_log() {
+        let dir = std::env::temp_dir().join(format!("ratatui-recording-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let lines = dir.join("lines.jsonl");
+        fs::write(
+            &lines,
+            r#"{ "at_ms": 0, "event": { "Key": { "code": "Right", "modifiers": "", "kind": "Press", "state": "" } } }
+
+{ "at_ms": 5, "event": { "Resize": [80, 24] } }"#,
+        )
+        .unwrap();
+        let array = dir.join("array.json");
+        fs::write(
+            &array,
+            r#"[{ "at_ms": 5, "event": { "Resize": [80, 24] } }]"#,
+        )
+        .unwrap();
+        let broken = dir.join("broken.jsonl");
+        fs::write(
+            &broken,
+            "{ \"at_ms\": 0, \"event\": \"FocusGained\" }\nnope\n",
+        )
+        .unwrap();
+
+        let player = Player::<Event>::from_file(&lines).unwrap();
+        assert_eq!(
+            Vec::from(player.events),
+            [
+                recorded(0, key(KeyCode::Right)),
+                recorded(5, Event::Resize(80, 24))
+            ]
+        );
+        let player = Player::<Event>::from_file(&array).unwrap();
+        assert_eq!(player.events.len(), 1);
+        let error = Player::<Event>::from_file(&broken).unwrap_err();
+        assert!(error.to_string().contains("broken.jsonl:2"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn record_then_play() {
+        let source = Player::new([
+            recorded(0, key(KeyCode::Right)),
+            recorded(0, key(KeyCode::Left)),
+        ]);
+        let mut recorder = Recorder::new(source, Vec::new());
+        assert_eq!(recorder.read().unwrap(), key(KeyCode::Right));
+        assert_eq!(recorder.read().unwrap(), key(KeyCode::Left));
+
+        let log = String::from_utf8(recorder.out).unwrap();
+        assert_eq!(log.lines().count(), 2);
+        let mut player = Player::new(
+            log.lines()
+                .map(|line| serde_json::from_str::<RecordedEvent<Event>>(line).unwrap()),
+        );
+        assert_eq!(player.read().unwrap(), key(KeyCode::Right));
+        assert_eq!(player.read().unwrap(), key(KeyCode::Left));
+        assert_eq!(
+            player.read().unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn play_in_order() {
+        let mut player = Player::new([
+            recorded(0, key(KeyCode::Right)),
+            recorded(10, key(KeyCode::Left)),
+        ]);
+        let start = Instant::now();
+        assert_eq!(player.read().unwrap(), key(KeyCode::Right));
+        assert_eq!(player.read().unwrap(), key(KeyCode::Left));
+        assert!(start.elapsed() >= Duration::from_millis(10));
+    }
+
+    #[test]
+    fn accelerated_playback() {
+        let mut player = Player::new([recorded(400, key(KeyCode::Right))]).speed(4.0);
+        let start = Instant::now();
+        assert_eq!(player.read().unwrap(), key(KeyCode::Right));
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(100));
+        assert!(elapsed < Duration::from_millis(400));
+    }
+
+    #[test]
+    fn parse_playback_speed() {
+        assert_eq!(parse_speed("0.5"), Ok(0.5));
+        assert!(parse_speed("0").is_err());
+        assert!(parse_speed("inf").is_err());
+        assert!(parse_speed("fast").is_err());
+
+        // 非常小的速度不会让计算等待时间时溢出。
+        let mut player = Player::new([
+            recorded(0, key(KeyCode::Right)),
+            recorded(400, key(KeyCode::Left)),
+        ])
+        .speed(parse_speed("1e-310").unwrap());
+        assert!(player.poll(Duration::ZERO).unwrap());
+        assert_eq!(player.read().unwrap(), key(KeyCode::Right));
+        assert!(!player.poll(Duration::from_millis(1)).unwrap());
+    }
+
+    #[test]
+    fn poll_waits_for_due_event() {
+        let mut player = Player::new([recorded(50, key(KeyCode::Right))]);
+        assert!(!player.poll(Duration::from_millis(10)).unwrap());
+        assert!(player.poll(Duration::from_millis(100)).unwrap());
+        assert_eq!(player.read().unwrap(), key(KeyCode::Right));
+    }
+
+    #[test]
+    fn continue_with_fallback() {
+        let mut player = Player::new([recorded(0, key(KeyCode::Right))])
+            .then(Player::new([recorded(0, key(KeyCode::Char('q')))]));
+        assert_eq!(player.read().unwrap(), key(KeyCode::Right));
+        assert!(player.poll(Duration::ZERO).unwrap());
+        assert_eq!(player.read().unwrap(), key(KeyCode::Char('q')));
+    }
+}
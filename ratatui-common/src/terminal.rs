@@ -0,0 +1,53 @@
+//! 演示程序共用的终端初始化和恢复：进入备用屏幕和原始模式，退出或恐慌时恢复。
+//!
+//! 需要备用屏幕之外的功能（例如只在主屏幕中绘制）的程序仍然可以自己管理终端，计数器演示就是这样。
+
+use std::{
+    io::{self, stdout, Stdout},
+    panic,
+};
+
+use crossterm::{
+    cursor,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::prelude::*;
+
+/// 绘制到标准输出的终端。
+pub type Tui = Terminal<CrosstermBackend<Stdout>>;
+
+/// 进入备用屏幕并启用原始模式。
+pub fn init() -> io::Result<Tui> {
+    execute!(stdout(), EnterAlternateScreen)?;
+    enable_raw_mode()?;
+    Terminal::new(CrosstermBackend::new(stdout()))
+}
+
+/// 与 [`init`] 相同，另外接收鼠标事件。
+pub fn init_with_mouse() -> io::Result<Tui> {
+    let terminal = init()?;
+    execute!(stdout(), EnableMouseCapture)?;
+    Ok(terminal)
+}
+
+/// 恢复终端。没有调用过 `init` 时也可以调用。
+pub fn restore() -> io::Result<()> {
+    execute!(
+        stdout(),
+        DisableMouseCapture,
+        LeaveAlternateScreen,
+        cursor::Show
+    )?;
+    disable_raw_mode()
+}
+
+/// 在已经安装的恐慌钩子（例如 color_eyre 的钩子）之前先恢复终端，否则恐慌信息会打印在备用屏幕中。
+pub fn install_panic_hook() {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore();
+        hook(info);
+    }));
+}
@@ -0,0 +1,61 @@
+//! 测试用的辅助函数：把绘制结果转换为文字，向应用程序依次输入按键。
+//!
+//! 各个演示程序的测试都用这些函数检查界面，不必各自复制一份。
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{buffer::Buffer, layout::Rect};
+
+/// 缓冲区中每一行的文字，不去掉末尾的空格。
+pub fn buffer_rows(buf: &Buffer) -> Vec<String> {
+    let area = buf.area;
+    (area.top()..area.bottom())
+        .map(|y| {
+            (area.left()..area.right())
+                .map(|x| buf.get(x, y).symbol())
+                .collect()
+        })
+        .collect()
+}
+
+/// 在 `width`×`height` 的空缓冲区中调用 `render`，返回每一行的文字。
+pub fn render_rows(width: u16, height: u16, render: impl FnOnce(Rect, &mut Buffer)) -> Vec<String> {
+    let mut buf = Buffer::empty(Rect::new(0, 0, width, height));
+    render(buf.area, &mut buf);
+    buffer_rows(&buf)
+}
+
+/// 依次按下 `codes` 中的按键，交给 `handle_key` 处理。
+pub fn press(codes: &[KeyCode], mut handle_key: impl FnMut(KeyEvent)) {
+    for &code in codes {
+        handle_key(code.into());
+    }
+}
+
+/// 逐个字符输入 `text`，交给 `handle_key` 处理。
+pub fn type_text(text: &str, mut handle_key: impl FnMut(KeyEvent)) {
+    for c in text.chars() {
+        handle_key(KeyCode::Char(c).into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{style::Style, text::Line, widgets::Widget};
+
+    use super::*;
+
+    #[test]
+    fn rows_and_keys() {
+        let rows = render_rows(4, 2, |area, buf| Line::from("ab").render(area, buf));
+        assert_eq!(rows, ["ab  ", "    "]);
+
+        let mut buf = Buffer::empty(Rect::new(2, 1, 2, 1));
+        buf.set_string(2, 1, "cd", Style::default());
+        assert_eq!(buffer_rows(&buf), ["cd"]);
+
+        let mut codes = Vec::new();
+        press(&[KeyCode::Up, KeyCode::Down], |key| codes.push(key.code));
+        type_text("x", |key| codes.push(key.code));
+        assert_eq!(codes, [KeyCode::Up, KeyCode::Down, KeyCode::Char('x')]);
+    }
+}
@@ -0,0 +1,211 @@
+//! 单行文本输入框，各个演示程序的表单共用。
+//!
+//! 光标以字符为单位移动，不会停在双宽字符的中间，所以中日韩文字也能正确编辑。
+//! [`History`] 为输入框提供 shell 那样用上下键浏览的历史记录。
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Input {
+    value: String,
+    /// 光标之前的字符数。
+    cursor: usize,
+}
+
+impl Input {
+    /// 带有初始内容的输入框，光标在末尾。
+    pub fn with_value(value: impl Into<String>) -> Self {
+        let value = value.into();
+        Self {
+            cursor: value.chars().count(),
+            value,
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// 处理编辑按键，返回该按键是否被输入框使用。
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> bool {
+        match key_event.code {
+            KeyCode::Char(c) => {
+                let at = self.byte_index();
+                self.value.insert(at, c);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                let at = self.byte_index();
+                self.value.remove(at);
+            }
+            KeyCode::Delete if self.cursor < self.value.chars().count() => {
+                let at = self.byte_index();
+                self.value.remove(at);
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.value.chars().count()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.value.chars().count(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn byte_index(&self) -> usize {
+        self.value
+            .char_indices()
+            .nth(self.cursor)
+            .map_or(self.value.len(), |(at, _)| at)
+    }
+
+    /// 以 `prompt` 开头的一行，光标所在的单元格反色显示。
+    pub fn line<'a>(&'a self, prompt: &'a str, style: Style) -> Line<'a> {
+        let at = self.byte_index();
+        let (before, after) = self.value.split_at(at);
+        let mut chars = after.chars();
+        let under_cursor = chars
+            .next()
+            .map_or_else(|| " ".to_string(), |c| c.to_string());
+        Line::from(vec![
+            prompt.set_style(style),
+            before.into(),
+            under_cursor.reversed(),
+            chars.as_str().into(),
+        ])
+    }
+
+    /// 与 [`Input::line`] 相同，但每个字符都显示为 `mask`，用于密码。
+    pub fn masked_line<'a>(&self, prompt: &'a str, style: Style, mask: char) -> Line<'a> {
+        let len = self.value.chars().count();
+        let under_cursor = if self.cursor < len { mask } else { ' ' };
+        let after = len.saturating_sub(self.cursor + 1);
+        Line::from(vec![
+            prompt.set_style(style),
+            mask.to_string().repeat(self.cursor).into(),
+            under_cursor.to_string().reversed(),
+            mask.to_string().repeat(after).into(),
+        ])
+    }
+}
+
+/// 输入历史。浏览时保存正在编辑的内容，回到最新处时恢复。
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct History {
+    entries: Vec<String>,
+    /// 正在浏览的条目，`None` 表示没有浏览。
+    position: Option<usize>,
+    draft: String,
+}
+
+impl History {
+    /// 记录一条输入，并结束浏览。空输入和与上一条相同的输入不记录。
+    pub fn push(&mut self, entry: &str) {
+        self.position = None;
+        if !entry.is_empty() && self.entries.last().map(String::as_str) != Some(entry) {
+            self.entries.push(entry.to_string());
+        }
+    }
+
+    /// 上一条（更早的）记录。`current` 是输入框中的内容，开始浏览时保存下来。
+    pub fn older(&mut self, current: &str) -> Option<&str> {
+        let position = match self.position {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len() - 1
+            }
+            Some(position) => position.saturating_sub(1),
+        };
+        self.position = Some(position);
+        Some(&self.entries[position])
+    }
+
+    /// 下一条（更新的）记录，越过最新的一条时返回开始浏览前的内容。
+    pub fn newer(&mut self) -> Option<&str> {
+        let position = self.position?;
+        if position + 1 < self.entries.len() {
+            self.position = Some(position + 1);
+            Some(&self.entries[position + 1])
+        } else {
+            self.position = None;
+            Some(&self.draft)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(text: &str) -> Input {
+        let mut input = Input::default();
+        for c in text.chars() {
+            input.handle_key_event(KeyCode::Char(c).into());
+        }
+        input
+    }
+
+    #[test]
+    fn edit() {
+        let mut input = typed("1+3");
+        input.handle_key_event(KeyCode::Left.into());
+        input.handle_key_event(KeyCode::Backspace.into());
+        input.handle_key_event(KeyCode::Char('*').into());
+        assert_eq!(input.value(), "1*3");
+
+        input.handle_key_event(KeyCode::Home.into());
+        input.handle_key_event(KeyCode::Delete.into());
+        assert_eq!(input.value(), "*3");
+        assert!(!input.handle_key_event(KeyCode::Enter.into()));
+
+        let mut input = Input::with_value("12");
+        input.handle_key_event(KeyCode::Char('3').into());
+        assert_eq!(input.value(), "123");
+    }
+
+    #[test]
+    fn wide_chars() {
+        let mut input = typed("计数");
+        input.handle_key_event(KeyCode::Left.into());
+        input.handle_key_event(KeyCode::Backspace.into());
+        assert_eq!(input.value(), "数");
+        assert_eq!(input.line("> ", Style::new()).spans[2].content, "数");
+    }
+
+    #[test]
+    fn masked() {
+        let mut input = typed("密码12");
+        input.handle_key_event(KeyCode::Left.into());
+        let line = input.masked_line("> ", Style::new(), '*');
+        assert_eq!(line.to_string(), "> ****");
+        assert_eq!(line.spans[2].content, "*");
+        input.handle_key_event(KeyCode::End.into());
+        assert_eq!(
+            input.masked_line("", Style::new(), '*').to_string(),
+            "**** "
+        );
+    }
+
+    #[test]
+    fn history() {
+        let mut history = History::default();
+        assert_eq!(history.older("draft"), None);
+        history.push("one");
+        history.push("two");
+        history.push("two");
+        history.push("");
+
+        assert_eq!(history.older("draft"), Some("two"));
+        assert_eq!(history.older("two"), Some("one"));
+        assert_eq!(history.older("one"), Some("one"));
+        assert_eq!(history.newer(), Some("two"));
+        assert_eq!(history.newer(), Some("draft"));
+        assert_eq!(history.newer(), None);
+
+        history.older("");
+        history.push("three");
+        assert_eq!(history.older(""), Some("three"));
+    }
+}
@@ -0,0 +1,159 @@
+//! 时间旅行调试：记录每一条消息和处理后的状态，调试时可以一步一步地前后查看，找出应用程序是
+//! 怎样进入错误状态的。
+//!
+//! 应用程序每处理一条消息就调用 [`TimeTravel::record`]。[`TimeTravel::start`] 进入查看模式，停在
+//! 最新的一步上，之后用 [`TimeTravel::back`] 和 [`TimeTravel::forward`] 移动，绘制时用
+//! [`TimeTravel::current`] 中的状态代替真实的状态。查看模式下应用程序不处理其他输入，也就不会
+//! 记录新的步骤；[`TimeTravel::stop`] 后回到真实的状态接着运行。
+
+use std::collections::VecDeque;
+
+/// 默认最多保留的步数，更早的丢弃。
+pub const CAPACITY: usize = 500;
+
+/// 一条消息和处理它之后的状态。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step<M, S> {
+    pub message: M,
+    pub state: S,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeTravel<M, S> {
+    steps: VecDeque<Step<M, S>>,
+    capacity: usize,
+    /// 查看模式下正在看的一步，没有在查看时为 `None`。
+    cursor: Option<usize>,
+}
+
+impl<M, S> Default for TimeTravel<M, S> {
+    fn default() -> Self {
+        Self::new(CAPACITY)
+    }
+}
+
+impl<M, S> TimeTravel<M, S> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            steps: VecDeque::new(),
+            capacity: capacity.max(1),
+            cursor: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn record(&mut self, message: M, state: S) {
+        if self.steps.len() == self.capacity {
+            self.steps.pop_front();
+            self.cursor = self.cursor.map(|cursor| cursor.saturating_sub(1));
+        }
+        self.steps.push_back(Step { message, state });
+    }
+
+    pub fn is_traveling(&self) -> bool {
+        self.cursor.is_some()
+    }
+
+    /// 进入查看模式，停在最新的一步上。还没有记录时返回 `false`。
+    pub fn start(&mut self) -> bool {
+        self.cursor = self.steps.len().checked_sub(1);
+        self.cursor.is_some()
+    }
+
+    pub fn stop(&mut self) {
+        self.cursor = None;
+    }
+
+    /// 后退一步，已经在最早一步时不动。
+    pub fn back(&mut self) {
+        if let Some(cursor) = &mut self.cursor {
+            *cursor = cursor.saturating_sub(1);
+        }
+    }
+
+    /// 前进一步，已经在最新一步时不动。
+    pub fn forward(&mut self) {
+        if let Some(cursor) = &mut self.cursor {
+            *cursor = (*cursor + 1).min(self.steps.len() - 1);
+        }
+    }
+
+    pub fn first(&mut self) {
+        if let Some(cursor) = &mut self.cursor {
+            *cursor = 0;
+        }
+    }
+
+    pub fn last(&mut self) {
+        if let Some(cursor) = &mut self.cursor {
+            *cursor = self.steps.len() - 1;
+        }
+    }
+
+    /// 查看模式下正在看的一步。
+    pub fn current(&self) -> Option<&Step<M, S>> {
+        self.steps.get(self.cursor?)
+    }
+
+    /// 正在看的是第几步（从 1 开始）和一共有几步。
+    pub fn position(&self) -> Option<(usize, usize)> {
+        Some((self.cursor? + 1, self.steps.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn step_through_states() {
+        let mut travel = TimeTravel::new(10);
+        assert!(!travel.start());
+        for (message, state) in [("start", 0), ("increment", 1), ("increment", 2)] {
+            travel.record(message, state);
+        }
+        assert!(travel.start());
+        assert_eq!(travel.position(), Some((3, 3)));
+        assert_eq!(travel.current().map(|step| step.state), Some(2));
+        travel.forward();
+        assert_eq!(travel.position(), Some((3, 3)));
+        travel.back();
+        travel.back();
+        travel.back();
+        assert_eq!(
+            travel.current(),
+            Some(&Step {
+                message: "start",
+                state: 0
+            })
+        );
+        travel.last();
+        assert_eq!(travel.position(), Some((3, 3)));
+        travel.first();
+        assert_eq!(travel.position(), Some((1, 3)));
+
+        travel.stop();
+        assert!(!travel.is_traveling());
+        assert_eq!(travel.current(), None);
+    }
+
+    #[test]
+    fn drop_the_oldest_steps() {
+        let mut travel = TimeTravel::new(2);
+        travel.record("a", 1);
+        travel.record("b", 2);
+        travel.start();
+        travel.record("c", 3);
+        // 正在看的一步没有被丢弃时仍然停在它上面。
+        assert_eq!(travel.current().map(|step| step.message), Some("b"));
+        assert_eq!(travel.position(), Some((1, 2)));
+        assert_eq!(travel.len(), 2);
+    }
+}
@@ -0,0 +1,327 @@
+//! 提示消息（toast）：在右下角叠放显示一会儿就自动消失的消息。
+//!
+//! 消息按到达的顺序排队，同时最多显示 [`Toasts::max_visible`] 条，其余的等前面的消失后再显示，
+//! 所以每条消息都能显示完整的时长。时间由调用方传入：程序的主循环用 [`Toasts::next_deadline`]
+//! 决定最多等待多久，到时调用 [`Toasts::expire`] 后重绘。
+//!
+//! 设置了 [`Toasts::slide_in`] 时新消息从右边滑入，主循环在每一帧绘制前调用 [`Toasts::tick`]。
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Padding, Paragraph},
+};
+
+use crate::{
+    animation::{Animation, Easing},
+    motion::Motion,
+};
+
+/// 每条消息默认显示的时长。
+pub const LIFETIME: Duration = Duration::from_secs(4);
+
+/// 消息框最宽的列数，更长的消息截断。
+const MAX_WIDTH: u16 = 48;
+
+/// 每个消息框的高度：上下边框和一行文字。
+const HEIGHT: u16 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    fn title(self) -> &'static str {
+        match self {
+            Self::Info => " Info ",
+            Self::Warn => " Warning ",
+            Self::Error => " Error ",
+        }
+    }
+
+    fn style(self) -> Style {
+        match self {
+            Self::Info => Style::new().cyan(),
+            Self::Warn => Style::new().yellow(),
+            Self::Error => Style::new().red(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Toast {
+    pub level: Level,
+    pub text: String,
+    /// 开始显示的时刻，还在排队时为 `None`。
+    shown: Option<Instant>,
+    /// 滑入的进度，0 时完全在右边之外，1 时到达位置。
+    slide: Animation<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Toasts {
+    queue: VecDeque<Toast>,
+    lifetime: Duration,
+    max_visible: usize,
+    /// 滑入的时长，为零时直接出现。
+    slide_in: Duration,
+    motion: Motion,
+}
+
+impl Default for Toasts {
+    fn default() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            lifetime: LIFETIME,
+            max_visible: 3,
+            slide_in: Duration::ZERO,
+            motion: Motion::default(),
+        }
+    }
+}
+
+impl Toasts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn lifetime(mut self, lifetime: Duration) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    /// 同时最多显示的消息数，至少为一。
+    pub fn max_visible(mut self, max_visible: usize) -> Self {
+        self.max_visible = max_visible.max(1);
+        self
+    }
+
+    /// 新消息用 `duration` 从右边滑入，减少动态效果时直接出现。
+    pub fn slide_in(mut self, duration: Duration, motion: Motion) -> Self {
+        self.slide_in = duration;
+        self.motion = motion;
+        self
+    }
+
+    /// 动画设置改变后调用，之后显示的消息按新的设置滑入。
+    pub fn set_motion(&mut self, motion: Motion) {
+        self.motion = motion;
+    }
+
+    pub fn push(&mut self, level: Level, text: impl Into<String>, now: Instant) {
+        self.queue.push_back(Toast {
+            level,
+            text: text.into(),
+            shown: None,
+            slide: Animation::new(0.0, self.slide_in, Easing::EaseOut),
+        });
+        self.show(now);
+    }
+
+    pub fn info(&mut self, text: impl Into<String>, now: Instant) {
+        self.push(Level::Info, text, now);
+    }
+
+    pub fn warn(&mut self, text: impl Into<String>, now: Instant) {
+        self.push(Level::Warn, text, now);
+    }
+
+    pub fn error(&mut self, text: impl Into<String>, now: Instant) {
+        self.push(Level::Error, text, now);
+    }
+
+    /// 排队的消息有空位时开始显示。
+    fn show(&mut self, now: Instant) {
+        for toast in self.queue.iter_mut().take(self.max_visible) {
+            if toast.shown.is_none() {
+                toast.shown = Some(now);
+                toast.slide.animate_to(1.0, self.motion, now);
+            }
+        }
+    }
+
+    /// 推进滑入的动画，返回是否还有消息在滑入。
+    pub fn tick(&mut self, now: Instant) -> bool {
+        let mut running = false;
+        for toast in &mut self.queue {
+            running |= toast.slide.tick(now);
+        }
+        running
+    }
+
+    /// 有消息在滑入时下一帧的时刻。
+    pub fn next_frame(&self, now: Instant) -> Option<Instant> {
+        self.queue
+            .iter()
+            .filter_map(|toast| toast.slide.next_frame(now))
+            .min()
+    }
+
+    /// 去掉显示时间已到的消息，返回是否去掉了消息。
+    pub fn expire(&mut self, now: Instant) -> bool {
+        let lifetime = self.lifetime;
+        let before = self.queue.len();
+        self.queue
+            .retain(|toast| toast.shown.is_none_or(|shown| now < shown + lifetime));
+        self.show(now);
+        self.queue.len() != before
+    }
+
+    /// 最早的一条消息消失的时刻。
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.queue
+            .iter()
+            .filter_map(|toast| toast.shown)
+            .min()
+            .map(|shown| shown + self.lifetime)
+    }
+
+    /// 正在显示的消息，从旧到新。
+    pub fn visible(&self) -> impl Iterator<Item = &Toast> {
+        self.queue.iter().filter(|toast| toast.shown.is_some())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.queue.clear();
+    }
+}
+
+/// 在 `area` 的右下角从下往上叠放，最新的消息在最下面，与边缘隔开一格，不盖住边框上的按键提示。
+/// 正在滑入的消息先画在单独的缓冲区中，再把 `area` 内的部分平移过来。
+impl Widget for &Toasts {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let toasts: Vec<&Toast> = self.visible().collect();
+        if area.width < 3 || area.height == 0 {
+            return;
+        }
+        let mut bottom = area.bottom() - 1;
+        for toast in toasts.iter().rev() {
+            let Some(top) = bottom.checked_sub(HEIGHT).filter(|&top| top >= area.y) else {
+                break;
+            };
+            let text_width =
+                u16::try_from(Line::from(toast.text.as_str()).width()).unwrap_or(u16::MAX);
+            let width = text_width
+                .max(toast.level.title().len() as u16)
+                .saturating_add(4)
+                .min(MAX_WIDTH)
+                .min(area.width.saturating_sub(2));
+            let rect = Rect::new(area.right() - 1 - width, top, width, HEIGHT);
+            let mut toast_buf = Buffer::empty(rect);
+            Paragraph::new(toast.text.as_str())
+                .block(
+                    Block::bordered()
+                        .title(toast.level.title())
+                        .border_style(toast.level.style())
+                        .padding(Padding::horizontal(1)),
+                )
+                .render(rect, &mut toast_buf);
+            let offset = ((1.0 - toast.slide.value()) * f64::from(width + 1)).round() as u16;
+            for y in rect.top()..rect.bottom() {
+                for x in rect.left()..rect.right() {
+                    if x + offset < area.right() {
+                        *buf.get_mut(x + offset, y) = toast_buf.get(x, y).clone();
+                    }
+                }
+            }
+            bottom = top;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn rows(toasts: &Toasts, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| toasts.render(area, buf))
+    }
+
+    #[test]
+    fn queue_and_expire() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut toasts = Toasts::new().max_visible(2);
+        assert_eq!(toasts.next_deadline(), None);
+        toasts.info("Saved session work", start);
+        toasts.warn("Almost full", at(1));
+        toasts.error("Disk is full", at(2));
+        let texts = |toasts: &Toasts| {
+            toasts
+                .visible()
+                .map(|toast| toast.text.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts(&toasts), ["Saved session work", "Almost full"]);
+        assert_eq!(toasts.next_deadline(), Some(at(4)));
+
+        // 排队的消息在前面的消失后才开始计时。
+        assert!(!toasts.expire(at(3)));
+        assert!(toasts.expire(at(4)));
+        assert_eq!(texts(&toasts), ["Almost full", "Disk is full"]);
+        assert_eq!(toasts.next_deadline(), Some(at(5)));
+        toasts.expire(at(5));
+        assert_eq!(toasts.next_deadline(), Some(at(8)));
+        toasts.expire(at(8));
+        assert!(toasts.is_empty());
+    }
+
+    #[test]
+    fn stack_in_the_corner() {
+        let now = Instant::now();
+        let mut toasts = Toasts::new();
+        toasts.info("Saved", now);
+        toasts.error("Disk is full", now);
+        assert_eq!(
+            rows(&toasts, 24, 8),
+            [
+                "                        ",
+                "             ┌ Info ──┐ ",
+                "             │ Saved  │ ",
+                "             └────────┘ ",
+                "       ┌ Error ───────┐ ",
+                "       │ Disk is full │ ",
+                "       └──────────────┘ ",
+                "                        ",
+            ]
+        );
+        // 放不下的旧消息不显示。
+        assert_eq!(rows(&toasts, 24, 5)[1], "       ┌ Error ───────┐ ");
+    }
+
+    #[test]
+    fn slide_in_from_the_right() {
+        let start = Instant::now();
+        let mut toasts = Toasts::new().slide_in(Duration::from_millis(100), Motion::default());
+        toasts.info("Saved", start);
+        assert_eq!(
+            toasts.next_frame(start),
+            Some(start + crate::animation::FRAME)
+        );
+        assert_eq!(rows(&toasts, 24, 4)[0], "                        ");
+        assert!(toasts.tick(start + Duration::from_millis(20)));
+        assert_eq!(rows(&toasts, 24, 4)[0], "                   ┌ Inf");
+        assert!(!toasts.tick(start + Duration::from_millis(100)));
+        assert_eq!(rows(&toasts, 24, 4)[0], "             ┌ Info ──┐ ");
+        assert_eq!(toasts.next_frame(start), None);
+
+        // 减少动态效果时直接出现。
+        let mut toasts = Toasts::new().slide_in(Duration::from_millis(100), Motion::default());
+        toasts.set_motion(Motion::reduced());
+        toasts.info("Saved", start);
+        assert!(!toasts.tick(start));
+        assert_eq!(rows(&toasts, 24, 4)[0], "             ┌ Info ──┐ ");
+    }
+}
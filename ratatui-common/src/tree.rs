@@ -0,0 +1,478 @@
+//! 树形控件：展开和折叠、按需加载子节点、用方向键选择。
+//!
+//! 节点保存在 [`Tree`] 中，用 [`NodeId`] 引用；展开、选择和滚动位置保存在 [`TreeState`] 中，
+//! 和 ratatui 的 `List` / `ListState` 一样用 [`TreeView`] 按状态绘制。节点的值可以是任何类型，
+//! 例如 JSON 文档中的路径或者文件系统中的路径。
+//!
+//! 子节点可以在第一次展开时才加载：添加节点时只说明它有没有子节点，展开时由调用方传入的函数
+//! 根据节点的值给出子节点。加载后没有子节点的节点变成叶子节点。
+
+use std::collections::HashSet;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::Block};
+
+pub type NodeId = usize;
+
+/// 要添加的节点。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item<T> {
+    pub value: T,
+    pub label: Line<'static>,
+    /// 为 `true` 时节点可以展开，子节点在第一次展开时加载。
+    pub has_children: bool,
+}
+
+impl<T> Item<T> {
+    pub fn leaf(value: T, label: impl Into<Line<'static>>) -> Self {
+        Self {
+            value,
+            label: label.into(),
+            has_children: false,
+        }
+    }
+
+    pub fn branch(value: T, label: impl Into<Line<'static>>) -> Self {
+        Self {
+            value,
+            label: label.into(),
+            has_children: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Children {
+    Leaf,
+    Unloaded,
+    Loaded(Vec<NodeId>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node<T> {
+    pub value: T,
+    pub label: Line<'static>,
+    parent: Option<NodeId>,
+    children: Children,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tree<T> {
+    nodes: Vec<Node<T>>,
+    roots: Vec<NodeId>,
+}
+
+impl<T> Default for Tree<T> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            roots: Vec::new(),
+        }
+    }
+}
+
+impl<T> Tree<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(&mut self, item: Item<T>, parent: Option<NodeId>) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            value: item.value,
+            label: item.label,
+            parent,
+            children: if item.has_children {
+                Children::Unloaded
+            } else {
+                Children::Leaf
+            },
+        });
+        id
+    }
+
+    pub fn push_root(&mut self, item: Item<T>) -> NodeId {
+        let id = self.push(item, None);
+        self.roots.push(id);
+        id
+    }
+
+    /// 添加子节点，`parent` 随之变成已经加载的节点。
+    pub fn push_child(&mut self, parent: NodeId, item: Item<T>) -> NodeId {
+        let id = self.push(item, Some(parent));
+        match &mut self.nodes[parent].children {
+            Children::Loaded(children) => children.push(id),
+            children => *children = Children::Loaded(vec![id]),
+        }
+        id
+    }
+
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node<T> {
+        &self.nodes[id]
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id].parent
+    }
+
+    /// 已经加载的子节点。
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        match &self.nodes[id].children {
+            Children::Loaded(children) => children,
+            Children::Leaf | Children::Unloaded => &[],
+        }
+    }
+
+    /// 节点可以展开：有子节点，或者子节点还没有加载。
+    pub fn has_children(&self, id: NodeId) -> bool {
+        match &self.nodes[id].children {
+            Children::Leaf => false,
+            Children::Unloaded => true,
+            Children::Loaded(children) => !children.is_empty(),
+        }
+    }
+
+    /// 从根节点到 `id` 的节点，包括两端。
+    pub fn ancestors(&self, id: NodeId) -> Vec<NodeId> {
+        let mut path = vec![id];
+        while let Some(parent) = self.parent(*path.last().unwrap()) {
+            path.push(parent);
+        }
+        path.reverse();
+        path
+    }
+
+    /// 需要时调用 `load` 加载子节点。
+    fn load(&mut self, id: NodeId, load: &mut impl FnMut(&T) -> Vec<Item<T>>) {
+        if self.nodes[id].children != Children::Unloaded {
+            return;
+        }
+        let items = load(&self.nodes[id].value);
+        self.nodes[id].children = Children::Leaf;
+        for item in items {
+            self.push_child(id, item);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeState {
+    expanded: HashSet<NodeId>,
+    selected: Option<NodeId>,
+    /// 显示在第一行的可见节点的位置。
+    offset: usize,
+}
+
+impl TreeState {
+    pub fn selected(&self) -> Option<NodeId> {
+        self.selected
+    }
+
+    pub fn select(&mut self, id: Option<NodeId>) {
+        self.selected = id;
+    }
+
+    pub fn is_expanded(&self, id: NodeId) -> bool {
+        self.expanded.contains(&id)
+    }
+
+    /// 展开的节点下可以看到的所有节点和它们的深度，按显示的顺序。
+    pub fn visible<T>(&self, tree: &Tree<T>) -> Vec<(NodeId, usize)> {
+        let mut visible = Vec::new();
+        let mut stack: Vec<(NodeId, usize)> =
+            tree.roots().iter().rev().map(|&id| (id, 0)).collect();
+        while let Some((id, depth)) = stack.pop() {
+            visible.push((id, depth));
+            if self.is_expanded(id) {
+                stack.extend(
+                    tree.children(id)
+                        .iter()
+                        .rev()
+                        .map(|&child| (child, depth + 1)),
+                );
+            }
+        }
+        visible
+    }
+
+    /// 展开节点，第一次展开时加载子节点。
+    pub fn expand<T>(
+        &mut self,
+        tree: &mut Tree<T>,
+        id: NodeId,
+        mut load: impl FnMut(&T) -> Vec<Item<T>>,
+    ) {
+        tree.load(id, &mut load);
+        if tree.has_children(id) {
+            self.expanded.insert(id);
+        }
+    }
+
+    pub fn collapse(&mut self, id: NodeId) {
+        self.expanded.remove(&id);
+    }
+
+    /// 展开到 `id` 并选中它，例如搜索到某个节点之后。
+    pub fn reveal<T>(&mut self, tree: &Tree<T>, id: NodeId) {
+        for ancestor in tree.ancestors(id) {
+            if ancestor != id {
+                self.expanded.insert(ancestor);
+            }
+        }
+        self.selected = Some(id);
+    }
+
+    fn move_by<T>(&mut self, tree: &Tree<T>, delta: isize) {
+        let visible = self.visible(tree);
+        let Some(last) = visible.len().checked_sub(1) else {
+            return;
+        };
+        let row = self
+            .selected
+            .and_then(|selected| visible.iter().position(|&(id, _)| id == selected));
+        let row = match row {
+            Some(row) => row.saturating_add_signed(delta).min(last),
+            None => 0,
+        };
+        self.selected = Some(visible[row].0);
+    }
+
+    /// 处理方向键：`↑` / `↓` 选择，`→` 展开或进入第一个子节点，`←` 折叠或回到父节点，
+    /// `Enter` / `Space` 展开或折叠，`Home` / `End` 到第一个和最后一个。返回该按键是否被使用。
+    pub fn handle_key_event<T>(
+        &mut self,
+        key_event: KeyEvent,
+        tree: &mut Tree<T>,
+        load: impl FnMut(&T) -> Vec<Item<T>>,
+    ) -> bool {
+        let Some(selected) = self.selected else {
+            if matches!(
+                key_event.code,
+                KeyCode::Up | KeyCode::Down | KeyCode::Home | KeyCode::End
+            ) {
+                self.move_by(tree, 0);
+                return true;
+            }
+            return false;
+        };
+        match key_event.code {
+            KeyCode::Up => self.move_by(tree, -1),
+            KeyCode::Down => self.move_by(tree, 1),
+            KeyCode::PageUp => self.move_by(tree, -10),
+            KeyCode::PageDown => self.move_by(tree, 10),
+            KeyCode::Home => self.move_by(tree, isize::MIN),
+            KeyCode::End => self.move_by(tree, isize::MAX),
+            KeyCode::Right if self.is_expanded(selected) => {
+                if let Some(&child) = tree.children(selected).first() {
+                    self.selected = Some(child);
+                }
+            }
+            KeyCode::Right => self.expand(tree, selected, load),
+            KeyCode::Left if self.is_expanded(selected) => self.collapse(selected),
+            KeyCode::Left => match tree.parent(selected) {
+                Some(parent) => self.selected = Some(parent),
+                None => return false,
+            },
+            KeyCode::Enter | KeyCode::Char(' ') if self.is_expanded(selected) => {
+                self.collapse(selected);
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => self.expand(tree, selected, load),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// 按 [`TreeState`] 绘制 [`Tree`]，每层缩进两列。
+#[derive(Debug, Clone)]
+pub struct TreeView<'a, T> {
+    tree: &'a Tree<T>,
+    block: Option<Block<'a>>,
+    highlight_style: Style,
+}
+
+impl<'a, T> TreeView<'a, T> {
+    pub fn new(tree: &'a Tree<T>) -> Self {
+        Self {
+            tree,
+            block: None,
+            highlight_style: Style::new().reversed(),
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn highlight_style(mut self, style: Style) -> Self {
+        self.highlight_style = style;
+        self
+    }
+}
+
+impl<T> StatefulWidget for TreeView<'_, T> {
+    type State = TreeState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut TreeState) {
+        let area = match self.block {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+        if area.is_empty() {
+            return;
+        }
+        let visible = state.visible(self.tree);
+        let height = usize::from(area.height);
+        // 滚动到能看到选中的节点。
+        if let Some(row) = state
+            .selected
+            .and_then(|selected| visible.iter().position(|&(id, _)| id == selected))
+        {
+            state.offset = state.offset.clamp(row.saturating_sub(height - 1), row);
+        }
+        state.offset = state.offset.min(visible.len().saturating_sub(height));
+
+        for (y, &(id, depth)) in visible.iter().skip(state.offset).take(height).enumerate() {
+            let row = Rect {
+                y: area.y + y as u16,
+                height: 1,
+                ..area
+            };
+            let symbol = if !self.tree.has_children(id) {
+                "  "
+            } else if state.is_expanded(id) {
+                "▾ "
+            } else {
+                "▸ "
+            };
+            let node = self.tree.node(id);
+            let mut line = Line::from(vec![Span::raw("  ".repeat(depth)), Span::raw(symbol)]);
+            line.spans.extend(node.label.spans.iter().cloned());
+            line.style = node.label.style;
+            line.render(row, buf);
+            if state.selected == Some(id) {
+                buf.set_style(row, self.highlight_style);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::testing;
+
+    /// 每个节点的值是它的路径，`a` 下有 `a/0` 到 `a/2`，两层以下是叶子节点。
+    fn load(path: &String) -> Vec<Item<String>> {
+        (0..3)
+            .map(|index| {
+                let path = format!("{path}/{index}");
+                let label = path.clone();
+                if path.matches('/').count() < 2 {
+                    Item::branch(path, label)
+                } else {
+                    Item::leaf(path, label)
+                }
+            })
+            .collect()
+    }
+
+    fn tree() -> Tree<String> {
+        let mut tree = Tree::new();
+        tree.push_root(Item::branch("a".to_string(), "a"));
+        tree.push_root(Item::leaf("b".to_string(), "b"));
+        tree
+    }
+
+    fn rows(tree: &Tree<String>, state: &mut TreeState, height: u16) -> Vec<String> {
+        testing::render_rows(12, height, |area, buf| {
+            StatefulWidget::render(TreeView::new(tree), area, buf, state)
+        })
+    }
+
+    fn press(state: &mut TreeState, tree: &mut Tree<String>, code: KeyCode) -> bool {
+        state.handle_key_event(code.into(), tree, load)
+    }
+
+    #[test]
+    fn lazy_loading() {
+        let mut tree = tree();
+        let mut state = TreeState::default();
+        let loads = Cell::new(0);
+        let counting = |path: &String| {
+            loads.set(loads.get() + 1);
+            load(path)
+        };
+        state.expand(&mut tree, 0, counting);
+        state.collapse(0);
+        state.expand(&mut tree, 0, counting);
+        assert_eq!(loads.get(), 1);
+        assert_eq!(tree.children(0).len(), 3);
+        assert_eq!(
+            state
+                .visible(&tree)
+                .iter()
+                .map(|&(id, depth)| (tree.node(id).value.as_str(), depth))
+                .collect::<Vec<_>>(),
+            [("a", 0), ("a/0", 1), ("a/1", 1), ("a/2", 1), ("b", 0)]
+        );
+
+        // 加载后没有子节点的节点不能展开。
+        let empty = tree.push_root(Item::branch("empty".to_string(), "empty"));
+        state.expand(&mut tree, empty, |_| Vec::new());
+        assert!(!tree.has_children(empty));
+        assert!(!state.is_expanded(empty));
+
+        // 选中折叠的节点下的节点时展开它的上层节点。
+        state.expand(&mut tree, 2, load);
+        let deep = tree.children(2)[2];
+        state.collapse(0);
+        state.reveal(&tree, deep);
+        assert_eq!(state.selected(), Some(deep));
+        assert!(state.is_expanded(0) && state.is_expanded(2));
+    }
+
+    #[test]
+    fn keys_and_scrolling() {
+        let mut tree = tree();
+        let mut state = TreeState::default();
+        assert!(press(&mut state, &mut tree, KeyCode::Down));
+        assert_eq!(
+            rows(&tree, &mut state, 3),
+            ["▸ a         ", "  b         ", "            "]
+        );
+
+        press(&mut state, &mut tree, KeyCode::Right);
+        press(&mut state, &mut tree, KeyCode::Right);
+        assert_eq!(tree.node(state.selected().unwrap()).value, "a/0");
+        press(&mut state, &mut tree, KeyCode::Enter);
+        press(&mut state, &mut tree, KeyCode::End);
+        assert_eq!(tree.node(state.selected().unwrap()).value, "b");
+        assert_eq!(
+            rows(&tree, &mut state, 3),
+            ["  ▸ a/1     ", "  ▸ a/2     ", "  b         "]
+        );
+
+        press(&mut state, &mut tree, KeyCode::Up);
+        press(&mut state, &mut tree, KeyCode::Left);
+        assert_eq!(state.selected(), Some(0));
+        press(&mut state, &mut tree, KeyCode::Left);
+        assert!(!state.is_expanded(0));
+        assert!(!press(&mut state, &mut tree, KeyCode::Left));
+        assert_eq!(
+            rows(&tree, &mut state, 3),
+            ["▸ a         ", "  b         ", "            "]
+        );
+    }
+}
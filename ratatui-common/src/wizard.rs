@@ -0,0 +1,181 @@
+//! 分步骤的向导：编号的步骤、每一步的校验、前进和后退，以及显示进度的一行。
+//!
+//! [`Wizard`] 只记录走到了哪一步和最近一次校验的错误；每一步的输入和校验由调用方负责，前进时把
+//! 这一步校验的结果交给 [`Wizard::next`]。最后一步通常是确认页，列出前面各步填写的内容，在这一步
+//! 前进就完成了向导。绘制 `&Wizard` 得到一行 `✓ Account › 2 Password › 3 Review`。
+
+use ratatui::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wizard {
+    steps: Vec<String>,
+    current: usize,
+    finished: bool,
+    error: Option<String>,
+}
+
+impl Wizard {
+    /// `steps` 是各步的名字，至少要有一步。
+    pub fn new<S: Into<String>>(steps: impl IntoIterator<Item = S>) -> Self {
+        let steps: Vec<String> = steps.into_iter().map(Into::into).collect();
+        assert!(!steps.is_empty(), "a wizard needs at least one step");
+        Self {
+            steps,
+            current: 0,
+            finished: false,
+            error: None,
+        }
+    }
+
+    pub fn steps(&self) -> &[String] {
+        &self.steps
+    }
+
+    /// 当前步骤的位置，从零开始。
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn is_first(&self) -> bool {
+        self.current == 0
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.current + 1 == self.steps.len()
+    }
+
+    /// 最后一步也通过了。
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// 最近一次校验的错误，前进、后退或者 [`Wizard::clear_error`] 时清除。
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn clear_error(&mut self) {
+        self.error = None;
+    }
+
+    /// 当前步骤校验通过时前进一步，最后一步通过时完成向导；没有通过时留在这一步并记下错误。
+    /// 返回是否通过。
+    pub fn next(&mut self, validation: Result<(), String>) -> bool {
+        if self.finished {
+            return false;
+        }
+        match validation {
+            Ok(()) => {
+                self.error = None;
+                if self.is_last() {
+                    self.finished = true;
+                } else {
+                    self.current += 1;
+                }
+                true
+            }
+            Err(error) => {
+                self.error = Some(error);
+                false
+            }
+        }
+    }
+
+    /// 回到上一步，已经在第一步或者已经完成时返回 `false`。
+    pub fn back(&mut self) -> bool {
+        if self.finished || self.is_first() {
+            return false;
+        }
+        self.error = None;
+        self.current -= 1;
+        true
+    }
+
+    /// 已经完成的比例，用于进度条。
+    pub fn ratio(&self) -> f64 {
+        let done = if self.finished {
+            self.steps.len()
+        } else {
+            self.current
+        };
+        done as f64 / self.steps.len() as f64
+    }
+}
+
+/// 完成的步骤显示为绿色的 `✓`，当前步骤加粗，后面的步骤变暗。
+impl Widget for &Wizard {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut spans = Vec::new();
+        for (index, step) in self.steps.iter().enumerate() {
+            if index > 0 {
+                spans.push(" › ".dim());
+            }
+            spans.push(if index < self.current || self.finished {
+                format!("✓ {step}").green()
+            } else if index == self.current {
+                format!("{} {step}", index + 1).bold()
+            } else {
+                format!("{} {step}", index + 1).dim()
+            });
+        }
+        Line::from(spans).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(wizard: &Wizard) -> (String, Vec<Modifier>) {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 32, 1));
+        wizard.render(buf.area, &mut buf);
+        let text = (0..32).map(|x| buf.get(x, 0).symbol()).collect();
+        // 每一步第一个字符的样式。
+        let modifiers = [0, 12, 24]
+            .iter()
+            .map(|&x| buf.get(x, 0).modifier)
+            .collect();
+        (text, modifiers)
+    }
+
+    #[test]
+    fn steps() {
+        let mut wizard = Wizard::new(["Account", "Password", "Review"]);
+        assert!(!wizard.back());
+        assert_eq!(
+            render(&wizard),
+            (
+                "1 Account › 2 Password › 3 Review"
+                    .chars()
+                    .take(32)
+                    .collect(),
+                vec![Modifier::BOLD, Modifier::DIM, Modifier::DIM]
+            )
+        );
+
+        assert!(!wizard.next(Err("the name cannot be empty".into())));
+        assert_eq!(
+            (wizard.current(), wizard.error()),
+            (0, Some("the name cannot be empty"))
+        );
+        assert!(wizard.next(Ok(())));
+        assert_eq!((wizard.current(), wizard.error()), (1, None));
+        assert_eq!(
+            render(&wizard).1,
+            vec![Modifier::empty(), Modifier::BOLD, Modifier::DIM]
+        );
+        assert!(render(&wizard).0.starts_with("✓ Account › 2 Password"));
+
+        assert!(wizard.back());
+        assert!(wizard.is_first());
+        wizard.next(Ok(()));
+        wizard.next(Ok(()));
+        assert!(wizard.is_last() && !wizard.is_finished());
+        assert!((wizard.ratio() - 2.0 / 3.0).abs() < 1e-9);
+        assert!(wizard.next(Ok(())));
+        assert!(wizard.is_finished());
+        assert_eq!(wizard.ratio(), 1.0);
+        assert!(!wizard.back());
+        assert!(!wizard.next(Ok(())));
+    }
+}
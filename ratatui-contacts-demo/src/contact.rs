@@ -0,0 +1,132 @@
+//! 联系人和表单中每一项的检查。
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Contact {
+    pub name: String,
+    pub email: String,
+    pub phone: String,
+    pub company: String,
+    /// 可以有多行。
+    pub note: String,
+}
+
+impl Contact {
+    /// 搜索时不区分大小写地匹配每一项，电话号码忽略空格和连字符。
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return true;
+        }
+        let digits = |text: &str| -> String {
+            text.chars()
+                .filter(|c| !matches!(c, ' ' | '-' | '(' | ')' | '.'))
+                .collect()
+        };
+        [&self.name, &self.email, &self.company, &self.note]
+            .iter()
+            .any(|field| field.to_lowercase().contains(&query))
+            || (!digits(&query).is_empty() && digits(&self.phone).contains(&digits(&query)))
+    }
+
+    /// 列表按名字排序时用的键。
+    pub fn sort_key(&self) -> String {
+        self.name.to_lowercase()
+    }
+}
+
+pub fn check_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Name is required".into());
+    }
+    Ok(())
+}
+
+/// 空的邮箱也可以，否则需要像 `sam@example.com` 这样，域名中至少有一个点。
+pub fn check_email(email: &str) -> Result<(), String> {
+    let email = email.trim();
+    if email.is_empty() {
+        return Ok(());
+    }
+    let valid = match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && domain.split('.').all(|part| !part.is_empty())
+                && !email.chars().any(char::is_whitespace)
+        }
+        None => false,
+    };
+    if !valid {
+        return Err(format!(
+            "{email:?} is not an email address like sam@example.com"
+        ));
+    }
+    Ok(())
+}
+
+/// 空的电话号码也可以，否则只能有数字、空格和 `+-().`，并且至少有三个数字。
+pub fn check_phone(phone: &str) -> Result<(), String> {
+    let phone = phone.trim();
+    if phone.is_empty() {
+        return Ok(());
+    }
+    let allowed = phone
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, ' ' | '+' | '-' | '(' | ')' | '.'));
+    let digits = phone.chars().filter(char::is_ascii_digit).count();
+    if !allowed || digits < 3 {
+        return Err(format!("{phone:?} is not a phone number"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_and_checks() {
+        let contact = Contact {
+            name: "Sam Lee".into(),
+            email: "sam@example.com".into(),
+            phone: "+1 (555) 010-2030".into(),
+            company: "Acme".into(),
+            note: String::new(),
+        };
+        assert!(contact.matches(""));
+        assert!(contact.matches("lee"));
+        assert!(contact.matches("ACME"));
+        assert!(contact.matches("555010"));
+        assert!(contact.matches("010-20"));
+        assert!(!contact.matches("bob"));
+        assert!(!contact.matches("-"));
+
+        assert_eq!(check_name("  "), Err("Name is required".into()));
+        assert_eq!(check_email(""), Ok(()));
+        assert_eq!(check_email("sam@mail.example.com"), Ok(()));
+        for email in [
+            "sam",
+            "@example.com",
+            "sam@example",
+            "sam@ex..com",
+            "s am@example.com",
+        ] {
+            assert_eq!(
+                check_email(email),
+                Err(format!(
+                    "{email:?} is not an email address like sam@example.com"
+                ))
+            );
+        }
+        assert_eq!(check_phone("+44 20 7946 0958"), Ok(()));
+        assert_eq!(
+            check_phone("12"),
+            Err("\"12\" is not a phone number".into())
+        );
+        assert_eq!(
+            check_phone("555-CALL"),
+            Err("\"555-CALL\" is not a phone number".into())
+        );
+    }
+}
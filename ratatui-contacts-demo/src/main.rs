@@ -0,0 +1,751 @@
+//! 通讯录演示：可以搜索的联系人列表、详情面板、带检查的添加和编辑表单，以及 vCard 导入导出。
+//! 这是工作区中增删改查类应用的参考实现。
+//!
+//! 联系人保存在命令行指定的 vCard 文件中（见 `vcard` 模块），每次修改后立即写入。导出时只导出
+//! 搜索到的联系人；导入时跳过已经存在的完全相同的联系人。表单中备注只有一行，编辑多行的备注时
+//! 换行被替换成空格。
+//!
+//! 按键：`/` 搜索，`a` 添加，`Enter` 编辑，`x` 删除，`i` 导入，`e` 导出，`q` 退出。
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use clap::Parser;
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Clear, List, ListState, Paragraph, Wrap},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    files,
+    layout::centered,
+    terminal,
+    text_input::Input,
+};
+
+use crate::contact::{check_email, check_name, check_phone, Contact};
+
+mod contact;
+mod vcard;
+
+/// 等待按键的最长时间。
+const REFRESH: Duration = Duration::from_secs(1);
+
+/// 联系人列表的宽度，正好放下底部的按键提示。
+const LIST_WIDTH: u16 = 36;
+
+const NAME: usize = 0;
+const EMAIL: usize = 1;
+const PHONE: usize = 2;
+const COMPANY: usize = 3;
+const NOTE: usize = 4;
+const LABELS: [&str; 5] = [
+    "Name:    ",
+    "Email:   ",
+    "Phone:   ",
+    "Company: ",
+    "Note:    ",
+];
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 保存联系人的 vCard 文件，不存在时在第一次修改时创建
+    #[arg(default_value = "contacts.vcf")]
+    file: PathBuf,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let contacts = match fs::read_to_string(&cli.file) {
+        Ok(text) => vcard::read(&text)
+            .map_err(|error| eyre!(error))
+            .wrap_err_with(|| format!("cannot read {}", cli.file.display()))?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => {
+            return Err(err).wrap_err_with(|| format!("cannot open {}", cli.file.display()))
+        }
+    };
+
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new(contacts, cli.file).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+/// 把联系人写入 vCard 文件。
+fn save_vcard<'a>(path: &Path, contacts: impl IntoIterator<Item = &'a Contact>) -> Result<()> {
+    files::write_atomic(path, vcard::write(contacts))
+        .wrap_err_with(|| format!("cannot write {}", path.display()))
+}
+
+fn load_vcard(path: &Path) -> Result<Vec<Contact>> {
+    let text =
+        fs::read_to_string(path).wrap_err_with(|| format!("cannot open {}", path.display()))?;
+    vcard::read(&text)
+        .map_err(|error| eyre!(error))
+        .wrap_err_with(|| format!("cannot read {}", path.display()))
+}
+
+fn count(count: usize) -> String {
+    match count {
+        1 => "1 contact".into(),
+        count => format!("{count} contacts"),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Prompt {
+    Import,
+    Export,
+}
+
+impl Prompt {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Import => "Import from: ",
+            Self::Export => "Export to: ",
+        }
+    }
+}
+
+/// 添加或编辑联系人的表单。
+struct Form {
+    fields: [Input; 5],
+    field: usize,
+    /// 正在编辑的联系人，添加时为 `None`。
+    editing: Option<usize>,
+}
+
+impl Form {
+    fn new(contact: &Contact, editing: Option<usize>) -> Self {
+        Self {
+            fields: [
+                Input::with_value(&contact.name),
+                Input::with_value(&contact.email),
+                Input::with_value(&contact.phone),
+                Input::with_value(&contact.company),
+                Input::with_value(contact.note.replace('\n', " ")),
+            ],
+            field: NAME,
+            editing,
+        }
+    }
+
+    /// 检查每一项，出错时返回出错的那一项和错误。
+    fn contact(&self) -> Result<Contact, (usize, String)> {
+        let value = |field: usize| self.fields[field].value().trim().to_string();
+        check_name(&value(NAME)).map_err(|error| (NAME, error))?;
+        check_email(&value(EMAIL)).map_err(|error| (EMAIL, error))?;
+        check_phone(&value(PHONE)).map_err(|error| (PHONE, error))?;
+        Ok(Contact {
+            name: value(NAME),
+            email: value(EMAIL),
+            phone: value(PHONE),
+            company: value(COMPANY),
+            note: value(NOTE),
+        })
+    }
+}
+
+struct App {
+    /// 按名字排序。
+    contacts: Vec<Contact>,
+    path: PathBuf,
+    search: Input,
+    searching: bool,
+    list: ListState,
+    form: Option<Form>,
+    prompt: Option<(Prompt, Input)>,
+    /// 等待确认删除的联系人。
+    confirm: Option<usize>,
+    /// 显示在状态栏中的提示或错误，下一次按键时清除。
+    message: Option<Result<String, String>>,
+    exit: bool,
+}
+
+impl App {
+    fn new(mut contacts: Vec<Contact>, path: PathBuf) -> Self {
+        contacts.sort_by_key(Contact::sort_key);
+        let mut app = Self {
+            contacts,
+            path,
+            search: Input::default(),
+            searching: false,
+            list: ListState::default(),
+            form: None,
+            prompt: None,
+            confirm: None,
+            message: None,
+            exit: false,
+        };
+        app.select(0);
+        app
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| frame.render_widget(&mut *self, frame.size()))?;
+            if events.poll(REFRESH)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 搜索到的联系人的位置。
+    fn visible(&self) -> Vec<usize> {
+        (0..self.contacts.len())
+            .filter(|&index| self.contacts[index].matches(self.search.value()))
+            .collect()
+    }
+
+    fn select(&mut self, row: usize) {
+        let last = self.visible().len().checked_sub(1);
+        self.list.select(last.map(|last| row.min(last)));
+    }
+
+    fn selected(&self) -> Option<usize> {
+        self.visible().get(self.list.selected()?).copied()
+    }
+
+    /// 选中某个联系人，搜索结果中没有它时清除搜索。
+    fn select_contact(&mut self, index: usize) {
+        if !self.visible().contains(&index) {
+            self.search = Input::default();
+        }
+        let row = self.visible().iter().position(|&other| other == index);
+        self.select(row.unwrap_or(0));
+    }
+
+    /// 按名字插入，返回插入的位置。
+    fn insert(&mut self, contact: Contact) -> usize {
+        let key = contact.sort_key();
+        let index = self
+            .contacts
+            .partition_point(|other| other.sort_key() <= key);
+        self.contacts.insert(index, contact);
+        index
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        self.message = None;
+        if self.form.is_some() {
+            self.handle_form_key(key);
+            return;
+        }
+        if let Some((prompt, input)) = &mut self.prompt {
+            match key.code {
+                KeyCode::Esc => self.prompt = None,
+                KeyCode::Enter => {
+                    let (prompt, path) = (*prompt, PathBuf::from(input.value().trim()));
+                    self.prompt = None;
+                    match prompt {
+                        Prompt::Import => self.import(&path),
+                        Prompt::Export => self.export(&path),
+                    }
+                }
+                _ => {
+                    input.handle_key_event(key);
+                }
+            }
+            return;
+        }
+        if let Some(index) = self.confirm.take() {
+            if key.code == KeyCode::Char('y') {
+                let contact = self.contacts.remove(index);
+                self.select(self.list.selected().unwrap_or(0));
+                self.changed(format!("Deleted {}", contact.name));
+            }
+            return;
+        }
+        if self.searching {
+            match key.code {
+                KeyCode::Esc => {
+                    self.searching = false;
+                    self.search = Input::default();
+                    self.select(0);
+                }
+                KeyCode::Enter | KeyCode::Down | KeyCode::Tab => self.searching = false,
+                _ => {
+                    if self.search.handle_key_event(key) {
+                        self.select(0);
+                    }
+                }
+            }
+            return;
+        }
+        let row = self.list.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Char('q') => self.exit = true,
+            // 有搜索条件时 `Esc` 先清除搜索。
+            KeyCode::Esc if !self.search.value().is_empty() => {
+                self.search = Input::default();
+                self.select(0);
+            }
+            KeyCode::Esc => self.exit = true,
+            KeyCode::Up | KeyCode::Char('k') => self.select(row.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.select(row + 1),
+            KeyCode::Char('/') => self.searching = true,
+            KeyCode::Char('a') => self.form = Some(Form::new(&Contact::default(), None)),
+            KeyCode::Enter => {
+                if let Some(index) = self.selected() {
+                    self.form = Some(Form::new(&self.contacts[index], Some(index)));
+                }
+            }
+            KeyCode::Char('x') | KeyCode::Delete => self.confirm = self.selected(),
+            KeyCode::Char('i') => self.prompt = Some((Prompt::Import, Input::default())),
+            KeyCode::Char('e') => {
+                self.prompt = Some((Prompt::Export, Input::with_value("export.vcf")));
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_form_key(&mut self, key: KeyEvent) {
+        let Some(form) = &mut self.form else {
+            return;
+        };
+        let len = form.fields.len();
+        match key.code {
+            KeyCode::Esc => self.form = None,
+            KeyCode::Tab | KeyCode::Down => form.field = (form.field + 1) % len,
+            KeyCode::BackTab | KeyCode::Up => form.field = (form.field + len - 1) % len,
+            KeyCode::Enter => match form.contact() {
+                Ok(contact) => {
+                    let editing = form.editing;
+                    self.form = None;
+                    let message = match editing {
+                        Some(index) => {
+                            self.contacts.remove(index);
+                            format!("Saved {}", contact.name)
+                        }
+                        None => format!("Added {}", contact.name),
+                    };
+                    let index = self.insert(contact);
+                    self.select_contact(index);
+                    self.changed(message);
+                }
+                Err((field, error)) => {
+                    form.field = field;
+                    self.message = Some(Err(error));
+                }
+            },
+            _ => {
+                form.fields[form.field].handle_key_event(key);
+            }
+        }
+    }
+
+    fn import(&mut self, path: &Path) {
+        match load_vcard(path) {
+            Ok(contacts) => {
+                let total = contacts.len();
+                let mut added = 0;
+                for contact in contacts {
+                    if !self.contacts.contains(&contact) {
+                        self.insert(contact);
+                        added += 1;
+                    }
+                }
+                self.select(0);
+                let mut message = format!("Imported {} from {}", count(added), path.display());
+                if added < total {
+                    message.push_str(&format!(", skipped {} already here", total - added));
+                }
+                self.changed(message);
+            }
+            Err(error) => self.message = Some(Err(format!("{error:#}"))),
+        }
+    }
+
+    fn export(&mut self, path: &Path) {
+        let visible = self.visible();
+        let exported = visible.iter().map(|&index| &self.contacts[index]);
+        self.message = Some(match save_vcard(path, exported) {
+            Ok(()) => Ok(format!(
+                "Exported {} to {}",
+                count(visible.len()),
+                path.display()
+            )),
+            Err(error) => Err(format!("{error:#}")),
+        });
+    }
+
+    /// 修改后立即保存。
+    fn changed(&mut self, message: String) {
+        self.message = Some(match save_vcard(&self.path, &self.contacts) {
+            Ok(()) => Ok(message),
+            Err(error) => Err(format!("{error:#}")),
+        });
+    }
+
+    fn render_list(&mut self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(" Contacts ")
+            .title_bottom(Line::from(" Search </> Import <i> Export <e> ").right_aligned())
+            .border_style(Style::new().green());
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let [search_area, list_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(inner);
+        let style = if self.searching {
+            Style::new().yellow().bold()
+        } else {
+            Style::new().dim()
+        };
+        Paragraph::new(self.search.line("/ ", style)).render(search_area, buf);
+
+        let visible = self.visible();
+        if visible.is_empty() {
+            let text = if self.contacts.is_empty() {
+                "No contacts yet, press a to add one"
+            } else {
+                "No contacts match"
+            };
+            Paragraph::new(text.dim())
+                .wrap(Wrap { trim: true })
+                .render(list_area, buf);
+            return;
+        }
+        let items: Vec<Line> = visible
+            .iter()
+            .map(|&index| {
+                let contact = &self.contacts[index];
+                let mut line = Line::from(contact.name.as_str());
+                if !contact.company.is_empty() {
+                    line.push_span(format!("  {}", contact.company).dim());
+                }
+                line
+            })
+            .collect();
+        StatefulWidget::render(
+            List::new(items).highlight_style(Style::new().reversed()),
+            list_area,
+            buf,
+            &mut self.list,
+        );
+    }
+
+    fn render_detail(&self, area: Rect, buf: &mut Buffer) {
+        let mut block = Block::bordered()
+            .title_bottom(Line::from(" Add <a> Edit <Enter> Delete <x> Quit <q> ").right_aligned());
+        let Some(contact) = self.selected().map(|index| &self.contacts[index]) else {
+            block.render(area, buf);
+            return;
+        };
+        block = block.title(format!(" {} ", contact.name).bold());
+        let mut lines: Vec<Line> = [
+            ("Email    ", &contact.email),
+            ("Phone    ", &contact.phone),
+            ("Company  ", &contact.company),
+        ]
+        .into_iter()
+        .map(|(label, value)| {
+            let value = if value.is_empty() {
+                "—".dark_gray()
+            } else {
+                value.as_str().into()
+            };
+            Line::from(vec![label.dim(), value])
+        })
+        .collect();
+        if !contact.note.is_empty() {
+            lines.push(Line::default());
+            lines.extend(contact.note.lines().map(Line::from));
+        }
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(block.padding(ratatui::widgets::Padding::horizontal(1)))
+            .render(area, buf);
+    }
+
+    fn render_form(form: &Form, area: Rect, buf: &mut Buffer) {
+        let popup = centered(area, 56, 9);
+        Clear.render(popup, buf);
+        let title = if form.editing.is_some() {
+            " Edit contact "
+        } else {
+            " New contact "
+        };
+        let block = Block::bordered()
+            .title(title.bold())
+            .title_bottom(Line::from(" Next <Tab> Save <Enter> Cancel <Esc> ").right_aligned())
+            .border_style(Style::new().yellow());
+        let inner = block.inner(popup);
+        block.render(popup, buf);
+        let rows = Layout::vertical([Constraint::Length(1); 5])
+            .margin(1)
+            .split(inner.inner(&Margin::new(1, 0)));
+        for (index, input) in form.fields.iter().enumerate() {
+            let style = if index == form.field {
+                Style::new().green().bold()
+            } else {
+                Style::new()
+            };
+            Paragraph::new(input.line(LABELS[index], style)).render(rows[index], buf);
+        }
+    }
+
+    fn status_line(&self) -> Line<'_> {
+        if let Some(index) = self.confirm {
+            return Line::from(format!("Delete {}? <Y>/<N>", self.contacts[index].name))
+                .red()
+                .bold();
+        }
+        if let Some((prompt, input)) = &self.prompt {
+            return input.line(prompt.label(), Style::new().bold());
+        }
+        match &self.message {
+            Some(Ok(message)) => Line::from(message.as_str()),
+            Some(Err(error)) => Line::from(error.as_str().red()),
+            None => {
+                let visible = self.visible().len();
+                let text = if visible == self.contacts.len() {
+                    format!("{} in {}", count(visible), self.path.display())
+                } else {
+                    format!("{} of {} match", visible, count(self.contacts.len()))
+                };
+                Line::from(text.dim())
+            }
+        }
+    }
+}
+
+impl Widget for &mut App {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let [list_area, detail_area] =
+            Layout::horizontal([Constraint::Length(LIST_WIDTH), Constraint::Fill(1)]).areas(main);
+        self.render_list(list_area, buf);
+        self.render_detail(detail_area, buf);
+        if let Some(form) = &self.form {
+            App::render_form(form, main, buf);
+        }
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    fn contact(name: &str, email: &str, company: &str) -> Contact {
+        Contact {
+            name: name.into(),
+            email: email.into(),
+            company: company.into(),
+            ..Contact::default()
+        }
+    }
+
+    fn app(name: &str) -> (App, PathBuf) {
+        let dir = env::temp_dir().join(format!("contacts-demo-{name}-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let contacts = vec![
+            contact("Sam Lee", "sam@example.com", "Acme"),
+            Contact {
+                phone: "555 0100".into(),
+                note: "Prefers calls\nafter 5pm".into(),
+                ..contact("ada lovelace", "ada@example.org", "")
+            },
+            contact("Bob Stone", "", "Acme"),
+        ];
+        (App::new(contacts, dir.join("contacts.vcf")), dir)
+    }
+
+    #[test]
+    fn render_and_search() {
+        let (mut app, dir) = app("search");
+        let screen = rows(&mut app, 80, 12);
+        assert_eq!(
+            screen[0],
+            "┌ Contacts ────────────────────────┐┌ ada lovelace ────────────────────────────┐"
+        );
+        assert_eq!(
+            screen[2],
+            "│ada lovelace                      ││ Phone    555 0100                        │"
+        );
+        assert_eq!(
+            screen[3],
+            "│Bob Stone  Acme                   ││ Company  —                               │"
+        );
+        assert_eq!(
+            screen[6],
+            "│                                  ││ after 5pm                                │"
+        );
+
+        app.handle_key(KeyCode::Char('/').into());
+        testing::type_text("acme", |key| app.handle_key(key));
+        let screen = rows(&mut app, 80, 12);
+        assert!(screen[1].starts_with("│/ acme"));
+        assert!(screen[2].starts_with("│Bob Stone  Acme"));
+        assert!(screen[3].starts_with("│Sam Lee  Acme"));
+        assert!(screen[4].starts_with("│     "));
+        assert_eq!(screen[11].trim_end(), "2 of 3 contacts match");
+
+        // 离开搜索框后方向键移动选择，`Esc` 先清除搜索再退出。
+        app.handle_key(KeyCode::Enter.into());
+        app.handle_key(KeyCode::Down.into());
+        assert_eq!(app.selected(), Some(2));
+        app.handle_key(KeyCode::Esc.into());
+        assert_eq!(app.search.value(), "");
+        assert!(!app.exit);
+        app.handle_key(KeyCode::Char('/').into());
+        testing::type_text("nobody", |key| app.handle_key(key));
+        assert!(rows(&mut app, 80, 12)[2].starts_with("│No contacts match"));
+        assert_eq!(app.selected(), None);
+        app.handle_key(KeyCode::Esc.into());
+        assert_eq!(app.selected(), Some(0));
+        app.handle_key(KeyCode::Esc.into());
+        assert!(app.exit);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn add_edit_and_delete() {
+        let (mut app, dir) = app("edit");
+        app.handle_key(KeyCode::Char('a').into());
+        assert!(rows(&mut app, 80, 12)[1].contains("┌ New contact ─"));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(app.message, Some(Err("Name is required".into())));
+        testing::type_text("Cleo Park", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Tab.into());
+        testing::type_text("cleo@@example.com", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(
+            app.message,
+            Some(Err(
+                "\"cleo@@example.com\" is not an email address like sam@example.com".into()
+            ))
+        );
+        assert_eq!(app.form.as_ref().unwrap().field, EMAIL);
+        for _ in 0.."@example.com".len() {
+            app.handle_key(KeyCode::Backspace.into());
+        }
+        testing::type_text("example.com", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(app.message, Some(Ok("Added Cleo Park".into())));
+        assert_eq!(app.contacts[2].name, "Cleo Park");
+        assert_eq!(app.selected(), Some(2));
+        let saved = fs::read_to_string(dir.join("contacts.vcf")).unwrap();
+        assert!(saved.contains("FN:Cleo Park\r\nN:Park;Cleo;;;\r\nEMAIL:cleo@example.com\r\n"));
+
+        // 编辑后按新的名字重新排序；表单中的备注是一行。
+        app.handle_key(KeyCode::Up.into());
+        app.handle_key(KeyCode::Up.into());
+        app.handle_key(KeyCode::Enter.into());
+        let form = app.form.as_ref().unwrap();
+        assert!(form.editing.is_some());
+        assert_eq!(form.fields[NOTE].value(), "Prefers calls after 5pm");
+        app.handle_key(KeyCode::Home.into());
+        app.handle_key(KeyCode::Delete.into());
+        testing::type_text("Zo", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(app.message, Some(Ok("Saved Zoda lovelace".into())));
+        assert_eq!(app.contacts.last().unwrap().name, "Zoda lovelace");
+        assert_eq!(app.contacts.len(), 4);
+        assert_eq!(app.selected(), Some(3));
+
+        app.handle_key(KeyCode::Char('x').into());
+        assert_eq!(
+            rows(&mut app, 80, 12)[11].trim_end(),
+            "Delete Zoda lovelace? <Y>/<N>"
+        );
+        app.handle_key(KeyCode::Char('y').into());
+        assert_eq!(app.contacts.len(), 3);
+        assert_eq!(app.selected(), Some(2));
+        assert_eq!(load_vcard(&dir.join("contacts.vcf")).unwrap(), app.contacts);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn import_and_export() {
+        let (mut app, dir) = app("vcard");
+        app.handle_key(KeyCode::Char('/').into());
+        testing::type_text("acme", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        app.handle_key(KeyCode::Char('e').into());
+        assert_eq!(
+            rows(&mut app, 80, 12)[11].trim_end(),
+            "Export to: export.vcf"
+        );
+        for _ in 0.."export.vcf".len() {
+            app.handle_key(KeyCode::Backspace.into());
+        }
+        let exported = dir.join("acme.vcf");
+        testing::type_text(exported.to_str().unwrap(), |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(
+            app.message,
+            Some(Ok(format!("Exported 2 contacts to {}", exported.display())))
+        );
+        let names: Vec<String> = load_vcard(&exported)
+            .unwrap()
+            .into_iter()
+            .map(|contact| contact.name)
+            .collect();
+        assert_eq!(names, ["Bob Stone", "Sam Lee"]);
+
+        let incoming = dir.join("incoming.vcf");
+        fs::write(
+            &incoming,
+            format!(
+                "{}BEGIN:VCARD\nVERSION:3.0\nFN:Dee Ray\nEND:VCARD\n",
+                vcard::write([&contact("Sam Lee", "sam@example.com", "Acme")])
+            ),
+        )
+        .unwrap();
+        app.handle_key(KeyCode::Char('i').into());
+        testing::type_text(incoming.to_str().unwrap(), |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(
+            app.message,
+            Some(Ok(format!(
+                "Imported 1 contact from {}, skipped 1 already here",
+                incoming.display()
+            )))
+        );
+        assert_eq!(app.contacts[2].name, "Dee Ray");
+
+        fs::write(&incoming, "BEGIN:VCARD\nFN:Eve\n").unwrap();
+        app.handle_key(KeyCode::Char('i').into());
+        testing::type_text(incoming.to_str().unwrap(), |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(
+            app.message,
+            Some(Err(format!(
+                "cannot read {}: line 1: the card is missing END:VCARD",
+                incoming.display()
+            )))
+        );
+        assert_eq!(app.contacts.len(), 4);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,252 @@
+//! 读写 vCard 3.0（RFC 2426）。
+//!
+//! 只处理联系人用到的几个属性：`FN`、`N`、`EMAIL`、`TEL`、`ORG` 和 `NOTE`，其余的属性和参数
+//! （例如 `TYPE=work`）在读取时忽略。同一个属性出现多次时使用第一个。读取时展开折叠的行，写入时
+//! 按规范折叠超过 75 个字节的行，换行符为 `\r\n`。
+
+use crate::contact::Contact;
+
+/// 折叠前一行的最大字节数。
+const LINE_LIMIT: usize = 75;
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ',' => escaped.push_str("\\,"),
+            ';' => escaped.push_str("\\;"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 按没有转义的 `;` 分成几部分，并去掉每一部分中的转义。
+fn components(value: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        let part = parts.last_mut().unwrap();
+        match c {
+            '\\' => match chars.next() {
+                Some('n' | 'N') => part.push('\n'),
+                Some(c) => part.push(c),
+                None => {}
+            },
+            ';' => parts.push(String::new()),
+            c => part.push(c),
+        }
+    }
+    parts
+}
+
+fn unescape(value: &str) -> String {
+    components(value).join(";")
+}
+
+/// 把一行折叠成不超过 [`LINE_LIMIT`] 个字节的几行，不拆开多字节的字符。
+fn fold(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > LINE_LIMIT {
+            out.push_str("\r\n ");
+            // 续行开头的空格也算在长度里。
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+pub fn write<'a>(contacts: impl IntoIterator<Item = &'a Contact>) -> String {
+    let mut out = String::new();
+    for contact in contacts {
+        // `N` 是必需的属性：最后一个词作为姓，前面的作为名。
+        let (given, family) = contact
+            .name
+            .trim()
+            .rsplit_once(' ')
+            .map_or(("", contact.name.trim()), |(given, family)| (given, family));
+        let mut lines = vec![
+            "BEGIN:VCARD".to_string(),
+            "VERSION:3.0".to_string(),
+            format!("FN:{}", escape(&contact.name)),
+            format!("N:{};{};;;", escape(family), escape(given)),
+        ];
+        for (property, value) in [
+            ("EMAIL", &contact.email),
+            ("TEL", &contact.phone),
+            ("ORG", &contact.company),
+            ("NOTE", &contact.note),
+        ] {
+            if !value.is_empty() {
+                lines.push(format!("{property}:{}", escape(value)));
+            }
+        }
+        lines.push("END:VCARD".into());
+        for line in lines {
+            fold(&line, &mut out);
+        }
+    }
+    out
+}
+
+/// 读取一个文件中所有的联系人，出错时带上行号。
+pub fn read(text: &str) -> Result<Vec<Contact>, String> {
+    // 先展开折叠的行，记下每一行开始的行号。
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some((_, previous))) => previous.push_str(rest),
+            _ if line.trim().is_empty() => {}
+            _ => lines.push((index + 1, line.to_string())),
+        }
+    }
+
+    let mut contacts = Vec::new();
+    let mut current: Option<(usize, Contact, Option<String>)> = None;
+    for (number, line) in lines {
+        let error = |message: &str| format!("line {number}: {message}");
+        let (head, value) = line
+            .split_once(':')
+            .ok_or_else(|| error("expected a property like FN:Sam Lee"))?;
+        // 去掉参数和分组前缀，例如 `item1.EMAIL;TYPE=work`。
+        let name = head.split(';').next().unwrap_or_default();
+        let name = name
+            .rsplit('.')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+        match (name.as_str(), &mut current) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VCARD") => {
+                current = Some((number, Contact::default(), None));
+            }
+            ("BEGIN", Some(_)) => return Err(error("a card starts before the previous one ends")),
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VCARD") => {
+                let (start, mut contact, structured) = current.take().unwrap();
+                if contact.name.is_empty() {
+                    contact.name = structured.unwrap_or_default();
+                }
+                if contact.name.trim().is_empty() {
+                    return Err(format!("line {start}: the card has no name"));
+                }
+                contacts.push(contact);
+            }
+            (_, None) => return Err(error("expected BEGIN:VCARD")),
+            (_, Some((_, contact, structured))) => {
+                let field = match name.as_str() {
+                    "FN" => &mut contact.name,
+                    "EMAIL" => &mut contact.email,
+                    "TEL" => &mut contact.phone,
+                    "NOTE" => &mut contact.note,
+                    "ORG" => {
+                        // 组织可以带部门，例如 `Acme;Sales`，只保留组织名。
+                        if contact.company.is_empty() {
+                            contact.company = components(value).swap_remove(0);
+                        }
+                        continue;
+                    }
+                    "N" => {
+                        // 没有 `FN` 时用 `N` 的名和姓拼出名字。
+                        let parts = components(value);
+                        let given = parts.get(1).map_or("", String::as_str);
+                        let name = [given, &parts[0]]
+                            .iter()
+                            .filter(|part| !part.is_empty())
+                            .copied()
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        structured.get_or_insert(name);
+                        continue;
+                    }
+                    _ => continue,
+                };
+                if field.is_empty() {
+                    *field = unescape(value);
+                }
+            }
+        }
+    }
+    if let Some((start, ..)) = current {
+        return Err(format!("line {start}: the card is missing END:VCARD"));
+    }
+    Ok(contacts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sam() -> Contact {
+        Contact {
+            name: "Sam Lee".into(),
+            email: "sam@example.com".into(),
+            phone: "+1 555 010 2030".into(),
+            company: "Acme, Inc.".into(),
+            note: "Met at the conference;\nlikes tea".into(),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = write([&sam()]);
+        assert_eq!(
+            text,
+            "BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Sam Lee\r\nN:Lee;Sam;;;\r\n\
+             EMAIL:sam@example.com\r\nTEL:+1 555 010 2030\r\nORG:Acme\\, Inc.\r\n\
+             NOTE:Met at the conference\\;\\nlikes tea\r\nEND:VCARD\r\n"
+        );
+        assert_eq!(read(&text), Ok(vec![sam()]));
+
+        let long = Contact {
+            name: "Ada".into(),
+            note: "会议".repeat(30),
+            ..Contact::default()
+        };
+        let text = write([&long]);
+        assert!(text
+            .lines()
+            .all(|line| line.trim_end_matches('\r').len() <= LINE_LIMIT));
+        assert!(text.contains("\r\n "));
+        assert_eq!(read(&text), Ok(vec![long]));
+    }
+
+    #[test]
+    fn read_other_writers() {
+        // 参数、分组前缀、小写的属性名，以及只有 `N` 没有 `FN` 的卡片。
+        let text = "begin:vcard\nVERSION:4.0\nN:Lovelace;Ada;;;\nitem1.EMAIL;TYPE=work:ada@\n example.org\n\
+                    TEL;TYPE=cell:555 0100\nTEL;TYPE=home:555 0199\nORG:Analytical Engines;R&D\n\
+                    PHOTO;ENCODING=b:AAAA\nend:vcard\n\nBEGIN:VCARD\nFN:Bob\nEND:VCARD\n";
+        let contacts = read(text).unwrap();
+        assert_eq!(
+            contacts[0],
+            Contact {
+                name: "Ada Lovelace".into(),
+                email: "ada@example.org".into(),
+                phone: "555 0100".into(),
+                company: "Analytical Engines".into(),
+                note: String::new(),
+            }
+        );
+        assert_eq!(contacts[1].name, "Bob");
+
+        assert_eq!(read("FN:Bob\n"), Err("line 1: expected BEGIN:VCARD".into()));
+        assert_eq!(
+            read("BEGIN:VCARD\nFN:Bob\n"),
+            Err("line 1: the card is missing END:VCARD".into())
+        );
+        assert_eq!(
+            read("BEGIN:VCARD\nEMAIL:a@b.c\nEND:VCARD\n"),
+            Err("line 1: the card has no name".into())
+        );
+        assert_eq!(
+            read("BEGIN:VCARD\nFN:Bob\ngarbage\nEND:VCARD\n"),
+            Err("line 3: expected a property like FN:Sam Lee".into())
+        );
+    }
+}
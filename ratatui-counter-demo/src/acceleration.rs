@@ -0,0 +1,89 @@
+//! 按住按键时的加速。
+//!
+//! 按住左右方向键时，终端会不断发送重复的按键事件。按住的时间越长，每次变化的步长越大（1、2、5、10），
+//! 松开按键（收到释放事件、按下其他键，或者两次事件的间隔超过 `HOLD_GAP`）后恢复为 1。
+
+use std::time::{Duration, Instant};
+
+use crossterm::event::KeyCode;
+
+/// 两次事件的间隔不超过这个值时，认为按键一直被按住。终端的自动重复间隔通常在 30~100 毫秒之间。
+const HOLD_GAP: Duration = Duration::from_millis(200);
+
+/// 连续重复次数达到第一个值后使用第二个值作为步长。
+const STEPS: [(u32, u8); 4] = [(0, 1), (5, 2), (15, 5), (30, 10)];
+
+#[derive(Debug, Default)]
+pub struct Acceleration {
+    /// 上一次按下的按键以及时间。
+    last: Option<(KeyCode, Instant)>,
+    /// 当前按键已经连续重复的次数。
+    repeats: u32,
+}
+
+impl Acceleration {
+    /// 记录一次按键，返回这次应使用的步长。
+    pub fn press(&mut self, code: KeyCode, now: Instant) -> u8 {
+        let held = self
+            .last
+            .is_some_and(|(last, at)| last == code && now.duration_since(at) <= HOLD_GAP);
+        self.repeats = if held { self.repeats + 1 } else { 0 };
+        self.last = Some((code, now));
+        self.step()
+    }
+
+    /// 按键被松开，步长恢复为 1。
+    pub fn release(&mut self) {
+        self.last = None;
+        self.repeats = 0;
+    }
+
+    /// 当前的步长。
+    pub fn step(&self) -> u8 {
+        STEPS
+            .iter()
+            .rev()
+            .find(|(repeats, _)| self.repeats >= *repeats)
+            .map_or(1, |(_, step)| *step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accelerate_while_held() {
+        let mut acceleration = Acceleration::default();
+        let start = Instant::now();
+        let steps: Vec<u8> = (0..32)
+            .map(|i| acceleration.press(KeyCode::Right, start + Duration::from_millis(50 * i)))
+            .collect();
+        assert_eq!(steps[..5], [1; 5]);
+        assert_eq!(steps[5], 2);
+        assert_eq!(steps[15], 5);
+        assert_eq!(steps[31], 10);
+
+        acceleration.release();
+        assert_eq!(acceleration.step(), 1);
+    }
+
+    #[test]
+    fn reset_after_gap_or_other_key() {
+        let mut acceleration = Acceleration::default();
+        let start = Instant::now();
+        for i in 0..10 {
+            acceleration.press(KeyCode::Right, start + Duration::from_millis(50 * i));
+        }
+        assert_eq!(acceleration.step(), 2);
+        assert_eq!(
+            acceleration.press(KeyCode::Left, start + Duration::from_millis(500)),
+            1
+        );
+        acceleration.press(KeyCode::Left, start + Duration::from_millis(550));
+        assert_eq!(
+            acceleration.press(KeyCode::Left, start + Duration::from_secs(5)),
+            1
+        );
+    }
+}
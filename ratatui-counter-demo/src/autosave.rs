@@ -0,0 +1,193 @@
+//! 定期自动保存：按配置的间隔把状态（计数器和历史）和当前会话写入档案，不用等到退出。
+//!
+//! 写文件的工作交给 [`Writer`] 的后台线程，主循环只复制一份快照，磁盘再慢也不会耽误绘制。任务按
+//! 提交的顺序执行；同步写入同样的文件之前先调用 [`Writer::flush`]，较早的快照就不会覆盖较新的。
+//! 间隔由配置文件中的 `autosave_secs` 设置，0 表示只在退出时保存。
+
+use std::{
+    sync::mpsc::{self, Receiver, Sender},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+
+use crate::{session::Session, state::State};
+
+/// 没有配置时两次自动保存之间的间隔。
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+
+type Job = Box<dyn FnOnce() -> Result<()> + Send>;
+
+/// 在后台线程中依次执行写入任务。
+#[derive(Debug)]
+pub struct Writer {
+    /// 丢弃后线程执行完剩下的任务就结束。
+    jobs: Option<Sender<Job>>,
+    errors: Receiver<String>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Writer {
+    pub fn spawn() -> Result<Self> {
+        let (jobs, receiver) = mpsc::channel::<Job>();
+        let (error_sender, errors) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("autosave".into())
+            .spawn(move || {
+                for job in receiver {
+                    if let Err(error) = job() {
+                        let _ = error_sender.send(format!("{error:#}"));
+                    }
+                }
+            })
+            .wrap_err("starting the autosave thread failed")?;
+        Ok(Self {
+            jobs: Some(jobs),
+            errors,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn submit(&self, job: impl FnOnce() -> Result<()> + Send + 'static) {
+        if let Some(jobs) = &self.jobs {
+            // 线程只会在恐慌后提前结束，那时恐慌钩子已经报告过了。
+            let _ = jobs.send(Box::new(job));
+        }
+    }
+
+    /// 等待已经提交的任务全部完成。
+    pub fn flush(&self) {
+        let (done, wait) = mpsc::channel();
+        self.submit(move || {
+            let _ = done.send(());
+            Ok(())
+        });
+        let _ = wait.recv();
+    }
+
+    /// 取出上次调用以来失败的任务的错误信息。
+    pub fn errors(&self) -> Vec<String> {
+        self.errors.try_iter().collect()
+    }
+
+    /// 执行完剩下的任务后结束线程，有失败的任务时返回第一个错误。
+    pub fn finish(mut self) -> Result<()> {
+        self.join();
+        match self.errors().into_iter().next() {
+            Some(error) => Err(eyre!("autosave failed: {error}")),
+            None => Ok(()),
+        }
+    }
+
+    fn join(&mut self) {
+        self.jobs = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Writer {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+/// 一次自动保存的内容。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub state: State,
+    /// 当前会话的名称和内容，没有打开会话时没有。
+    pub session: Option<(String, Session)>,
+}
+
+/// 决定什么时候保存：每隔 `interval` 一次，内容和上次相同时跳过。
+#[derive(Debug)]
+pub struct Autosave {
+    interval: Duration,
+    deadline: Instant,
+    /// 上次交给后台写入的快照。
+    saved: Option<Snapshot>,
+}
+
+impl Autosave {
+    /// 间隔为零时不自动保存，返回 `None`。
+    pub fn new(interval: Duration, now: Instant) -> Option<Self> {
+        (!interval.is_zero()).then(|| Self {
+            interval,
+            deadline: now + interval,
+            saved: None,
+        })
+    }
+
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// 到了保存的时间：返回需要写入的快照，和上次的相同时返回 `None`。
+    pub fn tick(&mut self, snapshot: Snapshot, now: Instant) -> Option<Snapshot> {
+        self.deadline = now + self.interval;
+        if self.saved.as_ref() == Some(&snapshot) {
+            return None;
+        }
+        self.saved = Some(snapshot.clone());
+        Some(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[test]
+    fn run_jobs_in_order() {
+        let writer = Writer::spawn().unwrap();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        for index in 0..3 {
+            let log = Arc::clone(&log);
+            writer.submit(move || {
+                thread::sleep(Duration::from_millis(5));
+                log.lock().unwrap().push(index);
+                Ok(())
+            });
+        }
+        writer.submit(|| Err(eyre!("disk full")));
+        writer.flush();
+        assert_eq!(*log.lock().unwrap(), [0, 1, 2]);
+        assert_eq!(writer.errors(), ["disk full"]);
+        assert!(writer.errors().is_empty());
+
+        writer.submit(|| Err(eyre!("read-only file system")));
+        assert_eq!(
+            writer.finish().unwrap_err().to_string(),
+            "autosave failed: read-only file system"
+        );
+    }
+
+    #[test]
+    fn save_only_changes() {
+        let now = Instant::now();
+        assert!(Autosave::new(Duration::ZERO, now).is_none());
+        let mut autosave = Autosave::new(Duration::from_secs(10), now).unwrap();
+        assert_eq!(autosave.deadline(), now + Duration::from_secs(10));
+
+        let snapshot = |counter| Snapshot {
+            state: State {
+                counter,
+                ..State::default()
+            },
+            session: None,
+        };
+        let later = now + Duration::from_secs(10);
+        assert_eq!(autosave.tick(snapshot(1), later), Some(snapshot(1)));
+        assert_eq!(autosave.deadline(), later + Duration::from_secs(10));
+        assert_eq!(autosave.tick(snapshot(1), later), None);
+        assert_eq!(autosave.tick(snapshot(3), later), Some(snapshot(3)));
+    }
+}
@@ -0,0 +1,202 @@
+//! 状态文件的备份：每次覆盖状态文件之前，把旧文件复制到旁边的 `backups` 目录，文件名是复制的时间，
+//! 只保留最新的几份（配置文件中的 `state_backups`）。
+//!
+//! ```text
+//! <状态目录>/profiles/<名称>/backups/2024-05-01_12-00-00.000.json
+//! ```
+//!
+//! 启动时用 `--restore <备份>` 换回一份备份，运行时也可以在备份选择界面中回滚。恢复同样会先备份
+//! 当时的状态，恢复错了还能再换回来。状态文件加密时备份是加密文件的原样副本，恢复时也原样复制，
+//! 启动时还没有解锁也能恢复。
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::NaiveDateTime;
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+
+use crate::{
+    state::{self, State},
+    vault::Key,
+};
+
+/// 没有配置时保留的备份数。
+pub const DEFAULT_KEEP: usize = 5;
+
+/// 备份的名称，按名称排序就是按时间排序。
+const NAME_FORMAT: &str = "%Y-%m-%d_%H-%M-%S%.3f";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backups {
+    state: PathBuf,
+    dir: PathBuf,
+    keep: usize,
+    key: Option<Key>,
+}
+
+impl Backups {
+    /// 状态文件 `state_path` 的备份，最多保留 `keep` 份，0 表示不备份。
+    pub fn new(state_path: &Path, keep: usize) -> Self {
+        Self {
+            state: state_path.to_path_buf(),
+            dir: state_path.with_file_name("backups"),
+            keep,
+            key: None,
+        }
+    }
+
+    /// 用 `key` 加密保存、解密读取。
+    pub fn with_key(mut self, key: Option<Key>) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// 列出已有的备份，最新的在前面。
+    pub fn list(&self) -> io::Result<Vec<String>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+        let mut names = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                names.extend(
+                    path.file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .map(str::to_string),
+                );
+            }
+        }
+        names.sort_by(|a, b| b.cmp(a));
+        Ok(names)
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.json"))
+    }
+
+    pub fn load(&self, name: &str) -> Result<State> {
+        match state::load_sealed(&self.path(name), self.key.as_ref())? {
+            Some(state) => Ok(state),
+            None => {
+                let names = self
+                    .list()
+                    .wrap_err_with(|| format!("listing {} failed", self.dir.display()))?;
+                Err(eyre!(
+                    "no backup named {name:?}, available: {}",
+                    if names.is_empty() {
+                        "none".to_string()
+                    } else {
+                        names.join(", ")
+                    }
+                ))
+            }
+        }
+    }
+
+    /// 备份现在的状态文件，然后写入 `state`。`now` 是备份的名称。
+    pub fn save(&self, state: &State, now: NaiveDateTime) -> Result<()> {
+        self.backup(now)?;
+        state::save_sealed(&self.state, state, self.key.as_ref())
+    }
+
+    /// 用名为 `name` 的备份替换状态文件。备份原样复制，不检查内容，需要时先用 [`Backups::load`]
+    /// 读一遍。
+    pub fn restore(&self, name: &str, now: NaiveDateTime) -> Result<()> {
+        let path = self.path(name);
+        if !path.exists() {
+            // 报告不存在，并列出已有的备份。
+            self.load(name)?;
+        }
+        // 先读出来：备份当前的状态时，要恢复的这一份可能因为太旧而被删除。
+        let contents =
+            fs::read(&path).wrap_err_with(|| format!("reading {} failed", path.display()))?;
+        self.backup(now)?;
+        fs::write(&self.state, contents)
+            .wrap_err_with(|| format!("writing {} failed", self.state.display()))
+    }
+
+    fn backup(&self, now: NaiveDateTime) -> Result<()> {
+        if self.keep == 0 || !self.state.exists() {
+            return Ok(());
+        }
+        let path = self.path(&now.format(NAME_FORMAT).to_string());
+        fs::create_dir_all(&self.dir)
+            .and_then(|()| fs::copy(&self.state, &path))
+            .wrap_err_with(|| format!("writing {} failed", path.display()))?;
+        let names = self
+            .list()
+            .wrap_err_with(|| format!("listing {} failed", self.dir.display()))?;
+        for name in names.iter().skip(self.keep) {
+            let path = self.path(name);
+            fs::remove_file(&path)
+                .wrap_err_with(|| format!("removing {} failed", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn at(second: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 5, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, second)
+            .unwrap()
+    }
+
+    fn state(counter: u8) -> State {
+        State {
+            counter,
+            ..State::default()
+        }
+    }
+
+    #[test]
+    fn keep_the_latest_backups() {
+        let dir = env::temp_dir().join(format!("counter-demo-backup-{}", std::process::id()));
+        let state_path = dir.join("state.json");
+        let backups = Backups::new(&state_path, 2);
+
+        // 第一次保存时还没有状态文件，不需要备份。
+        backups.save(&state(0), at(0)).unwrap();
+        assert!(backups.list().unwrap().is_empty());
+        for counter in 1..=3 {
+            backups.save(&state(counter), at(counter.into())).unwrap();
+        }
+        assert_eq!(
+            backups.list().unwrap(),
+            ["2024-05-01_12-00-03.000", "2024-05-01_12-00-02.000"]
+        );
+        assert_eq!(backups.load("2024-05-01_12-00-02.000").unwrap(), state(1));
+
+        backups.restore("2024-05-01_12-00-02.000", at(4)).unwrap();
+        assert_eq!(State::load(&state_path).unwrap(), state(1));
+        // 恢复之前的状态也留了一份。
+        assert_eq!(backups.load("2024-05-01_12-00-04.000").unwrap(), state(3));
+
+        assert_eq!(
+            backups.load("yesterday").unwrap_err().to_string(),
+            "no backup named \"yesterday\", available: 2024-05-01_12-00-04.000, \
+             2024-05-01_12-00-03.000"
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
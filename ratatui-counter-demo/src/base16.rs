@@ -0,0 +1,298 @@
+//! 读取 [base16](https://github.com/chriskempson/base16) 配色方案。
+//!
+//! 配色方案是 `themes` 目录中的 YAML 文件（`.yaml` 或 `.yml`），文件名（不含扩展名）就是方案的
+//! 名称。同时支持最初的格式（顶层的 `scheme` 和 `base00` … `base0F`）和 tinted-theming 的格式
+//! （`name` 加上 `palette` 下的 `base00` … `base0F`）。这两种格式只用到 YAML 中“键: 值”这一小部分，
+//! 所以这里直接逐行解析，不依赖完整的 YAML 库。
+//!
+//! [`Scheme::theme`] 按 base16 的样式约定把 16 种颜色对应到应用程序的语义化样式上。
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use ratatui::{
+    style::{Color, Style, Stylize},
+    symbols::border,
+};
+use ratatui_common::capabilities;
+
+use crate::theme::{Symbols, Theme};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scheme {
+    /// 文件名（不含扩展名），保存在状态文件中用来找回这个方案。
+    pub slug: String,
+    /// 方案中写的名称，没有写时使用 `slug`。
+    pub name: String,
+    pub author: String,
+    /// `base00` … `base0F`。
+    pub palette: [Color; 16],
+}
+
+impl Scheme {
+    pub fn parse(slug: &str, text: &str) -> Result<Self, String> {
+        let mut name = None;
+        let mut author = String::new();
+        let mut palette = [None; 16];
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = unquote(value.trim());
+            match key.trim() {
+                "scheme" | "name" => name = Some(value.to_string()),
+                "author" => author = value.to_string(),
+                key => {
+                    let Some(index) = key
+                        .strip_prefix("base0")
+                        .and_then(|digit| u8::from_str_radix(digit, 16).ok())
+                        .filter(|_| key.len() == 6)
+                    else {
+                        continue;
+                    };
+                    let color = parse_color(value)
+                        .ok_or_else(|| format!("invalid color {value:?} for {key}"))?;
+                    palette[usize::from(index)] = Some(color);
+                }
+            }
+        }
+        let mut colors = [Color::Reset; 16];
+        for (index, color) in palette.into_iter().enumerate() {
+            colors[index] = color.ok_or_else(|| format!("missing base0{index:X}"))?;
+        }
+        Ok(Self {
+            slug: slug.to_string(),
+            name: name.unwrap_or_else(|| slug.to_string()),
+            author,
+            palette: colors,
+        })
+    }
+
+    /// `base0X` 的颜色。
+    pub fn base(&self, index: usize) -> Color {
+        self.palette[index]
+    }
+
+    /// 背景用 `base00`、文字用 `base05`、边框用注释的颜色 `base03`，标题用函数的蓝色 `base0D`，
+    /// 按键提示用 `base0C`，数值用数字的颜色 `base09`，警告用 `base08`（通常是红色）。
+    pub fn theme(&self) -> Theme {
+        Theme {
+            base: Style::new().fg(self.base(0x05)).bg(self.base(0x00)),
+            border: Style::new().fg(self.base(0x03)),
+            title: Style::new().fg(self.base(0x0D)).bold(),
+            key: Style::new().fg(self.base(0x0C)).bold(),
+            value: Style::new().fg(self.base(0x09)),
+            warning: Style::new().fg(self.base(0x08)).bold(),
+            border_set: border::THICK,
+            symbols: Symbols::UNICODE,
+        }
+    }
+
+    /// 写成最初的 base16 格式。
+    pub fn to_yaml(&self) -> String {
+        let mut yaml = format!("scheme: {:?}\nauthor: {:?}\n", self.name, self.author);
+        for (index, &color) in self.palette.iter().enumerate() {
+            yaml.push_str(&format!("base0{index:X}: \"{}\"\n", hex(color)));
+        }
+        yaml
+    }
+}
+
+/// `1d1f21` 这样的十六进制颜色，不是 RGB 的颜色取调色板中的近似值。
+fn hex(color: Color) -> String {
+    let (r, g, b) = match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        color => capabilities::to_indexed(color).map_or((0, 0, 0), capabilities::indexed_to_rgb),
+    };
+    format!("{r:02x}{g:02x}{b:02x}")
+}
+
+/// 去掉值两边的引号；没有引号时去掉行尾的注释。
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(rest) = value.strip_prefix(quote) {
+            return rest.split(quote).next().unwrap_or(rest);
+        }
+    }
+    value.split(" #").next().unwrap_or(value).trim()
+}
+
+/// `1d1f21` 或 `#1d1f21`。
+fn parse_color(value: &str) -> Option<Color> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if hex.len() != 6 {
+        return None;
+    }
+    let rgb = u32::from_str_radix(hex, 16).ok()?;
+    Some(Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+}
+
+/// 读取 `dir` 中的所有配色方案，按名称排序。目录不存在时没有方案。
+pub fn discover(dir: &Path) -> Result<Vec<Scheme>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(error).wrap_err_with(|| format!("reading {} failed", dir.display()))
+        }
+    };
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "yaml" || extension == "yml")
+        {
+            paths.push(path);
+        }
+    }
+    let mut schemes = paths
+        .iter()
+        .map(|path| load(path))
+        .collect::<Result<Vec<_>>>()?;
+    schemes.sort_by_key(|scheme| scheme.name.to_lowercase());
+    Ok(schemes)
+}
+
+fn load(path: &Path) -> Result<Scheme> {
+    let text =
+        fs::read_to_string(path).wrap_err_with(|| format!("reading {} failed", path.display()))?;
+    let slug = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Scheme::parse(&slug, &text).map_err(|error| eyre!("parsing {} failed: {error}", path.display()))
+}
+
+/// 把配色方案写入 `dir` 中的 `<slug>.yaml`，已有的文件会被覆盖。
+pub fn save(dir: &Path, scheme: &Scheme) -> Result<PathBuf> {
+    let path = dir.join(format!("{}.yaml", scheme.slug));
+    fs::create_dir_all(dir)
+        .and_then(|()| fs::write(&path, scheme.to_yaml()))
+        .wrap_err_with(|| format!("writing {} failed", path.display()))?;
+    Ok(path)
+}
+
+/// `dir` 中名为 `slug` 的配色方案。
+pub fn find(dir: &Path, slug: &str) -> Result<Option<Scheme>> {
+    Ok(discover(dir)?
+        .into_iter()
+        .find(|scheme| scheme.slug == slug))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    const TOMORROW_NIGHT: &str = r##"
+scheme: "Tomorrow Night" # 注释
+author: "Chris Kempson (http://chriskempson.com)"
+base00: "1d1f21"
+base01: "282a2e"
+base02: "373b41"
+base03: "969896"
+base04: "b4b7b4"
+base05: "c5c8c6"
+base06: "e0e0e0"
+base07: "ffffff"
+base08: "cc6666"
+base09: "de935f"
+base0A: "f0c674"
+base0B: "b5bd68"
+base0C: "8abeb7"
+base0D: "81a2be"
+base0E: "b294bb"
+base0F: "a3685a"
+"##;
+
+    #[test]
+    fn parse_both_formats() {
+        let scheme = Scheme::parse("tomorrow-night", TOMORROW_NIGHT).unwrap();
+        assert_eq!(scheme.name, "Tomorrow Night");
+        assert_eq!(scheme.author, "Chris Kempson (http://chriskempson.com)");
+        assert_eq!(scheme.base(0x0F), Color::Rgb(0xa3, 0x68, 0x5a));
+        assert_eq!(
+            scheme.theme().warning.fg,
+            Some(Color::Rgb(0xcc, 0x66, 0x66))
+        );
+
+        let tinted = format!(
+            "system: \"base16\"\nname: 'Tomorrow Night'\npalette:\n{}",
+            TOMORROW_NIGHT
+                .lines()
+                .filter(|line| line.starts_with("base"))
+                .map(|line| format!("  {}\n", line.replace(": \"", ": \"#")))
+                .collect::<String>()
+        );
+        assert_eq!(
+            Scheme::parse("tomorrow-night", &tinted).unwrap().palette,
+            scheme.palette
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Scheme::parse("x", "base00: \"1d1f21\""),
+            Err("missing base01".to_string())
+        );
+        assert_eq!(
+            Scheme::parse("x", "base0A: red"),
+            Err("invalid color \"red\" for base0A".to_string())
+        );
+    }
+
+    #[test]
+    fn discover_and_find() {
+        let dir = env::temp_dir().join(format!("counter-demo-themes-{}", std::process::id()));
+        assert!(discover(&dir).unwrap().is_empty());
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("tomorrow-night.yaml"), TOMORROW_NIGHT).unwrap();
+        fs::write(
+            dir.join("plain.yml"),
+            TOMORROW_NIGHT.replace("Tomorrow Night", "Another"),
+        )
+        .unwrap();
+        fs::write(dir.join("README.md"), "not a scheme").unwrap();
+
+        let names: Vec<_> = discover(&dir)
+            .unwrap()
+            .into_iter()
+            .map(|scheme| scheme.name)
+            .collect();
+        assert_eq!(names, ["Another", "Tomorrow Night"]);
+        assert_eq!(
+            find(&dir, "plain").unwrap().map(|scheme| scheme.name),
+            Some("Another".to_string())
+        );
+        assert_eq!(find(&dir, "missing").unwrap(), None);
+
+        let mut copy = find(&dir, "tomorrow-night").unwrap().unwrap();
+        copy.slug = "copy".into();
+        copy.palette[0] = Color::Indexed(196);
+        save(&dir, &copy).unwrap();
+        let saved = find(&dir, "copy").unwrap().unwrap();
+        assert_eq!(saved.name, "Tomorrow Night");
+        assert_eq!(saved.base(0x00), Color::Rgb(0xff, 0, 0));
+        assert_eq!(saved.palette[1..], copy.palette[1..]);
+
+        fs::write(dir.join("broken.yaml"), "base00: nope").unwrap();
+        assert!(discover(&dir)
+            .unwrap_err()
+            .to_string()
+            .contains("broken.yaml failed"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
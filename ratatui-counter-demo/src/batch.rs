@@ -0,0 +1,178 @@
+//! 批处理：`--batch <FILE>` 不打开终端，按顺序执行文件中的命令，执行完就退出。
+//!
+//! 每行一个命令，`#` 之后是注释：
+//!
+//! ```text
+//! increment        # 动作，名称和配置文件 [keys] 中的相同
+//! key g x          # 依次按下按键，和在界面中按下一样经过按键绑定
+//! set 5            # 控制接口的命令（set、add、inc、dec）
+//! render           # 把当前画面追加到 --frames 指定的文件
+//! ```
+//!
+//! 画面绘制在 `--frame-size` 大小的虚拟终端中，只输出文字，不带颜色，每帧之后空一行。批处理关闭了
+//! 动画并且不保存状态，同样的命令总是得到同样的画面，可以用来生成截图或者检查界面。
+//!
+//! `repl` 子命令使用同样的命令，在提示符下逐行输入，执行的也是同一个 `App::execute`，`render` 把画面
+//! 直接输出到标准输出。
+
+use std::{fs, io, path::Path, str::FromStr};
+
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use crossterm::event::KeyCode;
+use ratatui::{buffer::Buffer, layout::Size};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    control::Command,
+    keymap::{self, Action},
+};
+
+/// 一行命令。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    Action(Action),
+    Keys(Vec<KeyCode>),
+    Control(Command),
+    Render,
+}
+
+impl FromStr for Instruction {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim();
+        if let Some(action) = Action::from_name(line) {
+            return Ok(Instruction::Action(action));
+        }
+        if line == "render" {
+            return Ok(Instruction::Render);
+        }
+        if let Some(keys) = line.strip_prefix("key ") {
+            return keymap::parse_keys(keys).map(Instruction::Keys);
+        }
+        match line.parse()? {
+            Command::Watch => Err("watch is only available on the control interface".into()),
+            command => Ok(Instruction::Control(command)),
+        }
+    }
+}
+
+/// 批处理文件中的一行，出错时报告行号。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub number: usize,
+    pub instruction: Instruction,
+}
+
+/// 解析一行，去掉注释后是空行时返回 `None`。
+pub fn parse_line(line: &str) -> Result<Option<Instruction>, String> {
+    let line = line.split('#').next().unwrap_or_default().trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+    line.parse().map(Some)
+}
+
+/// 解析批处理文件的内容，跳过空行和注释。
+pub fn parse(text: &str) -> Result<Vec<Line>> {
+    let mut lines = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        if let Some(instruction) =
+            parse_line(line).map_err(|error| eyre!("line {}: {error}", index + 1))?
+        {
+            lines.push(Line {
+                number: index + 1,
+                instruction,
+            });
+        }
+    }
+    Ok(lines)
+}
+
+pub fn load(path: &Path) -> Result<Vec<Line>> {
+    let text =
+        fs::read_to_string(path).wrap_err_with(|| format!("reading {} failed", path.display()))?;
+    parse(&text).wrap_err_with(|| format!("invalid batch file {}", path.display()))
+}
+
+/// 解析 `--frame-size` 的值，例如 `80x24`。
+pub fn parse_size(text: &str) -> Result<Size, String> {
+    let (width, height) = text
+        .split_once('x')
+        .ok_or_else(|| format!("{text:?} is not WIDTHxHEIGHT"))?;
+    let parse = |value: &str| match value.parse() {
+        Ok(value) if value > 0 => Ok(value),
+        _ => Err(format!("{value:?} is not a positive size")),
+    };
+    Ok(Size::new(parse(width)?, parse(height)?))
+}
+
+/// 把画面作为文字写入 `out`，每行去掉末尾的空格，最后空一行。
+pub fn write_frame(buffer: &Buffer, out: &mut impl io::Write) -> io::Result<()> {
+    let area = buffer.area;
+    for y in area.top()..area.bottom() {
+        let mut row = String::new();
+        // 双宽字符之后的格子只是占位，跳过。
+        let mut skip = 0;
+        for x in area.left()..area.right() {
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            let symbol = buffer.get(x, y).symbol();
+            skip = symbol.width().saturating_sub(1);
+            row.push_str(symbol);
+        }
+        writeln!(out, "{}", row.trim_end())?;
+    }
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{layout::Rect, style::Style};
+
+    use super::*;
+
+    #[test]
+    fn parse_batch_file() {
+        let lines = parse("# 截图\nincrement\n\nkey g x  # 和弦\nset 5\nrender\n").unwrap();
+        assert_eq!(
+            lines
+                .into_iter()
+                .map(|line| (line.number, line.instruction))
+                .collect::<Vec<_>>(),
+            [
+                (2, Instruction::Action(Action::Increment)),
+                (
+                    4,
+                    Instruction::Keys(vec![KeyCode::Char('g'), KeyCode::Char('x')])
+                ),
+                (5, Instruction::Control(Command::Set(5))),
+                (6, Instruction::Render),
+            ]
+        );
+        assert_eq!(
+            parse("increment\njump").unwrap_err().to_string(),
+            "line 2: unknown command \"jump\""
+        );
+        assert_eq!(
+            parse("watch").unwrap_err().to_string(),
+            "line 1: watch is only available on the control interface"
+        );
+        assert_eq!(parse_size("40x10"), Ok(Size::new(40, 10)));
+        assert_eq!(parse_size("40"), Err("\"40\" is not WIDTHxHEIGHT".into()));
+    }
+
+    #[test]
+    fn write_frame_as_text() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 6, 2));
+        buffer.set_string(0, 0, "计数 3", Style::default());
+        let mut out = Vec::new();
+        write_frame(&buffer, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "计数 3\n\n\n");
+    }
+}
@@ -0,0 +1,148 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use ratatui::layout::Size;
+use ratatui_common::recording;
+
+use crate::{batch, profile, theme::ThemeName};
+
+/// 计数器应用程序的命令行参数。
+#[derive(Debug, Default, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// 回放之前录制的事件日志（JSON），而不是读取真实终端的事件。回放完毕后继续读取终端。
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+
+    /// 回放速度的倍数，例如 `2` 表示两倍速、`0.5` 表示慢放。
+    #[arg(long, value_name = "FACTOR", default_value_t = 1.0, value_parser = recording::parse_speed)]
+    pub replay_speed: f64,
+
+    /// 把收到的所有事件连同时间戳录制到文件中，之后可以用 `--replay` 回放。
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+
+    /// 开启控制接口，外部工具可以通过它读取和修改计数器。Unix 上是套接字文件的路径，
+    /// Windows 上是命名管道的名称（可以省略 `\\.\pipe\` 前缀）。
+    #[arg(long, value_name = "ADDRESS")]
+    pub control: Option<String>,
+
+    /// 作为守护进程运行：不打开终端，只保存计数器的状态，通过 `--control` 指定的控制接口提供服务。
+    #[arg(long, requires = "control")]
+    pub daemon: bool,
+
+    /// 连接到 `--daemon` 启动的守护进程，界面显示并修改守护进程中的计数器，状态由守护进程保存。
+    #[arg(long, value_name = "ADDRESS", conflicts_with_all = ["daemon", "gossip"])]
+    pub attach: Option<String>,
+
+    /// 作为 SSH 服务器监听这个地址（例如 `127.0.0.1:2222`），每个客户端得到一个独立的计数器，
+    /// 而不是在本地终端中运行。
+    #[cfg(feature = "ssh")]
+    #[arg(long, value_name = "ADDRESS")]
+    pub ssh: Option<String>,
+
+    /// 作为 Telnet 服务器监听这个地址（例如 `0.0.0.0:2323`），把画面发送给每个连接的客户端。
+    /// 连接没有加密也没有认证。
+    #[arg(long, value_name = "ADDRESS")]
+    pub telnet: Option<String>,
+
+    /// 和其他实例共享计数器：监听这个地址（例如 `127.0.0.1:7000`）接收其他实例的状态，
+    /// 其他实例用 `--peer` 指向它。共享时计数器从所有实例合并后的值开始，不读取保存的值。
+    #[arg(long, value_name = "ADDRESS")]
+    pub gossip: Option<String>,
+
+    /// 共享计数器的另一个实例的地址，可以重复指定。只需要一个，其余实例的地址会自动传播过来。
+    #[arg(long = "peer", value_name = "ADDRESS", requires = "gossip")]
+    pub peers: Vec<String>,
+
+    /// 配色主题。不指定时使用在主题选择界面中选择的主题，没有选择过时使用 `default`。
+    #[arg(long, value_enum)]
+    pub theme: Option<ThemeName>,
+
+    /// 不使用任何颜色和粗体等样式，只保留布局。设置了 `NO_COLOR` 环境变量时同样生效。
+    #[arg(long)]
+    pub plain: bool,
+
+    /// 不进行全屏绘制，只在状态变化时输出一行描述，便于屏幕阅读器使用。
+    #[arg(long)]
+    pub line_output: bool,
+
+    /// 不使用备用屏幕，直接在主屏幕中绘制，退出后最后一帧留在滚动历史中。
+    #[arg(long)]
+    pub no_alt_screen: bool,
+
+    /// 退出时把最后的计数值输出到标准输出，便于在脚本中使用：`N=$(ratatui-counter-demo --print-result)`。
+    /// 界面改为画到标准错误。
+    #[arg(long)]
+    pub print_result: bool,
+
+    /// 用退出码说明退出的原因：选择退出时为 0，被 Ctrl-C 或信号中止时为 1。
+    #[arg(long)]
+    pub exit_code: bool,
+
+    /// 把最后的计数值作为退出码，便于在 shell 的条件语句中使用。
+    #[arg(long, conflicts_with = "exit_code")]
+    pub exit_with_value: bool,
+
+    /// 不打开终端，按顺序执行文件中的命令后退出，不保存状态。命令的格式见 `batch` 模块，
+    /// 例如 `increment`、`key g x`、`set 5` 和 `render`。
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["daemon", "line_output", "attach", "replay"])]
+    pub batch: Option<PathBuf>,
+
+    /// 批处理中的 `render` 把画面以文字追加到这个文件。
+    #[arg(long, value_name = "FILE", requires = "batch")]
+    pub frames: Option<PathBuf>,
+
+    /// 批处理和 `repl` 子命令中 `render` 绘制画面时虚拟终端的大小。
+    #[arg(long, value_name = "WIDTHxHEIGHT", default_value = "80x24", value_parser = batch::parse_size)]
+    pub frame_size: Size,
+
+    /// 界面以外的输出（`--paths`、`--print-result` 和错误）都输出为一行 JSON，便于其他工具读取。
+    #[arg(long)]
+    pub json: bool,
+
+    /// 自定义窗口标题，支持中日韩等双宽字符，过长时按显示宽度截断。
+    #[arg(long)]
+    pub title: Option<String>,
+
+    /// 使用的档案。每个档案有独立的状态文件和配置文件，位置见 `--paths`。
+    #[arg(long, value_name = "NAME", default_value = profile::DEFAULT_PROFILE, value_parser = profile::parse_name)]
+    pub profile: String,
+
+    /// 配置文件（TOML），默认使用当前档案的配置文件。
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// 启动前用当前档案中名为 BACKUP 的备份替换状态文件。名称就是备份的时间，可以在备份选择界面
+    /// （`b`）中看到；名称不存在时会列出所有的备份。
+    #[arg(long, value_name = "BACKUP")]
+    pub restore: Option<String>,
+
+    /// 用口令加密状态文件和会话：启动时设置口令，之后每次保存都加密。已经加密的状态文件不需要这个
+    /// 选项，启动时总是先要求输入口令。需要启用 `encrypt` 功能。
+    #[arg(long, conflicts_with_all = ["daemon", "line_output", "attach"])]
+    pub encrypt: bool,
+
+    /// 显示配置、状态、缓存和数据文件的位置，然后退出。
+    #[arg(long)]
+    pub paths: bool,
+
+    /// 重新显示第一次运行时的教程，即使已经完成过。
+    #[arg(long)]
+    pub tutorial: bool,
+
+    /// 减少动态效果：禁用所有动画，等同于配置文件中的 `reduced_motion = true`。
+    #[arg(long)]
+    pub reduced_motion: bool,
+}
+
+/// 子命令。没有子命令时打开全屏界面。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// 不打开全屏界面，在提示符下逐行输入命令，命令和 `--batch` 的相同（`inc`、`set 5`、`key Right`、
+    /// `render` ……）。退出时和平时一样保存状态。
+    Repl,
+}
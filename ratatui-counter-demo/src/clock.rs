@@ -0,0 +1,121 @@
+//! 上边框左侧的时钟，每秒更新一次。
+//!
+//! 在配置文件的 `[clock]` 中开启，可以设置格式和时区：
+//!
+//! ```toml
+//! [clock]
+//! # chrono 的 strftime 格式，默认是 `%H:%M:%S`
+//! format = "%m-%d %H:%M"
+//! # IANA 时区名称，例如 `Asia/Shanghai`，默认是 `local`（系统时区）
+//! timezone = "Europe/Berlin"
+//! ```
+
+use std::time::{Duration, Instant};
+
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Local, Utc,
+};
+use chrono_tz::Tz;
+use color_eyre::{eyre::eyre, Result};
+
+use crate::config::ClockConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Zone {
+    Local,
+    Named(Tz),
+}
+
+#[derive(Debug)]
+pub struct Clock {
+    format: String,
+    zone: Zone,
+    now: DateTime<Utc>,
+    /// 下一次更新的时间，对齐到整秒。
+    next_tick: Instant,
+}
+
+impl Clock {
+    pub fn new(config: &ClockConfig) -> Result<Self> {
+        if StrftimeItems::new(&config.format).any(|item| item == Item::Error) {
+            return Err(eyre!("invalid clock format {:?}", config.format));
+        }
+        let zone = if config.timezone == "local" {
+            Zone::Local
+        } else {
+            Zone::Named(
+                config
+                    .timezone
+                    .parse()
+                    .map_err(|_| eyre!("unknown timezone {:?}", config.timezone))?,
+            )
+        };
+        let mut clock = Self {
+            format: config.format.clone(),
+            zone,
+            now: Utc::now(),
+            next_tick: Instant::now(),
+        };
+        clock.tick(Instant::now(), Utc::now());
+        Ok(clock)
+    }
+
+    pub fn deadline(&self) -> Instant {
+        self.next_tick
+    }
+
+    /// 更新显示的时间，下一次更新安排在下一个整秒。
+    pub fn tick(&mut self, instant: Instant, now: DateTime<Utc>) {
+        self.now = now;
+        let to_next_second =
+            1_000_000_000 - u64::from(now.timestamp_subsec_nanos() % 1_000_000_000);
+        self.next_tick = instant + Duration::from_nanos(to_next_second);
+    }
+
+    pub fn text(&self) -> String {
+        match self.zone {
+            Zone::Local => self
+                .now
+                .with_timezone(&Local)
+                .format(&self.format)
+                .to_string(),
+            Zone::Named(tz) => self.now.with_timezone(&tz).format(&self.format).to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn config(format: &str, timezone: &str) -> ClockConfig {
+        ClockConfig {
+            format: format.into(),
+            timezone: timezone.into(),
+        }
+    }
+
+    #[test]
+    fn format_in_timezone() {
+        let mut clock = Clock::new(&config("%H:%M %Z", "Europe/Berlin")).unwrap();
+        let start = Instant::now();
+        // 夏令时前后使用不同的偏移。
+        clock.tick(start, Utc.with_ymd_and_hms(2024, 1, 15, 11, 0, 0).unwrap());
+        assert_eq!(clock.text(), "12:00 CET");
+        clock.tick(start, Utc.with_ymd_and_hms(2024, 7, 15, 11, 0, 0).unwrap());
+        assert_eq!(clock.text(), "13:00 CEST");
+
+        let half = Utc.timestamp_opt(1_700_000_000, 250_000_000).unwrap();
+        clock.tick(start, half);
+        assert_eq!(clock.deadline() - start, Duration::from_millis(750));
+    }
+
+    #[test]
+    fn invalid_config() {
+        assert!(Clock::new(&config("%Q", "local")).is_err());
+        assert!(Clock::new(&config("%H", "Mars/Olympus")).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//! 从 TOML 文件加载的用户配置。
+//!
+//! ```toml
+//! # 禁用所有动画，状态变化立即生效
+//! reduced_motion = true
+//! # 和弦（例如 `g g`）中两次按键之间允许的最长间隔，单位为毫秒
+//! chord_timeout_ms = 1000
+//! # 计数器的最大值，默认是 2
+//! counter_max = 100
+//! # 计数器向上到达这些值时发送桌面通知（需要启用 `notify` 功能）
+//! milestones = [10, 50, 100]
+//! # 切换界面时的过渡效果：slide（默认）、fade 或 instant
+//! transition = "fade"
+//! # 每隔多少秒在后台自动保存状态和当前会话，默认 60，0 表示只在退出时保存
+//! autosave_secs = 30
+//! # 覆盖状态文件之前保留的备份数，默认 5，0 表示不备份
+//! state_backups = 10
+//! # 定时执行的动作，格式见 `schedule` 模块
+//! schedule = ["every 10m increment", "at 17:00 reset"]
+//!
+//! # 限制每秒处理的输入事件，见 `rate_limit` 模块
+//! [rate_limit]
+//! events_per_sec = 30
+//!
+//! # 在上边框显示时钟，格式和时区见 `clock` 模块
+//! [clock]
+//! format = "%H:%M"
+//!
+//! # 重新绑定按键，名称见 `keymap` 模块，按键序列用空格分隔
+//! [keys]
+//! reset = "r"
+//! history = "g h"
+//!
+//! # 边框样式：thick、rounded、double、plain 或 ascii。键是界面的名称（见 `theme::Widget`），
+//! # `default` 用于没有单独设置的界面
+//! [borders]
+//! default = "rounded"
+//! help = "double"
+//!
+//! # 符号集：unicode 或 ascii。终端不支持 Unicode 时总是使用 ascii
+//! [symbols]
+//! tutorial = "ascii"
+//! ```
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use color_eyre::{eyre::WrapErr, Result};
+use serde::Deserialize;
+
+use crate::{
+    rate_limit::RateLimitConfig,
+    theme::{BorderStyle, SymbolSet, Widget},
+    transition,
+};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// 减少动态效果：禁用闪烁等动画。
+    pub reduced_motion: bool,
+    /// 和弦中两次按键之间允许的最长间隔（毫秒），默认 1000。
+    pub chord_timeout_ms: Option<u64>,
+    /// 计数器的最大值，超过它时报告溢出。
+    pub counter_max: Option<u8>,
+    /// 到达时发送桌面通知的计数值。
+    pub milestones: Vec<u8>,
+    /// 切换界面时的过渡效果。
+    pub transition: transition::Kind,
+    /// 自动保存的间隔（秒），默认 60，0 表示不自动保存。
+    pub autosave_secs: Option<u64>,
+    /// 保留的状态文件备份数，默认 5，0 表示不备份。
+    pub state_backups: Option<usize>,
+    /// 定时执行的动作，例如 `every 10m increment`。
+    pub schedule: Vec<String>,
+    /// 输入事件的限流，没有这一节时不限制。
+    pub rate_limit: Option<RateLimitConfig>,
+    /// 上边框中的时钟，没有这一节时不显示。
+    pub clock: Option<ClockConfig>,
+    /// 操作名称到按键序列，替换操作默认的绑定。
+    pub keys: BTreeMap<String, String>,
+    /// 每种界面的边框样式。
+    pub borders: BTreeMap<Widget, BorderStyle>,
+    /// 每种界面的符号集。
+    pub symbols: BTreeMap<Widget, SymbolSet>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClockConfig {
+    /// strftime 格式。
+    pub format: String,
+    /// IANA 时区名称，或者 `local`。
+    pub timezone: String,
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            format: "%H:%M:%S".into(),
+            timezone: "local".into(),
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .wrap_err_with(|| format!("reading config file {} failed", path.display()))?;
+        toml::from_str(&text)
+            .wrap_err_with(|| format!("parsing config file {} failed", path.display()))
+    }
+}
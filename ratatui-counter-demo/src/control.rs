@@ -0,0 +1,324 @@
+//! 外部工具控制应用程序的接口：Unix 上是套接字，Windows 上是命名管道（见 `named_pipe` 模块），
+//! 两者使用同一个文本协议。
+//!
+//! 每行一个命令，应用程序对每个命令回复一行（`watch` 除外）：
+//!
+//! ```text
+//! get          -> ok 3
+//! set 5        -> ok 5
+//! add -2       -> ok 3
+//! inc          -> ok 4
+//! dec          -> ok 3
+//! watch        -> ok 3、ok 4 ……（先回复当前值，之后计数器每次变化回复一行，直到连接关闭）
+//! reset        -> ok 0
+//! quit         -> ok 0
+//! set 300      -> error 300 is outside 0..=10
+//! ```
+//!
+//! 成功时回复 `ok` 和执行命令后的计数值，失败时回复 `error` 和原因。连接本身不保存状态，
+//! 命令作为事件进入主循环，和按键一样按顺序处理。
+
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+    str::FromStr,
+    sync::mpsc::{self, Sender},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::event::Event;
+
+/// 控制接口的命令。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Command {
+    Get,
+    Set(u8),
+    /// 增加（或减少）任意的量，结果必须在取值范围内。
+    Add(i16),
+    Increment,
+    Decrement,
+    Reset,
+    Quit,
+    /// 订阅计数器的变化。
+    Watch,
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let command = match (words.next(), words.next()) {
+            (Some("get"), None) => Command::Get,
+            (Some("set"), Some(value)) => Command::Set(
+                value
+                    .parse()
+                    .map_err(|_| format!("{value:?} is not a counter value"))?,
+            ),
+            (Some("add"), Some(delta)) => Command::Add(
+                delta
+                    .parse()
+                    .map_err(|_| format!("{delta:?} is not a number"))?,
+            ),
+            (Some("inc"), None) => Command::Increment,
+            (Some("dec"), None) => Command::Decrement,
+            (Some("reset"), None) => Command::Reset,
+            (Some("quit"), None) => Command::Quit,
+            (Some("watch"), None) => Command::Watch,
+            _ => return Err(format!("unknown command {:?}", line.trim())),
+        };
+        if words.next().is_some() {
+            return Err(format!("unknown command {:?}", line.trim()));
+        }
+        Ok(command)
+    }
+}
+
+/// 执行命令的结果：成功时是执行后的计数值。
+pub type Reply = Result<u8, String>;
+
+/// 来自控制连接的命令，应用程序处理后通过 [`Request::respond`] 回复。
+///
+/// 录制事件时只保存命令本身，回放的命令没有连接可以回复。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Request {
+    pub command: Command,
+    #[serde(skip)]
+    reply: Option<Sender<Reply>>,
+}
+
+impl Request {
+    pub fn respond(self, reply: Reply) {
+        if let Some(tx) = self.reply {
+            // 连接已经关闭时没有人需要回复。
+            let _ = tx.send(reply);
+        }
+    }
+
+    /// 回复 [`Command::Watch`]：先回复当前值，返回之后用来发送变化的通道。回放的命令返回 `None`。
+    pub fn subscribe(self, counter: u8) -> Option<Sender<Reply>> {
+        let tx = self.reply?;
+        tx.send(Ok(counter)).ok()?;
+        Some(tx)
+    }
+}
+
+impl From<Command> for Request {
+    fn from(command: Command) -> Self {
+        Self {
+            command,
+            reply: None,
+        }
+    }
+}
+
+impl PartialEq for Request {
+    fn eq(&self, other: &Self) -> bool {
+        self.command == other.command
+    }
+}
+
+impl Eq for Request {}
+
+struct ReplyLine(Reply);
+
+impl fmt::Display for ReplyLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Ok(counter) => write!(f, "ok {counter}"),
+            Err(reason) => write!(f, "error {reason}"),
+        }
+    }
+}
+
+/// 处理一个连接：逐行读取命令，交给主循环执行，再写回结果。连接关闭或应用程序退出时返回。
+///
+/// `watch` 命令之后连接一直用来发送变化，不再读取命令。
+pub fn serve(
+    reader: impl BufRead,
+    mut writer: impl Write,
+    events: &Sender<io::Result<Event>>,
+) -> io::Result<()> {
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let command = match line.parse() {
+            Ok(command) => command,
+            Err(reason) => {
+                writeln!(writer, "{}", ReplyLine(Err(reason)))?;
+                writer.flush()?;
+                continue;
+            }
+        };
+        let (tx, rx) = mpsc::channel();
+        let request = Request {
+            command,
+            reply: Some(tx),
+        };
+        if events.send(Ok(Event::Control(request))).is_err() {
+            return Ok(());
+        }
+        // 普通命令回复一次后通道就关闭了，订阅的通道在应用程序退出时关闭。
+        let mut replied = false;
+        for reply in rx {
+            writeln!(writer, "{}", ReplyLine(reply))?;
+            writer.flush()?;
+            replied = true;
+        }
+        if !replied {
+            writeln!(
+                writer,
+                "{}",
+                ReplyLine(Err("the application has exited".into()))
+            )?;
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// 监听 Unix 套接字，存在期间接受连接，丢弃时删除套接字文件。
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct Listener {
+    path: std::path::PathBuf,
+}
+
+#[cfg(unix)]
+impl Listener {
+    /// 在 `path` 创建套接字，每个连接在单独的线程中处理。
+    ///
+    /// 上次运行留下的套接字文件会被替换；如果另一个实例正在使用这个路径，则返回错误。
+    pub fn bind(path: &std::path::Path, events: Sender<io::Result<Event>>) -> io::Result<Self> {
+        use std::{
+            fs,
+            io::BufReader,
+            os::unix::net::{UnixListener, UnixStream},
+            thread,
+        };
+
+        if UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("{} is used by another instance", path.display()),
+            ));
+        }
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+        let listener = UnixListener::bind(path)?;
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                let events = events.clone();
+                thread::spawn(move || {
+                    let reader = BufReader::new(stream.try_clone()?);
+                    serve(reader, stream, &events)
+                });
+            }
+        });
+        Ok(Self {
+            path: path.to_owned(),
+        })
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Listener {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!("get".parse(), Ok(Command::Get));
+        assert_eq!(" set  7 ".parse(), Ok(Command::Set(7)));
+        assert_eq!("inc".parse(), Ok(Command::Increment));
+        assert_eq!("add -2".parse(), Ok(Command::Add(-2)));
+        assert_eq!(
+            "set 300".parse::<Command>(),
+            Err("\"300\" is not a counter value".into())
+        );
+        assert!("get 1".parse::<Command>().is_err());
+        assert!("jump".parse::<Command>().is_err());
+    }
+
+    /// 模拟主循环：计数值从 0 开始，上限为 2。
+    fn spawn_app() -> Sender<io::Result<Event>> {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut counter = 0;
+            for event in rx {
+                let Ok(Event::Control(request)) = event else {
+                    continue;
+                };
+                let reply = match request.command {
+                    Command::Increment if counter < 2 => {
+                        counter += 1;
+                        Ok(counter)
+                    }
+                    Command::Increment => Err("counter is at its maximum".into()),
+                    // 回复当前值和一次变化，然后结束订阅。
+                    Command::Watch => {
+                        if let Some(tx) = request.subscribe(counter) {
+                            let _ = tx.send(Ok(counter + 1));
+                        }
+                        continue;
+                    }
+                    _ => Ok(counter),
+                };
+                request.respond(reply);
+            }
+        });
+        tx
+    }
+
+    #[test]
+    fn serve_lines() {
+        let events = spawn_app();
+        let mut out = Vec::new();
+        serve("inc\n\ninc\ninc\nnope\nget\n".as_bytes(), &mut out, &events).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "ok 1\nok 2\nerror counter is at its maximum\nerror unknown command \"nope\"\nok 2\n"
+        );
+
+        let mut out = Vec::new();
+        serve("watch\n".as_bytes(), &mut out, &events).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "ok 2\nok 3\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket() {
+        use std::{io::BufReader, os::unix::net::UnixStream};
+
+        let path =
+            std::env::temp_dir().join(format!("counter-control-{}.sock", std::process::id()));
+        let listener = Listener::bind(&path, spawn_app()).unwrap();
+        assert_eq!(
+            Listener::bind(&path, spawn_app()).unwrap_err().kind(),
+            io::ErrorKind::AddrInUse
+        );
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        writeln!(stream, "inc").unwrap();
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line).unwrap();
+        assert_eq!(line, "ok 1\n");
+
+        drop(listener);
+        assert!(!path.exists());
+    }
+}
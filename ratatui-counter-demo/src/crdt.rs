@@ -0,0 +1,77 @@
+//! PN 计数器：一种无冲突复制数据类型（CRDT）。
+//!
+//! 每个实例（节点）只修改自己的两个计数：递增的总量 `p` 和递减的总量 `n`，计数值是所有节点的 `p` 之和减去 `n` 之和。
+//! 合并两个副本时对每个节点取较大的值，所以合并满足交换律、结合律和幂等律：
+//! 无论状态以什么顺序、被传递多少次，所有实例最终都会得到相同的计数值，不需要中心服务器。
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PnCounter {
+    p: BTreeMap<String, u64>,
+    n: BTreeMap<String, u64>,
+}
+
+impl PnCounter {
+    /// 在节点 `node` 上记录一次变化。
+    pub fn apply(&mut self, node: &str, delta: i64) {
+        let totals = if delta >= 0 { &mut self.p } else { &mut self.n };
+        *totals.entry(node.to_string()).or_default() += delta.unsigned_abs();
+    }
+
+    pub fn value(&self) -> i64 {
+        let sum = |totals: &BTreeMap<String, u64>| totals.values().map(|&v| v as i64).sum::<i64>();
+        sum(&self.p) - sum(&self.n)
+    }
+
+    /// 合并另一个副本，返回状态是否发生了变化。
+    pub fn merge(&mut self, other: &PnCounter) -> bool {
+        let mut changed = false;
+        for (mine, theirs) in [(&mut self.p, &other.p), (&mut self.n, &other.n)] {
+            for (node, &count) in theirs {
+                let entry = mine.entry(node.clone()).or_default();
+                if count > *entry {
+                    *entry = count;
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_changes_converge() {
+        let (mut a, mut b, mut c) = (
+            PnCounter::default(),
+            PnCounter::default(),
+            PnCounter::default(),
+        );
+        a.apply("a", 2);
+        b.apply("b", 3);
+        b.apply("b", -1);
+        c.apply("c", -4);
+
+        // 以不同的顺序合并，并重复合并同一个状态。
+        let mut ab = a.clone();
+        ab.merge(&b);
+        ab.merge(&c);
+        ab.merge(&b);
+        let mut cb = c.clone();
+        cb.merge(&b);
+        cb.merge(&a);
+        assert_eq!(ab, cb);
+        assert_eq!(ab.value(), 0);
+
+        assert!(!ab.merge(&a));
+        a.apply("a", 1);
+        assert!(ab.merge(&a));
+        assert_eq!(ab.value(), 1);
+    }
+}
@@ -0,0 +1,148 @@
+//! 连接守护进程的客户端。
+//!
+//! 用 `--daemon --control ADDRESS` 启动的实例不打开终端，只保存计数器的状态并通过控制接口提供服务；
+//! 用 `--attach ADDRESS` 启动的界面把计数器的修改发送给守护进程，并订阅守护进程的变化。
+//! 所以重启界面不会丢失计数值，多个终端也可以同时连接到同一个计数器。
+//!
+//! 每个客户端使用两个连接：一个发送命令并等待回复，另一个发送 `watch` 后只接收变化。
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    sync::mpsc::Sender,
+    thread,
+};
+
+use crate::event::Event;
+
+#[cfg(unix)]
+type Stream = std::os::unix::net::UnixStream;
+#[cfg(windows)]
+type Stream = std::fs::File;
+
+#[cfg(unix)]
+fn connect(address: &str) -> io::Result<Stream> {
+    Stream::connect(address)
+}
+
+/// 命名管道的客户端就是以读写方式打开的文件。
+#[cfg(windows)]
+fn connect(address: &str) -> io::Result<Stream> {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(crate::named_pipe::path(address))
+}
+
+#[derive(Debug)]
+pub struct Client {
+    reader: BufReader<Stream>,
+    writer: Stream,
+}
+
+impl Client {
+    /// 连接守护进程，返回客户端和当前的计数值。之后的变化作为 [`Event::Daemon`] 发送到 `events`，
+    /// 守护进程退出时发送错误。
+    pub fn connect(address: &str, events: Sender<io::Result<Event>>) -> io::Result<(Self, u8)> {
+        let writer = connect(address)?;
+        let reader = BufReader::new(writer.try_clone()?);
+        let mut watch = connect(address)?;
+        writeln!(watch, "watch")?;
+        let mut watch = BufReader::new(watch);
+        let counter = read_reply(&mut watch)?.map_err(io::Error::other)?;
+        thread::spawn(move || loop {
+            let event = match read_reply(&mut watch) {
+                Ok(Ok(counter)) => Ok(Event::Daemon(counter)),
+                Ok(Err(reason)) => Err(io::Error::other(reason)),
+                Err(err) => Err(err),
+            };
+            let failed = event.is_err();
+            if events.send(event).is_err() || failed {
+                break;
+            }
+        });
+        Ok((Self { reader, writer }, counter))
+    }
+
+    /// 让守护进程的计数器变化 `delta`，返回变化后的值。
+    ///
+    /// 守护进程拒绝时（例如它的上限不同）计数器保持原样，返回守护进程当前的值。
+    pub fn add(&mut self, delta: i16) -> io::Result<u8> {
+        match self.command(&format!("add {delta}"))? {
+            Ok(counter) => Ok(counter),
+            Err(_) => self.command("get")?.map_err(io::Error::other),
+        }
+    }
+
+    fn command(&mut self, line: &str) -> io::Result<Result<u8, String>> {
+        writeln!(self.writer, "{line}")?;
+        read_reply(&mut self.reader)
+    }
+}
+
+/// 读取一行回复：`ok N` 或 `error 原因`。
+fn read_reply(reader: &mut impl BufRead) -> io::Result<Result<u8, String>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "the daemon has exited",
+        ));
+    }
+    let line = line.trim_end();
+    if let Some(reason) = line.strip_prefix("error ") {
+        return Ok(Err(reason.to_string()));
+    }
+    line.strip_prefix("ok ")
+        .and_then(|counter| counter.parse().ok())
+        .map(Ok)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unexpected reply {line:?}"),
+            )
+        })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{sync::mpsc, time::Duration};
+
+    use super::*;
+    use crate::control::{Command, Listener};
+
+    #[test]
+    fn attach_to_daemon() {
+        let path = std::env::temp_dir().join(format!("counter-daemon-{}.sock", std::process::id()));
+        // 模拟守护进程：处理命令，并把变化发送给订阅者。
+        let (daemon_tx, daemon_rx) = mpsc::channel();
+        let _listener = Listener::bind(&path, daemon_tx).unwrap();
+        thread::spawn(move || {
+            let (mut counter, mut watchers) = (0u8, Vec::new());
+            for event in daemon_rx {
+                let Ok(Event::Control(request)) = event else {
+                    continue;
+                };
+                match request.command {
+                    Command::Watch => watchers.extend(request.subscribe(counter)),
+                    Command::Add(delta) if delta > 0 => {
+                        counter += delta as u8;
+                        watchers.retain(|tx: &Sender<_>| tx.send(Ok(counter)).is_ok());
+                        request.respond(Ok(counter));
+                    }
+                    Command::Add(_) => request.respond(Err("counter is at its minimum".into())),
+                    _ => request.respond(Ok(counter)),
+                }
+            }
+        });
+
+        let (tx, rx) = mpsc::channel();
+        let (mut client, counter) = Client::connect(path.to_str().unwrap(), tx).unwrap();
+        assert_eq!(counter, 0);
+        assert_eq!(client.add(2).unwrap(), 2);
+        assert_eq!(client.add(-5).unwrap(), 2);
+        match rx.recv_timeout(Duration::from_secs(5)).unwrap().unwrap() {
+            Event::Daemon(counter) => assert_eq!(counter, 2),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+}
@@ -1,28 +1,26 @@
 use std::panic;
 
-use color_eyre::{config::HookBuilder, eyre};
+use color_eyre::config::HookBuilder;
 
-use crate::tui;
+use crate::{recovery, tui};
 
-/// 这将标准 color_eyre 恐慌和错误挂钩替换为在打印恐慌或错误之前恢复终端的挂钩。
+/// 这将标准 color_eyre 恐慌挂钩替换为在打印恐慌之前恢复终端的挂钩。
+/// 恐慌时还把消息记在崩溃恢复的标记文件中，下次启动时显示。
+///
+/// 错误挂钩不恢复终端：创建 `Report` 时界面可能还在运行（例如口令输错、后台自动保存失败），
+/// 错误只在 `main` 中打印，打印之前才恢复终端。
 pub fn install_hooks() -> color_eyre::Result<()> {
     let (panic_hook, eyre_hook) = HookBuilder::default().into_hooks();
 
     // 从 color_eyre PanicHook 转换为标准恐慌钩子
     let panic_hook = panic_hook.into_panic_hook();
     panic::set_hook(Box::new(move |panic_info| {
+        recovery::record_panic(&panic_info.to_string());
         tui::restore().unwrap();
         panic_hook(panic_info);
     }));
 
-    // 从 color_eyre EyreHook 转换为 eyre ErrorHook
-    let eyre_hook = eyre_hook.into_eyre_hook();
-    eyre::set_hook(Box::new(
-        move |error: &(dyn std::error::Error + 'static)| {
-            tui::restore().unwrap();
-            eyre_hook(error)
-        },
-    ))?;
+    eyre_hook.install()?;
 
     Ok(())
 }
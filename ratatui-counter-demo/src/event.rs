@@ -0,0 +1,163 @@
+use std::{
+    io,
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
+    time::Duration,
+};
+
+use crossterm::event;
+use serde::{Deserialize, Serialize};
+
+use crate::{control::Request, gossip::Message};
+
+pub use ratatui_common::events::EventSource;
+
+/// 应用程序处理的事件：终端事件、进程收到的信号、控制接口收到的命令、其他实例发来的共享计数器状态、
+/// 守护进程报告的计数值，或者从管道读到的一行。
+///
+/// 序列化时不带外层标签，所以只包含终端事件的旧录制文件仍然可以读取。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Event {
+    Terminal(event::Event),
+    Signal(Signal),
+    Control(Request),
+    Gossip(Message),
+    Daemon(u8),
+    /// 不带外层标签时字符串会和 [`Signal`] 混淆，所以放在结构体里。
+    Stdin {
+        line: String,
+    },
+}
+
+impl From<event::Event> for Event {
+    fn from(event: event::Event) -> Self {
+        Event::Terminal(event)
+    }
+}
+
+/// 进程收到的信号：前三个要求应用程序退出，其余的修改计数器。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Signal {
+    /// SIGINT
+    Interrupt,
+    /// SIGTERM
+    Terminate,
+    /// SIGHUP
+    Hangup,
+    /// SIGUSR1
+    Increment,
+    /// SIGUSR2
+    Decrement,
+}
+
+/// 从真实终端读取事件，包装为应用程序的 [`Event`]。
+#[derive(Debug, Default)]
+pub struct TerminalEvents;
+
+impl EventSource<Event> for TerminalEvents {
+    fn read(&mut self) -> io::Result<Event> {
+        event::read().map(Event::Terminal)
+    }
+
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        event::poll(timeout)
+    }
+}
+
+/// 汇集多个来源的事件。
+///
+/// 每个来源在自己的线程中阻塞读取，然后把事件发送到同一个通道，主循环只需要等待这个通道。
+/// 这样即使终端没有任何输入，信号等其他事件也能立即唤醒主循环。
+#[derive(Debug)]
+pub struct EventChannel {
+    tx: Sender<io::Result<Event>>,
+    rx: Receiver<io::Result<Event>>,
+    /// `poll` 时已经收到、等待 `read` 返回的事件。
+    ready: Option<io::Result<Event>>,
+}
+
+impl Default for EventChannel {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            tx,
+            rx,
+            ready: None,
+        }
+    }
+}
+
+impl EventChannel {
+    pub fn sender(&self) -> Sender<io::Result<Event>> {
+        self.tx.clone()
+    }
+
+    /// 在后台线程中不断读取 `source`，把事件转发到通道中。
+    pub fn spawn_source(&self, mut source: impl EventSource<Event> + Send + 'static) {
+        let tx = self.sender();
+        thread::spawn(move || loop {
+            let event = source.read();
+            let failed = event.is_err();
+            if tx.send(event).is_err() || failed {
+                break;
+            }
+        });
+    }
+}
+
+fn disconnected() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "all event sources stopped")
+}
+
+impl EventSource<Event> for EventChannel {
+    fn read(&mut self) -> io::Result<Event> {
+        match self.ready.take() {
+            Some(event) => event,
+            None => self.rx.recv().map_err(|_| disconnected())?,
+        }
+    }
+
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        if self.ready.is_some() {
+            return Ok(true);
+        }
+        match self.rx.recv_timeout(timeout) {
+            Ok(event) => {
+                self.ready = Some(event);
+                Ok(true)
+            }
+            Err(RecvTimeoutError::Timeout) => Ok(false),
+            Err(RecvTimeoutError::Disconnected) => Err(disconnected()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyCode;
+
+    use super::*;
+
+    #[test]
+    fn deserialize_untagged() {
+        let key: Event = serde_json::from_str(
+            r#"{ "Key": { "code": "Right", "modifiers": "", "kind": "Press", "state": "" } }"#,
+        )
+        .unwrap();
+        assert_eq!(key, event::Event::Key(KeyCode::Right.into()).into());
+        let signal: Event = serde_json::from_str(r#""Terminate""#).unwrap();
+        assert_eq!(signal, Event::Signal(Signal::Terminate));
+    }
+
+    #[test]
+    fn channel_merges_sources() {
+        let mut channel = EventChannel::default();
+        assert!(!channel.poll(Duration::ZERO).unwrap());
+
+        let tx = channel.sender();
+        thread::spawn(move || tx.send(Ok(Event::Signal(Signal::Interrupt))));
+        assert!(channel.poll(Duration::from_secs(1)).unwrap());
+        assert_eq!(channel.read().unwrap(), Event::Signal(Signal::Interrupt));
+    }
+}
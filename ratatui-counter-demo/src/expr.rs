@@ -0,0 +1,157 @@
+//! 一个很小的算术表达式解析器，用于 `=` 输入行，例如 `3*7+1`。
+//!
+//! 支持整数、`+ - * / %`、一元负号和括号，运算符优先级与 Rust 相同。
+//!
+//! ```text
+//! expr   = term (("+" | "-") term)*
+//! term   = unary (("*" | "/" | "%") unary)*
+//! unary  = "-" unary | atom
+//! atom   = integer | "(" expr ")"
+//! ```
+
+use std::{fmt, iter::Peekable, str::CharIndices};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// 在给定的字符位置遇到了意外的字符。
+    Unexpected(char, usize),
+    UnexpectedEnd,
+    DivisionByZero,
+    Overflow,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Unexpected(c, at) => write!(f, "unexpected {c:?} at column {}", at + 1),
+            Error::UnexpectedEnd => write!(f, "unexpected end of expression"),
+            Error::DivisionByZero => write!(f, "division by zero"),
+            Error::Overflow => write!(f, "arithmetic overflow"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// 计算表达式的值。
+pub fn eval(input: &str) -> Result<i64, Error> {
+    let mut parser = Parser {
+        chars: input.char_indices().peekable(),
+    };
+    let value = parser.expr()?;
+    match parser.next_token() {
+        Some((at, c)) => Err(Error::Unexpected(c, at)),
+        None => Ok(value),
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+
+    fn peek_token(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.peek().map(|&(_, c)| c)
+    }
+
+    fn next_token(&mut self) -> Option<(usize, char)> {
+        self.skip_whitespace();
+        self.chars.next()
+    }
+
+    fn expr(&mut self) -> Result<i64, Error> {
+        let mut value = self.term()?;
+        while let Some(op @ ('+' | '-')) = self.peek_token() {
+            self.chars.next();
+            let rhs = self.term()?;
+            value = match op {
+                '+' => value.checked_add(rhs),
+                _ => value.checked_sub(rhs),
+            }
+            .ok_or(Error::Overflow)?;
+        }
+        Ok(value)
+    }
+
+    fn term(&mut self) -> Result<i64, Error> {
+        let mut value = self.unary()?;
+        while let Some(op @ ('*' | '/' | '%')) = self.peek_token() {
+            self.chars.next();
+            let rhs = self.unary()?;
+            if op != '*' && rhs == 0 {
+                return Err(Error::DivisionByZero);
+            }
+            value = match op {
+                '*' => value.checked_mul(rhs),
+                '/' => value.checked_div(rhs),
+                _ => value.checked_rem(rhs),
+            }
+            .ok_or(Error::Overflow)?;
+        }
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> Result<i64, Error> {
+        if self.peek_token() == Some('-') {
+            self.chars.next();
+            return self.unary()?.checked_neg().ok_or(Error::Overflow);
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<i64, Error> {
+        match self.next_token() {
+            Some((_, '(')) => {
+                let value = self.expr()?;
+                match self.next_token() {
+                    Some((_, ')')) => Ok(value),
+                    Some((at, c)) => Err(Error::Unexpected(c, at)),
+                    None => Err(Error::UnexpectedEnd),
+                }
+            }
+            Some((_, c)) if c.is_ascii_digit() => {
+                let mut value = i64::from(c as u8 - b'0');
+                while let Some((_, c)) = self.chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                    value = value
+                        .checked_mul(10)
+                        .and_then(|value| value.checked_add(i64::from(c as u8 - b'0')))
+                        .ok_or(Error::Overflow)?;
+                }
+                Ok(value)
+            }
+            Some((at, c)) => Err(Error::Unexpected(c, at)),
+            None => Err(Error::UnexpectedEnd),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluate() {
+        assert_eq!(eval("3*7+1"), Ok(22));
+        assert_eq!(eval(" 1 + 2 * 3 "), Ok(7));
+        assert_eq!(eval("(1 + 2) * 3"), Ok(9));
+        assert_eq!(eval("10 - 4 - 3"), Ok(3));
+        assert_eq!(eval("17 % 5 / 2"), Ok(1));
+        assert_eq!(eval("--2"), Ok(2));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(eval(""), Err(Error::UnexpectedEnd));
+        assert_eq!(eval("1 +"), Err(Error::UnexpectedEnd));
+        assert_eq!(eval("1 + x"), Err(Error::Unexpected('x', 4)));
+        assert_eq!(eval("(1"), Err(Error::UnexpectedEnd));
+        assert_eq!(eval("1 2"), Err(Error::Unexpected('2', 2)));
+        assert_eq!(eval("1 / 0"), Err(Error::DivisionByZero));
+        assert_eq!(eval("9223372036854775807 + 1"), Err(Error::Overflow));
+    }
+}
@@ -0,0 +1,189 @@
+//! 多个实例共享同一个计数器：每个实例保存一个 [`PnCounter`] 副本，通过 TCP 互相传播状态（gossip），
+//! 不需要中心服务器。
+//!
+//! 每个实例定期把完整的副本和已知的实例地址发送给所有已知的实例，每个连接发送一行 JSON 就关闭。
+//! 收到的副本在主循环中合并，所以各实例可以同时修改计数器，断开的实例重新连上后也会收敛到同一个值。
+//! 新实例只需要知道一个已有实例的地址，其他实例的地址会随着消息传播过来。
+
+use std::{
+    collections::{hash_map::RandomState, BTreeSet},
+    hash::{BuildHasher, Hasher},
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    process,
+    sync::{mpsc::Sender, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{crdt::PnCounter, event::Event};
+
+/// 每隔这么长时间向所有已知的实例发送一次状态。
+const GOSSIP_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 连接其他实例的超时时间，没有运行的实例不会拖慢其他实例。
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// 实例之间传递的消息。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Message {
+    /// 发送者监听的地址。
+    pub from: String,
+    /// 发送者已知的其他实例。
+    pub peers: BTreeSet<String>,
+    pub counter: PnCounter,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    counter: PnCounter,
+    peers: BTreeSet<String>,
+}
+
+/// 本实例的副本。
+#[derive(Debug)]
+pub struct Replica {
+    /// 副本中区分各实例的编号。每次启动都不同，重启的实例不会和之前的自己混淆。
+    node: String,
+    address: String,
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Replica {
+    /// 监听 `address`，把收到的消息作为 [`Event::Gossip`] 发送到主循环，并开始定期向 `peers` 发送状态。
+    pub fn start(
+        address: &str,
+        peers: &[String],
+        events: Sender<io::Result<Event>>,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        // 监听 0 号端口时使用系统分配的端口。
+        let address = listener.local_addr()?.to_string();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                let events = events.clone();
+                thread::spawn(move || {
+                    for line in BufReader::new(stream).lines() {
+                        // 无法解析的消息来自其他程序，直接丢弃。
+                        let Ok(message) = serde_json::from_str(&line?) else {
+                            continue;
+                        };
+                        if events.send(Ok(Event::Gossip(message))).is_err() {
+                            break;
+                        }
+                    }
+                    io::Result::Ok(())
+                });
+            }
+        });
+        let replica = Self {
+            node: node_id(),
+            address,
+            shared: Arc::new(Mutex::new(Shared {
+                counter: PnCounter::default(),
+                peers: peers.iter().cloned().collect(),
+            })),
+        };
+        let (address, shared) = (replica.address.clone(), Arc::clone(&replica.shared));
+        thread::spawn(move || loop {
+            let (message, peers) = {
+                let shared = shared.lock().expect("gossip state poisoned");
+                let message = Message {
+                    from: address.clone(),
+                    peers: shared.peers.clone(),
+                    counter: shared.counter.clone(),
+                };
+                (message, shared.peers.clone())
+            };
+            for peer in peers {
+                // 暂时连不上的实例下一轮再试。
+                let _ = send(&peer, &message);
+            }
+            thread::sleep(GOSSIP_INTERVAL);
+        });
+        Ok(replica)
+    }
+
+    fn shared(&self) -> std::sync::MutexGuard<'_, Shared> {
+        self.shared.lock().expect("gossip state poisoned")
+    }
+
+    /// 记录本实例的一次变化。
+    pub fn apply(&self, delta: i64) {
+        self.shared().counter.apply(&self.node, delta);
+    }
+
+    /// 合并收到的消息，记住发送者和它知道的实例。
+    pub fn merge(&self, message: &Message) {
+        let mut shared = self.shared();
+        shared.counter.merge(&message.counter);
+        let known = message.peers.iter().chain([&message.from]);
+        let new: Vec<String> = known
+            .filter(|peer| **peer != self.address)
+            .cloned()
+            .collect();
+        shared.peers.extend(new);
+    }
+
+    /// 所有实例合并后的计数值，可能超出本地计数器的取值范围。
+    pub fn value(&self) -> i64 {
+        self.shared().counter.value()
+    }
+}
+
+fn send(peer: &str, message: &Message) -> io::Result<()> {
+    let address: SocketAddr = peer
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{peer} has no address")))?;
+    let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+    serde_json::to_writer(&mut stream, message)?;
+    stream.write_all(b"\n")
+}
+
+/// 随机的节点编号：标准库的 `RandomState` 每次创建时使用不同的随机种子。
+fn node_id() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(process::id());
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    fn receive(rx: &mpsc::Receiver<io::Result<Event>>) -> Message {
+        match rx.recv_timeout(Duration::from_secs(5)).unwrap().unwrap() {
+            Event::Gossip(message) => message,
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    /// 合并收到的消息，直到副本的值等于 `value`。第一条消息可能在对方修改之前就已经发出。
+    fn merge_until(replica: &Replica, rx: &mpsc::Receiver<io::Result<Event>>, value: i64) {
+        while replica.value() != value {
+            replica.merge(&receive(rx));
+        }
+    }
+
+    #[test]
+    fn replicas_converge() {
+        let (tx_a, rx_a) = mpsc::channel();
+        let a = Replica::start("127.0.0.1:0", &[], tx_a).unwrap();
+        let (tx_b, rx_b) = mpsc::channel();
+        let b = Replica::start("127.0.0.1:0", std::slice::from_ref(&a.address), tx_b).unwrap();
+        a.apply(3);
+        b.apply(-1);
+
+        // a 只知道 b 发来的地址，之后开始向 b 发送状态。
+        merge_until(&a, &rx_a, 2);
+        merge_until(&b, &rx_b, 2);
+        assert_eq!(a.shared().peers, BTreeSet::from([b.address.clone()]));
+        assert_eq!(b.shared().peers, BTreeSet::from([a.address.clone()]));
+    }
+}
@@ -0,0 +1,320 @@
+//! 按键帮助界面：只列出当前界面可以用的按键，按分组列出，可以搜索。
+//!
+//! 每个界面通过 [`Help`] 说明自己的按键。计数器界面的内容从当前生效的按键绑定生成，不是写死的
+//! 文本，配置文件中重新绑定的按键和 `init.lua` 中定义的命令都会出现在这里。
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::*,
+    widgets::{block::*, *},
+};
+
+use crate::{
+    keymap::{self, Action, Category, Keymap},
+    theme::Theme,
+};
+
+/// 能在帮助中列出自己的按键的界面。
+pub trait Help {
+    /// 帮助界面的标题。
+    fn help_title(&self) -> String;
+
+    /// 这个界面可以用的按键，同一组的放在一起。
+    fn help_entries(&self) -> Vec<Entry>;
+}
+
+/// 帮助中的一行。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub group: &'static str,
+    pub keys: String,
+    pub description: String,
+}
+
+impl Entry {
+    pub fn new(group: &'static str, keys: &str, description: &str) -> Self {
+        Self {
+            group,
+            keys: keys.into(),
+            description: description.into(),
+        }
+    }
+
+    fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        [self.group, &self.keys, &self.description]
+            .iter()
+            .any(|text| text.to_lowercase().contains(&query))
+    }
+}
+
+/// 按键绑定中 `relevant` 的操作，按分组排列，组内保持绑定的顺序。`commands` 是 `init.lua`
+/// 中定义的命令名称。
+pub fn keymap_entries(
+    keymap: &Keymap,
+    commands: &[String],
+    relevant: impl Fn(Action) -> bool,
+) -> Vec<Entry> {
+    let mut bindings: Vec<_> = keymap
+        .effective()
+        .into_iter()
+        .filter(|binding| relevant(binding.action))
+        .collect();
+    bindings.sort_by_key(|binding| binding.action.category());
+    let mut entries: Vec<Entry> = bindings
+        .into_iter()
+        .map(|binding| Entry {
+            group: binding.action.category().title(),
+            keys: keymap::keys_name(&binding.keys),
+            description: describe(binding.action, commands),
+        })
+        .collect();
+    entries.push(ctrl_c());
+    entries
+}
+
+/// 按键绑定中的一部分操作：计数器界面使用全部操作，宽字符演示和插件面板这些不自己处理按键
+/// 的界面只列出在那里有用的几个。
+pub struct KeymapHelp<'a> {
+    pub title: String,
+    pub keymap: &'a Keymap,
+    /// `init.lua` 中定义的命令名称。
+    pub commands: Vec<String>,
+    pub relevant: fn(Action) -> bool,
+}
+
+impl Help for KeymapHelp<'_> {
+    fn help_title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn help_entries(&self) -> Vec<Entry> {
+        keymap_entries(self.keymap, &self.commands, self.relevant)
+    }
+}
+
+/// 在任何界面都可以用，不在按键绑定中。
+pub fn ctrl_c() -> Entry {
+    Entry::new(Category::General.title(), "Ctrl+C", "Quit from any screen")
+}
+
+fn describe(action: Action, commands: &[String]) -> String {
+    #[cfg(feature = "lua")]
+    if let Action::Command(index) = action {
+        if let Some(name) = commands.get(index) {
+            return format!("Run {name}");
+        }
+    }
+    #[cfg(not(feature = "lua"))]
+    let _ = commands;
+    action.description().to_string()
+}
+
+#[derive(Debug, Default)]
+pub struct HelpView {
+    title: String,
+    entries: Vec<Entry>,
+    query: String,
+    /// 是否正在输入搜索内容。
+    searching: bool,
+    scroll: usize,
+}
+
+impl HelpView {
+    pub fn new(view: &dyn Help) -> Self {
+        Self {
+            title: view.help_title(),
+            entries: view.help_entries(),
+            ..Self::default()
+        }
+    }
+
+    fn visible(&self) -> Vec<&Entry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.matches(&self.query))
+            .collect()
+    }
+
+    /// 返回 `true` 时关闭帮助。
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> bool {
+        if self.searching {
+            match key_event.code {
+                KeyCode::Char(c) => self.query.push(c),
+                KeyCode::Backspace => {
+                    self.query.pop();
+                }
+                KeyCode::Enter | KeyCode::Esc => self.searching = false,
+                _ => {}
+            }
+            self.scroll = 0;
+            return false;
+        }
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('q') => return true,
+            KeyCode::Char('/') => {
+                self.searching = true;
+                self.query.clear();
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.scroll += 1,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            _ => {}
+        }
+        false
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let instructions = Title::from(Line::from(vec![
+            " Scroll ".into(),
+            "<J/K>".set_style(theme.key),
+            " Search ".into(),
+            "</>".set_style(theme.key),
+            " Back ".into(),
+            "<Esc> ".set_style(theme.key),
+        ]));
+        let block = Block::default()
+            .title(
+                Title::from(format!(" Keys: {} ", self.title).set_style(theme.title))
+                    .alignment(Alignment::Center),
+            )
+            .title(
+                instructions
+                    .alignment(Alignment::Center)
+                    .position(Position::Bottom),
+            )
+            .borders(Borders::ALL)
+            .border_set(theme.border_set)
+            .border_style(theme.border)
+            .style(theme.base);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [search_area, list_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+        let cursor = if self.searching { "_" } else { "" };
+        Line::from(vec![
+            "Search: ".set_style(theme.key),
+            format!("{}{cursor}", self.query).into(),
+        ])
+        .render(search_area, buf);
+
+        let visible = self.visible();
+        if visible.is_empty() {
+            Line::from(format!("No keys match {:?}", self.query)).render(list_area, buf);
+            return;
+        }
+        let width = visible
+            .iter()
+            .map(|entry| entry.keys.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut lines = Vec::new();
+        let mut group = None;
+        for entry in visible {
+            if group != Some(entry.group) {
+                group = Some(entry.group);
+                lines.push(Line::from(entry.group.set_style(theme.title)));
+            }
+            lines.push(Line::from(vec![
+                format!("  {:<width$}  ", entry.keys).set_style(theme.key),
+                entry.description.clone().into(),
+            ]));
+        }
+        let max_scroll = lines.len().saturating_sub(usize::from(list_area.height));
+        self.scroll = self.scroll.min(max_scroll);
+        Paragraph::new(lines)
+            .scroll((self.scroll as u16, 0))
+            .render(list_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn counter(keymap: &Keymap) -> KeymapHelp<'_> {
+        KeymapHelp {
+            title: "Counter".into(),
+            keymap,
+            commands: vec!["double".into()],
+            relevant: |_| true,
+        }
+    }
+
+    fn rows(view: &mut HelpView, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| {
+            view.render(area, buf, &Theme::plain())
+        })
+    }
+
+    #[test]
+    fn generated_from_the_keymap() {
+        let mut keymap = Keymap::default();
+        keymap
+            .rebind(&BTreeMap::from([("reset".to_string(), "r".to_string())]))
+            .unwrap();
+        let entries = keymap_entries(&keymap, &[], |_| true);
+        assert_eq!(
+            entries[..3],
+            [
+                Entry::new("Counter", "r", "Reset to 0"),
+                Entry::new("Counter", "Left", "Decrement"),
+                Entry::new("Counter", "Right", "Increment"),
+            ]
+        );
+        assert!(!entries.iter().any(|entry| entry.keys == "g g"));
+        assert_eq!(entries.last(), Some(&ctrl_c()));
+
+        let only_quit = keymap_entries(&keymap, &[], |action| action == Action::Quit);
+        assert_eq!(only_quit, [Entry::new("General", "q", "Quit"), ctrl_c()]);
+    }
+
+    #[test]
+    fn search() {
+        let mut view = HelpView::new(&counter(&Keymap::default()));
+        assert_eq!(
+            rows(&mut view, 40, 6)[0],
+            "┏━━━━━━━━━━━ Keys: Counter ━━━━━━━━━━━━┓"
+        );
+        for c in "/session".chars() {
+            view.handle_key_event(KeyCode::Char(c).into());
+        }
+        view.handle_key_event(KeyCode::Enter.into());
+        assert_eq!(
+            rows(&mut view, 40, 6)[1..4],
+            [
+                "┃Search: session                       ┃",
+                "┃Screens                               ┃",
+                "┃  s  Switch or save session           ┃",
+            ]
+        );
+        for c in "/nothing".chars() {
+            view.handle_key_event(KeyCode::Char(c).into());
+        }
+        assert_eq!(
+            rows(&mut view, 40, 6)[2],
+            "┃No keys match \"nothing\"               ┃"
+        );
+        view.handle_key_event(KeyCode::Esc.into());
+        assert!(view.handle_key_event(KeyCode::Esc.into()));
+    }
+
+    #[cfg(feature = "lua")]
+    #[test]
+    fn commands_use_their_names() {
+        let mut keymap = Keymap::default();
+        keymap.bindings.insert(
+            0,
+            keymap::Binding {
+                keys: vec![KeyCode::Char('x')],
+                action: Action::Command(0),
+            },
+        );
+        let entries = counter(&keymap).help_entries();
+        assert!(entries.contains(&Entry::new("Commands", "x", "Run double")));
+    }
+}
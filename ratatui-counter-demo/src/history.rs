@@ -0,0 +1,258 @@
+//! 计数器的变化历史，以及浏览历史的时间线界面。
+
+use chrono::{DateTime, Local, Utc};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::*,
+    widgets::{block::*, *},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    help::{self, Entry, Help},
+    theme::Theme,
+};
+
+/// 计数器的一次变化。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Change {
+    pub at: DateTime<Utc>,
+    /// 变化量，例如 `+1`、`-1`。
+    pub delta: i16,
+    /// 变化之后的值。
+    pub value: u8,
+}
+
+impl Change {
+    /// 时间线中显示的各列：时间、变化量、变化后的值。搜索也基于这些文本进行匹配。
+    fn columns(&self) -> [String; 3] {
+        [
+            self.at
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            format!("{:+}", self.delta),
+            self.value.to_string(),
+        ]
+    }
+
+    fn matches(&self, query: &str) -> bool {
+        self.columns().iter().any(|column| column.contains(query))
+    }
+}
+
+/// 处理按键后时间线界面要求应用程序执行的操作。
+#[derive(Debug, PartialEq, Eq)]
+pub enum HistoryAction {
+    None,
+    Close,
+    /// 把计数器恢复为历史中的某个值。
+    Jump(u8),
+    /// 打开这个界面的按键帮助。
+    Help,
+}
+
+/// 全屏的历史时间线，最新的变化显示在最上面。
+#[derive(Debug, Default)]
+pub struct HistoryView {
+    state: TableState,
+    query: String,
+    /// 是否正在输入搜索内容。
+    searching: bool,
+    /// 上一次渲染时可见的行数，用于 PageUp/PageDown。
+    page_size: usize,
+}
+
+impl HistoryView {
+    /// 符合搜索条件的历史记录在 `history` 中的索引，从新到旧排列。
+    fn visible(&self, history: &[Change]) -> Vec<usize> {
+        (0..history.len())
+            .rev()
+            .filter(|&index| history[index].matches(&self.query))
+            .collect()
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent, history: &[Change]) -> HistoryAction {
+        if self.searching {
+            match key_event.code {
+                KeyCode::Char(c) => self.query.push(c),
+                KeyCode::Backspace => {
+                    self.query.pop();
+                }
+                KeyCode::Enter | KeyCode::Esc => self.searching = false,
+                _ => {}
+            }
+            self.state.select(Some(0));
+            return HistoryAction::None;
+        }
+
+        let len = self.visible(history).len();
+        let page = self.page_size.max(1);
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('h') | KeyCode::Char('q') => return HistoryAction::Close,
+            KeyCode::Char('/') => {
+                self.searching = true;
+                self.query.clear();
+            }
+            KeyCode::Char('?') => return HistoryAction::Help,
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1, len),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1, len),
+            KeyCode::PageDown => self.move_selection(page as isize, len),
+            KeyCode::PageUp => self.move_selection(-(page as isize), len),
+            KeyCode::Enter => {
+                let selected = self.state.selected().unwrap_or(0);
+                if let Some(&index) = self.visible(history).get(selected) {
+                    return HistoryAction::Jump(history[index].value);
+                }
+            }
+            _ => {}
+        }
+        HistoryAction::None
+    }
+
+    fn move_selection(&mut self, offset: isize, len: usize) {
+        if len == 0 {
+            return;
+        }
+        let selected = self.state.selected().unwrap_or(0);
+        self.state
+            .select(Some(selected.saturating_add_signed(offset).min(len - 1)));
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, history: &[Change], theme: &Theme) {
+        let instructions = Title::from(Line::from(vec![
+            " Scroll ".into(),
+            "<J/K>".set_style(theme.key),
+            " Search ".into(),
+            "</>".set_style(theme.key),
+            " Jump ".into(),
+            "<Enter>".set_style(theme.key),
+            " Back ".into(),
+            "<Esc> ".set_style(theme.key),
+        ]));
+        let block = Block::default()
+            .title(Title::from(" History ".set_style(theme.title)).alignment(Alignment::Center))
+            .title(
+                instructions
+                    .alignment(Alignment::Center)
+                    .position(Position::Bottom),
+            )
+            .borders(Borders::ALL)
+            .border_set(theme.border_set)
+            .border_style(theme.border)
+            .style(theme.base);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [search_area, table_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+        let cursor = if self.searching { "_" } else { "" };
+        Line::from(vec![
+            "Search: ".set_style(theme.key),
+            format!("{}{cursor}", self.query).into(),
+        ])
+        .render(search_area, buf);
+
+        let visible = self.visible(history);
+        match self.state.selected() {
+            _ if visible.is_empty() => self.state.select(None),
+            Some(selected) if selected < visible.len() => {}
+            Some(_) => self.state.select(Some(visible.len() - 1)),
+            None => self.state.select(Some(0)),
+        }
+        let rows = visible
+            .iter()
+            .map(|&index| Row::new(history[index].columns()));
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(19),
+                Constraint::Length(6),
+                Constraint::Min(5),
+            ],
+        )
+        .header(Row::new(["Time", "Delta", "Value"]).style(theme.title))
+        .highlight_symbol("> ")
+        .highlight_style(theme.key);
+        // 减去表头所占的一行。
+        self.page_size = usize::from(table_area.height.saturating_sub(1));
+        StatefulWidget::render(table, table_area, buf, &mut self.state);
+    }
+}
+
+impl Help for HistoryView {
+    fn help_title(&self) -> String {
+        "History".into()
+    }
+
+    fn help_entries(&self) -> Vec<Entry> {
+        vec![
+            Entry::new("Navigation", "Down, j", "Older change"),
+            Entry::new("Navigation", "Up, k", "Newer change"),
+            Entry::new("Navigation", "PageDown, PageUp", "Scroll a page"),
+            Entry::new("Actions", "Enter", "Restore the selected value"),
+            Entry::new("Actions", "/", "Search, Enter or Esc to finish"),
+            Entry::new("Actions", "Esc, h, q", "Back"),
+            Entry::new("Actions", "?", "This help"),
+            help::ctrl_c(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn history() -> Vec<Change> {
+        (0..5)
+            .map(|i| Change {
+                at: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, i).unwrap(),
+                delta: 1,
+                value: i as u8 + 1,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn scroll_and_jump() {
+        let history = history();
+        let mut view = HistoryView {
+            page_size: 3,
+            ..HistoryView::default()
+        };
+        // 最新的变化在最上面。
+        assert_eq!(
+            view.handle_key_event(KeyCode::Enter.into(), &history),
+            HistoryAction::Jump(5)
+        );
+        view.handle_key_event(KeyCode::Char('j').into(), &history);
+        assert_eq!(
+            view.handle_key_event(KeyCode::Enter.into(), &history),
+            HistoryAction::Jump(4)
+        );
+        view.handle_key_event(KeyCode::PageDown.into(), &history);
+        view.handle_key_event(KeyCode::PageDown.into(), &history);
+        assert_eq!(
+            view.handle_key_event(KeyCode::Enter.into(), &history),
+            HistoryAction::Jump(1)
+        );
+    }
+
+    #[test]
+    fn search_filters_rows() {
+        let history = history();
+        let mut view = HistoryView::default();
+        // 只按秒数搜索，这样结果不受本地时区影响。
+        for c in "/:03".chars() {
+            view.handle_key_event(KeyCode::Char(c).into(), &history);
+        }
+        view.handle_key_event(KeyCode::Enter.into(), &history);
+        assert_eq!(view.visible(&history), [3]);
+        assert_eq!(
+            view.handle_key_event(KeyCode::Enter.into(), &history),
+            HistoryAction::Jump(4)
+        );
+    }
+}
@@ -0,0 +1,72 @@
+//! `--json`：界面以外的输出都改为一行 JSON，便于其他工具读取。
+//!
+//! ```text
+//! --paths          -> stdout {"config":"…","state":"…","cache":"…","data":"…"}
+//! --print-result   -> stdout {"counter":3,"profile":"default","aborted":false}
+//! 出错时           -> stderr {"error":"loading config failed","causes":["…"]}
+//! ```
+//!
+//! 出错时退出码和平时一样是 1，只是错误不再由 color_eyre 格式化。
+
+use color_eyre::Report;
+use serde::Serialize;
+
+/// 退出时的结果，见 `--print-result`。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Outcome<'a> {
+    pub counter: u8,
+    pub profile: &'a str,
+    /// 被 Ctrl-C 或信号中止，而不是用户选择了退出。
+    pub aborted: bool,
+}
+
+/// 出错时输出的错误和它的原因。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Failure {
+    pub error: String,
+    /// 从外到内排列。
+    pub causes: Vec<String>,
+}
+
+impl From<&Report> for Failure {
+    fn from(report: &Report) -> Self {
+        let mut chain = report.chain().map(|cause| cause.to_string());
+        Self {
+            error: chain.next().unwrap_or_default(),
+            causes: chain.collect(),
+        }
+    }
+}
+
+/// 一行 JSON 文本。只有不是 UTF-8 的路径会序列化失败。
+pub fn line(value: &impl Serialize) -> serde_json::Result<String> {
+    serde_json::to_string(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use color_eyre::eyre::{eyre, WrapErr};
+
+    use super::*;
+
+    #[test]
+    fn error_with_causes() {
+        let report = Err::<(), _>(eyre!("expected `=`"))
+            .wrap_err("invalid config.toml")
+            .wrap_err("loading config failed")
+            .unwrap_err();
+        assert_eq!(
+            line(&Failure::from(&report)).unwrap(),
+            r#"{"error":"loading config failed","causes":["invalid config.toml","expected `=`"]}"#
+        );
+        assert_eq!(
+            line(&Outcome {
+                counter: 3,
+                profile: "default",
+                aborted: false
+            })
+            .unwrap(),
+            r#"{"counter":3,"profile":"default","aborted":false}"#
+        );
+    }
+}
@@ -0,0 +1,395 @@
+//! 按键绑定。
+//!
+//! 一个绑定可以是单个按键，也可以是按顺序输入的多个按键（和弦），例如 `g g`。
+//! 输入了某个和弦的前缀后，应用程序等待后续按键，超过 `timeout` 仍未完成则放弃。
+//! 帮助界面和边框上的按键提示都从这里的绑定生成，配置文件中改过的按键也会显示出来。
+
+use std::{collections::BTreeMap, time::Duration};
+
+use crossterm::event::KeyCode;
+
+/// 按键可以触发的操作。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Decrement,
+    Increment,
+    /// 把计数器重置为 0。
+    Reset,
+    /// 把计数器设置为取值范围的中间值。
+    Center,
+    WideDemo,
+    Profiles,
+    Sessions,
+    /// 打开备份选择界面，恢复状态文件的备份。
+    Backups,
+    /// 打开或关闭安排界面，列出接下来定时执行的动作。
+    Schedule,
+    History,
+    /// 打开主题选择界面。
+    Themes,
+    /// 打开表达式输入行。
+    Evaluate,
+    /// 切换到下一个插件面板，最后一个之后回到计数器。
+    NextPane,
+    /// 打开按键帮助。
+    Help,
+    /// 打开或关闭标出各部件区域的布局检查器。
+    Inspect,
+    /// 进入时间旅行调试，前后查看记录下来的状态。
+    TimeTravel,
+    /// `init.lua` 中定义的自定义命令，值为命令的下标。
+    #[cfg(feature = "lua")]
+    Command(usize),
+}
+
+/// 帮助界面中的分组，按显示的顺序排列。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    Counter,
+    Screens,
+    /// `init.lua` 中定义的命令。
+    #[cfg(feature = "lua")]
+    Commands,
+    General,
+}
+
+impl Category {
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Counter => "Counter",
+            Self::Screens => "Screens",
+            #[cfg(feature = "lua")]
+            Self::Commands => "Commands",
+            Self::General => "General",
+        }
+    }
+}
+
+/// 配置文件 `[keys]` 中使用的操作名称。
+const ACTION_NAMES: [(&str, Action); 17] = [
+    ("quit", Action::Quit),
+    ("decrement", Action::Decrement),
+    ("increment", Action::Increment),
+    ("reset", Action::Reset),
+    ("center", Action::Center),
+    ("wide_demo", Action::WideDemo),
+    ("profiles", Action::Profiles),
+    ("sessions", Action::Sessions),
+    ("backups", Action::Backups),
+    ("schedule", Action::Schedule),
+    ("history", Action::History),
+    ("themes", Action::Themes),
+    ("evaluate", Action::Evaluate),
+    ("next_pane", Action::NextPane),
+    ("help", Action::Help),
+    ("inspect", Action::Inspect),
+    ("time_travel", Action::TimeTravel),
+];
+
+impl Action {
+    pub fn from_name(name: &str) -> Option<Self> {
+        ACTION_NAMES
+            .iter()
+            .find(|(other, _)| *other == name)
+            .map(|(_, action)| *action)
+    }
+
+    pub fn category(self) -> Category {
+        match self {
+            Self::Decrement | Self::Increment | Self::Reset | Self::Center | Self::Evaluate => {
+                Category::Counter
+            }
+            Self::WideDemo
+            | Self::Profiles
+            | Self::Sessions
+            | Self::Backups
+            | Self::Schedule
+            | Self::History
+            | Self::Themes
+            | Self::NextPane => Category::Screens,
+            #[cfg(feature = "lua")]
+            Self::Command(_) => Category::Commands,
+            Self::Quit | Self::Help | Self::Inspect | Self::TimeTravel => Category::General,
+        }
+    }
+
+    /// 帮助界面中的说明。自定义命令使用命令的名称，由调用方给出。
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Quit => "Quit",
+            Self::Decrement => "Decrement",
+            Self::Increment => "Increment",
+            Self::Reset => "Reset to 0",
+            Self::Center => "Jump to the middle of the range",
+            Self::WideDemo => "Wide character demo",
+            Self::Profiles => "Switch profile",
+            Self::Sessions => "Switch or save session",
+            Self::Backups => "Restore a state backup",
+            Self::Schedule => "Scheduled actions",
+            Self::History => "History timeline",
+            Self::Themes => "Choose a color theme",
+            Self::Evaluate => "Set the counter from an expression",
+            Self::NextPane => "Next plugin pane",
+            Self::Help => "This help",
+            Self::Inspect => "Toggle the layout inspector",
+            Self::TimeTravel => "Step through recorded states",
+            #[cfg(feature = "lua")]
+            Self::Command(_) => "Run a command",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binding {
+    pub keys: Vec<KeyCode>,
+    pub action: Action,
+}
+
+/// 查找按键序列的结果。
+#[derive(Debug, PartialEq, Eq)]
+pub enum Lookup {
+    /// 按键序列完整匹配了一个绑定。
+    Action(Action),
+    /// 按键序列是某个和弦的前缀，需要等待后续按键。
+    Pending,
+    /// 没有任何绑定以此序列开头。
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    pub bindings: Vec<Binding>,
+    /// 和弦中两次按键之间允许的最长间隔。
+    pub timeout: Duration,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use KeyCode::*;
+        let bind = |keys: &[KeyCode], action| Binding {
+            keys: keys.to_vec(),
+            action,
+        };
+        Self {
+            bindings: vec![
+                bind(&[Char('q')], Action::Quit),
+                bind(&[Left], Action::Decrement),
+                bind(&[Right], Action::Increment),
+                bind(&[Char('g'), Char('g')], Action::Reset),
+                bind(&[Char('z'), Char('z')], Action::Center),
+                bind(&[Char('w')], Action::WideDemo),
+                bind(&[Char('p')], Action::Profiles),
+                bind(&[Char('s')], Action::Sessions),
+                bind(&[Char('b')], Action::Backups),
+                bind(&[Char('a')], Action::Schedule),
+                bind(&[Char('h')], Action::History),
+                bind(&[Char('t')], Action::Themes),
+                bind(&[Char('=')], Action::Evaluate),
+                bind(&[Tab], Action::NextPane),
+                bind(&[Char('?')], Action::Help),
+                bind(&[F(11)], Action::Inspect),
+                bind(&[F(10)], Action::TimeTravel),
+            ],
+            timeout: Duration::from_secs(1),
+        }
+    }
+}
+
+impl Keymap {
+    pub fn lookup(&self, keys: &[KeyCode]) -> Lookup {
+        let mut pending = false;
+        for binding in &self.bindings {
+            if binding.keys == keys {
+                return Lookup::Action(binding.action);
+            }
+            pending |= binding.keys.starts_with(keys);
+        }
+        if pending {
+            Lookup::Pending
+        } else {
+            Lookup::None
+        }
+    }
+
+    /// 按配置文件的 `[keys]` 重新绑定操作，例如 `reset = "r"`：去掉操作原来的绑定，换成给出的
+    /// 按键序列。新的绑定优先于其他操作使用相同按键的绑定。
+    pub fn rebind(&mut self, keys: &BTreeMap<String, String>) -> Result<(), String> {
+        for (name, sequence) in keys {
+            let action =
+                Action::from_name(name).ok_or_else(|| format!("unknown action {name:?}"))?;
+            let keys = parse_keys(sequence)?;
+            self.bindings.retain(|binding| binding.action != action);
+            self.bindings.insert(0, Binding { keys, action });
+        }
+        Ok(())
+    }
+
+    /// 实际生效的绑定：同一个按键序列绑定了多次时只有第一个生效。
+    pub fn effective(&self) -> Vec<&Binding> {
+        let mut effective: Vec<&Binding> = Vec::new();
+        for binding in &self.bindings {
+            if !effective.iter().any(|other| other.keys == binding.keys) {
+                effective.push(binding);
+            }
+        }
+        effective
+    }
+
+    /// 触发 `action` 的第一个生效的按键序列。
+    pub fn keys_for(&self, action: Action) -> Option<&[KeyCode]> {
+        self.effective()
+            .into_iter()
+            .find(|binding| binding.action == action)
+            .map(|binding| binding.keys.as_slice())
+    }
+}
+
+/// 边框上按键提示中的名称，单个字母大写，例如 `Q`、`Left`、`G G`。
+pub fn hint_name(keys: &[KeyCode]) -> String {
+    keys.iter()
+        .map(|&code| match code {
+            KeyCode::Char(c) if c != ' ' => c.to_uppercase().to_string(),
+            code => key_name(code),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 按键序列的显示名称，例如 `g g`。
+pub fn keys_name(keys: &[KeyCode]) -> String {
+    keys.iter()
+        .copied()
+        .map(key_name)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 按键的显示名称，例如 `g`、`Left`。
+pub fn key_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{n}"),
+        other => format!("{other:?}"),
+    }
+}
+
+/// 解析按键名称，是 [`key_name`] 的逆操作，例如 `x`、`Space`、`F5`、`Left`。
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    if let Some(n) = name.strip_prefix('F').and_then(|n| n.parse().ok()) {
+        return Some(KeyCode::F(n));
+    }
+    use KeyCode::*;
+    let code = match name {
+        "Space" => Char(' '),
+        "Left" => Left,
+        "Right" => Right,
+        "Up" => Up,
+        "Down" => Down,
+        "Enter" => Enter,
+        "Tab" => Tab,
+        "Backspace" => Backspace,
+        "Esc" => Esc,
+        "Home" => Home,
+        "End" => End,
+        "PageUp" => PageUp,
+        "PageDown" => PageDown,
+        "Delete" => Delete,
+        "Insert" => Insert,
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// 解析用空格分隔的按键序列，例如 `g x`。
+pub fn parse_keys(text: &str) -> Result<Vec<KeyCode>, String> {
+    let keys = text
+        .split_whitespace()
+        .map(|key| parse_key(key).ok_or_else(|| format!("unknown key {key:?}")))
+        .collect::<Result<Vec<_>, _>>()?;
+    if keys.is_empty() {
+        return Err("the key binding is empty".into());
+    }
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_chords() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.lookup(&[KeyCode::Right]),
+            Lookup::Action(Action::Increment)
+        );
+        assert_eq!(keymap.lookup(&[KeyCode::Char('g')]), Lookup::Pending);
+        assert_eq!(
+            keymap.lookup(&[KeyCode::Char('g'), KeyCode::Char('g')]),
+            Lookup::Action(Action::Reset)
+        );
+        assert_eq!(
+            keymap.lookup(&[KeyCode::Char('g'), KeyCode::Char('z')]),
+            Lookup::None
+        );
+    }
+
+    #[test]
+    fn rebind_from_config() {
+        let mut keymap = Keymap::default();
+        let keys = BTreeMap::from([
+            ("reset".to_string(), "r".to_string()),
+            ("history".to_string(), "Left".to_string()),
+        ]);
+        keymap.rebind(&keys).unwrap();
+        assert_eq!(
+            keymap.lookup(&[KeyCode::Char('r')]),
+            Lookup::Action(Action::Reset)
+        );
+        assert_eq!(keymap.lookup(&[KeyCode::Char('g')]), Lookup::None);
+        // 被新绑定占用按键的操作不再有生效的绑定。
+        assert_eq!(
+            keymap.lookup(&[KeyCode::Left]),
+            Lookup::Action(Action::History)
+        );
+        assert_eq!(keymap.keys_for(Action::Decrement), None);
+        assert_eq!(
+            keymap.keys_for(Action::Center),
+            Some(&[KeyCode::Char('z'); 2][..])
+        );
+
+        let unknown = BTreeMap::from([("jump".to_string(), "j".to_string())]);
+        assert_eq!(
+            keymap.rebind(&unknown),
+            Err("unknown action \"jump\"".into())
+        );
+        let bad = BTreeMap::from([("quit".to_string(), "Ctrl+Q".to_string())]);
+        assert_eq!(keymap.rebind(&bad), Err("unknown key \"Ctrl+Q\"".into()));
+    }
+
+    #[test]
+    fn parse_key_names() {
+        for code in [
+            KeyCode::Char('x'),
+            KeyCode::Char(' '),
+            KeyCode::F(5),
+            KeyCode::Left,
+            KeyCode::PageDown,
+        ] {
+            assert_eq!(parse_key(&key_name(code)), Some(code));
+        }
+        assert_eq!(parse_key("Nope"), None);
+        assert_eq!(parse_key(""), None);
+        assert_eq!(
+            parse_keys("g  x"),
+            Ok(vec![KeyCode::Char('g'), KeyCode::Char('x')])
+        );
+        assert_eq!(parse_keys(" "), Err("the key binding is empty".into()));
+    }
+}
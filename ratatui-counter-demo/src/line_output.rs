@@ -0,0 +1,28 @@
+//! 屏幕阅读器友好的输出方式。
+//!
+//! 全屏重绘会让屏幕阅读器反复朗读整个界面。在这种模式下，应用程序不进入备用屏幕，
+//! 也不绘制任何窗口部件，只在状态变化时输出一行简短的描述，例如 `counter is now 3`。
+
+use std::io::{self, Write};
+
+pub struct LineOutput<W: Write> {
+    out: W,
+}
+
+impl<W: Write> LineOutput<W> {
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+
+    /// 输出一行文本。
+    ///
+    /// 原始模式下终端不会把 `\n` 转换为回车换行，所以这里显式写出 `\r\n`。
+    pub fn say(&mut self, line: &str) -> io::Result<()> {
+        write!(self.out, "{line}\r\n")?;
+        self.out.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
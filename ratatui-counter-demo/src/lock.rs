@@ -0,0 +1,109 @@
+//! 档案的锁：同一个档案同时只有一个实例写入，两个实例不会互相覆盖状态文件和会话。
+//!
+//! 锁是状态文件旁边 `state.lock` 上的建议锁（advisory lock），进程退出或者崩溃时由操作系统释放，
+//! 不会留下锁死的档案。状态文件保存时会被替换（见 `state::save_json`），所以锁放在单独的文件上。
+//! 拿到锁的实例把自己的进程号写进锁文件，另一个实例就能在提示中说明是谁占用了档案。拿不到锁的
+//! 实例以只读方式运行：计数器照常使用，但什么也不保存。
+
+use std::{
+    fs::{self, File, TryLockError},
+    io::Write,
+    path::Path,
+};
+
+use color_eyre::{eyre::WrapErr, Result};
+
+/// 持有期间其他实例拿不到锁，丢弃时释放。
+#[derive(Debug)]
+pub struct StateLock {
+    _file: File,
+}
+
+/// 锁已经被另一个实例拿走了。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Held {
+    /// 持有锁的进程，锁文件中读不出来时没有。
+    pub pid: Option<u32>,
+}
+
+impl Held {
+    pub fn message(&self, profile: &str) -> String {
+        format!(
+            "Profile {profile} is open in {}, changes will not be saved",
+            self.holder()
+        )
+    }
+
+    /// 持有锁的实例，例如 `another instance (pid 42)`。
+    pub fn holder(&self) -> String {
+        match self.pid {
+            Some(pid) => format!("another instance (pid {pid})"),
+            None => "another instance".to_string(),
+        }
+    }
+}
+
+/// 锁住状态文件 `state_path` 所在的档案。
+pub fn acquire(state_path: &Path) -> Result<Result<StateLock, Held>> {
+    let path = state_path.with_file_name("state.lock");
+    let open = || -> std::io::Result<File> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+    };
+    let mut file = open().wrap_err_with(|| format!("opening {} failed", path.display()))?;
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            let pid = fs::read_to_string(&path)
+                .ok()
+                .and_then(|pid| pid.trim().parse().ok());
+            return Ok(Err(Held { pid }));
+        }
+        Err(TryLockError::Error(error)) => {
+            return Err(error).wrap_err_with(|| format!("locking {} failed", path.display()))
+        }
+    }
+    file.set_len(0)
+        .and_then(|()| write!(file, "{}", std::process::id()))
+        .wrap_err_with(|| format!("writing {} failed", path.display()))?;
+    Ok(Ok(StateLock { _file: file }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn second_instance_is_read_only() {
+        let dir = env::temp_dir().join(format!("counter-demo-lock-{}", std::process::id()));
+        let state_path = dir.join("state.json");
+
+        let lock = acquire(&state_path).unwrap().unwrap();
+        // 同一个进程中另外打开的文件也拿不到锁，和另一个实例一样。
+        let held = acquire(&state_path).unwrap().unwrap_err();
+        assert_eq!(
+            held,
+            Held {
+                pid: Some(std::process::id())
+            }
+        );
+        assert_eq!(
+            Held { pid: None }.message("work"),
+            "Profile work is open in another instance, changes will not be saved"
+        );
+
+        drop(lock);
+        assert!(acquire(&state_path).unwrap().is_ok());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -3,11 +3,15 @@
 //! 2. 循环运行应用程序，直到用户退出应用程序
 //! 3. 将终端恢复到原始状态
 
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use clap::Parser;
+use crossterm::event::{
+    Event as TerminalEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent,
+    MouseEventKind,
+};
 
 use ratatui::{
+    layout::Size,
     prelude::*,
-    symbols::border,
     widgets::{block::*, *},
 };
 
@@ -16,194 +20,3454 @@ use color_eyre::{
     Result,
 };
 
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    process::ExitCode,
+    sync::mpsc::Sender,
+    time::{Duration, Instant},
+};
+
+use chrono::{Local, Utc};
+use ratatui_common::{
+    animation::{self, Animation, Easing, Lerp},
+    capabilities::Capabilities,
+    console::{Console, ConsoleAction},
+    inspector::Inspector,
+    motion::Motion,
+    recording::{Player, Recorder},
+    text_input::Input,
+    time_travel::TimeTravel,
+    toast::Toasts,
+};
+
+use crate::{
+    acceleration::Acceleration,
+    autosave::{Autosave, Snapshot, Writer},
+    backup::Backups,
+    cli::Cli,
+    clock::Clock,
+    config::Config,
+    event::{Event, EventChannel, EventSource, TerminalEvents},
+    help::{HelpView, KeymapHelp},
+    history::{Change, HistoryAction, HistoryView},
+    keymap::{Action, Keymap, Lookup},
+    line_output::LineOutput,
+    lock::StateLock,
+    paths::Paths,
+    picker::{Picker, PickerAction},
+    plugins::LoadedPlugin,
+    profile::Profiles,
+    rate_limit::RateLimit,
+    recovery::{Crash, Recovery},
+    schedule::Schedule,
+    session::{Session, Sessions, Settings},
+    state::State,
+    theme::{Decorations, Theme},
+    theme_editor::{ThemeEditor, ThemeEditorAction},
+    theme_picker::{Choice, ThemePicker, ThemePickerAction},
+    transition::Transition,
+    tutorial::Tutorial,
+    vault::PromptAction,
+};
+
+mod acceleration;
+mod autosave;
+mod backup;
+mod base16;
+mod batch;
+mod cli;
+mod clock;
+mod config;
+mod control;
+mod crdt;
+mod daemon;
 mod errors;
+mod event;
+mod expr;
+mod gossip;
+mod help;
+mod history;
+mod json;
+mod keymap;
+mod line_output;
+mod lock;
+#[cfg(windows)]
+mod named_pipe;
+mod notifications;
+mod paths;
+mod picker;
+mod plugins;
+mod profile;
+mod rate_limit;
+mod recovery;
+mod remote;
+mod schedule;
+#[cfg(feature = "lua")]
+mod scripting;
+mod session;
+#[cfg(unix)]
+mod signals;
+#[cfg(feature = "ssh")]
+mod ssh;
+mod state;
+mod stdin;
+mod telnet;
+mod text;
+mod theme;
+mod theme_editor;
+mod theme_picker;
+mod transition;
 mod tui;
+mod tutorial;
+mod vault;
+#[cfg(feature = "wasm")]
+mod wasm_plugins;
+#[cfg(any(windows, test))]
+mod windows_input;
 
 /// `main` 函数通过调用 `tui` 模块（接下来定义）中的方法来设置终端，然后创建并运行应用程序（稍后定义）。
 /// 它推迟评估调用 `App::run()` 的结果，直到终端恢复后，以确保在应用程序退出后将任何 `Error` 结果显示给用户。
-fn main() -> Result<()> {
+///
+/// 传入 `--replay <FILE>` 时，事件来自录制的日志而不是真实终端；传入 `--record <FILE>` 时把收到的事件录制下来。
+/// 状态和配置按 `--profile` 指定的档案分别保存，正常退出时保存状态。运行时在后台定期自动保存（见
+/// `autosave` 模块），上次没有正常退出时启动后询问是否恢复（见 `recovery` 模块）。
+///
+/// 退出码平时总是 0；`--exit-code` 时被 Ctrl-C 或信号中止返回 1，`--exit-with-value` 时返回最后的计数值，
+/// shell 的条件语句可以直接使用。`--json` 时界面以外的输出（包括错误）都是 JSON，见 `json` 模块。
+fn main() -> Result<ExitCode> {
+    let cli = Cli::parse();
     errors::install_hooks()?;
-    let mut terminal = tui::init()?;
-    App::default().run(&mut terminal)?;
-    Ok(())
+    let json = cli.json;
+    let result = run(cli);
+    if result.is_err() {
+        // 在打印错误之前恢复终端，错误中途返回时界面可能还没有恢复。
+        tui::restore()?;
+    }
+    if !json {
+        return result;
+    }
+    Ok(result.unwrap_or_else(|report| {
+        // 只有字符串，不会序列化失败。
+        eprintln!(
+            "{}",
+            json::line(&json::Failure::from(&report)).unwrap_or_default()
+        );
+        ExitCode::FAILURE
+    }))
+}
+
+fn run(cli: Cli) -> Result<ExitCode> {
+    let paths = Paths::detect();
+    if cli.paths && cli.json {
+        println!("{}", json::line(&paths)?);
+        return Ok(ExitCode::SUCCESS);
+    }
+    if cli.paths {
+        println!("config: {}", paths.config.display());
+        println!("state:  {}", paths.state.display());
+        println!("cache:  {}", paths.cache.display());
+        println!("data:   {}", paths.data.display());
+        return Ok(ExitCode::SUCCESS);
+    }
+    let profiles = Profiles::new(&paths);
+    let config = match &cli.config {
+        Some(path) => Config::load(path)?,
+        None => {
+            let path = profiles.config_path(&cli.profile);
+            if path.exists() {
+                Config::load(&path)?
+            } else {
+                Config::default()
+            }
+        }
+    };
+    let state_backups = config.state_backups.unwrap_or(backup::DEFAULT_KEEP);
+    // 恢复备份之前先锁住档案，之后占用档案时接着使用这个锁。
+    let mut restore_lock = match &cli.restore {
+        Some(name) => Some(restore_from_backup(
+            &profiles,
+            &cli.profile,
+            name,
+            state_backups,
+        )?),
+        None => None,
+    };
+    // 加密的状态文件要在全屏界面中输入口令解锁，之前先用默认的状态。
+    let envelope = state::encrypted(&profiles.state_path(&cli.profile))?;
+    if (envelope.is_some() || cli.encrypt) && !cfg!(feature = "encrypt") {
+        bail!("encrypting the state file needs the `encrypt` feature");
+    }
+    let repl = cli.command == Some(cli::Command::Repl);
+    if repl && (cli.daemon || cli.line_output || cli.batch.is_some() || cli.encrypt) {
+        bail!("repl cannot be combined with --daemon, --line-output, --batch or --encrypt");
+    }
+    if envelope.is_some() && (cli.daemon || cli.line_output || repl) {
+        bail!("the state file is encrypted, unlock it in the full-screen interface");
+    }
+    let state = match envelope {
+        Some(_) => State::default(),
+        None => State::load(&profiles.state_path(&cli.profile))?,
+    };
+    #[cfg(feature = "lua")]
+    let script_path = profiles.script_path(&cli.profile);
+    let replay = match &cli.replay {
+        Some(path) => Some(
+            Player::<Event>::from_file(path)
+                .wrap_err_with(|| format!("reading {} failed", path.display()))?,
+        ),
+        None => None,
+    };
+    let no_color = cli.plain || theme::no_color();
+    // 没有指定 `--theme` 时使用上次在主题选择界面中选择的主题，找不到时使用默认主题。
+    let theme_choice = match (cli.theme, &state.theme) {
+        (Some(name), _) => Choice::Builtin(name),
+        (None, Some(id)) if !no_color => Choice::find(id, &paths.themes())?.unwrap_or_default(),
+        (None, _) => Choice::default(),
+    };
+    let theme = if no_color {
+        Theme::plain()
+    } else {
+        theme_choice.theme(&Capabilities::default())
+    };
+    #[cfg(feature = "ssh")]
+    if let Some(address) = &cli.ssh {
+        return ssh::serve(address, theme).map(|()| ExitCode::SUCCESS);
+    }
+    if let Some(address) = &cli.telnet {
+        return telnet::serve(address, theme).map(|()| ExitCode::SUCCESS);
+    }
+    let motion = Motion {
+        reduced: cli.reduced_motion || config.reduced_motion,
+    };
+    let mut app = App {
+        profile: cli.profile,
+        profiles: Some(profiles),
+        state_backups,
+        title: cli.title,
+        motion,
+        toasts: Toasts::new().slide_in(TOAST_SLIDE_IN, motion),
+        theme_choice,
+        saved_theme: state.theme.clone(),
+        themes: Some(paths.themes()),
+        no_color,
+        ..App::new(theme)
+    };
+    app.plugins = plugins::discover(&paths.plugins())?;
+    #[cfg(feature = "wasm")]
+    {
+        app.wasm_plugins = wasm_plugins::WasmPlugins::discover(&paths.plugins())?;
+        app.notify_wasm_plugins(wasm_plugins::EVENT_START, 0);
+    }
+    app.apply_state(state);
+    if let Some(name) = &cli.restore {
+        app.toasts
+            .info(format!("Restored backup {name}"), Instant::now());
+    }
+    app.max = config.counter_max;
+    app.milestones = config.milestones;
+    app.transition_kind = config.transition;
+    app.decorations = Decorations {
+        borders: config.borders,
+        symbols: config.symbols,
+    };
+    app.clock = config.clock.as_ref().map(Clock::new).transpose()?;
+    app.schedule = Schedule::new(&config.schedule, Local::now().naive_local())?;
+    if let Some(ms) = config.chord_timeout_ms {
+        app.keymap.timeout = Duration::from_millis(ms);
+    }
+    if let Err(error) = app.keymap.rebind(&config.keys) {
+        bail!("invalid [keys] in the config file: {error}");
+    }
+    #[cfg(feature = "lua")]
+    if let Some((scripts, bindings)) = scripting::Scripts::load(&script_path)? {
+        // 脚本中的绑定优先于默认绑定。
+        app.keymap.bindings.splice(0..0, bindings);
+        app.scripts = Some(scripts);
+        app.run_script(|scripts, counter, max| scripts.on_start(counter, max))?;
+    }
+    if cli.print_result {
+        tui::draw_to_stderr();
+    }
+    let mut output = if let Some(path) = &cli.batch {
+        // 批处理不保存状态，也不占用档案；关闭动画，同样的命令总是画出同样的画面。
+        app.profiles = None;
+        app.motion = Motion::reduced();
+        app.toasts = Toasts::new().slide_in(TOAST_SLIDE_IN, app.motion);
+        Output::Batch(batch::load(path)?)
+    } else if repl {
+        Output::Repl
+    } else if cli.daemon {
+        Output::Daemon
+    } else if cli.line_output {
+        tui::init_raw()?;
+        Output::Lines
+    } else {
+        let (terminal, capabilities) = tui::init(tui::Options {
+            alternate_screen: !cli.no_alt_screen,
+        })?;
+        app.theme = app.theme.adapt(&capabilities);
+        app.synchronized_output = capabilities.synchronized_output;
+        app.capabilities = capabilities;
+        app.unlock = match envelope {
+            Some(envelope) => Some(vault::Prompt::unlock(envelope)),
+            None if cli.encrypt => Some(vault::Prompt::create()),
+            None => None,
+        };
+        // 教程只在全屏界面中显示。解锁前还不知道有没有完成过，不显示。
+        if cli.tutorial || !(app.tutorial_done || app.unlock.is_some()) {
+            app.tutorial = Some(Tutorial::default());
+        }
+        Output::Terminal(terminal)
+    };
+    // 终端事件和信号汇入同一个通道，Ctrl-C 或 kill 也会经过主循环正常退出：
+    // 恢复终端并保存状态。
+    let channel = EventChannel::default();
+    let source: Box<dyn EventSource<Event> + Send> = match replay {
+        Some(replay) => Box::new(replay.speed(cli.replay_speed).then(TerminalEvents)),
+        None => Box::new(TerminalEvents),
+    };
+    // 守护进程没有终端，只处理信号和控制接口的命令。
+    match &cli.record {
+        _ if cli.daemon || cli.batch.is_some() || repl => {}
+        Some(path) => channel.spawn_source(
+            Recorder::create(source, path)
+                .wrap_err_with(|| format!("creating {} failed", path.display()))?,
+        ),
+        None => channel.spawn_source(source),
+    }
+    #[cfg(unix)]
+    signals::forward(channel.sender())?;
+    if repl {
+        stdin::forward_lines(channel.sender());
+    } else {
+        #[cfg(unix)]
+        stdin::forward(channel.sender());
+    }
+    // 控制接口在 main 返回时关闭，Unix 上同时删除套接字文件。
+    #[cfg(unix)]
+    let _control = match &cli.control {
+        Some(path) => Some(
+            control::Listener::bind(path.as_ref(), channel.sender())
+                .wrap_err_with(|| format!("listening on {path} failed"))?,
+        ),
+        None => None,
+    };
+    #[cfg(windows)]
+    if let Some(name) = &cli.control {
+        named_pipe::listen(name, channel.sender())
+            .wrap_err_with(|| format!("creating named pipe {name} failed"))?;
+    }
+    if let Some(address) = &cli.gossip {
+        let replica = gossip::Replica::start(address, &cli.peers, channel.sender())
+            .wrap_err_with(|| format!("listening on {address} failed"))?;
+        // 共享的计数器从合并后的值开始，而不是本地保存的值。
+        app.counter = 0;
+        app.replica = Some(replica);
+    }
+    if let Some(address) = &cli.attach {
+        let (client, counter) = daemon::Client::connect(address, channel.sender())
+            .wrap_err_with(|| format!("connecting to {address} failed"))?;
+        // 状态由守护进程保存，界面退出时不写入档案。
+        app.counter = counter;
+        app.profiles = None;
+        app.daemon = Some(client);
+    }
+    // 另一个实例正在使用这个档案时只读运行，守护进程只读就没有意义了。
+    if let Some(profiles) = &app.profiles {
+        let acquired = match restore_lock.take() {
+            Some(lock) => Ok(lock),
+            None => lock::acquire(&profiles.state_path(&app.profile))?,
+        };
+        match acquired {
+            Ok(lock) => app.lock = Some(lock),
+            Err(held) if cli.daemon => bail!("{}", held.message(&app.profile)),
+            Err(held) => {
+                app.read_only = true;
+                app.toasts.warn(held.message(&app.profile), Instant::now());
+            }
+        }
+    }
+    // 只有全屏界面能询问是否恢复，也只在那里自动保存。加密时不写明文的崩溃恢复文件（见 `vault`
+    // 模块）。
+    if let (Output::Terminal(_), Some(_)) = (&output, &app.profiles) {
+        let now = Instant::now();
+        app.writer = Some(Writer::spawn()?);
+        let interval = config
+            .autosave_secs
+            .map_or(autosave::DEFAULT_INTERVAL, Duration::from_secs);
+        app.autosave = Autosave::new(interval, now);
+    }
+    if let (Output::Terminal(_), Some(profiles), None, false) =
+        (&output, &app.profiles, &app.unlock, app.read_only)
+    {
+        let now = Instant::now();
+        let (recovery, crash) = Recovery::start(&profiles.state_path(&app.profile), now)?;
+        app.recovery = Some(recovery);
+        match crash {
+            Some(Crash {
+                reason,
+                state: Some(state),
+            }) => app.restore_prompt = Some(recovery::Prompt { reason, state }),
+            Some(Crash { state: None, .. }) => {
+                app.toasts.warn("The last run did not exit cleanly", now)
+            }
+            None => {}
+        }
+    }
+    let events: Box<dyn EventSource<Event>> = Box::new(channel);
+    // Windows 控制台的输入先经过统一的修正层。
+    #[cfg(windows)]
+    let events: Box<dyn EventSource<Event>> = Box::new(windows_input::WindowsInput::new(events));
+    // 配置了限流时，最外层丢弃或推迟过多的输入事件。
+    let mut events: Box<dyn EventSource<Event>> = match config.rate_limit {
+        Some(limit) => Box::new(RateLimit::new(events, limit)),
+        None => events,
+    };
+    let app_result = match &mut output {
+        Output::Terminal(terminal) => app.run(terminal, events.as_mut()),
+        Output::Lines => app.run_lines(&mut LineOutput::new(tui::out()), events.as_mut()),
+        Output::Daemon => app.run_daemon(events.as_mut()),
+        Output::Repl => app.run_repl(&mut io::stdout(), events.as_mut(), cli.frame_size),
+        Output::Batch(lines) => match &cli.frames {
+            Some(path) => File::create(path)
+                .wrap_err_with(|| format!("creating {} failed", path.display()))
+                .and_then(|file| {
+                    let mut frames = BufWriter::new(file);
+                    app.run_batch(lines, cli.frame_size, Some(&mut frames))?;
+                    frames
+                        .flush()
+                        .wrap_err_with(|| format!("writing {} failed", path.display()))
+                }),
+            None => app.run_batch(lines, cli.frame_size, None),
+        },
+    };
+    if !matches!(output, Output::Daemon | Output::Batch(_) | Output::Repl) {
+        tui::restore()?;
+    }
+    app_result?;
+    if cli.print_result && cli.json {
+        let outcome = json::Outcome {
+            counter: app.counter,
+            profile: &app.profile,
+            aborted: app.aborted,
+        };
+        println!("{}", json::line(&outcome)?);
+    } else if cli.print_result {
+        println!("{}", app.counter);
+    }
+    app.save_state()?;
+    if let Some(writer) = app.writer.take() {
+        writer.finish()?;
+    }
+    if let Some(recovery) = app.recovery.take() {
+        recovery.finish()?;
+    }
+    Ok(app.exit_code(cli.exit_code, cli.exit_with_value).into())
+}
+
+/// `--restore`：锁住档案 `profile` 后用备份 `name` 替换状态文件，返回拿到的锁。
+///
+/// 另一个实例正在使用这个档案时不恢复，否则它之后保存时会覆盖恢复的状态。
+fn restore_from_backup(
+    profiles: &Profiles,
+    profile: &str,
+    name: &str,
+    keep: usize,
+) -> Result<StateLock> {
+    let path = profiles.state_path(profile);
+    let lock = match lock::acquire(&path)? {
+        Ok(lock) => lock,
+        Err(held) => bail!(
+            "profile {profile} is open in {}, not restoring backup {name}",
+            held.holder()
+        ),
+    };
+    Backups::new(&path, keep).restore(name, Local::now().naive_local())?;
+    Ok(lock)
+}
+
+/// 应用程序的输出方式。
+enum Output {
+    Terminal(tui::Tui),
+    /// `--line-output`：每次变化输出一行描述。
+    Lines,
+    /// `--daemon`：没有输出。
+    Daemon,
+    /// `--batch`：执行文件中的命令，画面写入 `--frames`。
+    Batch(Vec<batch::Line>),
+    /// `repl` 子命令：在提示符下逐行输入命令。
+    Repl,
 }
 
 /// 调用 `App::default()` 将创建一个 `App` ，其初始化为 `counter` 设置为 0， `exit` 设置为 false 。
 #[derive(Debug, Default)]
 pub struct App {
     counter: u8,
+    /// 计数器的变化历史，从旧到新排列。
+    history: Vec<Change>,
     exit: bool,
+    /// 退出是因为 Ctrl-C 或信号，而不是用户选择了退出。
+    aborted: bool,
+    /// 批处理和 `repl` 中为 true：计数器已经是 0 时减少返回错误，而不是像教程中那样溢出崩溃。
+    bounded: bool,
+    theme: Theme,
+    /// 当前使用的主题，在主题选择界面中标出。
+    theme_choice: Choice,
+    /// 在主题选择界面中选择过的主题，保存在状态文件中。
+    saved_theme: Option<String>,
+    /// base16 配色方案所在的目录，为 `None` 时（例如测试中）只有内置主题。
+    themes: Option<PathBuf>,
+    /// `--plain` 或 `NO_COLOR`：不使用颜色，也不能切换主题。
+    no_color: bool,
+    /// 终端的能力，切换主题时按它降级颜色。
+    capabilities: Capabilities,
+    /// 配置文件中每种界面的边框和符号。
+    decorations: Decorations,
+    /// 自定义标题，为 `None` 时使用 `DEFAULT_TITLE`。
+    title: Option<String>,
+    screen: Screen,
+    /// 当前档案的名称。
+    profile: String,
+    /// 档案的存储位置，为 `None` 时（例如测试中）不读写磁盘。
+    profiles: Option<Profiles>,
+    /// 覆盖状态文件之前保留的备份数。
+    state_backups: usize,
+    /// 当前会话的名称，还没有保存为会话时为 `None`。
+    session: Option<String>,
+    motion: Motion,
+    /// 计数器变化后数值闪烁的强度，从 1 渐变到 0，闪烁结束后为 `None`。
+    flash: Option<Animation<f64>>,
+    transition_kind: transition::Kind,
+    /// 正在播放的界面过渡。
+    transition: Option<Transition>,
+    /// 上一帧的界面和画出的内容（不含提示消息），界面改变时从它开始过渡。
+    last_frame: Option<(ScreenId, Buffer)>,
+    keymap: Keymap,
+    /// 已经输入、但还没有组成完整和弦的按键。
+    pending_keys: Vec<KeyCode>,
+    /// 等待和弦后续按键的截止时间。
+    pending_deadline: Option<Instant>,
+    /// 按住左右方向键时的加速状态。
+    acceleration: Acceleration,
+    /// 计数器的最大值，为 `None` 时使用 `COUNTER_MAX`。
+    max: Option<u8>,
+    /// 打开的表达式输入行。
+    expression: Option<ExpressionPrompt>,
+    /// 终端是否支持同步输出，支持时每一帧作为一次更新显示。
+    synchronized_output: bool,
+    /// 启动时从插件目录加载的插件面板。
+    plugins: Vec<LoadedPlugin>,
+    /// 当前档案的 `init.lua`，没有脚本时为 `None`。
+    #[cfg(feature = "lua")]
+    scripts: Option<scripting::Scripts>,
+    /// 在状态栏中显示文本的 WebAssembly 插件。
+    #[cfg(feature = "wasm")]
+    wasm_plugins: wasm_plugins::WasmPlugins,
+    /// 和其他实例共享计数器时本实例的副本。
+    replica: Option<gossip::Replica>,
+    /// 作为守护进程运行时订阅计数器变化的客户端。
+    watchers: Vec<Sender<control::Reply>>,
+    /// 连接到守护进程时的客户端，计数器的修改发送给守护进程。
+    daemon: Option<daemon::Client>,
+    /// 到达时发送桌面通知的计数值。
+    milestones: Vec<u8>,
+    /// 上边框左侧的时钟。
+    clock: Option<Clock>,
+    /// 配置文件中定时执行的动作。
+    schedule: Schedule,
+    /// 右下角的提示消息，例如保存了会话或者名称无效。
+    toasts: Toasts,
+    /// 正在进行的教程，只在计数器界面上显示。
+    tutorial: Option<Tutorial>,
+    /// 已经完成或者跳过了教程，保存在状态文件中。
+    tutorial_done: bool,
+    /// 标出各部件区域的布局检查器，用 F11 打开。
+    inspector: Inspector,
+    /// 用 F12 打开的调试控制台。
+    console: Console,
+    /// 每个操作和执行后的状态，用 F10 前后查看。
+    time_travel: TimeTravel<String, State>,
+    /// 在后台线程中写入自动保存的文件。
+    writer: Option<Writer>,
+    /// 定期把状态和当前会话保存到档案中。
+    autosave: Option<Autosave>,
+    /// 定期自动保存状态，正常退出时删除。
+    recovery: Option<Recovery>,
+    /// 上次没有正常退出时，询问是否恢复自动保存的状态。
+    restore_prompt: Option<recovery::Prompt>,
+    /// 当前档案的锁，防止另一个实例同时写入。
+    lock: Option<StateLock>,
+    /// 另一个实例拿着锁：照常使用，但不保存状态和会话。
+    read_only: bool,
+    /// 加密状态文件和会话的密钥，没有加密时为 `None`。
+    key: Option<vault::Key>,
+    /// 启动时解锁加密的状态文件，或者为 `--encrypt` 设置口令。
+    unlock: Option<vault::Prompt>,
+}
+
+/// 用 `=` 打开的表达式输入行，计算结果赋值给计数器。
+#[derive(Debug, Default)]
+struct ExpressionPrompt {
+    input: Input,
+    /// 上一次计算失败的原因，显示在输入行下方。
+    error: Option<String>,
+}
+
+/// 当前显示的界面。
+#[derive(Debug, Default)]
+enum Screen {
+    #[default]
+    Counter,
+    /// 宽字符演示视图。
+    WideDemo,
+    /// 档案切换界面。
+    Profiles(Picker),
+    /// 会话切换界面。
+    Sessions(Picker),
+    /// 恢复状态备份的界面。
+    Backups(Picker),
+    /// 接下来定时执行的动作。
+    Schedule,
+    /// 历史时间线。
+    History(HistoryView),
+    /// 主题选择界面。
+    Themes(ThemePicker),
+    /// 主题编辑器。
+    ThemeEditor(ThemeEditor),
+    /// 按键帮助，只列出 `back` 中可以用的按键，关闭后回到 `back`。
+    Help { view: HelpView, back: Box<Screen> },
+    /// 插件面板，值为 `App::plugins` 中的下标。
+    Plugin(usize),
 }
 
+/// 区分界面的标识：界面的种类，插件面板还包括是第几个插件。
+type ScreenId = (std::mem::Discriminant<Screen>, Option<usize>);
+
+impl Screen {
+    fn id(&self) -> ScreenId {
+        let plugin = match self {
+            Self::Plugin(index) => Some(*index),
+            _ => None,
+        };
+        (std::mem::discriminant(self), plugin)
+    }
+
+    /// 配置边框和符号时这个界面的名称。
+    fn widget(&self) -> theme::Widget {
+        match self {
+            Self::Counter => theme::Widget::Counter,
+            Self::WideDemo => theme::Widget::WideDemo,
+            Self::Profiles(_) | Self::Sessions(_) | Self::Backups(_) => theme::Widget::Picker,
+            Self::History(_) => theme::Widget::History,
+            Self::Themes(_) => theme::Widget::Themes,
+            Self::ThemeEditor(_) => theme::Widget::ThemeEditor,
+            Self::Help { .. } => theme::Widget::Help,
+            Self::Plugin(_) => theme::Widget::Plugin,
+            Self::Schedule => theme::Widget::Schedule,
+        }
+    }
+}
+
+/// 只读时试图保存的提示。
+const READ_ONLY: &str = "Read-only: another instance is using this profile";
+
+/// 计数器变化时数值闪烁的时长。
+const FLASH_DURATION: Duration = Duration::from_millis(200);
+
+/// 提示消息滑入的时长。
+const TOAST_SLIDE_IN: Duration = Duration::from_millis(150);
+
+const DEFAULT_TITLE: &str = "Counter App Tutorial";
+
+/// 宽字符演示视图中展示的样例，覆盖中日韩文字、混排文本和表情符号。
+const WIDE_SAMPLES: [&str; 6] = [
+    "计数器应用程序教程",
+    "カウンターアプリのチュートリアル",
+    "카운터 앱 튜토리얼",
+    "状态：计数器已达到上限",
+    "Mixed 混合 text 文本",
+    "Emoji 🎉🚀 表情",
+];
+
+/// 计数器默认允许的最大值，超过它时 `increment_counter` 会返回错误。可以通过配置文件的 `counter_max` 修改。
+const COUNTER_MAX: u8 = 2;
+
 /// 大多数应用程序都有一个主循环，一直运行到用户选择退出为止。
 /// 循环的每次迭代都会通过调用 `Terminal::draw()` 绘制单个帧，然后更新应用程序的状态。
 ///
 /// 使用新的 run 方法为 App 创建一个 impl 块，该方法将充当应用程序的主循环。
 impl App {
-    pub fn run(&mut self, terminal: &mut tui::Tui) -> Result<()> {
+    /// `widget` 界面使用的主题：按配置替换边框和符号。
+    fn theme_for(&self, widget: theme::Widget) -> Theme {
+        self.decorations
+            .apply(self.theme, widget, &self.capabilities)
+    }
+
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            theme,
+            profile: profile::DEFAULT_PROFILE.to_string(),
+            state_backups: backup::DEFAULT_KEEP,
+            ..Default::default()
+        }
+    }
+
+    /// 绘制到任意后端：本地终端，或者 SSH 会话的连接。
+    pub fn run<B: Backend + Write>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        // 只在检查器打开时捕获鼠标。
+        let mut mouse_capture = false;
+        while !self.exit {
+            if self.inspector.is_enabled() != mouse_capture {
+                mouse_capture = !mouse_capture;
+                tui::set_mouse_capture(terminal, mouse_capture)?;
+            }
+            self.animate(Instant::now());
+            let synchronized = self.synchronized_output;
+            tui::draw(terminal, synchronized, |frame| self.render_frame(frame))?;
+            // 有计时器（动画、和弦超时、时钟）时只等待到最早的截止时间，超时后更新状态并重绘。
+            if let Some(deadline) = self.next_deadline() {
+                if !events.poll(deadline.saturating_duration_since(Instant::now()))? {
+                    self.expire_timers(Instant::now());
+                    continue;
+                }
+            }
+            self.handle_events(events)
+                .wrap_err("handle events failed")?;
+            self.console
+                .watch(&(self.screen.widget(), self.session_snapshot()));
+        }
+        if mouse_capture {
+            tui::set_mouse_capture(terminal, false)?;
+        }
+
+        Ok(())
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        let now = Instant::now();
+        [
+            self.flash.as_ref().and_then(|flash| flash.next_frame(now)),
+            self.transition
+                .as_ref()
+                .and_then(|transition| transition.next_frame(now)),
+            self.toasts.next_frame(now),
+            self.pending_deadline,
+            self.clock.as_ref().map(Clock::deadline),
+            self.toasts.next_deadline(),
+            self.autosave.as_ref().map(Autosave::deadline),
+            self.recovery.as_ref().map(Recovery::deadline),
+            self.schedule.deadline(Local::now().naive_local(), now),
+            // 安排界面中的倒计时每秒更新。
+            matches!(self.screen, Screen::Schedule).then(|| now + Duration::from_secs(1)),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+    }
+
+    /// 推进闪烁、界面过渡和提示消息滑入的动画，每一帧绘制前调用。
+    fn animate(&mut self, now: Instant) {
+        if self.flash.as_mut().is_some_and(|flash| !flash.tick(now)) {
+            self.flash = None;
+        }
+        if self
+            .transition
+            .as_mut()
+            .is_some_and(|transition| !transition.tick(now))
+        {
+            self.transition = None;
+        }
+        self.toasts.tick(now);
+    }
+
+    fn expire_timers(&mut self, now: Instant) {
+        if self
+            .pending_deadline
+            .is_some_and(|deadline| deadline <= now)
+        {
+            self.clear_pending_keys();
+        }
+        if let Some(clock) = &mut self.clock {
+            if clock.deadline() <= now {
+                clock.tick(now, Utc::now());
+            }
+        }
+        self.toasts.expire(now);
+        self.autosave(now);
+        for rule in self.schedule.due(Local::now().naive_local()) {
+            self.run_scheduled(&rule);
+        }
+    }
+
+    /// 执行到期的规则。计数器已经到达边界时和控制接口的命令一样不执行，而不是溢出。
+    fn run_scheduled(&mut self, rule: &schedule::Rule) {
+        self.console.message(format!("Scheduled {rule}"));
+        let result = match rule.action {
+            Action::Increment => self.control(control::Command::Increment),
+            Action::Decrement => self.control(control::Command::Decrement),
+            action => self
+                .perform(action)
+                .map(|()| self.counter)
+                .map_err(|error| format!("{error:#}")),
+        };
+        if let Err(message) = result {
+            self.toasts
+                .warn(format!("Skipped {rule}: {message}"), Instant::now());
+        }
+    }
+
+    /// 到时间后把状态交给后台线程写入，并报告之前失败的写入。
+    fn autosave(&mut self, now: Instant) {
+        let Some(writer) = self.writer.as_ref().filter(|_| self.can_save()) else {
+            return;
+        };
+        if self
+            .recovery
+            .as_ref()
+            .is_some_and(|recovery| recovery.deadline() <= now)
+        {
+            let state = self.state();
+            if let Some(recovery) = &mut self.recovery {
+                recovery.tick(&state, now, writer);
+            }
+        }
+        if self
+            .autosave
+            .as_ref()
+            .is_some_and(|autosave| autosave.deadline() <= now)
+        {
+            let snapshot = Snapshot {
+                state: self.state(),
+                session: self
+                    .session
+                    .clone()
+                    .map(|name| (name, self.session_snapshot())),
+            };
+            if let (Some(snapshot), Some(profiles), Some(backups)) = (
+                self.autosave
+                    .as_mut()
+                    .and_then(|autosave| autosave.tick(snapshot, now)),
+                &self.profiles,
+                self.backups(),
+            ) {
+                let sessions = profiles.sessions(&self.profile).with_key(self.key.clone());
+                writer.submit(move || {
+                    backups.save(&snapshot.state, Local::now().naive_local())?;
+                    match &snapshot.session {
+                        Some((name, session)) => sessions.save(name, session),
+                        None => Ok(()),
+                    }
+                });
+            }
+        }
+        for error in writer.errors() {
+            self.toasts.warn(format!("Autosave failed: {error}"), now);
+        }
+    }
+
+    /// 等待后台的写入完成，接下来同步写入同样的文件时不会被较早的快照覆盖。
+    fn flush_writes(&self) {
+        if let Some(writer) = &self.writer {
+            writer.flush();
+        }
+    }
+
+    fn clear_pending_keys(&mut self) {
+        self.pending_keys.clear();
+        self.pending_deadline = None;
+    }
+
+    /// 屏幕阅读器友好的主循环：不重绘整个屏幕，只在计数器变化时输出一行描述。
+    pub fn run_lines<W: Write>(
+        &mut self,
+        out: &mut LineOutput<W>,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        out.say(&format!(
+            "counter is {}. press Left to decrement, Right to increment, Q to quit",
+            self.counter
+        ))?;
+        while !self.exit {
+            let previous = self.counter;
+            self.handle_events(events)
+                .wrap_err("handle events failed")?;
+            if self.counter != previous {
+                out.say(&self.describe_counter())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 作为守护进程运行：没有界面，只处理事件，直到收到 `quit` 命令或退出信号。
+    pub fn run_daemon(&mut self, events: &mut dyn EventSource<Event>) -> Result<()> {
         while !self.exit {
-            terminal.draw(|frame| self.render_frame(frame))?;
-            self.handle_events().wrap_err("handle events failed")?;
+            self.handle_events(events)
+                .wrap_err("handle events failed")?;
+        }
+        Ok(())
+    }
+
+    /// 批处理：依次执行命令，遇到 `render` 时把画面写入 `frames`，执行完或者退出时返回。
+    pub fn run_batch(
+        &mut self,
+        lines: &[batch::Line],
+        size: Size,
+        mut frames: Option<&mut dyn Write>,
+    ) -> Result<()> {
+        self.bounded = true;
+        let mut terminal = Terminal::new(backend::TestBackend::new(size.width, size.height))?;
+        for line in lines {
+            if self.exit {
+                break;
+            }
+            let result = match &line.instruction {
+                batch::Instruction::Render => match &mut frames {
+                    Some(frames) => terminal
+                        .draw(|frame| self.render_frame(frame))
+                        .and_then(|frame| batch::write_frame(frame.buffer, frames))
+                        .map_err(Into::into),
+                    None => Ok(()),
+                },
+                instruction => self.execute(instruction),
+            };
+            result.wrap_err_with(|| format!("line {} failed", line.number))?;
         }
+        Ok(())
+    }
 
+    /// `repl` 子命令：从 `events` 中读取输入的每一行作为命令执行，像控制接口一样回复 `ok` 和计数值，
+    /// 或者 `error` 和原因。`render` 把画面输出到 `out`。其他事件（信号、控制接口的命令）照常处理。
+    pub fn run_repl(
+        &mut self,
+        out: &mut impl Write,
+        events: &mut dyn EventSource<Event>,
+        size: Size,
+    ) -> Result<()> {
+        self.bounded = true;
+        writeln!(
+            out,
+            "counter is {}. enter commands such as inc, set 5, key Right or render, Ctrl-D to quit",
+            self.counter
+        )?;
+        let mut terminal = Terminal::new(backend::TestBackend::new(size.width, size.height))?;
+        while !self.exit {
+            write!(out, "> ")?;
+            out.flush()?;
+            let line = loop {
+                match events.read()? {
+                    Event::Stdin { line } => break Some(line),
+                    event => self.handle_event(event)?,
+                }
+                if self.exit {
+                    break None;
+                }
+            };
+            let Some(line) = line else {
+                writeln!(out)?;
+                break;
+            };
+            match batch::parse_line(&line) {
+                Ok(None) => {}
+                Ok(Some(batch::Instruction::Render)) => {
+                    let frame = terminal.draw(|frame| self.render_frame(frame))?;
+                    batch::write_frame(frame.buffer, out)?;
+                }
+                Ok(Some(instruction)) => match self.execute(&instruction) {
+                    Ok(()) => writeln!(out, "ok {}", self.counter)?,
+                    Err(error) => writeln!(out, "error {error:#}")?,
+                },
+                Err(error) => writeln!(out, "error {error}")?,
+            }
+        }
         Ok(())
     }
 
+    /// 执行一条命令，和在界面中操作一样。批处理和 `repl` 子命令共用。
+    fn execute(&mut self, instruction: &batch::Instruction) -> Result<()> {
+        match instruction {
+            batch::Instruction::Action(action) => self.perform(*action),
+            batch::Instruction::Keys(keys) => keys
+                .iter()
+                .try_for_each(|&code| self.handle_key_event(code.into())),
+            batch::Instruction::Control(command) => match self.control(*command) {
+                Ok(_) => Ok(()),
+                Err(message) => bail!("{message}"),
+            },
+            batch::Instruction::Render => Ok(()),
+        }
+    }
+
+    /// 用一句话描述计数器的当前状态。
+    fn describe_counter(&self) -> String {
+        if self.counter >= self.max() {
+            format!("counter is now {} (max)", self.counter)
+        } else {
+            format!("counter is now {}", self.counter)
+        }
+    }
+
     /// 为了呈现 UI，应用程序使用接受 `Frame` 的闭包调用 `Terminal::draw()` 。
     /// `Frame` 上最重要的方法是 `render_widget()` ，它呈现实现 `Widget` 特征的任何类型，
     /// 例如 `Paragraph` 、 `List` 结构实现 `Widget` 特征，以便将与渲染相关的代码组织在一个地方。
     /// 这允许我们调用 `Frame::render_widget()` 并将闭包中的应用程序传递给 `Terminal::draw` 。
-    fn render_frame(&self, frame: &mut Frame) {
-        frame.render_widget(self, frame.size());
+    fn render_frame(&mut self, frame: &mut Frame) {
+        let area = frame.size();
+        let id = self.screen.id();
+        if let Some((last, from)) = self.last_frame.take() {
+            if last != id {
+                self.transition =
+                    Transition::start(self.transition_kind, from, self.motion, Instant::now());
+            }
+        }
+        self.inspector.begin_frame();
+        self.inspector
+            .record(format!("{:?}", self.screen.widget()), area);
+        // 时间旅行时暂时换成记录中的状态绘制，画完再换回来。
+        let live = self
+            .time_travel
+            .current()
+            .map(|step| step.state.clone())
+            .map(|state| {
+                let live = self.state();
+                self.apply_state(state);
+                live
+            });
+        let theme = self.theme_for(self.screen.widget());
+        match &mut self.screen {
+            Screen::Profiles(picker) | Screen::Sessions(picker) | Screen::Backups(picker) => {
+                picker.render(area, frame.buffer_mut(), &theme)
+            }
+            Screen::History(view) => view.render(area, frame.buffer_mut(), &self.history, &theme),
+            Screen::Themes(picker) => picker.render(area, frame.buffer_mut(), &theme),
+            Screen::ThemeEditor(editor) => editor.render(area, frame.buffer_mut(), &theme),
+            Screen::Help { view, .. } => view.render(area, frame.buffer_mut(), &theme),
+            Screen::Schedule => {
+                self.schedule
+                    .render(area, frame.buffer_mut(), Local::now().naive_local(), &theme)
+            }
+            Screen::Plugin(index) => {
+                let plugin = &self.plugins[*index].plugin;
+                let block = Block::bordered()
+                    .title(format!(" {} ", plugin.name()))
+                    .border_set(theme.border_set)
+                    .border_style(theme.border)
+                    .style(theme.base);
+                let inner = block.inner(area);
+                frame.render_widget(block, area);
+                self.inspector.record(plugin.name(), inner);
+                plugin.render(inner, frame.buffer_mut());
+            }
+            Screen::Counter => {
+                frame.render_widget(&*self, area);
+                if let Some(tutorial) = &self.tutorial {
+                    let theme = self.theme_for(theme::Widget::Tutorial);
+                    self.inspector
+                        .record("Tutorial", tutorial.region().area(area));
+                    tutorial.render(area, frame.buffer_mut(), &self.keymap, &theme);
+                }
+            }
+            Screen::WideDemo => frame.render_widget(&*self, area),
+        }
+        if let Some(prompt) = &self.restore_prompt {
+            prompt.render(area, frame.buffer_mut(), &theme);
+        }
+        if let Some(prompt) = &self.unlock {
+            prompt.render(area, frame.buffer_mut(), &theme);
+        }
+        if let Some(live) = live {
+            self.apply_state(live);
+        }
+        if let Some(transition) = &self.transition {
+            transition.render(frame.buffer_mut(), &self.capabilities);
+        }
+        self.last_frame = Some((id, frame.buffer_mut().clone()));
+        frame.render_widget(&self.toasts, area);
+        if self.console.is_open() {
+            self.inspector.record("Console", Console::area(area));
+        }
+        frame.render_widget(&self.console, area);
+        self.inspector.render(frame.buffer_mut());
     }
 
-    fn handle_events(&mut self) -> Result<()> {
+    fn handle_events(&mut self, events: &mut dyn EventSource<Event>) -> Result<()> {
         // event::read 函数会阻塞，直到发生事件为止。
         // 如果您的应用程序需要执行 UI 之外的其他任务，那么它应该通过调用 event::poll 来检查是否存在待处理事件，
         // 并设置适合您的应用程序的合理超时时间。有关此内容的更多信息将在以后的章节中介绍。
-        match event::read()? {
+        let event = events.read()?;
+        self.handle_event(event)
+    }
+
+    fn handle_event(&mut self, event: Event) -> Result<()> {
+        if !matches!(
+            event,
+            Event::Terminal(TerminalEvent::Mouse(MouseEvent {
+                kind: MouseEventKind::Moved,
+                ..
+            }))
+        ) {
+            self.console.event(format!("{event:?}"));
+        }
+        if let Event::Terminal(TerminalEvent::Mouse(mouse)) = &event {
+            if self.inspector.handle_mouse(mouse) {
+                return Ok(());
+            }
+        }
+        // 插件面板先收到终端事件，插件没有处理的事件再按计数器的按键绑定处理。
+        if let (Event::Terminal(terminal_event), Screen::Plugin(index)) = (&event, &self.screen) {
+            if self.plugins[*index].plugin.handle_event(terminal_event) {
+                return Ok(());
+            }
+        }
+        match event {
             // 检查该事件是否为按键事件非常重要，因为 crossterm 还会在 Windows 上发出按键释放和重复事件。
             // 检查它是否等于 KeyEventKind::Press 非常重要，否则您的应用程序可能会看到重复的事件（按键按下、按键重复和按键向上）。
-            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => self
+            // 重复事件交给 `handle_key_event` 用于按住加速，释放事件只用来结束加速。
+            Event::Terminal(TerminalEvent::Key(key_event))
+                if key_event.kind == KeyEventKind::Release =>
+            {
+                self.acceleration.release();
+                Ok(())
+            }
+            Event::Terminal(TerminalEvent::Key(key_event)) => self
                 .handle_key_event(key_event)
                 .wrap_err_with(|| format!("handling key event failed:\n{key_event:#?}")),
-            _ => Ok(()),
+            // SIGUSR1 和 SIGUSR2 像控制接口的命令一样修改计数器，到了边界时提示而不是报错。
+            Event::Signal(signal @ (event::Signal::Increment | event::Signal::Decrement)) => {
+                let command = if signal == event::Signal::Increment {
+                    control::Command::Increment
+                } else {
+                    control::Command::Decrement
+                };
+                if let Err(message) = self.control(command) {
+                    self.toasts.warn(
+                        format!("Ignored {signal:?} signal: {message}"),
+                        Instant::now(),
+                    );
+                }
+                Ok(())
+            }
+            // SIGINT、SIGTERM 和 SIGHUP 都按正常退出处理，由 main 恢复终端并保存状态。
+            Event::Signal(_) => {
+                self.abort();
+                Ok(())
+            }
+            Event::Control(request) if request.command == control::Command::Watch => {
+                let counter = self.counter;
+                self.watchers.extend(request.subscribe(counter));
+                Ok(())
+            }
+            Event::Control(request) => {
+                let reply = self.control(request.command);
+                request.respond(reply);
+                Ok(())
+            }
+            Event::Gossip(message) => self.recorded("Gossip".into(), |app| {
+                app.merge_gossip(&message);
+                Ok(())
+            }),
+            // 守护进程中的计数器被其他客户端修改了。
+            Event::Daemon(counter) => self.recorded(format!("Daemon({counter})"), |app| {
+                app.sync_counter(counter);
+                Ok(())
+            }),
+            Event::Stdin { line } => {
+                let reply = stdin::parse(&line).and_then(|command| {
+                    command.map_or(Ok(self.counter), |command| self.control(command))
+                });
+                if let Err(message) = reply {
+                    self.toasts.warn(
+                        format!("Ignored line from stdin: {message}"),
+                        Instant::now(),
+                    );
+                }
+                Ok(())
+            }
+            Event::Terminal(_) => Ok(()),
         }
     }
 
     /// 用于处理按键事件。
     fn handle_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
-        // KeyCode 表示按下了哪个特定键。
-        match key_event.code {
-            KeyCode::Char('q') => self.exit(),
-            KeyCode::Left => self.decrement_counter()?,
-            KeyCode::Right => self.increment_counter()?,
-            _ => {}
+        // 原始模式下 Ctrl-C 不会产生 SIGINT，在任何界面都直接退出。
+        if key_event.code == KeyCode::Char('c') && key_event.modifiers == KeyModifiers::CONTROL {
+            self.abort();
+            return Ok(());
+        }
+        match self.console.handle_key_event(key_event) {
+            ConsoleAction::Ignored => {}
+            ConsoleAction::Handled => return Ok(()),
+            ConsoleAction::Error(message) => bail!("{message}"),
+        }
+        if let Some(prompt) = &mut self.unlock {
+            match prompt.handle_key_event(key_event) {
+                PromptAction::None => return Ok(()),
+                PromptAction::Unlocked(key, state) => {
+                    self.key = Some(key);
+                    self.saved_theme = state.theme.clone();
+                    self.apply_state(state);
+                }
+                PromptAction::Created(key) => {
+                    self.key = Some(key);
+                    self.unlock = None;
+                    self.encrypt_saved_files()?;
+                    self.toasts
+                        .info("The state is now encrypted", Instant::now());
+                }
+                PromptAction::Cancel => {}
+                PromptAction::Quit => self.exit(),
+            }
+            self.unlock = None;
+            return Ok(());
+        }
+        if let Some(prompt) = &self.restore_prompt {
+            match key_event.code {
+                KeyCode::Char('y') => {
+                    self.apply_state(prompt.state.clone());
+                    self.toasts
+                        .info("Restored the autosaved state", Instant::now());
+                }
+                KeyCode::Char('n') | KeyCode::Esc => {}
+                _ => return Ok(()),
+            }
+            self.restore_prompt = None;
+            return Ok(());
+        }
+        if self.time_travel.is_traveling() {
+            self.handle_time_travel_key(key_event);
+            return Ok(());
+        }
+        #[cfg(feature = "wasm")]
+        if let (KeyCode::Char(c), KeyEventKind::Press) = (key_event.code, key_event.kind) {
+            self.notify_wasm_plugins(wasm_plugins::EVENT_KEY, c as i32);
+        }
+        if let Screen::Profiles(picker) = &mut self.screen {
+            match picker.handle_key_event(key_event) {
+                PickerAction::None => {}
+                PickerAction::Close => self.screen = Screen::Counter,
+                PickerAction::Help => self.open_help(),
+                PickerAction::Switch(name) | PickerAction::Create(name) => {
+                    // 名称会成为目录名，无效的名称不使用，留在选择界面。
+                    match profile::parse_name(&name) {
+                        Ok(name) => {
+                            self.switch_profile(name)?;
+                            self.screen = Screen::Counter;
+                        }
+                        Err(error) => self.toasts.error(error, Instant::now()),
+                    }
+                }
+            }
+            return Ok(());
+        }
+        if let Screen::Sessions(picker) = &mut self.screen {
+            match picker.handle_key_event(key_event) {
+                PickerAction::None => {}
+                PickerAction::Close => self.screen = Screen::Counter,
+                PickerAction::Help => self.open_help(),
+                PickerAction::Switch(name) => {
+                    self.switch_session(name)?;
+                    self.screen = Screen::Counter;
+                }
+                PickerAction::Create(name) => match profile::parse_name(&name) {
+                    Ok(name) => {
+                        self.save_session_as(name)?;
+                        self.screen = Screen::Counter;
+                    }
+                    Err(error) => self.toasts.error(error, Instant::now()),
+                },
+            }
+            return Ok(());
+        }
+        if let Screen::Backups(picker) = &mut self.screen {
+            match picker.handle_key_event(key_event) {
+                PickerAction::None | PickerAction::Create(_) => {}
+                PickerAction::Close => self.screen = Screen::Counter,
+                PickerAction::Help => self.open_help(),
+                PickerAction::Switch(name) => {
+                    self.restore_backup(&name);
+                    self.screen = Screen::Counter;
+                }
+            }
+            return Ok(());
+        }
+        if let Screen::History(view) = &mut self.screen {
+            match view.handle_key_event(key_event, &self.history) {
+                HistoryAction::None => {}
+                HistoryAction::Close => self.screen = Screen::Counter,
+                HistoryAction::Help => self.open_help(),
+                HistoryAction::Jump(value) => {
+                    self.set_counter(value);
+                    self.screen = Screen::Counter;
+                }
+            }
+            return Ok(());
+        }
+        if let Screen::Themes(picker) = &mut self.screen {
+            match picker.handle_key_event(key_event) {
+                ThemePickerAction::None => {}
+                ThemePickerAction::Close => self.screen = Screen::Counter,
+                ThemePickerAction::Help => self.open_help(),
+                ThemePickerAction::Apply(choice) => {
+                    self.apply_theme(choice);
+                    self.screen = Screen::Counter;
+                }
+                ThemePickerAction::Edit(choice) => {
+                    let theme = choice.theme(&Capabilities::default());
+                    self.screen = Screen::ThemeEditor(ThemeEditor::new(&theme, self.capabilities));
+                }
+            }
+            return Ok(());
+        }
+        if let Screen::ThemeEditor(editor) = &mut self.screen {
+            match editor.handle_key_event(key_event) {
+                ThemeEditorAction::None => {}
+                ThemeEditorAction::Close => self.screen = Screen::Counter,
+                ThemeEditorAction::Help => self.open_help(),
+                ThemeEditorAction::Save(scheme) => self.save_theme(scheme),
+            }
+            return Ok(());
+        }
+        if let Screen::Help { view, .. } = &mut self.screen {
+            if view.handle_key_event(key_event) {
+                if let Screen::Help { back, .. } = std::mem::take(&mut self.screen) {
+                    self.screen = *back;
+                }
+            }
+            return Ok(());
+        }
+
+        if self.expression.is_some() {
+            self.handle_expression_key(key_event);
+            return Ok(());
+        }
+
+        if key_event.code == KeyCode::Esc
+            && self.pending_keys.is_empty()
+            && self.tutorial.is_some()
+            && matches!(self.screen, Screen::Counter)
+        {
+            self.finish_tutorial("Tutorial skipped, run with --tutorial to see it again");
+            return Ok(());
+        }
+
+        // 按住按键产生的重复事件只用于增减计数器，其他按键忽略重复事件。
+        if key_event.kind == KeyEventKind::Repeat
+            && !matches!(
+                self.keymap.lookup(&[key_event.code]),
+                Lookup::Action(Action::Increment | Action::Decrement)
+            )
+        {
+            return Ok(());
+        }
+
+        // KeyCode 表示按下了哪个特定键。按键先追加到未完成的和弦后面，再到按键绑定中查找。
+        let now = Instant::now();
+        self.expire_timers(now);
+        self.pending_keys.push(key_event.code);
+        let mut lookup = self.keymap.lookup(&self.pending_keys);
+        if lookup == Lookup::None && self.pending_keys.len() > 1 {
+            // 放弃未完成的和弦，把最后一个按键单独重新解释。
+            self.pending_keys = vec![key_event.code];
+            lookup = self.keymap.lookup(&self.pending_keys);
+        }
+        match lookup {
+            Lookup::Action(action) => {
+                self.clear_pending_keys();
+                if matches!(action, Action::Increment | Action::Decrement) {
+                    self.acceleration.press(key_event.code, now);
+                } else {
+                    self.acceleration.release();
+                }
+                self.perform(action)?;
+                if self
+                    .tutorial
+                    .as_mut()
+                    .is_some_and(|tutorial| tutorial.performed(action))
+                {
+                    self.finish_tutorial("Tutorial complete");
+                }
+            }
+            Lookup::Pending => self.pending_deadline = Some(now + self.keymap.timeout),
+            Lookup::None => self.clear_pending_keys(),
         }
         Ok(())
     }
 
-    fn exit(&mut self) {
-        self.exit = true;
+    fn perform(&mut self, action: Action) -> Result<()> {
+        self.console.message(format!("{action:?}"));
+        if action == Action::TimeTravel {
+            self.start_time_travel();
+            return Ok(());
+        }
+        self.recorded(format!("{action:?}"), |app| app.dispatch(action))
     }
 
-    fn increment_counter(&mut self) -> Result<()> {
-        self.counter += 1;
-        if self.counter > 2 {
-            bail!("counter overflow");
+    /// 执行一条消息，把执行后的状态记入时间旅行的记录。第一次执行前先记下初始状态。
+    fn recorded(
+        &mut self,
+        message: String,
+        update: impl FnOnce(&mut Self) -> Result<()>,
+    ) -> Result<()> {
+        if self.time_travel.is_empty() {
+            self.time_travel.record("Start".into(), self.state());
         }
+        update(self)?;
+        self.time_travel.record(message, self.state());
         Ok(())
     }
 
-    fn decrement_counter(&mut self) -> Result<()> {
-        self.counter -= 1;
+    fn dispatch(&mut self, action: Action) -> Result<()> {
+        match action {
+            Action::Quit => self.exit(),
+            Action::Decrement if self.bounded && self.counter == 0 => {
+                bail!("counter is at its minimum")
+            }
+            Action::Decrement => self.decrement_counter()?,
+            Action::Increment => {
+                self.increment_counter()?;
+                #[cfg(feature = "lua")]
+                self.run_script(|scripts, counter, max| scripts.on_increment(counter, max))?;
+            }
+            Action::Reset => self.set_counter(0),
+            Action::Center => self.set_counter(self.max() / 2),
+            Action::WideDemo => self.toggle_wide_demo(),
+            Action::Profiles => self.open_profile_picker()?,
+            Action::Sessions => self.open_session_picker()?,
+            Action::Backups => self.open_backup_picker()?,
+            Action::Schedule => self.toggle_schedule(),
+            Action::History => self.screen = Screen::History(HistoryView::default()),
+            Action::Themes => self.open_theme_picker(),
+            Action::Evaluate => self.expression = Some(ExpressionPrompt::default()),
+            Action::NextPane => self.next_pane(),
+            Action::Help => self.open_help(),
+            Action::Inspect => self.inspector.toggle(),
+            Action::TimeTravel => {}
+            #[cfg(feature = "lua")]
+            Action::Command(index) => {
+                self.run_script(|scripts, counter, max| scripts.run_command(index, counter, max))?
+            }
+        }
         Ok(())
     }
-}
 
-/// 首先，添加一个新的 `impl Widget for &App` 块。
-/// 我们在对 App 类型的引用上实现这一点，因为渲染函数不会改变任何状态，并且我们希望能够在调用绘图后使用该应用程序。
-///
-/// 渲染函数将创建一个带有标题、底部说明文本和一些边框的块。
-/// 使用块内的应用程序状态（ `App` 计数器字段的值）渲染 `Paragraph` 小部件。
-/// 块和段落将占据小部件的整个大小。
-impl Widget for &App {
-    fn render(self, area: Rect, buf: &mut Buffer)
-    where
-        Self: Sized,
-    {
-        let title = Title::from(" Counter App Tutorial ".bold());
-        let instructions = Title::from(Line::from(vec![
-            " Decrement ".into(),
-            "<Left>".blue().bold(),
-            " Increment ".into(),
-            "<Right>".blue().bold(),
-            " Quit ".into(),
-            "<Q> ".blue().bold(),
-        ]));
-        let block = Block::default()
-            .title(title.alignment(Alignment::Center))
-            .title(
-                instructions
-                    .alignment(Alignment::Center)
-                    .position(Position::Bottom),
-            )
-            .borders(Borders::ALL)
-            .border_set(border::THICK);
+    /// 进入时间旅行调试：回到计数器界面，停在最新的一步上。
+    fn start_time_travel(&mut self) {
+        if !self.time_travel.start() {
+            self.toasts.info("Nothing recorded yet", Instant::now());
+            return;
+        }
+        self.screen = Screen::Counter;
+        self.toasts.info(
+            "Time travel: Left/Right to step, Esc to return",
+            Instant::now(),
+        );
+    }
 
-        let counter_text = Text::from(vec![Line::from(vec![
-            "Value: ".into(),
-            self.counter.to_string().yellow(),
-        ])]);
+    /// 时间旅行时的按键：左右移动一步，Home 和 End 到两头，Esc 或者再按一次进入的按键回到真实的状态。
+    fn handle_time_travel_key(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Left | KeyCode::Char('h') => self.time_travel.back(),
+            KeyCode::Right | KeyCode::Char('l') => self.time_travel.forward(),
+            KeyCode::Home => self.time_travel.first(),
+            KeyCode::End => self.time_travel.last(),
+            KeyCode::Esc => self.time_travel.stop(),
+            code if self.keymap.lookup(&[code]) == Lookup::Action(Action::TimeTravel) => {
+                self.time_travel.stop()
+            }
+            _ => {}
+        }
+    }
 
-        Paragraph::new(counter_text)
-            .centered()
-            .block(block)
-            .render(area, buf);
+    fn handle_expression_key(&mut self, key_event: KeyEvent) {
+        let max = self.max();
+        let Some(prompt) = &mut self.expression else {
+            return;
+        };
+        match key_event.code {
+            KeyCode::Esc => self.expression = None,
+            KeyCode::Enter => match evaluate(prompt.input.value(), max) {
+                Ok(value) => {
+                    self.expression = None;
+                    self.set_counter(value);
+                }
+                Err(error) => prompt.error = Some(error),
+            },
+            _ => {
+                if prompt.input.handle_key_event(key_event) {
+                    prompt.error = None;
+                }
+            }
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    fn finish_tutorial(&mut self, message: &str) {
+        self.tutorial = None;
+        self.tutorial_done = true;
+        self.toasts.info(message, Instant::now());
+    }
 
-    #[test]
-    fn render() {
-        let app = App::default();
-        let mut buf = Buffer::empty(Rect::new(0, 0, 50, 4));
+    fn exit(&mut self) {
+        self.exit = true;
+    }
 
-        app.render(buf.area, &mut buf);
+    /// 和 [`App::exit`] 一样退出，但记为中止，见 [`App::exit_code`]。
+    fn abort(&mut self) {
+        self.exit = true;
+        self.aborted = true;
+    }
 
-        let mut expected = Buffer::with_lines(vec![
-            "┏━━━━━━━━━━━━━ Counter App Tutorial ━━━━━━━━━━━━━┓",
-            "┃                    Value: 0                    ┃",
-            "┃                                                ┃",
-            "┗━ Decrement <Left> Increment <Right> Quit <Q> ━━┛",
-        ]);
-        let title_style = Style::new().bold();
-        let counter_style = Style::new().yellow();
-        let key_style = Style::new().blue().bold();
-        expected.set_style(Rect::new(14, 0, 22, 1), title_style);
-        expected.set_style(Rect::new(28, 1, 1, 1), counter_style);
-        expected.set_style(Rect::new(13, 3, 6, 1), key_style);
-        expected.set_style(Rect::new(30, 3, 7, 1), key_style);
-        expected.set_style(Rect::new(43, 3, 4, 1), key_style);
+    /// 进程的退出码：`with_value` 时是计数值；`with_outcome` 时中止为 1，正常退出为 0；都没有时总是 0。
+    fn exit_code(&self, with_outcome: bool, with_value: bool) -> u8 {
+        if with_value {
+            self.counter
+        } else {
+            u8::from(with_outcome && self.aborted)
+        }
+    }
 
-        // 注意 ratatui 还有一个 assert_buffer_eq！可用于比较缓冲区并以更易读的方式显示差异的宏。
-        assert_eq!(buf, expected);
+    fn toggle_schedule(&mut self) {
+        self.screen = match self.screen {
+            Screen::Schedule => Screen::Counter,
+            _ => Screen::Schedule,
+        };
     }
 
-    #[test]
-    fn handle_key_event() {
-        let mut app = App::default();
-        app.handle_key_event(KeyCode::Right.into()).unwrap();
-        assert_eq!(app.counter, 1);
+    fn toggle_wide_demo(&mut self) {
+        self.screen = match self.screen {
+            Screen::WideDemo => Screen::Counter,
+            _ => Screen::WideDemo,
+        };
+    }
 
-        app.handle_key_event(KeyCode::Left.into()).unwrap();
-        assert_eq!(app.counter, 0);
+    /// 执行脚本中的命令或钩子，并应用脚本设置的计数值。
+    #[cfg(feature = "lua")]
+    fn run_script(
+        &mut self,
+        run: impl FnOnce(&scripting::Scripts, u8, u8) -> Result<Option<u8>>,
+    ) -> Result<()> {
+        let Some(scripts) = &self.scripts else {
+            return Ok(());
+        };
+        if let Some(value) = run(scripts, self.counter, self.max())? {
+            self.set_counter(value);
+        }
+        Ok(())
+    }
 
-        let mut app = App::default();
-        app.handle_key_event(KeyCode::Char('q').into()).unwrap();
-        assert_eq!(app.exit, true);
+    /// 计数器 → 第一个插件面板 → …… → 最后一个插件面板 → 计数器。没有插件时不切换。
+    fn next_pane(&mut self) {
+        let next = match self.screen {
+            Screen::Plugin(index) => index + 1,
+            _ => 0,
+        };
+        self.screen = if next < self.plugins.len() {
+            Screen::Plugin(next)
+        } else {
+            Screen::Counter
+        };
     }
 
-    #[test]
-    #[should_panic(expected = "attempt to subtract with overflow")]
-    fn handle_key_event_panic() {
-        let mut app = App::default();
-        let _ = app.handle_key_event(KeyCode::Left.into());
+    /// 打开当前界面的按键帮助。
+    fn open_help(&mut self) {
+        #[cfg(feature = "lua")]
+        let commands = self
+            .scripts
+            .as_ref()
+            .map(scripting::Scripts::command_names)
+            .unwrap_or_default();
+        #[cfg(not(feature = "lua"))]
+        let commands = Vec::new();
+        let keymap = |title: String, relevant: fn(Action) -> bool| KeymapHelp {
+            title,
+            keymap: &self.keymap,
+            commands: commands.clone(),
+            relevant,
+        };
+        let back = std::mem::take(&mut self.screen);
+        let view = match &back {
+            Screen::Profiles(picker) | Screen::Sessions(picker) | Screen::Backups(picker) => {
+                HelpView::new(picker)
+            }
+            Screen::History(view) => HelpView::new(view),
+            Screen::Themes(picker) => HelpView::new(picker),
+            Screen::ThemeEditor(editor) => HelpView::new(editor),
+            Screen::WideDemo => HelpView::new(&keymap("Wide character demo".into(), |action| {
+                matches!(action, Action::WideDemo | Action::Quit | Action::Help)
+            })),
+            Screen::Schedule => HelpView::new(&keymap("Schedule".into(), |action| {
+                matches!(action, Action::Schedule | Action::Quit | Action::Help)
+            })),
+            Screen::Plugin(index) => HelpView::new(&keymap(
+                self.plugins[*index].plugin.name().to_string(),
+                |action| matches!(action, Action::NextPane | Action::Quit | Action::Help),
+            )),
+            Screen::Counter | Screen::Help { .. } => {
+                HelpView::new(&keymap("Counter".into(), |_| true))
+            }
+        };
+        self.screen = Screen::Help {
+            view,
+            back: Box::new(back),
+        };
     }
 
-    #[test]
-    fn handle_key_event_overflow() {
-        let mut app = App::default();
-        assert!(app.handle_key_event(KeyCode::Right.into()).is_ok());
-        assert!(app.handle_key_event(KeyCode::Right.into()).is_ok());
+    /// 打开主题选择界面。读取配色方案失败时留在原来的界面。
+    fn open_theme_picker(&mut self) {
+        let now = Instant::now();
+        if self.no_color {
+            self.toasts
+                .warn("Colors are disabled by --plain or NO_COLOR", now);
+            return;
+        }
+        let schemes = match &self.themes {
+            Some(dir) => match base16::discover(dir) {
+                Ok(schemes) => schemes,
+                Err(error) => {
+                    self.toasts.error(format!("{error:#}"), now);
+                    return;
+                }
+            },
+            None => Vec::new(),
+        };
+        self.screen = Screen::Themes(ThemePicker::new(
+            schemes,
+            &self.theme_choice,
+            self.capabilities,
+        ));
+    }
+
+    /// 使用选择的主题，并记下来下次启动时使用。
+    fn apply_theme(&mut self, choice: Choice) {
+        self.theme = choice.theme(&self.capabilities);
+        self.saved_theme = Some(choice.id());
+        self.toasts.info(
+            format!("Switched to theme {}", choice.name()),
+            Instant::now(),
+        );
+        self.theme_choice = choice;
+    }
+
+    /// 保存主题编辑器中编辑好的配色方案并使用它。保存失败时留在编辑器中。
+    fn save_theme(&mut self, scheme: base16::Scheme) {
+        if let Some(dir) = &self.themes {
+            match base16::save(dir, &scheme) {
+                Ok(path) => self
+                    .toasts
+                    .info(format!("Saved {}", path.display()), Instant::now()),
+                Err(error) => {
+                    self.toasts.error(format!("{error:#}"), Instant::now());
+                    return;
+                }
+            }
+        }
+        self.apply_theme(Choice::Base16(scheme));
+        self.screen = Screen::Counter;
+    }
+
+    fn open_profile_picker(&mut self) -> Result<()> {
+        let names = match &self.profiles {
+            Some(profiles) => profiles.list().wrap_err("listing profiles failed")?,
+            None => Vec::new(),
+        };
+        self.screen = Screen::Profiles(Picker::new("Profiles", names, Some(&self.profile)));
+        Ok(())
+    }
+
+    fn open_session_picker(&mut self) -> Result<()> {
+        let names = match self.sessions() {
+            Some(sessions) => sessions.list().wrap_err("listing sessions failed")?,
+            None => Vec::new(),
+        };
+        self.screen = Screen::Sessions(Picker::new("Sessions", names, self.session.as_deref()));
+        Ok(())
+    }
+
+    fn open_backup_picker(&mut self) -> Result<()> {
+        let names = match self.backups() {
+            Some(backups) => backups.list().wrap_err("listing backups failed")?,
+            None => Vec::new(),
+        };
+        self.screen = Screen::Backups(Picker::new("Backups", names, None).list_only());
+        Ok(())
+    }
+
+    /// 用备份替换状态文件并加载它。备份读不出来时只提示，不退出。
+    fn restore_backup(&mut self, name: &str) {
+        let Some(backups) = self.backups() else {
+            return;
+        };
+        if self.read_only {
+            self.toasts.error(READ_ONLY, Instant::now());
+            return;
+        }
+        self.flush_writes();
+        let now = Instant::now();
+        let restored = backups.load(name).and_then(|state| {
+            backups.restore(name, Local::now().naive_local())?;
+            Ok(state)
+        });
+        match restored {
+            Ok(state) => {
+                self.apply_state(state);
+                self.toasts.info(format!("Restored backup {name}"), now);
+            }
+            Err(error) => self.toasts.error(format!("{error:#}"), now),
+        }
+    }
+
+    /// 当前档案的状态文件和它的备份。
+    fn backups(&self) -> Option<Backups> {
+        self.profiles.as_ref().map(|profiles| {
+            Backups::new(&profiles.state_path(&self.profile), self.state_backups)
+                .with_key(self.key.clone())
+        })
+    }
+
+    fn sessions(&self) -> Option<Sessions> {
+        self.profiles
+            .as_ref()
+            .map(|profiles| profiles.sessions(&self.profile).with_key(self.key.clone()))
+    }
+
+    /// 可以保存：没有另一个实例在写这个档案，加密的状态文件也已经解锁了（解锁之前的状态不是真正的
+    /// 状态）。
+    fn can_save(&self) -> bool {
+        !self.read_only
+            && !self
+                .unlock
+                .as_ref()
+                .is_some_and(vault::Prompt::is_unlocking)
+    }
+
+    /// 刚设置了口令：加密保存状态文件和所有的会话。
+    fn encrypt_saved_files(&mut self) -> Result<()> {
+        self.save_state()?;
+        if let Some(sessions) = self.sessions().filter(|_| self.can_save()) {
+            for name in sessions.list().wrap_err("listing sessions failed")? {
+                let session = sessions.load(&name)?;
+                sessions.save(&name, &session)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn session_snapshot(&self) -> Session {
+        Session {
+            state: self.state(),
+            settings: Settings {
+                reduced_motion: self.motion.reduced,
+                counter_max: self.max,
+            },
+        }
+    }
+
+    /// 把当前状态保存为名为 `name` 的会话，并把它作为当前会话。
+    fn save_session_as(&mut self, name: String) -> Result<()> {
+        if self.read_only {
+            self.toasts.error(READ_ONLY, Instant::now());
+            return Ok(());
+        }
+        self.flush_writes();
+        if let Some(sessions) = self.sessions() {
+            sessions.save(&name, &self.session_snapshot())?;
+        }
+        self.toasts
+            .info(format!("Saved session {name}"), Instant::now());
+        self.session = Some(name);
+        Ok(())
+    }
+
+    /// 保存当前会话（如果有），然后加载另一个会话的状态和设置。
+    fn switch_session(&mut self, name: String) -> Result<()> {
+        if self.session.as_ref() == Some(&name) {
+            return Ok(());
+        }
+        let Some(sessions) = self.sessions() else {
+            return Ok(());
+        };
+        self.flush_writes();
+        if let Some(current) = self.session.as_ref().filter(|_| self.can_save()) {
+            sessions.save(current, &self.session_snapshot())?;
+        }
+        let session = sessions.load(&name)?;
+        self.apply_state(session.state);
+        self.motion.reduced = session.settings.reduced_motion;
+        self.toasts.set_motion(self.motion);
+        self.max = session.settings.counter_max;
+        self.toasts
+            .info(format!("Loaded session {name}"), Instant::now());
+        self.session = Some(name);
+        Ok(())
+    }
+
+    /// 需要持久化的状态。
+    fn state(&self) -> State {
+        State {
+            counter: self.counter,
+            history: self.history.clone(),
+            tutorial_done: self.tutorial_done,
+            theme: self.saved_theme.clone(),
+        }
+    }
+
+    fn apply_state(&mut self, state: State) {
+        self.counter = state.counter;
+        self.history = state.history;
+        self.tutorial_done = state.tutorial_done;
+    }
+
+    fn save_state(&self) -> Result<()> {
+        self.flush_writes();
+        if !self.can_save() {
+            return Ok(());
+        }
+        match self.backups() {
+            Some(backups) => backups.save(&self.state(), Local::now().naive_local()),
+            None => Ok(()),
+        }
+    }
+
+    /// 保存当前档案的状态，然后加载另一个档案的状态。
+    fn switch_profile(&mut self, name: String) -> Result<()> {
+        if name == self.profile {
+            return Ok(());
+        }
+        let path = self
+            .profiles
+            .as_ref()
+            .map(|profiles| profiles.state_path(&name));
+        // 另一个档案的口令要在启动时输入。
+        if let Some(path) = &path {
+            if state::encrypted(path)?.is_some() {
+                self.toasts.error(
+                    format!(
+                        "Profile {name} is encrypted, start with --profile {name} to unlock it"
+                    ),
+                    Instant::now(),
+                );
+                return Ok(());
+            }
+        }
+        self.save_state()?;
+        self.key = None;
+        let mut held = None;
+        if let Some(path) = path {
+            let state = State::load(&path)?;
+            self.apply_state(state);
+            // 先放开原来档案的锁，再去拿新档案的锁。
+            self.lock = None;
+            match lock::acquire(&path)? {
+                Ok(lock) => self.lock = Some(lock),
+                Err(other) => held = Some(other),
+            }
+            self.read_only = held.is_some();
+            // 自动保存跟着换到新的档案，只读时不自动保存。
+            if let Some(recovery) = self.recovery.take() {
+                recovery.finish()?;
+                if !self.read_only {
+                    self.recovery = Some(Recovery::start(&path, Instant::now())?.0);
+                }
+            }
+        }
+        match held {
+            Some(held) => self.toasts.warn(held.message(&name), Instant::now()),
+            None => self
+                .toasts
+                .info(format!("Switched to profile {name}"), Instant::now()),
+        }
+        self.profile = name;
+        self.session = None;
+        Ok(())
+    }
+
+    /// 让计数器的值短暂闪烁。减少动态效果时不闪烁。
+    fn flash(&mut self) {
+        let mut flash = Animation::new(1.0, FLASH_DURATION, Easing::EaseIn);
+        flash.animate_to(0.0, self.motion, Instant::now());
+        self.flash = flash.is_running().then_some(flash);
+    }
+
+    /// 闪烁时数值的样式：开始时前景和背景互换，然后渐变回普通的颜色。颜色无法插值时（例如
+    /// 没有颜色的主题）前一半时间反色显示。
+    fn flash_style(&self, theme: &Theme, intensity: f64) -> Style {
+        let colors = theme
+            .value
+            .fg
+            .or(theme.base.fg)
+            .zip(theme.base.bg)
+            .filter(|&(fg, bg)| animation::to_rgb(fg).is_some() && animation::to_rgb(bg).is_some());
+        match colors {
+            Some((fg, bg)) => theme
+                .value
+                .fg(self.capabilities.adapt_color(fg.lerp(bg, intensity)))
+                .bg(self.capabilities.adapt_color(bg.lerp(fg, intensity))),
+            None if intensity >= 0.5 => theme.value.reversed(),
+            None => theme.value,
+        }
+    }
+
+    /// 本实例的一次变化，`self.counter` 已经是变化之后的值。共享计数器时同时记入副本；
+    /// 连接到守护进程时发送给守护进程，计数器改为守护进程回复的值。
+    fn record_change(&mut self, delta: i16) {
+        if let Some(replica) = &self.replica {
+            replica.apply(delta.into());
+        }
+        if let Some(daemon) = &mut self.daemon {
+            // 连接断开时订阅连接会报告错误并结束主循环，这里不需要处理。
+            if let Ok(counter) = daemon.add(delta) {
+                self.counter = counter;
+            }
+        }
+        self.note_change(delta);
+    }
+
+    /// 在历史中记录一次变化，可能来自本实例，也可能来自共享计数器的其他实例。
+    fn note_change(&mut self, delta: i16) {
+        self.history.push(Change {
+            at: Utc::now(),
+            delta,
+            value: self.counter,
+        });
+        self.flash();
+        let counter = self.counter;
+        if let Some(milestone) =
+            notifications::reached(&self.milestones, i16::from(counter) - delta, counter)
+        {
+            self.toasts
+                .info(format!("Counter reached {milestone}"), Instant::now());
+            notifications::send(
+                format!("Counter reached {milestone}"),
+                format!("The counter is now {counter}."),
+            );
+        }
+        self.watchers.retain(|tx| tx.send(Ok(counter)).is_ok());
+        #[cfg(feature = "wasm")]
+        self.notify_wasm_plugins(wasm_plugins::EVENT_COUNTER, self.counter.into());
+    }
+
+    #[cfg(feature = "wasm")]
+    fn notify_wasm_plugins(&mut self, kind: i32, value: i32) {
+        let (counter, max) = (self.counter, self.max());
+        self.wasm_plugins.notify(kind, value, counter, max);
+    }
+
+    fn max(&self) -> u8 {
+        self.max.unwrap_or(COUNTER_MAX)
+    }
+
+    /// 计数器增加当前的加速步长。
+    ///
+    /// 步长不会越过上限；已经在上限时仍然增加 1，从而像以前一样报告溢出。
+    fn increment_counter(&mut self) -> Result<()> {
+        let room = self.max().saturating_sub(self.counter);
+        let step = if room == 0 {
+            1
+        } else {
+            self.acceleration.step().min(room)
+        };
+        // `counter_max = 255` 时没有更大的 u8，同样算作溢出。
+        match self.counter.checked_add(step) {
+            Some(counter) if counter <= self.max() => self.counter = counter,
+            _ => bail!("counter overflow"),
+        }
+        self.record_change(i16::from(step));
+        Ok(())
+    }
+
+    /// 计数器减少当前的加速步长。步长不会越过 0；已经是 0 时仍然减 1。
+    fn decrement_counter(&mut self) -> Result<()> {
+        let step = if self.counter == 0 {
+            1
+        } else {
+            self.acceleration.step().min(self.counter)
+        };
+        self.counter -= step;
+        self.record_change(-i16::from(step));
+        Ok(())
+    }
+
+    /// 执行控制接口的命令。计数器已经到达边界时回复错误，而不是像按键那样溢出。
+    fn control(&mut self, command: control::Command) -> control::Reply {
+        use control::Command;
+
+        let max = self.max();
+        match command {
+            Command::Get | Command::Watch => {}
+            Command::Set(value) if value > max => {
+                return Err(format!("{value} is outside 0..={max}"))
+            }
+            Command::Set(value) => self.set_counter(value),
+            Command::Add(delta) => {
+                // 用 i32 计算，`add 32767` 这样的大增量只会超出范围，不会溢出。
+                let value = i32::from(self.counter) + i32::from(delta);
+                if !(0..=i32::from(max)).contains(&value) {
+                    return Err(format!("{value} is outside 0..={max}"));
+                }
+                self.set_counter(value as u8);
+            }
+            Command::Increment if self.counter >= max => {
+                return Err("counter is at its maximum".into())
+            }
+            Command::Decrement if self.counter == 0 => {
+                return Err("counter is at its minimum".into())
+            }
+            Command::Increment => self
+                .perform(Action::Increment)
+                .map_err(|err| format!("{err:#}"))?,
+            Command::Decrement => self
+                .perform(Action::Decrement)
+                .map_err(|err| format!("{err:#}"))?,
+            Command::Reset => self.set_counter(0),
+            Command::Quit => self.exit(),
+        }
+        Ok(self.counter)
+    }
+
+    /// 合并其他实例发来的状态，计数器显示合并后的值。
+    ///
+    /// 各实例同时修改时合并后的值可能越过边界（例如两个实例同时从 1 减少），显示时限制在 `0..=max` 内。
+    fn merge_gossip(&mut self, message: &gossip::Message) {
+        let Some(replica) = &self.replica else { return };
+        replica.merge(message);
+        let value = replica.value().clamp(0, self.max().into()) as u8;
+        self.sync_counter(value);
+    }
+
+    /// 显示其他实例或守护进程中的计数值，不再转发出去。
+    fn sync_counter(&mut self, value: u8) {
+        let delta = i16::from(value) - i16::from(self.counter);
+        if delta != 0 {
+            self.counter = value;
+            self.note_change(delta);
+        }
+    }
+
+    /// 直接把计数器设置为某个值，例如从历史时间线跳回之前的值。
+    fn set_counter(&mut self, value: u8) {
+        let delta = i16::from(value) - i16::from(self.counter);
+        if delta != 0 {
+            self.counter = value;
+            self.record_change(delta);
+        }
+    }
+}
+
+/// 计算表达式，结果必须在计数器的取值范围 `0..=max` 内。
+fn evaluate(expression: &str, max: u8) -> std::result::Result<u8, String> {
+    let value = expr::eval(expression).map_err(|err| err.to_string())?;
+    u8::try_from(value)
+        .ok()
+        .filter(|&value| value <= max)
+        .ok_or_else(|| format!("{value} is outside 0..={max}"))
+}
+
+/// 首先，添加一个新的 `impl Widget for &App` 块。
+/// 我们在对 App 类型的引用上实现这一点，因为渲染函数不会改变任何状态，并且我们希望能够在调用绘图后使用该应用程序。
+///
+/// 渲染函数将创建一个带有标题、底部说明文本和一些边框的块。
+/// 使用块内的应用程序状态（ `App` 计数器字段的值）渲染 `Paragraph` 小部件。
+/// 块和段落将占据小部件的整个大小。
+impl Widget for &App {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        if let Screen::WideDemo = self.screen {
+            self.render_wide_demo(area, buf);
+            return;
+        }
+
+        let theme = &self.theme_for(theme::Widget::Counter);
+        // 左右边框和标题两侧的空格各占一个单元格。
+        let title_text = text::truncate(
+            self.title.as_deref().unwrap_or(DEFAULT_TITLE),
+            usize::from(area.width.saturating_sub(4)),
+        );
+        let title = Title::from(format!(" {title_text} ").set_style(theme.title));
+        // 按键提示按当前的绑定生成，重新绑定过的按键也能显示正确。
+        let bound: Vec<_> = [Action::Decrement, Action::Increment, Action::Quit]
+            .into_iter()
+            .filter_map(|action| Some((action, self.keymap.keys_for(action)?)))
+            .collect();
+        let mut hints = Vec::new();
+        for (index, (action, keys)) in bound.iter().enumerate() {
+            let end = if index + 1 == bound.len() { " " } else { "" };
+            hints.push(format!(" {} ", action.description()).into());
+            hints.push(format!("<{}>{end}", keymap::hint_name(keys)).set_style(theme.key));
+        }
+        let instructions = Title::from(Line::from(hints));
+        let mut block = Block::default()
+            .title(title.alignment(Alignment::Center))
+            .title(
+                instructions
+                    .alignment(Alignment::Center)
+                    .position(Position::Bottom),
+            )
+            .borders(Borders::ALL)
+            .border_set(theme.border_set)
+            .border_style(theme.border);
+        if let Some(clock) = &self.clock {
+            let text = format!(" {} ", clock.text());
+            block =
+                block.title(Title::from(text.set_style(theme.title)).alignment(Alignment::Left));
+        }
+        // 状态栏位于上边框的右侧，只在有内容时显示。
+        if let Some(status) = self.status_line(theme) {
+            block = block.title(Title::from(status).alignment(Alignment::Right));
+        }
+
+        let value_style = match &self.flash {
+            Some(flash) => self.flash_style(theme, flash.value()),
+            None => theme.value,
+        };
+        let mut value_line = Line::from(vec![
+            "Value: ".into(),
+            self.counter.to_string().set_style(value_style),
+        ]);
+        // 到达上限时同时使用文字提示，不能只依赖颜色。
+        if self.counter >= self.max() {
+            value_line.push_span(" (max)".set_style(theme.warning));
+        }
+
+        let mut lines = vec![value_line];
+        if let Some(prompt) = &self.expression {
+            lines.push(prompt.input.line("= ", theme.key));
+            if let Some(error) = &prompt.error {
+                lines.push(Line::from(
+                    format!("error: {error}").set_style(theme.warning),
+                ));
+            }
+        }
+
+        self.inspector.record("Paragraph", block.inner(area));
+        Paragraph::new(Text::from(lines))
+            .style(theme.base)
+            .centered()
+            .block(block)
+            .render(area, buf);
+    }
+}
+
+impl App {
+    /// 状态栏的内容：时间旅行的位置、当前会话、未完成的和弦和加速步长。
+    fn status_line(&self, theme: &Theme) -> Option<Line<'static>> {
+        let mut spans = Vec::new();
+        if self.read_only {
+            spans.push(" Read-only ".set_style(theme.warning));
+        }
+        if let (Some((position, len)), Some(step)) =
+            (self.time_travel.position(), self.time_travel.current())
+        {
+            spans.push(format!(" Travel {position}/{len}: ").into());
+            spans.push(format!("{} ", step.message).set_style(theme.warning));
+        }
+        if let Some(session) = &self.session {
+            spans.push(" Session: ".into());
+            spans.push(format!("{session} ").set_style(theme.value));
+        }
+        if !self.pending_keys.is_empty() {
+            let keys: Vec<String> = self
+                .pending_keys
+                .iter()
+                .copied()
+                .map(keymap::key_name)
+                .collect();
+            spans.push(" Pending: ".into());
+            spans.push(format!("{} ", keys.join(" ")).set_style(theme.key));
+        }
+        let step = self.acceleration.step();
+        if step > 1 {
+            spans.push(" Step: ".into());
+            spans.push(format!("{}{step} ", theme.symbols.times).set_style(theme.key));
+        }
+        #[cfg(feature = "wasm")]
+        for segment in self.wasm_plugins.segments() {
+            spans.push(format!(" {segment} ").set_style(theme.value));
+        }
+        (!spans.is_empty()).then(|| Line::from(spans))
+    }
+
+    /// 逐行居中显示 `WIDE_SAMPLES`，放不下的样例会按显示宽度截断。
+    fn render_wide_demo(&self, area: Rect, buf: &mut Buffer) {
+        let theme = &self.theme_for(theme::Widget::WideDemo);
+        let mut block = Block::default()
+            .title(Title::from(" 宽字符演示 ".set_style(theme.title)).alignment(Alignment::Center));
+        // 和计数器的按键提示一样按当前的绑定生成。
+        if let Some(keys) = self.keymap.keys_for(Action::WideDemo) {
+            block = block.title(
+                Title::from(Line::from(vec![
+                    " 返回 ".into(),
+                    format!("<{}> ", keymap::hint_name(keys)).set_style(theme.key),
+                ]))
+                .alignment(Alignment::Center)
+                .position(Position::Bottom),
+            );
+        }
+        let block = block
+            .borders(Borders::ALL)
+            .border_set(theme.border_set)
+            .border_style(theme.border)
+            .style(theme.base);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let inner_width = usize::from(inner.width);
+        for (sample, y) in WIDE_SAMPLES.iter().zip(inner.top()..inner.bottom()) {
+            let sample = text::truncate(sample, inner_width);
+            let x = inner.x + text::center_offset(&sample, inner_width) as u16;
+            buf.set_string(x, y, &sample, theme.value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::{console, recording::RecordedEvent};
+
+    use super::*;
+    use crate::theme::ThemeName;
+
+    /// 读取缓冲区中的一行，跳过双宽字符后面的占位单元格。
+    fn row(buf: &Buffer, y: u16) -> String {
+        let mut line = String::new();
+        let mut x = buf.area.left();
+        while x < buf.area.right() {
+            let symbol = buf.get(x, y).symbol();
+            line.push_str(symbol);
+            x += text::width(symbol).max(1) as u16;
+        }
+        line
+    }
+
+    #[test]
+    fn render() {
+        let app = App::default();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 50, 4));
+
+        app.render(buf.area, &mut buf);
+
+        let mut expected = Buffer::with_lines(vec![
+            "┏━━━━━━━━━━━━━ Counter App Tutorial ━━━━━━━━━━━━━┓",
+            "┃                    Value: 0                    ┃",
+            "┃                                                ┃",
+            "┗━ Decrement <Left> Increment <Right> Quit <Q> ━━┛",
+        ]);
+        let title_style = Style::new().bold();
+        let counter_style = Style::new().yellow();
+        let key_style = Style::new().blue().bold();
+        expected.set_style(Rect::new(14, 0, 22, 1), title_style);
+        expected.set_style(Rect::new(28, 1, 1, 1), counter_style);
+        expected.set_style(Rect::new(13, 3, 6, 1), key_style);
+        expected.set_style(Rect::new(30, 3, 7, 1), key_style);
+        expected.set_style(Rect::new(43, 3, 4, 1), key_style);
+
+        // 注意 ratatui 还有一个 assert_buffer_eq！可用于比较缓冲区并以更易读的方式显示差异的宏。
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn render_plain() {
+        let app = App {
+            counter: COUNTER_MAX,
+            ..App::new(Theme::plain())
+        };
+        let mut buf = Buffer::empty(Rect::new(0, 0, 50, 4));
+
+        app.render(buf.area, &mut buf);
+
+        let expected = Buffer::with_lines(vec![
+            "┏━━━━━━━━━━━━━ Counter App Tutorial ━━━━━━━━━━━━━┓",
+            "┃                 Value: 2 (max)                 ┃",
+            "┃                                                ┃",
+            "┗━ Decrement <Left> Increment <Right> Quit <Q> ━━┛",
+        ]);
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn render_clock() {
+        use chrono::TimeZone;
+
+        let mut clock = Clock::new(&config::ClockConfig {
+            format: "%H:%M".into(),
+            timezone: "Asia/Tokyo".into(),
+        })
+        .unwrap();
+        clock.tick(
+            Instant::now(),
+            Utc.with_ymd_and_hms(2024, 5, 1, 3, 4, 0).unwrap(),
+        );
+        let app = App {
+            clock: Some(clock),
+            ..App::new(Theme::plain())
+        };
+        let mut buf = Buffer::empty(Rect::new(0, 0, 50, 4));
+
+        app.render(buf.area, &mut buf);
+
+        let top: String = (0..50).map(|x| buf.get(x, 0).symbol()).collect();
+        assert_eq!(top, "┏ 12:04 ━━━━━━ Counter App Tutorial ━━━━━━━━━━━━━┓");
+    }
+
+    #[test]
+    fn render_at_max() {
+        let app = App {
+            counter: COUNTER_MAX,
+            ..App::new(Theme::new(ThemeName::HighContrast))
+        };
+        let mut buf = Buffer::empty(Rect::new(0, 0, 50, 4));
+
+        app.render(buf.area, &mut buf);
+
+        assert_eq!(
+            row(&buf, 1),
+            "┃                 Value: 2 (max)                 ┃"
+        );
+        assert_eq!(buf.get(27, 1).bg, Color::Yellow);
+    }
+
+    #[test]
+    fn render_wide_title() {
+        let app = App {
+            title: Some("计数器应用程序教程".into()),
+            ..App::default()
+        };
+        let mut buf = Buffer::empty(Rect::new(0, 0, 16, 3));
+
+        app.render(buf.area, &mut buf);
+
+        assert_eq!(row(&buf, 0), "┏ 计数器应用… ━┓");
+    }
+
+    #[test]
+    fn render_wide_demo() {
+        let mut app = App {
+            screen: Screen::WideDemo,
+            ..App::default()
+        };
+        let mut buf = Buffer::empty(Rect::new(0, 0, 21, 8));
+
+        app.render(buf.area, &mut buf);
+
+        assert_eq!(row(&buf, 1), "┃计数器应用程序教程 ┃");
+        assert_eq!(row(&buf, 2), "┃カウンターアプリの…┃");
+        assert_eq!(row(&buf, 7), "┗━━━━ 返回 <W> ━━━━━┛");
+
+        // 重新绑定之后提示显示新的按键。
+        app.keymap
+            .rebind(&[("wide_demo".to_string(), "F2".to_string())].into())
+            .unwrap();
+        app.render(buf.area, &mut buf);
+        assert_eq!(row(&buf, 7), "┗━━━━ 返回 <F2> ━━━━┛");
+    }
+
+    #[test]
+    fn flash_on_change() {
+        let mut app = App::default();
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+        assert!(app.flash.is_some());
+        // 闪烁逐渐减弱，结束后主循环不再按帧重绘。
+        let start = Instant::now();
+        app.animate(start + FLASH_DURATION / 2);
+        let intensity = app.flash.as_ref().unwrap().value();
+        assert!(0.0 < intensity && intensity < 1.0);
+        let theme = Theme {
+            base: Style::new()
+                .fg(Color::Rgb(200, 200, 200))
+                .bg(Color::Rgb(0, 0, 0)),
+            value: Style::new().fg(Color::Rgb(200, 0, 0)),
+            ..Theme::plain()
+        };
+        assert_eq!(
+            app.flash_style(&theme, 1.0),
+            Style::new()
+                .fg(Color::Rgb(0, 0, 0))
+                .bg(Color::Rgb(200, 0, 0))
+        );
+        assert_eq!(
+            app.flash_style(&Theme::plain(), 0.8),
+            Theme::plain().value.reversed()
+        );
+        app.animate(start + FLASH_DURATION);
+        assert!(app.flash.is_none());
+        assert_eq!(app.next_deadline(), None);
+
+        let mut app = App {
+            motion: Motion::reduced(),
+            ..App::default()
+        };
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+        assert!(app.flash.is_none());
+    }
+
+    #[test]
+    fn transition_between_screens() {
+        let mut app = App::new(Theme::plain());
+        let mut terminal = Terminal::new(backend::TestBackend::new(20, 3)).unwrap();
+        terminal.draw(|frame| app.render_frame(frame)).unwrap();
+        assert!(app.transition.is_none());
+        app.handle_key_event(KeyCode::Char('w').into()).unwrap();
+        terminal.draw(|frame| app.render_frame(frame)).unwrap();
+        assert!(app.transition.is_some());
+        assert!(app.next_deadline().is_some());
+        // 过渡结束后只画新界面。
+        app.animate(Instant::now() + Duration::from_secs(1));
+        assert!(app.transition.is_none());
+
+        let mut app = App {
+            motion: Motion::reduced(),
+            ..App::new(Theme::plain())
+        };
+        terminal.draw(|frame| app.render_frame(frame)).unwrap();
+        app.handle_key_event(KeyCode::Char('w').into()).unwrap();
+        terminal.draw(|frame| app.render_frame(frame)).unwrap();
+        assert!(app.transition.is_none());
+    }
+
+    #[test]
+    fn inspect_layout() {
+        let mut app = App::new(Theme::plain());
+        let mut terminal = Terminal::new(backend::TestBackend::new(40, 4)).unwrap();
+        app.handle_key_event(KeyCode::F(11).into()).unwrap();
+        terminal.draw(|frame| app.render_frame(frame)).unwrap();
+        let regions: Vec<_> = app
+            .inspector
+            .regions()
+            .into_iter()
+            .map(|region| (region.label, region.area))
+            .collect();
+        assert_eq!(
+            regions,
+            [
+                ("Counter".to_string(), Rect::new(0, 0, 40, 4)),
+                ("Paragraph".to_string(), Rect::new(1, 1, 38, 2)),
+            ]
+        );
+
+        let mouse = |column| RecordedEvent {
+            at_ms: 0,
+            event: TerminalEvent::Mouse(MouseEvent {
+                kind: MouseEventKind::Moved,
+                column,
+                row: 3,
+                modifiers: KeyModifiers::NONE,
+            })
+            .into(),
+        };
+        app.handle_events(&mut Player::new([mouse(0)])).unwrap();
+        terminal.draw(|frame| app.render_frame(frame)).unwrap();
+        assert_eq!(
+            row(terminal.backend().buffer(), 0),
+            "Counter 40x4+0+0r App Tutorial ━━━━━━━━┓"
+        );
+        assert!(row(terminal.backend().buffer(), 3).ends_with(" 0,3 Counter "));
+
+        // 关闭后鼠标事件不再由检查器处理，界面上没有标记。
+        app.handle_key_event(KeyCode::F(11).into()).unwrap();
+        app.handle_events(&mut Player::new([mouse(1)])).unwrap();
+        terminal.draw(|frame| app.render_frame(frame)).unwrap();
+        assert!(app.inspector.regions().is_empty());
+        assert!(row(terminal.backend().buffer(), 0).contains("Counter App Tutorial"));
+    }
+
+    #[test]
+    fn debug_console() {
+        let mut app = App::new(Theme::plain());
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+        assert!(app
+            .console
+            .entries()
+            .any(|entry| entry.kind == console::Kind::Message && entry.text == "Increment"));
+
+        // 打开时按键都交给控制台。
+        app.handle_key_event(console::TOGGLE.into()).unwrap();
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+        assert_eq!(app.counter, 1);
+        for c in "error boom".chars() {
+            app.handle_key_event(KeyCode::Char(c).into()).unwrap();
+        }
+        let error = app.handle_key_event(KeyCode::Enter.into()).unwrap_err();
+        assert_eq!(error.to_string(), "boom");
+    }
+
+    #[test]
+    fn time_travel() {
+        let mut app = App::new(Theme::plain());
+        app.handle_key_event(KeyCode::F(10).into()).unwrap();
+        assert!(!app.time_travel.is_traveling());
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+        app.handle_key_event(KeyCode::F(10).into()).unwrap();
+        assert!(app.time_travel.is_traveling());
+
+        // 其他按键不改变状态，绘制的是记录中的状态。
+        let mut terminal = Terminal::new(backend::TestBackend::new(80, 4)).unwrap();
+        for code in [KeyCode::Left, KeyCode::Char('q')] {
+            app.handle_key_event(code.into()).unwrap();
+        }
+        assert!(!app.exit);
+        app.toasts.clear();
+        terminal.draw(|frame| app.render_frame(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        assert!(row(buffer, 0).contains("Travel 2/3: Increment"));
+        assert!(row(buffer, 1).contains("Value: 1"));
+        assert_eq!(app.counter, 2);
+
+        app.handle_key_event(KeyCode::Home.into()).unwrap();
+        assert_eq!(
+            app.time_travel.current().map(|step| step.message.as_str()),
+            Some("Start")
+        );
+        app.handle_key_event(KeyCode::Esc.into()).unwrap();
+        assert!(!app.time_travel.is_traveling());
+        app.handle_key_event(KeyCode::Left.into()).unwrap();
+        assert_eq!(app.counter, 1);
+    }
+
+    #[test]
+    fn restore_after_crash() {
+        let prompt = recovery::Prompt {
+            reason: Some("attempt to add with overflow".into()),
+            state: State {
+                counter: 2,
+                ..State::default()
+            },
+        };
+        let mut app = App {
+            restore_prompt: Some(prompt.clone()),
+            ..App::new(Theme::plain())
+        };
+        let mut terminal = Terminal::new(backend::TestBackend::new(50, 7)).unwrap();
+        terminal.draw(|frame| app.render_frame(frame)).unwrap();
+        let rows: Vec<String> = (1..6)
+            .map(|y| row(terminal.backend().buffer(), y))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                "┏━━━━━━━━━━━━━━━━━━━ Recover ━━━━━━━━━━━━━━━━━━━━┓",
+                "┃       The last run did not exit cleanly.       ┃",
+                "┃          attempt to add with overflow          ┃",
+                "┃       Restore the autosaved counter (2)?       ┃",
+                "┗━━━━━━━━━━━ Restore <Y> Discard <N> ━━━━━━━━━━━━┛",
+            ]
+        );
+
+        // 回答之前其他按键不起作用。
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+        assert_eq!(app.counter, 0);
+        app.handle_key_event(KeyCode::Char('y').into()).unwrap();
+        assert_eq!(app.counter, 2);
+        assert!(app.restore_prompt.is_none());
+
+        let mut app = App {
+            restore_prompt: Some(prompt),
+            ..App::new(Theme::plain())
+        };
+        app.handle_key_event(KeyCode::Char('n').into()).unwrap();
+        assert_eq!(app.counter, 0);
+        assert!(app.restore_prompt.is_none());
+    }
+
+    #[test]
+    fn autosave_in_background() {
+        let data =
+            std::env::temp_dir().join(format!("counter-demo-autosave-{}", std::process::id()));
+        let profiles = Profiles::new(&Paths::under(&data));
+        let now = Instant::now();
+        let mut app = App {
+            profiles: Some(profiles.clone()),
+            writer: Some(Writer::spawn().unwrap()),
+            autosave: Autosave::new(Duration::from_secs(10), now),
+            ..App::new(Theme::plain())
+        };
+        app.save_session_as("work".into()).unwrap();
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+        app.expire_timers(now + Duration::from_secs(5));
+        app.flush_writes();
+        let state_path = profiles.state_path(&app.profile);
+        assert!(!state_path.exists());
+
+        app.expire_timers(now + Duration::from_secs(10));
+        assert_eq!(
+            app.autosave
+                .as_ref()
+                .map(|autosave| autosave.deadline() - now),
+            Some(Duration::from_secs(20))
+        );
+        app.flush_writes();
+        assert_eq!(State::load(&state_path).unwrap().counter, 1);
+        let session = profiles.sessions(&app.profile).load("work").unwrap();
+        assert_eq!(session.state.counter, 1);
+
+        std::fs::remove_dir_all(data).unwrap();
+    }
+
+    #[test]
+    fn restore_backup() {
+        let data =
+            std::env::temp_dir().join(format!("counter-demo-backups-{}", std::process::id()));
+        let mut app = App {
+            profiles: Some(Profiles::new(&Paths::under(&data))),
+            counter: 1,
+            ..App::new(Theme::plain())
+        };
+        app.save_state().unwrap();
+        app.counter = 2;
+        app.save_state().unwrap();
+
+        app.handle_key_event(KeyCode::Char('b').into()).unwrap();
+        assert!(matches!(app.screen, Screen::Backups(_)));
+        app.handle_key_event(KeyCode::Enter.into()).unwrap();
+        assert!(matches!(app.screen, Screen::Counter));
+        assert_eq!(app.counter, 1);
+        let path = app.profiles.as_ref().unwrap().state_path(&app.profile);
+        assert_eq!(State::load(&path).unwrap().counter, 1);
+
+        std::fs::remove_dir_all(data).unwrap();
+    }
+
+    #[test]
+    fn restore_from_backup_when_locked() {
+        let data =
+            std::env::temp_dir().join(format!("counter-demo-restore-{}", std::process::id()));
+        let profiles = Profiles::new(&Paths::under(&data));
+        let mut app = App {
+            profiles: Some(profiles.clone()),
+            counter: 1,
+            ..App::new(Theme::plain())
+        };
+        app.save_state().unwrap();
+        app.counter = 2;
+        app.save_state().unwrap();
+        let path = profiles.state_path(&app.profile);
+        let name = Backups::new(&path, backup::DEFAULT_KEEP).list().unwrap()[0].clone();
+
+        // 另一个实例正在使用档案时不恢复，状态文件保持不变。
+        let other = lock::acquire(&path).unwrap().unwrap();
+        let err =
+            restore_from_backup(&profiles, &app.profile, &name, backup::DEFAULT_KEEP).unwrap_err();
+        assert!(err.to_string().contains("not restoring backup"));
+        assert_eq!(State::load(&path).unwrap().counter, 2);
+
+        // 恢复之后锁还拿在手里。
+        drop(other);
+        let _lock =
+            restore_from_backup(&profiles, &app.profile, &name, backup::DEFAULT_KEEP).unwrap();
+        assert_eq!(State::load(&path).unwrap().counter, 1);
+        assert!(lock::acquire(&path).unwrap().is_err());
+
+        std::fs::remove_dir_all(data).unwrap();
+    }
+
+    #[cfg(feature = "encrypt")]
+    #[test]
+    fn encrypt_and_unlock() {
+        let data =
+            std::env::temp_dir().join(format!("counter-demo-encrypt-{}", std::process::id()));
+        let profiles = Profiles::new(&Paths::under(&data));
+        let type_passphrase = |app: &mut App| {
+            for c in "secret\n".chars() {
+                let code = if c == '\n' {
+                    KeyCode::Enter
+                } else {
+                    KeyCode::Char(c)
+                };
+                app.handle_key_event(code.into()).unwrap();
+            }
+        };
+        let mut app = App {
+            profiles: Some(profiles.clone()),
+            unlock: Some(vault::Prompt::create()),
+            counter: 2,
+            ..App::new(Theme::plain())
+        };
+        app.save_session_as("work".into()).unwrap();
+        type_passphrase(&mut app);
+        type_passphrase(&mut app);
+        assert!(app.unlock.is_none() && app.key.is_some());
+        let path = profiles.state_path(&app.profile);
+        let envelope = state::encrypted(&path).unwrap().unwrap();
+        assert!(State::load(&path).is_err());
+        assert!(profiles.sessions(&app.profile).load("work").is_err());
+
+        let mut app = App {
+            profiles: Some(profiles.clone()),
+            unlock: Some(vault::Prompt::unlock(envelope)),
+            ..App::new(Theme::plain())
+        };
+        // 解锁之前不会用默认的状态覆盖加密的文件。
+        app.save_state().unwrap();
+        assert!(state::encrypted(&path).unwrap().is_some());
+        type_passphrase(&mut app);
+        assert_eq!(app.counter, 2);
+        app.switch_session("work".into()).unwrap();
+        assert_eq!(app.counter, 2);
+
+        std::fs::remove_dir_all(data).unwrap();
+    }
+
+    #[test]
+    fn read_only_when_locked() {
+        let data = std::env::temp_dir().join(format!("counter-demo-locked-{}", std::process::id()));
+        let profiles = Profiles::new(&Paths::under(&data));
+        // 另一个实例拿着 work 档案的锁。
+        let _other = lock::acquire(&profiles.state_path("work"))
+            .unwrap()
+            .unwrap();
+        let mut app = App {
+            profiles: Some(profiles.clone()),
+            ..App::new(Theme::plain())
+        };
+        app.switch_profile("work".into()).unwrap();
+        assert!(app.read_only);
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+        app.save_state().unwrap();
+        app.save_session_as("mine".into()).unwrap();
+        assert!(!profiles.state_path("work").exists());
+        assert!(profiles.sessions("work").list().unwrap().is_empty());
+        assert_eq!(app.session, None);
+        let status = app.status_line(&Theme::plain()).unwrap();
+        assert_eq!(status.spans[0].content, " Read-only ");
+
+        app.switch_profile(profile::DEFAULT_PROFILE.into()).unwrap();
+        assert!(!app.read_only);
+
+        std::fs::remove_dir_all(data).unwrap();
+    }
+
+    #[test]
+    fn switch_profile() {
+        let data = std::env::temp_dir().join(format!("counter-demo-switch-{}", std::process::id()));
+        let mut app = App {
+            profiles: Some(Profiles::new(&Paths::under(&data))),
+            ..App::new(Theme::default())
+        };
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+
+        app.switch_profile("work".into()).unwrap();
+        assert_eq!(app.counter, 0);
+        app.switch_profile(profile::DEFAULT_PROFILE.into()).unwrap();
+        assert_eq!(app.counter, 1);
+
+        std::fs::remove_dir_all(data).unwrap();
+    }
+
+    #[test]
+    fn record_history() {
+        let mut app = App::default();
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+        app.handle_key_event(KeyCode::Left.into()).unwrap();
+        let changes: Vec<_> = app.history.iter().map(|c| (c.delta, c.value)).collect();
+        assert_eq!(changes, [(1, 1), (1, 2), (-1, 1)]);
+
+        // 在时间线中选中第二新的记录（值为 2）并跳回去。
+        for code in [KeyCode::Char('h'), KeyCode::Char('j'), KeyCode::Enter] {
+            app.handle_key_event(code.into()).unwrap();
+        }
+        assert_eq!(app.counter, 2);
+        assert_eq!(app.history.last().map(|c| c.delta), Some(1));
+        assert!(matches!(app.screen, Screen::Counter));
+    }
+
+    #[test]
+    fn help_for_the_current_screen() {
+        let mut app = App::default();
+        app.handle_key_event(KeyCode::Char('h').into()).unwrap();
+        app.handle_key_event(KeyCode::Char('?').into()).unwrap();
+        assert!(
+            matches!(&app.screen, Screen::Help { back, .. } if matches!(**back, Screen::History(_)))
+        );
+        // 关闭帮助后回到打开它的界面。
+        app.handle_key_event(KeyCode::Esc.into()).unwrap();
+        assert!(matches!(app.screen, Screen::History(_)));
+
+        app.screen = Screen::WideDemo;
+        app.open_help();
+        app.handle_key_event(KeyCode::Char('?').into()).unwrap();
+        assert!(matches!(app.screen, Screen::WideDemo));
+    }
+
+    #[test]
+    fn tutorial() {
+        let mut app = App {
+            tutorial: Some(Tutorial::default()),
+            ..App::default()
+        };
+        for code in [KeyCode::Right, KeyCode::Right, KeyCode::Left] {
+            app.handle_key_event(code.into()).unwrap();
+        }
+        // 帮助和历史界面上不显示教程，回到计数器后继续。
+        app.handle_key_event(KeyCode::Char('?').into()).unwrap();
+        app.handle_key_event(KeyCode::Esc.into()).unwrap();
+        assert!(app.tutorial.is_some());
+        app.handle_key_event(KeyCode::Char('h').into()).unwrap();
+        assert!(app.tutorial.is_none());
+        assert!(app.state().tutorial_done);
+
+        let mut app = App {
+            tutorial: Some(Tutorial::default()),
+            ..App::default()
+        };
+        app.handle_key_event(KeyCode::Esc.into()).unwrap();
+        assert!(app.tutorial.is_none() && app.tutorial_done);
+    }
+
+    #[test]
+    fn choose_theme() {
+        let mut app = App::default();
+        for code in [KeyCode::Char('t'), KeyCode::Down, KeyCode::Enter] {
+            app.handle_key_event(code.into()).unwrap();
+        }
+        assert!(matches!(app.screen, Screen::Counter));
+        assert_eq!(app.theme, Theme::new(ThemeName::HighContrast));
+        assert_eq!(app.state().theme.as_deref(), Some("high-contrast"));
+
+        // 编辑一份副本并保存为新的主题。
+        for code in [
+            KeyCode::Char('t'),
+            KeyCode::Char('e'),
+            KeyCode::Down,
+            KeyCode::Char('s'),
+        ] {
+            app.handle_key_event(code.into()).unwrap();
+        }
+        for c in "mine".chars() {
+            app.handle_key_event(KeyCode::Char(c).into()).unwrap();
+        }
+        app.handle_key_event(KeyCode::Enter.into()).unwrap();
+        assert!(matches!(app.screen, Screen::Counter));
+        assert_eq!(app.state().theme.as_deref(), Some("mine"));
+        assert!(matches!(&app.theme_choice, Choice::Base16(scheme) if scheme.name == "mine"));
+
+        let mut app = App {
+            no_color: true,
+            ..App::default()
+        };
+        app.handle_key_event(KeyCode::Char('t').into()).unwrap();
+        assert!(matches!(app.screen, Screen::Counter));
+        assert!(!app.toasts.is_empty());
+    }
+
+    #[test]
+    fn handle_chords() {
+        let mut app = App::default();
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+
+        app.handle_key_event(KeyCode::Char('g').into()).unwrap();
+        assert_eq!(app.pending_keys, [KeyCode::Char('g')]);
+        app.handle_key_event(KeyCode::Char('g').into()).unwrap();
+        assert_eq!(app.counter, 0);
+        assert!(app.pending_keys.is_empty());
+
+        app.handle_key_event(KeyCode::Char('z').into()).unwrap();
+        app.handle_key_event(KeyCode::Char('z').into()).unwrap();
+        assert_eq!(app.counter, COUNTER_MAX / 2);
+
+        // 未完成的和弦被放弃，后面的按键照常生效。
+        app.handle_key_event(KeyCode::Char('g').into()).unwrap();
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+        assert_eq!(app.counter, COUNTER_MAX / 2 + 1);
+        assert!(app.pending_keys.is_empty());
+    }
+
+    #[test]
+    fn chord_timeout() {
+        let mut app = App::default();
+        app.handle_key_event(KeyCode::Char('g').into()).unwrap();
+        app.expire_timers(Instant::now() + app.keymap.timeout);
+        assert!(app.pending_keys.is_empty());
+    }
+
+    #[test]
+    fn render_pending_keys() {
+        let mut app = App::default();
+        app.handle_key_event(KeyCode::Char('g').into()).unwrap();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 50, 4));
+
+        app.render(buf.area, &mut buf);
+
+        assert_eq!(
+            row(&buf, 0),
+            "┏━━━━━━━━━━━━━ Counter App Tutorial ━ Pending: g ┓"
+        );
+    }
+
+    #[test]
+    fn hold_to_accelerate() {
+        let mut app = App {
+            max: Some(100),
+            ..App::default()
+        };
+        let repeat =
+            KeyEvent::new_with_kind(KeyCode::Right, KeyModifiers::NONE, KeyEventKind::Repeat);
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+        for _ in 0..5 {
+            app.handle_key_event(repeat).unwrap();
+        }
+        assert_eq!(app.counter, 7);
+        assert_eq!(app.acceleration.step(), 2);
+
+        // 加速不会越过上限。
+        let mut app = App { counter: 1, ..app };
+        app.handle_key_event(repeat).unwrap();
+        assert_eq!(app.counter, 3);
+
+        let mut replay = Player::new([RecordedEvent {
+            at_ms: 0,
+            event: TerminalEvent::Key(KeyEvent::new_with_kind(
+                KeyCode::Right,
+                KeyModifiers::NONE,
+                KeyEventKind::Release,
+            ))
+            .into(),
+        }]);
+        app.handle_events(&mut replay).unwrap();
+        assert_eq!(app.acceleration.step(), 1);
+    }
+
+    #[test]
+    fn ignore_repeat_of_other_keys() {
+        let mut app = App::default();
+        app.handle_key_event(KeyEvent::new_with_kind(
+            KeyCode::Char('q'),
+            KeyModifiers::NONE,
+            KeyEventKind::Repeat,
+        ))
+        .unwrap();
+        assert!(!app.exit);
+    }
+
+    #[test]
+    fn evaluate_expression() {
+        let mut app = App {
+            max: Some(100),
+            ..App::default()
+        };
+        for c in "=3*7+1".chars() {
+            app.handle_key_event(KeyCode::Char(c).into()).unwrap();
+        }
+        app.handle_key_event(KeyCode::Enter.into()).unwrap();
+        assert_eq!(app.counter, 22);
+        assert!(app.expression.is_none());
+
+        for c in "=1+".chars() {
+            app.handle_key_event(KeyCode::Char(c).into()).unwrap();
+        }
+        app.handle_key_event(KeyCode::Enter.into()).unwrap();
+        assert_eq!(app.counter, 22);
+        let error = app.expression.as_ref().and_then(|p| p.error.as_deref());
+        assert_eq!(error, Some("unexpected end of expression"));
+
+        app.handle_key_event(KeyCode::Esc.into()).unwrap();
+        assert!(app.expression.is_none());
+    }
+
+    #[test]
+    fn render_expression_error() {
+        let mut app = App::default();
+        for c in "=9".chars() {
+            app.handle_key_event(KeyCode::Char(c).into()).unwrap();
+        }
+        app.handle_key_event(KeyCode::Enter.into()).unwrap();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 30, 5));
+
+        app.render(buf.area, &mut buf);
+
+        assert_eq!(row(&buf, 2), "┃            = 9             ┃");
+        assert_eq!(row(&buf, 3), "┃  error: 9 is outside 0..=2 ┃");
+    }
+
+    #[test]
+    fn switch_session() {
+        let data =
+            std::env::temp_dir().join(format!("counter-demo-session-{}", std::process::id()));
+        let mut app = App {
+            profiles: Some(Profiles::new(&Paths::under(&data))),
+            ..App::new(Theme::default())
+        };
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+        for c in "snfirst".chars() {
+            app.handle_key_event(KeyCode::Char(c).into()).unwrap();
+        }
+        app.handle_key_event(KeyCode::Enter.into()).unwrap();
+        assert_eq!(app.session.as_deref(), Some("first"));
+
+        app.save_session_as("second".into()).unwrap();
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+        app.motion = Motion::reduced();
+        app.switch_session("first".into()).unwrap();
+        assert_eq!(app.counter, 1);
+        assert!(!app.motion.reduced);
+
+        app.switch_session("second".into()).unwrap();
+        assert_eq!(app.counter, 2);
+        assert_eq!(app.history.len(), 2);
+        assert!(app.motion.reduced);
+
+        std::fs::remove_dir_all(data).unwrap();
+    }
+
+    #[test]
+    fn toasts() {
+        let data = std::env::temp_dir().join(format!("counter-demo-toasts-{}", std::process::id()));
+        let mut app = App {
+            profiles: Some(Profiles::new(&Paths::under(&data))),
+            ..App::new(Theme::plain())
+        };
+        for c in "sna/b".chars() {
+            app.handle_key_event(KeyCode::Char(c).into()).unwrap();
+        }
+        app.handle_key_event(KeyCode::Enter.into()).unwrap();
+        // 无效的名称留在选择界面，并提示原因。
+        assert!(matches!(app.screen, Screen::Sessions(_)));
+        let texts = |app: &App| {
+            app.toasts
+                .visible()
+                .map(|toast| toast.text.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts(&app), ["invalid profile name \"a/b\""]);
+
+        app.handle_key_event(KeyCode::Esc.into()).unwrap();
+        for c in "snwork".chars() {
+            app.handle_key_event(KeyCode::Char(c).into()).unwrap();
+        }
+        app.handle_key_event(KeyCode::Enter.into()).unwrap();
+        assert_eq!(
+            texts(&app),
+            ["invalid profile name \"a/b\"", "Saved session work"]
+        );
+        let mut terminal = Terminal::new(backend::TestBackend::new(40, 10)).unwrap();
+        terminal.draw(|frame| app.render_frame(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        assert_eq!(row(buffer, 7), "┃                │ Saved session work │┃");
+
+        // 消息到时自动消失，主循环等待到那个时刻。
+        let deadline = app.next_deadline().unwrap();
+        app.expire_timers(deadline + Duration::from_secs(1));
+        assert!(app.toasts.is_empty());
+
+        std::fs::remove_dir_all(data).unwrap();
+    }
+
+    #[test]
+    fn handle_key_event() {
+        let mut app = App::default();
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+        assert_eq!(app.counter, 1);
+
+        app.handle_key_event(KeyCode::Left.into()).unwrap();
+        assert_eq!(app.counter, 0);
+
+        let mut app = App::default();
+        app.handle_key_event(KeyCode::Char('q').into()).unwrap();
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn handle_replayed_events() {
+        let mut replay = Player::new([KeyCode::Right, KeyCode::Right, KeyCode::Char('q')].map(
+            |code| RecordedEvent {
+                at_ms: 0,
+                event: TerminalEvent::Key(code.into()).into(),
+            },
+        ));
+        let mut app = App::default();
+        while !app.exit {
+            app.handle_events(&mut replay).unwrap();
+        }
+        assert_eq!(app.counter, 2);
+    }
+
+    #[test]
+    fn run_lines() {
+        let mut replay = Player::new(
+            [
+                KeyCode::Right,
+                KeyCode::Right,
+                KeyCode::Left,
+                KeyCode::Char('q'),
+            ]
+            .map(|code| RecordedEvent {
+                at_ms: 0,
+                event: TerminalEvent::Key(code.into()).into(),
+            }),
+        );
+        let mut out = LineOutput::new(Vec::new());
+        App::default().run_lines(&mut out, &mut replay).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out.into_inner()).unwrap(),
+            "counter is 0. press Left to decrement, Right to increment, Q to quit\r\n\
+             counter is now 1\r\n\
+             counter is now 2 (max)\r\n\
+             counter is now 1\r\n"
+        );
+    }
+
+    #[test]
+    fn scheduled_actions() {
+        let mut app = App::default();
+        let increment = schedule::Rule::parse("every 1m increment").unwrap();
+        for _ in 0..3 {
+            app.run_scheduled(&increment);
+        }
+        // 到达最大值后跳过，不会溢出。
+        assert_eq!(app.counter, 2);
+        assert!(!app.toasts.is_empty());
+        app.run_scheduled(&schedule::Rule::parse("at 17:00 reset").unwrap());
+        assert_eq!(app.counter, 0);
+
+        app.handle_key_event(KeyCode::Char('a').into()).unwrap();
+        assert!(matches!(app.screen, Screen::Schedule));
+        let mut terminal = Terminal::new(backend::TestBackend::new(40, 3)).unwrap();
+        terminal.draw(|frame| app.render_frame(frame)).unwrap();
+        assert_eq!(
+            row(terminal.backend().buffer(), 1),
+            "┃Nothing is scheduled. Add rules to `sc┃"
+        );
+        app.handle_key_event(KeyCode::Char('a').into()).unwrap();
+        assert!(matches!(app.screen, Screen::Counter));
+    }
+
+    #[test]
+    fn run_batch() {
+        let lines = batch::parse("inc\nrender\nkey Right Left\nset 2\nrender\nquit\ninc").unwrap();
+        let mut app = App {
+            motion: Motion::reduced(),
+            ..App::new(Theme::plain())
+        };
+        let mut frames = Vec::new();
+        app.run_batch(&lines, Size::new(30, 6), Some(&mut frames))
+            .unwrap();
+        assert_eq!(app.counter, 2);
+        assert!(app.exit);
+
+        // 每帧 6 行，之后空一行。
+        let frames = String::from_utf8(frames).unwrap();
+        let frames: Vec<_> = frames.lines().collect();
+        assert_eq!(frames.len(), 14);
+        let mut terminal = Terminal::new(backend::TestBackend::new(30, 6)).unwrap();
+        let mut expected = Vec::new();
+        let frame = terminal.draw(|frame| app.render_frame(frame)).unwrap();
+        batch::write_frame(frame.buffer, &mut expected).unwrap();
+        assert_eq!(
+            frames[7..].join("\n") + "\n",
+            String::from_utf8(expected).unwrap()
+        );
+        assert_ne!(frames[..7], frames[7..]);
+
+        let lines = batch::parse("dec").unwrap();
+        assert_eq!(
+            format!(
+                "{:#}",
+                App::default()
+                    .run_batch(&lines, Size::new(30, 6), None)
+                    .unwrap_err()
+            ),
+            "line 1 failed: counter is at its minimum"
+        );
+        // 动作和按键同样不会越过 0。
+        for text in ["decrement", "key Left"] {
+            let lines = batch::parse(text).unwrap();
+            let mut app = App::default();
+            assert_eq!(
+                format!(
+                    "{:#}",
+                    app.run_batch(&lines, Size::new(30, 6), None).unwrap_err()
+                ),
+                "line 1 failed: counter is at its minimum"
+            );
+            assert_eq!(app.counter, 0);
+        }
+    }
+
+    #[test]
+    fn run_repl() {
+        let lines = [
+            "inc",
+            "",
+            "jump",
+            "dec",
+            "dec",
+            "decrement",
+            "key Left",
+            "render",
+        ];
+        let mut replay = Player::new(
+            lines
+                .map(|line| Event::Stdin { line: line.into() })
+                .into_iter()
+                .chain([Event::Signal(event::Signal::Interrupt)])
+                .map(|event| RecordedEvent { at_ms: 0, event }),
+        );
+        let mut app = App::new(Theme::plain());
+        let mut out = Vec::new();
+        app.run_repl(&mut out, &mut replay, Size::new(20, 3))
+            .unwrap();
+        assert!(app.exit);
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "counter is 0. enter commands such as inc, set 5, key Right or render, Ctrl-D to quit\n\
+             > ok 1\n\
+             > > error unknown command \"jump\"\n\
+             > ok 0\n\
+             > error counter is at its minimum\n\
+             > error counter is at its minimum\n\
+             > error counter is at its minimum\n\
+             > ┏ Counter App Tut… ┓\n\
+             ┃     Value: 0     ┃\n\
+             ┗ Decrement <Left> ┛\n\
+             \n\
+             > \n"
+        );
+    }
+
+    /// 记录收到的按键数量，并处理 `x` 键。
+    struct CountingPane(usize);
+
+    impl ratatui_common::plugin::Plugin for CountingPane {
+        fn name(&self) -> &str {
+            "Counting"
+        }
+
+        fn render(&self, area: Rect, buf: &mut Buffer) {
+            Line::from(format!("{} events", self.0)).render(area, buf);
+        }
+
+        fn handle_event(&mut self, event: &TerminalEvent) -> bool {
+            self.0 += 1;
+            matches!(event, TerminalEvent::Key(key) if key.code == KeyCode::Char('x'))
+        }
+    }
+
+    #[test]
+    fn plugin_panes() {
+        let mut app = App {
+            plugins: vec![LoadedPlugin::from(Box::new(CountingPane(0)) as Box<_>)],
+            ..App::new(Theme::plain())
+        };
+        let mut replay = Player::new([KeyCode::Tab, KeyCode::Char('x'), KeyCode::Right].map(
+            |code| RecordedEvent {
+                at_ms: 0,
+                event: TerminalEvent::Key(code.into()).into(),
+            },
+        ));
+        for _ in 0..3 {
+            app.handle_events(&mut replay).unwrap();
+        }
+        assert!(matches!(app.screen, Screen::Plugin(0)));
+        // 插件处理了 x，没有处理的 Right 仍然按计数器的按键绑定处理。
+        assert_eq!(app.counter, 1);
+
+        let mut terminal = Terminal::new(backend::TestBackend::new(20, 3)).unwrap();
+        terminal.draw(|frame| app.render_frame(frame)).unwrap();
+        terminal.backend().assert_buffer(&Buffer::with_lines(vec![
+            "┏ Counting ━━━━━━━━┓",
+            "┃2 events          ┃",
+            "┗━━━━━━━━━━━━━━━━━━┛",
+        ]));
+
+        app.handle_key_event(KeyCode::Tab.into()).unwrap();
+        assert!(matches!(app.screen, Screen::Counter));
+    }
+
+    #[test]
+    fn control_commands() {
+        use control::Command;
+
+        let mut app = App::default();
+        assert_eq!(
+            app.control(Command::Decrement),
+            Err("counter is at its minimum".into())
+        );
+        assert_eq!(app.control(Command::Increment), Ok(1));
+        assert_eq!(app.control(Command::Set(2)), Ok(2));
+        assert_eq!(
+            app.control(Command::Increment),
+            Err("counter is at its maximum".into())
+        );
+        assert_eq!(
+            app.control(Command::Set(3)),
+            Err("3 is outside 0..=2".into())
+        );
+        assert_eq!(app.control(Command::Reset), Ok(0));
+        assert_eq!(app.control(Command::Add(2)), Ok(2));
+        assert_eq!(
+            app.control(Command::Add(-3)),
+            Err("-1 is outside 0..=2".into())
+        );
+        assert_eq!(
+            app.control(Command::Add(i16::MAX)),
+            Err("32769 is outside 0..=2".into())
+        );
+        assert_eq!(
+            app.control(Command::Add(i16::MIN)),
+            Err("-32766 is outside 0..=2".into())
+        );
+        assert_eq!(app.history.len(), 4);
+
+        let mut replay = Player::new([RecordedEvent {
+            at_ms: 0,
+            event: Event::Control(Command::Quit.into()),
+        }]);
+        app.handle_events(&mut replay).unwrap();
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn merge_shared_counter() {
+        let (tx, _rx) = std::sync::mpsc::channel();
+        let mut app = App {
+            replica: Some(gossip::Replica::start("127.0.0.1:0", &[], tx).unwrap()),
+            ..App::default()
+        };
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+
+        let mut counter = crdt::PnCounter::default();
+        counter.apply("other", 1);
+        let message = |counter: &crdt::PnCounter| gossip::Message {
+            from: "127.0.0.1:1".into(),
+            peers: Default::default(),
+            counter: counter.clone(),
+        };
+        app.merge_gossip(&message(&counter));
+        assert_eq!(app.counter, 2);
+        // 合并后的值超出范围时显示边界值。
+        counter.apply("other", 5);
+        app.merge_gossip(&message(&counter));
+        assert_eq!(app.counter, 2);
+        assert_eq!(app.replica.as_ref().unwrap().value(), 7);
+        assert_eq!(
+            app.history
+                .iter()
+                .map(|change| change.delta)
+                .collect::<Vec<_>>(),
+            [1, 1]
+        );
+    }
+
+    #[test]
+    fn read_counter_from_stdin() {
+        let mut app = App::default();
+        // 最大的增量同样作为错误提示，不会让计数器溢出。
+        let lines = ["2", "", "-1", "+5", "x", "+32767", "-32768"];
+        let mut replay = Player::new(lines.map(|line| RecordedEvent {
+            at_ms: 0,
+            event: Event::Stdin { line: line.into() },
+        }));
+        for counter in [2, 2, 1, 1, 1, 1, 1] {
+            app.handle_events(&mut replay).unwrap();
+            assert_eq!(app.counter, counter);
+        }
+        assert!(!app.toasts.is_empty());
+    }
+
+    #[test]
+    fn change_counter_on_signal() {
+        let mut app = App::default();
+        let mut replay = Player::new(
+            [
+                event::Signal::Increment,
+                event::Signal::Increment,
+                event::Signal::Decrement,
+            ]
+            .map(|signal| RecordedEvent {
+                at_ms: 0,
+                event: Event::Signal(signal),
+            }),
+        );
+        for _ in 0..3 {
+            app.handle_events(&mut replay).unwrap();
+        }
+        assert_eq!(app.counter, 1);
+        assert!(!app.exit);
+
+        // 到了边界时只提示，不会溢出。
+        let mut replay = Player::new([event::Signal::Decrement; 2].map(|signal| RecordedEvent {
+            at_ms: 0,
+            event: Event::Signal(signal),
+        }));
+        app.handle_events(&mut replay).unwrap();
+        app.handle_events(&mut replay).unwrap();
+        assert_eq!(app.counter, 0);
+    }
+
+    #[test]
+    fn exit_on_signal() {
+        let mut app = App::default();
+        let mut replay = Player::new([RecordedEvent {
+            at_ms: 0,
+            event: Event::Signal(event::Signal::Terminate),
+        }]);
+        app.handle_events(&mut replay).unwrap();
+        assert!(app.exit);
+
+        let mut app = App {
+            screen: Screen::History(HistoryView::default()),
+            ..App::default()
+        };
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL))
+            .unwrap();
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn exit_code() {
+        let mut app = App::default();
+        app.handle_key_event(KeyCode::Right.into()).unwrap();
+        app.handle_key_event(KeyCode::Char('q').into()).unwrap();
+        assert!(app.exit);
+        assert_eq!(app.exit_code(false, false), 0);
+        assert_eq!(app.exit_code(true, false), 0);
+        assert_eq!(app.exit_code(false, true), 1);
+
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL))
+            .unwrap();
+        assert_eq!(app.exit_code(false, false), 0);
+        assert_eq!(app.exit_code(true, false), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "attempt to subtract with overflow")]
+    fn handle_key_event_panic() {
+        let mut app = App::default();
+        let _ = app.handle_key_event(KeyCode::Left.into());
+    }
+
+    #[test]
+    fn handle_key_event_overflow() {
+        let mut app = App::default();
+        assert!(app.handle_key_event(KeyCode::Right.into()).is_ok());
+        assert!(app.handle_key_event(KeyCode::Right.into()).is_ok());
+        assert_eq!(
+            app.handle_key_event(KeyCode::Right.into())
+                .unwrap_err()
+                .to_string(),
+            "counter overflow"
+        );
+
+        let mut app = App {
+            counter: u8::MAX,
+            max: Some(u8::MAX),
+            ..App::default()
+        };
         assert_eq!(
             app.handle_key_event(KeyCode::Right.into())
                 .unwrap_err()
                 .to_string(),
             "counter overflow"
         );
+        assert_eq!(app.counter, u8::MAX);
     }
 }
@@ -0,0 +1,137 @@
+//! Windows 上的控制接口：命名管道服务器，协议与 Unix 套接字相同，见 [`crate::control`]。
+
+use std::{
+    io::{self, BufReader, Read, Write},
+    iter, ptr,
+    sync::mpsc::Sender,
+    thread,
+};
+
+use windows_sys::Win32::{
+    Foundation::{
+        CloseHandle, GetLastError, ERROR_BROKEN_PIPE, ERROR_PIPE_CONNECTED, HANDLE,
+        INVALID_HANDLE_VALUE,
+    },
+    Storage::FileSystem::{FlushFileBuffers, ReadFile, WriteFile, PIPE_ACCESS_DUPLEX},
+    System::Pipes::{
+        ConnectNamedPipe, CreateNamedPipeW, DisconnectNamedPipe, PIPE_READMODE_BYTE,
+        PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    },
+};
+
+use crate::{control, event::Event};
+
+/// 管道名称的前缀。
+const PREFIX: &str = r"\\.\pipe\";
+
+/// 缓冲区大小，命令和回复都很短。
+const BUFFER_SIZE: u32 = 4096;
+
+/// 一个已连接的管道实例，丢弃时断开连接并关闭句柄。
+struct Pipe(HANDLE);
+
+// 管道句柄可以在任意线程中使用，同一时刻只有处理这个连接的线程访问它。
+unsafe impl Send for Pipe {}
+
+impl Pipe {
+    /// 创建管道的一个新实例。
+    fn create(name: &[u16]) -> io::Result<Self> {
+        let handle = unsafe {
+            CreateNamedPipeW(
+                name.as_ptr(),
+                PIPE_ACCESS_DUPLEX,
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT,
+                PIPE_UNLIMITED_INSTANCES,
+                BUFFER_SIZE,
+                BUFFER_SIZE,
+                0,
+                ptr::null(),
+            )
+        };
+        if handle == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self(handle))
+    }
+
+    /// 等待客户端连接到这个实例。
+    fn connect(self) -> io::Result<Self> {
+        // 客户端在 CreateNamedPipeW 和 ConnectNamedPipe 之间连接时返回 ERROR_PIPE_CONNECTED，同样算作成功。
+        if unsafe { ConnectNamedPipe(self.0, ptr::null_mut()) } == 0
+            && unsafe { GetLastError() } != ERROR_PIPE_CONNECTED
+        {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(self)
+    }
+}
+
+impl Read for &Pipe {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+        let len = buf.len().min(u32::MAX as usize) as u32;
+        if unsafe { ReadFile(self.0, buf.as_mut_ptr(), len, &mut read, ptr::null_mut()) } == 0 {
+            // 客户端关闭连接相当于读到文件末尾。
+            if unsafe { GetLastError() } == ERROR_BROKEN_PIPE {
+                return Ok(0);
+            }
+            return Err(io::Error::last_os_error());
+        }
+        Ok(read as usize)
+    }
+}
+
+impl Write for &Pipe {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut written = 0;
+        let len = buf.len().min(u32::MAX as usize) as u32;
+        if unsafe { WriteFile(self.0, buf.as_ptr(), len, &mut written, ptr::null_mut()) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(written as usize)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if unsafe { FlushFileBuffers(self.0) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Pipe {
+    fn drop(&mut self) {
+        unsafe {
+            DisconnectNamedPipe(self.0);
+            CloseHandle(self.0);
+        }
+    }
+}
+
+/// 命名管道的完整路径，`name` 可以省略 `\\.\pipe\` 前缀。
+pub fn path(name: &str) -> String {
+    if name.starts_with(PREFIX) {
+        name.to_owned()
+    } else {
+        format!("{PREFIX}{name}")
+    }
+}
+
+/// 在后台线程中监听命名管道 `name`，每个连接在单独的线程中处理。
+pub fn listen(name: &str, events: Sender<io::Result<Event>>) -> io::Result<()> {
+    let name: Vec<u16> = path(name).encode_utf16().chain(iter::once(0)).collect();
+    // 先创建第一个实例，这样管道名称无效等错误可以在启动时报告。
+    let mut next = Some(Pipe::create(&name)?);
+    thread::spawn(move || loop {
+        let pipe = match next.take().map_or_else(|| Pipe::create(&name), Ok) {
+            Ok(pipe) => pipe,
+            Err(_) => break,
+        };
+        // 连接失败的实例直接丢弃，继续等待下一个客户端。
+        if let Ok(pipe) = pipe.connect() {
+            let events = events.clone();
+            thread::spawn(move || control::serve(BufReader::new(&pipe), &pipe, &events));
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,44 @@
+//! 计数器到达里程碑时发送桌面通知。
+//!
+//! 里程碑在配置文件的 `milestones` 中设置。只有启用 `notify` 功能时才真正发送通知；
+//! 没有通知服务的平台或会话（例如 SSH 登录、没有 D-Bus 的环境）发送失败时直接忽略。
+
+/// 计数器从 `previous` 变为 `current` 时向上越过的里程碑，同时越过多个时返回最大的一个。
+pub fn reached(milestones: &[u8], previous: i16, current: u8) -> Option<u8> {
+    milestones
+        .iter()
+        .copied()
+        .filter(|&milestone| previous < i16::from(milestone) && milestone <= current)
+        .max()
+}
+
+/// 在后台线程中发送通知，不会因为通知服务响应慢而阻塞界面。
+#[cfg(feature = "notify")]
+pub fn send(summary: String, body: String) {
+    std::thread::spawn(move || {
+        let _ = notify_rust::Notification::new()
+            .appname("ratatui-counter-demo")
+            .summary(&summary)
+            .body(&body)
+            .show();
+    });
+}
+
+#[cfg(not(feature = "notify"))]
+pub fn send(_summary: String, _body: String) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crossing_milestones() {
+        let milestones = [5, 10];
+        assert_eq!(reached(&milestones, 4, 5), Some(5));
+        assert_eq!(reached(&milestones, 3, 12), Some(10));
+        // 减少或停在里程碑上时不通知。
+        assert_eq!(reached(&milestones, 6, 5), None);
+        assert_eq!(reached(&milestones, 5, 5), None);
+        assert_eq!(reached(&[], -1, 0), None);
+    }
+}
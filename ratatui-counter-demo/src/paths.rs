@@ -0,0 +1,135 @@
+//! 配置、状态、缓存和数据文件的位置。
+//!
+//! 默认遵循各平台的约定（通过 `directories`）：Linux 上是 XDG 基础目录，
+//! 例如 `~/.config/ratatui-counter-demo` 和 `~/.local/state/ratatui-counter-demo`；
+//! macOS 上是 `~/Library/Application Support` 等；Windows 上是 `%APPDATA%` 等。
+//!
+//! 每个位置都可以用环境变量覆盖：
+//!
+//! | 用途 | 环境变量 |
+//! | --- | --- |
+//! | 配置（`config.toml`、`init.lua`、主题） | `RATATUI_COUNTER_DEMO_CONFIG` |
+//! | 状态（计数值、会话） | `RATATUI_COUNTER_DEMO_STATE` |
+//! | 缓存 | `RATATUI_COUNTER_DEMO_CACHE` |
+//! | 数据（插件） | `RATATUI_COUNTER_DEMO_DATA` |
+//!
+//! 为了兼容旧版本，只设置了 `RATATUI_COUNTER_DEMO_DATA` 时所有文件都放在这个目录下。
+
+use std::{
+    env,
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+use directories::ProjectDirs;
+use serde::Serialize;
+
+const APPLICATION: &str = "ratatui-counter-demo";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Paths {
+    pub config: PathBuf,
+    pub state: PathBuf,
+    pub cache: PathBuf,
+    pub data: PathBuf,
+}
+
+impl Paths {
+    /// 根据当前进程的环境变量和平台约定确定位置。
+    pub fn detect() -> Self {
+        Self::from_env(
+            |name| env::var_os(name),
+            ProjectDirs::from("", "", APPLICATION),
+        )
+    }
+
+    /// 所有文件都放在同一个目录下，例如测试中使用的临时目录。
+    pub fn under(dir: &Path) -> Self {
+        Self {
+            config: dir.to_path_buf(),
+            state: dir.to_path_buf(),
+            cache: dir.to_path_buf(),
+            data: dir.to_path_buf(),
+        }
+    }
+
+    fn from_env(var: impl Fn(&str) -> Option<OsString>, project: Option<ProjectDirs>) -> Self {
+        let var = |name: &str| {
+            var(name)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+        };
+        let defaults = match (var("RATATUI_COUNTER_DEMO_DATA"), project) {
+            (Some(data), _) => Self::under(&data),
+            (None, Some(project)) => Self {
+                config: project.config_dir().to_path_buf(),
+                // 只有 Linux 有单独的状态目录，其他平台放在本地数据目录中。
+                state: project
+                    .state_dir()
+                    .unwrap_or_else(|| project.data_local_dir())
+                    .to_path_buf(),
+                cache: project.cache_dir().to_path_buf(),
+                data: project.data_dir().to_path_buf(),
+            },
+            // 找不到主目录时退回到当前目录。
+            (None, None) => Self::under(Path::new(APPLICATION)),
+        };
+        Self {
+            config: var("RATATUI_COUNTER_DEMO_CONFIG").unwrap_or(defaults.config),
+            state: var("RATATUI_COUNTER_DEMO_STATE").unwrap_or(defaults.state),
+            cache: var("RATATUI_COUNTER_DEMO_CACHE").unwrap_or(defaults.cache),
+            data: defaults.data,
+        }
+    }
+
+    /// base16 配色方案所在的目录，所有档案共用。
+    pub fn themes(&self) -> PathBuf {
+        self.config.join("themes")
+    }
+
+    /// 动态插件和 WebAssembly 插件所在的目录。
+    pub fn plugins(&self) -> PathBuf {
+        self.data.join("plugins")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn paths(vars: &[(&str, &str)]) -> Paths {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        Paths::from_env(
+            |name| vars.get(name).map(OsString::from),
+            ProjectDirs::from_path(PathBuf::from(APPLICATION)),
+        )
+    }
+
+    #[test]
+    fn data_dir_holds_everything() {
+        let paths = paths(&[("RATATUI_COUNTER_DEMO_DATA", "/tmp/demo")]);
+        assert_eq!(paths, Paths::under(Path::new("/tmp/demo")));
+        assert_eq!(paths.plugins(), Path::new("/tmp/demo/plugins"));
+    }
+
+    #[test]
+    fn override_each_location() {
+        let paths = paths(&[
+            ("RATATUI_COUNTER_DEMO_DATA", "/tmp/demo"),
+            ("RATATUI_COUNTER_DEMO_CONFIG", "/etc/demo"),
+            ("RATATUI_COUNTER_DEMO_STATE", ""),
+        ]);
+        assert_eq!(paths.config, Path::new("/etc/demo"));
+        // 空值视为没有设置。
+        assert_eq!(paths.state, Path::new("/tmp/demo"));
+    }
+
+    #[test]
+    fn platform_defaults() {
+        let paths = paths(&[]);
+        assert!(paths.config.ends_with(APPLICATION));
+        assert!(paths.state.ends_with(APPLICATION));
+    }
+}
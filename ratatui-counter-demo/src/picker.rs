@@ -0,0 +1,275 @@
+//! 名称选择界面，用于切换档案和会话：列出已有的名称，选中后切换，也可以输入新名称。恢复状态的
+//! 备份也用它，那里不能新建。
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::*,
+    widgets::{block::*, *},
+};
+
+use ratatui_common::text_input::Input;
+
+use crate::{
+    help::{self, Entry, Help},
+    theme::Theme,
+};
+
+#[derive(Debug, Default)]
+pub struct Picker {
+    title: &'static str,
+    names: Vec<String>,
+    current: Option<String>,
+    state: ListState,
+    /// 正在输入的新名称。
+    new_name: Option<Input>,
+    /// 不能输入新名称，只能从列表中选择。
+    list_only: bool,
+}
+
+/// 处理按键后选择界面要求应用程序执行的操作。
+#[derive(Debug, PartialEq, Eq)]
+pub enum PickerAction {
+    None,
+    Close,
+    /// 切换到已有的名称。
+    Switch(String),
+    /// 用输入的新名称创建一项并切换过去。
+    Create(String),
+    /// 打开这个界面的按键帮助。
+    Help,
+}
+
+impl Picker {
+    /// `names` 是已有的名称；当前名称即使还没有保存过也会出现在列表中。
+    pub fn new(title: &'static str, mut names: Vec<String>, current: Option<&str>) -> Self {
+        if let Some(current) = current {
+            if !names.iter().any(|name| name == current) {
+                names.push(current.to_string());
+                names.sort();
+            }
+        }
+        let selected = names
+            .iter()
+            .position(|name| Some(name.as_str()) == current)
+            .or((!names.is_empty()).then_some(0));
+        Self {
+            title,
+            names,
+            current: current.map(str::to_string),
+            state: ListState::default().with_selected(selected),
+            new_name: None,
+            list_only: false,
+        }
+    }
+
+    /// 只能从列表中选择，没有 `n` 键。
+    pub fn list_only(mut self) -> Self {
+        self.list_only = true;
+        self
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> PickerAction {
+        if let Some(input) = &mut self.new_name {
+            match key_event.code {
+                KeyCode::Esc => self.new_name = None,
+                KeyCode::Enter if !input.value().trim().is_empty() => {
+                    let name = input.value().trim().to_string();
+                    self.new_name = None;
+                    return if self.names.contains(&name) {
+                        PickerAction::Switch(name)
+                    } else {
+                        PickerAction::Create(name)
+                    };
+                }
+                _ => {
+                    input.handle_key_event(key_event);
+                }
+            }
+            return PickerAction::None;
+        }
+
+        match key_event.code {
+            KeyCode::Esc => PickerAction::Close,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.select_offset(-1);
+                PickerAction::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.select_offset(1);
+                PickerAction::None
+            }
+            KeyCode::Char('n') if !self.list_only => {
+                self.new_name = Some(Input::default());
+                PickerAction::None
+            }
+            KeyCode::Char('?') => PickerAction::Help,
+            KeyCode::Enter => match self.state.selected() {
+                Some(index) => PickerAction::Switch(self.names[index].clone()),
+                None => PickerAction::Close,
+            },
+            _ => PickerAction::None,
+        }
+    }
+
+    fn select_offset(&mut self, offset: isize) {
+        if self.names.is_empty() {
+            return;
+        }
+        let last = self.names.len() - 1;
+        let index = self.state.selected().unwrap_or(0);
+        self.state
+            .select(Some(index.saturating_add_signed(offset).min(last)));
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let mut instructions = vec![" Switch ".into(), "<Enter>".set_style(theme.key)];
+        if !self.list_only {
+            instructions.extend([" New ".into(), "<N>".set_style(theme.key)]);
+        }
+        instructions.extend([" Back ".into(), "<Esc> ".set_style(theme.key)]);
+        let instructions = Title::from(Line::from(instructions));
+        let block = Block::default()
+            .title(
+                Title::from(format!(" {} ", self.title).set_style(theme.title))
+                    .alignment(Alignment::Center),
+            )
+            .title(
+                instructions
+                    .alignment(Alignment::Center)
+                    .position(Position::Bottom),
+            )
+            .borders(Borders::ALL)
+            .border_set(theme.border_set)
+            .border_style(theme.border)
+            .style(theme.base);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [list_area, input_area] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(u16::from(self.new_name.is_some())),
+        ])
+        .areas(inner);
+        if let Some(input) = &self.new_name {
+            input.line("Name: ", theme.key).render(input_area, buf);
+        }
+
+        let items = self.names.iter().map(|name| {
+            if Some(name) == self.current.as_ref() {
+                Line::from(vec![name.into(), " (current)".set_style(theme.value)])
+            } else {
+                Line::from(name.as_str())
+            }
+        });
+        StatefulWidget::render(
+            List::new(items)
+                .highlight_symbol("> ")
+                .highlight_style(theme.key),
+            list_area,
+            buf,
+            &mut self.state,
+        );
+    }
+}
+
+impl Help for Picker {
+    fn help_title(&self) -> String {
+        self.title.into()
+    }
+
+    fn help_entries(&self) -> Vec<Entry> {
+        let mut entries = vec![
+            Entry::new("Navigation", "Up, k", "Previous name"),
+            Entry::new("Navigation", "Down, j", "Next name"),
+            Entry::new("Actions", "Enter", "Switch to the selected name"),
+        ];
+        if !self.list_only {
+            entries.push(Entry::new("Actions", "n", "Type a new name"));
+        }
+        entries.extend([
+            Entry::new("Actions", "Esc", "Back"),
+            Entry::new("Actions", "?", "This help"),
+            help::ctrl_c(),
+        ]);
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_and_switch() {
+        let mut picker = Picker::new(
+            "Profiles",
+            vec!["home".into(), "work".into()],
+            Some("default"),
+        );
+        assert_eq!(picker.names, ["default", "home", "work"]);
+        assert_eq!(picker.state.selected(), Some(0));
+
+        picker.handle_key_event(KeyCode::Down.into());
+        picker.handle_key_event(KeyCode::Down.into());
+        picker.handle_key_event(KeyCode::Down.into());
+        assert_eq!(
+            picker.handle_key_event(KeyCode::Enter.into()),
+            PickerAction::Switch("work".into())
+        );
+        assert_eq!(
+            picker.handle_key_event(KeyCode::Esc.into()),
+            PickerAction::Close
+        );
+    }
+
+    #[test]
+    fn create_new_name() {
+        let mut picker = Picker::new("Sessions", vec!["a".into()], None);
+        picker.handle_key_event(KeyCode::Char('n').into());
+        for c in "b".chars() {
+            picker.handle_key_event(KeyCode::Char(c).into());
+        }
+        assert_eq!(
+            picker.handle_key_event(KeyCode::Enter.into()),
+            PickerAction::Create("b".into())
+        );
+
+        picker.handle_key_event(KeyCode::Char('n').into());
+        picker.handle_key_event(KeyCode::Char('a').into());
+        assert_eq!(
+            picker.handle_key_event(KeyCode::Enter.into()),
+            PickerAction::Switch("a".into())
+        );
+    }
+
+    #[test]
+    fn list_only() {
+        let mut picker = Picker::new("Backups", vec!["b".into(), "a".into()], None).list_only();
+        assert_eq!(
+            picker.handle_key_event(KeyCode::Char('n').into()),
+            PickerAction::None
+        );
+        assert!(picker.new_name.is_none());
+        picker.handle_key_event(KeyCode::Down.into());
+        assert_eq!(
+            picker.handle_key_event(KeyCode::Enter.into()),
+            PickerAction::Switch("a".into())
+        );
+        assert!(!picker.help_entries().iter().any(|entry| entry.keys == "n"));
+    }
+
+    #[test]
+    fn help_only_outside_the_name_input() {
+        let mut picker = Picker::new("Sessions", Vec::new(), None);
+        assert_eq!(
+            picker.handle_key_event(KeyCode::Char('?').into()),
+            PickerAction::Help
+        );
+        assert_eq!(picker.help_title(), "Sessions");
+        picker.handle_key_event(KeyCode::Char('n').into());
+        assert_eq!(
+            picker.handle_key_event(KeyCode::Char('?').into()),
+            PickerAction::None
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! 从插件目录加载动态插件，每个插件提供一个可以用 Tab 切换到的面板。
+//!
+//! 插件接口见 [`ratatui_common::plugin`]。
+
+use std::{fmt, fs, io, path::Path};
+
+use color_eyre::{
+    eyre::{bail, WrapErr},
+    Result,
+};
+use libloading::{Library, Symbol};
+use ratatui_common::plugin::{self, CreateFn, Plugin};
+
+/// 已加载的插件。
+pub struct LoadedPlugin {
+    // 字段按声明顺序析构：插件的代码在共享库中，必须先于共享库释放。
+    pub plugin: Box<dyn Plugin>,
+    _library: Option<Library>,
+}
+
+impl fmt::Debug for LoadedPlugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoadedPlugin")
+            .field("name", &self.plugin.name())
+            .finish_non_exhaustive()
+    }
+}
+
+impl From<Box<dyn Plugin>> for LoadedPlugin {
+    /// 直接在进程内创建的插件，例如测试中使用的插件。
+    fn from(plugin: Box<dyn Plugin>) -> Self {
+        Self {
+            plugin,
+            _library: None,
+        }
+    }
+}
+
+/// 加载 `dir` 中的所有共享库，按文件名排序。目录不存在时没有插件。
+///
+/// 插件的代码会在本进程中运行，只应该把可信的插件放进插件目录。
+pub fn discover(dir: &Path) -> Result<Vec<LoadedPlugin>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(error).wrap_err_with(|| format!("reading {} failed", dir.display()))
+        }
+    };
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let is_library = path
+            .extension()
+            .is_some_and(|extension| extension == std::env::consts::DLL_EXTENSION);
+        if is_library {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| {
+            load(path).wrap_err_with(|| format!("loading plugin {} failed", path.display()))
+        })
+        .collect()
+}
+
+fn load(path: &Path) -> Result<LoadedPlugin> {
+    // SAFETY: 加载共享库会运行其中的初始化代码，插件目录中只应该有可信的插件；
+    // 符号的类型由插件接口约定，并在调用创建函数之前检查 API 版本。
+    unsafe {
+        let library = Library::new(path)?;
+        let version: Symbol<*const u32> = library.get(plugin::VERSION_SYMBOL)?;
+        let version = **version;
+        if version != plugin::API_VERSION {
+            bail!(
+                "plugin API version {version} is not supported (expected {})",
+                plugin::API_VERSION
+            );
+        }
+        let create: Symbol<CreateFn> = library.get(plugin::CREATE_SYMBOL)?;
+        let plugin = *Box::from_raw(create());
+        Ok(LoadedPlugin {
+            plugin,
+            _library: Some(library),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skip_missing_dir_and_other_files() {
+        let dir = std::env::temp_dir().join(format!("counter-demo-plugins-{}", std::process::id()));
+        assert!(discover(&dir).unwrap().is_empty());
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("README.txt"), "not a plugin").unwrap();
+        assert!(discover(&dir).unwrap().is_empty());
+
+        // 扩展名对但不是共享库时报告错误，而不是静默忽略。
+        let fake = dir.join(format!("fake.{}", std::env::consts::DLL_EXTENSION));
+        fs::write(&fake, "not a library").unwrap();
+        let error = discover(&dir).unwrap_err();
+        assert!(error.to_string().contains("fake"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,117 @@
+//! 用户配置档案（profile）。
+//!
+//! 每个档案在配置目录和状态目录（见 [`crate::paths`]）下各有自己的子目录：
+//!
+//! ```text
+//! <配置目录>/profiles/<名称>/config.toml
+//! <配置目录>/profiles/<名称>/init.lua
+//! <状态目录>/profiles/<名称>/state.json
+//! <状态目录>/profiles/<名称>/sessions/<会话>.json
+//! ```
+
+use std::{fs, io, path::PathBuf};
+
+use crate::{paths::Paths, session::Sessions};
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// 检查档案名称，供 clap 解析 `--profile` 时使用。名称会成为目录名，所以不能包含路径分隔符。
+pub fn parse_name(name: &str) -> Result<String, String> {
+    let valid = !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !name.contains(|c: char| std::path::is_separator(c) || c.is_control());
+    if valid {
+        Ok(name.to_string())
+    } else {
+        Err(format!("invalid profile name {name:?}"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profiles {
+    config: PathBuf,
+    state: PathBuf,
+}
+
+impl Profiles {
+    pub fn new(paths: &Paths) -> Self {
+        Self {
+            config: paths.config.join("profiles"),
+            state: paths.state.join("profiles"),
+        }
+    }
+
+    pub fn state_path(&self, name: &str) -> PathBuf {
+        self.state.join(name).join("state.json")
+    }
+
+    pub fn config_path(&self, name: &str) -> PathBuf {
+        self.config.join(name).join("config.toml")
+    }
+
+    /// 启动时执行的 Lua 脚本。
+    #[cfg(feature = "lua")]
+    pub fn script_path(&self, name: &str) -> PathBuf {
+        self.config.join(name).join("init.lua")
+    }
+
+    pub fn sessions(&self, name: &str) -> Sessions {
+        Sessions::new(&self.state.join(name).join("sessions"))
+    }
+
+    /// 列出已有的档案（在配置目录或状态目录中有子目录），按名称排序。
+    pub fn list(&self) -> io::Result<Vec<String>> {
+        let mut names = Vec::new();
+        for root in [&self.config, &self.state] {
+            let entries = match fs::read_dir(root) {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err),
+            };
+            for entry in entries {
+                let entry = entry?;
+                if entry.file_type()?.is_dir() {
+                    names.extend(entry.file_name().to_str().map(str::to_string));
+                }
+            }
+        }
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_names() {
+        assert_eq!(parse_name("work").unwrap(), "work");
+        assert!(parse_name("").is_err());
+        assert!(parse_name("..").is_err());
+        assert!(parse_name("a/b").is_err());
+    }
+
+    #[test]
+    fn list_profiles() {
+        let data =
+            std::env::temp_dir().join(format!("counter-demo-profiles-{}", std::process::id()));
+        let paths = Paths {
+            config: data.join("config"),
+            ..Paths::under(&data)
+        };
+        let profiles = Profiles::new(&paths);
+        assert!(profiles.list().unwrap().is_empty());
+
+        fs::create_dir_all(data.join("profiles/work")).unwrap();
+        fs::create_dir_all(data.join("profiles/home")).unwrap();
+        // 只有配置文件的档案也会列出，两边都有的只列出一次。
+        fs::create_dir_all(data.join("config/profiles/home")).unwrap();
+        fs::create_dir_all(data.join("config/profiles/play")).unwrap();
+        assert_eq!(profiles.list().unwrap(), ["home", "play", "work"]);
+
+        fs::remove_dir_all(data).unwrap();
+    }
+}
@@ -0,0 +1,209 @@
+//! 输入事件的限流：每秒最多处理配置数量的按键、鼠标和粘贴事件，坏掉的键盘或者一次粘贴大量文本时
+//! 界面仍然能及时响应，计数器也不会被打乱。
+//!
+//! ```toml
+//! [rate_limit]
+//! events_per_sec = 30
+//! # 超出的事件：drop（默认）直接丢弃，queue 排队稍后处理，最多排一秒的量
+//! overflow = "queue"
+//! ```
+//!
+//! 和 `windows_input` 一样，这一层包装任意的 [`EventSource`]。限流用令牌桶：桶里最多有一秒的令牌，
+//! 允许短时间的连续输入。按键释放、窗口大小变化、信号和控制接口的命令不受限制，也不会排在被限流的
+//! 事件后面。
+
+use std::{
+    collections::VecDeque,
+    io,
+    num::NonZeroU32,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{Event as TerminalEvent, KeyEventKind};
+use serde::Deserialize;
+
+use crate::event::{Event, EventSource};
+
+/// 超出限制的事件怎么处理。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Overflow {
+    #[default]
+    Drop,
+    Queue,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub events_per_sec: NonZeroU32,
+    #[serde(default)]
+    pub overflow: Overflow,
+}
+
+#[derive(Debug)]
+pub struct RateLimit<S> {
+    inner: S,
+    rate: f64,
+    overflow: Overflow,
+    tokens: f64,
+    refilled: Instant,
+    /// 排队等待令牌的事件。
+    queue: VecDeque<Event>,
+    /// `poll` 时已经放行、等待 `read` 返回的事件。
+    ready: Option<Event>,
+}
+
+/// 受限制的事件：按下（和按住重复）按键、鼠标和粘贴。
+fn limited(event: &Event) -> bool {
+    match event {
+        Event::Terminal(TerminalEvent::Key(key)) => key.kind != KeyEventKind::Release,
+        Event::Terminal(TerminalEvent::Mouse(_) | TerminalEvent::Paste(_)) => true,
+        _ => false,
+    }
+}
+
+impl<S: EventSource<Event>> RateLimit<S> {
+    pub fn new(inner: S, config: RateLimitConfig) -> Self {
+        let rate = f64::from(config.events_per_sec.get());
+        Self {
+            inner,
+            rate,
+            overflow: config.overflow,
+            tokens: rate,
+            refilled: Instant::now(),
+            queue: VecDeque::new(),
+            ready: None,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.refilled = now;
+    }
+
+    fn take_token(&mut self) -> bool {
+        self.refill(Instant::now());
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 下一个令牌到来之前还要等多久。
+    fn until_next_token(&self) -> Duration {
+        Duration::from_secs_f64(((1.0 - self.tokens) / self.rate).max(0.0))
+    }
+
+    /// 处理从内层读到的事件：放行、排队或者丢弃。
+    fn admit(&mut self, event: Event) {
+        if !limited(&event) || (self.queue.is_empty() && self.take_token()) {
+            self.ready = Some(event);
+        } else if self.overflow == Overflow::Queue && (self.queue.len() as f64) < self.rate {
+            self.queue.push_back(event);
+        }
+    }
+}
+
+impl<S: EventSource<Event>> EventSource<Event> for RateLimit<S> {
+    fn read(&mut self) -> io::Result<Event> {
+        loop {
+            if let Some(event) = self.ready.take() {
+                return Ok(event);
+            }
+            if self.queue.is_empty() {
+                let event = self.inner.read()?;
+                self.admit(event);
+            } else {
+                self.poll(self.until_next_token())?;
+            }
+        }
+    }
+
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.ready.is_some() {
+                return Ok(true);
+            }
+            if !self.queue.is_empty() && self.take_token() {
+                self.ready = self.queue.pop_front();
+                continue;
+            }
+            let now = Instant::now();
+            // 有排队的事件时最多等到下一个令牌，期间到达的其他事件照常放行。
+            let until = if self.queue.is_empty() {
+                deadline
+            } else {
+                deadline.min(now + self.until_next_token())
+            };
+            if self.inner.poll(until.saturating_duration_since(now))? {
+                let event = self.inner.read()?;
+                self.admit(event);
+            } else if Instant::now() >= deadline {
+                return Ok(false);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyCode;
+    use ratatui_common::recording::{Player, RecordedEvent};
+
+    use super::*;
+    use crate::event::Signal;
+
+    fn key(c: char) -> Event {
+        TerminalEvent::Key(KeyCode::Char(c).into()).into()
+    }
+
+    fn limit(events: Vec<Event>, overflow: Overflow) -> RateLimit<Player<Event>> {
+        let player = Player::new(
+            events
+                .into_iter()
+                .map(|event| RecordedEvent { at_ms: 0, event }),
+        );
+        let config = RateLimitConfig {
+            events_per_sec: NonZeroU32::new(20).unwrap(),
+            overflow,
+        };
+        RateLimit::new(player, config)
+    }
+
+    #[test]
+    fn drop_excess_events() {
+        let mut events: Vec<_> = (0..30).map(|_| key('x')).collect();
+        events.push(Event::Signal(Signal::Terminate));
+        let mut limit = limit(events, Overflow::Drop);
+        for _ in 0..20 {
+            assert_eq!(limit.read().unwrap(), key('x'));
+        }
+        // 剩下的按键被丢弃了，信号不受限制。
+        assert_eq!(limit.read().unwrap(), Event::Signal(Signal::Terminate));
+    }
+
+    #[test]
+    fn queue_excess_events() {
+        let events = "abcdefghijklmnopqrstuvwxyz".chars().map(key).collect();
+        let mut limit = limit(events, Overflow::Queue);
+        let start = Instant::now();
+        let read: String = (0..26)
+            .map(|_| match limit.read().unwrap() {
+                Event::Terminal(TerminalEvent::Key(key)) => match key.code {
+                    KeyCode::Char(c) => c,
+                    code => panic!("unexpected {code:?}"),
+                },
+                event => panic!("unexpected {event:?}"),
+            })
+            .collect();
+        assert_eq!(read, "abcdefghijklmnopqrstuvwxyz");
+        // 超出的 6 个按键按每秒 20 个的速度放行。
+        assert!(start.elapsed() >= Duration::from_millis(250));
+        assert!(!limit.poll(Duration::ZERO).unwrap());
+    }
+}
@@ -0,0 +1,216 @@
+//! 崩溃恢复：运行时定期把状态写入自动保存文件，上次运行没有正常退出时，下次启动询问是否恢复。
+//!
+//! 启动时在档案的状态目录中写入标记文件 `running`，正常退出时把它和自动保存文件 `autosave.json`
+//! 一起删除，所以启动时标记文件还在就说明上次没有正常退出（恐慌，或者进程被强行结束）。恐慌钩子
+//! （见 `errors` 模块）把恐慌的消息写进标记文件，询问时可以显示原因。
+//!
+//! 自动保存文件由 `autosave` 模块的后台线程写入，删除它们之前要先等写入完成。自动保存文件不放在系统的临时目录中，那里的文件可能在重启后就被清理掉了。
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use color_eyre::{eyre::WrapErr, Result};
+use ratatui::{
+    prelude::*,
+    widgets::{block::*, *},
+};
+
+use crate::{
+    autosave::Writer,
+    state::{self, State},
+    text,
+    theme::Theme,
+};
+
+/// 两次自动保存之间的间隔，状态没有变化时不写入。
+pub const INTERVAL: Duration = Duration::from_secs(30);
+
+/// 恐慌钩子写入消息的标记文件。
+static MARKER: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// 上次没有正常退出时留下的信息。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crash {
+    /// 恐慌的消息；进程被强行结束时没有。
+    pub reason: Option<String>,
+    /// 最后一次自动保存的状态，还没来得及保存时没有。
+    pub state: Option<State>,
+}
+
+#[derive(Debug)]
+pub struct Recovery {
+    marker: PathBuf,
+    autosave: PathBuf,
+    /// 最近一次写入的状态。
+    saved: Option<State>,
+    deadline: Instant,
+}
+
+impl Recovery {
+    /// 开始为状态文件 `state_path` 所在的档案记录，返回上次运行没有正常退出时留下的信息。
+    pub fn start(state_path: &Path, now: Instant) -> Result<(Self, Option<Crash>)> {
+        let marker = state_path.with_file_name("running");
+        let autosave = state_path.with_file_name("autosave.json");
+        let crash = match fs::read_to_string(&marker) {
+            Ok(reason) => Some(Crash {
+                reason: Some(reason.trim().to_string()).filter(|reason| !reason.is_empty()),
+                state: state::load_json(&autosave)?,
+            }),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+            Err(error) => {
+                return Err(error).wrap_err_with(|| format!("reading {} failed", marker.display()))
+            }
+        };
+        if let Some(parent) = marker.parent() {
+            fs::create_dir_all(parent)
+                .wrap_err_with(|| format!("creating {} failed", parent.display()))?;
+        }
+        fs::write(&marker, "").wrap_err_with(|| format!("writing {} failed", marker.display()))?;
+        *MARKER.lock().unwrap_or_else(|error| error.into_inner()) = Some(marker.clone());
+        let recovery = Self {
+            marker,
+            autosave,
+            saved: None,
+            deadline: now + INTERVAL,
+        };
+        Ok((recovery, crash))
+    }
+
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// 到了自动保存的时间：状态和上次保存的不同时交给 `writer` 写入。
+    pub fn tick(&mut self, state: &State, now: Instant, writer: &Writer) {
+        self.deadline = now + INTERVAL;
+        if self.saved.as_ref() != Some(state) {
+            let (path, state) = (self.autosave.clone(), state.clone());
+            self.saved = Some(state.clone());
+            writer.submit(move || state::save_json(&path, &state));
+        }
+    }
+
+    /// 正常退出：删除标记文件和自动保存文件。
+    pub fn finish(self) -> Result<()> {
+        *MARKER.lock().unwrap_or_else(|error| error.into_inner()) = None;
+        for path in [&self.autosave, &self.marker] {
+            match fs::remove_file(path) {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => {
+                    return Err(error)
+                        .wrap_err_with(|| format!("removing {} failed", path.display()))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 由恐慌钩子调用：把恐慌的消息写进标记文件。钩子中不能再出错，写入失败时什么也不做。
+pub fn record_panic(message: &str) {
+    if let Some(marker) = &*MARKER.lock().unwrap_or_else(|error| error.into_inner()) {
+        let _ = fs::write(marker, message);
+    }
+}
+
+/// 启动时询问是否恢复自动保存的状态。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prompt {
+    pub reason: Option<String>,
+    pub state: State,
+}
+
+impl Prompt {
+    /// 在 `area` 中间显示说明和按键。
+    pub fn render(&self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let width = area.width.min(56);
+        let inner_width = usize::from(width.saturating_sub(2));
+        let mut lines = vec![Line::from("The last run did not exit cleanly.")];
+        if let Some(reason) = &self.reason {
+            lines.push(Line::from(
+                text::truncate(reason, inner_width)
+                    .to_string()
+                    .set_style(theme.warning),
+            ));
+        }
+        lines.push(Line::from(format!(
+            "Restore the autosaved counter ({})?",
+            self.state.counter
+        )));
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let popup = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+        let block = Block::bordered()
+            .title(Title::from(" Recover ".set_style(theme.title)).alignment(Alignment::Center))
+            .title(
+                Title::from(Line::from(vec![
+                    " Restore ".into(),
+                    "<Y>".set_style(theme.key),
+                    " Discard ".into(),
+                    "<N> ".set_style(theme.key),
+                ]))
+                .alignment(Alignment::Center)
+                .position(Position::Bottom),
+            )
+            .border_set(theme.border_set)
+            .border_style(theme.border)
+            .style(theme.base);
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .centered()
+            .block(block)
+            .render(popup, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn detect_unclean_exit() {
+        let dir = env::temp_dir().join(format!("counter-demo-recovery-{}", std::process::id()));
+        let state_path = dir.join("state.json");
+        let now = Instant::now();
+
+        let (mut recovery, crash) = Recovery::start(&state_path, now).unwrap();
+        assert_eq!(crash, None);
+        assert_eq!(recovery.deadline(), now + INTERVAL);
+        let state = State {
+            counter: 2,
+            ..State::default()
+        };
+        let writer = Writer::spawn().unwrap();
+        recovery.tick(&state, now + INTERVAL, &writer);
+        assert_eq!(recovery.deadline(), now + INTERVAL * 2);
+        writer.finish().unwrap();
+        record_panic("attempt to add with overflow");
+
+        // 没有调用 finish 就再次启动，相当于上次崩溃了。
+        let (recovery, crash) = Recovery::start(&state_path, now).unwrap();
+        assert_eq!(
+            crash,
+            Some(Crash {
+                reason: Some("attempt to add with overflow".into()),
+                state: Some(state),
+            })
+        );
+        recovery.finish().unwrap();
+        let (recovery, crash) = Recovery::start(&state_path, now).unwrap();
+        assert_eq!(crash, None);
+        recovery.finish().unwrap();
+        assert!(!dir.join("running").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
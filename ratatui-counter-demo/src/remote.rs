@@ -0,0 +1,193 @@
+//! 在远程连接上运行计数器：每个连接有自己的 `App`、自己的窗口大小，画面写入连接而不是标准输出。
+//!
+//! 协议相关的部分（SSH、Telnet）只负责把客户端的输入转换为事件、把窗口大小的变化告诉这里，
+//! 绘制由独立线程中的普通主循环完成。
+
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+use color_eyre::Result;
+use crossterm::{
+    cursor, execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::{ClearType, WindowSize},
+    buffer::Cell,
+    layout::Size,
+    prelude::*,
+};
+
+use crate::{event::Event, theme::Theme, App, EventSource};
+
+/// 远程客户端的窗口大小，连接处理程序在客户端调整窗口后更新它。
+#[derive(Debug, Clone)]
+pub struct ClientSize(Arc<Mutex<Size>>);
+
+impl ClientSize {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self(Arc::new(Mutex::new(Size::new(width, height))))
+    }
+
+    pub fn set(&self, width: u16, height: u16) {
+        *self.0.lock().expect("size lock") = Size::new(width, height);
+    }
+
+    fn get(&self) -> Size {
+        *self.0.lock().expect("size lock")
+    }
+}
+
+/// 绘制到任意输出的后端。与 `CrosstermBackend` 相同，只是终端大小来自客户端而不是本地终端。
+pub struct RemoteBackend<W: Write> {
+    inner: CrosstermBackend<W>,
+    size: ClientSize,
+}
+
+impl<W: Write> RemoteBackend<W> {
+    pub fn new(output: W, size: ClientSize) -> Self {
+        Self {
+            inner: CrosstermBackend::new(output),
+            size,
+        }
+    }
+}
+
+impl<W: Write> Write for RemoteBackend<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Write::flush(&mut self.inner)
+    }
+}
+
+impl<W: Write> Backend for RemoteBackend<W> {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        self.inner.draw(content)
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.inner.hide_cursor()
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.inner.show_cursor()
+    }
+
+    /// 查询光标位置需要读取本地终端的回复，远程连接不支持。只有内联视口会用到它。
+    fn get_cursor(&mut self) -> io::Result<(u16, u16)> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "cursor position is not available for remote clients",
+        ))
+    }
+
+    fn set_cursor(&mut self, x: u16, y: u16) -> io::Result<()> {
+        self.inner.set_cursor(x, y)
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.inner.clear()
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
+        self.inner.clear_region(clear_type)
+    }
+
+    fn size(&self) -> io::Result<Rect> {
+        let Size { width, height } = self.size.get();
+        Ok(Rect::new(0, 0, width, height))
+    }
+
+    fn window_size(&mut self) -> io::Result<WindowSize> {
+        Ok(WindowSize {
+            columns_rows: self.size.get(),
+            pixels: Size::default(),
+        })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Backend::flush(&mut self.inner)
+    }
+}
+
+/// 为一个连接运行计数器，直到用户退出或连接断开（事件来源发送 `Signal::Hangup`）。
+///
+/// 每个连接的计数器状态互相独立，也不会保存到磁盘。计数器有边界：到达边界时按键返回错误并结束会话，
+/// 而不是溢出让服务器的线程恐慌。
+pub fn run(
+    theme: Theme,
+    output: impl Write,
+    size: ClientSize,
+    events: &mut dyn EventSource<Event>,
+) -> Result<()> {
+    let mut terminal = Terminal::new(RemoteBackend::new(output, size))?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    terminal.clear()?;
+    let mut app = App {
+        bounded: true,
+        ..App::new(theme)
+    };
+    let result = app.run(&mut terminal, events);
+    // 连接可能已经断开，恢复客户端终端失败也没有关系。
+    let _ = execute!(terminal.backend_mut(), LeaveAlternateScreen, cursor::Show);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{Event as TerminalEvent, KeyCode};
+    use ratatui_common::recording::{Player, RecordedEvent};
+
+    use super::*;
+
+    #[test]
+    fn render_to_writer_at_client_size() {
+        let size = ClientSize::new(50, 8);
+        let mut events =
+            Player::new(
+                [KeyCode::Right, KeyCode::Char('q')].map(|code| RecordedEvent {
+                    at_ms: 0,
+                    event: Event::Terminal(TerminalEvent::Key(code.into())),
+                }),
+            );
+        let mut output = Vec::new();
+        run(Theme::plain(), &mut output, size.clone(), &mut events).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("\x1b[?1049h"));
+        assert!(output.contains("Tutorial"));
+        // 画面按客户端的大小绘制：第 8 行是底部边框。
+        assert!(output.contains("\x1b[8;1H┗"));
+        // 递增之后只重新绘制了计数值。
+        assert!(output.contains("\x1b[2;29H\x1b[7m1"));
+        assert!(output.contains("\x1b[?1049l"));
+        assert_eq!(
+            RemoteBackend::new(Vec::new(), size).size().unwrap(),
+            Rect::new(0, 0, 50, 8)
+        );
+    }
+
+    #[test]
+    fn decrement_at_zero() {
+        let mut events = Player::new([RecordedEvent {
+            at_ms: 0,
+            event: Event::Terminal(TerminalEvent::Key(KeyCode::Left.into())),
+        }]);
+        let err = run(
+            Theme::plain(),
+            Vec::new(),
+            ClientSize::new(50, 8),
+            &mut events,
+        )
+        .unwrap_err();
+        assert_eq!(err.root_cause().to_string(), "counter is at its minimum");
+    }
+}
@@ -0,0 +1,318 @@
+//! 定时执行的动作：配置文件中的 `schedule` 列出规则，到时间后像按下对应的按键一样执行。
+//!
+//! ```toml
+//! schedule = ["every 10m increment", "at 17:00 reset"]
+//! ```
+//!
+//! `every` 之后是间隔，单位是 `s`、`m` 或 `h`，从启动时开始计时；`at` 之后是本地时间 `HH:MM`，每天一次。
+//! 最后是动作的名称，和配置文件 `[keys]` 中的相同。休眠错过的时间不补执行，醒来后只执行一次。
+//! 安排界面（`a`）列出接下来要执行的动作。
+
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use chrono::{NaiveDateTime, NaiveTime, TimeDelta};
+use color_eyre::{eyre::eyre, Result};
+use ratatui::{
+    prelude::*,
+    widgets::{block::*, *},
+};
+
+use crate::{keymap::Action, text, theme::Theme};
+
+/// 规则什么时候执行。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum When {
+    Every(Duration),
+    At(NaiveTime),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub when: When,
+    /// 动作的名称，显示在列表中。
+    pub name: String,
+    pub action: Action,
+}
+
+impl Rule {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let words: Vec<_> = text.split_whitespace().collect();
+        let [kind, time, name] = words[..] else {
+            return Err(format!(
+                "{text:?} is not \"every <interval> <action>\" or \"at <HH:MM> <action>\""
+            ));
+        };
+        let when = match kind {
+            "every" => When::Every(parse_interval(time)?),
+            "at" => When::At(
+                NaiveTime::parse_from_str(time, "%H:%M")
+                    .map_err(|_| format!("{time:?} is not a time of day (HH:MM)"))?,
+            ),
+            _ => return Err(format!("unknown schedule {kind:?}, expected every or at")),
+        };
+        let action = Action::from_name(name).ok_or_else(|| format!("unknown action {name:?}"))?;
+        Ok(Self {
+            when,
+            name: name.to_string(),
+            action,
+        })
+    }
+
+    /// `now` 之后第一次执行的时间。`At` 正好是现在时排到明天，刚执行过的规则不会马上再执行。
+    ///
+    /// 间隔不超过 [`MAX_INTERVAL`]，只有 `now` 接近日期的上限时才会溢出，这时不再执行。
+    fn next_after(&self, now: NaiveDateTime) -> NaiveDateTime {
+        match self.when {
+            When::Every(interval) => TimeDelta::from_std(interval)
+                .ok()
+                .and_then(|interval| now.checked_add_signed(interval))
+                .unwrap_or(NaiveDateTime::MAX),
+            When::At(time) => {
+                let today = now.date().and_time(time);
+                if today > now {
+                    today
+                } else {
+                    today + TimeDelta::days(1)
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.when {
+            When::Every(interval) => write!(f, "every {}", interval_name(interval))?,
+            When::At(time) => write!(f, "at {}", time.format("%H:%M"))?,
+        }
+        write!(f, " {}", self.name)
+    }
+}
+
+/// 最长的间隔：一年。
+const MAX_INTERVAL: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+/// 解析 `30s`、`10m`、`2h` 这样的间隔，不超过 [`MAX_INTERVAL`]。
+fn parse_interval(text: &str) -> Result<Duration, String> {
+    let error = || format!("{text:?} is not an interval such as 30s, 10m or 2h");
+    let split = text.len().checked_sub(1).ok_or_else(error)?;
+    let (number, unit) = text.split_at_checked(split).ok_or_else(error)?;
+    let number: u64 = number.parse().map_err(|_| error())?;
+    let unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        _ => return Err(error()),
+    };
+    let interval = number
+        .checked_mul(unit)
+        .map(Duration::from_secs)
+        .filter(|interval| *interval <= MAX_INTERVAL)
+        .ok_or_else(|| format!("{text:?} is longer than a year"))?;
+    if interval.is_zero() {
+        return Err(format!("{text:?} is not a positive interval"));
+    }
+    Ok(interval)
+}
+
+fn interval_name(interval: Duration) -> String {
+    match interval.as_secs() {
+        seconds if seconds % 3600 == 0 => format!("{}h", seconds / 3600),
+        seconds if seconds % 60 == 0 => format!("{}m", seconds / 60),
+        seconds => format!("{seconds}s"),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    rule: Rule,
+    next: NaiveDateTime,
+}
+
+/// 所有规则和它们下一次执行的本地时间。
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    entries: Vec<Entry>,
+}
+
+impl Schedule {
+    /// 解析配置文件中的规则，从本地时间 `now` 开始安排。
+    pub fn new(rules: &[String], now: NaiveDateTime) -> Result<Self> {
+        let entries = rules
+            .iter()
+            .map(|text| {
+                let rule = Rule::parse(text).map_err(|error| eyre!("invalid schedule: {error}"))?;
+                let next = rule.next_after(now);
+                Ok(Entry { rule, next })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { entries })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 最早的一条规则到期的时刻。`now` 和 `instant` 是同一时刻的本地时间和单调时钟。
+    pub fn deadline(&self, now: NaiveDateTime, instant: Instant) -> Option<Instant> {
+        let next = self.entries.iter().map(|entry| entry.next).min()?;
+        instant.checked_add((next - now).to_std().unwrap_or_default())
+    }
+
+    /// 取出已经到期的动作，并安排下一次执行。
+    pub fn due(&mut self, now: NaiveDateTime) -> Vec<Rule> {
+        let mut due = Vec::new();
+        for entry in &mut self.entries {
+            if entry.next <= now {
+                due.push(entry.rule.clone());
+                entry.next = entry.rule.next_after(now);
+            }
+        }
+        due
+    }
+
+    /// 接下来要执行的规则，最早的在前面。
+    pub fn upcoming(&self) -> Vec<(NaiveDateTime, &Rule)> {
+        let mut upcoming: Vec<_> = self
+            .entries
+            .iter()
+            .map(|entry| (entry.next, &entry.rule))
+            .collect();
+        upcoming.sort_by_key(|(next, _)| *next);
+        upcoming
+    }
+
+    /// 在 `area` 中列出接下来的动作和剩余的时间。
+    pub fn render(&self, area: Rect, buf: &mut Buffer, now: NaiveDateTime, theme: &Theme) {
+        let block = Block::bordered()
+            .title(Title::from(" Schedule ".set_style(theme.title)).alignment(Alignment::Center))
+            .title(
+                Title::from(Line::from(vec![
+                    " Back ".into(),
+                    "<A> ".set_style(theme.key),
+                ]))
+                .alignment(Alignment::Center)
+                .position(Position::Bottom),
+            )
+            .border_set(theme.border_set)
+            .border_style(theme.border)
+            .style(theme.base);
+        let inner_width = usize::from(block.inner(area).width);
+        let lines: Vec<Line> = if self.is_empty() {
+            vec![Line::from(
+                "Nothing is scheduled. Add rules to `schedule` in the config file.",
+            )]
+        } else {
+            self.upcoming()
+                .into_iter()
+                .map(|(next, rule)| {
+                    let remaining = (next - now).num_seconds().max(0);
+                    Line::from(vec![
+                        next.format("%H:%M:%S ").to_string().set_style(theme.value),
+                        format!(
+                            "in {:02}:{:02}:{:02} ",
+                            remaining / 3600,
+                            remaining / 60 % 60,
+                            remaining % 60
+                        )
+                        .into(),
+                        text::truncate(&rule.to_string(), inner_width.saturating_sub(21))
+                            .to_string()
+                            .set_style(theme.key),
+                    ])
+                })
+                .collect()
+        };
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 5, 1)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn parse_rules() {
+        let rule = Rule::parse("every 10m increment").unwrap();
+        assert_eq!(rule.when, When::Every(Duration::from_secs(600)));
+        assert_eq!(rule.action, Action::Increment);
+        assert_eq!(rule.to_string(), "every 10m increment");
+        assert_eq!(
+            Rule::parse("at 17:00 reset").unwrap().to_string(),
+            "at 17:00 reset"
+        );
+        assert_eq!(
+            Rule::parse("every 0s increment"),
+            Err("\"0s\" is not a positive interval".into())
+        );
+        assert_eq!(
+            Rule::parse("every 99999999999h increment"),
+            Err("\"99999999999h\" is longer than a year".into())
+        );
+        assert_eq!(
+            Rule::parse("every 18446744073709551615m increment"),
+            Err("\"18446744073709551615m\" is longer than a year".into())
+        );
+        assert!(Rule::parse("every 8760h increment").is_ok());
+        assert_eq!(
+            Rule::parse("every 10 increment"),
+            Err("\"10\" is not an interval such as 30s, 10m or 2h".into())
+        );
+        assert_eq!(
+            Rule::parse("at 25:00 reset"),
+            Err("\"25:00\" is not a time of day (HH:MM)".into())
+        );
+        assert_eq!(
+            Rule::parse("at 17:00 jump"),
+            Err("unknown action \"jump\"".into())
+        );
+        assert!(Rule::parse("increment").is_err());
+    }
+
+    #[test]
+    fn run_due_rules() {
+        let rules = ["every 10m increment".into(), "at 17:00 reset".into()];
+        let mut schedule = Schedule::new(&rules, at(16, 55)).unwrap();
+        let instant = Instant::now();
+        assert_eq!(
+            schedule.deadline(at(16, 55), instant),
+            Some(instant + Duration::from_secs(5 * 60))
+        );
+        assert!(schedule.due(at(16, 59)).is_empty());
+
+        let due = schedule.due(at(17, 0));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].action, Action::Reset);
+        // 每天一次，下一次是明天。
+        assert_eq!(
+            schedule
+                .upcoming()
+                .into_iter()
+                .map(|(next, rule)| (next, rule.to_string()))
+                .collect::<Vec<_>>(),
+            [
+                (at(17, 5), "every 10m increment".to_string()),
+                (at(17, 0) + TimeDelta::days(1), "at 17:00 reset".to_string()),
+            ]
+        );
+
+        // 错过了好几次也只执行一次。
+        let due = schedule.due(at(18, 0));
+        assert_eq!(due.len(), 1);
+        assert_eq!(schedule.upcoming()[0].0, at(18, 10));
+
+        assert!(Schedule::new(&["every 1m jump".into()], at(0, 0)).is_err());
+    }
+}
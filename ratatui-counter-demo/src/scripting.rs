@@ -0,0 +1,298 @@
+//! 用 Lua 编写的自定义命令、按键绑定和钩子。
+//!
+//! 启动时执行当前档案目录下的 `init.lua`。脚本通过全局表 `app` 访问应用程序：
+//!
+//! ```lua
+//! app.counter()            -- 当前的计数值
+//! app.max()                -- 计数器的最大值
+//! app.set_counter(n)       -- 设置计数值，超出 0..=max 时报错
+//! app.command(name, fn)    -- 定义自定义命令
+//! app.bind(keys, name)     -- 把按键（例如 "x"、"F5"、"g x"）绑定到自定义命令
+//! ```
+//!
+//! 脚本还可以定义全局函数作为钩子：`on_start()` 在启动时调用，`on_increment(value)` 在每次递增后调用。
+//!
+//! 脚本不直接持有 `App`：每次调用之前把计数值等状态复制给 Lua，调用结束后再把脚本请求的修改交给 `App` 应用，
+//! 所以只有上面这些函数是稳定的接口。
+
+use std::{fmt, fs, io, path::Path};
+
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use mlua::{Function, IntoLuaMulti, Lua, RegistryKey, Table};
+
+use crate::keymap::{self, Action, Binding};
+
+/// 用 `app.command` 定义的命令。
+struct Command {
+    name: String,
+    function: RegistryKey,
+}
+
+/// 执行 `init.lua` 时收集的定义。
+#[derive(Default)]
+struct Definitions {
+    commands: Vec<Command>,
+    /// 按键序列和命令名称。
+    bindings: Vec<(String, String)>,
+}
+
+/// 脚本调用期间 `app` 表看到的状态。
+#[derive(Debug, Clone, Copy)]
+struct Host {
+    counter: u8,
+    max: u8,
+    /// 脚本通过 `app.set_counter` 请求设置的值。
+    set: Option<u8>,
+}
+
+pub struct Scripts {
+    lua: Lua,
+    commands: Vec<Command>,
+}
+
+impl fmt::Debug for Scripts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<_> = self.commands.iter().map(|command| &command.name).collect();
+        f.debug_struct("Scripts")
+            .field("commands", &names)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Scripts {
+    /// 读取并执行 `path`，返回脚本和它定义的按键绑定。文件不存在时返回 `None`。
+    pub fn load(path: &Path) -> Result<Option<(Self, Vec<Binding>)>> {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err).wrap_err_with(|| format!("reading {} failed", path.display()))
+            }
+        };
+        let scripts = Self::from_source(&source, &path.display().to_string())
+            .wrap_err_with(|| format!("running {} failed", path.display()))?;
+        Ok(Some(scripts))
+    }
+
+    fn from_source(source: &str, name: &str) -> Result<(Self, Vec<Binding>)> {
+        let lua = Lua::new();
+        lua.set_app_data(Definitions::default());
+        lua.globals()
+            .set("app", api(&lua).map_err(lua_error)?)
+            .map_err(lua_error)?;
+        lua.load(source).set_name(name).exec().map_err(lua_error)?;
+
+        let definitions = lua.remove_app_data::<Definitions>().unwrap_or_default();
+        let mut bindings = Vec::new();
+        for (keys, name) in definitions.bindings {
+            let index = definitions
+                .commands
+                .iter()
+                .position(|command| command.name == name)
+                .ok_or_else(|| eyre!("binding {keys:?} refers to unknown command {name:?}"))?;
+            let keys = keys
+                .split_whitespace()
+                .map(|key| keymap::parse_key(key).ok_or_else(|| eyre!("unknown key {key:?}")))
+                .collect::<Result<Vec<_>>>()?;
+            if keys.is_empty() {
+                return Err(eyre!("empty key binding for command {name:?}"));
+            }
+            bindings.push(Binding {
+                keys,
+                action: Action::Command(index),
+            });
+        }
+        let scripts = Self {
+            lua,
+            commands: definitions.commands,
+        };
+        Ok((scripts, bindings))
+    }
+
+    /// 自定义命令的名称，下标与 [`Action::Command`] 的值对应。
+    pub fn command_names(&self) -> Vec<String> {
+        self.commands
+            .iter()
+            .map(|command| command.name.clone())
+            .collect()
+    }
+
+    /// 执行第 `index` 个自定义命令，返回脚本设置的新计数值。
+    pub fn run_command(&self, index: usize, counter: u8, max: u8) -> Result<Option<u8>> {
+        let command = &self.commands[index];
+        let function: Function = self
+            .lua
+            .registry_value(&command.function)
+            .map_err(lua_error)?;
+        self.call(function, (), counter, max)
+            .wrap_err_with(|| format!("running command {:?} failed", command.name))
+    }
+
+    /// 调用 `on_start` 钩子。
+    pub fn on_start(&self, counter: u8, max: u8) -> Result<Option<u8>> {
+        self.hook("on_start", (), counter, max)
+    }
+
+    /// 调用 `on_increment` 钩子，参数是递增后的值。
+    pub fn on_increment(&self, counter: u8, max: u8) -> Result<Option<u8>> {
+        self.hook("on_increment", counter, counter, max)
+    }
+
+    fn hook<'lua>(
+        &'lua self,
+        name: &str,
+        args: impl IntoLuaMulti<'lua>,
+        counter: u8,
+        max: u8,
+    ) -> Result<Option<u8>> {
+        let function: Option<Function> = self.lua.globals().get(name).map_err(lua_error)?;
+        match function {
+            Some(function) => self
+                .call(function, args, counter, max)
+                .wrap_err_with(|| format!("running hook {name} failed")),
+            None => Ok(None),
+        }
+    }
+
+    fn call<'lua>(
+        &'lua self,
+        function: Function<'lua>,
+        args: impl IntoLuaMulti<'lua>,
+        counter: u8,
+        max: u8,
+    ) -> Result<Option<u8>> {
+        self.lua.set_app_data(Host {
+            counter,
+            max,
+            set: None,
+        });
+        let result = function.call::<_, ()>(args).map_err(lua_error);
+        let host = self.lua.remove_app_data::<Host>();
+        result?;
+        Ok(host.and_then(|host| host.set))
+    }
+}
+
+/// 创建全局表 `app`。
+fn api(lua: &Lua) -> mlua::Result<Table<'_>> {
+    let app = lua.create_table()?;
+    app.set(
+        "counter",
+        lua.create_function(|lua, ()| Ok(host(lua)?.counter))?,
+    )?;
+    app.set("max", lua.create_function(|lua, ()| Ok(host(lua)?.max))?)?;
+    app.set(
+        "set_counter",
+        lua.create_function(|lua, value: i64| {
+            let mut host = lua
+                .app_data_mut::<Host>()
+                .ok_or_else(|| mlua::Error::runtime("app.set_counter is not available here"))?;
+            let value = u8::try_from(value)
+                .ok()
+                .filter(|&value| value <= host.max)
+                .ok_or_else(|| {
+                    mlua::Error::runtime(format!("{value} is outside 0..={}", host.max))
+                })?;
+            host.counter = value;
+            host.set = Some(value);
+            Ok(())
+        })?,
+    )?;
+    app.set(
+        "command",
+        lua.create_function(|lua, (name, function): (String, Function)| {
+            let function = lua.create_registry_value(function)?;
+            definitions(lua)?.commands.push(Command { name, function });
+            Ok(())
+        })?,
+    )?;
+    app.set(
+        "bind",
+        lua.create_function(|lua, (keys, name): (String, String)| {
+            definitions(lua)?.bindings.push((keys, name));
+            Ok(())
+        })?,
+    )?;
+    Ok(app)
+}
+
+fn host(lua: &Lua) -> mlua::Result<Host> {
+    lua.app_data_ref::<Host>().map(|host| *host).ok_or_else(|| {
+        mlua::Error::runtime("app state is only available inside commands and hooks")
+    })
+}
+
+fn definitions(lua: &Lua) -> mlua::Result<mlua::AppDataRefMut<'_, Definitions>> {
+    lua.app_data_mut::<Definitions>()
+        .ok_or_else(|| mlua::Error::runtime("commands can only be defined while loading init.lua"))
+}
+
+/// Lua 的错误信息已经包含脚本名称和行号，直接转换为文本。
+fn lua_error(error: mlua::Error) -> color_eyre::Report {
+    eyre!("{error}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyCode;
+
+    use super::*;
+
+    const SCRIPT: &str = r#"
+        app.command("double", function()
+            app.set_counter(app.counter() * 2)
+        end)
+        app.bind("d", "double")
+        app.bind("g d", "double")
+
+        seen = {}
+        function on_increment(value)
+            table.insert(seen, value)
+            if value == app.max() then app.set_counter(0) end
+        end
+    "#;
+
+    #[test]
+    fn commands_and_bindings() {
+        let (scripts, bindings) = Scripts::from_source(SCRIPT, "init.lua").unwrap();
+        assert_eq!(scripts.commands[0].name, "double");
+        assert_eq!(
+            bindings,
+            [
+                Binding {
+                    keys: vec![KeyCode::Char('d')],
+                    action: Action::Command(0),
+                },
+                Binding {
+                    keys: vec![KeyCode::Char('g'), KeyCode::Char('d')],
+                    action: Action::Command(0),
+                },
+            ]
+        );
+        assert_eq!(scripts.run_command(0, 3, 10).unwrap(), Some(6));
+
+        let error = scripts.run_command(0, 6, 10).unwrap_err();
+        assert!(format!("{error:#}").contains("12 is outside 0..=10"));
+    }
+
+    #[test]
+    fn hooks() {
+        let (scripts, _) = Scripts::from_source(SCRIPT, "init.lua").unwrap();
+        assert_eq!(scripts.on_start(0, 5).unwrap(), None);
+        assert_eq!(scripts.on_increment(1, 5).unwrap(), None);
+        assert_eq!(scripts.on_increment(5, 5).unwrap(), Some(0));
+        let seen: Vec<u8> = scripts.lua.load("return seen").eval::<Vec<u8>>().unwrap();
+        assert_eq!(seen, [1, 5]);
+    }
+
+    #[test]
+    fn reject_unknown_command() {
+        let error = Scripts::from_source(r#"app.bind("x", "nope")"#, "init.lua").unwrap_err();
+        assert!(error.to_string().contains("unknown command \"nope\""));
+        // 状态只能在命令和钩子中访问。
+        assert!(Scripts::from_source("app.counter()", "init.lua").is_err());
+    }
+}
@@ -0,0 +1,119 @@
+//! 命名会话。
+//!
+//! 会话是当前状态（计数器的值和历史）加上一组设置的快照，保存在档案目录下的 `sessions/<名称>.json`。
+//! 用户可以把当前状态保存为会话，并在会话切换界面中来回切换。
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    state::{self, State},
+    vault::Key,
+};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub state: State,
+    pub settings: Settings,
+}
+
+/// 每个会话单独保存的设置。
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub reduced_motion: bool,
+    pub counter_max: Option<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sessions {
+    dir: PathBuf,
+    /// 状态文件加密时会话也用同一个密钥加密。
+    key: Option<Key>,
+}
+
+impl Sessions {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            key: None,
+        }
+    }
+
+    pub fn with_key(mut self, key: Option<Key>) -> Self {
+        self.key = key;
+        self
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.json"))
+    }
+
+    /// 列出已保存的会话，按名称排序。
+    pub fn list(&self) -> io::Result<Vec<String>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+        let mut names = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                names.extend(
+                    path.file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .map(str::to_string),
+                );
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// 读取会话。会话不存在时返回默认的空会话。
+    pub fn load(&self, name: &str) -> Result<Session> {
+        Ok(state::load_sealed(&self.path(name), self.key.as_ref())?.unwrap_or_default())
+    }
+
+    pub fn save(&self, name: &str, session: &Session) -> Result<()> {
+        state::save_sealed(&self.path(name), session, self.key.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn save_list_load() {
+        let dir = env::temp_dir().join(format!("counter-demo-sessions-{}", std::process::id()));
+        let sessions = Sessions::new(&dir);
+        assert!(sessions.list().unwrap().is_empty());
+
+        let session = Session {
+            state: State {
+                counter: 1,
+                ..State::default()
+            },
+            settings: Settings {
+                reduced_motion: true,
+                counter_max: Some(10),
+            },
+        };
+        sessions.save("b", &session).unwrap();
+        sessions.save("a", &Session::default()).unwrap();
+        assert_eq!(sessions.list().unwrap(), ["a", "b"]);
+        assert_eq!(sessions.load("b").unwrap(), session);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,39 @@
+//! 把 SIGINT、SIGTERM、SIGHUP、SIGUSR1 和 SIGUSR2 转换为主循环中的事件。
+//!
+//! 收到前三个信号时应用程序像用户按下退出键一样正常退出：恢复终端、保存状态，而不是停留在原始模式中。
+//! 注意原始模式下按 Ctrl-C 不会产生 SIGINT，而是作为按键事件交给应用程序处理。
+//!
+//! SIGUSR1 增加计数器，SIGUSR2 减少计数器，脚本不用控制接口也能修改运行中的实例：
+//!
+//! ```text
+//! kill -USR1 <pid>
+//! ```
+
+use std::{io, sync::mpsc::Sender, thread};
+
+use signal_hook::{
+    consts::{SIGHUP, SIGINT, SIGTERM, SIGUSR1, SIGUSR2},
+    iterator::Signals,
+};
+
+use crate::event::{Event, Signal};
+
+/// 安装信号处理程序，并在后台线程中把信号转发为事件。
+pub fn forward(tx: Sender<io::Result<Event>>) -> io::Result<()> {
+    let mut signals = Signals::new([SIGINT, SIGTERM, SIGHUP, SIGUSR1, SIGUSR2])?;
+    thread::spawn(move || {
+        for signal in signals.forever() {
+            let signal = match signal {
+                SIGINT => Signal::Interrupt,
+                SIGTERM => Signal::Terminate,
+                SIGUSR1 => Signal::Increment,
+                SIGUSR2 => Signal::Decrement,
+                _ => Signal::Hangup,
+            };
+            if tx.send(Ok(Event::Signal(signal))).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,284 @@
+//! SSH 服务器模式：每个连接的客户端都得到一个独立的计数器实例。
+//!
+//! ```text
+//! ratatui-counter-demo --ssh 127.0.0.1:2222
+//! ssh -p 2222 localhost
+//! ```
+//!
+//! 这只是一个演示：服务器接受任何用户名和任何认证方式，主机密钥在每次启动时重新生成，
+//! 所以不要在不受信任的网络上监听。
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    sync::{mpsc, Arc},
+    thread,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use color_eyre::{eyre::WrapErr, Result};
+use crossterm::event::Event as TerminalEvent;
+use ratatui_common::input::InputParser;
+use russh::{
+    keys::key::{KeyPair, PublicKey},
+    server::{Auth, Config, Handle, Handler, Msg, Server as _, Session},
+    Channel, ChannelId,
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+use crate::{
+    event::{Event, EventChannel, Signal},
+    remote::{self, ClientSize},
+    theme::Theme,
+};
+
+/// 在 `address` 上监听，直到进程被终止。
+pub fn serve(address: &str, theme: Theme) -> Result<()> {
+    let key = KeyPair::generate_ed25519()
+        .ok_or_else(|| color_eyre::eyre::eyre!("generating the host key failed"))?;
+    let fingerprint = key.clone_public_key()?.fingerprint();
+    let config = Config {
+        keys: vec![key],
+        auth_rejection_time: Duration::from_secs(1),
+        auth_rejection_time_initial: Some(Duration::ZERO),
+        ..Default::default()
+    };
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        eprintln!("listening on {address}, host key SHA256:{fingerprint}");
+        Server { theme }
+            .run_on_address(Arc::new(config), address)
+            .await
+            .wrap_err_with(|| format!("listening on {address} failed"))
+    })
+}
+
+struct Server {
+    theme: Theme,
+}
+
+impl russh::server::Server for Server {
+    type Handler = Client;
+
+    fn new_client(&mut self, _peer: Option<std::net::SocketAddr>) -> Client {
+        Client {
+            theme: self.theme,
+            sessions: HashMap::new(),
+        }
+    }
+}
+
+/// 一个 SSH 连接。一个连接可以打开多个会话通道，每个通道运行一个计数器。
+struct Client {
+    theme: Theme,
+    sessions: HashMap<ChannelId, SessionChannel>,
+}
+
+struct SessionChannel {
+    size: ClientSize,
+    parser: InputParser,
+    /// 启动 shell 之后才有主循环接收事件。
+    events: Option<mpsc::Sender<io::Result<Event>>>,
+}
+
+impl SessionChannel {
+    fn send(&self, event: Event) {
+        if let Some(events) = &self.events {
+            // 主循环已经退出时不再需要事件。
+            let _ = events.send(Ok(event));
+        }
+    }
+}
+
+/// 把绘制的内容写入 SSH 通道。每次 `flush` 发送一个数据包，由异步任务转发给客户端。
+struct ChannelOutput {
+    tx: UnboundedSender<Vec<u8>>,
+    pending: Vec<u8>,
+}
+
+impl Write for ChannelOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        self.tx
+            .send(std::mem::take(&mut self.pending))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "client disconnected"))
+    }
+}
+
+impl Client {
+    /// 启动会话的主循环：绘制在单独的线程中进行，输出经过异步任务写回通道，主循环退出后关闭通道。
+    fn start(&mut self, channel: ChannelId, handle: Handle) {
+        let Some(session) = self.sessions.get_mut(&channel) else {
+            return;
+        };
+        let (tx, mut rx) = unbounded_channel::<Vec<u8>>();
+        tokio::spawn(async move {
+            while let Some(data) = rx.recv().await {
+                if handle.data(channel, data.into()).await.is_err() {
+                    return;
+                }
+            }
+            let _ = handle.exit_status_request(channel, 0).await;
+            let _ = handle.eof(channel).await;
+            let _ = handle.close(channel).await;
+        });
+
+        let mut events = EventChannel::default();
+        session.events = Some(events.sender());
+        let (theme, size) = (self.theme, session.size.clone());
+        thread::spawn(move || {
+            let output = ChannelOutput {
+                tx,
+                pending: Vec::new(),
+            };
+            if let Err(err) = remote::run(theme, output, size, &mut events) {
+                eprintln!("session failed: {err:#}");
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl Handler for Client {
+    type Error = russh::Error;
+
+    async fn auth_none(&mut self, _user: &str) -> Result<Auth, Self::Error> {
+        Ok(Auth::Accept)
+    }
+
+    async fn auth_password(&mut self, _user: &str, _password: &str) -> Result<Auth, Self::Error> {
+        Ok(Auth::Accept)
+    }
+
+    async fn auth_publickey(
+        &mut self,
+        _user: &str,
+        _public_key: &PublicKey,
+    ) -> Result<Auth, Self::Error> {
+        Ok(Auth::Accept)
+    }
+
+    async fn channel_open_session(
+        &mut self,
+        channel: Channel<Msg>,
+        _session: &mut Session,
+    ) -> Result<bool, Self::Error> {
+        self.sessions.insert(
+            channel.id(),
+            SessionChannel {
+                size: ClientSize::new(80, 24),
+                parser: InputParser::default(),
+                events: None,
+            },
+        );
+        Ok(true)
+    }
+
+    async fn pty_request(
+        &mut self,
+        channel: ChannelId,
+        _term: &str,
+        col_width: u32,
+        row_height: u32,
+        _pix_width: u32,
+        _pix_height: u32,
+        _modes: &[(russh::Pty, u32)],
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        if let Some(channel) = self.sessions.get(&channel) {
+            channel.size.set(clamp(col_width), clamp(row_height));
+        }
+        session.channel_success(channel);
+        Ok(())
+    }
+
+    async fn shell_request(
+        &mut self,
+        channel: ChannelId,
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        self.start(channel, session.handle());
+        session.channel_success(channel);
+        Ok(())
+    }
+
+    async fn data(
+        &mut self,
+        channel: ChannelId,
+        data: &[u8],
+        _session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        if let Some(channel) = self.sessions.get_mut(&channel) {
+            for event in channel.parser.feed(data) {
+                channel.send(event.into());
+            }
+        }
+        Ok(())
+    }
+
+    async fn window_change_request(
+        &mut self,
+        channel: ChannelId,
+        col_width: u32,
+        row_height: u32,
+        _pix_width: u32,
+        _pix_height: u32,
+        _session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        if let Some(channel) = self.sessions.get(&channel) {
+            let (width, height) = (clamp(col_width), clamp(row_height));
+            channel.size.set(width, height);
+            // 唤醒主循环，按新的大小重新绘制。
+            channel.send(TerminalEvent::Resize(width, height).into());
+        }
+        Ok(())
+    }
+
+    async fn channel_eof(
+        &mut self,
+        channel: ChannelId,
+        _session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        self.hang_up(channel);
+        Ok(())
+    }
+
+    async fn channel_close(
+        &mut self,
+        channel: ChannelId,
+        _session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        self.hang_up(channel);
+        Ok(())
+    }
+}
+
+impl Client {
+    /// 客户端断开后让主循环退出。
+    fn hang_up(&mut self, channel: ChannelId) {
+        if let Some(channel) = self.sessions.remove(&channel) {
+            channel.send(Event::Signal(Signal::Hangup));
+        }
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        for (_, channel) in self.sessions.drain() {
+            channel.send(Event::Signal(Signal::Hangup));
+        }
+    }
+}
+
+/// 客户端报告的大小是 `u32`，超出终端能表示的范围时截断。
+fn clamp(value: u32) -> u16 {
+    value.clamp(1, u16::MAX.into()) as u16
+}
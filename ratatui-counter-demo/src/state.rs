@@ -0,0 +1,226 @@
+//! 持久化的应用程序状态。
+//!
+//! 状态以 JSON 保存，应用程序退出时写入，下次启动时恢复。
+//!
+//! 写入的状态带有格式的版本号 `version`。读取时先用 [`MIGRATIONS`] 把旧版本一步一步地升级到
+//! [`VERSION`]，再解析成 [`State`]；状态文件、会话、备份和崩溃恢复的自动保存都经过这一步。改变格式
+//! 时把 `VERSION` 加一，在 `MIGRATIONS` 末尾加上从上一版升级的函数，并在 `fixtures/state` 中留一份
+//! 上一版的文件供测试读取。
+
+use std::{fs, io, path::Path};
+
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use ratatui_common::files;
+use serde::{
+    de::{self, DeserializeOwned},
+    ser, Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_json::{Map, Value};
+
+use crate::{
+    history::Change,
+    vault::{Envelope, Key},
+};
+
+/// 现在的状态格式的版本。
+pub const VERSION: u64 = 1;
+
+/// 第 `i` 个函数把第 `i` 版的状态升级为第 `i + 1` 版。
+const MIGRATIONS: [fn(&mut Map<String, Value>); VERSION as usize] = [v0_to_v1];
+
+/// 第 0 版是加入版本号之前的文件，后来加的字段都可以省略。没有 `tutorial_done` 的文件是在加入
+/// 教程之前写的，用户已经用过计数器了，不再显示教程。
+fn v0_to_v1(state: &mut Map<String, Value>) {
+    state.entry("tutorial_done").or_insert(Value::Bool(true));
+}
+
+/// 把任意版本的状态升级到现在的版本。
+fn migrate(mut value: Value) -> Result<Value, String> {
+    let state = value
+        .as_object_mut()
+        .ok_or_else(|| "the state is not a JSON object".to_string())?;
+    let version = match state.remove("version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| format!("invalid state version {version}"))?,
+    };
+    if version > VERSION {
+        return Err(format!(
+            "state version {version} is newer than the supported version {VERSION}"
+        ));
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(state);
+    }
+    Ok(value)
+}
+
+/// 序列化和反序列化由下面手写的实现包一层版本号，派生的代码（`remote = "Self"`）只处理字段。
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(remote = "Self", default)]
+pub struct State {
+    pub counter: u8,
+    pub history: Vec<Change>,
+    /// 已经完成或者跳过了第一次运行时的教程。
+    pub tutorial_done: bool,
+    /// 在主题选择界面中选择的主题：内置主题的名称或者 base16 配色方案的文件名（不含扩展名），
+    /// 启动时没有指定 `--theme` 就使用它。
+    pub theme: Option<String>,
+}
+
+impl Serialize for State {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut value =
+            State::serialize(self, serde_json::value::Serializer).map_err(ser::Error::custom)?;
+        if let Some(state) = value.as_object_mut() {
+            state.insert("version".into(), VERSION.into());
+        }
+        value.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for State {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = migrate(Value::deserialize(deserializer)?).map_err(de::Error::custom)?;
+        State::deserialize(value).map_err(de::Error::custom)
+    }
+}
+
+impl State {
+    /// 读取没有加密的状态文件，加密时报告错误。文件还不存在时返回默认状态。
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(load_sealed(path, None)?.unwrap_or_default())
+    }
+}
+
+/// 状态文件加密时返回加密的内容，需要先解锁（见 `vault` 模块）。
+pub fn encrypted(path: &Path) -> Result<Option<Envelope>> {
+    match load_json::<Value>(path)? {
+        Some(value) if Envelope::detect(&value) => serde_json::from_value(value)
+            .map(Some)
+            .wrap_err_with(|| format!("parsing {} failed", path.display())),
+        _ => Ok(None),
+    }
+}
+
+/// 读取可能加密的 JSON 文件。加密的文件需要 `key`；有 `key` 时也能读取没有加密的文件，下次保存时
+/// 加密。文件不存在时返回 `None`。
+pub fn load_sealed<T: DeserializeOwned>(path: &Path, key: Option<&Key>) -> Result<Option<T>> {
+    let Some(value) = load_json::<Value>(path)? else {
+        return Ok(None);
+    };
+    if !Envelope::detect(&value) {
+        return serde_json::from_value(value)
+            .map(Some)
+            .wrap_err_with(|| format!("parsing {} failed", path.display()));
+    }
+    let envelope = serde_json::from_value(value)
+        .wrap_err_with(|| format!("parsing {} failed", path.display()))?;
+    let key = key.ok_or_else(|| eyre!("{} is encrypted", path.display()))?;
+    key.open(&envelope)
+        .map(Some)
+        .wrap_err_with(|| format!("decrypting {} failed", path.display()))
+}
+
+/// 写入 JSON 文件，有 `key` 时加密。
+pub fn save_sealed<T: Serialize>(path: &Path, value: &T, key: Option<&Key>) -> Result<()> {
+    match key {
+        Some(key) => save_json(path, &key.seal(value)?),
+        None => save_json(path, value),
+    }
+}
+
+/// 读取 JSON 文件。文件不存在时返回 `None`。
+pub fn load_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    let json = match fs::read_to_string(path) {
+        Ok(json) => json,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err).wrap_err_with(|| format!("reading {} failed", path.display())),
+    };
+    serde_json::from_str(&json)
+        .map(Some)
+        .wrap_err_with(|| format!("parsing {} failed", path.display()))
+}
+
+/// 写入 JSON 文件，中途失败时原来的文件保持不变。
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    serde_json::to_vec_pretty(value)
+        .map_err(io::Error::from)
+        .and_then(|bytes| files::write_atomic(path, bytes))
+        .wrap_err_with(|| format!("writing {} failed", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn save_and_load() {
+        let dir = env::temp_dir().join(format!("counter-demo-state-{}", std::process::id()));
+        let path = dir.join("state.json");
+
+        assert_eq!(State::load(&path).unwrap(), State::default());
+        let state = State {
+            counter: 2,
+            ..State::default()
+        };
+        save_sealed(&path, &state, None).unwrap();
+        assert_eq!(State::load(&path).unwrap(), state);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn parse(json: &str) -> State {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn migrate_past_versions() {
+        // 第 0 版在加入历史、教程和主题的前后各有一种样子。
+        let before_tutorial = parse(include_str!("../fixtures/state/v0-counter.json"));
+        assert_eq!(
+            before_tutorial,
+            State {
+                counter: 2,
+                tutorial_done: true,
+                ..State::default()
+            }
+        );
+        let with_history = parse(include_str!("../fixtures/state/v0-history.json"));
+        assert_eq!(with_history.history.len(), 2);
+        assert_eq!(with_history.history[1].value, 2);
+        assert!(with_history.tutorial_done);
+        let with_tutorial = parse(include_str!("../fixtures/state/v0-tutorial.json"));
+        assert!(!with_tutorial.tutorial_done);
+        let with_theme = parse(include_str!("../fixtures/state/v0-theme.json"));
+        assert_eq!(with_theme.theme.as_deref(), Some("solarized"));
+        assert!(!with_theme.tutorial_done);
+
+        let v1 = parse(include_str!("../fixtures/state/v1.json"));
+        assert_eq!(
+            v1,
+            State {
+                counter: 1,
+                theme: Some("default".into()),
+                ..State::default()
+            }
+        );
+        assert_eq!(serde_json::to_value(&v1).unwrap()["version"], VERSION);
+    }
+
+    #[test]
+    fn reject_newer_versions() {
+        let error = serde_json::from_str::<State>(r#"{"version": 99, "counter": 1}"#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "state version 99 is newer than the supported version 1"
+        );
+        assert!(serde_json::from_str::<State>("[1]").is_err());
+    }
+}
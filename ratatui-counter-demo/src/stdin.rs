@@ -0,0 +1,90 @@
+//! 从管道读取计数值：标准输入不是终端时，逐行读取并交给主循环，应用程序可以放在 shell 管道的末尾。
+//!
+//! 每行一个值，不带符号的数设置计数器，带符号的数增加或减少计数器，空行忽略：
+//!
+//! ```text
+//! echo 5 | ratatui-counter-demo          # 从 5 开始
+//! tail -f deltas | ratatui-counter-demo  # +1、-2 …… 随着文件增长修改计数器
+//! ```
+//!
+//! 按键照常可用：标准输入被管道占用时，crossterm 从 `/dev/tty` 读取终端事件。每一行都作为事件进入
+//! 主循环，和按键按顺序处理，第一行通常在第一次绘制之前就到了。读到文件末尾后不再读取，应用程序继续
+//! 运行。
+//!
+//! `repl` 子命令同样从这里读取输入的每一行，只是按命令解释（见 `batch` 模块），读到末尾时退出。
+
+use std::{
+    io::{self, BufRead},
+    sync::mpsc::Sender,
+    thread,
+};
+
+use crate::{control::Command, event::Event};
+
+/// 把一行转换为控制接口的命令。
+pub fn parse(line: &str) -> Result<Option<Command>, String> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+    let command = if line.starts_with(['+', '-']) {
+        Command::Add(
+            line.parse()
+                .map_err(|_| format!("{line:?} is not a number"))?,
+        )
+    } else {
+        Command::Set(
+            line.parse()
+                .map_err(|_| format!("{line:?} is not a counter value"))?,
+        )
+    };
+    Ok(Some(command))
+}
+
+/// 标准输入是管道或文件时，在后台线程中把每一行转发为事件。标准输入是终端时什么也不做。
+///
+/// 只在 Unix 上可用：Windows 的控制台输入跟着标准输入走，被管道占用时就读不到按键了。
+#[cfg(unix)]
+pub fn forward(tx: Sender<io::Result<Event>>) {
+    if !io::IsTerminal::is_terminal(&io::stdin()) {
+        spawn(tx, false);
+    }
+}
+
+/// 不管标准输入是不是终端都转发每一行，读到末尾时（例如按下 Ctrl-D）发送 `quit` 命令。
+pub fn forward_lines(tx: Sender<io::Result<Event>>) {
+    spawn(tx, true);
+}
+
+fn spawn(tx: Sender<io::Result<Event>>, quit_at_end: bool) {
+    thread::spawn(move || {
+        // 读取失败（例如不是 UTF-8 文本）时和读到文件末尾一样停止，不影响键盘输入。
+        for line in io::stdin().lock().lines().map_while(Result::ok) {
+            if tx.send(Ok(Event::Stdin { line })).is_err() {
+                return;
+            }
+        }
+        if quit_at_end {
+            let _ = tx.send(Ok(Event::Control(Command::Quit.into())));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_lines() {
+        assert_eq!(parse("5\n"), Ok(Some(Command::Set(5))));
+        assert_eq!(parse("+2"), Ok(Some(Command::Add(2))));
+        assert_eq!(parse(" -1 "), Ok(Some(Command::Add(-1))));
+        assert_eq!(parse(""), Ok(None));
+        assert_eq!(parse("+32767"), Ok(Some(Command::Add(i16::MAX))));
+        assert_eq!(
+            parse("300"),
+            Err("\"300\" is not a counter value".to_string())
+        );
+        assert_eq!(parse("+x"), Err("\"+x\" is not a number".to_string()));
+    }
+}
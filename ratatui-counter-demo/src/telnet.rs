@@ -0,0 +1,265 @@
+//! Telnet（或原始 TCP）模式：把画面作为 ANSI 转义序列发送给连接的客户端，每个客户端一个独立的计数器，
+//! 适合在展示终端上运行。
+//!
+//! ```text
+//! ratatui-counter-demo --telnet 0.0.0.0:2323
+//! telnet localhost 2323
+//! ```
+//!
+//! 连接后服务器请求客户端关闭本地回显、逐字符发送（SUPPRESS-GO-AHEAD），并通过 NAWS（RFC 1073）报告窗口大小。
+//! 不支持 Telnet 协商的客户端（例如 `nc`）按 80×24 绘制。
+//! 和 SSH 模式不同，连接没有加密也没有认证。
+
+use std::{
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::mpsc::Sender,
+    thread,
+};
+
+use color_eyre::{eyre::WrapErr, Result};
+use crossterm::event::Event as TerminalEvent;
+use ratatui_common::input::InputParser;
+
+use crate::{
+    event::{Event, EventChannel, Signal},
+    remote::{self, ClientSize},
+    theme::Theme,
+};
+
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+
+const ECHO: u8 = 1;
+const SUPPRESS_GO_AHEAD: u8 = 3;
+const NAWS: u8 = 31;
+
+/// 客户端不报告窗口大小时使用的大小。
+const DEFAULT_SIZE: (u16, u16) = (80, 24);
+
+/// 在 `address` 上监听，每个连接在单独的线程中处理，直到进程被终止。
+pub fn serve(address: &str, theme: Theme) -> Result<()> {
+    let listener =
+        TcpListener::bind(address).wrap_err_with(|| format!("listening on {address} failed"))?;
+    eprintln!("listening on {}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        thread::spawn(move || {
+            let peer = stream.peer_addr();
+            if let Err(err) = serve_client(stream, theme) {
+                eprintln!("session {peer:?} failed: {err:#}");
+            }
+        });
+    }
+    Ok(())
+}
+
+fn serve_client(mut stream: TcpStream, theme: Theme) -> Result<()> {
+    stream.set_nodelay(true)?;
+    stream.write_all(&[IAC, WILL, ECHO, IAC, WILL, SUPPRESS_GO_AHEAD, IAC, DO, NAWS])?;
+    let size = ClientSize::new(DEFAULT_SIZE.0, DEFAULT_SIZE.1);
+    let mut events = EventChannel::default();
+    let reader = stream.try_clone()?;
+    let (tx, reader_size) = (events.sender(), size.clone());
+    thread::spawn(move || read_client(reader, reader_size, tx));
+    let result = remote::run(theme, &stream, size, &mut events);
+    // 用户退出后断开连接，读取线程随之结束。
+    let _ = stream.shutdown(std::net::Shutdown::Both);
+    result
+}
+
+/// 读取客户端的输入，直到连接断开，然后让主循环退出。
+fn read_client(mut stream: TcpStream, size: ClientSize, tx: Sender<io::Result<Event>>) {
+    let mut telnet = TelnetParser::default();
+    let mut input = InputParser::default();
+    let mut buf = [0; 1024];
+    loop {
+        let n = match stream.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        let mut events = Vec::new();
+        for output in telnet.feed(&buf[..n]) {
+            match output {
+                Output::Data(data) => {
+                    events.extend(input.feed(&data).into_iter().map(Event::from));
+                }
+                Output::Resize(width, height) => {
+                    size.set(width, height);
+                    events.push(TerminalEvent::Resize(width, height).into());
+                }
+            }
+        }
+        for event in events {
+            if tx.send(Ok(event)).is_err() {
+                return;
+            }
+        }
+    }
+    let _ = tx.send(Ok(Event::Signal(Signal::Hangup)));
+}
+
+/// 从客户端数据中分离出的内容。
+#[derive(Debug, PartialEq, Eq)]
+enum Output {
+    /// 用户的输入。
+    Data(Vec<u8>),
+    /// 客户端通过 NAWS 报告的新窗口大小。
+    Resize(u16, u16),
+}
+
+/// 最多保留的子协商内容，NAWS 只需要 5 个字节，更多的直接丢弃，客户端不能让服务端无限缓存。
+const MAX_SUBNEGOTIATION: usize = 64;
+
+/// 去掉 Telnet 命令，只留下用户的输入和窗口大小。命令可能被拆到两次读取中。
+#[derive(Debug, Default)]
+struct TelnetParser {
+    state: State,
+    /// 正在接收的子协商内容。
+    subnegotiation: Vec<u8>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum State {
+    #[default]
+    Data,
+    Iac,
+    /// WILL、WONT、DO 或 DONT 之后的选项字节。
+    Option,
+    Subnegotiation,
+    SubnegotiationIac,
+}
+
+impl TelnetParser {
+    fn feed(&mut self, bytes: &[u8]) -> Vec<Output> {
+        let mut outputs = Vec::new();
+        let mut data = Vec::new();
+        for &byte in bytes {
+            self.state = match (self.state, byte) {
+                (State::Data, IAC) => State::Iac,
+                (State::Data, _) => {
+                    data.push(byte);
+                    State::Data
+                }
+                // IAC IAC 表示数据中的 255。
+                (State::Iac, IAC) => {
+                    data.push(IAC);
+                    State::Data
+                }
+                (State::Iac, WILL | WONT | DO | DONT) => State::Option,
+                (State::Iac, SB) => {
+                    self.subnegotiation.clear();
+                    State::Subnegotiation
+                }
+                (State::Iac, _) | (State::Option, _) => State::Data,
+                (State::Subnegotiation, IAC) => State::SubnegotiationIac,
+                (State::Subnegotiation, _) => {
+                    self.push_subnegotiation(byte);
+                    State::Subnegotiation
+                }
+                (State::SubnegotiationIac, SE) => {
+                    if let Some(size) = self.window_size() {
+                        if !data.is_empty() {
+                            outputs.push(Output::Data(std::mem::take(&mut data)));
+                        }
+                        outputs.push(size);
+                    }
+                    State::Data
+                }
+                (State::SubnegotiationIac, _) => {
+                    self.push_subnegotiation(byte);
+                    State::Subnegotiation
+                }
+            };
+        }
+        if !data.is_empty() {
+            outputs.push(Output::Data(data));
+        }
+        outputs
+    }
+
+    fn push_subnegotiation(&mut self, byte: u8) {
+        if self.subnegotiation.len() < MAX_SUBNEGOTIATION {
+            self.subnegotiation.push(byte);
+        }
+    }
+
+    /// `NAWS 宽度（2 字节）高度（2 字节）`，大小为 0 表示客户端不知道，忽略。
+    fn window_size(&self) -> Option<Output> {
+        match self.subnegotiation[..] {
+            [NAWS, w1, w2, h1, h2] => {
+                let (width, height) = (u16::from_be_bytes([w1, w2]), u16::from_be_bytes([h1, h2]));
+                (width > 0 && height > 0).then_some(Output::Resize(width, height))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufRead;
+
+    use super::*;
+
+    #[test]
+    fn strip_commands() {
+        let mut parser = TelnetParser::default();
+        assert_eq!(
+            parser.feed(&[b'a', IAC, DO, ECHO, IAC, IAC, b'b']),
+            [Output::Data(vec![b'a', IAC, b'b'])]
+        );
+        // 窗口大小 100×30，其中的 255 需要转义；子协商被拆到两次读取中。
+        assert_eq!(parser.feed(&[IAC, SB, NAWS, 0, 100, 0]), []);
+        assert_eq!(
+            parser.feed(&[30, IAC, SE, b'q']),
+            [Output::Resize(100, 30), Output::Data(vec![b'q'])]
+        );
+        assert_eq!(
+            parser.feed(&[IAC, SB, NAWS, 1, IAC, IAC, 0, 40, IAC, SE]),
+            [Output::Resize(511, 40)]
+        );
+        // 大小为 0 时忽略。
+        assert_eq!(parser.feed(&[IAC, SB, NAWS, 0, 0, 0, 0, IAC, SE]), []);
+
+        // 没有结束的子协商不会无限缓存，结束后照常处理之后的输入。
+        parser.feed(&[IAC, SB, NAWS]);
+        for _ in 0..100 {
+            assert_eq!(parser.feed(&[0; 1024]), []);
+        }
+        assert_eq!(parser.subnegotiation.len(), MAX_SUBNEGOTIATION);
+        assert_eq!(parser.feed(&[IAC, SE, b'q']), [Output::Data(vec![b'q'])]);
+    }
+
+    #[test]
+    fn serve_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            serve_client(stream, Theme::plain())
+        });
+
+        let mut client = TcpStream::connect(address).unwrap();
+        client
+            .write_all(&[IAC, SB, NAWS, 0, 40, 0, 6, IAC, SE])
+            .unwrap();
+        client.write_all(b"\x1b[Cq").unwrap();
+        server.join().unwrap().unwrap();
+
+        let mut output = Vec::new();
+        io::BufReader::new(client)
+            .read_until(0, &mut output)
+            .unwrap();
+        assert!(output.starts_with(&[IAC, WILL, ECHO]));
+        let text = String::from_utf8_lossy(&output);
+        // 底部边框画在客户端报告的第 6 行。
+        assert!(text.contains("\x1b[6;1H┗"));
+        assert!(text.contains("\x1b[?1049l"));
+    }
+}
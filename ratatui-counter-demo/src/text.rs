@@ -0,0 +1,76 @@
+//! 按显示宽度（终端单元格数）处理文本。
+//!
+//! 中日韩文字和许多表情符号在终端里占两个单元格，按 `char` 或字节计数会让居中偏移、截断出错，
+//! 所以标题、状态消息和输入框都应该通过这里的函数计算宽度。
+
+use std::borrow::Cow;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// 截断时追加的省略号，本身占一个单元格。
+const ELLIPSIS: char = '…';
+
+/// 文本在终端中占用的单元格数。
+pub fn width(text: &str) -> usize {
+    text.width()
+}
+
+/// 把文本截断到最多 `max_width` 个单元格，被截断时以 `…` 结尾。
+///
+/// 双宽字符不会被拆成两半：如果放不下整个字符，就在它之前截断。
+pub fn truncate(text: &str, max_width: usize) -> Cow<'_, str> {
+    if width(text) <= max_width {
+        return Cow::Borrowed(text);
+    }
+    if max_width == 0 {
+        return Cow::Borrowed("");
+    }
+
+    let budget = max_width - 1;
+    let mut used = 0;
+    let mut truncated = String::new();
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w > budget {
+            break;
+        }
+        used += w;
+        truncated.push(c);
+    }
+    truncated.push(ELLIPSIS);
+    Cow::Owned(truncated)
+}
+
+/// 在 `area_width` 个单元格中居中显示文本时，左侧需要留出的单元格数。
+pub fn center_offset(text: &str, area_width: usize) -> usize {
+    area_width.saturating_sub(width(text)) / 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn width_counts_cells() {
+        assert_eq!(width("abc"), 3);
+        assert_eq!(width("计数器"), 6);
+        assert_eq!(width("カウンター"), 10);
+    }
+
+    #[test]
+    fn truncate_keeps_wide_chars_whole() {
+        assert_eq!(truncate("计数器", 6), "计数器");
+        assert_eq!(truncate("计数器", 5), "计数…");
+        // 第二个字符只剩一个单元格，放不下，所以在它之前截断。
+        assert_eq!(truncate("计数器", 4), "计…");
+        assert_eq!(truncate("计数器", 1), "…");
+        assert_eq!(truncate("计数器", 0), "");
+    }
+
+    #[test]
+    fn center_offset_uses_display_width() {
+        assert_eq!(center_offset("ab", 10), 4);
+        assert_eq!(center_offset("计数", 10), 3);
+        assert_eq!(center_offset("计数器计数器", 10), 0);
+    }
+}
@@ -0,0 +1,320 @@
+//! 应用程序的配色主题。
+//!
+//! 渲染代码只引用语义化的样式（标题、按键提示、计数值等），具体颜色由主题决定。
+//! 所有主题都不能只靠颜色传达信息：例如计数器到达上限时，除了警告样式之外还会显示文字提示。
+//! 终端不支持真彩色或 Unicode 时，[`Theme::adapt`] 会把颜色、边框和符号降级为终端能显示的样子。
+//! 配置文件可以为每种界面选择边框样式和符号（见 [`Decorations`]）。
+
+use std::{collections::BTreeMap, env};
+
+use clap::ValueEnum;
+use ratatui::{
+    style::{Color, Modifier, Style, Stylize},
+    symbols::border,
+};
+use ratatui_common::capabilities::{Capabilities, ASCII_BORDER};
+use serde::Deserialize;
+
+/// 可以通过 `--theme` 选择的主题。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ThemeName {
+    #[default]
+    Default,
+    /// 黑底白字的高对比度主题。
+    HighContrast,
+    /// 避免红绿搭配的主题，适合红绿色盲（绿色弱）用户。
+    Deuteranopia,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// 整个应用程序区域的基础样式。
+    pub base: Style,
+    pub border: Style,
+    pub title: Style,
+    /// 说明文本中的按键提示，例如 `<Left>`。
+    pub key: Style,
+    /// 计数器的值。
+    pub value: Style,
+    /// 计数器到达上限等需要引起注意的状态。
+    pub warning: Style,
+    /// 边框使用的字符。
+    pub border_set: border::Set,
+    pub symbols: Symbols,
+}
+
+/// 界面中除了边框以外用到的符号。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbols {
+    /// 教程中从左右两侧指向突出显示的区域的箭头。
+    pub arrow_right: &'static str,
+    pub arrow_left: &'static str,
+    /// 主题界面中的颜色样本。
+    pub swatch: &'static str,
+    /// 加速步长前面的乘号。
+    pub times: &'static str,
+}
+
+impl Symbols {
+    pub const UNICODE: Self = Self {
+        arrow_right: "▶",
+        arrow_left: "◀",
+        swatch: "█",
+        times: "×",
+    };
+
+    pub const ASCII: Self = Self {
+        arrow_right: ">",
+        arrow_left: "<",
+        swatch: "#",
+        times: "x",
+    };
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(ThemeName::Default)
+    }
+}
+
+impl Theme {
+    /// 去掉所有颜色和修饰（粗体等）的主题，只保留布局。
+    pub const fn plain() -> Self {
+        Self {
+            base: Style::new(),
+            border: Style::new(),
+            title: Style::new(),
+            key: Style::new(),
+            value: Style::new(),
+            warning: Style::new(),
+            border_set: border::THICK,
+            symbols: Symbols::UNICODE,
+        }
+    }
+
+    pub fn new(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Default => Self {
+                base: Style::new(),
+                border: Style::new(),
+                title: Style::new().bold(),
+                key: Style::new().blue().bold(),
+                value: Style::new().yellow(),
+                warning: Style::new().red().bold(),
+                border_set: border::THICK,
+                symbols: Symbols::UNICODE,
+            },
+            ThemeName::HighContrast => Self {
+                base: Style::new().fg(Color::White).bg(Color::Black),
+                border: Style::new().fg(Color::White).bold(),
+                title: Style::new().fg(Color::White).bold(),
+                key: Style::new()
+                    .fg(Color::Black)
+                    .bg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+                value: Style::new().fg(Color::White).bold(),
+                warning: Style::new()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+                border_set: border::THICK,
+                symbols: Symbols::UNICODE,
+            },
+            // 蓝色与橙色（黄色）在绿色弱视觉下仍然容易区分。
+            ThemeName::Deuteranopia => Self {
+                base: Style::new(),
+                border: Style::new(),
+                title: Style::new().bold(),
+                key: Style::new().fg(Color::LightBlue).bold(),
+                value: Style::new().fg(Color::Rgb(230, 159, 0)),
+                warning: Style::new()
+                    .fg(Color::Rgb(86, 180, 233))
+                    .bold()
+                    .underlined(),
+                border_set: border::THICK,
+                symbols: Symbols::UNICODE,
+            },
+        }
+    }
+}
+
+impl Theme {
+    /// 按照终端能力降级：颜色换成终端能显示的最接近的颜色，不支持 Unicode 时使用 ASCII 边框。
+    pub fn adapt(self, capabilities: &Capabilities) -> Self {
+        let style = |style: Style| Style {
+            fg: style.fg.map(|color| capabilities.adapt_color(color)),
+            bg: style.bg.map(|color| capabilities.adapt_color(color)),
+            underline_color: style
+                .underline_color
+                .map(|color| capabilities.adapt_color(color)),
+            ..style
+        };
+        Self {
+            base: style(self.base),
+            border: style(self.border),
+            title: style(self.title),
+            key: style(self.key),
+            value: style(self.value),
+            warning: style(self.warning),
+            border_set: capabilities.adapt_border(self.border_set),
+            symbols: if capabilities.unicode {
+                self.symbols
+            } else {
+                Symbols::ASCII
+            },
+        }
+    }
+}
+
+/// 配置文件 `[borders]` 中的边框样式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BorderStyle {
+    Thick,
+    Rounded,
+    Double,
+    Plain,
+    Ascii,
+}
+
+impl BorderStyle {
+    pub fn set(self) -> border::Set {
+        match self {
+            Self::Thick => border::THICK,
+            Self::Rounded => border::ROUNDED,
+            Self::Double => border::DOUBLE,
+            Self::Plain => border::PLAIN,
+            Self::Ascii => ASCII_BORDER,
+        }
+    }
+}
+
+/// 配置文件 `[symbols]` 中的符号集。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolSet {
+    Unicode,
+    Ascii,
+}
+
+impl SymbolSet {
+    pub fn symbols(self) -> Symbols {
+        match self {
+            Self::Unicode => Symbols::UNICODE,
+            Self::Ascii => Symbols::ASCII,
+        }
+    }
+}
+
+/// 可以单独设置边框和符号的界面，也是配置文件 `[borders]` 和 `[symbols]` 中的键。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Widget {
+    /// 没有单独设置的界面使用的样式。
+    Default,
+    Counter,
+    WideDemo,
+    /// 档案和会话的选择界面。
+    Picker,
+    History,
+    Help,
+    Themes,
+    ThemeEditor,
+    Tutorial,
+    Plugin,
+    Schedule,
+}
+
+/// 每种界面的边框样式和符号。没有设置的界面使用 `default` 的设置，都没有设置时使用主题自己的。
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Decorations {
+    pub borders: BTreeMap<Widget, BorderStyle>,
+    pub symbols: BTreeMap<Widget, SymbolSet>,
+}
+
+impl Decorations {
+    /// 绘制 `widget` 时使用的主题。终端不支持 Unicode 时总是使用 ASCII 边框和符号。
+    pub fn apply(&self, theme: Theme, widget: Widget, capabilities: &Capabilities) -> Theme {
+        let mut theme = theme;
+        if let Some(style) = pick(&self.borders, widget) {
+            theme.border_set = style.set();
+        }
+        if let Some(set) = pick(&self.symbols, widget) {
+            theme.symbols = set.symbols();
+        }
+        if !capabilities.unicode {
+            theme.border_set = ASCII_BORDER;
+            theme.symbols = Symbols::ASCII;
+        }
+        theme
+    }
+}
+
+/// `widget` 的设置，没有时使用 `default` 的设置。
+fn pick<T: Copy>(map: &BTreeMap<Widget, T>, widget: Widget) -> Option<T> {
+    map.get(&widget)
+        .or_else(|| map.get(&Widget::Default))
+        .copied()
+}
+
+/// 按照 <https://no-color.org> 的约定，`NO_COLOR` 环境变量存在且不为空时禁用颜色。
+pub fn no_color() -> bool {
+    env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::capabilities::ColorSupport;
+
+    use super::*;
+
+    #[test]
+    fn adapt_to_basic_terminal() {
+        let capabilities = Capabilities {
+            colors: ColorSupport::Ansi16,
+            unicode: false,
+            ..Capabilities::default()
+        };
+        let theme = Theme::new(ThemeName::Deuteranopia).adapt(&capabilities);
+        assert_eq!(theme.value.fg, Some(Color::Yellow));
+        assert_eq!(theme.border_set, ASCII_BORDER);
+        assert_eq!(theme.symbols, Symbols::ASCII);
+        assert_eq!(
+            Theme::default().adapt(&capabilities).key,
+            Theme::default().key
+        );
+    }
+
+    #[test]
+    fn decorations_per_widget() {
+        let decorations = Decorations {
+            borders: BTreeMap::from([
+                (Widget::Default, BorderStyle::Rounded),
+                (Widget::Help, BorderStyle::Double),
+            ]),
+            symbols: BTreeMap::from([(Widget::Tutorial, SymbolSet::Ascii)]),
+        };
+        let unicode = Capabilities::default();
+        let help = decorations.apply(Theme::default(), Widget::Help, &unicode);
+        assert_eq!(help.border_set, border::DOUBLE);
+        assert_eq!(help.symbols, Symbols::UNICODE);
+        let tutorial = decorations.apply(Theme::default(), Widget::Tutorial, &unicode);
+        assert_eq!(tutorial.border_set, border::ROUNDED);
+        assert_eq!(tutorial.symbols, Symbols::ASCII);
+        assert_eq!(
+            Decorations::default().apply(Theme::default(), Widget::Counter, &unicode),
+            Theme::default()
+        );
+
+        // 终端不支持 Unicode 时不管配置了什么都使用 ASCII。
+        let ascii = Capabilities {
+            unicode: false,
+            ..Capabilities::default()
+        };
+        let help = decorations.apply(Theme::default(), Widget::Help, &ascii);
+        assert_eq!(
+            (help.border_set, help.symbols),
+            (ASCII_BORDER, Symbols::ASCII)
+        );
+    }
+}
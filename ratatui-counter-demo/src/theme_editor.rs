@@ -0,0 +1,355 @@
+//! 主题编辑器：逐个调整语义化样式的颜色（从 256 色调色板中选），右边实时预览，最后保存为
+//! `themes` 目录中新的 base16 配色方案。
+//!
+//! 七种语义化颜色按 [`Scheme::theme`] 的对应关系写进 base16 的 16 个位置，所以保存的方案在主题选择
+//! 界面中打开时得到同样的颜色。
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::*,
+    widgets::{block::*, *},
+};
+use ratatui_common::{
+    capabilities::{self, Capabilities},
+    text_input::Input,
+};
+
+use crate::{
+    base16::Scheme,
+    help::{self, Entry, Help},
+    profile,
+    theme::Theme,
+    theme_picker,
+};
+
+/// 可以调整的颜色和它们在 base16 中的位置。
+const SLOTS: [(&str, &[usize]); 7] = [
+    ("Background", &[0x00, 0x01, 0x02]),
+    ("Text", &[0x05, 0x06, 0x07]),
+    ("Border", &[0x03, 0x04]),
+    ("Title", &[0x0D, 0x0E]),
+    ("Key", &[0x0C, 0x0B]),
+    ("Value", &[0x09, 0x0A]),
+    ("Warning", &[0x08, 0x0F]),
+];
+
+/// 调色板每行的颜色数。
+const COLUMNS: u8 = 16;
+
+#[derive(Debug)]
+pub struct ThemeEditor {
+    /// 每种颜色在 256 色调色板中的位置，顺序同 `SLOTS`。
+    colors: [u8; 7],
+    /// 正在调整的颜色。
+    slot: usize,
+    /// 正在输入保存的名称。
+    name: Option<Input>,
+    /// 名称无效的原因，显示在输入行后面。
+    error: Option<String>,
+    capabilities: Capabilities,
+}
+
+/// 处理按键后主题编辑器要求应用程序执行的操作。
+#[derive(Debug, PartialEq, Eq)]
+pub enum ThemeEditorAction {
+    None,
+    Close,
+    /// 保存编辑好的配色方案并使用它。
+    Save(Scheme),
+    /// 打开这个界面的按键帮助。
+    Help,
+}
+
+impl ThemeEditor {
+    /// 从 `theme` 的颜色开始编辑。没有设置颜色的样式使用终端的默认颜色（黑底浅灰字）。
+    pub fn new(theme: &Theme, capabilities: Capabilities) -> Self {
+        let index = |color: Option<Color>| color.and_then(capabilities::to_indexed);
+        let text = index(theme.base.fg).unwrap_or(7);
+        let colors = [
+            index(theme.base.bg).unwrap_or(0),
+            text,
+            index(theme.border.fg).unwrap_or(text),
+            index(theme.title.fg).unwrap_or(text),
+            index(theme.key.fg).unwrap_or(text),
+            index(theme.value.fg).unwrap_or(text),
+            index(theme.warning.fg).unwrap_or(1),
+        ];
+        Self {
+            colors,
+            slot: 0,
+            name: None,
+            error: None,
+            capabilities,
+        }
+    }
+
+    /// 用编辑好的颜色组成的配色方案。
+    pub fn scheme(&self, name: &str) -> Scheme {
+        let mut palette = [Color::Reset; 16];
+        for ((_, positions), &index) in SLOTS.iter().zip(&self.colors) {
+            let (r, g, b) = capabilities::indexed_to_rgb(index);
+            for &position in *positions {
+                palette[position] = Color::Rgb(r, g, b);
+            }
+        }
+        Scheme {
+            slug: name.to_string(),
+            name: name.to_string(),
+            author: String::new(),
+            palette,
+        }
+    }
+
+    fn theme(&self) -> Theme {
+        self.scheme("").theme().adapt(&self.capabilities)
+    }
+
+    fn color(&self, index: u8) -> Color {
+        let (r, g, b) = capabilities::indexed_to_rgb(index);
+        self.capabilities.adapt_color(Color::Rgb(r, g, b))
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> ThemeEditorAction {
+        if let Some(input) = &mut self.name {
+            match key_event.code {
+                KeyCode::Esc => {
+                    self.name = None;
+                    self.error = None;
+                }
+                KeyCode::Enter => match profile::parse_name(input.value().trim()) {
+                    Ok(name) => {
+                        self.name = None;
+                        return ThemeEditorAction::Save(self.scheme(&name));
+                    }
+                    Err(_) => self.error = Some("not a valid file name".into()),
+                },
+                _ => {
+                    if input.handle_key_event(key_event) {
+                        self.error = None;
+                    }
+                }
+            }
+            return ThemeEditorAction::None;
+        }
+
+        let color = &mut self.colors[self.slot];
+        match key_event.code {
+            KeyCode::Esc => return ThemeEditorAction::Close,
+            KeyCode::Char('?') => return ThemeEditorAction::Help,
+            KeyCode::Char('s') => self.name = Some(Input::default()),
+            KeyCode::Tab => self.slot = (self.slot + 1) % SLOTS.len(),
+            KeyCode::BackTab => self.slot = (self.slot + SLOTS.len() - 1) % SLOTS.len(),
+            KeyCode::Left | KeyCode::Char('h') => *color = color.saturating_sub(1),
+            KeyCode::Right | KeyCode::Char('l') => *color = color.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => *color = color.saturating_sub(COLUMNS),
+            KeyCode::Down | KeyCode::Char('j') => *color = color.saturating_add(COLUMNS),
+            _ => {}
+        }
+        ThemeEditorAction::None
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let instructions = Title::from(Line::from(vec![
+            " Color ".into(),
+            "<Tab>".set_style(theme.key),
+            " Pick ".into(),
+            "<Arrows>".set_style(theme.key),
+            " Save ".into(),
+            "<S>".set_style(theme.key),
+            " Back ".into(),
+            "<Esc> ".set_style(theme.key),
+        ]));
+        let block = Block::default()
+            .title(
+                Title::from(" Theme Editor ".set_style(theme.title)).alignment(Alignment::Center),
+            )
+            .title(
+                instructions
+                    .alignment(Alignment::Center)
+                    .position(Position::Bottom),
+            )
+            .borders(Borders::ALL)
+            .border_set(theme.border_set)
+            .border_style(theme.border)
+            .style(theme.base);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [main, input_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(inner);
+        let [slots_area, grid_area, preview_area] = Layout::horizontal([
+            Constraint::Length(15),
+            Constraint::Length(u16::from(COLUMNS) * 2 + 1),
+            Constraint::Fill(1),
+        ])
+        .areas(main);
+
+        let slots: Vec<Line> = SLOTS
+            .iter()
+            .zip(&self.colors)
+            .enumerate()
+            .map(|(index, ((name, _), &color))| {
+                let (marker, style) = if index == self.slot {
+                    ("> ", theme.key)
+                } else {
+                    ("  ", Style::new())
+                };
+                Line::from(vec![
+                    marker.set_style(style),
+                    theme.symbols.swatch.repeat(2).fg(self.color(color)),
+                    format!(" {name}").set_style(style),
+                ])
+            })
+            .collect();
+        Paragraph::new(slots).render(slots_area, buf);
+
+        self.render_grid(grid_area, buf);
+
+        let selected = self.colors[self.slot];
+        let (r, g, b) = capabilities::indexed_to_rgb(selected);
+        theme_picker::render_sample(
+            preview_area,
+            buf,
+            &self.theme(),
+            vec![Line::from(format!(
+                "{} #{r:02x}{g:02x}{b:02x}",
+                SLOTS[self.slot].0
+            ))],
+        );
+
+        if let Some(input) = &self.name {
+            let mut line = input.line("Save as: ", theme.key);
+            if let Some(error) = &self.error {
+                line.push_span(format!("  {error}").set_style(theme.warning));
+            }
+            line.render(input_area, buf);
+        }
+    }
+
+    /// 256 色调色板，每种颜色占两列；选中的颜色标为 `[]`。放不下时滚动到选中的那一行。
+    fn render_grid(&self, area: Rect, buf: &mut Buffer) {
+        let selected = self.colors[self.slot];
+        let selected_row = u16::from(selected / COLUMNS);
+        let first_row = selected_row.saturating_sub(area.height.saturating_sub(1));
+        for y in 0..area.height {
+            let row = first_row + y;
+            if row >= 256 / u16::from(COLUMNS) {
+                break;
+            }
+            for column in 0..COLUMNS {
+                let index = row as u8 * COLUMNS + column;
+                let x = area.x + 1 + u16::from(column) * 2;
+                if x + 1 >= area.right() {
+                    break;
+                }
+                let (r, g, b) = capabilities::indexed_to_rgb(index);
+                let symbol = if index == selected { "[]" } else { "  " };
+                // 选中标记的颜色和背景形成对比。
+                let contrast =
+                    if u32::from(r) * 299 + u32::from(g) * 587 + u32::from(b) * 114 > 128_000 {
+                        Color::Black
+                    } else {
+                        Color::White
+                    };
+                buf.set_string(
+                    x,
+                    area.y + y,
+                    symbol,
+                    Style::new().bg(self.color(index)).fg(contrast),
+                );
+            }
+        }
+    }
+}
+
+impl Help for ThemeEditor {
+    fn help_title(&self) -> String {
+        "Theme Editor".into()
+    }
+
+    fn help_entries(&self) -> Vec<Entry> {
+        vec![
+            Entry::new("Colors", "Tab", "Next color"),
+            Entry::new("Colors", "Shift+Tab", "Previous color"),
+            Entry::new("Colors", "Left, h", "Previous palette color"),
+            Entry::new("Colors", "Right, l", "Next palette color"),
+            Entry::new("Colors", "Up, k", "Palette row above"),
+            Entry::new("Colors", "Down, j", "Palette row below"),
+            Entry::new("Actions", "s", "Save as a new theme"),
+            Entry::new("Actions", "Esc", "Back"),
+            Entry::new("Actions", "?", "This help"),
+            help::ctrl_c(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+
+    use crate::theme::ThemeName;
+
+    use super::*;
+
+    fn rows(editor: &ThemeEditor, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| {
+            editor.render(area, buf, &Theme::plain())
+        })
+    }
+
+    #[test]
+    fn start_from_a_theme() {
+        let editor = ThemeEditor::new(&Theme::new(ThemeName::Default), Capabilities::default());
+        // 默认主题的按键是蓝色，数值是黄色，标题没有颜色时和文字相同。
+        assert_eq!(editor.colors, [0, 7, 7, 7, 4, 3, 1]);
+        let scheme = editor.scheme("mine");
+        assert_eq!(scheme.base(0x0C), Color::Rgb(0, 0, 238));
+        assert_eq!(scheme.theme().key.fg, Some(Color::Rgb(0, 0, 238)));
+    }
+
+    #[test]
+    fn pick_and_save() {
+        let mut editor = ThemeEditor::new(&Theme::plain(), Capabilities::default());
+        for code in [
+            KeyCode::Down,
+            KeyCode::Right,
+            KeyCode::Tab,
+            KeyCode::BackTab,
+        ] {
+            editor.handle_key_event(code.into());
+        }
+        assert_eq!(editor.colors[0], 17);
+        assert_eq!(
+            rows(&editor, 80, 12)[1..6],
+            [
+                "┃> ██ Background                                 ┏━━━━━━━━━ Preview ━━━━━━━━━━┓┃",
+                "┃  ██ Text         []                            ┃          Value: 7          ┃┃",
+                "┃  ██ Border                                     ┃       Value: 9 (max)       ┃┃",
+                "┃  ██ Title                                      ┃                            ┃┃",
+                "┃  ██ Key                                        ┃     Background #00005f     ┃┃",
+            ]
+        );
+
+        editor.handle_key_event(KeyCode::Char('s').into());
+        editor.handle_key_event(KeyCode::Char('/').into());
+        assert_eq!(
+            editor.handle_key_event(KeyCode::Enter.into()),
+            ThemeEditorAction::None
+        );
+        assert_eq!(
+            rows(&editor, 80, 12)[10],
+            "┃Save as: /   not a valid file name                                            ┃"
+        );
+        editor.handle_key_event(KeyCode::Backspace.into());
+        for c in "navy".chars() {
+            editor.handle_key_event(KeyCode::Char(c).into());
+        }
+        let ThemeEditorAction::Save(scheme) = editor.handle_key_event(KeyCode::Enter.into()) else {
+            panic!("expected the theme to be saved");
+        };
+        assert_eq!(
+            (scheme.slug.as_str(), scheme.base(0x01)),
+            ("navy", Color::Rgb(0, 0, 95))
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! 主题选择界面：列出内置的主题和 `themes` 目录中的 base16 配色方案，右边用选中的主题预览计数器，
+//! 确认后才应用。
+
+use std::path::Path;
+
+use clap::ValueEnum;
+use color_eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::*,
+    widgets::{block::*, *},
+};
+use ratatui_common::capabilities::Capabilities;
+
+use crate::{
+    base16::{self, Scheme},
+    help::{self, Entry, Help},
+    theme::{Theme, ThemeName},
+};
+
+/// 可以选择的主题。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Choice {
+    Builtin(ThemeName),
+    Base16(Scheme),
+}
+
+impl Default for Choice {
+    fn default() -> Self {
+        Self::Builtin(ThemeName::default())
+    }
+}
+
+impl Choice {
+    pub fn name(&self) -> String {
+        match self {
+            Self::Builtin(name) => name
+                .to_possible_value()
+                .map(|value| value.get_name().to_string())
+                .unwrap_or_default(),
+            Self::Base16(scheme) => scheme.name.clone(),
+        }
+    }
+
+    /// 保存在状态文件中的名称：内置主题的名称或者 base16 配色方案的文件名。
+    pub fn id(&self) -> String {
+        match self {
+            Self::Builtin(_) => self.name(),
+            Self::Base16(scheme) => scheme.slug.clone(),
+        }
+    }
+
+    /// [`Choice::id`] 对应的主题：内置主题优先，其次是 `dir` 中的 base16 配色方案。
+    pub fn find(id: &str, dir: &Path) -> Result<Option<Self>> {
+        if let Ok(name) = ThemeName::from_str(id, true) {
+            return Ok(Some(Self::Builtin(name)));
+        }
+        Ok(base16::find(dir, id)?.map(Self::Base16))
+    }
+
+    /// 按终端能力降级后的主题。
+    pub fn theme(&self, capabilities: &Capabilities) -> Theme {
+        let theme = match self {
+            Self::Builtin(name) => Theme::new(*name),
+            Self::Base16(scheme) => scheme.theme(),
+        };
+        theme.adapt(capabilities)
+    }
+}
+
+#[derive(Debug)]
+pub struct ThemePicker {
+    choices: Vec<Choice>,
+    /// 正在使用的主题在 `choices` 中的位置。
+    current: Option<usize>,
+    state: ListState,
+    capabilities: Capabilities,
+}
+
+/// 处理按键后主题选择界面要求应用程序执行的操作。
+#[derive(Debug, PartialEq, Eq)]
+pub enum ThemePickerAction {
+    None,
+    Close,
+    /// 应用选中的主题。
+    Apply(Choice),
+    /// 在主题编辑器中修改选中的主题的副本。
+    Edit(Choice),
+    /// 打开这个界面的按键帮助。
+    Help,
+}
+
+impl ThemePicker {
+    /// 内置主题排在前面，后面是 `schemes`。
+    pub fn new(schemes: Vec<Scheme>, current: &Choice, capabilities: Capabilities) -> Self {
+        let choices: Vec<Choice> = ThemeName::value_variants()
+            .iter()
+            .copied()
+            .map(Choice::Builtin)
+            .chain(schemes.into_iter().map(Choice::Base16))
+            .collect();
+        let current = choices.iter().position(|choice| choice == current);
+        Self {
+            state: ListState::default().with_selected(Some(current.unwrap_or(0))),
+            choices,
+            current,
+            capabilities,
+        }
+    }
+
+    fn selected(&self) -> &Choice {
+        &self.choices[self.state.selected().unwrap_or(0)]
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> ThemePickerAction {
+        let last = self.choices.len() - 1;
+        let index = self.state.selected().unwrap_or(0);
+        match key_event.code {
+            KeyCode::Esc => return ThemePickerAction::Close,
+            KeyCode::Enter => return ThemePickerAction::Apply(self.selected().clone()),
+            KeyCode::Char('e') => return ThemePickerAction::Edit(self.selected().clone()),
+            KeyCode::Char('?') => return ThemePickerAction::Help,
+            KeyCode::Up | KeyCode::Char('k') => self.state.select(Some(index.saturating_sub(1))),
+            KeyCode::Down | KeyCode::Char('j') => self.state.select(Some((index + 1).min(last))),
+            _ => {}
+        }
+        ThemePickerAction::None
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let instructions = Title::from(Line::from(vec![
+            " Apply ".into(),
+            "<Enter>".set_style(theme.key),
+            " Edit ".into(),
+            "<E>".set_style(theme.key),
+            " Back ".into(),
+            "<Esc> ".set_style(theme.key),
+        ]));
+        let block = Block::default()
+            .title(Title::from(" Themes ".set_style(theme.title)).alignment(Alignment::Center))
+            .title(
+                instructions
+                    .alignment(Alignment::Center)
+                    .position(Position::Bottom),
+            )
+            .borders(Borders::ALL)
+            .border_set(theme.border_set)
+            .border_style(theme.border)
+            .style(theme.base);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [list_area, preview_area] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Fill(1)]).areas(inner);
+        let items = self.choices.iter().enumerate().map(|(index, choice)| {
+            let name = choice.name();
+            if Some(index) == self.current {
+                Line::from(vec![name.into(), " (current)".set_style(theme.value)])
+            } else {
+                Line::from(name)
+            }
+        });
+        StatefulWidget::render(
+            List::new(items)
+                .highlight_symbol("> ")
+                .highlight_style(theme.key),
+            list_area,
+            buf,
+            &mut self.state,
+        );
+        self.render_preview(preview_area, buf, theme);
+    }
+
+    /// 用选中的主题预览，base16 方案还列出 16 种颜色。
+    fn render_preview(&self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let choice = self.selected();
+        let mut lines = Vec::new();
+        if let Choice::Base16(scheme) = choice {
+            let swatches = scheme.palette.iter().map(|&color| {
+                theme
+                    .symbols
+                    .swatch
+                    .fg(self.capabilities.adapt_color(color))
+            });
+            lines.push(Line::from(swatches.collect::<Vec<_>>()));
+            if !scheme.author.is_empty() {
+                lines.push(Line::from(format!("by {}", scheme.author)));
+            }
+        }
+        render_sample(area, buf, &choice.theme(&self.capabilities), lines);
+    }
+}
+
+/// 用 `theme` 画一个缩小的计数器，`extra` 显示在计数值下面。
+pub fn render_sample(area: Rect, buf: &mut Buffer, theme: &Theme, extra: Vec<Line<'static>>) {
+    let block = Block::default()
+        .title(Title::from(" Preview ".set_style(theme.title)).alignment(Alignment::Center))
+        .title(
+            Title::from(Line::from(vec![
+                " Increment ".into(),
+                "<Right> ".set_style(theme.key),
+            ]))
+            .alignment(Alignment::Center)
+            .position(Position::Bottom),
+        )
+        .borders(Borders::ALL)
+        .border_set(theme.border_set)
+        .border_style(theme.border)
+        .style(theme.base);
+    let mut lines = vec![
+        Line::from(vec!["Value: ".into(), "7".set_style(theme.value)]),
+        Line::from(vec![
+            "Value: ".into(),
+            "9".set_style(theme.value),
+            " (max)".set_style(theme.warning),
+        ]),
+    ];
+    if !extra.is_empty() {
+        lines.push(Line::default());
+        lines.extend(extra);
+    }
+    Paragraph::new(lines)
+        .centered()
+        .wrap(Wrap { trim: true })
+        .block(block)
+        .render(area, buf);
+}
+
+impl Help for ThemePicker {
+    fn help_title(&self) -> String {
+        "Themes".into()
+    }
+
+    fn help_entries(&self) -> Vec<Entry> {
+        vec![
+            Entry::new("Navigation", "Up, k", "Previous theme"),
+            Entry::new("Navigation", "Down, j", "Next theme"),
+            Entry::new("Actions", "Enter", "Apply the selected theme"),
+            Entry::new("Actions", "e", "Edit a copy of the selected theme"),
+            Entry::new("Actions", "Esc", "Back"),
+            Entry::new("Actions", "?", "This help"),
+            help::ctrl_c(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn scheme() -> Scheme {
+        let palette: String = (0..16)
+            .map(|index| format!("base0{index:X}: \"{:02x}0000\"\n", index * 16))
+            .collect();
+        Scheme::parse("red", &format!("scheme: Reds\nauthor: Ada\n{palette}")).unwrap()
+    }
+
+    fn rows(picker: &mut ThemePicker, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| {
+            picker.render(area, buf, &Theme::plain())
+        })
+    }
+
+    #[test]
+    fn preview_and_apply() {
+        let mut picker =
+            ThemePicker::new(vec![scheme()], &Choice::default(), Capabilities::default());
+        assert_eq!(
+            rows(&mut picker, 50, 9),
+            [
+                "┏━━━━━━━━━━━━━━━━━━━━ Themes ━━━━━━━━━━━━━━━━━━━━┓",
+                "┃> default (current)┏━━━━━━━━━ Preview ━━━━━━━━━┓┃",
+                "┃  high-contrast    ┃         Value: 7          ┃┃",
+                "┃  deuteranopia     ┃      Value: 9 (max)       ┃┃",
+                "┃  Reds             ┃                           ┃┃",
+                "┃                   ┃                           ┃┃",
+                "┃                   ┃                           ┃┃",
+                "┃                   ┗━━━━ Increment <Right> ━━━━┛┃",
+                "┗━━━━━━ Apply <Enter> Edit <E> Back <Esc> ━━━━━━━┛",
+            ]
+        );
+        for _ in 0..3 {
+            picker.handle_key_event(KeyCode::Down.into());
+        }
+        assert_eq!(
+            rows(&mut picker, 50, 9)[4..7],
+            [
+                "┃> Reds             ┃                           ┃┃",
+                "┃                   ┃     ████████████████      ┃┃",
+                "┃                   ┃          by Ada           ┃┃",
+            ]
+        );
+        // 预览使用选中的方案，列表仍然使用当前的主题。
+        let mut buf = Buffer::empty(Rect::new(0, 0, 50, 9));
+        picker.render(buf.area, &mut buf, &Theme::plain());
+        assert_eq!(buf.get(21, 2).bg, Color::Rgb(0, 0, 0));
+        assert_eq!(buf.get(41, 5).fg, Color::Rgb(0xf0, 0, 0));
+        assert_eq!(buf.get(1, 2).bg, Color::Reset);
+        assert_eq!(
+            picker.handle_key_event(KeyCode::Enter.into()),
+            ThemePickerAction::Apply(Choice::Base16(scheme()))
+        );
+        assert_eq!(
+            picker.handle_key_event(KeyCode::Esc.into()),
+            ThemePickerAction::Close
+        );
+    }
+
+    #[test]
+    fn mark_the_current_theme() {
+        let current = Choice::Base16(scheme());
+        let picker = ThemePicker::new(vec![scheme()], &current, Capabilities::default());
+        assert_eq!(picker.selected(), &current);
+        assert_eq!(
+            Choice::Builtin(ThemeName::HighContrast).name(),
+            "high-contrast"
+        );
+        let missing = Path::new("no-such-themes-directory");
+        assert_eq!(
+            Choice::find("high-contrast", missing).unwrap(),
+            Some(Choice::Builtin(ThemeName::HighContrast))
+        );
+        assert_eq!(Choice::find("reds", missing).unwrap(), None);
+    }
+}
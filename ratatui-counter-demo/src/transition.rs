@@ -0,0 +1,193 @@
+//! 切换界面时的过渡效果：新界面从右边滑入，或者旧界面淡出后新界面淡入。
+//!
+//! 过渡不需要各个界面配合：主循环记住上一帧画出的内容，发现界面变了（包括用 `Tab` 切换插件面板、
+//! 打开帮助和各个选择界面）就在它和新界面之间合成。效果在配置文件的 `transition` 中选择，
+//! 减少动态效果时总是立即切换。
+
+use std::time::{Duration, Instant};
+
+use ratatui::{buffer::Cell, prelude::*};
+use ratatui_common::{
+    animation::{self, Animation, Easing, Lerp},
+    capabilities::Capabilities,
+    motion::Motion,
+};
+use serde::Deserialize;
+
+/// 过渡的时长。
+const DURATION: Duration = Duration::from_millis(200);
+
+/// 配置文件中 `transition` 的取值。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    #[default]
+    Slide,
+    /// 通过插值颜色淡出淡入。
+    Fade,
+    Instant,
+}
+
+#[derive(Debug)]
+pub struct Transition {
+    kind: Kind,
+    /// 切换前最后一帧的内容。
+    from: Buffer,
+    progress: Animation<f64>,
+}
+
+impl Transition {
+    /// 从 `from` 开始过渡到下一个界面；不需要过渡时返回 `None`。
+    pub fn start(kind: Kind, from: Buffer, motion: Motion, now: Instant) -> Option<Self> {
+        if kind == Kind::Instant {
+            return None;
+        }
+        let mut progress = Animation::new(0.0, DURATION, Easing::EaseInOut);
+        progress.animate_to(1.0, motion, now);
+        progress.is_running().then_some(Self {
+            kind,
+            from,
+            progress,
+        })
+    }
+
+    /// 返回是否还在过渡。
+    pub fn tick(&mut self, now: Instant) -> bool {
+        self.progress.tick(now)
+    }
+
+    pub fn next_frame(&self, now: Instant) -> Option<Instant> {
+        self.progress.next_frame(now)
+    }
+
+    /// 把 `buf` 中已经画好的新界面和旧界面按进度合成。终端大小变了时直接显示新界面。
+    pub fn render(&self, buf: &mut Buffer, capabilities: &Capabilities) {
+        let area = buf.area;
+        if self.from.area != area {
+            return;
+        }
+        let to = buf.clone();
+        let progress = self.progress.value();
+        match self.kind {
+            // 旧界面向左移出，新界面跟在它右边。
+            Kind::Slide => {
+                let shift = (progress * f64::from(area.width)).round() as u16;
+                let split = area.width - shift;
+                for y in area.top()..area.bottom() {
+                    for x in 0..area.width {
+                        let cell = if x < split {
+                            self.from.get(area.x + x + shift, y)
+                        } else {
+                            to.get(area.x + x - split, y)
+                        };
+                        *buf.get_mut(area.x + x, y) = cell.clone();
+                    }
+                }
+            }
+            // 前一半时间旧界面的文字褪成背景色，后一半时间新界面的文字从背景色显现。
+            Kind::Fade => {
+                let (source, visible) = if progress < 0.5 {
+                    (&self.from, 1.0 - 2.0 * progress)
+                } else {
+                    (&to, 2.0 * progress - 1.0)
+                };
+                for y in area.top()..area.bottom() {
+                    for x in area.left()..area.right() {
+                        *buf.get_mut(x, y) = fade(source.get(x, y), visible, capabilities);
+                    }
+                }
+            }
+            Kind::Instant => {}
+        }
+    }
+}
+
+/// 文字颜色向背景色靠近，`visible` 为 0 时和背景一样。颜色无法插值时（例如没有颜色的主题）
+/// 不到一半的文字直接隐藏。
+fn fade(cell: &Cell, visible: f64, capabilities: &Capabilities) -> Cell {
+    let mut cell = cell.clone();
+    if animation::to_rgb(cell.fg).is_some() && animation::to_rgb(cell.bg).is_some() {
+        cell.fg = capabilities.adapt_color(cell.bg.lerp(cell.fg, visible));
+    } else if visible < 0.5 {
+        cell.set_symbol(" ");
+    }
+    cell
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing::buffer_rows;
+
+    use super::*;
+
+    fn screens() -> (Buffer, Buffer) {
+        (
+            Buffer::with_lines(vec!["aaaa", "aaaa"]),
+            Buffer::with_lines(vec!["bbbb", "bbbb"]),
+        )
+    }
+
+    fn halfway(kind: Kind, from: Buffer, to: &Buffer) -> Buffer {
+        let start = Instant::now();
+        let mut transition = Transition::start(kind, from, Motion::default(), start).unwrap();
+        assert!(transition.tick(start + DURATION / 2));
+        let mut buf = to.clone();
+        transition.render(&mut buf, &Capabilities::default());
+        buf
+    }
+
+    #[test]
+    fn slide() {
+        let (from, to) = screens();
+        assert_eq!(
+            buffer_rows(&halfway(Kind::Slide, from, &to)),
+            ["aabb", "aabb"]
+        );
+
+        let start = Instant::now();
+        let (from, _) = screens();
+        let mut transition =
+            Transition::start(Kind::Slide, from, Motion::default(), start).unwrap();
+        assert_eq!(transition.next_frame(start), Some(start + animation::FRAME));
+        assert!(!transition.tick(start + DURATION));
+        let mut buf = to.clone();
+        transition.render(&mut buf, &Capabilities::default());
+        assert_eq!(buf, to);
+    }
+
+    #[test]
+    fn fade_through_the_background() {
+        let (mut from, to) = screens();
+        from.set_style(
+            from.area,
+            Style::new()
+                .fg(Color::Rgb(200, 0, 0))
+                .bg(Color::Rgb(0, 0, 100)),
+        );
+        let start = Instant::now();
+        let mut transition = Transition::start(Kind::Fade, from, Motion::default(), start).unwrap();
+        transition.tick(start + DURATION / 4);
+        let mut buf = to.clone();
+        transition.render(&mut buf, &Capabilities::default());
+        assert_eq!(buffer_rows(&buf), ["aaaa", "aaaa"]);
+        let fg = buf.get(0, 0).fg;
+        assert!(fg != Color::Rgb(200, 0, 0) && fg != Color::Rgb(0, 0, 100));
+
+        // 没有颜色时文字在中间隐藏。
+        let (from, to) = screens();
+        assert_eq!(
+            buffer_rows(&halfway(Kind::Fade, from, &to)),
+            ["    ", "    "]
+        );
+    }
+
+    #[test]
+    fn instant_or_reduced_motion() {
+        let (from, _) = screens();
+        assert!(
+            Transition::start(Kind::Instant, from, Motion::default(), Instant::now()).is_none()
+        );
+        let (from, _) = screens();
+        assert!(Transition::start(Kind::Slide, from, Motion::reduced(), Instant::now()).is_none());
+    }
+}
@@ -1,19 +1,195 @@
-use std::io::{self, stdout, Stdout};
+use std::{
+    io::{self, stderr, stdout, Stderr, Stdout, Write},
+    sync::atomic::{AtomicBool, Ordering},
+};
 
-use crossterm::{execute, terminal::*};
+use crossterm::{
+    cursor,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute, queue,
+    terminal::*,
+};
 use ratatui::prelude::*;
+use ratatui_common::capabilities::Capabilities;
 
 /// 此应用程序中使用的终端类型的类型别名
-pub type Tui = Terminal<CrosstermBackend<Stdout>>;
+pub type Tui = Terminal<CrosstermBackend<Out>>;
 
-pub fn init() -> io::Result<Tui> {
-    execute!(stdout(), EnterAlternateScreen)?;
+/// `init` 是进入了备用屏幕还是直接在主屏幕中绘制。恐慌钩子也会调用 `restore`，所以记录在全局状态里。
+static ALTERNATE_SCREEN: AtomicBool = AtomicBool::new(false);
+static MAIN_SCREEN: AtomicBool = AtomicBool::new(false);
+/// 是否打开了鼠标捕获，恐慌时也要关掉。
+static MOUSE_CAPTURE: AtomicBool = AtomicBool::new(false);
+/// 界面是否画到标准错误，见 [`draw_to_stderr`]。
+static STDERR: AtomicBool = AtomicBool::new(false);
+
+/// 界面输出的位置：平时是标准输出，调用 [`draw_to_stderr`] 之后是标准错误。
+#[derive(Debug)]
+pub enum Out {
+    Stdout(Stdout),
+    Stderr(Stderr),
+}
+
+impl Write for Out {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Out::Stdout(out) => out.write(buf),
+            Out::Stderr(out) => out.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Out::Stdout(out) => out.flush(),
+            Out::Stderr(out) => out.flush(),
+        }
+    }
+}
+
+/// 界面现在输出的位置。
+pub fn out() -> Out {
+    if STDERR.load(Ordering::Relaxed) {
+        Out::Stderr(stderr())
+    } else {
+        Out::Stdout(stdout())
+    }
+}
+
+/// 把界面画到标准错误，标准输出留给脚本读取的结果（`--print-result`）。要在 [`init`] 之前调用。
+pub fn draw_to_stderr() {
+    STDERR.store(true, Ordering::Relaxed);
+}
+
+/// 终端初始化选项。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// 是否使用备用屏幕。不使用时直接在主屏幕中绘制，退出后最后一帧留在滚动历史中。
+    pub alternate_screen: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            alternate_screen: true,
+        }
+    }
+}
+
+/// 初始化终端，同时检测终端支持的颜色数量和 Unicode 字符，供主题降级使用。
+pub fn init(options: Options) -> io::Result<(Tui, Capabilities)> {
+    let capabilities = Capabilities::detect();
+    if options.alternate_screen {
+        execute!(out(), EnterAlternateScreen)?;
+        ALTERNATE_SCREEN.store(true, Ordering::Relaxed);
+    } else {
+        // 先把屏幕上已有的内容滚动到历史中，避免被第一帧覆盖。
+        let (_, rows) = size()?;
+        let mut out = out();
+        out.write_all("\n".repeat(rows.into()).as_bytes())?;
+        out.flush()?;
+        MAIN_SCREEN.store(true, Ordering::Relaxed);
+    }
     enable_raw_mode()?;
-    Terminal::new(CrosstermBackend::new(stdout()))
+    let mut terminal = Terminal::new(CrosstermBackend::new(out()))?;
+    if !options.alternate_screen {
+        terminal.clear()?;
+    }
+    Ok((terminal, capabilities))
+}
+
+/// 绘制一帧。`synchronized` 为真时把整帧包在同步更新的开始和结束序列之间，
+/// 终端会一次性显示整帧，快速重绘（动画、连续递增）时不会看到画了一半的画面。
+pub fn draw<B: Backend + Write>(
+    terminal: &mut Terminal<B>,
+    synchronized: bool,
+    render: impl FnOnce(&mut Frame),
+) -> io::Result<()> {
+    if !synchronized {
+        return terminal.draw(render).map(drop);
+    }
+    queue!(terminal.backend_mut(), BeginSynchronizedUpdate)?;
+    let drawn = terminal.draw(render).map(drop);
+    // 绘制失败时同样结束同步更新，否则终端一直等待结束序列，不再刷新画面。
+    let ended = execute!(terminal.backend_mut(), EndSynchronizedUpdate);
+    drawn.and(ended)
+}
+
+/// 打开或关闭鼠标捕获。只在布局检查器打开时捕获，平时不影响在终端中选择文字。
+pub fn set_mouse_capture<B: Backend + Write>(
+    terminal: &mut Terminal<B>,
+    enabled: bool,
+) -> io::Result<()> {
+    MOUSE_CAPTURE.store(enabled, Ordering::Relaxed);
+    if enabled {
+        execute!(terminal.backend_mut(), EnableMouseCapture)
+    } else {
+        execute!(terminal.backend_mut(), DisableMouseCapture)
+    }
+}
+
+/// 只启用原始模式，不进入备用屏幕，供逐行输出模式使用。
+pub fn init_raw() -> io::Result<()> {
+    enable_raw_mode()
 }
 
 pub fn restore() -> io::Result<()> {
-    execute!(stdout(), LeaveAlternateScreen)?;
+    if MOUSE_CAPTURE.swap(false, Ordering::Relaxed) {
+        execute!(out(), DisableMouseCapture)?;
+    }
+    if ALTERNATE_SCREEN.swap(false, Ordering::Relaxed) {
+        execute!(out(), LeaveAlternateScreen)?;
+    } else if MAIN_SCREEN.swap(false, Ordering::Relaxed) {
+        // 在主屏幕中绘制时，把光标移到最后一帧下方，让 shell 提示符接着显示。
+        let (_, rows) = size()?;
+        execute!(
+            out(),
+            cursor::MoveTo(0, rows.saturating_sub(1)),
+            cursor::Show
+        )?;
+        out().write_all(b"\r\n")?;
+    }
     disable_raw_mode()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 第一次 `flush` 失败的输出。
+    #[derive(Default)]
+    struct FailingFlush {
+        written: Vec<u8>,
+        failed: bool,
+    }
+
+    impl Write for FailingFlush {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.written.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            if self.failed {
+                return Ok(());
+            }
+            self.failed = true;
+            Err(io::Error::other("terminal went away"))
+        }
+    }
+
+    #[test]
+    fn end_synchronized_update_after_failed_draw() {
+        let mut out = FailingFlush::default();
+        let mut terminal = Terminal::with_options(
+            CrosstermBackend::new(&mut out),
+            TerminalOptions {
+                viewport: Viewport::Fixed(Rect::new(0, 0, 4, 1)),
+            },
+        )
+        .unwrap();
+        let error = draw(&mut terminal, true, |_| {}).unwrap_err();
+        assert_eq!(error.to_string(), "terminal went away");
+        drop(terminal);
+        assert!(out.written.ends_with(b"\x1b[?2026l"));
+    }
+}
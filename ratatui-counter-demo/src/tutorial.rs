@@ -0,0 +1,219 @@
+//! 第一次运行时的教程：依次突出显示计数器界面的一部分，在旁边用文字说明该做什么，用户执行了
+//! 说明中的操作后进入下一步。
+//!
+//! 说明中的按键从当前的按键绑定中取，重新绑定过的按键也能显示正确。教程完成或者跳过后记录在
+//! 状态文件中，之后启动不再显示，`--tutorial` 可以重新开始。
+
+use ratatui::{
+    prelude::*,
+    widgets::{block::*, *},
+};
+
+use crate::{
+    keymap::{self, Action, Keymap},
+    text,
+    theme::Theme,
+};
+
+/// 教程突出显示的区域。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    /// 计数值所在的一行。
+    Value,
+    /// 下边框上的按键提示。
+    Hints,
+}
+
+impl Region {
+    /// 在计数器界面 `area` 中的位置。
+    pub fn area(self, area: Rect) -> Rect {
+        match self {
+            Self::Value => Rect::new(area.x + 1, area.y + 1, area.width.saturating_sub(2), 1),
+            Self::Hints => Rect::new(area.x, area.bottom() - 1, area.width, 1),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Step {
+    region: Region,
+    /// 进入下一步需要执行的操作。
+    action: Action,
+    /// 说明文字，`{keys}` 替换为 `action` 绑定的按键。
+    text: &'static str,
+}
+
+const STEPS: [Step; 4] = [
+    Step {
+        region: Region::Value,
+        action: Action::Increment,
+        text: "This is the counter. Press {keys} to increment it.",
+    },
+    Step {
+        region: Region::Value,
+        action: Action::Decrement,
+        text: "Press {keys} to decrement it again.",
+    },
+    Step {
+        region: Region::Hints,
+        action: Action::Help,
+        text: "The most used keys are listed here. Press {keys} to see all of them.",
+    },
+    Step {
+        region: Region::Value,
+        action: Action::History,
+        text: "Every change is recorded. Press {keys} to open the history.",
+    },
+];
+
+/// 说明框最宽的列数。
+const MAX_WIDTH: u16 = 44;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Tutorial {
+    step: usize,
+}
+
+impl Tutorial {
+    pub fn region(&self) -> Region {
+        STEPS[self.step].region
+    }
+
+    /// 用户执行了 `action`，是当前步骤要求的操作时进入下一步。返回教程是否已经完成。
+    pub fn performed(&mut self, action: Action) -> bool {
+        if STEPS[self.step].action == action {
+            self.step += 1;
+        }
+        self.step == STEPS.len()
+    }
+
+    fn text(&self, keymap: &Keymap) -> String {
+        let step = &STEPS[self.step];
+        let keys = keymap
+            .keys_for(step.action)
+            .map(|keys| format!("<{}>", keymap::hint_name(keys)))
+            .unwrap_or_else(|| format!("the {} key", step.action.description()));
+        step.text.replace("{keys}", &keys)
+    }
+
+    /// 在计数器界面 `area` 上叠加当前步骤：突出显示区域，在区域旁边显示说明框。
+    pub fn render(&self, area: Rect, buf: &mut Buffer, keymap: &Keymap, theme: &Theme) {
+        if area.width < 8 || area.height < 6 {
+            return;
+        }
+        let region = self.region().area(area);
+        buf.set_style(region, theme.key);
+        // 两侧的箭头指向突出显示的区域，不依赖颜色也能看出来。
+        buf.get_mut(region.x, region.y)
+            .set_symbol(theme.symbols.arrow_right);
+        buf.get_mut(region.right() - 1, region.y)
+            .set_symbol(theme.symbols.arrow_left);
+
+        let text = self.text(keymap);
+        let width = (text::width(&text) as u16 + 4)
+            .min(MAX_WIDTH)
+            .min(area.width - 4);
+        let lines = wrap(&text, usize::from(width - 4));
+        let height = (lines.len() as u16 + 2).min(area.height - 3);
+        let x = area.x + (area.width - width) / 2;
+        // 说明框放在区域的另一侧，不盖住区域本身。
+        let y = match self.region() {
+            Region::Value => region.bottom() + 1,
+            Region::Hints => region.y - height - 1,
+        };
+        let callout = Rect::new(x, y, width, height).intersection(area);
+        Clear.render(callout, buf);
+        Paragraph::new(lines.into_iter().map(Line::from).collect::<Vec<_>>())
+            .block(
+                Block::bordered()
+                    .title(
+                        format!(" Tutorial {}/{} ", self.step + 1, STEPS.len())
+                            .set_style(theme.title),
+                    )
+                    .title(
+                        Title::from(Line::from(vec![
+                            " Skip ".into(),
+                            "<Esc> ".set_style(theme.key),
+                        ]))
+                        .alignment(Alignment::Right)
+                        .position(Position::Bottom),
+                    )
+                    .border_set(theme.border_set)
+                    .border_style(theme.border)
+                    .padding(Padding::horizontal(1)),
+            )
+            .style(theme.base)
+            .render(callout, buf);
+    }
+}
+
+/// 按单词折行，每行不超过 `width` 列，放不下的单词单独占一行。
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if text::width(line) + 1 + text::width(word) <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn rows(tutorial: &Tutorial, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| {
+            tutorial.render(area, buf, &Keymap::default(), &Theme::plain())
+        })
+    }
+
+    #[test]
+    fn advance_on_the_right_action() {
+        let mut tutorial = Tutorial::default();
+        assert!(!tutorial.performed(Action::Decrement));
+        assert_eq!(tutorial.step, 0);
+        assert!(!tutorial.performed(Action::Increment));
+        assert!(!tutorial.performed(Action::Decrement));
+        assert_eq!(tutorial.region(), Region::Hints);
+        assert!(!tutorial.performed(Action::Help));
+        assert!(tutorial.performed(Action::History));
+    }
+
+    #[test]
+    fn callout_next_to_the_region() {
+        let mut tutorial = Tutorial::default();
+        assert_eq!(
+            rows(&tutorial, 40, 10)[1..7],
+            [
+                " ▶                                    ◀ ",
+                "                                        ",
+                "  ┏ Tutorial 1/4 ━━━━━━━━━━━━━━━━━━━━┓  ",
+                "  ┃ This is the counter. Press       ┃  ",
+                "  ┃ <Right> to increment it.         ┃  ",
+                "  ┗━━━━━━━━━━━━━━━━━━━━━━ Skip <Esc> ┛  ",
+            ]
+        );
+        // 按键提示在下边框上，说明框放在它上面。
+        tutorial.performed(Action::Increment);
+        tutorial.performed(Action::Decrement);
+        assert_eq!(
+            rows(&tutorial, 40, 10)[3..],
+            [
+                "  ┏ Tutorial 3/4 ━━━━━━━━━━━━━━━━━━━━┓  ",
+                "  ┃ The most used keys are listed    ┃  ",
+                "  ┃ here. Press <?> to see all of    ┃  ",
+                "  ┃ them.                            ┃  ",
+                "  ┗━━━━━━━━━━━━━━━━━━━━━━ Skip <Esc> ┛  ",
+                "                                        ",
+                "▶                                      ◀",
+            ]
+        );
+    }
+}
@@ -0,0 +1,426 @@
+//! 加密保存的状态：用 `--encrypt` 设置口令后，状态文件和会话都加密保存，启动时要先输入口令解锁。
+//! 只有启用 `encrypt` 功能时才能加密和解锁，否则遇到加密的文件时报告错误。
+//!
+//! 口令和随机的盐经过 Argon2id 派生出 256 位的密钥，内容用 ChaCha20-Poly1305 加密，每次保存都换一个
+//! 随机的 nonce。盐在设置口令时生成，之后保持不变，所以解锁时派生一次密钥就能一直用来保存。加密的
+//! 文件仍然是 JSON，二进制的部分用十六进制表示：
+//!
+//! ```json
+//! {"encrypted": "argon2id-chacha20poly1305", "salt": "…", "nonce": "…", "ciphertext": "…"}
+//! ```
+//!
+//! 状态文件的备份是加密文件的副本，用同一个口令解锁。崩溃恢复的自动保存文件在启动时就要读取，那时
+//! 还没有口令，所以加密时不写自动保存文件。
+
+use std::fmt;
+
+use color_eyre::{eyre::eyre, Result};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::*,
+    widgets::{block::*, *},
+};
+use ratatui_common::text_input::Input;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{state::State, theme::Theme};
+
+/// 加密文件中 `encrypted` 的值，以后换用别的算法时可以区分。
+const SCHEME: &str = "argon2id-chacha20poly1305";
+
+const SALT_LEN: usize = 16;
+
+/// 加密后写入文件的内容。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Envelope {
+    encrypted: String,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl Envelope {
+    /// 文件的内容是不是加密的。
+    pub fn detect(value: &serde_json::Value) -> bool {
+        value.get("encrypted").is_some()
+    }
+}
+
+/// 从口令派生的密钥，连同派生时用的盐，保存时写进文件。
+#[derive(Clone, PartialEq, Eq)]
+pub struct Key {
+    salt: [u8; SALT_LEN],
+    key: [u8; 32],
+}
+
+/// 不在日志和调试控制台中显示密钥。
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Key(..)")
+    }
+}
+
+impl Key {
+    /// 设置新口令：生成新的盐。
+    pub fn generate(passphrase: &str) -> Result<Self> {
+        let mut salt = [0; SALT_LEN];
+        cipher::random(&mut salt)?;
+        Self::derive(passphrase, salt)
+    }
+
+    fn derive(passphrase: &str, salt: [u8; SALT_LEN]) -> Result<Self> {
+        Ok(Self {
+            salt,
+            key: cipher::derive(passphrase, &salt)?,
+        })
+    }
+
+    /// 用口令解锁 `envelope`，返回密钥和解密后的内容。
+    pub fn unlock<T: DeserializeOwned>(passphrase: &str, envelope: &Envelope) -> Result<(Self, T)> {
+        if envelope.encrypted != SCHEME {
+            return Err(eyre!("unknown encryption {:?}", envelope.encrypted));
+        }
+        let salt = decode(&envelope.salt)?
+            .try_into()
+            .map_err(|_| eyre!("invalid salt"))?;
+        let key = Self::derive(passphrase, salt)?;
+        let value = key.open(envelope)?;
+        Ok((key, value))
+    }
+
+    pub fn seal<T: Serialize>(&self, value: &T) -> Result<Envelope> {
+        let mut nonce = [0; cipher::NONCE_LEN];
+        cipher::random(&mut nonce)?;
+        let plaintext = serde_json::to_vec(value)?;
+        Ok(Envelope {
+            encrypted: SCHEME.into(),
+            salt: encode(&self.salt),
+            nonce: encode(&nonce),
+            ciphertext: encode(&cipher::seal(&self.key, &nonce, &plaintext)?),
+        })
+    }
+
+    /// 解密 `envelope`。用别的口令加密的（盐不同）和被改动过的内容都会失败。
+    pub fn open<T: DeserializeOwned>(&self, envelope: &Envelope) -> Result<T> {
+        if decode(&envelope.salt)? != self.salt {
+            return Err(eyre!("encrypted with a different passphrase"));
+        }
+        let nonce = decode(&envelope.nonce)?;
+        let plaintext = cipher::open(&self.key, &nonce, &decode(&envelope.ciphertext)?)?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+}
+
+fn encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn decode(text: &str) -> Result<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return Err(eyre!("invalid hex {text:?}"));
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|index| {
+            text.get(index..index + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| eyre!("invalid hex {text:?}"))
+        })
+        .collect()
+}
+
+#[cfg(feature = "encrypt")]
+mod cipher {
+    use argon2::Argon2;
+    use chacha20poly1305::{
+        aead::{rand_core::RngCore, Aead, KeyInit, OsRng},
+        ChaCha20Poly1305, Nonce,
+    };
+    use color_eyre::{eyre::eyre, Result};
+
+    pub const NONCE_LEN: usize = 12;
+
+    pub fn random(bytes: &mut [u8]) -> Result<()> {
+        OsRng
+            .try_fill_bytes(bytes)
+            .map_err(|error| eyre!("reading random bytes failed: {error}"))
+    }
+
+    pub fn derive(passphrase: &str, salt: &[u8]) -> Result<[u8; 32]> {
+        let mut key = [0; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|error| eyre!("deriving the key failed: {error}"))?;
+        Ok(key)
+    }
+
+    pub fn seal(key: &[u8; 32], nonce: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+        ChaCha20Poly1305::new(key.into())
+            .encrypt(Nonce::from_slice(nonce), plaintext)
+            .map_err(|_| eyre!("encrypting failed"))
+    }
+
+    pub fn open(key: &[u8; 32], nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+        if nonce.len() != NONCE_LEN {
+            return Err(eyre!("invalid nonce"));
+        }
+        ChaCha20Poly1305::new(key.into())
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| eyre!("wrong passphrase or damaged file"))
+    }
+}
+
+/// 没有启用 `encrypt` 功能：所有操作都报告错误。
+#[cfg(not(feature = "encrypt"))]
+mod cipher {
+    use color_eyre::{eyre::eyre, Result};
+
+    pub const NONCE_LEN: usize = 12;
+
+    fn unsupported() -> color_eyre::Report {
+        eyre!("encryption needs the `encrypt` feature")
+    }
+
+    pub fn random(_bytes: &mut [u8]) -> Result<()> {
+        Err(unsupported())
+    }
+
+    pub fn derive(_passphrase: &str, _salt: &[u8]) -> Result<[u8; 32]> {
+        Err(unsupported())
+    }
+
+    pub fn seal(_key: &[u8; 32], _nonce: &[u8], _plaintext: &[u8]) -> Result<Vec<u8>> {
+        Err(unsupported())
+    }
+
+    pub fn open(_key: &[u8; 32], _nonce: &[u8], _ciphertext: &[u8]) -> Result<Vec<u8>> {
+        Err(unsupported())
+    }
+}
+
+/// 启动时的口令界面：解锁加密的状态文件，或者为 `--encrypt` 设置新口令（输入两遍）。
+#[derive(Debug)]
+pub struct Prompt {
+    mode: Mode,
+    input: Input,
+    error: Option<String>,
+}
+
+#[derive(Debug)]
+enum Mode {
+    Unlock(Envelope),
+    /// 设置新口令，`first` 是第一遍输入的口令。
+    Create {
+        first: Option<String>,
+    },
+}
+
+/// 处理按键后口令界面要求应用程序执行的操作。
+#[derive(Debug, PartialEq, Eq)]
+pub enum PromptAction {
+    None,
+    /// 解锁了状态文件。
+    Unlocked(Key, State),
+    /// 设置了新口令，应用程序应当马上加密保存。
+    Created(Key),
+    /// 不设置口令，继续不加密地使用。
+    Cancel,
+    /// 没有口令就不能继续，退出。
+    Quit,
+}
+
+impl Prompt {
+    pub fn unlock(envelope: Envelope) -> Self {
+        Self {
+            mode: Mode::Unlock(envelope),
+            input: Input::default(),
+            error: None,
+        }
+    }
+
+    pub fn create() -> Self {
+        Self {
+            mode: Mode::Create { first: None },
+            input: Input::default(),
+            error: None,
+        }
+    }
+
+    /// 正在解锁：真正的状态还没有读出来，这时不能保存。
+    pub fn is_unlocking(&self) -> bool {
+        matches!(self.mode, Mode::Unlock(_))
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> PromptAction {
+        match key_event.code {
+            KeyCode::Esc => match self.mode {
+                Mode::Unlock(_) => PromptAction::Quit,
+                Mode::Create { .. } => PromptAction::Cancel,
+            },
+            KeyCode::Enter if !self.input.value().is_empty() => {
+                let passphrase = std::mem::take(&mut self.input).value().to_string();
+                self.submit(passphrase)
+            }
+            _ => {
+                self.input.handle_key_event(key_event);
+                PromptAction::None
+            }
+        }
+    }
+
+    fn submit(&mut self, passphrase: String) -> PromptAction {
+        let result = match &mut self.mode {
+            Mode::Unlock(envelope) => Key::unlock(&passphrase, envelope)
+                .map(|(key, state)| PromptAction::Unlocked(key, state)),
+            Mode::Create { first: None } => {
+                self.mode = Mode::Create {
+                    first: Some(passphrase),
+                };
+                self.error = None;
+                return PromptAction::None;
+            }
+            Mode::Create { first: Some(first) } if *first != passphrase => {
+                self.mode = Mode::Create { first: None };
+                Err(eyre!("The passphrases did not match"))
+            }
+            Mode::Create { .. } => Key::generate(&passphrase).map(PromptAction::Created),
+        };
+        result.unwrap_or_else(|error| {
+            self.error = Some(format!("{error:#}"));
+            PromptAction::None
+        })
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let (title, question, escape) = match &self.mode {
+            Mode::Unlock(_) => (" Unlock ", "Passphrase for the state file:", " Quit "),
+            Mode::Create { first: None } => (" Encrypt ", "Choose a passphrase:", " Skip "),
+            Mode::Create { first: Some(_) } => (" Encrypt ", "Repeat the passphrase:", " Skip "),
+        };
+        let width = area.width.min(50);
+        let height = area.height.min(5);
+        let popup = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+        let block = Block::bordered()
+            .title(Title::from(title.set_style(theme.title)).alignment(Alignment::Center))
+            .title(
+                Title::from(Line::from(vec![
+                    " OK ".into(),
+                    "<Enter>".set_style(theme.key),
+                    escape.into(),
+                    "<Esc> ".set_style(theme.key),
+                ]))
+                .alignment(Alignment::Center)
+                .position(Position::Bottom),
+            )
+            .border_set(theme.border_set)
+            .border_style(theme.border)
+            .style(theme.base);
+        let lines = vec![
+            Line::from(question),
+            self.input.masked_line("> ", theme.key, '*'),
+            Line::from(
+                self.error
+                    .clone()
+                    .unwrap_or_default()
+                    .set_style(theme.warning),
+            ),
+        ];
+        Clear.render(popup, buf);
+        Paragraph::new(lines).block(block).render(popup, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing::buffer_rows;
+
+    use super::*;
+
+    fn type_passphrase(prompt: &mut Prompt, passphrase: &str) -> PromptAction {
+        for c in passphrase.chars() {
+            prompt.handle_key_event(KeyCode::Char(c).into());
+        }
+        prompt.handle_key_event(KeyCode::Enter.into())
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!(encode(&[0, 171, 255]), "00abff");
+        assert_eq!(decode("00abff").unwrap(), [0, 171, 255]);
+        assert!(decode("abc").is_err());
+        assert!(decode("zz").is_err());
+    }
+
+    #[test]
+    fn render_prompt() {
+        let mut prompt = Prompt::create();
+        type_passphrase(&mut prompt, "secret");
+        type_passphrase(&mut prompt, "other");
+        prompt.handle_key_event(KeyCode::Char('s').into());
+        let mut buf = Buffer::empty(Rect::new(0, 0, 40, 5));
+        prompt.render(buf.area, &mut buf, &Theme::plain());
+        assert_eq!(
+            buffer_rows(&buf),
+            [
+                "┏━━━━━━━━━━━━━━ Encrypt ━━━━━━━━━━━━━━━┓",
+                "┃Choose a passphrase:                  ┃",
+                "┃> *                                   ┃",
+                "┃The passphrases did not match         ┃",
+                "┗━━━━━━━ OK <Enter> Skip <Esc> ━━━━━━━━┛",
+            ]
+        );
+        assert_eq!(
+            prompt.handle_key_event(KeyCode::Esc.into()),
+            PromptAction::Cancel
+        );
+    }
+
+    #[cfg(feature = "encrypt")]
+    #[test]
+    fn seal_and_unlock() {
+        let mut prompt = Prompt::create();
+        type_passphrase(&mut prompt, "secret");
+        let PromptAction::Created(key) = type_passphrase(&mut prompt, "secret") else {
+            panic!("no key");
+        };
+        let state = State {
+            counter: 2,
+            ..State::default()
+        };
+        let envelope = key.seal(&state).unwrap();
+        assert!(Envelope::detect(&serde_json::to_value(&envelope).unwrap()));
+        assert!(!envelope.ciphertext.contains(&encode(b"counter")));
+
+        let mut prompt = Prompt::unlock(envelope.clone());
+        assert_eq!(type_passphrase(&mut prompt, "wrong"), PromptAction::None);
+        assert_eq!(
+            prompt.error.as_deref(),
+            Some("wrong passphrase or damaged file")
+        );
+        assert_eq!(
+            type_passphrase(&mut prompt, "secret"),
+            PromptAction::Unlocked(key.clone(), state)
+        );
+
+        let other = Key::generate("secret").unwrap();
+        assert!(other.open::<State>(&envelope).is_err());
+        let mut damaged = envelope;
+        let byte = if damaged.ciphertext.starts_with("00") {
+            "01"
+        } else {
+            "00"
+        };
+        damaged.ciphertext.replace_range(0..2, byte);
+        assert!(key.open::<State>(&damaged).is_err());
+        assert_eq!(
+            Prompt::unlock(key.seal(&1).unwrap()).handle_key_event(KeyCode::Esc.into()),
+            PromptAction::Quit
+        );
+    }
+}
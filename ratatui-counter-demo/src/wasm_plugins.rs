@@ -0,0 +1,315 @@
+//! WebAssembly 插件：订阅应用程序事件，并在状态栏中显示一段文本。
+//!
+//! 插件是插件目录中的 `.wasm`（或文本格式 `.wat`）模块，运行在沙箱中：不提供 WASI，
+//! 插件不能访问文件、网络或时钟，只能通过下面的宿主函数读取计数器的状态。
+//! 每次调用都有燃料（指令数）限制，内存也有上限；插件出错或超出限制时会被停用，而不会影响应用程序。
+//!
+//! 插件导入（模块名 `host`）：
+//!
+//! ```text
+//! counter() -> i32          当前的计数值
+//! max() -> i32              计数器的最大值
+//! ```
+//!
+//! 插件导出：
+//!
+//! ```text
+//! memory                    线性内存
+//! subscriptions() -> i32    订阅的事件，`EVENT_*` 的按位或
+//! on_event(kind: i32, value: i32)
+//! status() -> i64           状态栏文本在内存中的位置：高 32 位是地址，低 32 位是长度（UTF-8）
+//! ```
+
+use std::{fs, io, path::Path};
+
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Result,
+};
+use wasmtime::{
+    Config, Engine, Instance, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder,
+    TypedFunc,
+};
+
+/// 启动时发送一次，`value` 为 0。
+pub const EVENT_START: i32 = 1;
+/// 计数值变化后发送，`value` 是新的计数值。
+pub const EVENT_COUNTER: i32 = 2;
+/// 按下字符键时发送，`value` 是字符的 Unicode 码位。
+pub const EVENT_KEY: i32 = 4;
+
+/// 每次调用插件函数可以消耗的燃料。
+const FUEL_PER_CALL: u64 = 1_000_000;
+
+/// 插件线性内存的上限。
+const MEMORY_LIMIT: usize = 16 << 20;
+
+/// 状态栏文本的最大长度（字节），更长的文本会被截断。
+const STATUS_LIMIT: usize = 64;
+
+/// 插件可以看到的状态。
+struct HostState {
+    counter: u8,
+    max: u8,
+    limits: StoreLimits,
+}
+
+struct WasmPlugin {
+    name: String,
+    store: Store<HostState>,
+    memory: Memory,
+    subscriptions: i32,
+    on_event: TypedFunc<(i32, i32), ()>,
+    status: TypedFunc<(), i64>,
+    /// 上一次读取到的状态栏文本。
+    segment: String,
+    /// 插件出错时的原因，出错后插件不再被调用。
+    error: Option<String>,
+}
+
+/// 所有已加载的 WebAssembly 插件。
+#[derive(Default)]
+pub struct WasmPlugins {
+    plugins: Vec<WasmPlugin>,
+}
+
+impl std::fmt::Debug for WasmPlugins {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<_> = self.plugins.iter().map(|plugin| &plugin.name).collect();
+        f.debug_struct("WasmPlugins")
+            .field("plugins", &names)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WasmPlugins {
+    /// 加载 `dir` 中所有的 `.wasm` 和 `.wat` 模块，按文件名排序。目录不存在时没有插件。
+    pub fn discover(dir: &Path) -> Result<Self> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => {
+                return Err(error).wrap_err_with(|| format!("reading {} failed", dir.display()))
+            }
+        };
+        let mut paths = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "wasm" || extension == "wat")
+            {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let engine = engine()?;
+        let mut plugins = Self::default();
+        for path in paths {
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let bytes = fs::read(&path)?;
+            plugins.add(&engine, name, &bytes).wrap_err_with(|| {
+                format!("loading WebAssembly plugin {} failed", path.display())
+            })?;
+        }
+        Ok(plugins)
+    }
+
+    fn add(&mut self, engine: &Engine, name: String, bytes: &[u8]) -> Result<()> {
+        let module = Module::new(engine, bytes).map_err(wasm_error)?;
+        let mut store = Store::new(
+            engine,
+            HostState {
+                counter: 0,
+                max: 0,
+                limits: StoreLimitsBuilder::new().memory_size(MEMORY_LIMIT).build(),
+            },
+        );
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(FUEL_PER_CALL).map_err(wasm_error)?;
+
+        // 宿主 API 只有只读的函数，插件不能修改应用程序的状态。
+        let mut linker = Linker::new(engine);
+        linker
+            .func_wrap(
+                "host",
+                "counter",
+                |caller: wasmtime::Caller<'_, HostState>| i32::from(caller.data().counter),
+            )
+            .map_err(wasm_error)?;
+        linker
+            .func_wrap("host", "max", |caller: wasmtime::Caller<'_, HostState>| {
+                i32::from(caller.data().max)
+            })
+            .map_err(wasm_error)?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .map_err(wasm_error)?;
+
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| eyre!("plugin does not export its memory"))?;
+        let subscriptions = typed::<(), i32>(&instance, &mut store, "subscriptions")?
+            .call(&mut store, ())
+            .map_err(wasm_error)?;
+        let plugin = WasmPlugin {
+            name,
+            memory,
+            subscriptions,
+            on_event: typed(&instance, &mut store, "on_event")?,
+            status: typed(&instance, &mut store, "status")?,
+            store,
+            segment: String::new(),
+            error: None,
+        };
+        self.plugins.push(plugin);
+        Ok(())
+    }
+
+    /// 把事件发送给订阅了它的插件，然后刷新状态栏文本。
+    pub fn notify(&mut self, kind: i32, value: i32, counter: u8, max: u8) {
+        for plugin in &mut self.plugins {
+            if plugin.error.is_some() || plugin.subscriptions & kind == 0 {
+                continue;
+            }
+            if let Err(error) = plugin.notify(kind, value, counter, max) {
+                plugin.error = Some(format!("{error:#}"));
+            }
+        }
+    }
+
+    /// 各个插件的状态栏文本，停用的插件显示为 `<名称>: disabled`。
+    pub fn segments(&self) -> impl Iterator<Item = String> + '_ {
+        self.plugins.iter().filter_map(|plugin| {
+            if plugin.error.is_some() {
+                Some(format!("{}: disabled", plugin.name))
+            } else {
+                (!plugin.segment.is_empty()).then(|| plugin.segment.clone())
+            }
+        })
+    }
+}
+
+impl WasmPlugin {
+    fn notify(&mut self, kind: i32, value: i32, counter: u8, max: u8) -> Result<()> {
+        let state = self.store.data_mut();
+        state.counter = counter;
+        state.max = max;
+        self.store.set_fuel(FUEL_PER_CALL).map_err(wasm_error)?;
+        self.on_event
+            .call(&mut self.store, (kind, value))
+            .map_err(wasm_error)?;
+
+        self.store.set_fuel(FUEL_PER_CALL).map_err(wasm_error)?;
+        let location = self.status.call(&mut self.store, ()).map_err(wasm_error)? as u64;
+        let (address, len) = ((location >> 32) as usize, location as u32 as usize);
+        let mut bytes = vec![0; len.min(STATUS_LIMIT)];
+        self.memory
+            .read(&self.store, address, &mut bytes)
+            .map_err(|error| eyre!("status text is outside the plugin memory: {error}"))?;
+        let text = String::from_utf8_lossy(&bytes);
+        // 控制字符会破坏终端的显示。
+        self.segment = text.chars().filter(|c| !c.is_control()).collect();
+        Ok(())
+    }
+}
+
+fn engine() -> Result<Engine> {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    Engine::new(&config).map_err(wasm_error)
+}
+
+fn typed<Params, Results>(
+    instance: &Instance,
+    store: &mut Store<HostState>,
+    name: &str,
+) -> Result<TypedFunc<Params, Results>>
+where
+    Params: wasmtime::WasmParams,
+    Results: wasmtime::WasmResults,
+{
+    match instance.get_typed_func(store, name) {
+        Ok(function) => Ok(function),
+        Err(error) => bail!("plugin export {name} is missing or has the wrong type: {error}"),
+    }
+}
+
+/// wasmtime 的错误类型来自 anyhow，转换为文本后交给 color_eyre。
+fn wasm_error(error: wasmtime::Error) -> color_eyre::Report {
+    eyre!("{error:#}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 订阅计数值变化，状态栏显示 `n/max`（只支持一位数）。
+    const PLUGIN: &str = r#"
+        (module
+          (import "host" "counter" (func $counter (result i32)))
+          (import "host" "max" (func $max (result i32)))
+          (memory (export "memory") 1)
+          (data (i32.const 0) "0/0")
+          (func (export "subscriptions") (result i32) (i32.const 2))
+          (func (export "on_event") (param i32 i32)
+            (i32.store8 (i32.const 0) (i32.add (i32.const 48) (call $counter)))
+            (i32.store8 (i32.const 2) (i32.add (i32.const 48) (call $max))))
+          (func (export "status") (result i64) (i64.const 3)))
+    "#;
+
+    /// 处理事件时陷入死循环。
+    const SPINNING: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (func (export "subscriptions") (result i32) (i32.const 7))
+          (func (export "on_event") (param i32 i32) (loop (br 0)))
+          (func (export "status") (result i64) (i64.const 0)))
+    "#;
+
+    #[test]
+    fn status_segments() {
+        let engine = engine().unwrap();
+        let mut plugins = WasmPlugins::default();
+        plugins
+            .add(&engine, "progress".into(), PLUGIN.as_bytes())
+            .unwrap();
+        assert_eq!(plugins.segments().count(), 0);
+
+        // 没有订阅的事件不会发送。
+        plugins.notify(EVENT_KEY, 'x' as i32, 1, 2);
+        assert_eq!(plugins.segments().count(), 0);
+
+        plugins.notify(EVENT_COUNTER, 1, 1, 2);
+        assert_eq!(plugins.segments().collect::<Vec<_>>(), ["1/2"]);
+    }
+
+    #[test]
+    fn disable_plugin_that_runs_out_of_fuel() {
+        let engine = engine().unwrap();
+        let mut plugins = WasmPlugins::default();
+        plugins
+            .add(&engine, "spin".into(), SPINNING.as_bytes())
+            .unwrap();
+        plugins.notify(EVENT_START, 0, 0, 2);
+        assert_eq!(plugins.segments().collect::<Vec<_>>(), ["spin: disabled"]);
+    }
+
+    #[test]
+    fn reject_missing_exports() {
+        let engine = engine().unwrap();
+        let mut plugins = WasmPlugins::default();
+        let error = plugins
+            .add(
+                &engine,
+                "empty".into(),
+                b"(module (memory (export \"memory\") 1))",
+            )
+            .unwrap_err();
+        assert!(error.to_string().contains("subscriptions"));
+    }
+}
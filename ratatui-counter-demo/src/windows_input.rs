@@ -0,0 +1,223 @@
+//! Windows 控制台（ConPTY）的输入修正层。
+//!
+//! 这一层包装任意的 [`EventSource`]，在事件到达处理函数之前统一修正 ConPTY 的怪癖，
+//! 而不是在每个处理函数中分别处理：
+//!
+//! - 忽略重复的按键释放事件：没有对应按下事件的释放事件会被丢弃；
+//! - 支持 Alt 组合键：ConPTY 有时把 Alt+键 拆成 `Esc` 和该键两个事件，这里把它们合并回带 `ALT` 修饰的按键；
+//! - 合并控制台大小变化通知：拖动窗口时会连续产生大量 `Resize` 事件，只保留最后一个。
+
+use std::{
+    collections::{HashSet, VecDeque},
+    io,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{Event as TerminalEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+use crate::event::{Event, EventSource};
+
+/// `Esc` 之后在这段时间内到达的字符被认为是同一个 Alt 组合键。
+const ALT_SEQUENCE_GAP: Duration = Duration::from_millis(10);
+
+#[derive(Debug)]
+pub struct WindowsInput<S> {
+    inner: S,
+    /// 已经按下、还没有释放的按键。
+    pressed: HashSet<KeyCode>,
+    /// 为了向前查看而提前读取、还没有处理的原始事件。
+    raw: VecDeque<Event>,
+    /// `poll` 时已经处理好、等待 `read` 返回的事件。
+    ready: Option<Event>,
+}
+
+impl<S: EventSource<Event>> WindowsInput<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            pressed: HashSet::new(),
+            raw: VecDeque::new(),
+            ready: None,
+        }
+    }
+
+    fn next_raw(&mut self) -> io::Result<Event> {
+        match self.raw.pop_front() {
+            Some(event) => Ok(event),
+            None => self.inner.read(),
+        }
+    }
+
+    /// 在 `timeout` 内取得下一个原始事件，用于向前查看。
+    fn peek_raw(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+        if self.raw.is_empty() {
+            if !self.inner.poll(timeout)? {
+                return Ok(None);
+            }
+            let event = self.inner.read()?;
+            self.raw.push_back(event);
+        }
+        Ok(self.raw.front().cloned())
+    }
+
+    /// 修正一个原始事件，需要丢弃时返回 `None`。
+    fn normalize(&mut self, event: Event) -> io::Result<Option<Event>> {
+        let Event::Terminal(terminal_event) = &event else {
+            return Ok(Some(event));
+        };
+        match *terminal_event {
+            TerminalEvent::Key(key) if key.kind == KeyEventKind::Release => {
+                Ok(self.pressed.remove(&key.code).then_some(event))
+            }
+            TerminalEvent::Key(key) if key.code == KeyCode::Esc && key.modifiers.is_empty() => {
+                match self.peek_raw(ALT_SEQUENCE_GAP)? {
+                    Some(Event::Terminal(TerminalEvent::Key(
+                        next @ KeyEvent {
+                            code: KeyCode::Char(_),
+                            ..
+                        },
+                    ))) if next.kind != KeyEventKind::Release => {
+                        self.raw.pop_front();
+                        self.pressed.insert(next.code);
+                        Ok(Some(
+                            TerminalEvent::Key(KeyEvent {
+                                modifiers: next.modifiers | KeyModifiers::ALT,
+                                ..next
+                            })
+                            .into(),
+                        ))
+                    }
+                    _ => {
+                        self.pressed.insert(key.code);
+                        Ok(Some(event))
+                    }
+                }
+            }
+            TerminalEvent::Key(key) => {
+                self.pressed.insert(key.code);
+                Ok(Some(event))
+            }
+            TerminalEvent::Resize(mut width, mut height) => {
+                while let Some(Event::Terminal(TerminalEvent::Resize(w, h))) =
+                    self.peek_raw(Duration::ZERO)?
+                {
+                    self.raw.pop_front();
+                    (width, height) = (w, h);
+                }
+                Ok(Some(TerminalEvent::Resize(width, height).into()))
+            }
+            _ => Ok(Some(event)),
+        }
+    }
+}
+
+impl<S: EventSource<Event>> EventSource<Event> for WindowsInput<S> {
+    fn read(&mut self) -> io::Result<Event> {
+        if let Some(event) = self.ready.take() {
+            return Ok(event);
+        }
+        loop {
+            let event = self.next_raw()?;
+            if let Some(event) = self.normalize(event)? {
+                return Ok(event);
+            }
+        }
+    }
+
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        if self.ready.is_some() {
+            return Ok(true);
+        }
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if self.raw.is_empty() && !self.inner.poll(remaining)? {
+                return Ok(false);
+            }
+            let event = self.next_raw()?;
+            if let Some(event) = self.normalize(event)? {
+                self.ready = Some(event);
+                return Ok(true);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::recording::{Player, RecordedEvent};
+
+    use super::*;
+
+    fn input(events: Vec<TerminalEvent>) -> WindowsInput<Player<Event>> {
+        WindowsInput::new(Player::new(events.into_iter().map(|event| RecordedEvent {
+            at_ms: 0,
+            event: event.into(),
+        })))
+    }
+
+    fn key(code: KeyCode, kind: KeyEventKind) -> TerminalEvent {
+        TerminalEvent::Key(KeyEvent::new_with_kind(code, KeyModifiers::NONE, kind))
+    }
+
+    #[test]
+    fn drop_duplicate_releases() {
+        let right = KeyCode::Right;
+        let mut input = input(vec![
+            key(right, KeyEventKind::Press),
+            key(right, KeyEventKind::Release),
+            key(right, KeyEventKind::Release),
+            key(KeyCode::Left, KeyEventKind::Press),
+        ]);
+        assert_eq!(
+            input.read().unwrap(),
+            key(right, KeyEventKind::Press).into()
+        );
+        assert_eq!(
+            input.read().unwrap(),
+            key(right, KeyEventKind::Release).into()
+        );
+        assert_eq!(
+            input.read().unwrap(),
+            key(KeyCode::Left, KeyEventKind::Press).into()
+        );
+    }
+
+    #[test]
+    fn merge_alt_sequences() {
+        let mut input = input(vec![
+            key(KeyCode::Esc, KeyEventKind::Press),
+            key(KeyCode::Char('x'), KeyEventKind::Press),
+            key(KeyCode::Esc, KeyEventKind::Press),
+            key(KeyCode::Left, KeyEventKind::Press),
+        ]);
+        assert_eq!(
+            input.read().unwrap(),
+            TerminalEvent::Key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT)).into()
+        );
+        assert_eq!(
+            input.read().unwrap(),
+            key(KeyCode::Esc, KeyEventKind::Press).into()
+        );
+        assert_eq!(
+            input.read().unwrap(),
+            key(KeyCode::Left, KeyEventKind::Press).into()
+        );
+    }
+
+    #[test]
+    fn coalesce_resizes() {
+        let mut input = input(vec![
+            TerminalEvent::Resize(80, 24),
+            TerminalEvent::Resize(100, 30),
+            TerminalEvent::Resize(120, 40),
+            key(KeyCode::Left, KeyEventKind::Press),
+        ]);
+        assert!(input.poll(Duration::ZERO).unwrap());
+        assert_eq!(input.read().unwrap(), TerminalEvent::Resize(120, 40).into());
+        assert_eq!(
+            input.read().unwrap(),
+            key(KeyCode::Left, KeyEventKind::Press).into()
+        );
+    }
+}
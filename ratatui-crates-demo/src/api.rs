@@ -0,0 +1,146 @@
+//! 在后台线程中调用 crates.io 的 API。请求按顺序处理，结果通过通道送回界面。
+
+use std::{
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use serde::Deserialize;
+
+const API: &str = "https://crates.io/api/v1";
+
+/// crates.io 要求每个请求都带有能识别程序的 `User-Agent`。
+const USER_AGENT: &str = concat!("ratatui-crates-demo/", env!("CARGO_PKG_VERSION"));
+
+/// 每次搜索返回的结果数。
+const PER_PAGE: &str = "50";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+    Search(String),
+    Readme { name: String, version: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Crate {
+    pub name: String,
+    pub max_version: String,
+    /// 只有预发布版本的 crate 没有稳定版本。
+    pub max_stable_version: Option<String>,
+    pub description: Option<String>,
+    pub downloads: u64,
+    pub recent_downloads: Option<u64>,
+}
+
+impl Crate {
+    /// 默认使用的版本，和 `cargo add` 的选择一致：优先稳定版本。
+    pub fn version(&self) -> &str {
+        self.max_stable_version
+            .as_deref()
+            .unwrap_or(&self.max_version)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Update {
+    Results {
+        query: String,
+        crates: Result<Vec<Crate>, String>,
+    },
+    /// README 是 crates.io 渲染好的 HTML。
+    Readme {
+        name: String,
+        html: Result<String, String>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct Search {
+    crates: Vec<Crate>,
+}
+
+/// 解析搜索接口返回的 JSON。
+pub fn parse_search(json: &str) -> Result<Vec<Crate>, serde_json::Error> {
+    serde_json::from_str::<Search>(json).map(|search| search.crates)
+}
+
+/// 启动后台线程，返回发送请求的一端。界面退出（接收端被丢弃）后线程结束。
+pub fn spawn(updates: Sender<Update>) -> Sender<Request> {
+    let (requests, receiver): (Sender<Request>, Receiver<Request>) = mpsc::channel();
+    thread::spawn(move || {
+        for request in receiver {
+            let update = match request {
+                Request::Search(query) => Update::Results {
+                    crates: search(&query).map_err(|error| error.to_string()),
+                    query,
+                },
+                Request::Readme { name, version } => Update::Readme {
+                    html: readme(&name, &version).map_err(|error| error.to_string()),
+                    name,
+                },
+            };
+            if updates.send(update).is_err() {
+                break;
+            }
+        }
+    });
+    requests
+}
+
+fn get(url: &str) -> ureq::RequestBuilder<ureq::typestate::WithoutBody> {
+    ureq::get(url).header("User-Agent", USER_AGENT)
+}
+
+fn search(query: &str) -> Result<Vec<Crate>, Box<dyn std::error::Error>> {
+    let json = get(&format!("{API}/crates"))
+        .query("q", query)
+        .query("per_page", PER_PAGE)
+        .call()?
+        .body_mut()
+        .read_to_string()?;
+    Ok(parse_search(&json)?)
+}
+
+fn readme(name: &str, version: &str) -> Result<String, ureq::Error> {
+    // 这个接口重定向到静态文件，ureq 自动跟随。
+    get(&format!("{API}/crates/{name}/{version}/readme"))
+        .call()?
+        .body_mut()
+        .read_to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_results() {
+        let json = r#"{
+            "crates": [
+                {
+                    "name": "ratatui",
+                    "max_version": "0.30.0-alpha.1",
+                    "max_stable_version": "0.29.0",
+                    "description": "A library to build rich terminal user interfaces",
+                    "downloads": 12345678,
+                    "recent_downloads": 2345678,
+                    "homepage": null
+                },
+                {
+                    "name": "ratatui-nightly",
+                    "max_version": "0.1.0-rc.1",
+                    "max_stable_version": null,
+                    "description": null,
+                    "downloads": 10,
+                    "recent_downloads": null
+                }
+            ],
+            "meta": { "total": 2 }
+        }"#;
+        let crates = parse_search(json).unwrap();
+        assert_eq!(crates.len(), 2);
+        assert_eq!(crates[0].version(), "0.29.0");
+        assert_eq!(crates[1].version(), "0.1.0-rc.1");
+        assert!(parse_search("{}").is_err());
+    }
+}
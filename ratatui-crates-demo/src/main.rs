@@ -0,0 +1,414 @@
+//! crates.io 搜索演示：输入关键词搜索 crate，列出下载量和最新版本，右侧显示所选 crate 的
+//! README（HTML 转成纯文本），并可以把 `cargo add` 命令复制到剪贴板。
+//!
+//! 网络请求在后台线程中进行，见 `api` 模块。复制使用终端的 OSC 52 序列，
+//! 所以通过 SSH 运行时也会复制到本机的剪贴板（需要终端支持）。
+//!
+//! 按键：输入关键词后按 `Enter` 搜索，`Up` / `Down` 选择，`PageUp` / `PageDown` 滚动 README，
+//! `Ctrl+Y` 复制 `cargo add` 命令，`Esc` 退出。
+
+use std::{
+    collections::HashMap,
+    io,
+    sync::mpsc::{self, Receiver, Sender},
+    time::Duration,
+};
+
+use color_eyre::{eyre::WrapErr, Result};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Paragraph, Row, Table, TableState, Wrap},
+};
+use ratatui_common::{
+    clipboard,
+    events::{EventSource, TerminalEvents},
+    terminal,
+    text_input::Input,
+};
+
+use crate::api::{Crate, Request, Update};
+
+mod api;
+
+/// 等待按键的最长时间，之后处理后台送回的结果。
+const REFRESH: Duration = Duration::from_millis(100);
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    let (sender, updates) = mpsc::channel();
+    let requests = api::spawn(sender);
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new(requests, updates).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+/// 一个 crate 的 README。
+enum Readme {
+    Loading,
+    Failed(String),
+    Loaded {
+        html: String,
+        /// 按宽度换行后的文本，宽度变化时重新转换。
+        text: Option<(u16, String)>,
+    },
+}
+
+struct App {
+    requests: Sender<Request>,
+    updates: Receiver<Update>,
+    query: Input,
+    /// 正在等待结果的关键词，之前的搜索结果到达时忽略。
+    searching: Option<String>,
+    crates: Vec<Crate>,
+    table: TableState,
+    /// 按 crate 名称缓存的 README，选择来回切换时不重复请求。
+    readmes: HashMap<String, Readme>,
+    scroll: u16,
+    /// 等待写到终端的剪贴板内容。
+    clipboard: Option<String>,
+    /// 最近一次操作的结果，`Err` 显示为红色。
+    message: Option<Result<String, String>>,
+    exit: bool,
+}
+
+impl App {
+    fn new(requests: Sender<Request>, updates: Receiver<Update>) -> Self {
+        Self {
+            requests,
+            updates,
+            query: Input::default(),
+            searching: None,
+            crates: Vec::new(),
+            table: TableState::default(),
+            readmes: HashMap::new(),
+            scroll: 0,
+            clipboard: None,
+            message: None,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            while let Ok(update) = self.updates.try_recv() {
+                self.update(update);
+            }
+            if let Some(text) = self.clipboard.take() {
+                clipboard::copy(&mut io::stdout(), &text)
+                    .wrap_err_with(|| format!("failed to copy {text:?}"))?;
+            }
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut()))?;
+            if events.poll(REFRESH)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, update: Update) {
+        match update {
+            Update::Results { query, crates } => {
+                if self.searching.as_ref() != Some(&query) {
+                    return;
+                }
+                self.searching = None;
+                match crates {
+                    Ok(crates) => {
+                        self.message = Some(Ok(format!("{} results for {query:?}", crates.len())));
+                        self.crates = crates;
+                        self.table.select((!self.crates.is_empty()).then_some(0));
+                        self.load_readme();
+                    }
+                    Err(error) => self.message = Some(Err(format!("Search failed: {error}"))),
+                }
+            }
+            Update::Readme { name, html } => {
+                let readme = match html {
+                    Ok(html) => Readme::Loaded { html, text: None },
+                    Err(error) => Readme::Failed(error),
+                };
+                self.readmes.insert(name, readme);
+            }
+        }
+    }
+
+    fn selected(&self) -> Option<&Crate> {
+        self.crates.get(self.table.selected()?)
+    }
+
+    /// 所选 crate 的 README 还没有请求过时，发出请求。
+    fn load_readme(&mut self) {
+        self.scroll = 0;
+        let Some(krate) = self.selected() else {
+            return;
+        };
+        if self.readmes.contains_key(&krate.name) {
+            return;
+        }
+        let request = Request::Readme {
+            name: krate.name.clone(),
+            version: krate.version().to_string(),
+        };
+        self.readmes.insert(krate.name.clone(), Readme::Loading);
+        let _ = self.requests.send(request);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        let selected = self.table.selected().unwrap_or(0);
+        let last = self.crates.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Esc => self.exit = true,
+            KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::CONTROL) => self.copy(),
+            KeyCode::Enter => self.search(),
+            KeyCode::Up if !self.crates.is_empty() => {
+                self.table.select(Some(selected.saturating_sub(1)));
+                self.load_readme();
+            }
+            KeyCode::Down if !self.crates.is_empty() => {
+                self.table.select(Some((selected + 1).min(last)));
+                self.load_readme();
+            }
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+            _ => {
+                self.query.handle_key_event(key);
+            }
+        }
+    }
+
+    fn search(&mut self) {
+        let query = self.query.value().trim().to_string();
+        if query.is_empty() {
+            return;
+        }
+        self.message = Some(Ok(format!("Searching for {query:?}…")));
+        self.searching = Some(query.clone());
+        let _ = self.requests.send(Request::Search(query));
+    }
+
+    fn copy(&mut self) {
+        let Some(krate) = self.selected() else {
+            return;
+        };
+        let line = format!("cargo add {}@{}", krate.name, krate.version());
+        self.message = Some(Ok(format!("Copied: {line}")));
+        self.clipboard = Some(line);
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let [input, main, status] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+        let [list_area, readme_area] =
+            Layout::horizontal([Constraint::Fill(2), Constraint::Fill(3)]).areas(main);
+
+        Paragraph::new(self.query.line("> ", Style::new().bold()))
+            .block(Block::bordered().title(" Search crates.io ".bold()))
+            .render(input, buf);
+
+        let rows: Vec<Row> = self
+            .crates
+            .iter()
+            .map(|krate| {
+                Row::new(vec![
+                    Line::from(krate.name.clone()),
+                    Line::from(krate.version().to_string().dim()),
+                    Line::from(format_downloads(krate.downloads)).right_aligned(),
+                ])
+            })
+            .collect();
+        let widths = [
+            Constraint::Fill(1),
+            Constraint::Length(10),
+            Constraint::Length(6),
+        ];
+        let table = Table::new(rows, widths)
+            .highlight_style(Style::new().reversed())
+            .block(
+                Block::bordered()
+                    .title(" Crates ")
+                    .title_bottom(Line::from(" Copy <Ctrl+Y> Quit <Esc> ").centered()),
+            );
+        StatefulWidget::render(table, list_area, buf, &mut self.table);
+
+        self.render_readme(readme_area, buf);
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+
+    fn render_readme(&mut self, area: Rect, buf: &mut Buffer) {
+        // 分别借用各个字段，转换后的文本要写回 `readmes`。
+        let Some(krate) = self.table.selected().and_then(|i| self.crates.get(i)) else {
+            Block::bordered().title(" README ").render(area, buf);
+            return;
+        };
+        let mut block = Block::bordered().title(format!(" {} {} ", krate.name, krate.version()));
+        let mut lines = Vec::new();
+        if let Some(description) = &krate.description {
+            lines.push(Line::from(description.trim().to_string().italic()));
+            lines.push(Line::default());
+        }
+        let width = area.width.saturating_sub(2);
+        match self.readmes.get_mut(&krate.name) {
+            None | Some(Readme::Loading) => lines.push(Line::from("Loading README…".dim())),
+            Some(Readme::Failed(error)) => {
+                lines.push(Line::from(format!("No README: {error}").red()))
+            }
+            Some(Readme::Loaded { html, text }) => {
+                if text.as_ref().map(|(cached, _)| *cached) != Some(width) {
+                    let converted = html2text::from_read(html.as_bytes(), width.max(1).into())
+                        .unwrap_or_else(|error| format!("Cannot render README: {error}"));
+                    *text = Some((width, converted));
+                }
+                let (_, text) = text.as_ref().unwrap();
+                lines.extend(text.lines().map(|line| Line::from(line.to_string())));
+                block = block.title_bottom(Line::from(" Scroll <PgUp/PgDn> ").centered());
+            }
+        }
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0))
+            .block(block)
+            .render(area, buf);
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        match &self.message {
+            Some(Ok(message)) => Line::from(message.clone().green()),
+            Some(Err(message)) => Line::from(message.clone().red()),
+            None => Line::from("Type a search term and press Enter".dim()),
+        }
+    }
+}
+
+/// 把下载量缩写成 `5.2M`、`34.5k` 这样最多 6 个字符的形式。
+fn format_downloads(downloads: u64) -> String {
+    match downloads {
+        0..=9_999 => downloads.to_string(),
+        10_000..=999_999 => format!("{:.1}k", downloads as f64 / 1e3),
+        1_000_000..=999_999_999 => format!("{:.1}M", downloads as f64 / 1e6),
+        _ => format!("{:.1}G", downloads as f64 / 1e9),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn krate(name: &str, downloads: u64) -> Crate {
+        Crate {
+            name: name.into(),
+            max_version: "1.0.0-rc.1".into(),
+            max_stable_version: Some("0.9.2".into()),
+            description: Some(format!("The {name} crate")),
+            downloads,
+            recent_downloads: None,
+        }
+    }
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    #[test]
+    fn downloads() {
+        assert_eq!(format_downloads(9_999), "9999");
+        assert_eq!(format_downloads(34_512), "34.5k");
+        assert_eq!(format_downloads(5_234_567), "5.2M");
+        assert_eq!(format_downloads(1_500_000_000), "1.5G");
+    }
+
+    #[test]
+    fn search_and_copy() {
+        let (requests, sent) = mpsc::channel();
+        let (_updates, receiver) = mpsc::channel();
+        let mut app = App::new(requests, receiver);
+        testing::type_text("serde", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(sent.try_recv(), Ok(Request::Search("serde".into())));
+
+        // 过时的搜索结果被忽略。
+        app.update(Update::Results {
+            query: "ser".into(),
+            crates: Ok(vec![krate("stale", 1)]),
+        });
+        assert!(app.crates.is_empty());
+        app.update(Update::Results {
+            query: "serde".into(),
+            crates: Ok(vec![krate("serde", 5_234_567), krate("serde_json", 34_512)]),
+        });
+        assert_eq!(
+            sent.try_recv(),
+            Ok(Request::Readme {
+                name: "serde".into(),
+                version: "0.9.2".into()
+            })
+        );
+
+        app.handle_key(KeyCode::Down.into());
+        app.handle_key(KeyCode::Up.into());
+        // 已经请求过的 README 不再请求。
+        assert_eq!(
+            sent.try_recv(),
+            Ok(Request::Readme {
+                name: "serde_json".into(),
+                version: "0.9.2".into()
+            })
+        );
+        assert!(sent.try_recv().is_err());
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::CONTROL));
+        assert_eq!(app.clipboard.as_deref(), Some("cargo add serde@0.9.2"));
+        assert_eq!(app.query.value(), "serde");
+    }
+
+    #[test]
+    fn render_results() {
+        let (requests, _sent) = mpsc::channel();
+        let (_updates, receiver) = mpsc::channel();
+        let mut app = App::new(requests, receiver);
+        app.searching = Some("serde".into());
+        app.update(Update::Results {
+            query: "serde".into(),
+            crates: Ok(vec![krate("serde", 5_234_567), krate("serde_json", 34_512)]),
+        });
+        app.update(Update::Readme {
+            name: "serde".into(),
+            html: Ok("<h1>Serde</h1><p>A framework.</p>".into()),
+        });
+        let rows = rows(&mut app, 70, 10);
+        assert_eq!(
+            rows[4],
+            "│serde    0.9.2        5.2M││The serde crate                         │"
+        );
+        assert_eq!(
+            rows[6],
+            "│                          ││# Serde                                 │"
+        );
+        assert_eq!(rows[9].trim_end(), "2 results for \"serde\"");
+
+        // 搜索失败时保留之前的结果。
+        app.searching = Some("serde".into());
+        app.update(Update::Results {
+            query: "serde".into(),
+            crates: Err("timed out".into()),
+        });
+        assert_eq!(app.crates.len(), 2);
+        assert_eq!(app.status_line().to_string(), "Search failed: timed out");
+    }
+}
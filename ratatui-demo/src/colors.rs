@@ -0,0 +1,137 @@
+//! 彩虹色循环：文本的每一列使用不同色相的颜色，整体色相随时间旋转。
+//!
+//! 颜色在每一帧根据经过的时间重新计算，因此动画速度与帧率无关；暂停时色相保持不变。
+
+use std::time::Instant;
+
+use ratatui::style::Color;
+
+/// 相邻两列之间的色相差（度）。
+const HUE_STEP: f32 = 12.0;
+
+/// 默认的旋转速度（度/秒）。
+const DEFAULT_SPEED: f32 = 90.0;
+
+/// 每次按键调整速度的倍数。
+const SPEED_FACTOR: f32 = 1.5;
+
+/// 速度的取值范围（度/秒）。
+const SPEED_RANGE: (f32, f32) = (10.0, 1440.0);
+
+#[derive(Debug)]
+pub struct ColorCycle {
+    /// 当前的起始色相（度）。
+    hue: f32,
+    /// 旋转速度（度/秒）。
+    speed: f32,
+    paused: bool,
+    /// 上一次更新的时间。
+    last: Instant,
+}
+
+impl ColorCycle {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            hue: 0.0,
+            speed: DEFAULT_SPEED,
+            paused: false,
+            last: now,
+        }
+    }
+
+    /// 按照经过的时间推进色相，每一帧绘制之前调用。
+    pub fn update(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f32();
+        self.last = now;
+        if !self.paused {
+            self.hue = (self.hue + self.speed * elapsed) % 360.0;
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed * SPEED_FACTOR).min(SPEED_RANGE.1);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = (self.speed / SPEED_FACTOR).max(SPEED_RANGE.0);
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    /// 第 `column` 列的颜色。
+    pub fn color_at(&self, column: u16) -> Color {
+        hsv_to_rgb(self.hue + f32::from(column) * HUE_STEP)
+    }
+}
+
+/// 饱和度和亮度都为最大值时，色相对应的 RGB 颜色。
+fn hsv_to_rgb(hue: f32) -> Color {
+    let h = hue.rem_euclid(360.0) / 60.0;
+    let x = 1.0 - (h % 2.0 - 1.0).abs();
+    let (r, g, b) = match h as u8 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    let channel = |value: f32| (value * 255.0).round() as u8;
+    Color::Rgb(channel(r), channel(g), channel(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn primary_hues() {
+        assert_eq!(hsv_to_rgb(0.0), Color::Rgb(255, 0, 0));
+        assert_eq!(hsv_to_rgb(120.0), Color::Rgb(0, 255, 0));
+        assert_eq!(hsv_to_rgb(240.0), Color::Rgb(0, 0, 255));
+        assert_eq!(hsv_to_rgb(360.0), Color::Rgb(255, 0, 0));
+    }
+
+    #[test]
+    fn rotate_with_time_unless_paused() {
+        let start = Instant::now();
+        let mut colors = ColorCycle::new(start);
+        colors.update(start + Duration::from_secs(1));
+        assert_eq!(colors.hue, DEFAULT_SPEED);
+        assert_eq!(colors.color_at(0), hsv_to_rgb(DEFAULT_SPEED));
+
+        colors.toggle_pause();
+        colors.update(start + Duration::from_secs(3));
+        assert_eq!(colors.hue, DEFAULT_SPEED);
+
+        // 恢复后不会补上暂停期间的时间。
+        colors.toggle_pause();
+        colors.update(start + Duration::from_secs(4));
+        assert_eq!(colors.hue, DEFAULT_SPEED * 2.0);
+    }
+
+    #[test]
+    fn speed_is_clamped() {
+        let mut colors = ColorCycle::new(Instant::now());
+        for _ in 0..20 {
+            colors.faster();
+        }
+        assert_eq!(colors.speed(), SPEED_RANGE.1);
+        for _ in 0..40 {
+            colors.slower();
+        }
+        assert_eq!(colors.speed(), SPEED_RANGE.0);
+    }
+}
@@ -0,0 +1,62 @@
+//! 根据真实的帧时间戳计算的帧率。
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// 统计最近这么长时间内绘制的帧数。
+const WINDOW: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Default)]
+pub struct FpsCounter {
+    /// 最近一个统计窗口内每一帧的绘制时间，最早的在前。
+    frames: VecDeque<Instant>,
+}
+
+impl FpsCounter {
+    /// 记录绘制了一帧。
+    pub fn frame(&mut self, now: Instant) {
+        self.frames.push_back(now);
+        while self
+            .frames
+            .front()
+            .is_some_and(|&first| now.duration_since(first) > WINDOW)
+        {
+            self.frames.pop_front();
+        }
+    }
+
+    /// 每秒帧数。不足两帧时无法计算，返回 0。
+    pub fn fps(&self) -> f64 {
+        match (self.frames.front(), self.frames.back()) {
+            (Some(first), Some(last)) if self.frames.len() > 1 => {
+                let elapsed = last.duration_since(*first).as_secs_f64();
+                (self.frames.len() - 1) as f64 / elapsed
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_frames_in_window() {
+        let start = Instant::now();
+        let mut fps = FpsCounter::default();
+        assert_eq!(fps.fps(), 0.0);
+        for i in 0..=20 {
+            fps.frame(start + Duration::from_millis(i * 50));
+        }
+        assert_eq!(fps.fps().round(), 20.0);
+
+        // 超出窗口的旧帧不再计入。
+        for i in 1..=10 {
+            fps.frame(start + Duration::from_millis(1000 + i * 100));
+        }
+        assert_eq!(fps.fps().round(), 10.0);
+    }
+}
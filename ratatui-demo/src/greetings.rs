@@ -0,0 +1,107 @@
+//! 轮流显示的多语言问候语。
+//!
+//! 问候语包含中日韩文字（每个字符占两个单元格）、带组合字符的文字以及从右向左书写的文字。
+//! 居中和换行都按显示宽度计算，而不是按字节或 `char` 的数量；终端太窄时问候语会折成多行。
+//! 从右向左的文字按逻辑顺序保存，是否按视觉顺序显示由终端决定，但不影响宽度。
+
+use ratatui::{
+    prelude::*,
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+/// 语言名称和问候语。
+pub const GREETINGS: [(&str, &str); 8] = [
+    ("English", "Hello, world!"),
+    ("中文", "你好，世界！"),
+    ("日本語", "こんにちは世界"),
+    ("한국어", "안녕하세요 세계"),
+    ("Tiếng Việt", "Xin chào thế giới"),
+    ("हिन्दी", "नमस्ते दुनिया"),
+    ("العربية", "مرحبا بالعالم"),
+    ("עברית", "שלום עולם"),
+];
+
+#[derive(Debug, Default)]
+pub struct Greetings {
+    index: usize,
+}
+
+impl Greetings {
+    /// 切换到下一种语言。
+    pub fn next(&mut self) {
+        self.index = (self.index + 1) % GREETINGS.len();
+    }
+
+    /// 当前的问候语，附带语言名称。
+    pub fn current(&self) -> String {
+        let (language, text) = GREETINGS[self.index];
+        format!("{text} ({language})")
+    }
+}
+
+impl Widget for &Greetings {
+    /// 每一行都居中显示，放不下时换行。
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.current())
+            .centered()
+            .wrap(Wrap { trim: true })
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycle_languages() {
+        let mut greetings = Greetings::default();
+        for _ in 0..GREETINGS.len() {
+            greetings.next();
+        }
+        assert_eq!(greetings.current(), "Hello, world! (English)");
+    }
+
+    #[test]
+    fn display_width() {
+        use unicode_width::UnicodeWidthStr;
+
+        // 双宽字符：6 个字符占 12 个单元格。
+        assert_eq!("你好，世界！".width(), 12);
+        // 组合字符不占单元格。
+        assert_eq!("Xin chào thế giới".width(), 17);
+        // 从右向左的文字宽度与字符数相同。
+        assert_eq!("שלום עולם".width(), 9);
+    }
+
+    #[test]
+    fn wrap_when_narrow() {
+        let mut greetings = Greetings::default();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 3));
+        greetings.render(buf.area, &mut buf);
+        assert_eq!(
+            buf,
+            Buffer::with_lines(vec!["  Hello,  ", "  world!  ", " (English)"])
+        );
+
+        // 没有空格的中日韩文字在字符之间换行，双宽字符不会被拆开。
+        greetings.next();
+        greetings.next();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 9, 3));
+        greetings.render(buf.area, &mut buf);
+        assert_eq!(buf.get(0, 0).symbol(), "こ");
+        assert_eq!(buf.get(1, 1).symbol(), "は");
+    }
+
+    #[test]
+    fn render_centered() {
+        let mut greetings = Greetings::default();
+        greetings.next();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 24, 1));
+        greetings.render(buf.area, &mut buf);
+        // "你好，世界！ (中文)" 占 19 个单元格，左侧留出 3 个。
+        assert_eq!(buf.get(3, 0).symbol(), "你");
+        assert_eq!(buf.get(15, 0).symbol(), " ");
+        assert_eq!(buf.get(16, 0).symbol(), "(");
+    }
+}
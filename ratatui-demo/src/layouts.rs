@@ -0,0 +1,151 @@
+//! 布局约束演示：几种可以按键切换的布局预设。
+//!
+//! 每个预设把主区域切分为若干区域，并记录切分时使用的约束，绘制时显示在区域的标题中，
+//! 便于观察 `Percentage`、`Length` 和 `Min` 在终端大小变化时的表现。
+
+use ratatui::prelude::*;
+
+/// 区域中显示的内容。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// 跑马灯问候语。
+    Greeting,
+    /// 回显的消息列表。
+    Messages,
+    /// 当前布局的说明。
+    Info,
+}
+
+/// 布局切分出的一个区域。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub role: Role,
+    pub area: Rect,
+    /// 切分出这个区域时使用的约束。
+    pub constraint: Constraint,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Preset {
+    /// 上下排列：按百分比和最小值切分。
+    #[default]
+    Stacked,
+    /// 左右排列：按百分比和最小值切分。
+    SideBySide,
+    /// 嵌套布局：固定宽度的侧栏，右侧再上下平分。
+    Nested,
+}
+
+impl Preset {
+    /// 下一个预设，最后一个之后回到第一个。
+    pub fn next(self) -> Self {
+        match self {
+            Self::Stacked => Self::SideBySide,
+            Self::SideBySide => Self::Nested,
+            Self::Nested => Self::Stacked,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Stacked => "stacked",
+            Self::SideBySide => "side by side",
+            Self::Nested => "nested",
+        }
+    }
+
+    /// 对当前布局的说明，显示在 `Role::Info` 区域中。
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Stacked => "vertical: Percentage(70) + Min(3)",
+            Self::SideBySide => "horizontal: Percentage(60) + Min(20)",
+            Self::Nested => {
+                "horizontal: Length(24) + Min(0), right side vertical: Percentage(50) × 2"
+            }
+        }
+    }
+
+    /// 按预设切分区域。
+    pub fn split(self, area: Rect) -> Vec<Region> {
+        match self {
+            Self::Stacked => regions(
+                area,
+                Direction::Vertical,
+                [
+                    (Role::Greeting, Constraint::Percentage(70)),
+                    (Role::Messages, Constraint::Min(3)),
+                ],
+            ),
+            Self::SideBySide => regions(
+                area,
+                Direction::Horizontal,
+                [
+                    (Role::Greeting, Constraint::Percentage(60)),
+                    (Role::Messages, Constraint::Min(20)),
+                ],
+            ),
+            Self::Nested => {
+                let [sidebar, right] =
+                    Layout::horizontal([Constraint::Length(24), Constraint::Min(0)]).areas(area);
+                let mut split = vec![Region {
+                    role: Role::Messages,
+                    area: sidebar,
+                    constraint: Constraint::Length(24),
+                }];
+                split.extend(regions(
+                    right,
+                    Direction::Vertical,
+                    [
+                        (Role::Greeting, Constraint::Percentage(50)),
+                        (Role::Info, Constraint::Percentage(50)),
+                    ],
+                ));
+                split
+            }
+        }
+    }
+}
+
+/// 沿 `direction` 按约束切分，每个约束对应一个区域。
+fn regions<const N: usize>(
+    area: Rect,
+    direction: Direction,
+    parts: [(Role, Constraint); N],
+) -> Vec<Region> {
+    let areas = Layout::new(direction, parts.map(|(_, constraint)| constraint)).split(area);
+    parts
+        .into_iter()
+        .zip(areas.iter())
+        .map(|((role, constraint), &area)| Region {
+            role,
+            area,
+            constraint,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycle_presets() {
+        let preset = Preset::default();
+        assert_eq!(preset.next().next().next(), preset);
+    }
+
+    #[test]
+    fn split_presets() {
+        let area = Rect::new(0, 0, 80, 20);
+        let stacked = Preset::Stacked.split(area);
+        assert_eq!(stacked[0].area, Rect::new(0, 0, 80, 14));
+        assert_eq!(stacked[1].area, Rect::new(0, 14, 80, 6));
+
+        let nested = Preset::Nested.split(area);
+        let roles: Vec<_> = nested.iter().map(|region| region.role).collect();
+        assert_eq!(roles, [Role::Messages, Role::Greeting, Role::Info]);
+        assert_eq!(nested[0].area, Rect::new(0, 0, 24, 20));
+        assert_eq!(nested[1].area, Rect::new(24, 0, 56, 10));
+        assert_eq!(nested[2].area, Rect::new(24, 10, 56, 10));
+    }
+}
@@ -3,17 +3,97 @@
 //! 我们假设您对终端有基本的了解，并且拥有文本编辑器或 Rust IDE。
 //! 如果您没有偏好，VSCode 是一个不错的默认选择。
 
+use clap::Parser;
 use crossterm::{
     event::{self, KeyCode, KeyEventKind},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
 
-use ratatui::{prelude::*, widgets::Paragraph};
+use ratatui::{
+    prelude::*,
+    widgets::{block::Title, Block, Borders, List, Paragraph, Wrap},
+};
+use unicode_width::UnicodeWidthStr;
+
+use std::{
+    io::{stdout, Result},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use ratatui_common::{
+    capabilities::Capabilities,
+    events::{EventSource, TerminalEvents},
+    recording::{self, Player, Recorder},
+};
+
+use crate::{
+    colors::ColorCycle,
+    fps::FpsCounter,
+    greetings::Greetings,
+    layouts::{Preset, Role},
+    marquee::Marquee,
+};
+
+mod colors;
+mod fps;
+mod greetings;
+mod layouts;
+mod marquee;
+
+/// 动画的节拍：跑马灯每隔这么长时间向左移动一个单元格。
+const TICK_RATE: Duration = Duration::from_millis(100);
 
-use std::io::{stdout, Result};
+/// 多语言问候语每隔这么长时间切换一次。
+const GREETING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 终端的最小尺寸（宽、高），小于这个尺寸时只显示提示。
+const MIN_SIZE: (u16, u16) = (40, 10);
+
+/// 输入行的提示符。
+const PROMPT: &str = "> ";
+
+/// 默认的事件轮询超时，也是两次绘制之间的最短间隔（16 毫秒约为 60 fps）。
+const DEFAULT_POLL_TIMEOUT: Duration = Duration::from_millis(16);
+
+/// 可以通过按键调整的事件轮询超时范围。
+const POLL_TIMEOUT_RANGE: (Duration, Duration) =
+    (Duration::from_millis(1), Duration::from_millis(1024));
+
+/// 命令行参数。
+#[derive(Debug, Parser)]
+#[command(about)]
+struct Cli {
+    /// 回放之前录制的事件，回放完毕后继续读取终端。
+    #[arg(long, value_name = "FILE")]
+    replay: Option<PathBuf>,
+
+    /// 回放速度的倍数，例如 `2` 表示两倍速。
+    #[arg(long, value_name = "FACTOR", default_value_t = 1.0, value_parser = recording::parse_speed)]
+    replay_speed: f64,
+
+    /// 把收到的所有事件连同时间戳录制到文件中。
+    #[arg(long, value_name = "FILE")]
+    record: Option<PathBuf>,
+}
 
 fn main() -> Result<()> {
+    let cli = Cli::parse();
+    // 事件来自终端或录制文件；需要录制时再包装一层。
+    let source: Box<dyn EventSource<event::Event> + Send> = match &cli.replay {
+        Some(path) => Box::new(
+            Player::from_file(path)?
+                .speed(cli.replay_speed)
+                .then(TerminalEvents),
+        ),
+        None => Box::new(TerminalEvents),
+    };
+    let mut events: Box<dyn EventSource<event::Event> + Send> = match &cli.record {
+        Some(path) => Box::new(Recorder::create(source, path)?),
+        None => source,
+    };
+
     // 首先，应用程序进入备用屏幕，这是一个辅助屏幕，允许您的应用程序呈现所需的任何内容，而不会干扰 shell 中终端应用程序的正常输出。
     stdout().execute(EnterAlternateScreen)?;
 
@@ -25,7 +105,7 @@ fn main() -> Result<()> {
     terminal.clear()?;
 
     // 主程序循环。
-    main_loop(&mut terminal)?;
+    main_loop(&mut terminal, events.as_mut())?;
 
     // 当应用程序完成时，它需要通过离开备用屏幕并禁用原始模式来恢复终端状态。
     stdout().execute(LeaveAlternateScreen)?;
@@ -33,40 +113,307 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-/// 主程序循环。它做两件事：
-/// 1. 绘制界面
+/// 演示程序的状态。
+struct App {
+    marquee: Marquee,
+    greetings: Greetings,
+    colors: ColorCycle,
+    /// 不支持真彩色的终端上，渐变颜色会换成最接近的调色板颜色。
+    capabilities: Capabilities,
+    /// 输入行中正在编辑的文本。
+    input: String,
+    /// 是否在输入行中打字。打字时按键都进入输入行，而不是作为命令。
+    editing: bool,
+    /// 按 Enter 回显的消息，最新的在最后。
+    messages: Vec<String>,
+    /// 当前的布局预设。
+    layout: Preset,
+    fps: FpsCounter,
+    /// 事件轮询超时：两次绘制之间至少间隔这么长时间。
+    poll_timeout: Duration,
+    exit: bool,
+}
+
+impl App {
+    fn new(now: Instant) -> Self {
+        Self {
+            marquee: Marquee::new("Hello Ratatui!"),
+            greetings: Greetings::default(),
+            colors: ColorCycle::new(now),
+            capabilities: Capabilities::detect(),
+            input: String::new(),
+            editing: false,
+            messages: Vec::new(),
+            layout: Preset::default(),
+            fps: FpsCounter::default(),
+            poll_timeout: DEFAULT_POLL_TIMEOUT,
+            exit: false,
+        }
+    }
+
+    fn render(&self, frame: &mut Frame) {
+        let area = frame.size();
+        if area.width < MIN_SIZE.0 || area.height < MIN_SIZE.1 {
+            Self::render_too_small(frame, area);
+            return;
+        }
+        let [main, input, help] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+        frame
+            .buffer_mut()
+            .set_style(area, Style::new().white().on_blue());
+
+        let fps = format!(
+            " {:.0} fps · poll {} ms ([/]) ",
+            self.fps.fps(),
+            self.poll_timeout.as_millis()
+        );
+
+        // 每个区域的标题显示切分出它的约束，右上角显示帧率。
+        for region in self.layout.split(main) {
+            let block = Block::new()
+                .borders(Borders::ALL)
+                .title(format!(" {:?} ", region.constraint));
+            let block = if region.area.y == main.y && region.area.right() == main.right() {
+                block.title(Title::from(fps.as_str()).alignment(Alignment::Right))
+            } else {
+                block
+            };
+            let inner = block.inner(region.area);
+            frame.render_widget(block, region.area);
+            match region.role {
+                Role::Greeting => self.render_greeting(frame, inner),
+                Role::Messages => {
+                    // 只显示放得下的最新消息。
+                    let skip = self
+                        .messages
+                        .len()
+                        .saturating_sub(usize::from(inner.height));
+                    frame.render_widget(
+                        List::new(self.messages[skip..].iter().map(String::as_str)),
+                        inner,
+                    );
+                }
+                Role::Info => frame.render_widget(
+                    Paragraph::new(self.layout.description()).wrap(Wrap { trim: true }),
+                    inner,
+                ),
+            }
+        }
+
+        frame.render_widget(Line::from(format!("{PROMPT}{}", self.input)), input);
+        if self.editing {
+            let x = input.x + (PROMPT.width() + self.input.width()) as u16;
+            frame.set_cursor(x.min(input.right().saturating_sub(1)), input.y);
+        }
+
+        let help_text = if self.editing {
+            "enter send · esc stop typing".to_string()
+        } else {
+            let state = if self.colors.paused() {
+                "paused"
+            } else {
+                "running"
+            };
+            format!(
+                "q quit · i type · l layout ({}) · space pause · +/- speed ({:.0}°/s, {state})",
+                self.layout.name(),
+                self.colors.speed()
+            )
+        };
+        frame.render_widget(Line::from(help_text).centered(), help);
+    }
+
+    /// 终端小于 `MIN_SIZE` 时只显示提示，不绘制其他内容。
+    fn render_too_small(frame: &mut Frame, area: Rect) {
+        let text = format!(
+            "terminal too small: {}×{}, need at least {}×{}",
+            area.width, area.height, MIN_SIZE.0, MIN_SIZE.1
+        );
+        let paragraph = Paragraph::new(text).centered().wrap(Wrap { trim: true });
+        // 提示从中间偏上开始，折行后大致垂直居中。
+        let top = area.height.saturating_sub(3) / 2;
+        let middle = Rect {
+            y: area.y + top,
+            height: area.height - top,
+            ..area
+        };
+        frame.render_widget(paragraph, middle);
+    }
+
+    /// 绘制跑马灯，并为它所在的行逐列计算颜色；下面几行居中显示多语言问候语，太窄时折行。
+    fn render_greeting(&self, frame: &mut Frame, area: Rect) {
+        frame.render_widget(&self.marquee, area);
+        if area.is_empty() {
+            return;
+        }
+        let y = area.y + area.height / 2;
+        if y + 1 < area.bottom() {
+            frame.render_widget(
+                &self.greetings,
+                Rect {
+                    y: y + 1,
+                    height: area.bottom() - y - 1,
+                    ..area
+                },
+            );
+        }
+        let buf = frame.buffer_mut();
+        for x in area.left()..area.right() {
+            let color = self.colors.color_at(x - area.x);
+            buf.get_mut(x, y)
+                .set_fg(self.capabilities.adapt_color(color));
+        }
+    }
+
+    fn handle_key(&mut self, code: KeyCode) {
+        if self.editing {
+            match code {
+                KeyCode::Char(c) => self.input.push(c),
+                KeyCode::Backspace => {
+                    self.input.pop();
+                }
+                KeyCode::Enter if !self.input.is_empty() => {
+                    self.messages.push(std::mem::take(&mut self.input));
+                }
+                KeyCode::Esc => self.editing = false,
+                _ => {}
+            }
+            return;
+        }
+        match code {
+            KeyCode::Char('i') => self.editing = true,
+            KeyCode::Char('l') => self.layout = self.layout.next(),
+            KeyCode::Char('q') => self.exit = true,
+            KeyCode::Char(' ') => self.colors.toggle_pause(),
+            KeyCode::Char('+') | KeyCode::Char('=') => self.colors.faster(),
+            KeyCode::Char('-') => self.colors.slower(),
+            KeyCode::Char('[') => {
+                self.poll_timeout = (self.poll_timeout / 2).max(POLL_TIMEOUT_RANGE.0);
+            }
+            KeyCode::Char(']') => {
+                self.poll_timeout = (self.poll_timeout * 2).min(POLL_TIMEOUT_RANGE.1);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 主程序循环。它做三件事：
+/// 1. 绘制界面（两次绘制之间至少间隔一个轮询超时）
 /// 2. 处理事件
-fn main_loop<B>(terminal: &mut Terminal<B>) -> Result<()>
+/// 3. 计时器到期时推进动画
+fn main_loop<B>(
+    terminal: &mut Terminal<B>,
+    events: &mut dyn EventSource<event::Event>,
+) -> Result<()>
 where
     B: Backend,
 {
-    loop {
+    let now = Instant::now();
+    let mut app = App::new(now);
+    let mut next_tick = now + TICK_RATE;
+    let mut next_greeting = now + GREETING_INTERVAL;
+    let mut next_frame = now;
+    while !app.exit {
         // `terminal` 上的 `draw` 方法是应用程序与 Ratatui 的主要交互点。
         // `draw` 方法接受带有单个 `Frame` 参数的闭包（匿名方法），并呈现整个屏幕。
-        // 您的应用程序将创建一个与终端窗口全尺寸的区域，并呈现一个具有白色前景文本和蓝色背景的新段落。
-        terminal.draw(|frame| {
-            let area = frame.size();
-            frame.render_widget(
-                Paragraph::new("Hello Ratatui! (press 'q' to quit)")
-                    .white()
-                    .on_blue(),
-                area,
-            );
-        })?;
+        // 颜色随时间变化，所以每一帧绘制之前都先更新颜色；两次绘制之间至少间隔一个轮询超时，
+        // 调大轮询超时可以直接在右上角看到帧率下降。
+        let now = Instant::now();
+        if now >= next_frame {
+            app.colors.update(now);
+            app.fps.frame(now);
+            terminal.draw(|frame| app.render(frame))?;
+            next_frame = now + app.poll_timeout;
+        }
 
         // Ratatui 绘制框架后，您的应用程序需要检查是否发生了任何事件。这些是键盘按下、鼠标事件、调整大小等。
-        // 如果用户按下 `q` 键，应用程序应该跳出循环。
         //
-        // 为事件轮询添加一个小的超时，以确保无论是否有待处理的事件，UI 都保持响应（16 毫秒约为 60 fps）。
+        // 事件轮询只等待到下一次计时器触发或下一帧为止：动画的速度由 `TICK_RATE` 决定，与事件多少无关。
         // 检查事件类型是否为 `Press` 很重要，否则 Windows 终端将看到每个键两次。
-        if event::poll(std::time::Duration::from_millis(16))? {
-            if let event::Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press && key.code == KeyCode::Char('q') {
-                    break;
+        let timeout = next_tick
+            .min(next_frame)
+            .min(next_greeting)
+            .saturating_duration_since(Instant::now());
+        if events.poll(timeout)? {
+            match events.read()? {
+                event::Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    app.handle_key(key.code);
                 }
+                // 终端大小变化后立即按新的尺寸重绘，不等到下一帧。
+                event::Event::Resize(..) => next_frame = Instant::now(),
+                _ => {}
             }
         }
+        if Instant::now() >= next_tick {
+            app.marquee.tick();
+            next_tick += TICK_RATE;
+        }
+        if Instant::now() >= next_greeting {
+            app.greetings.next();
+            next_greeting += GREETING_INTERVAL;
+        }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn echo_input() {
+        let mut app = App::new(Instant::now());
+        // 不在打字时按键是命令。
+        app.handle_key(KeyCode::Char(' '));
+        assert!(app.colors.paused());
+        assert!(app.input.is_empty());
+
+        app.handle_key(KeyCode::Char('i'));
+        for c in "hi q".chars() {
+            app.handle_key(KeyCode::Char(c));
+        }
+        app.handle_key(KeyCode::Backspace);
+        assert_eq!(app.input, "hi ");
+        assert!(!app.exit);
+
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.messages, ["hi "]);
+        assert!(app.input.is_empty());
+        // 空输入不会回显。
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.messages.len(), 1);
+
+        app.handle_key(KeyCode::Esc);
+        app.handle_key(KeyCode::Char('q'));
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn render_too_small() {
+        let app = App::new(Instant::now());
+        let mut terminal = Terminal::new(backend::TestBackend::new(20, 5)).unwrap();
+        terminal.draw(|frame| app.render(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let text: String = buffer.content.iter().map(|cell| cell.symbol()).collect();
+        assert!(text.contains("terminal too small"));
+        assert!(!text.contains("Hello"));
+    }
+
+    #[test]
+    fn adjust_poll_timeout() {
+        let mut app = App::new(Instant::now());
+        app.handle_key(KeyCode::Char(']'));
+        assert_eq!(app.poll_timeout, DEFAULT_POLL_TIMEOUT * 2);
+        for _ in 0..20 {
+            app.handle_key(KeyCode::Char('['));
+        }
+        assert_eq!(app.poll_timeout, POLL_TIMEOUT_RANGE.0);
+    }
+}
@@ -0,0 +1,92 @@
+//! 从右向左滚动的跑马灯文本。
+//!
+//! 每次计时器触发时 `tick` 让文本向左移动一个单元格；文本完全移出左边缘后重新从右边缘进入。
+//! 位置按显示宽度计算，双宽字符不会被拆成两半。
+
+use ratatui::{prelude::*, widgets::Widget};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+#[derive(Debug, Default)]
+pub struct Marquee {
+    text: String,
+    /// 已经滚动的单元格数。
+    offset: usize,
+}
+
+impl Marquee {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            offset: 0,
+        }
+    }
+
+    /// 向左移动一个单元格。
+    pub fn tick(&mut self) {
+        self.offset = self.offset.wrapping_add(1);
+    }
+
+    /// 在 `width` 个单元格宽的区域中，文本第一个单元格所在的列，可能在区域左侧（为负数）。
+    fn start(&self, width: u16) -> i32 {
+        // 一个周期：从右边缘进入，直到完全移出左边缘。
+        let cycle = usize::from(width) + self.text.width();
+        i32::from(width) - (self.offset % cycle.max(1)) as i32
+    }
+}
+
+impl Widget for &Marquee {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+        let y = area.y + area.height / 2;
+        let mut x = self.start(area.width);
+        for c in self.text.chars() {
+            let w = c.width().unwrap_or(0) as i32;
+            if x >= i32::from(area.width) {
+                break;
+            }
+            // 只绘制完整落在区域内的字符。
+            if x >= 0 && x + w <= i32::from(area.width) {
+                buf.get_mut(area.x + x as u16, y).set_char(c);
+            }
+            x += w;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(marquee: &Marquee, width: u16) -> String {
+        let mut buf = Buffer::empty(Rect::new(0, 0, width, 1));
+        marquee.render(buf.area, &mut buf);
+        buf.content.iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn scrolls_from_right_to_left() {
+        let mut marquee = Marquee::new("hi");
+        assert_eq!(render(&marquee, 4), "    ");
+        marquee.tick();
+        assert_eq!(render(&marquee, 4), "   h");
+        marquee.tick();
+        marquee.tick();
+        assert_eq!(render(&marquee, 4), " hi ");
+        for _ in 0..3 {
+            marquee.tick();
+        }
+        // 完全移出左边缘后回到起点。
+        assert_eq!(render(&marquee, 4), "    ");
+    }
+
+    #[test]
+    fn keeps_wide_chars_whole() {
+        let mut marquee = Marquee::new("你好");
+        marquee.tick();
+        assert_eq!(render(&marquee, 3), "   ");
+        marquee.tick();
+        assert_eq!(render(&marquee, 3), " 你 ");
+    }
+}
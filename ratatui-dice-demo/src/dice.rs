@@ -0,0 +1,276 @@
+//! 骰子表达式，例如 `3d6+2`、`d20-1`、`2d8+1d6+3`。
+//!
+//! 表达式由 `+` / `-` 连接的若干项组成，每一项是若干个骰子（`NdM`，`N` 省略时为 1，
+//! `d%` 是 `d100`）或者一个常数。空白字符被忽略，`D` 和 `d` 相同。
+
+use std::{fmt, str::FromStr};
+
+use rand::Rng;
+
+/// 一项中最多的骰子数。
+pub const MAX_DICE: u32 = 100;
+/// 骰子最多的面数。
+pub const MAX_SIDES: u32 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Dice { count: u32, sides: u32 },
+    Constant(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Term {
+    pub negative: bool,
+    pub kind: Kind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expression {
+    pub terms: Vec<Term>,
+}
+
+impl Expression {
+    /// 可能掷出的最小值和最大值。
+    pub fn range(&self) -> (i64, i64) {
+        self.terms.iter().fold((0, 0), |(low, high), term| {
+            let (min, max) = match term.kind {
+                Kind::Dice { count, sides } => (i64::from(count), i64::from(count * sides)),
+                Kind::Constant(value) => (i64::from(value), i64::from(value)),
+            };
+            if term.negative {
+                (low - max, high - min)
+            } else {
+                (low + min, high + max)
+            }
+        })
+    }
+
+    pub fn roll(&self, rng: &mut impl Rng) -> Roll {
+        let faces = self
+            .terms
+            .iter()
+            .map(|term| match term.kind {
+                Kind::Dice { count, sides } => {
+                    (0..count).map(|_| rng.gen_range(1..=sides)).collect()
+                }
+                Kind::Constant(_) => Vec::new(),
+            })
+            .collect();
+        Roll {
+            expression: self.clone(),
+            faces,
+        }
+    }
+}
+
+impl FromStr for Expression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let text: Vec<char> = s
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        if text.is_empty() {
+            return Err("type a dice expression like 3d6+2".into());
+        }
+        let mut terms = Vec::new();
+        let mut at = 0;
+        let number = |at: &mut usize| -> Result<Option<u32>, String> {
+            let start = *at;
+            while text.get(*at).is_some_and(char::is_ascii_digit) {
+                *at += 1;
+            }
+            if start == *at {
+                return Ok(None);
+            }
+            let digits: String = text[start..*at].iter().collect();
+            digits
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("{digits} is too large"))
+        };
+        loop {
+            let negative = match text.get(at) {
+                Some('-') => true,
+                Some('+') => false,
+                _ if terms.is_empty() => false,
+                Some(c) => return Err(format!("unexpected {c:?} at position {}", at + 1)),
+                None => break,
+            };
+            if text.get(at).is_some_and(|&c| c == '+' || c == '-') {
+                at += 1;
+            }
+            let count = number(&mut at)?;
+            let kind = if text.get(at) == Some(&'d') {
+                at += 1;
+                let sides = if text.get(at) == Some(&'%') {
+                    at += 1;
+                    100
+                } else {
+                    number(&mut at)?.ok_or_else(|| {
+                        format!("expected the number of sides at position {}", at + 1)
+                    })?
+                };
+                let count = count.unwrap_or(1);
+                if !(1..=MAX_DICE).contains(&count) {
+                    return Err(format!("roll between 1 and {MAX_DICE} dice at a time"));
+                }
+                if !(2..=MAX_SIDES).contains(&sides) {
+                    return Err(format!("dice need between 2 and {MAX_SIDES} sides"));
+                }
+                Kind::Dice { count, sides }
+            } else {
+                Kind::Constant(count.ok_or_else(|| {
+                    format!("expected a number or a die like d6 at position {}", at + 1)
+                })?)
+            };
+            terms.push(Term { negative, kind });
+        }
+        Ok(Self { terms })
+    }
+}
+
+/// 规范的写法：`1d20` 写作 `d20`，项之间没有空格。
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, term) in self.terms.iter().enumerate() {
+            if term.negative {
+                f.write_str("-")?;
+            } else if index > 0 {
+                f.write_str("+")?;
+            }
+            match term.kind {
+                Kind::Dice { count: 1, sides } => write!(f, "d{sides}")?,
+                Kind::Dice { count, sides } => write!(f, "{count}d{sides}")?,
+                Kind::Constant(value) => write!(f, "{value}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 一次掷骰的结果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Roll {
+    pub expression: Expression,
+    /// 每一项掷出的点数，常数项为空。
+    pub faces: Vec<Vec<u32>>,
+}
+
+impl Roll {
+    pub fn total(&self) -> i64 {
+        self.expression
+            .terms
+            .iter()
+            .zip(&self.faces)
+            .map(|(term, faces)| {
+                let value = match term.kind {
+                    Kind::Dice { .. } => faces.iter().map(|&face| i64::from(face)).sum(),
+                    Kind::Constant(value) => i64::from(value),
+                };
+                if term.negative {
+                    -value
+                } else {
+                    value
+                }
+            })
+            .sum()
+    }
+
+    /// 每一项的点数，例如 `[4, 6, 2] + 2`。
+    pub fn breakdown(&self) -> String {
+        let mut text = String::new();
+        for (index, (term, faces)) in self.expression.terms.iter().zip(&self.faces).enumerate() {
+            if term.negative {
+                text.push_str(if index == 0 { "-" } else { " - " });
+            } else if index > 0 {
+                text.push_str(" + ");
+            }
+            match term.kind {
+                Kind::Dice { .. } => {
+                    let faces: Vec<String> = faces.iter().map(u32::to_string).collect();
+                    text.push_str(&format!("[{}]", faces.join(", ")));
+                }
+                Kind::Constant(value) => text.push_str(&value.to_string()),
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn parse(text: &str) -> Result<Expression, String> {
+        text.parse()
+    }
+
+    #[test]
+    fn parse_expressions() {
+        let expression = parse("3d6 + 2").unwrap();
+        assert_eq!(
+            expression.terms,
+            [
+                Term {
+                    negative: false,
+                    kind: Kind::Dice { count: 3, sides: 6 }
+                },
+                Term {
+                    negative: false,
+                    kind: Kind::Constant(2)
+                },
+            ]
+        );
+        assert_eq!(expression.range(), (5, 20));
+        assert_eq!(parse("1D20-1").unwrap().to_string(), "d20-1");
+        assert_eq!(parse("-2+d%").unwrap().to_string(), "-2+d100");
+        assert_eq!(parse("2d8-1d4").unwrap().range(), (-2, 15));
+
+        assert_eq!(parse("").unwrap_err(), "type a dice expression like 3d6+2");
+        assert_eq!(
+            parse("3d").unwrap_err(),
+            "expected the number of sides at position 3"
+        );
+        assert_eq!(
+            parse("2d6+").unwrap_err(),
+            "expected a number or a die like d6 at position 5"
+        );
+        assert_eq!(parse("2d6x").unwrap_err(), "unexpected 'x' at position 4");
+        assert_eq!(
+            parse("0d6").unwrap_err(),
+            "roll between 1 and 100 dice at a time"
+        );
+        assert_eq!(
+            parse("d1").unwrap_err(),
+            "dice need between 2 and 1000 sides"
+        );
+        assert_eq!(
+            parse("99999999999").unwrap_err(),
+            "99999999999 is too large"
+        );
+    }
+
+    #[test]
+    fn roll_within_range() {
+        let expression = parse("4d6-d4+3").unwrap();
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..100 {
+            let roll = expression.roll(&mut rng);
+            let (low, high) = expression.range();
+            assert!((low..=high).contains(&roll.total()));
+            assert_eq!(roll.faces[0].len(), 4);
+            assert!(roll.faces[0].iter().all(|face| (1..=6).contains(face)));
+        }
+        let roll = Roll {
+            expression: parse("3d6-d4+2").unwrap(),
+            faces: vec![vec![4, 6, 2], vec![3], vec![]],
+        };
+        assert_eq!(roll.total(), 11);
+        assert_eq!(roll.breakdown(), "[4, 6, 2] - [3] + 2");
+    }
+}
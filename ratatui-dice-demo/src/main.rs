@@ -0,0 +1,475 @@
+//! 掷骰子演示：输入 `3d6+2` 这样的表达式后按 `Enter` 掷骰（语法见 `dice` 模块），显示每个骰子的
+//! 点数和总和，之前的结果保留在右边的历史中。
+//!
+//! 掷骰时骰子先快速翻动几帧，再从左到右依次停在掷出的点数上。结果在掷骰时就已经确定，动画只是
+//! 显示效果；减少动态效果时直接显示结果。
+//!
+//! 按键：`Enter` 掷骰（输入为空时重掷上一次的表达式），`↑` / `↓` 浏览输入过的表达式，`Esc` 退出。
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use clap::Parser;
+use color_eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use rand::{rngs::StdRng, SeedableRng};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, List, ListItem, Paragraph},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    motion::Motion,
+    terminal,
+    text_input::{History, Input},
+};
+
+use crate::dice::{Expression, Kind, Roll};
+
+mod dice;
+
+const ROLL_DURATION: Duration = Duration::from_millis(700);
+/// 动画中骰子翻面的间隔，也是两帧之间的间隔。
+const FRAME: Duration = Duration::from_millis(50);
+/// 没有动画时等待按键的最长时间。
+const IDLE: Duration = Duration::from_secs(1);
+
+/// 最多保留的历史条数。
+const HISTORY_LIMIT: usize = 100;
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 输入框中的初始表达式
+    #[arg(default_value = "3d6+2")]
+    expression: String,
+    /// 减少动态效果：不播放掷骰动画
+    #[arg(long)]
+    reduced_motion: bool,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let motion = Motion {
+        reduced: cli.reduced_motion,
+    };
+
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new(&cli.expression, motion, StdRng::from_entropy())
+        .run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+/// 动画中显示的点数。每一帧换一次，和骰子的位置一起决定，不需要随机数生成器。
+fn tumbling_face(frame: u64, index: usize, sides: u32) -> u32 {
+    let mut x = frame.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ (index as u64 + 1);
+    x ^= x >> 29;
+    x = x.wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x ^= x >> 32;
+    (x % u64::from(sides)) as u32 + 1
+}
+
+/// 正在播放的掷骰动画。
+struct Rolling {
+    started: Instant,
+    duration: Duration,
+}
+
+impl Rolling {
+    /// 第 `index` 个骰子（共 `count` 个）停下来的时间，从左到右依次停下。
+    fn settles_at(&self, index: usize, count: usize) -> Duration {
+        // 第一个骰子在一半的时候停下，最后一个在动画结束时停下。
+        let share = (index + 1) as u32 + count as u32;
+        self.duration * share / (2 * count as u32)
+    }
+}
+
+struct App {
+    input: Input,
+    inputs: History,
+    motion: Motion,
+    rng: StdRng,
+    /// 最近一次掷骰，动画结束前不计入历史。
+    current: Option<Roll>,
+    rolling: Option<Rolling>,
+    /// 最新的在前面。
+    history: VecDeque<Roll>,
+    error: Option<String>,
+    exit: bool,
+}
+
+impl App {
+    fn new(expression: &str, motion: Motion, rng: StdRng) -> Self {
+        Self {
+            input: Input::with_value(expression),
+            inputs: History::default(),
+            motion,
+            rng,
+            current: None,
+            rolling: None,
+            history: VecDeque::new(),
+            error: None,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            let now = Instant::now();
+            self.tick(now);
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut(), now))?;
+            let timeout = if self.rolling.is_some() { FRAME } else { IDLE };
+            if events.poll(timeout)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key, Instant::now());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 动画结束后把结果计入历史。
+    fn tick(&mut self, now: Instant) {
+        if let Some(rolling) = &self.rolling {
+            if now.saturating_duration_since(rolling.started) >= rolling.duration {
+                self.settle();
+            }
+        }
+    }
+
+    fn settle(&mut self) {
+        self.rolling = None;
+        if let Some(roll) = &self.current {
+            self.history.push_front(roll.clone());
+            self.history.truncate(HISTORY_LIMIT);
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, now: Instant) {
+        match key.code {
+            KeyCode::Esc => self.exit = true,
+            KeyCode::Char('c') if key.modifiers == KeyModifiers::CONTROL => self.exit = true,
+            KeyCode::Enter => self.roll(now),
+            KeyCode::Up => {
+                if let Some(entry) = self.inputs.older(self.input.value()) {
+                    self.input = Input::with_value(entry);
+                }
+            }
+            KeyCode::Down => {
+                if let Some(entry) = self.inputs.newer() {
+                    self.input = Input::with_value(entry);
+                }
+            }
+            _ => {
+                self.input.handle_key_event(key);
+            }
+        }
+    }
+
+    fn roll(&mut self, now: Instant) {
+        // 上一次的动画还没有播放完时先把它的结果计入历史。
+        if self.rolling.is_some() {
+            self.settle();
+        }
+        let text = self.input.value().trim().to_string();
+        let expression = if text.is_empty() {
+            match &self.current {
+                Some(roll) => Ok(roll.expression.clone()),
+                None => text.parse::<Expression>(),
+            }
+        } else {
+            text.parse()
+        };
+        let expression = match expression {
+            Ok(expression) => expression,
+            Err(error) => {
+                self.error = Some(error);
+                return;
+            }
+        };
+        self.error = None;
+        self.inputs.push(&expression.to_string());
+        self.input = Input::default();
+        self.current = Some(expression.roll(&mut self.rng));
+        self.rolling = self.motion.animate(ROLL_DURATION).map(|duration| Rolling {
+            started: now,
+            duration,
+        });
+        if self.rolling.is_none() {
+            self.settle();
+        }
+    }
+
+    /// 一个骰子：三行高，宽度按面数的位数决定。
+    fn die(face: Option<u32>, sides: u32) -> [Span<'static>; 3] {
+        let width = sides.to_string().len() + 2;
+        let style = match face {
+            None => Style::new().dark_gray(),
+            Some(face) if face == sides => Style::new().light_green().bold(),
+            Some(1) => Style::new().light_red().bold(),
+            Some(_) => Style::new().bold(),
+        };
+        let text = face.map_or_else(|| "·".to_string(), |face| face.to_string());
+        [
+            Span::styled(format!("╭{}╮", "─".repeat(width)), style),
+            Span::styled(format!("│{text:^width$}│"), style),
+            Span::styled(format!("╰{}╯", "─".repeat(width)), style),
+        ]
+    }
+
+    /// 按宽度换行排列骰子和运算符，每一排三行。
+    fn dice_lines(&self, roll: &Roll, width: u16, now: Instant) -> Vec<Line<'static>> {
+        let count = roll.faces.iter().map(Vec::len).sum::<usize>().max(1);
+        let elapsed = self
+            .rolling
+            .as_ref()
+            .map(|rolling| (rolling, now.saturating_duration_since(rolling.started)));
+        let frame = elapsed.map_or(0, |(_, elapsed)| {
+            (elapsed.as_millis() / FRAME.as_millis()) as u64
+        });
+
+        let mut items: Vec<[Span<'static>; 3]> = Vec::new();
+        let mut index = 0;
+        for (position, (term, faces)) in roll.expression.terms.iter().zip(&roll.faces).enumerate() {
+            let sign = match (term.negative, position) {
+                (true, _) => Some("−"),
+                (false, 0) => None,
+                (false, _) => Some("+"),
+            };
+            if let Some(sign) = sign {
+                items.push(["   ".into(), format!(" {sign} ").into(), "   ".into()]);
+            }
+            match term.kind {
+                Kind::Dice { sides, .. } => {
+                    for &face in faces {
+                        let face = match elapsed {
+                            Some((rolling, elapsed))
+                                if elapsed < rolling.settles_at(index, count) =>
+                            {
+                                tumbling_face(frame, index, sides)
+                            }
+                            _ => face,
+                        };
+                        items.push(Self::die(Some(face), sides));
+                        index += 1;
+                    }
+                }
+                Kind::Constant(value) => {
+                    let text = value.to_string();
+                    let blank = " ".repeat(text.len());
+                    items.push([blank.clone().into(), text.bold(), blank.into()]);
+                }
+            }
+        }
+
+        let mut lines = Vec::new();
+        let mut row: [Vec<Span<'static>>; 3] = Default::default();
+        let mut used = 0;
+        for item in items {
+            let item_width = item[0].width() + 1;
+            if used > 0 && used + item_width > usize::from(width) {
+                lines.extend(row.map(Line::from));
+                row = Default::default();
+                used = 0;
+            }
+            for (line, span) in row.iter_mut().zip(item) {
+                line.push(span);
+                line.push(" ".into());
+            }
+            used += item_width;
+        }
+        lines.extend(row.map(Line::from));
+        lines
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, now: Instant) {
+        let [input_area, main, status] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+        Paragraph::new(self.input.line(" ", Style::new()))
+            .block(
+                Block::bordered()
+                    .title(" Dice ")
+                    .title_bottom(
+                        Line::from(" Roll <Enter> Previous <↑/↓> Quit <Esc> ").right_aligned(),
+                    )
+                    .border_style(Style::new().green()),
+            )
+            .render(input_area, buf);
+
+        let [roll_area, history_area] =
+            Layout::horizontal([Constraint::Fill(2), Constraint::Fill(1)]).areas(main);
+        let mut block = Block::bordered();
+        if let Some(roll) = &self.current {
+            let (low, high) = roll.expression.range();
+            let total = if self.rolling.is_some() {
+                " Total … ".to_string()
+            } else {
+                format!(" Total {} ", roll.total())
+            };
+            block = block
+                .title(format!(" {} ", roll.expression).bold())
+                .title(Line::from(format!(" Range {low}–{high} ")).right_aligned())
+                .title_bottom(Line::from(total.bold()).centered());
+        }
+        let inner = block.inner(roll_area);
+        block.render(roll_area, buf);
+        match &self.current {
+            Some(roll) => {
+                Paragraph::new(self.dice_lines(roll, inner.width, now)).render(inner, buf)
+            }
+            None => Paragraph::new("Type an expression like 3d6+2 and press Enter".dim())
+                .render(inner, buf),
+        }
+
+        let items: Vec<ListItem> = self
+            .history
+            .iter()
+            .map(|roll| {
+                ListItem::new(Line::from(vec![
+                    format!("{:>4} ", roll.total()).bold(),
+                    roll.expression.to_string().into(),
+                    format!("  {}", roll.breakdown()).dim(),
+                ]))
+            })
+            .collect();
+        Widget::render(
+            List::new(items).block(Block::bordered().title(" History ")),
+            history_area,
+            buf,
+        );
+
+        let status_line = match &self.error {
+            Some(error) => Line::from(error.clone().red()),
+            None => match self.history.len() {
+                1 => Line::from("1 roll".dim()),
+                count => Line::from(format!("{count} rolls").dim()),
+            },
+        };
+        Paragraph::new(status_line).render(status, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn rows(app: &App, width: u16, height: u16, now: Instant) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf, now))
+    }
+
+    fn app(motion: Motion) -> App {
+        App::new("3d6+2", motion, StdRng::seed_from_u64(2))
+    }
+
+    #[test]
+    fn roll_without_animation() {
+        let now = Instant::now();
+        let mut app = app(Motion::reduced());
+        app.handle_key(KeyCode::Enter.into(), now);
+        let roll = app.current.clone().unwrap();
+        assert!(app.rolling.is_none());
+        assert_eq!(app.history.front(), Some(&roll));
+        assert_eq!(app.input.value(), "");
+
+        let screen = rows(&app, 60, 12, now);
+        let faces = &roll.faces[0];
+        assert_eq!(
+            screen[3],
+            "┌ 3d6+2 ─────────────────── Range 5–20 ┐┌ History ─────────┐"
+        );
+        assert_eq!(
+            screen[5],
+            format!(
+                "││ {} │ │ {} │ │ {} │  +  2               ││                  │",
+                faces[0], faces[1], faces[2]
+            )
+        );
+        assert!(screen[10].contains(&format!(" Total {} ", roll.total())));
+        assert!(screen[4].contains(&format!("{:>4} 3d6+2", roll.total())));
+        assert_eq!(screen[11].trim_end(), "1 roll");
+
+        // 输入为空时重掷上一次的表达式。
+        app.handle_key(KeyCode::Enter.into(), now);
+        assert_eq!(app.history.len(), 2);
+        assert_eq!(app.current.as_ref().unwrap().expression, roll.expression);
+    }
+
+    #[test]
+    fn animation_settles_left_to_right() {
+        let start = Instant::now();
+        let mut app = App::new("4d6", Motion::default(), StdRng::seed_from_u64(2));
+        app.handle_key(KeyCode::Enter.into(), start);
+        let roll = app.current.clone().unwrap();
+        assert!(app.rolling.is_some());
+        assert!(app.history.is_empty());
+        assert!(rows(&app, 60, 12, start)[10].contains(" Total … "));
+
+        // 到一半多一点时第一个骰子已经停下。
+        let rolling = app.rolling.as_ref().unwrap();
+        let first = rolling.settles_at(0, 4);
+        assert_eq!(first, ROLL_DURATION * 5 / 8);
+        assert_eq!(rolling.settles_at(3, 4), ROLL_DURATION);
+        let screen = rows(&app, 60, 12, start + first);
+        assert!(screen[5].starts_with(&format!("││ {} │", roll.faces[0][0])));
+
+        app.tick(start + ROLL_DURATION);
+        assert!(app.rolling.is_none());
+        assert_eq!(app.history.front(), Some(&roll));
+
+        // 动画中再次掷骰时上一次的结果先计入历史。
+        app.handle_key(KeyCode::Enter.into(), start + ROLL_DURATION);
+        app.handle_key(KeyCode::Enter.into(), start + ROLL_DURATION);
+        assert_eq!(app.history.len(), 2);
+        assert!(app.rolling.is_some());
+    }
+
+    #[test]
+    fn errors_and_input_history() {
+        let now = Instant::now();
+        let mut app = App::new("", Motion::reduced(), StdRng::seed_from_u64(2));
+        app.handle_key(KeyCode::Enter.into(), now);
+        assert_eq!(
+            app.error.as_deref(),
+            Some("type a dice expression like 3d6+2")
+        );
+
+        testing::type_text("2d6x", |key| app.handle_key(key, now));
+        app.handle_key(KeyCode::Enter.into(), now);
+        assert_eq!(app.error.as_deref(), Some("unexpected 'x' at position 4"));
+        assert_eq!(
+            rows(&app, 60, 12, now)[11].trim_end(),
+            "unexpected 'x' at position 4"
+        );
+        assert!(app.current.is_none());
+
+        app.handle_key(KeyCode::Backspace.into(), now);
+        app.handle_key(KeyCode::Enter.into(), now);
+        testing::type_text("1D20 - 1", |key| app.handle_key(key, now));
+        app.handle_key(KeyCode::Enter.into(), now);
+        assert_eq!(app.error, None);
+        assert_eq!(app.history.len(), 2);
+
+        app.handle_key(KeyCode::Up.into(), now);
+        assert_eq!(app.input.value(), "d20-1");
+        app.handle_key(KeyCode::Up.into(), now);
+        assert_eq!(app.input.value(), "2d6");
+        app.handle_key(KeyCode::Esc.into(), now);
+        assert!(app.exit);
+    }
+}
@@ -0,0 +1,376 @@
+//! 磁盘占用分析演示，类似 `ncdu`：在后台线程中扫描目录树，按大小列出当前目录的内容，
+//! 并用横条表示各项在当前目录中所占的比例。扫描没有完成时界面照常响应，数字会持续增长。
+//!
+//! 按键：
+//!
+//! ```text
+//! Up / Down            选择            Enter / Right   进入目录
+//! Backspace / Left     返回上级目录    d               删除（需要确认）
+//! q                    退出
+//! ```
+//!
+//! 删除会直接从磁盘上移除文件或整个目录，不经过回收站。
+
+use std::{fs, path::PathBuf, thread, time::Duration};
+
+use clap::Parser;
+use color_eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, List, ListState, Paragraph},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+};
+
+use crate::scan::{Scanner, Tree};
+
+mod scan;
+
+/// 扫描期间刷新界面的间隔。
+const SCAN_REFRESH: Duration = Duration::from_millis(100);
+
+/// 比例横条的宽度。
+const BAR_WIDTH: usize = 20;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// 要分析的目录。
+    #[arg(default_value = ".")]
+    path: PathBuf,
+
+    /// 扫描使用的线程数，默认和 CPU 数量相同。
+    #[arg(long)]
+    threads: Option<usize>,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let threads = cli
+        .threads
+        .unwrap_or_else(|| thread::available_parallelism().map_or(4, |n| n.get()));
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new(cli.path, threads).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+struct App {
+    tree: Tree,
+    scanner: Scanner,
+    /// 正在查看的目录。
+    current: usize,
+    list: ListState,
+    /// 等待确认删除的节点。
+    confirm: Option<usize>,
+    /// 显示在状态栏中的提示或错误，下一次按键时清除。
+    message: Option<String>,
+    exit: bool,
+}
+
+impl App {
+    fn new(root: PathBuf, threads: usize) -> Self {
+        let scanner = Scanner::start(threads);
+        scanner.submit(0, root.clone());
+        Self {
+            tree: Tree::new(root),
+            scanner,
+            current: 0,
+            list: ListState::default().with_selected(Some(0)),
+            confirm: None,
+            message: None,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            self.receive();
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut()))?;
+            // 扫描期间定时刷新，扫描结束后只在按键时重新绘制。
+            if self.tree.is_scanning() && !events.poll(SCAN_REFRESH)? {
+                continue;
+            }
+            if let Event::Key(key) = events.read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 把工作线程已经读取的目录加入树中，并提交新发现的子目录。
+    fn receive(&mut self) {
+        while let Some(scanned) = self.scanner.try_recv() {
+            self.insert(scanned);
+        }
+    }
+
+    fn insert(&mut self, scanned: scan::Scanned) {
+        for (dir, path) in self.tree.insert(scanned) {
+            self.scanner.submit(dir, path);
+        }
+    }
+
+    fn selected(&self) -> Option<usize> {
+        let children = self.tree.children(self.current);
+        children.get(self.list.selected()?).copied()
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        self.message = None;
+        if key.code == KeyCode::Char('c') && key.modifiers == KeyModifiers::CONTROL {
+            self.exit = true;
+            return;
+        }
+        if let Some(id) = self.confirm.take() {
+            if key.code == KeyCode::Char('y') {
+                self.delete(id);
+            }
+            return;
+        }
+        let count = self.tree.node(self.current).children.len();
+        let selected = self.list.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
+            KeyCode::Up | KeyCode::Char('k') => self.list.select(Some(selected.saturating_sub(1))),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.list
+                    .select(Some((selected + 1).min(count.saturating_sub(1))));
+            }
+            KeyCode::Enter | KeyCode::Right => {
+                if let Some(id) = self.selected().filter(|&id| self.tree.node(id).dir) {
+                    self.current = id;
+                    self.list.select(Some(0));
+                }
+            }
+            KeyCode::Backspace | KeyCode::Left => {
+                if let Some(parent) = self.tree.node(self.current).parent {
+                    // 回到上级目录时选中刚才所在的目录。
+                    let position = self
+                        .tree
+                        .children(parent)
+                        .iter()
+                        .position(|&id| id == self.current);
+                    self.current = parent;
+                    self.list.select(position.or(Some(0)));
+                }
+            }
+            KeyCode::Char('d') => match self.selected() {
+                // 扫描期间删除，工作线程可能还在读取要删除的目录。
+                Some(_) if self.tree.is_scanning() => {
+                    self.message = Some("wait for the scan to finish before deleting".into())
+                }
+                Some(id) => self.confirm = Some(id),
+                None => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn delete(&mut self, id: usize) {
+        let node = self.tree.node(id);
+        let path = self.tree.path(id);
+        let result = if node.dir {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        match result {
+            Ok(()) => {
+                self.message = Some(format!(
+                    "deleted {} ({})",
+                    path.display(),
+                    format_size(node.size)
+                ));
+                self.tree.remove(id);
+                let count = self.tree.node(self.current).children.len();
+                let selected = self.list.selected().unwrap_or(0);
+                self.list
+                    .select(Some(selected.min(count.saturating_sub(1))));
+            }
+            Err(error) => {
+                self.message = Some(format!("deleting {} failed: {error}", path.display()))
+            }
+        }
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let current = self.tree.node(self.current);
+        let items: Vec<Line> = self
+            .tree
+            .children(self.current)
+            .into_iter()
+            .map(|id| entry_line(self.tree.node(id), current.size))
+            .collect();
+        let list = List::new(items)
+            .highlight_style(Style::new().reversed())
+            .block(
+                Block::bordered()
+                    .title(format!(" {} ", self.tree.path(self.current).display()).bold())
+                    .title_bottom(Line::from(" Open <Enter> Delete <D> Quit <Q> ").centered()),
+            );
+        StatefulWidget::render(list, main, buf, &mut self.list);
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        if let Some(id) = self.confirm {
+            let node = self.tree.node(id);
+            return Line::from(format!(
+                "Delete {} ({})? <Y>/<N>",
+                self.tree.path(id).display(),
+                format_size(node.size)
+            ))
+            .red()
+            .bold();
+        }
+        if let Some(message) = &self.message {
+            return Line::from(message.clone().yellow());
+        }
+        let root = self.tree.node(0);
+        let mut spans = vec![
+            if self.tree.is_scanning() {
+                "Scanning… ".yellow()
+            } else {
+                "Total ".into()
+            },
+            format_size(root.size).bold(),
+            format!(" in {} items", root.items).into(),
+        ];
+        if self.tree.errors() > 0 {
+            spans.push(format!(", {} unreadable", self.tree.errors()).red());
+        }
+        Line::from(spans)
+    }
+}
+
+/// 一行：大小、占当前目录的比例横条和名称，目录名后面加 `/`。
+fn entry_line(node: &scan::Node, total: u64) -> Line<'static> {
+    let filled = if total == 0 {
+        0
+    } else {
+        ((node.size as f64 / total as f64) * BAR_WIDTH as f64).round() as usize
+    };
+    let name = if node.dir {
+        Span::from(format!("{}/", node.name)).blue().bold()
+    } else {
+        Span::from(node.name.clone())
+    };
+    Line::from(vec![
+        format!("{:>10} ", format_size(node.size)).into(),
+        "█".repeat(filled).green(),
+        "░".repeat(BAR_WIDTH - filled).dark_gray(),
+        " ".into(),
+        name,
+    ])
+}
+
+/// 用二进制前缀显示大小，例如 `1.5 KiB`。
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+    if size < 1024 {
+        return format!("{size} B");
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process;
+
+    use ratatui_common::testing;
+
+    use super::*;
+
+    /// 在临时目录中建立一个小的目录树。
+    fn fixture(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("disk-usage-{name}-{}", process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("logs/old")).unwrap();
+        fs::write(root.join("notes.txt"), [0; 300]).unwrap();
+        fs::write(root.join("logs/today.log"), [0; 600]).unwrap();
+        fs::write(root.join("logs/old/june.log"), [0; 100]).unwrap();
+        root
+    }
+
+    fn scan(root: PathBuf) -> App {
+        let mut app = App::new(root, 2);
+        while app.tree.is_scanning() {
+            let scanned = app.scanner.recv().unwrap();
+            app.insert(scanned);
+        }
+        app
+    }
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 << 30), "5.0 GiB");
+    }
+
+    #[test]
+    fn drill_down() {
+        let root = fixture("drill");
+        let mut app = scan(root.clone());
+        let rows = rows(&mut app, 44, 5);
+        assert_eq!(rows[1], "│     700 B ██████████████░░░░░░ logs/     │");
+        assert_eq!(rows[2], "│     300 B ██████░░░░░░░░░░░░░░ notes.txt │");
+        assert_eq!(rows[4], "Total 1000 B in 5 items                     ");
+
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(app.tree.path(app.current), root.join("logs"));
+        app.handle_key(KeyCode::Down.into());
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(app.tree.path(app.current), root.join("logs/old"));
+        app.handle_key(KeyCode::Backspace.into());
+        assert_eq!(app.list.selected(), Some(1));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn delete_with_confirmation() {
+        let root = fixture("delete");
+        let mut app = scan(root.clone());
+        // 不确认时什么都不删除。
+        app.handle_key(KeyCode::Char('d').into());
+        assert_eq!(app.confirm, Some(app.selected().unwrap()));
+        app.handle_key(KeyCode::Char('n').into());
+        assert!(root.join("logs").exists());
+
+        app.handle_key(KeyCode::Char('d').into());
+        app.handle_key(KeyCode::Char('y').into());
+        assert!(!root.join("logs").exists());
+        assert_eq!(app.tree.node(0).size, 300);
+        assert_eq!(app.tree.node(0).items, 1);
+        assert_eq!(
+            app.tree.path(app.selected().unwrap()),
+            root.join("notes.txt")
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,268 @@
+//! 在后台线程中扫描目录，结果汇总到 [`Tree`]。
+//!
+//! 每个工作线程一次读取一个目录，把其中的文件大小和子目录交回主线程；主线程把子目录加入树中，
+//! 再作为新的任务交给工作线程。树只由主线程修改，不需要加锁。
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+/// 树中的一个文件或目录。目录的大小是其中所有文件大小的总和。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub name: String,
+    pub size: u64,
+    pub dir: bool,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    /// 目录下一共有多少个文件和目录（不包括它自己）。
+    pub items: u64,
+}
+
+/// 一个目录的读取结果。
+#[derive(Debug)]
+pub struct Scanned {
+    pub dir: usize,
+    pub result: io::Result<Vec<Entry>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    pub size: u64,
+    pub dir: bool,
+}
+
+/// 扫描得到的目录树。节点保存在数组中，用下标互相引用，根节点是 0。
+#[derive(Debug)]
+pub struct Tree {
+    root: PathBuf,
+    nodes: Vec<Node>,
+    /// 已经交给工作线程但还没有收到结果的目录数。
+    pending: usize,
+    /// 无法读取的目录数。
+    errors: usize,
+}
+
+impl Tree {
+    /// 新的树只有根目录，根目录等待扫描。
+    pub fn new(root: PathBuf) -> Self {
+        let node = Node {
+            name: root.display().to_string(),
+            size: 0,
+            dir: true,
+            parent: None,
+            children: Vec::new(),
+            items: 0,
+        };
+        Self {
+            root,
+            nodes: vec![node],
+            pending: 1,
+            errors: 0,
+        }
+    }
+
+    pub fn node(&self, id: usize) -> &Node {
+        &self.nodes[id]
+    }
+
+    pub fn is_scanning(&self) -> bool {
+        self.pending > 0
+    }
+
+    pub fn errors(&self) -> usize {
+        self.errors
+    }
+
+    pub fn path(&self, id: usize) -> PathBuf {
+        let mut names = Vec::new();
+        let mut current = id;
+        while let Some(parent) = self.nodes[current].parent {
+            names.push(self.nodes[current].name.as_str());
+            current = parent;
+        }
+        let mut path = self.root.clone();
+        path.extend(names.iter().rev());
+        path
+    }
+
+    /// 子节点，大的在前。
+    pub fn children(&self, id: usize) -> Vec<usize> {
+        let mut children = self.nodes[id].children.clone();
+        children.sort_by(|&a, &b| {
+            let (a, b) = (&self.nodes[a], &self.nodes[b]);
+            b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name))
+        });
+        children
+    }
+
+    /// 加入一个目录的读取结果，返回需要继续扫描的子目录。
+    pub fn insert(&mut self, scanned: Scanned) -> Vec<(usize, PathBuf)> {
+        self.pending -= 1;
+        let entries = match scanned.result {
+            Ok(entries) => entries,
+            Err(_) => {
+                self.errors += 1;
+                return Vec::new();
+            }
+        };
+        let mut dirs = Vec::new();
+        let mut size = 0;
+        for entry in &entries {
+            let id = self.nodes.len();
+            self.nodes.push(Node {
+                name: entry.name.clone(),
+                size: entry.size,
+                dir: entry.dir,
+                parent: Some(scanned.dir),
+                children: Vec::new(),
+                items: 0,
+            });
+            self.nodes[scanned.dir].children.push(id);
+            size += entry.size;
+            if entry.dir {
+                dirs.push((id, self.path(id)));
+            }
+        }
+        self.pending += dirs.len();
+        self.add_to_ancestors(scanned.dir, size as i64, entries.len() as i64);
+        dirs
+    }
+
+    /// 从树中去掉一个节点，例如在磁盘上删除之后。
+    pub fn remove(&mut self, id: usize) {
+        let Some(parent) = self.nodes[id].parent else {
+            return;
+        };
+        self.nodes[parent].children.retain(|&child| child != id);
+        let node = &self.nodes[id];
+        let (size, items) = (node.size as i64, node.items as i64 + 1);
+        self.add_to_ancestors(parent, -size, -items);
+    }
+
+    fn add_to_ancestors(&mut self, id: usize, size: i64, items: i64) {
+        let mut current = Some(id);
+        while let Some(id) = current {
+            let node = &mut self.nodes[id];
+            node.size = node.size.saturating_add_signed(size);
+            node.items = node.items.saturating_add_signed(items);
+            current = node.parent;
+        }
+    }
+}
+
+/// 读取目录的工作线程。任务队列关闭（`Scanner` 被丢弃）后线程退出。
+pub struct Scanner {
+    jobs: Sender<(usize, PathBuf)>,
+    results: Receiver<Scanned>,
+}
+
+impl Scanner {
+    pub fn start(threads: usize) -> Self {
+        let (jobs, job_receiver) = mpsc::channel::<(usize, PathBuf)>();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let (result_sender, results) = mpsc::channel();
+        for _ in 0..threads.max(1) {
+            let job_receiver = Arc::clone(&job_receiver);
+            let result_sender = result_sender.clone();
+            thread::spawn(move || loop {
+                // 锁只在取任务时持有，读取目录时其他线程可以取下一个任务。
+                let job = job_receiver.lock().expect("scanner poisoned").recv();
+                let Ok((dir, path)) = job else {
+                    break;
+                };
+                let result = read_dir(&path);
+                if result_sender.send(Scanned { dir, result }).is_err() {
+                    break;
+                }
+            });
+        }
+        Self { jobs, results }
+    }
+
+    pub fn submit(&self, dir: usize, path: PathBuf) {
+        // 工作线程只在任务队列关闭后退出，这里不会失败。
+        let _ = self.jobs.send((dir, path));
+    }
+
+    pub fn try_recv(&self) -> Option<Scanned> {
+        self.results.try_recv().ok()
+    }
+
+    /// 阻塞直到下一个结果，测试用来等待扫描结束。
+    #[cfg(test)]
+    pub fn recv(&self) -> Option<Scanned> {
+        self.results.recv().ok()
+    }
+}
+
+/// 读取一个目录。符号链接不跟随，按链接本身的大小计算。
+fn read_dir(path: &Path) -> io::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        entries.push(Entry {
+            name: entry.file_name().to_string_lossy().into_owned(),
+            size: if metadata.is_dir() { 0 } else { metadata.len() },
+            dir: metadata.is_dir(),
+        });
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, size: u64, dir: bool) -> Entry {
+        Entry {
+            name: name.into(),
+            size,
+            dir,
+        }
+    }
+
+    #[test]
+    fn sizes_add_up() {
+        let mut tree = Tree::new("/data".into());
+        let dirs = tree.insert(Scanned {
+            dir: 0,
+            result: Ok(vec![entry("a.txt", 10, false), entry("sub", 0, true)]),
+        });
+        assert_eq!(dirs, [(2, PathBuf::from("/data/sub"))]);
+        assert!(tree.is_scanning());
+        tree.insert(Scanned {
+            dir: 2,
+            result: Ok(vec![entry("big", 100, false), entry("small", 1, false)]),
+        });
+        assert!(!tree.is_scanning());
+        assert_eq!(tree.node(0).size, 111);
+        assert_eq!(tree.node(0).items, 4);
+        assert_eq!(tree.children(0), [2, 1]);
+        assert_eq!(tree.children(2), [3, 4]);
+
+        tree.remove(2);
+        assert_eq!(tree.node(0).size, 10);
+        assert_eq!(tree.node(0).items, 1);
+        assert_eq!(tree.children(0), [1]);
+    }
+
+    #[test]
+    fn unreadable_directory() {
+        let mut tree = Tree::new("/data".into());
+        tree.insert(Scanned {
+            dir: 0,
+            result: Err(io::ErrorKind::PermissionDenied.into()),
+        });
+        assert!(!tree.is_scanning());
+        assert_eq!(tree.errors(), 1);
+    }
+}
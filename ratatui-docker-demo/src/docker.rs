@@ -0,0 +1,198 @@
+//! 在后台线程的 tokio 运行时中访问 Docker API。
+//!
+//! 界面线程通过 [`Command`] 发出请求，结果以 [`Update`] 的形式通过标准库的通道送回，
+//! 主循环不需要是异步的。
+
+use std::{sync::mpsc, thread, time::Duration};
+
+use bollard::{
+    container::LogOutput,
+    query_parameters::{ListContainersOptions, LogsOptionsBuilder},
+    Docker,
+};
+use futures_util::StreamExt;
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+};
+
+/// 容器列表的刷新间隔。
+const REFRESH: Duration = Duration::from_secs(2);
+
+/// 跟随日志时先显示的历史行数。
+const LOG_TAIL: &str = "200";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Start,
+    Stop,
+    Restart,
+}
+
+impl Action {
+    pub fn past_tense(self) -> &'static str {
+        match self {
+            Action::Start => "started",
+            Action::Stop => "stopped",
+            Action::Restart => "restarted",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// 跟随这个容器的日志，之前跟随的日志流停止。
+    Follow(String),
+    Run(Action, String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Container {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    /// `running`、`exited` 等。
+    pub state: String,
+    /// 给人看的状态，例如 `Up 3 hours`。
+    pub status: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Update {
+    Containers(Vec<Container>),
+    Log {
+        id: String,
+        line: String,
+        stderr: bool,
+    },
+    /// 日志流结束，例如容器停止。
+    LogEnded(String),
+    Done(String),
+    Error(String),
+    /// 无法连接 Docker，后台线程退出。
+    Disconnected(String),
+}
+
+/// 启动后台线程，返回发送命令的一端。
+pub fn spawn(updates: mpsc::Sender<Update>) -> UnboundedSender<Command> {
+    let (commands, receiver) = unbounded_channel();
+    thread::spawn(move || {
+        let runtime = match tokio::runtime::Runtime::new() {
+            Ok(runtime) => runtime,
+            Err(error) => {
+                let _ = updates.send(Update::Disconnected(error.to_string()));
+                return;
+            }
+        };
+        runtime.block_on(serve(receiver, updates));
+    });
+    commands
+}
+
+async fn serve(mut commands: UnboundedReceiver<Command>, updates: mpsc::Sender<Update>) {
+    let docker = match Docker::connect_with_local_defaults() {
+        Ok(docker) => docker,
+        Err(error) => {
+            let _ = updates.send(Update::Disconnected(error.to_string()));
+            return;
+        }
+    };
+    let mut refresh = tokio::time::interval(REFRESH);
+    let mut logs: Option<JoinHandle<()>> = None;
+    loop {
+        tokio::select! {
+            _ = refresh.tick() => {
+                let options = ListContainersOptions { all: true, ..Default::default() };
+                let update = match docker.list_containers(Some(options)).await {
+                    Ok(containers) => Update::Containers(containers.into_iter().map(container).collect()),
+                    // 第一次列出容器就失败，通常是 Docker 没有运行。
+                    Err(error) => Update::Error(error.to_string()),
+                };
+                if updates.send(update).is_err() {
+                    break;
+                }
+            }
+            command = commands.recv() => match command {
+                Some(Command::Follow(id)) => {
+                    if let Some(previous) = logs.take() {
+                        previous.abort();
+                    }
+                    logs = Some(tokio::spawn(follow(docker.clone(), id, updates.clone())));
+                }
+                Some(Command::Run(action, id)) => {
+                    let (docker, updates) = (docker.clone(), updates.clone());
+                    tokio::spawn(async move {
+                        let result = match action {
+                            Action::Start => docker.start_container(&id, None).await,
+                            Action::Stop => docker.stop_container(&id, None).await,
+                            Action::Restart => docker.restart_container(&id, None).await,
+                        };
+                        let short = short_id(&id);
+                        let _ = updates.send(match result {
+                            Ok(()) => Update::Done(format!("{} {short}", action.past_tense())),
+                            Err(error) => Update::Error(format!("{short}: {error}")),
+                        });
+                    });
+                    // 尽快显示新的状态，不等下一次定时刷新。
+                    refresh.reset_immediately();
+                }
+                None => break,
+            },
+        }
+    }
+}
+
+async fn follow(docker: Docker, id: String, updates: mpsc::Sender<Update>) {
+    let options = LogsOptionsBuilder::new()
+        .follow(true)
+        .stdout(true)
+        .stderr(true)
+        .tail(LOG_TAIL)
+        .build();
+    let mut stream = docker.logs(&id, Some(options));
+    while let Some(item) = stream.next().await {
+        let (stderr, message) = match item {
+            Ok(LogOutput::StdErr { message }) => (true, message),
+            Ok(LogOutput::StdOut { message } | LogOutput::Console { message }) => (false, message),
+            Ok(LogOutput::StdIn { .. }) => continue,
+            Err(error) => {
+                let _ = updates.send(Update::Error(error.to_string()));
+                break;
+            }
+        };
+        for line in String::from_utf8_lossy(&message).lines() {
+            let update = Update::Log {
+                id: id.clone(),
+                line: line.to_string(),
+                stderr,
+            };
+            if updates.send(update).is_err() {
+                return;
+            }
+        }
+    }
+    let _ = updates.send(Update::LogEnded(id));
+}
+
+fn container(summary: bollard::models::ContainerSummary) -> Container {
+    let name = summary
+        .names
+        .and_then(|names| names.into_iter().next())
+        .unwrap_or_default();
+    Container {
+        id: summary.id.unwrap_or_default(),
+        // Docker 返回的名称以 `/` 开头。
+        name: name.trim_start_matches('/').to_string(),
+        image: summary.image.unwrap_or_default(),
+        state: summary
+            .state
+            .map(|state| state.to_string())
+            .unwrap_or_default(),
+        status: summary.status.unwrap_or_default(),
+    }
+}
+
+/// 和 `docker ps` 一样显示 ID 的前 12 个字符。
+pub fn short_id(id: &str) -> &str {
+    id.get(..12).unwrap_or(id)
+}
@@ -0,0 +1,394 @@
+//! Docker 容器管理演示：列出所有容器，右侧实时显示所选容器的日志，并可以启动、停止和重启容器。
+//!
+//! Docker API 是异步的（`bollard`），在后台线程的 tokio 运行时中调用，结果通过通道送回界面，
+//! 见 `docker` 模块。连接使用本机默认的 Docker 套接字或命名管道。
+//!
+//! 按键：`Up` / `Down` 选择容器，`s` 启动，`x` 停止，`r` 重启，`q` 退出。
+
+use std::{
+    collections::VecDeque,
+    sync::mpsc::{self, Receiver},
+    time::Duration,
+};
+
+use color_eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Paragraph, Row, Table, TableState},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::docker::{Action, Command, Container, Update};
+
+mod docker;
+
+/// 等待按键的最长时间，之后处理后台送回的结果。
+const REFRESH: Duration = Duration::from_millis(100);
+
+/// 日志面板保留的行数。
+const LOG_LINES: usize = 1000;
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    let (sender, updates) = mpsc::channel();
+    let commands = docker::spawn(sender);
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new(commands, updates).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Connection {
+    /// 还没有收到第一次的容器列表。
+    Connecting,
+    Connected,
+    Failed(String),
+}
+
+struct App {
+    commands: UnboundedSender<Command>,
+    updates: Receiver<Update>,
+    connection: Connection,
+    containers: Vec<Container>,
+    table: TableState,
+    /// 正在跟随日志的容器。
+    following: Option<String>,
+    logs: VecDeque<(bool, String)>,
+    /// 最近一次操作的结果，`Err` 显示为红色。
+    message: Option<Result<String, String>>,
+    exit: bool,
+}
+
+impl App {
+    fn new(commands: UnboundedSender<Command>, updates: Receiver<Update>) -> Self {
+        Self {
+            commands,
+            updates,
+            connection: Connection::Connecting,
+            containers: Vec::new(),
+            table: TableState::default().with_selected(Some(0)),
+            following: None,
+            logs: VecDeque::new(),
+            message: None,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            while let Ok(update) = self.updates.try_recv() {
+                self.update(update);
+            }
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut()))?;
+            if events.poll(REFRESH)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key.code);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, update: Update) {
+        match update {
+            Update::Containers(containers) => {
+                // 刷新后仍然选中同一个容器。
+                let selected = self.selected().map(|container| container.id.clone());
+                self.containers = containers;
+                let position = selected
+                    .and_then(|id| self.containers.iter().position(|c| c.id == id))
+                    .unwrap_or(0);
+                self.table.select(Some(position));
+                if self.connection != Connection::Connected {
+                    self.connection = Connection::Connected;
+                    self.message = None;
+                }
+                self.follow_selected();
+            }
+            Update::Log { id, line, stderr } => {
+                if self.following.as_ref() == Some(&id) {
+                    if self.logs.len() == LOG_LINES {
+                        self.logs.pop_front();
+                    }
+                    self.logs.push_back((stderr, line));
+                }
+            }
+            // 容器重新启动后要重新建立日志流。
+            Update::LogEnded(id) => {
+                if self.following.as_ref() == Some(&id) {
+                    self.following = None;
+                }
+            }
+            Update::Done(message) => self.message = Some(Ok(message)),
+            // 还没有连接成功时出错，多半是 Docker 没有运行。后台会继续重试。
+            Update::Error(message) if self.connection != Connection::Connected => {
+                self.connection = Connection::Failed(message)
+            }
+            Update::Error(message) => self.message = Some(Err(message)),
+            Update::Disconnected(message) => self.connection = Connection::Failed(message),
+        }
+    }
+
+    fn selected(&self) -> Option<&Container> {
+        self.containers.get(self.table.selected()?)
+    }
+
+    /// 所选容器正在运行且还没有跟随它的日志时，开始跟随。
+    fn follow_selected(&mut self) {
+        let Some(container) = self.selected() else {
+            return;
+        };
+        if self.following.as_ref() == Some(&container.id) || container.state != "running" {
+            return;
+        }
+        let id = container.id.clone();
+        self.logs.clear();
+        self.following = Some(id.clone());
+        let _ = self.commands.send(Command::Follow(id));
+    }
+
+    fn handle_key(&mut self, code: KeyCode) {
+        let selected = self.table.selected().unwrap_or(0);
+        let last = self.containers.len().saturating_sub(1);
+        let action = match code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.exit = true;
+                return;
+            }
+            KeyCode::Up => {
+                self.table.select(Some(selected.saturating_sub(1)));
+                self.follow_selected();
+                return;
+            }
+            KeyCode::Down => {
+                self.table.select(Some((selected + 1).min(last)));
+                self.follow_selected();
+                return;
+            }
+            KeyCode::Char('s') => Action::Start,
+            KeyCode::Char('x') => Action::Stop,
+            KeyCode::Char('r') => Action::Restart,
+            _ => return,
+        };
+        if let Some(container) = self.selected() {
+            let id = container.id.clone();
+            self.message = Some(Ok(format!("{:?} {}…", action, container.name)));
+            let _ = self.commands.send(Command::Run(action, id));
+        }
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let [list_area, logs_area] =
+            Layout::horizontal([Constraint::Fill(2), Constraint::Fill(3)]).areas(main);
+
+        let rows: Vec<Row> = self
+            .containers
+            .iter()
+            .map(|container| {
+                let state = match container.state.as_str() {
+                    "running" => "●".green(),
+                    "paused" | "restarting" => "●".yellow(),
+                    _ => "○".dark_gray(),
+                };
+                Row::new(vec![
+                    Line::from(state),
+                    Line::from(container.name.clone()),
+                    Line::from(container.status.clone().dim()),
+                ])
+            })
+            .collect();
+        let widths = [
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(12),
+        ];
+        let table =
+            Table::new(rows, widths)
+                .highlight_style(Style::new().reversed())
+                .block(Block::bordered().title(" Containers ".bold()).title_bottom(
+                    Line::from(" Start <S> Stop <X> Restart <R> Quit <Q> ").centered(),
+                ));
+        StatefulWidget::render(table, list_area, buf, &mut self.table);
+
+        // 只显示最后几行，新日志出现在底部。
+        let height = logs_area.height.saturating_sub(2) as usize;
+        let lines: Vec<Line> = self
+            .logs
+            .iter()
+            .skip(self.logs.len().saturating_sub(height))
+            .map(|(stderr, line)| {
+                if *stderr {
+                    Line::from(line.clone().red())
+                } else {
+                    Line::from(line.clone())
+                }
+            })
+            .collect();
+        let title = match self.selected() {
+            Some(container) => format!(" Logs: {} ({}) ", container.name, container.image),
+            None => " Logs ".into(),
+        };
+        Paragraph::new(lines)
+            .block(Block::bordered().title(title))
+            .render(logs_area, buf);
+
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        match &self.connection {
+            Connection::Connecting => return Line::from("Connecting to Docker…".yellow()),
+            Connection::Failed(error) => {
+                return Line::from(format!("Cannot connect to Docker: {error}").red())
+            }
+            Connection::Connected => {}
+        }
+        match &self.message {
+            Some(Ok(message)) => Line::from(message.clone().green()),
+            Some(Err(message)) => Line::from(message.clone().red()),
+            None => {
+                let running = self
+                    .containers
+                    .iter()
+                    .filter(|container| container.state == "running")
+                    .count();
+                Line::from(format!(
+                    "{} containers, {running} running",
+                    self.containers.len()
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    use super::*;
+
+    fn container(id: &str, name: &str, state: &str) -> Container {
+        Container {
+            id: id.into(),
+            name: name.into(),
+            image: "nginx".into(),
+            state: state.into(),
+            status: if state == "running" {
+                "Up 2 hours".into()
+            } else {
+                "Exited (0)".into()
+            },
+        }
+    }
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    #[test]
+    fn follows_selected_container() {
+        let (commands, mut sent) = unbounded_channel();
+        let (_updates, receiver) = mpsc::channel();
+        let mut app = App::new(commands, receiver);
+        assert!(rows(&mut app, 60, 6)[5].starts_with("Connecting to Docker…"));
+        app.update(Update::Error("connection refused".into()));
+        assert!(
+            rows(&mut app, 60, 6)[5].starts_with("Cannot connect to Docker: connection refused")
+        );
+
+        app.update(Update::Containers(vec![
+            container("aaa", "web", "running"),
+            container("bbb", "db", "exited"),
+        ]));
+        assert_eq!(sent.try_recv(), Ok(Command::Follow("aaa".into())));
+        app.update(Update::Log {
+            id: "aaa".into(),
+            line: "GET /".into(),
+            stderr: false,
+        });
+        // 其他容器的日志不显示。
+        app.update(Update::Log {
+            id: "zzz".into(),
+            line: "stale".into(),
+            stderr: false,
+        });
+        assert_eq!(app.logs, [(false, "GET /".to_string())]);
+
+        // 停止的容器没有日志可以跟随。
+        app.handle_key(KeyCode::Down);
+        assert!(sent.try_recv().is_err());
+        app.handle_key(KeyCode::Char('s'));
+        assert_eq!(
+            sent.try_recv(),
+            Ok(Command::Run(Action::Start, "bbb".into()))
+        );
+
+        // 刷新后选择仍然跟着同一个容器。
+        app.update(Update::Containers(vec![
+            container("ccc", "cache", "running"),
+            container("aaa", "web", "running"),
+            container("bbb", "db", "running"),
+        ]));
+        assert_eq!(app.selected().unwrap().id, "bbb");
+        assert_eq!(sent.try_recv(), Ok(Command::Follow("bbb".into())));
+        assert!(app.logs.is_empty());
+    }
+
+    #[test]
+    fn render_containers() {
+        let (commands, _sent) = unbounded_channel();
+        let (_updates, receiver) = mpsc::channel();
+        let mut app = App::new(commands, receiver);
+        app.update(Update::Containers(vec![
+            container("aaa", "web", "running"),
+            container("bbb", "db", "exited"),
+        ]));
+        app.update(Update::Log {
+            id: "aaa".into(),
+            line: "oops".into(),
+            stderr: true,
+        });
+        let rows = rows(&mut app, 60, 6);
+        assert_eq!(
+            rows[1],
+            "│● web     Up 2 hours  ││oops                              │"
+        );
+        assert_eq!(
+            rows[2],
+            "│○ db      Exited (0)  ││                                  │"
+        );
+        assert_eq!(
+            rows[5],
+            "2 containers, 1 running                                     "
+        );
+
+        app.update(Update::Error("bbb: no such container".into()));
+        assert_eq!(app.connection, Connection::Connected);
+        assert!(rows_contain(&mut app, "bbb: no such container"));
+        app.update(Update::Disconnected("socket not found".into()));
+        assert!(rows_contain(
+            &mut app,
+            "Cannot connect to Docker: socket not found"
+        ));
+    }
+
+    fn rows_contain(app: &mut App, text: &str) -> bool {
+        rows(app, 60, 6).iter().any(|row| row.contains(text))
+    }
+}
@@ -0,0 +1,301 @@
+//! 支出记录、按月汇总和 CSV 导入导出。
+//!
+//! 金额以分为单位保存为整数，避免浮点数的舍入误差。CSV 的列依次是
+//! `date,category,amount,note`，日期写作 `YYYY-MM-DD`，金额写作 `12.50`，第一行是表头。
+
+use std::io;
+
+use chrono::{Datelike, Months, NaiveDate};
+
+/// CSV 中日期的格式。
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+const HEADER: [&str; 4] = ["date", "category", "amount", "note"];
+
+/// 解析金额，最多两位小数，必须大于零。
+pub fn parse_amount(text: &str) -> Result<i64, String> {
+    let text = text.trim();
+    let invalid = || format!("{text:?} is not an amount like 12.50");
+    let (units, fraction) = text.split_once('.').unwrap_or((text, ""));
+    if units.is_empty() && fraction.is_empty()
+        || fraction.len() > 2
+        || !units
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    let units: i64 = if units.is_empty() {
+        0
+    } else {
+        units.parse().map_err(|_| invalid())?
+    };
+    let cents = format!("{fraction:0<2}")
+        .parse::<i64>()
+        .map_err(|_| invalid())?;
+    let amount = units
+        .checked_mul(100)
+        .and_then(|units| units.checked_add(cents))
+        .ok_or_else(invalid)?;
+    if amount == 0 {
+        return Err("the amount must be more than zero".into());
+    }
+    Ok(amount)
+}
+
+pub fn format_amount(cents: i64) -> String {
+    format!("{}.{:02}", cents / 100, cents % 100)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expense {
+    pub date: NaiveDate,
+    pub category: String,
+    /// 以分为单位。
+    pub amount: i64,
+    pub note: String,
+}
+
+/// 一个月的第一天。
+pub fn month_of(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).expect("every month has a first day")
+}
+
+#[derive(Debug, Default)]
+pub struct Ledger {
+    /// 按日期排列，同一天的按添加的顺序。
+    expenses: Vec<Expense>,
+}
+
+impl Ledger {
+    pub fn new(expenses: Vec<Expense>) -> Self {
+        let mut ledger = Self::default();
+        for expense in expenses {
+            ledger.add(expense);
+        }
+        ledger
+    }
+
+    pub fn expenses(&self) -> &[Expense] {
+        &self.expenses
+    }
+
+    /// 添加一笔支出，返回它在列表中的位置。
+    pub fn add(&mut self, expense: Expense) -> usize {
+        let index = self
+            .expenses
+            .partition_point(|other| other.date <= expense.date);
+        self.expenses.insert(index, expense);
+        index
+    }
+
+    pub fn remove(&mut self, index: usize) -> Expense {
+        self.expenses.remove(index)
+    }
+
+    /// 导入一批支出，跳过已经存在的完全相同的记录，返回添加和跳过的条数。
+    pub fn import(&mut self, expenses: Vec<Expense>) -> (usize, usize) {
+        let mut added = 0;
+        for expense in expenses.iter() {
+            if !self.expenses.contains(expense) {
+                self.add(expense.clone());
+                added += 1;
+            }
+        }
+        (added, expenses.len() - added)
+    }
+
+    /// 用到的分类，按字母顺序。
+    pub fn categories(&self) -> Vec<&str> {
+        let mut categories: Vec<&str> = self
+            .expenses
+            .iter()
+            .map(|expense| expense.category.as_str())
+            .collect();
+        categories.sort_unstable();
+        categories.dedup();
+        categories
+    }
+
+    /// 到 `last` 所在的月份为止 `months` 个月每个月的合计，从早到晚。`category` 为 `None` 时
+    /// 统计所有分类。
+    pub fn monthly_totals(
+        &self,
+        category: Option<&str>,
+        last: NaiveDate,
+        months: u32,
+    ) -> Vec<(NaiveDate, i64)> {
+        let last = month_of(last);
+        (0..months)
+            .rev()
+            .filter_map(|back| last.checked_sub_months(Months::new(back)))
+            .map(|month| {
+                let total = self
+                    .expenses
+                    .iter()
+                    .filter(|expense| month_of(expense.date) == month)
+                    .filter(|expense| category.is_none_or(|category| expense.category == category))
+                    .map(|expense| expense.amount)
+                    .sum();
+                (month, total)
+            })
+            .collect()
+    }
+}
+
+/// 读取 CSV。任意一行有错误时整个文件都不导入，错误中带有行号。
+pub fn read_csv(reader: impl io::Read) -> Result<Vec<Expense>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let mut expenses = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|error| error.to_string())?;
+        let line = record.position().map_or(0, csv::Position::line);
+        let parse = || -> Result<Expense, String> {
+            let field = |index: usize| record.get(index).unwrap_or_default();
+            let date = NaiveDate::parse_from_str(field(0), DATE_FORMAT)
+                .map_err(|_| format!("{:?} is not a date like 2026-10-14", field(0)))?;
+            if field(1).is_empty() {
+                return Err("the category is empty".into());
+            }
+            Ok(Expense {
+                date,
+                category: field(1).to_string(),
+                amount: parse_amount(field(2))?,
+                note: field(3).to_string(),
+            })
+        };
+        expenses.push(parse().map_err(|error| format!("line {line}: {error}"))?);
+    }
+    Ok(expenses)
+}
+
+pub fn write_csv<'a>(
+    writer: impl io::Write,
+    expenses: impl IntoIterator<Item = &'a Expense>,
+) -> csv::Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(HEADER)?;
+    for expense in expenses {
+        writer.write_record([
+            expense.date.format(DATE_FORMAT).to_string(),
+            expense.category.clone(),
+            format_amount(expense.amount),
+            expense.note.clone(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+    }
+
+    fn expense(month: u32, day: u32, category: &str, amount: i64) -> Expense {
+        Expense {
+            date: date(month, day),
+            category: category.into(),
+            amount,
+            note: String::new(),
+        }
+    }
+
+    #[test]
+    fn amounts() {
+        assert_eq!(parse_amount("12"), Ok(1200));
+        assert_eq!(parse_amount(" 12.5 "), Ok(1250));
+        assert_eq!(parse_amount("0.07"), Ok(7));
+        assert_eq!(parse_amount(".5"), Ok(50));
+        assert_eq!(
+            parse_amount("1.234"),
+            Err("\"1.234\" is not an amount like 12.50".into())
+        );
+        assert_eq!(
+            parse_amount("-3"),
+            Err("\"-3\" is not an amount like 12.50".into())
+        );
+        assert_eq!(
+            parse_amount("."),
+            Err("\".\" is not an amount like 12.50".into())
+        );
+        assert_eq!(
+            parse_amount("0.00"),
+            Err("the amount must be more than zero".into())
+        );
+        assert_eq!(format_amount(1205), "12.05");
+        assert_eq!(format_amount(7), "0.07");
+    }
+
+    #[test]
+    fn monthly_totals_and_categories() {
+        let mut ledger = Ledger::new(vec![
+            expense(10, 3, "Food", 1250),
+            expense(8, 20, "Rent", 90000),
+            expense(10, 1, "Transport", 300),
+            expense(8, 2, "Food", 800),
+        ]);
+        assert_eq!(ledger.expenses()[0].date, date(8, 2));
+        assert_eq!(ledger.add(expense(9, 30, "Food", 100)), 2);
+        assert_eq!(ledger.categories(), ["Food", "Rent", "Transport"]);
+        assert_eq!(
+            ledger.monthly_totals(None, date(10, 14), 3),
+            [(date(8, 1), 90800), (date(9, 1), 100), (date(10, 1), 1550)]
+        );
+        assert_eq!(
+            ledger.monthly_totals(Some("Food"), date(10, 14), 4),
+            [
+                (date(7, 1), 0),
+                (date(8, 1), 800),
+                (date(9, 1), 100),
+                (date(10, 1), 1250)
+            ]
+        );
+        assert_eq!(ledger.remove(2).category, "Food");
+        assert_eq!(
+            ledger.monthly_totals(None, date(9, 1), 1),
+            [(date(9, 1), 0)]
+        );
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        let csv = "date,category,amount,note\n2026-10-01,Transport,3.00,\n\
+                   2026-10-03,Food,12.50,\"Lunch, with Sam\"\n";
+        let expenses = read_csv(csv.as_bytes()).unwrap();
+        assert_eq!(expenses[1].note, "Lunch, with Sam");
+        assert_eq!(expenses[1].amount, 1250);
+        let mut written = Vec::new();
+        write_csv(&mut written, &expenses).unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), csv);
+
+        // 备注可以省略，多余的空白被忽略。
+        let short = "date,category,amount\n2026-10-05, Food , 4\n";
+        assert_eq!(
+            read_csv(short.as_bytes()).unwrap(),
+            [expense(10, 5, "Food", 400)]
+        );
+
+        let mut ledger = Ledger::new(expenses.clone());
+        assert_eq!(ledger.import(read_csv(short.as_bytes()).unwrap()), (1, 0));
+        assert_eq!(ledger.import(expenses), (0, 2));
+
+        let bad = "date,category,amount\n2026-10-05,Food,4\n2026-13-01,Food,4\n";
+        assert_eq!(
+            read_csv(bad.as_bytes()).unwrap_err(),
+            "line 3: \"2026-13-01\" is not a date like 2026-10-14"
+        );
+        let bad = "date,category,amount\n2026-10-05,,4\n";
+        assert_eq!(
+            read_csv(bad.as_bytes()).unwrap_err(),
+            "line 2: the category is empty"
+        );
+    }
+}
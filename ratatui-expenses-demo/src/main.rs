@@ -0,0 +1,745 @@
+//! 记账演示：录入带分类和金额的支出，用柱状图显示最近几个月每月的合计，可以只看某个分类，
+//! 并导入导出 CSV。
+//!
+//! 支出保存在命令行指定的 CSV 文件中（格式见 `ledger` 模块），每次修改后立即写入。导出时只
+//! 导出当前分类下的支出；导入时跳过已经存在的完全相同的记录。
+//!
+//! 按键：`a` 添加支出，`x` 删除选中的支出，`Tab` / `Shift+Tab` 切换分类，`i` 导入，`e` 导出，
+//! `q` 退出。
+
+use std::{
+    fs::File,
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{Local, NaiveDate};
+use clap::Parser;
+use color_eyre::{eyre::eyre, eyre::WrapErr, Result};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{
+        Bar, BarChart, BarGroup, Block, Cell, Clear, Paragraph, Row, Table, TableState, Tabs,
+    },
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    files,
+    layout::centered,
+    terminal,
+    text_input::Input,
+};
+
+use crate::ledger::{
+    format_amount, parse_amount, read_csv, write_csv, Expense, Ledger, DATE_FORMAT,
+};
+
+mod ledger;
+
+/// 等待按键的最长时间。
+const REFRESH: Duration = Duration::from_secs(1);
+
+/// 柱状图显示的月数。
+const MONTHS: u32 = 6;
+const BAR_WIDTH: u16 = 5;
+/// 柱状图的宽度：每个月一根柱子和间隔，加上边框。
+const CHART_WIDTH: u16 = MONTHS as u16 * (BAR_WIDTH + 1) + 3;
+
+const DATE: usize = 0;
+const CATEGORY: usize = 1;
+const AMOUNT: usize = 2;
+const NOTE: usize = 3;
+const LABELS: [&str; 4] = ["Date:     ", "Category: ", "Amount:   ", "Note:     "];
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 保存支出的 CSV 文件，不存在时在第一次修改时创建
+    #[arg(default_value = "expenses.csv")]
+    file: PathBuf,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let expenses = match File::open(&cli.file) {
+        Ok(file) => read_csv(BufReader::new(file))
+            .map_err(|error| eyre!(error))
+            .wrap_err_with(|| format!("cannot read {}", cli.file.display()))?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => {
+            return Err(err).wrap_err_with(|| format!("cannot open {}", cli.file.display()))
+        }
+    };
+
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let today = Local::now().date_naive();
+    let result =
+        App::new(Ledger::new(expenses), cli.file, today).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+/// 把支出写入 CSV 文件。
+fn save_csv<'a>(path: &Path, expenses: impl IntoIterator<Item = &'a Expense>) -> Result<()> {
+    let mut bytes = Vec::new();
+    write_csv(&mut bytes, expenses)
+        .map_err(io::Error::from)
+        .and_then(|()| files::write_atomic(path, bytes))
+        .wrap_err_with(|| format!("cannot write {}", path.display()))
+}
+
+fn load_csv(path: &Path) -> Result<Vec<Expense>> {
+    let file = File::open(path).wrap_err_with(|| format!("cannot open {}", path.display()))?;
+    read_csv(BufReader::new(file))
+        .map_err(|error| eyre!(error))
+        .wrap_err_with(|| format!("cannot read {}", path.display()))
+}
+
+fn count(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {noun}"),
+        count => format!("{count} {noun}s"),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Prompt {
+    Import,
+    Export,
+}
+
+impl Prompt {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Import => "Import from: ",
+            Self::Export => "Export to: ",
+        }
+    }
+}
+
+/// 添加支出的表单。
+struct Form {
+    fields: [Input; 4],
+    field: usize,
+}
+
+impl Form {
+    fn new(today: NaiveDate, category: Option<&str>) -> Self {
+        Self {
+            fields: [
+                Input::with_value(today.format(DATE_FORMAT).to_string()),
+                Input::with_value(category.unwrap_or_default()),
+                Input::default(),
+                Input::default(),
+            ],
+            // 日期默认是今天，通常从金额或者分类开始填。
+            field: if category.is_some() { AMOUNT } else { CATEGORY },
+        }
+    }
+
+    /// 检查每一项，出错时返回出错的那一项和错误。
+    fn expense(&self) -> Result<Expense, (usize, String)> {
+        let value = |field: usize| self.fields[field].value().trim();
+        let date = NaiveDate::parse_from_str(value(DATE), DATE_FORMAT).map_err(|_| {
+            (
+                DATE,
+                format!("{:?} is not a date like 2026-10-14", value(DATE)),
+            )
+        })?;
+        if value(CATEGORY).is_empty() {
+            return Err((CATEGORY, "Category is required".into()));
+        }
+        let amount = parse_amount(value(AMOUNT)).map_err(|error| (AMOUNT, error))?;
+        Ok(Expense {
+            date,
+            category: value(CATEGORY).to_string(),
+            amount,
+            note: value(NOTE).to_string(),
+        })
+    }
+}
+
+struct App {
+    ledger: Ledger,
+    path: PathBuf,
+    today: NaiveDate,
+    /// 只显示这个分类的支出。
+    filter: Option<String>,
+    table: TableState,
+    form: Option<Form>,
+    prompt: Option<(Prompt, Input)>,
+    /// 等待确认删除的支出。
+    confirm: Option<usize>,
+    /// 显示在状态栏中的提示或错误，下一次按键时清除。
+    message: Option<Result<String, String>>,
+    exit: bool,
+}
+
+impl App {
+    fn new(ledger: Ledger, path: PathBuf, today: NaiveDate) -> Self {
+        let mut app = Self {
+            ledger,
+            path,
+            today,
+            filter: None,
+            table: TableState::default(),
+            form: None,
+            prompt: None,
+            confirm: None,
+            message: None,
+            exit: false,
+        };
+        app.select(0);
+        app
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| frame.render_widget(&mut *self, frame.size()))?;
+            if events.poll(REFRESH)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 当前分类下的支出在账本中的位置，最新的在前面。
+    fn visible(&self) -> Vec<usize> {
+        let expenses = self.ledger.expenses();
+        (0..expenses.len())
+            .rev()
+            .filter(|&index| {
+                self.filter
+                    .as_ref()
+                    .is_none_or(|category| &expenses[index].category == category)
+            })
+            .collect()
+    }
+
+    fn select(&mut self, row: usize) {
+        let last = self.visible().len().checked_sub(1);
+        self.table.select(last.map(|last| row.min(last)));
+    }
+
+    fn selected(&self) -> Option<usize> {
+        self.visible().get(self.table.selected()?).copied()
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        self.message = None;
+        if self.form.is_some() {
+            self.handle_form_key(key);
+            return;
+        }
+        if let Some((prompt, input)) = &mut self.prompt {
+            match key.code {
+                KeyCode::Esc => self.prompt = None,
+                KeyCode::Enter => {
+                    let (prompt, path) = (*prompt, PathBuf::from(input.value().trim()));
+                    self.prompt = None;
+                    match prompt {
+                        Prompt::Import => self.import(&path),
+                        Prompt::Export => self.export(&path),
+                    }
+                }
+                _ => {
+                    input.handle_key_event(key);
+                }
+            }
+            return;
+        }
+        if let Some(index) = self.confirm.take() {
+            if key.code == KeyCode::Char('y') {
+                let expense = self.ledger.remove(index);
+                // 删除了某个分类的最后一笔后回到所有分类。
+                if self.visible().is_empty() {
+                    self.filter = None;
+                }
+                self.select(self.table.selected().unwrap_or(0));
+                self.changed(format!(
+                    "Deleted {} {}",
+                    expense.category,
+                    format_amount(expense.amount)
+                ));
+            }
+            return;
+        }
+        let row = self.table.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
+            KeyCode::Up | KeyCode::Char('k') => self.select(row.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.select(row + 1),
+            KeyCode::Tab => self.cycle_filter(1),
+            KeyCode::BackTab => self.cycle_filter(-1),
+            KeyCode::Char('a') => self.form = Some(Form::new(self.today, self.filter.as_deref())),
+            KeyCode::Char('x') | KeyCode::Delete => self.confirm = self.selected(),
+            KeyCode::Char('i') => self.prompt = Some((Prompt::Import, Input::default())),
+            KeyCode::Char('e') => {
+                let name = match &self.filter {
+                    Some(category) => format!("{}.csv", category.to_lowercase().replace(' ', "-")),
+                    None => "export.csv".into(),
+                };
+                self.prompt = Some((Prompt::Export, Input::with_value(name)));
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_form_key(&mut self, key: KeyEvent) {
+        let Some(form) = &mut self.form else {
+            return;
+        };
+        let len = form.fields.len();
+        match key.code {
+            KeyCode::Esc => self.form = None,
+            KeyCode::Tab | KeyCode::Down => form.field = (form.field + 1) % len,
+            KeyCode::BackTab | KeyCode::Up => form.field = (form.field + len - 1) % len,
+            KeyCode::Enter => match form.expense() {
+                Ok(expense) => {
+                    self.form = None;
+                    let message = format!(
+                        "Added {} {} on {}",
+                        expense.category,
+                        format_amount(expense.amount),
+                        expense.date
+                    );
+                    let index = self.ledger.add(expense);
+                    // 不在当前分类中的支出添加后切换到所有分类，这样能看到刚添加的一笔。
+                    if !self.visible().contains(&index) {
+                        self.filter = None;
+                    }
+                    let row = self.visible().iter().position(|&other| other == index);
+                    self.select(row.unwrap_or(0));
+                    self.changed(message);
+                }
+                Err((field, error)) => {
+                    form.field = field;
+                    self.message = Some(Err(error));
+                }
+            },
+            _ => {
+                form.fields[form.field].handle_key_event(key);
+            }
+        }
+    }
+
+    /// 在“所有分类”和每个分类之间切换。
+    fn cycle_filter(&mut self, step: isize) {
+        let mut options: Vec<Option<String>> = vec![None];
+        options.extend(
+            self.ledger
+                .categories()
+                .into_iter()
+                .map(|c| Some(c.to_string())),
+        );
+        let current = options
+            .iter()
+            .position(|option| option == &self.filter)
+            .unwrap_or(0);
+        let next = (current as isize + step).rem_euclid(options.len() as isize) as usize;
+        self.filter = options.swap_remove(next);
+        self.select(0);
+    }
+
+    fn import(&mut self, path: &Path) {
+        match load_csv(path) {
+            Ok(expenses) => {
+                let (added, skipped) = self.ledger.import(expenses);
+                self.select(0);
+                let mut message = format!(
+                    "Imported {} from {}",
+                    count(added, "expense"),
+                    path.display()
+                );
+                if skipped > 0 {
+                    message.push_str(&format!(
+                        ", skipped {} already here",
+                        count(skipped, "duplicate")
+                    ));
+                }
+                self.changed(message);
+            }
+            Err(error) => self.message = Some(Err(format!("{error:#}"))),
+        }
+    }
+
+    fn export(&mut self, path: &Path) {
+        let expenses = self.ledger.expenses();
+        let visible = self.visible();
+        // 导出时按日期从早到晚。
+        let exported = visible.iter().rev().map(|&index| &expenses[index]);
+        self.message = Some(match save_csv(path, exported) {
+            Ok(()) => Ok(format!(
+                "Exported {} to {}",
+                count(visible.len(), "expense"),
+                path.display()
+            )),
+            Err(error) => Err(format!("{error:#}")),
+        });
+    }
+
+    /// 修改后立即保存。
+    fn changed(&mut self, message: String) {
+        self.message = Some(match save_csv(&self.path, self.ledger.expenses()) {
+            Ok(()) => Ok(message),
+            Err(error) => Err(format!("{error:#}")),
+        });
+    }
+
+    fn render_tabs(&self, area: Rect, buf: &mut Buffer) {
+        let categories = self.ledger.categories();
+        let selected = match &self.filter {
+            Some(filter) => categories
+                .iter()
+                .position(|c| c == filter)
+                .map_or(0, |i| i + 1),
+            None => 0,
+        };
+        let hints = Line::from(" Category <Tab> Import <i> Export <e> ").dim();
+        let [tabs_area, hints_area] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(hints.width() as u16),
+        ])
+        .areas(area);
+        Tabs::new(std::iter::once("All").chain(categories))
+            .select(selected)
+            .highlight_style(Style::new().bold().reversed())
+            .render(tabs_area, buf);
+        hints.render(hints_area, buf);
+    }
+
+    fn render_table(&mut self, area: Rect, buf: &mut Buffer) {
+        let expenses = self.ledger.expenses();
+        let visible = self.visible();
+        let total: i64 = visible.iter().map(|&index| expenses[index].amount).sum();
+        let rows = visible.iter().map(|&index| {
+            let expense = &expenses[index];
+            Row::new([
+                Cell::from(expense.date.format(DATE_FORMAT).to_string()),
+                Cell::from(expense.category.as_str()),
+                Cell::from(Line::from(format_amount(expense.amount)).right_aligned()),
+                Cell::from(expense.note.as_str().dim()),
+            ])
+        });
+        let block = Block::bordered()
+            .title(" Expenses ")
+            .title(Line::from(format!(" Total {} ", format_amount(total)).bold()).right_aligned())
+            .title_bottom(Line::from(" Add <a> Delete <x> Quit <q> ").right_aligned())
+            .border_style(Style::new().green());
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(10),
+                Constraint::Length(12),
+                Constraint::Length(10),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(Row::new(["Date", "Category", "    Amount", "Note"]).bold())
+        .highlight_style(Style::new().reversed())
+        .block(block);
+        StatefulWidget::render(table, area, buf, &mut self.table);
+        if visible.is_empty() {
+            let inner = area.inner(&Margin::new(2, 2));
+            Paragraph::new("No expenses yet, press a to add one".dim()).render(inner, buf);
+        }
+    }
+
+    fn render_chart(&self, area: Rect, buf: &mut Buffer) {
+        let totals = self
+            .ledger
+            .monthly_totals(self.filter.as_deref(), self.today, MONTHS);
+        let bars: Vec<Bar> = totals
+            .iter()
+            .map(|&(month, total)| {
+                Bar::default()
+                    .value(total as u64)
+                    // 柱子上只显示整数部分，放不下小数。
+                    .text_value(((total + 50) / 100).to_string())
+                    .label(month.format("%b").to_string().into())
+            })
+            .collect();
+        let title = match &self.filter {
+            Some(category) => format!(" Monthly · {category} "),
+            None => " Monthly ".into(),
+        };
+        BarChart::default()
+            .block(Block::bordered().title(title))
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(BAR_WIDTH)
+            .bar_gap(1)
+            .bar_style(Style::new().cyan())
+            .value_style(Style::new().black().on_cyan())
+            .render(area, buf);
+    }
+
+    fn render_form(form: &Form, area: Rect, buf: &mut Buffer) {
+        let popup = centered(area, 46, 8);
+        Clear.render(popup, buf);
+        let block = Block::bordered()
+            .title(" New expense ".bold())
+            .title_bottom(Line::from(" Next <Tab> Add <Enter> Cancel <Esc> ").right_aligned())
+            .border_style(Style::new().yellow());
+        let inner = block.inner(popup);
+        block.render(popup, buf);
+        let rows = Layout::vertical([Constraint::Length(1); 4])
+            .margin(1)
+            .split(inner.inner(&Margin::new(1, 0)));
+        for (index, input) in form.fields.iter().enumerate() {
+            let style = if index == form.field {
+                Style::new().green().bold()
+            } else {
+                Style::new()
+            };
+            Paragraph::new(input.line(LABELS[index], style)).render(rows[index], buf);
+        }
+    }
+
+    fn status_line(&self) -> Line<'_> {
+        if let Some(index) = self.confirm {
+            let expense = &self.ledger.expenses()[index];
+            return Line::from(format!(
+                "Delete {} {} on {}? <Y>/<N>",
+                expense.category,
+                format_amount(expense.amount),
+                expense.date
+            ))
+            .red()
+            .bold();
+        }
+        if let Some((prompt, input)) = &self.prompt {
+            return input.line(prompt.label(), Style::new().bold());
+        }
+        match &self.message {
+            Some(Ok(message)) => Line::from(message.as_str()),
+            Some(Err(error)) => Line::from(error.as_str().red()),
+            None => Line::from(
+                format!(
+                    "{} in {}",
+                    count(self.ledger.expenses().len(), "expense"),
+                    self.path.display()
+                )
+                .dim(),
+            ),
+        }
+    }
+}
+
+impl Widget for &mut App {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [tabs_area, main, status] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+        let [table_area, chart_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(CHART_WIDTH)]).areas(main);
+        self.render_tabs(tabs_area, buf);
+        self.render_table(table_area, buf);
+        self.render_chart(chart_area, buf);
+        if let Some(form) = &self.form {
+            App::render_form(form, main, buf);
+        }
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+    }
+
+    fn app(name: &str) -> (App, PathBuf) {
+        let dir = env::temp_dir().join(format!("expenses-demo-{name}-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let csv = "date,category,amount,note\n2026-08-20,Rent,900.00,\n\
+                   2026-09-12,Food,23.40,Groceries\n2026-10-01,Transport,3.00,Bus\n\
+                   2026-10-03,Food,12.50,Lunch\n";
+        let ledger = Ledger::new(read_csv(csv.as_bytes()).unwrap());
+        (
+            App::new(ledger, dir.join("expenses.csv"), date(10, 14)),
+            dir,
+        )
+    }
+
+    #[test]
+    fn render_and_filter() {
+        let (mut app, dir) = app("filter");
+        let screen = rows(&mut app, 90, 14);
+        assert_eq!(
+            screen[0],
+            " All │ Food │ Rent │ Transport                       Category <Tab> Import <i> Export <e> "
+        );
+        assert_eq!(
+            screen[1],
+            "┌ Expenses ───────────────────────── Total 938.90 ┐┌ Monthly ────────────────────────────┐"
+        );
+        assert_eq!(
+            screen[3],
+            "│2026-10-03 Food              12.50 Lunch         ││                  █████              │"
+        );
+        assert_eq!(
+            screen[10],
+            "│                                                 ││                  █900█ ▁23▁▁ ▁16▁▁  │"
+        );
+        assert_eq!(
+            screen[11],
+            "│                                                 ││ May   Jun   Jul   Aug   Sep   Oct   │"
+        );
+
+        app.handle_key(KeyCode::Tab.into());
+        assert_eq!(app.filter.as_deref(), Some("Food"));
+        let screen = rows(&mut app, 90, 14);
+        assert!(screen[1].contains(" Total 35.90 ┐┌ Monthly · Food "));
+        assert!(screen[4].starts_with("│2026-09-12 Food              23.40 Groceries"));
+        assert!(screen[5].starts_with("│        "));
+        app.handle_key(KeyCode::BackTab.into());
+        app.handle_key(KeyCode::BackTab.into());
+        assert_eq!(app.filter.as_deref(), Some("Transport"));
+
+        app.handle_key(KeyCode::Char('x').into());
+        assert_eq!(
+            rows(&mut app, 90, 14)[13].trim_end(),
+            "Delete Transport 3.00 on 2026-10-01? <Y>/<N>"
+        );
+        app.handle_key(KeyCode::Char('y').into());
+        assert_eq!(app.ledger.expenses().len(), 3);
+        assert_eq!(app.filter, None);
+        assert_eq!(app.table.selected(), Some(0));
+        assert!(fs::read_to_string(dir.join("expenses.csv"))
+            .unwrap()
+            .ends_with("2026-10-03,Food,12.50,Lunch\n"));
+        app.handle_key(KeyCode::BackTab.into());
+        assert_eq!(app.filter.as_deref(), Some("Rent"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn add_with_validation() {
+        let (mut app, dir) = app("add");
+        app.handle_key(KeyCode::Char('a').into());
+        let screen = rows(&mut app, 90, 14);
+        assert!(screen[3].contains("┌ New expense ──"));
+        assert!(screen[5].contains("│  Date:     2026-10-14                      │"));
+
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(app.message, Some(Err("Category is required".into())));
+        testing::type_text("Books", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Tab.into());
+        testing::type_text("12.345", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(
+            app.message,
+            Some(Err("\"12.345\" is not an amount like 12.50".into()))
+        );
+        assert_eq!(app.form.as_ref().unwrap().field, AMOUNT);
+        app.handle_key(KeyCode::Backspace.into());
+        app.handle_key(KeyCode::Tab.into());
+        testing::type_text("Novel", |key| app.handle_key(key));
+        app.handle_key(KeyCode::BackTab.into());
+        app.handle_key(KeyCode::BackTab.into());
+        app.handle_key(KeyCode::BackTab.into());
+        for _ in 0..2 {
+            app.handle_key(KeyCode::Backspace.into());
+        }
+        testing::type_text("02", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert!(app.form.is_none());
+        assert_eq!(
+            app.message,
+            Some(Ok("Added Books 12.34 on 2026-10-02".into()))
+        );
+        // 新的一笔按日期排在 10 月 1 日和 3 日之间。
+        assert_eq!(app.selected(), Some(3));
+        assert_eq!(app.table.selected(), Some(1));
+        assert!(fs::read_to_string(dir.join("expenses.csv"))
+            .unwrap()
+            .contains("\n2026-10-02,Books,12.34,Novel\n"));
+
+        // 在某个分类下添加时分类已经填好。
+        app.handle_key(KeyCode::Tab.into());
+        assert_eq!(app.filter.as_deref(), Some("Books"));
+        app.handle_key(KeyCode::Char('a').into());
+        assert_eq!(app.form.as_ref().unwrap().fields[CATEGORY].value(), "Books");
+        assert_eq!(app.form.as_ref().unwrap().field, AMOUNT);
+        app.handle_key(KeyCode::Esc.into());
+        assert!(app.form.is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn import_and_export() {
+        let (mut app, dir) = app("csv");
+        app.handle_key(KeyCode::Tab.into());
+        app.handle_key(KeyCode::Char('e').into());
+        assert_eq!(rows(&mut app, 90, 14)[13].trim_end(), "Export to: food.csv");
+        for _ in 0.."food.csv".len() {
+            app.handle_key(KeyCode::Backspace.into());
+        }
+        let exported = dir.join("food.csv");
+        testing::type_text(exported.to_str().unwrap(), |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(
+            fs::read_to_string(&exported).unwrap(),
+            "date,category,amount,note\n2026-09-12,Food,23.40,Groceries\n2026-10-03,Food,12.50,Lunch\n"
+        );
+
+        let incoming = dir.join("incoming.csv");
+        fs::write(
+            &incoming,
+            "date,category,amount,note\n2026-10-03,Food,12.50,Lunch\n2026-10-10,Gifts,30,\n",
+        )
+        .unwrap();
+        app.handle_key(KeyCode::Char('i').into());
+        testing::type_text(incoming.to_str().unwrap(), |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(
+            app.message,
+            Some(Ok(format!(
+                "Imported 1 expense from {}, skipped 1 duplicate already here",
+                incoming.display()
+            )))
+        );
+        assert_eq!(
+            app.ledger.categories(),
+            ["Food", "Gifts", "Rent", "Transport"]
+        );
+
+        fs::write(&incoming, "date,category,amount\n2026-10-10,Gifts,abc\n").unwrap();
+        app.handle_key(KeyCode::Char('i').into());
+        testing::type_text(incoming.to_str().unwrap(), |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(
+            app.message,
+            Some(Err(format!(
+                "cannot read {}: line 2: \"abc\" is not an amount like 12.50",
+                incoming.display()
+            )))
+        );
+        assert_eq!(app.ledger.expenses().len(), 5);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
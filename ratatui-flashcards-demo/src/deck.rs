@@ -0,0 +1,91 @@
+//! 从 TOML 文件加载卡组。
+//!
+//! ```toml
+//! name = "Rust basics"
+//!
+//! [[card]]
+//! front = "What is the type of a string literal?"
+//! back = "`&'static str`"
+//! ```
+
+use std::{collections::HashSet, fs, path::Path};
+
+use color_eyre::{
+    eyre::{bail, WrapErr},
+    Result,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Deck {
+    pub name: String,
+    #[serde(rename = "card")]
+    pub cards: Vec<Card>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Card {
+    /// 同时用作复习记录的键，所以在卡组中必须唯一。
+    pub front: String,
+    pub back: String,
+}
+
+impl Deck {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .wrap_err_with(|| format!("reading deck {} failed", path.display()))?;
+        Self::parse(&text).wrap_err_with(|| format!("parsing deck {} failed", path.display()))
+    }
+
+    fn parse(text: &str) -> Result<Self> {
+        let deck: Self = toml::from_str(text)?;
+        if deck.cards.is_empty() {
+            bail!("the deck has no cards");
+        }
+        let mut fronts = HashSet::new();
+        if let Some(card) = deck.cards.iter().find(|card| !fronts.insert(&card.front)) {
+            bail!("duplicate card {:?}", card.front);
+        }
+        Ok(deck)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_deck() {
+        let deck = Deck::parse(
+            r#"
+            name = "Capitals"
+
+            [[card]]
+            front = "France"
+            back = "Paris"
+
+            [[card]]
+            front = "Japan"
+            back = "Tokyo"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(deck.name, "Capitals");
+        assert_eq!(deck.cards[1].back, "Tokyo");
+
+        assert!(Deck::parse("name = \"Empty\"\ncard = []").is_err());
+        let error = Deck::parse(
+            r#"
+            name = "Duplicates"
+            card = [{ front = "a", back = "1" }, { front = "a", back = "2" }]
+            "#,
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "duplicate card \"a\"");
+
+        let example = Path::new(env!("CARGO_MANIFEST_DIR")).join("decks/rust.toml");
+        assert_eq!(Deck::load(&example).unwrap().cards.len(), 6);
+    }
+}
@@ -0,0 +1,304 @@
+//! 间隔重复的记忆卡片演示：从 TOML 文件加载卡组，依次显示今天到期的卡片，想好答案后翻到背面，
+//! 按记住的程度评分，`schedule` 模块据此安排下一次复习的日期。
+//!
+//! 复习记录默认保存在数据目录中，和卡组文件同名（扩展名为 `.json`），下次启动时恢复。
+//! 卡组的格式见 `deck` 模块，`decks/rust.toml` 是一个示例。
+//!
+//! 按键：`Space` / `Enter` 翻面，翻面后 `1`–`4` 评分（Again、Hard、Good、Easy），`q` 退出。
+
+use std::{collections::VecDeque, path::PathBuf};
+
+use chrono::{Local, NaiveDate};
+use clap::Parser;
+use color_eyre::{eyre::eyre, Result};
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use directories::ProjectDirs;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Padding, Paragraph, Wrap},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+};
+
+use crate::{
+    deck::Deck,
+    schedule::{Grade, Review, Schedule},
+};
+
+mod deck;
+mod schedule;
+
+const APPLICATION: &str = "ratatui-flashcards-demo";
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 卡组文件
+    #[arg(value_name = "DECK")]
+    deck: PathBuf,
+    /// 复习记录，默认是数据目录中与卡组同名的 JSON 文件
+    #[arg(long, value_name = "FILE")]
+    state: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let deck = Deck::load(&cli.deck)?;
+    let state = cli
+        .state
+        .or_else(|| {
+            let project = ProjectDirs::from("", "", APPLICATION)?;
+            let name = cli.deck.file_stem()?;
+            Some(project.data_dir().join(name).with_extension("json"))
+        })
+        .ok_or_else(|| eyre!("no data directory, pass --state"))?;
+    let schedule = Schedule::load(state)?;
+
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let today = Local::now().date_naive();
+    let result = App::new(deck, schedule, today).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+/// 复习间隔的简短写法。
+fn interval(days: u32) -> String {
+    match days {
+        0 => "<1d".into(),
+        1..=29 => format!("{days}d"),
+        30..=364 => format!("{}mo", days / 30),
+        _ => format!("{:.1}y", f64::from(days) / 365.0),
+    }
+}
+
+struct App {
+    deck: Deck,
+    schedule: Schedule,
+    today: NaiveDate,
+    /// 本次还要复习的卡片在卡组中的位置。
+    queue: VecDeque<usize>,
+    revealed: bool,
+    reviewed: usize,
+    /// 最近一次操作的结果，`Err` 显示为红色。
+    message: Option<Result<String, String>>,
+    exit: bool,
+}
+
+impl App {
+    fn new(deck: Deck, schedule: Schedule, today: NaiveDate) -> Self {
+        let queue = (0..deck.cards.len())
+            .filter(|&index| schedule.is_due(&deck.cards[index].front, today))
+            .collect();
+        Self {
+            deck,
+            schedule,
+            today,
+            queue,
+            revealed: false,
+            reviewed: 0,
+            message: None,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut()))?;
+            if let Event::Key(key) = events.read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key.code);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
+            KeyCode::Char(' ') | KeyCode::Enter if !self.queue.is_empty() => self.revealed = true,
+            KeyCode::Char(c @ '1'..='4') if self.revealed => {
+                self.grade(Grade::ALL[usize::from(c as u8 - b'1')])
+            }
+            _ => {}
+        }
+    }
+
+    fn grade(&mut self, grade: Grade) {
+        let Some(index) = self.queue.pop_front() else {
+            return;
+        };
+        let front = &self.deck.cards[index].front;
+        self.message = match self.schedule.grade(front, grade, self.today) {
+            Ok(()) => None,
+            Err(error) => Some(Err(format!("{error:#}"))),
+        };
+        if grade == Grade::Again {
+            self.queue.push_back(index);
+        }
+        self.revealed = false;
+        self.reviewed += 1;
+    }
+
+    /// 卡组中下一张卡到期的日期。
+    fn next_due(&self) -> Option<NaiveDate> {
+        self.deck
+            .cards
+            .iter()
+            .filter_map(|card| self.schedule.review(&card.front))
+            .map(|review| review.due)
+            .min()
+    }
+
+    /// 翻面后显示每种评分对应的下一次间隔。
+    fn grades_line(&self, index: usize) -> Line<'static> {
+        let previous = self.schedule.review(&self.deck.cards[index].front);
+        let mut spans = Vec::new();
+        for (key, grade) in Grade::ALL.into_iter().enumerate() {
+            let next = Review::next(previous, grade, self.today);
+            spans.push(format!(" {} <{}> ", grade.name(), key + 1).bold());
+            spans.push(interval(next.interval_days).dim());
+            spans.push(" ".into());
+        }
+        Line::from(spans).centered()
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        match &self.message {
+            Some(Ok(message)) => Line::from(message.clone()),
+            Some(Err(error)) => Line::from(error.clone().red()),
+            None => Line::from(format!(
+                "{} left  {} reviewed",
+                self.queue.len(),
+                self.reviewed
+            )),
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let [card_area, grades_area, status_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+        let block = Block::bordered()
+            .title(format!(" {} ", self.deck.name).bold())
+            .padding(Padding::new(2, 2, 1, 1));
+
+        let Some(&index) = self.queue.front() else {
+            let next = self.next_due().map_or_else(String::new, |due| {
+                format!(" Next review on {}.", due.format("%Y-%m-%d"))
+            });
+            Paragraph::new(format!("No cards are due.{next}"))
+                .centered()
+                .wrap(Wrap { trim: true })
+                .block(block.title_bottom(Line::from(" Quit <Q> ").right_aligned()))
+                .render(card_area, buf);
+            Paragraph::new(self.status_line()).render(status_area, buf);
+            return;
+        };
+
+        let card = &self.deck.cards[index];
+        let mut lines = vec![Line::from(card.front.clone().bold())];
+        let hint = if self.revealed {
+            let width = usize::from(card_area.width.saturating_sub(6));
+            lines.push(Line::default());
+            lines.push(Line::from("─".repeat(width).dim()));
+            lines.push(Line::default());
+            lines.push(Line::from(card.back.clone()));
+            self.grades_line(index).render(grades_area, buf);
+            " Grade <1-4> Quit <Q> "
+        } else {
+            " Show <Space> Quit <Q> "
+        };
+        Paragraph::new(lines)
+            .centered()
+            .wrap(Wrap { trim: true })
+            .block(block.title_bottom(Line::from(hint).right_aligned()))
+            .render(card_area, buf);
+        Paragraph::new(self.status_line()).render(status_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use ratatui_common::testing;
+
+    use super::*;
+    use crate::deck::Card;
+
+    fn app(name: &str) -> (App, PathBuf) {
+        let dir = env::temp_dir().join(format!("flashcards-demo-{name}-{}", process::id()));
+        let deck = Deck {
+            name: "Capitals".into(),
+            cards: [("France", "Paris"), ("Japan", "Tokyo")]
+                .into_iter()
+                .map(|(front, back)| Card {
+                    front: front.into(),
+                    back: back.into(),
+                })
+                .collect(),
+        };
+        let schedule = Schedule::load(dir.join("capitals.json")).unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        (App::new(deck, schedule, today), dir)
+    }
+
+    fn rows(app: &App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    #[test]
+    fn review_session() {
+        let (mut app, dir) = app("session");
+        // 翻面之前不能评分。
+        app.handle_key(KeyCode::Char('3'));
+        assert_eq!(app.reviewed, 0);
+
+        app.handle_key(KeyCode::Char(' '));
+        app.handle_key(KeyCode::Char('1'));
+        assert_eq!(app.queue, [1, 0]);
+        for _ in 0..2 {
+            app.handle_key(KeyCode::Enter);
+            app.handle_key(KeyCode::Char('3'));
+        }
+        assert!(app.queue.is_empty());
+        assert_eq!(app.reviewed, 3);
+        assert_eq!(app.next_due(), NaiveDate::from_ymd_opt(2024, 1, 2));
+
+        // 重新启动后今天没有到期的卡片。
+        let (app, _) = self::app("session");
+        assert!(app.queue.is_empty());
+        let rows = rows(&app, 40, 8);
+        assert_eq!(rows[2], "│   No cards are due. Next review on   │");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reveal_back() {
+        let (mut app, dir) = app("reveal");
+        let front = rows(&app, 40, 10);
+        assert_eq!(front[2], "│                France                │");
+        assert!(!front.iter().any(|row| row.contains("Paris")));
+        assert_eq!(front[9].trim_end(), "2 left  0 reviewed");
+
+        app.handle_key(KeyCode::Char(' '));
+        let back = rows(&app, 60, 12);
+        assert!(back[6].contains("Paris"));
+        assert_eq!(
+            back[10].trim(),
+            "Again <1> <1d  Hard <2> 1d  Good <3> 1d  Easy <4> 4d"
+        );
+        assert!(!dir.exists());
+    }
+}
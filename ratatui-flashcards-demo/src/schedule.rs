@@ -0,0 +1,182 @@
+//! 间隔重复的排程，使用简化的 SM-2 算法：每张卡有一个难易系数，答对时间隔按系数增长，
+//! 答错时从头开始。复习记录保存为 JSON，每次评分后立即写入，下次启动时恢复。
+
+use std::{collections::BTreeMap, fs, io, path::PathBuf};
+
+use chrono::{Days, NaiveDate};
+use color_eyre::{eyre::WrapErr, Result};
+use ratatui_common::files;
+use serde::{Deserialize, Serialize};
+
+/// 新卡的难易系数，和 Anki 一样用千分数表示，避免浮点误差。
+const INITIAL_EASE: u32 = 2500;
+const MIN_EASE: u32 = 1300;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grade {
+    /// 没有想起来，本次复习中稍后再出现一次。
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+impl Grade {
+    pub const ALL: [Self; 4] = [Self::Again, Self::Hard, Self::Good, Self::Easy];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Again => "Again",
+            Self::Hard => "Hard",
+            Self::Good => "Good",
+            Self::Easy => "Easy",
+        }
+    }
+}
+
+/// 一张卡的复习记录。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Review {
+    pub due: NaiveDate,
+    pub interval_days: u32,
+    /// 千分数，2500 表示间隔每次乘以 2.5。
+    pub ease: u32,
+    /// 连续答对的次数。
+    pub repetitions: u32,
+}
+
+impl Review {
+    /// 在 `today` 评分后的记录。`previous` 为 `None` 时是新卡。
+    pub fn next(previous: Option<&Self>, grade: Grade, today: NaiveDate) -> Self {
+        let (interval, ease, repetitions) = previous.map_or((0, INITIAL_EASE, 0), |review| {
+            (review.interval_days, review.ease, review.repetitions)
+        });
+        let (interval_days, ease, repetitions) = match grade {
+            Grade::Again => (0, ease.saturating_sub(200).max(MIN_EASE), 0),
+            Grade::Hard => (
+                scale(interval, 1200).max(1),
+                ease.saturating_sub(150).max(MIN_EASE),
+                repetitions + 1,
+            ),
+            Grade::Good | Grade::Easy => {
+                let interval = match repetitions {
+                    0 => 1,
+                    1 => 6,
+                    _ => scale(interval, ease),
+                };
+                if grade == Grade::Easy {
+                    (scale(interval, 1300).max(4), ease + 150, repetitions + 1)
+                } else {
+                    (interval, ease, repetitions + 1)
+                }
+            }
+        };
+        Self {
+            due: today + Days::new(interval_days.into()),
+            interval_days,
+            ease,
+            repetitions,
+        }
+    }
+}
+
+/// `days` 乘以千分数 `factor`，四舍五入。
+fn scale(days: u32, factor: u32) -> u32 {
+    (days * factor + 500) / 1000
+}
+
+#[derive(Debug)]
+pub struct Schedule {
+    path: PathBuf,
+    /// 按卡片正面记录。
+    reviews: BTreeMap<String, Review>,
+}
+
+impl Schedule {
+    /// 读取记录文件。文件还不存在时所有卡都是新卡。
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let reviews = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .wrap_err_with(|| format!("parsing {} failed", path.display()))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => {
+                return Err(err).wrap_err_with(|| format!("reading {} failed", path.display()))
+            }
+        };
+        Ok(Self { path, reviews })
+    }
+
+    pub fn review(&self, front: &str) -> Option<&Review> {
+        self.reviews.get(front)
+    }
+
+    /// 新卡总是到期的。
+    pub fn is_due(&self, front: &str, today: NaiveDate) -> bool {
+        self.review(front).is_none_or(|review| review.due <= today)
+    }
+
+    /// 记录评分并写入文件。写入失败时记录仍然保留在内存中。
+    pub fn grade(&mut self, front: &str, grade: Grade, today: NaiveDate) -> Result<()> {
+        let review = Review::next(self.review(front), grade, today);
+        self.reviews.insert(front.to_string(), review);
+        self.save()
+    }
+
+    /// 保存所有卡片的复习记录。
+    fn save(&self) -> Result<()> {
+        serde_json::to_vec_pretty(&self.reviews)
+            .map_err(io::Error::from)
+            .and_then(|bytes| files::write_atomic(&self.path, bytes))
+            .wrap_err_with(|| format!("writing {} failed", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    #[test]
+    fn intervals_grow() {
+        let first = Review::next(None, Grade::Good, day(1));
+        assert_eq!((first.interval_days, first.due), (1, day(2)));
+        let second = Review::next(Some(&first), Grade::Good, day(2));
+        assert_eq!((second.interval_days, second.due), (6, day(8)));
+        let third = Review::next(Some(&second), Grade::Good, day(8));
+        assert_eq!((third.interval_days, third.repetitions), (15, 3));
+
+        let hard = Review::next(Some(&third), Grade::Hard, day(23));
+        assert_eq!((hard.interval_days, hard.ease), (18, 2350));
+        let easy = Review::next(None, Grade::Easy, day(1));
+        assert_eq!((easy.interval_days, easy.ease), (4, 2650));
+
+        let again = Review::next(Some(&third), Grade::Again, day(23));
+        assert_eq!(
+            (again.interval_days, again.repetitions, again.due),
+            (0, 0, day(23))
+        );
+        assert_eq!(again.ease, 2300);
+    }
+
+    #[test]
+    fn persist_reviews() {
+        let dir = env::temp_dir().join(format!("flashcards-demo-schedule-{}", process::id()));
+        let path = dir.join("deck.json");
+
+        let mut schedule = Schedule::load(path.clone()).unwrap();
+        assert!(schedule.is_due("a", day(1)));
+        schedule.grade("a", Grade::Good, day(1)).unwrap();
+        assert!(!schedule.is_due("a", day(1)));
+
+        let schedule = Schedule::load(path).unwrap();
+        assert!(!schedule.is_due("a", day(1)));
+        assert!(schedule.is_due("a", day(2)));
+        assert!(schedule.is_due("b", day(1)));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
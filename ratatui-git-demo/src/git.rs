@@ -0,0 +1,332 @@
+//! 用 `git2` 读取工作区状态、暂存和取消暂存文件或单个差异块，以及提交。
+//!
+//! 暂存一个差异块的做法是把“索引到工作区”的差异中只包含这个块的部分应用到索引；
+//! 取消暂存则应用“HEAD 到索引”的反向差异中的这个块。
+
+use git2::{
+    ApplyLocation, ApplyOptions, Diff, DiffOptions, Patch, Repository, Status, StatusOptions,
+};
+
+/// `git status --short` 中的一行。两个字符分别是索引和工作区的状态，空格表示没有改动。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStatus {
+    pub path: String,
+    pub index: char,
+    pub worktree: char,
+}
+
+impl FileStatus {
+    pub fn is_staged(&self) -> bool {
+        self.index != ' ' && self.index != '?'
+    }
+
+    pub fn is_deleted_in_worktree(&self) -> bool {
+        self.worktree == 'D'
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// 在索引中（已暂存）还是只在工作区中。
+    pub staged: bool,
+    /// 同一侧的差异中的序号，暂存和取消暂存时用来选中这个块。
+    pub index: usize,
+    /// `@@ -1,3 +1,4 @@` 这样的标题。
+    pub header: String,
+    /// 每行的类型（`+`、`-` 或空格）和内容，不包括换行。
+    pub lines: Vec<(char, String)>,
+}
+
+pub fn status(repo: &Repository) -> Result<Vec<FileStatus>, git2::Error> {
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+    let statuses = repo.statuses(Some(&mut options))?;
+    let mut files: Vec<FileStatus> = statuses
+        .iter()
+        .filter_map(|entry| {
+            let status = entry.status();
+            let path = entry.path().ok()?.to_string();
+            if status.contains(Status::WT_NEW) {
+                return Some(FileStatus {
+                    path,
+                    index: '?',
+                    worktree: '?',
+                });
+            }
+            let index = [
+                (Status::INDEX_NEW, 'A'),
+                (Status::INDEX_MODIFIED, 'M'),
+                (Status::INDEX_DELETED, 'D'),
+                (Status::INDEX_RENAMED, 'R'),
+                (Status::INDEX_TYPECHANGE, 'T'),
+            ];
+            let worktree = [
+                (Status::WT_MODIFIED, 'M'),
+                (Status::WT_DELETED, 'D'),
+                (Status::WT_RENAMED, 'R'),
+                (Status::WT_TYPECHANGE, 'T'),
+            ];
+            let flag = |flags: &[(Status, char)]| {
+                flags
+                    .iter()
+                    .find(|(flag, _)| status.contains(*flag))
+                    .map_or(' ', |&(_, c)| c)
+            };
+            let file = FileStatus {
+                path,
+                index: flag(&index),
+                worktree: flag(&worktree),
+            };
+            (file.index != ' ' || file.worktree != ' ').then_some(file)
+        })
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+fn options(path: &str) -> DiffOptions {
+    let mut options = DiffOptions::new();
+    options
+        .pathspec(path)
+        .disable_pathspec_match(true)
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+    options
+}
+
+fn head_tree(repo: &Repository) -> Result<Option<git2::Tree<'_>>, git2::Error> {
+    match repo.head() {
+        Ok(head) => Ok(Some(head.peel_to_tree()?)),
+        // 还没有任何提交。
+        Err(error) if error.code() == git2::ErrorCode::UnbornBranch => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+fn staged_diff<'a>(
+    repo: &'a Repository,
+    path: &str,
+    reverse: bool,
+) -> Result<Diff<'a>, git2::Error> {
+    let tree = head_tree(repo)?;
+    let index = repo.index()?;
+    repo.diff_tree_to_index(
+        tree.as_ref(),
+        Some(&index),
+        Some(options(path).reverse(reverse)),
+    )
+}
+
+fn unstaged_diff<'a>(repo: &'a Repository, path: &str) -> Result<Diff<'a>, git2::Error> {
+    repo.diff_index_to_workdir(None, Some(&mut options(path)))
+}
+
+/// 一个文件的所有差异块，已暂存的在后。
+pub fn hunks(repo: &Repository, path: &str) -> Result<Vec<Hunk>, git2::Error> {
+    let mut hunks = collect(&unstaged_diff(repo, path)?, false)?;
+    hunks.extend(collect(&staged_diff(repo, path, false)?, true)?);
+    Ok(hunks)
+}
+
+fn collect(diff: &Diff, staged: bool) -> Result<Vec<Hunk>, git2::Error> {
+    let mut hunks = Vec::new();
+    for delta in 0..diff.deltas().len() {
+        // 二进制文件没有补丁。
+        let Some(patch) = Patch::from_diff(diff, delta)? else {
+            continue;
+        };
+        for index in 0..patch.num_hunks() {
+            let (hunk, count) = patch.hunk(index)?;
+            let mut lines = Vec::with_capacity(count);
+            for line in 0..count {
+                let line = patch.line_in_hunk(index, line)?;
+                let content = String::from_utf8_lossy(line.content());
+                lines.push((
+                    line.origin(),
+                    content.trim_end_matches(['\n', '\r']).to_string(),
+                ));
+            }
+            hunks.push(Hunk {
+                staged,
+                index: hunks.len(),
+                header: String::from_utf8_lossy(hunk.header())
+                    .trim_end()
+                    .to_string(),
+                lines,
+            });
+        }
+    }
+    Ok(hunks)
+}
+
+/// 把差异中的第 `hunk` 个块应用到索引。
+fn apply_hunk(repo: &Repository, diff: &Diff, hunk: usize) -> Result<(), git2::Error> {
+    let mut seen = 0;
+    let mut options = ApplyOptions::new();
+    options.hunk_callback(|_| {
+        seen += 1;
+        seen - 1 == hunk
+    });
+    repo.apply(diff, ApplyLocation::Index, Some(&mut options))
+}
+
+pub fn stage_hunk(repo: &Repository, path: &str, hunk: usize) -> Result<(), git2::Error> {
+    apply_hunk(repo, &unstaged_diff(repo, path)?, hunk)
+}
+
+pub fn unstage_hunk(repo: &Repository, path: &str, hunk: usize) -> Result<(), git2::Error> {
+    apply_hunk(repo, &staged_diff(repo, path, true)?, hunk)
+}
+
+/// 和 `git add` 一样暂存整个文件，工作区中删除的文件从索引中移除。
+pub fn stage_file(repo: &Repository, file: &FileStatus) -> Result<(), git2::Error> {
+    let mut index = repo.index()?;
+    if file.is_deleted_in_worktree() {
+        index.remove_path(file.path.as_ref())?;
+    } else {
+        index.add_path(file.path.as_ref())?;
+    }
+    index.write()
+}
+
+/// 和 `git restore --staged` 一样让索引中的文件回到 HEAD 的版本。
+pub fn unstage_file(repo: &Repository, file: &FileStatus) -> Result<(), git2::Error> {
+    match repo.head() {
+        Ok(head) => {
+            let commit = head.peel_to_commit()?;
+            repo.reset_default(Some(commit.as_object()), [&file.path])
+        }
+        Err(error) if error.code() == git2::ErrorCode::UnbornBranch => {
+            let mut index = repo.index()?;
+            index.remove_path(file.path.as_ref())?;
+            index.write()
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// 用索引的内容提交，返回新提交的 ID。作者和提交者来自 git 配置。
+pub fn commit(repo: &Repository, message: &str) -> Result<git2::Oid, git2::Error> {
+    let signature = repo.signature()?;
+    let mut index = repo.index()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let parent = match repo.head() {
+        Ok(head) => Some(head.peel_to_commit()?),
+        Err(error) if error.code() == git2::ErrorCode::UnbornBranch => None,
+        Err(error) => return Err(error),
+    };
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &parents,
+    )
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::{fs, path::PathBuf, process};
+
+    use super::*;
+
+    /// 临时目录中的仓库，`file.txt` 已经提交。
+    pub fn fixture(name: &str) -> (PathBuf, Repository) {
+        let root = std::env::temp_dir().join(format!("git-demo-{name}-{}", process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let repo = Repository::init(&root).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        let lines: Vec<String> = (1..=20).map(|n| format!("line {n}")).collect();
+        fs::write(root.join("file.txt"), lines.join("\n") + "\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path("file.txt".as_ref()).unwrap();
+        index.write().unwrap();
+        commit(&repo, "initial").unwrap();
+        (root, repo)
+    }
+
+    /// 修改第一行和最后一行，得到两个差异块。
+    pub fn edit_two_places(root: &std::path::Path) {
+        let mut lines: Vec<String> = (1..=20).map(|n| format!("line {n}")).collect();
+        lines[0] = "first".into();
+        lines[19] = "last".into();
+        fs::write(root.join("file.txt"), lines.join("\n") + "\n").unwrap();
+    }
+
+    #[test]
+    fn stage_and_unstage_hunks() {
+        let (root, repo) = fixture("hunks");
+        edit_two_places(&root);
+        fs::write(root.join("new.txt"), "hello\n").unwrap();
+        assert_eq!(
+            status(&repo).unwrap(),
+            [
+                FileStatus {
+                    path: "file.txt".into(),
+                    index: ' ',
+                    worktree: 'M'
+                },
+                FileStatus {
+                    path: "new.txt".into(),
+                    index: '?',
+                    worktree: '?'
+                },
+            ]
+        );
+        let hunks = hunks(&repo, "file.txt").unwrap();
+        assert_eq!(hunks.len(), 2);
+        assert!(hunks[0].header.starts_with("@@ -1,"));
+        assert!(hunks[0].lines.contains(&('+', "first".into())));
+
+        // 只暂存第二个块。
+        stage_hunk(&repo, "file.txt", 1).unwrap();
+        let hunks = super::hunks(&repo, "file.txt").unwrap();
+        let staged: Vec<bool> = hunks.iter().map(|hunk| hunk.staged).collect();
+        assert_eq!(staged, [false, true]);
+        assert!(hunks[1].lines.contains(&('+', "last".into())));
+        assert_eq!(status(&repo).unwrap()[0].index, 'M');
+
+        unstage_hunk(&repo, "file.txt", 0).unwrap();
+        assert!(super::hunks(&repo, "file.txt")
+            .unwrap()
+            .iter()
+            .all(|hunk| !hunk.staged));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn stage_files_and_commit() {
+        let (root, repo) = fixture("commit");
+        fs::write(root.join("new.txt"), "hello\n").unwrap();
+        fs::remove_file(root.join("file.txt")).unwrap();
+        for file in status(&repo).unwrap() {
+            stage_file(&repo, &file).unwrap();
+        }
+        let files = status(&repo).unwrap();
+        let flags: Vec<(char, char)> = files.iter().map(|f| (f.index, f.worktree)).collect();
+        assert_eq!(flags, [('D', ' '), ('A', ' ')]);
+
+        unstage_file(&repo, &files[1]).unwrap();
+        assert_eq!(status(&repo).unwrap()[1].index, '?');
+
+        commit(&repo, "remove file.txt").unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.message().unwrap(), "remove file.txt");
+        assert_eq!(head.parent_count(), 1);
+        let flags: Vec<String> = status(&repo)
+            .unwrap()
+            .iter()
+            .map(|f| f.path.clone())
+            .collect();
+        assert_eq!(flags, ["new.txt"]);
+        fs::remove_dir_all(root).unwrap();
+    }
+}
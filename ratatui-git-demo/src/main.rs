@@ -0,0 +1,433 @@
+//! 最小的 git 暂存界面：左侧是工作区状态，右侧是所选文件的差异；可以暂存或取消暂存整个文件或单个差异块，
+//! 然后在编辑框中写提交说明并提交。仓库操作见 `git` 模块，基于 `git2`，不需要安装 git 命令。
+//!
+//! 按键：
+//!
+//! ```text
+//! Up / Down    选择文件或差异块     Tab        在文件和差异块之间切换
+//! s            暂存                 u          取消暂存
+//! c            写提交说明           q          退出
+//! ```
+//!
+//! 提交说明编辑框中 `Ctrl+S` 提交，`Esc` 取消。
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use color_eyre::{eyre::WrapErr, Result};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use git2::Repository;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Clear, List, ListState, Paragraph},
+};
+use ratatui_common::{
+    editor::Editor,
+    events::{EventSource, TerminalEvents},
+    layout::centered,
+    terminal,
+};
+
+use crate::git::{FileStatus, Hunk};
+
+mod git;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// 仓库中的任意路径。
+    #[arg(default_value = ".")]
+    path: PathBuf,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let repo = Repository::discover(&cli.path)
+        .wrap_err_with(|| format!("{} is not in a git repository", cli.path.display()))?;
+    let mut app = App::new(repo).wrap_err("reading the repository status failed")?;
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = app.run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Files,
+    Hunks,
+}
+
+struct App {
+    repo: Repository,
+    files: Vec<FileStatus>,
+    list: ListState,
+    /// 所选文件的差异块和所选的块。
+    hunks: Vec<Hunk>,
+    hunk: usize,
+    focus: Focus,
+    /// 正在编辑的提交说明。
+    editor: Option<Editor>,
+    /// 最近一次操作的结果，`Err` 显示为红色。
+    message: Option<Result<String, String>>,
+    exit: bool,
+}
+
+impl App {
+    fn new(repo: Repository) -> Result<Self, git2::Error> {
+        let mut app = Self {
+            repo,
+            files: Vec::new(),
+            list: ListState::default().with_selected(Some(0)),
+            hunks: Vec::new(),
+            hunk: 0,
+            focus: Focus::Files,
+            editor: None,
+            message: None,
+            exit: false,
+        };
+        app.refresh()?;
+        Ok(app)
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut()))?;
+            if let Event::Key(key) = events.read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 重新读取状态和所选文件的差异，选择尽量停在原来的位置。
+    fn refresh(&mut self) -> Result<(), git2::Error> {
+        self.files = git::status(&self.repo)?;
+        let last = self.files.len().saturating_sub(1);
+        self.list
+            .select(Some(self.list.selected().unwrap_or(0).min(last)));
+        self.hunks = match self.selected() {
+            Some(file) => git::hunks(&self.repo, &file.path)?,
+            None => Vec::new(),
+        };
+        self.hunk = self.hunk.min(self.hunks.len().saturating_sub(1));
+        if self.hunks.is_empty() {
+            self.focus = Focus::Files;
+        }
+        Ok(())
+    }
+
+    fn selected(&self) -> Option<&FileStatus> {
+        self.files.get(self.list.selected()?)
+    }
+
+    /// 执行一个仓库操作并刷新，结果显示在状态栏中。
+    fn perform(
+        &mut self,
+        success: String,
+        operation: impl FnOnce(&Repository) -> Result<(), git2::Error>,
+    ) {
+        let result = operation(&self.repo).and_then(|()| self.refresh());
+        self.message = Some(result.map(|()| success).map_err(|error| error.to_string()));
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if key.code == KeyCode::Char('c') && key.modifiers == KeyModifiers::CONTROL {
+            self.exit = true;
+            return;
+        }
+        if let Some(editor) = &mut self.editor {
+            match key.code {
+                KeyCode::Esc => self.editor = None,
+                KeyCode::Char('s') if key.modifiers == KeyModifiers::CONTROL => {
+                    let message = editor.text().trim().to_string();
+                    self.commit(&message);
+                }
+                _ => {
+                    editor.handle_key_event(key);
+                }
+            }
+            return;
+        }
+        self.message = None;
+        match (self.focus, key.code) {
+            (_, KeyCode::Char('q') | KeyCode::Esc) => self.exit = true,
+            (_, KeyCode::Char('c')) => {
+                if self.files.iter().any(FileStatus::is_staged) {
+                    self.editor = Some(Editor::new(""));
+                } else {
+                    self.message = Some(Err("nothing staged to commit".into()));
+                }
+            }
+            (Focus::Files, KeyCode::Tab) if !self.hunks.is_empty() => {
+                self.focus = Focus::Hunks;
+                self.hunk = 0;
+            }
+            (Focus::Hunks, KeyCode::Tab | KeyCode::BackTab) => self.focus = Focus::Files,
+            (Focus::Files, KeyCode::Up | KeyCode::Down) => {
+                let selected = self.list.selected().unwrap_or(0);
+                let selected = if key.code == KeyCode::Up {
+                    selected.saturating_sub(1)
+                } else {
+                    (selected + 1).min(self.files.len().saturating_sub(1))
+                };
+                self.list.select(Some(selected));
+                self.hunk = 0;
+                if let Err(error) = self.refresh() {
+                    self.message = Some(Err(error.to_string()));
+                }
+            }
+            (Focus::Hunks, KeyCode::Up) => self.hunk = self.hunk.saturating_sub(1),
+            (Focus::Hunks, KeyCode::Down) => {
+                self.hunk = (self.hunk + 1).min(self.hunks.len().saturating_sub(1))
+            }
+            (Focus::Files, KeyCode::Char('s')) => {
+                if let Some(file) = self.selected().cloned() {
+                    self.perform(format!("staged {}", file.path), |repo| {
+                        git::stage_file(repo, &file)
+                    });
+                }
+            }
+            (Focus::Files, KeyCode::Char('u')) => {
+                if let Some(file) = self.selected().filter(|file| file.is_staged()).cloned() {
+                    self.perform(format!("unstaged {}", file.path), |repo| {
+                        git::unstage_file(repo, &file)
+                    });
+                }
+            }
+            (Focus::Hunks, KeyCode::Char(c @ ('s' | 'u'))) => {
+                let (Some(file), Some(hunk)) = (self.selected(), self.hunks.get(self.hunk)) else {
+                    return;
+                };
+                // 已暂存的块只能取消暂存，反之亦然。
+                if hunk.staged != (c == 'u') {
+                    return;
+                }
+                let (path, index, staged) = (file.path.clone(), hunk.index, hunk.staged);
+                if staged {
+                    self.perform(format!("unstaged a hunk of {path}"), |repo| {
+                        git::unstage_hunk(repo, &path, index)
+                    });
+                } else {
+                    self.perform(format!("staged a hunk of {path}"), |repo| {
+                        git::stage_hunk(repo, &path, index)
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn commit(&mut self, message: &str) {
+        if message.is_empty() {
+            self.message = Some(Err("the commit message is empty".into()));
+            return;
+        }
+        let result = git::commit(&self.repo, message).and_then(|id| {
+            self.refresh()?;
+            Ok(id)
+        });
+        match result {
+            Ok(id) => {
+                let summary = message.lines().next().unwrap_or_default();
+                self.message = Some(Ok(format!("committed {:.7} {summary}", id)));
+                self.editor = None;
+            }
+            // 出错时保留编辑框，说明不会丢失。
+            Err(error) => self.message = Some(Err(error.to_string())),
+        }
+    }
+
+    fn border(&self, focus: Focus) -> Style {
+        if self.focus == focus && self.editor.is_none() {
+            Style::new().green()
+        } else {
+            Style::new()
+        }
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let [files_area, diff_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Fill(2)]).areas(main);
+
+        let items: Vec<Line> = self
+            .files
+            .iter()
+            .map(|file| {
+                Line::from(vec![
+                    file.index.to_string().green(),
+                    file.worktree.to_string().red(),
+                    format!(" {}", file.path).into(),
+                ])
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(Style::new().reversed())
+            .block(
+                Block::bordered()
+                    .title(" Status ".bold())
+                    .title_bottom(Line::from(" Stage <S> Unstage <U> Commit <C> ").centered())
+                    .border_style(self.border(Focus::Files)),
+            );
+        StatefulWidget::render(list, files_area, buf, &mut self.list);
+
+        let (lines, top) = self.diff_lines();
+        let title = match self.selected() {
+            Some(file) => format!(" {} ", file.path),
+            None => " Diff ".into(),
+        };
+        Paragraph::new(lines)
+            .scroll((top, 0))
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .border_style(self.border(Focus::Hunks)),
+            )
+            .render(diff_area, buf);
+
+        Paragraph::new(self.status_line()).render(status, buf);
+
+        if let Some(editor) = &self.editor {
+            let popup = centered(main, 60, 10);
+            Clear.render(popup, buf);
+            Paragraph::new(editor.styled_lines())
+                .block(
+                    Block::bordered()
+                        .title(" Commit message ".bold())
+                        .title_bottom(Line::from(" Commit <Ctrl+S> Cancel <Esc> ").centered())
+                        .border_style(Style::new().green()),
+                )
+                .render(popup, buf);
+        }
+    }
+
+    /// 差异的所有行，以及让所选的块出现在顶部的滚动位置。
+    fn diff_lines(&self) -> (Vec<Line<'static>>, u16) {
+        let mut lines = Vec::new();
+        let mut top = 0;
+        let mut section = None;
+        for (position, hunk) in self.hunks.iter().enumerate() {
+            if section != Some(hunk.staged) {
+                section = Some(hunk.staged);
+                let label = if hunk.staged { "Staged" } else { "Unstaged" };
+                lines.push(Line::from(label.bold().underlined()));
+            }
+            let mut header = Span::from(hunk.header.clone()).cyan();
+            if position == self.hunk && self.focus == Focus::Hunks {
+                header = header.reversed();
+                top = lines.len().saturating_sub(1) as u16;
+            }
+            lines.push(Line::from(header));
+            for (origin, text) in &hunk.lines {
+                let line = format!("{origin}{text}");
+                lines.push(match origin {
+                    '+' => Line::from(line.green()),
+                    '-' => Line::from(line.red()),
+                    _ => Line::from(line),
+                });
+            }
+        }
+        if lines.is_empty() {
+            lines.push(Line::from("No changes.".dim()));
+        }
+        (lines, top)
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        match &self.message {
+            Some(Ok(message)) => Line::from(message.clone().green()),
+            Some(Err(message)) => Line::from(message.clone().red()),
+            None => {
+                let staged = self.files.iter().filter(|file| file.is_staged()).count();
+                Line::from(format!(
+                    "{} changed files, {staged} staged  Focus <Tab> Quit <Q>",
+                    self.files.len()
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use ratatui_common::testing;
+
+    use super::*;
+    use crate::git::tests::{edit_two_places, fixture};
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    #[test]
+    fn stage_hunk_and_commit() {
+        let (root, repo) = fixture("app");
+        edit_two_places(&root);
+        let mut app = App::new(repo).unwrap();
+        assert_eq!(app.hunks.len(), 2);
+        // 没有暂存任何内容时不能提交。
+        testing::press(&[KeyCode::Char('c')], |key| app.handle_key(key));
+        assert!(app.editor.is_none());
+
+        testing::press(&[KeyCode::Tab, KeyCode::Down, KeyCode::Char('s')], |key| {
+            app.handle_key(key)
+        });
+        assert_eq!(
+            app.message,
+            Some(Ok("staged a hunk of file.txt".to_string()))
+        );
+        let staged: Vec<bool> = app.hunks.iter().map(|hunk| hunk.staged).collect();
+        assert_eq!(staged, [false, true]);
+        let rows = rows(&mut app, 60, 12);
+        assert!(rows[1].starts_with("│MM file.txt"));
+        assert!(rows.iter().any(|row| row.contains("│Staged")));
+
+        testing::press(&[KeyCode::Char('c')], |key| app.handle_key(key));
+        for c in "Change the last line".chars() {
+            app.handle_key(KeyCode::Char(c).into());
+        }
+        app.handle_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL));
+        assert!(app.editor.is_none());
+        assert!(
+            matches!(&app.message, Some(Ok(message)) if message.ends_with(" Change the last line"))
+        );
+        // 没有暂存的块仍然在工作区中。
+        let staged: Vec<bool> = app.hunks.iter().map(|hunk| hunk.staged).collect();
+        assert_eq!(staged, [false]);
+        assert_eq!(app.files[0].index, ' ');
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn empty_commit_message() {
+        let (root, repo) = fixture("empty-message");
+        fs::write(root.join("new.txt"), "hello\n").unwrap();
+        let mut app = App::new(repo).unwrap();
+        testing::press(&[KeyCode::Char('s'), KeyCode::Char('c')], |key| {
+            app.handle_key(key)
+        });
+        assert_eq!(app.files[0].index, 'A');
+        app.handle_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL));
+        assert!(app.editor.is_some());
+        assert_eq!(
+            app.message,
+            Some(Err("the commit message is empty".to_string()))
+        );
+        testing::press(&[KeyCode::Esc, KeyCode::Char('u')], |key| {
+            app.handle_key(key)
+        });
+        assert_eq!(app.files[0].index, '?');
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,158 @@
+//! 习惯和每天的完成记录，以及连续完成的天数。
+
+use std::collections::BTreeSet;
+
+use time::Date;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Habit {
+    pub name: String,
+    done: BTreeSet<Date>,
+}
+
+impl Habit {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self::with_days(name, [])
+    }
+
+    pub fn with_days(name: impl Into<String>, days: impl IntoIterator<Item = Date>) -> Self {
+        Self {
+            name: name.into(),
+            done: days.into_iter().collect(),
+        }
+    }
+
+    /// 完成的日期，从早到晚。
+    pub fn days(&self) -> impl Iterator<Item = Date> + '_ {
+        self.done.iter().copied()
+    }
+
+    pub fn is_done(&self, date: Date) -> bool {
+        self.done.contains(&date)
+    }
+
+    /// 切换某一天是否完成，返回切换后的状态。
+    pub fn toggle(&mut self, date: Date) -> bool {
+        if self.done.remove(&date) {
+            false
+        } else {
+            self.done.insert(date);
+            true
+        }
+    }
+
+    /// 到今天为止连续完成的天数。今天还没有完成时从昨天算起，这样一天没有结束之前连续记录不会断。
+    pub fn streak(&self, today: Date) -> u32 {
+        let mut day = if self.is_done(today) {
+            Some(today)
+        } else {
+            today.previous_day()
+        };
+        let mut streak = 0;
+        while let Some(date) = day.filter(|&date| self.is_done(date)) {
+            streak += 1;
+            day = date.previous_day();
+        }
+        streak
+    }
+
+    /// 历史上最长的连续完成天数。
+    pub fn longest_streak(&self) -> u32 {
+        let mut longest = 0;
+        let mut current = 0;
+        let mut previous: Option<Date> = None;
+        for day in self.days() {
+            current = if previous.and_then(Date::next_day) == Some(day) {
+                current + 1
+            } else {
+                1
+            };
+            longest = longest.max(current);
+            previous = Some(day);
+        }
+        longest
+    }
+
+    /// `date` 所在的月份中完成的天数。
+    pub fn done_in_month(&self, date: Date) -> usize {
+        self.days()
+            .filter(|day| day.year() == date.year() && day.month() == date.month())
+            .count()
+    }
+}
+
+/// 一个月的第一天。
+pub fn first_of_month(date: Date) -> Date {
+    date.replace_day(1).expect("every month has a first day")
+}
+
+/// 上个月的第一天。
+pub fn previous_month(date: Date) -> Date {
+    first_of_month(date)
+        .previous_day()
+        .map_or(date, first_of_month)
+}
+
+/// 下个月的第一天。
+pub fn next_month(date: Date) -> Date {
+    let first = first_of_month(date);
+    // 从一号往后 31 天一定落在下个月里。
+    first
+        .checked_add(time::Duration::days(31))
+        .map_or(first, first_of_month)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Month;
+
+    use super::*;
+
+    fn day(month: Month, day: u8) -> Date {
+        Date::from_calendar_date(2026, month, day).unwrap()
+    }
+
+    #[test]
+    fn streaks() {
+        let mut habit = Habit::with_days(
+            "Read",
+            [
+                day(Month::September, 29),
+                day(Month::September, 30),
+                day(Month::October, 1),
+                day(Month::October, 5),
+                day(Month::October, 6),
+            ],
+        );
+        let today = day(Month::October, 7);
+        // 今天还没有完成，从昨天算起。
+        assert_eq!(habit.streak(today), 2);
+        assert!(habit.toggle(today));
+        assert_eq!(habit.streak(today), 3);
+        assert_eq!(habit.longest_streak(), 3);
+        assert_eq!(habit.done_in_month(today), 4);
+
+        // 中间断了一天之后重新开始计算。
+        assert_eq!(habit.streak(day(Month::October, 9)), 0);
+        assert!(!habit.toggle(today));
+        assert_eq!(habit.streak(today), 2);
+        assert_eq!(Habit::new("Run").longest_streak(), 0);
+    }
+
+    #[test]
+    fn months() {
+        assert_eq!(
+            previous_month(day(Month::March, 31)),
+            day(Month::February, 1)
+        );
+        assert_eq!(next_month(day(Month::January, 31)), day(Month::February, 1));
+        assert_eq!(
+            next_month(day(Month::December, 15)),
+            Date::from_calendar_date(2027, Month::January, 1).unwrap()
+        );
+        assert_eq!(
+            previous_month(day(Month::January, 1)),
+            Date::from_calendar_date(2025, Month::December, 1).unwrap()
+        );
+    }
+}
@@ -0,0 +1,489 @@
+//! 习惯打卡演示：左边是习惯列表和连续完成的天数，右边用 ratatui 的月历控件显示选中习惯一个月
+//! 内的完成记录。
+//!
+//! 只能给今天打卡，这样记录反映的是真实的习惯而不是补出来的。习惯和记录保存在数据目录中的
+//! `habits.json`（见 `store` 模块），每次修改后立即写入。
+//!
+//! 按键：`↑` / `↓` 选择习惯，`Space` 切换今天是否完成，`a` 添加习惯，`x` 删除习惯，
+//! `←` / `→` 查看上个月和下个月，`t` 回到本月，`q` 退出。
+
+use std::{path::PathBuf, time::Duration};
+
+use clap::Parser;
+use color_eyre::{eyre::eyre, Result};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use directories::ProjectDirs;
+use ratatui::{
+    prelude::*,
+    widgets::{
+        calendar::{CalendarEventStore, Monthly},
+        Block, List, ListItem, ListState, Padding, Paragraph,
+    },
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+    text_input::Input,
+};
+use time::{Date, OffsetDateTime, UtcOffset};
+
+use crate::{
+    habits::{first_of_month, next_month, previous_month, Habit},
+    store::Store,
+};
+
+mod habits;
+mod store;
+
+const APPLICATION: &str = "ratatui-habits-demo";
+
+/// 等待按键的最长时间，之后重新读取日期，过了午夜后“今天”随之改变。
+const REFRESH: Duration = Duration::from_secs(1);
+
+/// 月历的宽度：七列日期加上边框和留白。
+const CALENDAR_WIDTH: u16 = 27;
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 保存习惯的文件，默认是数据目录中的 `habits.json`
+    #[arg(long, value_name = "FILE")]
+    data: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let path = cli
+        .data
+        .or_else(|| {
+            let project = ProjectDirs::from("", "", APPLICATION)?;
+            Some(project.data_dir().join("habits.json"))
+        })
+        .ok_or_else(|| eyre!("no data directory, pass --data"))?;
+    let store = Store::new(path);
+    let habits = store.load()?;
+    // 时区只在启动时读取一次：程序开始使用多个线程之后就无法可靠地读取了。
+    let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result =
+        App::new(habits, store, today(offset)).run(&mut terminal, &mut TerminalEvents, offset);
+    terminal::restore()?;
+    result
+}
+
+fn today(offset: UtcOffset) -> Date {
+    OffsetDateTime::now_utc().to_offset(offset).date()
+}
+
+fn days(count: u32) -> String {
+    match count {
+        1 => "1 day".into(),
+        count => format!("{count} days"),
+    }
+}
+
+struct App {
+    habits: Vec<Habit>,
+    store: Store,
+    list: ListState,
+    today: Date,
+    /// 月历显示的月份，总是某个月的一号。
+    month: Date,
+    /// 正在输入的新习惯名称。
+    new_habit: Option<Input>,
+    /// 等待确认删除的习惯。
+    confirm: Option<usize>,
+    /// 显示在状态栏中的提示或错误，下一次按键时清除。
+    message: Option<Result<String, String>>,
+    exit: bool,
+}
+
+impl App {
+    fn new(habits: Vec<Habit>, store: Store, today: Date) -> Self {
+        let selected = (!habits.is_empty()).then_some(0);
+        Self {
+            habits,
+            store,
+            list: ListState::default().with_selected(selected),
+            today,
+            month: first_of_month(today),
+            new_habit: None,
+            confirm: None,
+            message: None,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+        offset: UtcOffset,
+    ) -> Result<()> {
+        while !self.exit {
+            self.set_today(today(offset));
+            terminal.draw(|frame| frame.render_widget(&mut *self, frame.size()))?;
+            if events.poll(REFRESH)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 日期变化时，如果正在看本月的月历就跟着换到新的月份。
+    fn set_today(&mut self, today: Date) {
+        if self.month == first_of_month(self.today) {
+            self.month = first_of_month(today);
+        }
+        self.today = today;
+    }
+
+    fn selected(&self) -> Option<usize> {
+        self.list
+            .selected()
+            .filter(|&index| index < self.habits.len())
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        self.message = None;
+        if let Some(input) = &mut self.new_habit {
+            match key.code {
+                KeyCode::Esc => self.new_habit = None,
+                KeyCode::Enter => {
+                    let name = input.value().trim().to_string();
+                    self.new_habit = None;
+                    self.add(name);
+                }
+                _ => {
+                    input.handle_key_event(key);
+                }
+            }
+            return;
+        }
+        if let Some(index) = self.confirm.take() {
+            if key.code == KeyCode::Char('y') {
+                let habit = self.habits.remove(index);
+                self.select(index.min(self.habits.len().saturating_sub(1)));
+                self.changed(format!("Deleted {}", habit.name));
+            }
+            return;
+        }
+        let selected = self.selected();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.select(selected.unwrap_or(0).saturating_sub(1));
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.select(selected.map_or(0, |index| index + 1));
+            }
+            KeyCode::Char(' ') | KeyCode::Enter => {
+                if let Some(index) = selected {
+                    let habit = &mut self.habits[index];
+                    let message = if habit.toggle(self.today) {
+                        format!(
+                            "{} done today, streak {}",
+                            habit.name,
+                            days(habit.streak(self.today))
+                        )
+                    } else {
+                        format!("{} not done today", habit.name)
+                    };
+                    self.changed(message);
+                }
+            }
+            KeyCode::Char('a') => self.new_habit = Some(Input::default()),
+            KeyCode::Char('x') | KeyCode::Delete => self.confirm = selected,
+            KeyCode::Left | KeyCode::Char('h') => self.month = previous_month(self.month),
+            KeyCode::Right | KeyCode::Char('l') => self.month = next_month(self.month),
+            KeyCode::Char('t') => self.month = first_of_month(self.today),
+            _ => {}
+        }
+    }
+
+    fn select(&mut self, index: usize) {
+        let last = self.habits.len().checked_sub(1);
+        self.list.select(last.map(|last| index.min(last)));
+    }
+
+    fn add(&mut self, name: String) {
+        if name.is_empty() {
+            return;
+        }
+        if self.habits.iter().any(|habit| habit.name == name) {
+            self.message = Some(Err(format!("{name} is already on the list")));
+            return;
+        }
+        self.habits.push(Habit::new(name.clone()));
+        self.select(self.habits.len() - 1);
+        self.changed(format!("Added {name}"));
+    }
+
+    /// 修改后立即保存。
+    fn changed(&mut self, message: String) {
+        self.message = Some(match self.store.save(&self.habits) {
+            Ok(()) => Ok(message),
+            Err(error) => Err(format!("{error:#}")),
+        });
+    }
+
+    fn render_list(&mut self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(" Habits ")
+            .title_bottom(Line::from(" Done <Space> Add <a> Delete <x> Quit <q> ").right_aligned())
+            .border_style(Style::new().green());
+        if self.habits.is_empty() {
+            Paragraph::new("No habits yet, press a to add one".dim())
+                .block(block)
+                .render(area, buf);
+            return;
+        }
+        let width = usize::from(area.width.saturating_sub(2));
+        let items: Vec<ListItem> = self
+            .habits
+            .iter()
+            .map(|habit| {
+                let (mark, style) = if habit.is_done(self.today) {
+                    ("✓", Style::new().light_green().bold())
+                } else {
+                    ("·", Style::new().dark_gray())
+                };
+                let streak = format!(
+                    "{:>8}  best {:>3} ",
+                    days(habit.streak(self.today)),
+                    habit.longest_streak()
+                );
+                let name_width = width.saturating_sub(streak.len() + 4);
+                Line::from(vec![
+                    format!(" {mark} ").set_style(style),
+                    format!("{:<name_width$.name_width$}", habit.name).into(),
+                    streak.dim(),
+                ])
+                .into()
+            })
+            .collect();
+        StatefulWidget::render(
+            List::new(items)
+                .block(block)
+                .highlight_style(Style::new().reversed()),
+            area,
+            buf,
+            &mut self.list,
+        );
+    }
+
+    fn render_calendar(&self, area: Rect, buf: &mut Buffer) {
+        let [calendar_area, stats_area] =
+            Layout::vertical([Constraint::Length(10), Constraint::Fill(1)]).areas(area);
+        let habit = self.selected().map(|index| &self.habits[index]);
+        let mut events = CalendarEventStore::default();
+        for day in habit.into_iter().flat_map(Habit::days) {
+            events.add(day, Style::new().black().on_green());
+        }
+        let today = match habit {
+            Some(habit) if habit.is_done(self.today) => {
+                Style::new().black().on_green().underlined()
+            }
+            _ => Style::new().yellow().bold().underlined(),
+        };
+        events.add(self.today, today);
+        let title = habit.map_or_else(String::new, |habit| format!(" {} ", habit.name));
+        Monthly::new(self.month, events)
+            .show_month_header(Style::new().bold())
+            .show_weekdays_header(Style::new().dim())
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .title_bottom(Line::from(" Month <←/→> ").right_aligned())
+                    .padding(Padding::horizontal(2)),
+            )
+            .render(calendar_area, buf);
+
+        if let Some(habit) = habit {
+            let month_days = next_month(self.month)
+                .previous_day()
+                .map_or(31, |last| last.day());
+            let lines = vec![
+                Line::from(vec![
+                    "This month ".dim(),
+                    format!("{}/{month_days}", habit.done_in_month(self.month)).into(),
+                ]),
+                Line::from(vec!["Streak ".dim(), days(habit.streak(self.today)).bold()]),
+                Line::from(vec!["Best ".dim(), days(habit.longest_streak()).into()]),
+            ];
+            Paragraph::new(lines)
+                .block(Block::new().padding(Padding::horizontal(1)))
+                .render(stats_area, buf);
+        }
+    }
+
+    fn status_line(&self) -> Line<'_> {
+        if let Some(index) = self.confirm {
+            return Line::from(format!(
+                "Delete {} and its history? <Y>/<N>",
+                self.habits[index].name
+            ))
+            .red()
+            .bold();
+        }
+        if let Some(input) = &self.new_habit {
+            return input.line("New habit: ", Style::new().bold());
+        }
+        match &self.message {
+            Some(Ok(message)) => Line::from(message.as_str()),
+            Some(Err(error)) => Line::from(error.as_str().red()),
+            None => Line::from(format!("Today is {}", self.today).dim()),
+        }
+    }
+}
+
+impl Widget for &mut App {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let [list_area, calendar_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(CALENDAR_WIDTH)])
+                .areas(main);
+        self.render_list(list_area, buf);
+        self.render_calendar(calendar_area, buf);
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use ratatui_common::testing;
+    use time::Month;
+
+    use super::*;
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    fn day(month: Month, day: u8) -> Date {
+        Date::from_calendar_date(2026, month, day).unwrap()
+    }
+
+    fn app(name: &str) -> (App, PathBuf) {
+        let dir = env::temp_dir().join(format!("habits-demo-{name}-{}", process::id()));
+        let habits = vec![
+            Habit::with_days(
+                "Read",
+                [
+                    day(Month::October, 11),
+                    day(Month::October, 12),
+                    day(Month::October, 13),
+                ],
+            ),
+            Habit::new("Stretch"),
+        ];
+        let app = App::new(
+            habits,
+            Store::new(dir.join("habits.json")),
+            day(Month::October, 14),
+        );
+        (app, dir)
+    }
+
+    #[test]
+    fn render_and_toggle_today() {
+        let (mut app, dir) = app("toggle");
+        let screen = rows(&mut app, 70, 14);
+        assert_eq!(
+            screen[0],
+            "┌ Habits ─────────────────────────────────┐┌ Read ───────────────────┐"
+        );
+        assert_eq!(
+            screen[1],
+            "│ · Read                3 days  best   3  ││      October 2026       │"
+        );
+        assert_eq!(
+            screen[5],
+            "│                                         ││   11 12 13 14 15 16 17  │"
+        );
+        assert_eq!(
+            screen[11],
+            "│                                         │ Streak 3 days             "
+        );
+        assert_eq!(screen[13].trim_end(), "Today is 2026-10-14");
+
+        app.handle_key(KeyCode::Char(' ').into());
+        assert_eq!(
+            app.message,
+            Some(Ok("Read done today, streak 4 days".into()))
+        );
+        let screen = rows(&mut app, 70, 14);
+        assert!(screen[1].starts_with("│ ✓ Read                4 days  best   4"));
+        assert_eq!(
+            screen[10],
+            "│                                         │ This month 4/31           "
+        );
+        let saved = Store::new(dir.join("habits.json")).load().unwrap();
+        assert!(saved[0].is_done(day(Month::October, 14)));
+
+        // 另一个习惯有自己的月历，切换回来时今天的记录取消。
+        app.handle_key(KeyCode::Down.into());
+        assert!(rows(&mut app, 70, 14)[0].contains("┌ Stretch "));
+        app.handle_key(KeyCode::Up.into());
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(app.message, Some(Ok("Read not done today".into())));
+        assert_eq!(app.habits[0].streak(app.today), 3);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn add_delete_and_months() {
+        let (mut app, dir) = app("edit");
+        app.handle_key(KeyCode::Char('a').into());
+        for c in "Stretch".chars() {
+            app.handle_key(KeyCode::Char(c).into());
+        }
+        assert_eq!(rows(&mut app, 70, 14)[13].trim_end(), "New habit: Stretch");
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(
+            app.message,
+            Some(Err("Stretch is already on the list".into()))
+        );
+        app.handle_key(KeyCode::Char('a').into());
+        for c in "Walk".chars() {
+            app.handle_key(KeyCode::Char(c).into());
+        }
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(app.habits.len(), 3);
+        assert_eq!(app.selected(), Some(2));
+
+        app.handle_key(KeyCode::Char('x').into());
+        assert_eq!(
+            rows(&mut app, 70, 14)[13].trim_end(),
+            "Delete Walk and its history? <Y>/<N>"
+        );
+        app.handle_key(KeyCode::Char('n').into());
+        assert_eq!(app.habits.len(), 3);
+        app.handle_key(KeyCode::Char('x').into());
+        app.handle_key(KeyCode::Char('y').into());
+        assert_eq!(app.habits.len(), 2);
+        assert_eq!(app.selected(), Some(1));
+        assert_eq!(Store::new(dir.join("habits.json")).load().unwrap().len(), 2);
+
+        app.handle_key(KeyCode::Left.into());
+        app.handle_key(KeyCode::Left.into());
+        assert_eq!(app.month, day(Month::August, 1));
+        assert!(rows(&mut app, 70, 14)[1].contains("August 2026"));
+        // 看别的月份时日期变化不会改变显示的月份。
+        app.set_today(day(Month::October, 15));
+        assert_eq!(app.month, day(Month::August, 1));
+        app.handle_key(KeyCode::Char('t').into());
+        app.set_today(day(Month::November, 1));
+        assert_eq!(app.month, day(Month::November, 1));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,138 @@
+//! 保存习惯列表和完成记录，保存为 JSON，每次修改后立即写入。
+//!
+//! 日期写作 `YYYY-MM-DD`，这样文件可以直接阅读和编辑。
+
+use std::{fs, io, path::PathBuf};
+
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use ratatui_common::files;
+use serde::{Deserialize, Serialize};
+use time::{Date, Month};
+
+use crate::habits::Habit;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Saved {
+    habits: Vec<SavedHabit>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedHabit {
+    name: String,
+    done: Vec<String>,
+}
+
+fn format_date(date: Date) -> String {
+    format!(
+        "{:04}-{:02}-{:02}",
+        date.year(),
+        u8::from(date.month()),
+        date.day()
+    )
+}
+
+fn parse_date(text: &str) -> Result<Date> {
+    let parse = || -> Option<Date> {
+        let mut parts = text.splitn(3, '-');
+        let year = parts.next()?.parse().ok()?;
+        let month = Month::try_from(parts.next()?.parse::<u8>().ok()?).ok()?;
+        let day = parts.next()?.parse().ok()?;
+        Date::from_calendar_date(year, month, day).ok()
+    };
+    parse().ok_or_else(|| eyre!("{text:?} is not a date like 2026-10-14"))
+}
+
+#[derive(Debug)]
+pub struct Store {
+    path: PathBuf,
+}
+
+impl Store {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// 读取保存的习惯。文件还不存在时为空列表。
+    pub fn load(&self) -> Result<Vec<Habit>> {
+        let json = match fs::read_to_string(&self.path) {
+            Ok(json) => json,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(err).wrap_err_with(|| format!("reading {} failed", self.path.display()))
+            }
+        };
+        let parse = || -> Result<Vec<Habit>> {
+            let saved: Saved = serde_json::from_str(&json)?;
+            saved
+                .habits
+                .into_iter()
+                .map(|habit| {
+                    let days = habit
+                        .done
+                        .iter()
+                        .map(|text| parse_date(text))
+                        .collect::<Result<Vec<_>>>()?;
+                    Ok(Habit::with_days(habit.name, days))
+                })
+                .collect()
+        };
+        parse().wrap_err_with(|| format!("parsing {} failed", self.path.display()))
+    }
+
+    /// 保存所有习惯和完成的日期。
+    pub fn save(&self, habits: &[Habit]) -> Result<()> {
+        let saved = Saved {
+            habits: habits
+                .iter()
+                .map(|habit| SavedHabit {
+                    name: habit.name.clone(),
+                    done: habit.days().map(format_date).collect(),
+                })
+                .collect(),
+        };
+        serde_json::to_vec_pretty(&saved)
+            .map_err(io::Error::from)
+            .and_then(|bytes| files::write_atomic(&self.path, bytes))
+            .wrap_err_with(|| format!("writing {} failed", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    #[test]
+    fn persist_habits() {
+        let dir = env::temp_dir().join(format!("habits-demo-store-{}", process::id()));
+        let store = Store::new(dir.join("habits.json"));
+        assert!(store.load().unwrap().is_empty());
+
+        let day = |month, day| Date::from_calendar_date(2026, month, day).unwrap();
+        let habits = vec![
+            Habit::with_days("Read", [day(Month::October, 3), day(Month::October, 14)]),
+            Habit::new("Stretch"),
+        ];
+        store.save(&habits).unwrap();
+        assert!(fs::read_to_string(dir.join("habits.json"))
+            .unwrap()
+            .contains("\"2026-10-03\""));
+        assert_eq!(store.load().unwrap(), habits);
+
+        fs::write(
+            dir.join("habits.json"),
+            r#"{"habits":[{"name":"Read","done":["2026-02-30"]}]}"#,
+        )
+        .unwrap();
+        let error = store.load().unwrap_err();
+        assert_eq!(
+            error.root_cause().to_string(),
+            "\"2026-02-30\" is not a date like 2026-10-14"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,328 @@
+//! 十六进制查看器演示：把文件映射到内存，只绘制屏幕上可见的行，所以几 GB 的文件也能立即打开。
+//!
+//! 按键：
+//!
+//! ```text
+//! 方向键 / PgUp / PgDn    移动光标          Home / End   文件开头 / 结尾
+//! g                       跳转到偏移        /            搜索字节
+//! n                       下一个匹配        v            开始 / 取消选择
+//! q                       退出
+//! ```
+//!
+//! 跳转时输入十进制或 `0x` 开头的十六进制偏移。搜索时输入十六进制字节（例如 `de ad be ef`），
+//! 或者用双引号括起来的文本（例如 `"PNG"`）。
+
+use std::{fs::File, path::PathBuf};
+
+use clap::Parser;
+use color_eyre::{eyre::WrapErr, Result};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use memmap2::Mmap;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Paragraph},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+    text_input::Input,
+};
+
+use crate::view::HexView;
+
+mod view;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// 要查看的文件。
+    file: PathBuf,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let file =
+        File::open(&cli.file).wrap_err_with(|| format!("opening {} failed", cli.file.display()))?;
+    // 安全性：文件在查看期间被其他程序截断时，访问映射的内存会出错。演示程序接受这个风险。
+    let data = unsafe { Mmap::map(&file) }
+        .wrap_err_with(|| format!("mapping {} failed", cli.file.display()))?;
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let name = cli.file.display().to_string();
+    let result = App::new(&data[..], name).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+/// 底部的输入行。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Prompt {
+    Goto,
+    Search,
+}
+
+struct App<'a> {
+    data: &'a [u8],
+    name: String,
+    view: HexView,
+    prompt: Option<(Prompt, Input)>,
+    /// 上一次搜索的字节，`n` 继续搜索。
+    search: Option<Vec<u8>>,
+    /// 显示在状态栏中的提示或错误，下一次按键时清除。
+    message: Option<String>,
+    exit: bool,
+}
+
+impl<'a> App<'a> {
+    fn new(data: &'a [u8], name: String) -> Self {
+        Self {
+            data,
+            name,
+            view: HexView::default(),
+            prompt: None,
+            search: None,
+            message: None,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut()))?;
+            if let Event::Key(key) = events.read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        self.message = None;
+        if key.code == KeyCode::Char('c') && key.modifiers == KeyModifiers::CONTROL {
+            self.exit = true;
+            return;
+        }
+        if let Some((prompt, input)) = &mut self.prompt {
+            match key.code {
+                KeyCode::Esc => self.prompt = None,
+                KeyCode::Enter => {
+                    let (prompt, text) = (*prompt, input.value().to_string());
+                    self.prompt = None;
+                    self.submit(prompt, &text);
+                }
+                _ => {
+                    input.handle_key_event(key);
+                }
+            }
+            return;
+        }
+        let len = self.data.len();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
+            KeyCode::Char('g') => self.prompt = Some((Prompt::Goto, Input::default())),
+            KeyCode::Char('/') => self.prompt = Some((Prompt::Search, Input::default())),
+            KeyCode::Char('n') => match self.search.clone() {
+                Some(needle) => self.find(&needle),
+                None => self.message = Some("no previous search".into()),
+            },
+            KeyCode::Char('v') => self.view.toggle_selection(),
+            KeyCode::Left => self.view.move_by(-1, len),
+            KeyCode::Right => self.view.move_by(1, len),
+            KeyCode::Up => self.view.move_by(-(view::BYTES_PER_ROW as i64), len),
+            KeyCode::Down => self.view.move_by(view::BYTES_PER_ROW as i64, len),
+            KeyCode::PageUp => self.view.move_by(-self.view.page_bytes(), len),
+            KeyCode::PageDown => self.view.move_by(self.view.page_bytes(), len),
+            KeyCode::Home => self.view.move_to(0, len),
+            KeyCode::End => self.view.move_to(len.saturating_sub(1), len),
+            _ => {}
+        }
+    }
+
+    fn submit(&mut self, prompt: Prompt, text: &str) {
+        match prompt {
+            Prompt::Goto => match parse_offset(text) {
+                Some(offset) if offset < self.data.len() => {
+                    self.view.move_to(offset, self.data.len())
+                }
+                Some(offset) => self.message = Some(format!("offset {offset:#x} is past the end")),
+                None => self.message = Some(format!("{text:?} is not an offset")),
+            },
+            Prompt::Search => match parse_needle(text) {
+                Some(needle) => {
+                    self.find(&needle);
+                    self.search = Some(needle);
+                }
+                None => {
+                    self.message = Some(format!("{text:?} is not hex bytes or a quoted string"))
+                }
+            },
+        }
+    }
+
+    /// 从光标之后开始搜索，到结尾后从头继续。找到时选中匹配的字节。
+    fn find(&mut self, needle: &[u8]) {
+        let start = (self.view.cursor() + 1).min(self.data.len());
+        let found = memchr::memmem::find(&self.data[start..], needle)
+            .map(|at| start + at)
+            .or_else(|| memchr::memmem::find(self.data, needle));
+        match found {
+            Some(at) => {
+                self.view.select(at, at + needle.len() - 1, self.data.len());
+                if at < start {
+                    self.message = Some("search wrapped to the beginning".into());
+                }
+            }
+            None => self.message = Some("not found".into()),
+        }
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let block = Block::bordered().title(format!(" {} ", self.name));
+        let inner = block.inner(main);
+        block.render(main, buf);
+        self.view.render(self.data, inner, buf);
+
+        let line = match &self.prompt {
+            Some((prompt, input)) => {
+                let label = match prompt {
+                    Prompt::Goto => "goto: ",
+                    Prompt::Search => "search: ",
+                };
+                input.line(label, Style::new().bold())
+            }
+            None => self.status_line(),
+        };
+        Paragraph::new(line).render(status, buf);
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        if let Some(message) = &self.message {
+            return Line::from(message.clone().yellow());
+        }
+        let cursor = self.view.cursor();
+        let mut spans = vec![format!("{cursor:#010x}").bold()];
+        if let Some(&byte) = self.data.get(cursor) {
+            spans.push(format!("  {byte:#04x} {byte}").into());
+        }
+        if let Some((start, end)) = self.view.selection() {
+            spans.push(
+                format!(
+                    "  selected {start:#x}..={end:#x} ({} bytes)",
+                    end - start + 1
+                )
+                .cyan(),
+            );
+        }
+        spans.push(format!("  {} bytes", self.data.len()).dim());
+        Line::from(spans)
+    }
+}
+
+/// 十进制，或者 `0x` 开头的十六进制。
+fn parse_offset(text: &str) -> Option<usize> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+/// 用双引号括起来的文本，或者十六进制字节（可以用空格分隔）。
+fn parse_needle(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim();
+    if let Some(quoted) = text
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+    {
+        return (!quoted.is_empty()).then(|| quoted.as_bytes().to_vec());
+    }
+    let digits: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if digits.is_empty() || !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|at| u8::from_str_radix(digits.get(at..at + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_line(app: &mut App, text: &str) {
+        for c in text.chars() {
+            app.handle_key(KeyCode::Char(c).into());
+        }
+        app.handle_key(KeyCode::Enter.into());
+    }
+
+    #[test]
+    fn parse_prompts() {
+        assert_eq!(parse_offset("0x1F"), Some(31));
+        assert_eq!(parse_offset(" 42 "), Some(42));
+        assert_eq!(parse_offset("zz"), None);
+        assert_eq!(parse_needle("de ad BE"), Some(vec![0xde, 0xad, 0xbe]));
+        assert_eq!(parse_needle("\"PNG\""), Some(b"PNG".to_vec()));
+        assert_eq!(parse_needle("abc"), None);
+        assert_eq!(parse_needle("\"\""), None);
+    }
+
+    #[test]
+    fn goto_and_search() {
+        let data: Vec<u8> = (0..=255).chain(*b"needle").chain(0..=255).collect();
+        let mut app = App::new(&data, "test".into());
+        app.handle_key(KeyCode::Char('g').into());
+        type_line(&mut app, "0x20");
+        assert_eq!(app.view.cursor(), 0x20);
+
+        app.handle_key(KeyCode::Char('/').into());
+        type_line(&mut app, "\"needle\"");
+        assert_eq!(app.view.selection(), Some((256, 261)));
+        // 再次搜索字节 0x10：先找到第二段中的，再从头找到第一段中的。
+        app.handle_key(KeyCode::Char('/').into());
+        type_line(&mut app, "10");
+        assert_eq!(app.view.cursor(), 262 + 16);
+        app.handle_key(KeyCode::Char('n').into());
+        assert_eq!(app.view.cursor(), 16);
+        assert_eq!(
+            app.message.as_deref(),
+            Some("search wrapped to the beginning")
+        );
+
+        app.handle_key(KeyCode::Char('g').into());
+        type_line(&mut app, "100000");
+        assert_eq!(
+            app.message.as_deref(),
+            Some("offset 0x186a0 is past the end")
+        );
+    }
+
+    #[test]
+    fn status_line() {
+        let data = b"hello world";
+        let mut app = App::new(data, "test".into());
+        app.handle_key(KeyCode::Char('v').into());
+        app.handle_key(KeyCode::Right.into());
+        app.handle_key(KeyCode::Right.into());
+        let text: String = app
+            .status_line()
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+        assert_eq!(
+            text,
+            "0x00000002  0x6c 108  selected 0x0..=0x2 (3 bytes)  11 bytes"
+        );
+    }
+}
@@ -0,0 +1,172 @@
+//! 十六进制和 ASCII 对照的视图，只绘制可见的行。
+
+use ratatui::prelude::*;
+
+pub const BYTES_PER_ROW: usize = 16;
+
+/// 光标、选择和滚动位置。数据本身由调用者保存，视图只记录位置。
+#[derive(Debug, Default)]
+pub struct HexView {
+    cursor: usize,
+    /// 选择的另一端，选择的范围包括两端。
+    anchor: Option<usize>,
+    /// 第一行可见的行号。
+    top: usize,
+    /// 上一次绘制时可见的行数，用于翻页。
+    rows: usize,
+}
+
+impl HexView {
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        self.anchor
+            .map(|anchor| (anchor.min(self.cursor), anchor.max(self.cursor)))
+    }
+
+    pub fn toggle_selection(&mut self) {
+        self.anchor = match self.anchor {
+            Some(_) => None,
+            None => Some(self.cursor),
+        };
+    }
+
+    /// 选中 `start..=end`，光标停在 `end`。
+    pub fn select(&mut self, start: usize, end: usize, len: usize) {
+        self.move_to(end, len);
+        self.anchor = Some(start);
+    }
+
+    pub fn move_to(&mut self, offset: usize, len: usize) {
+        self.cursor = offset.min(len.saturating_sub(1));
+    }
+
+    pub fn move_by(&mut self, delta: i64, len: usize) {
+        let offset = (self.cursor as i64).saturating_add(delta).max(0);
+        self.move_to(offset as usize, len);
+    }
+
+    /// 一页的字节数。
+    pub fn page_bytes(&self) -> i64 {
+        (self.rows.max(1) * BYTES_PER_ROW) as i64
+    }
+
+    /// 绘制 `data` 中可见的部分，必要时滚动，让光标所在的行可见。
+    pub fn render(&mut self, data: &[u8], area: Rect, buf: &mut Buffer) {
+        self.rows = area.height.into();
+        let row = self.cursor / BYTES_PER_ROW;
+        if row < self.top {
+            self.top = row;
+        } else if row >= self.top + self.rows {
+            self.top = row + 1 - self.rows;
+        }
+        let total_rows = data.len().div_ceil(BYTES_PER_ROW);
+        for (y, row) in (self.top..total_rows.min(self.top + self.rows)).enumerate() {
+            let start = row * BYTES_PER_ROW;
+            let end = (start + BYTES_PER_ROW).min(data.len());
+            let line = self.row_line(start, &data[start..end]);
+            line.render(Rect::new(area.x, area.y + y as u16, area.width, 1), buf);
+        }
+    }
+
+    fn style(&self, offset: usize, byte: u8) -> Style {
+        let mut style = if byte == 0 {
+            Style::new().dark_gray()
+        } else {
+            Style::new()
+        };
+        if self
+            .selection()
+            .is_some_and(|(start, end)| (start..=end).contains(&offset))
+        {
+            style = style.black().on_cyan();
+        }
+        if offset == self.cursor {
+            style = style.reversed();
+        }
+        style
+    }
+
+    /// 一行：偏移、十六进制字节（每 8 个字节之间多一个空格）和 ASCII。
+    fn row_line(&self, start: usize, bytes: &[u8]) -> Line<'static> {
+        let mut spans = vec![format!("{start:08x}  ").dim()];
+        for column in 0..BYTES_PER_ROW {
+            if column == BYTES_PER_ROW / 2 {
+                spans.push(" ".into());
+            }
+            match bytes.get(column) {
+                Some(&byte) => {
+                    spans.push(Span::styled(
+                        format!("{byte:02x}"),
+                        self.style(start + column, byte),
+                    ));
+                    spans.push(" ".into());
+                }
+                None => spans.push("   ".into()),
+            }
+        }
+        spans.push(" |".dim());
+        for (column, &byte) in bytes.iter().enumerate() {
+            let c = if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            };
+            spans.push(Span::styled(
+                c.to_string(),
+                self.style(start + column, byte),
+            ));
+        }
+        spans.push("|".dim());
+        Line::from(spans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn rows(view: &mut HexView, data: &[u8], height: u16) -> Vec<String> {
+        testing::render_rows(78, height, |area, buf| view.render(data, area, buf))
+            .into_iter()
+            .map(|row| row.trim_end().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn render_rows() {
+        let data: Vec<u8> = b"Hello, hex view!\x00\x01\x7f".to_vec();
+        let mut view = HexView::default();
+        assert_eq!(
+            rows(&mut view, &data, 3),
+            [
+                "00000000  48 65 6c 6c 6f 2c 20 68  65 78 20 76 69 65 77 21  |Hello, hex view!|",
+                "00000010  00 01 7f                                          |...|",
+                "",
+            ]
+        );
+    }
+
+    #[test]
+    fn scroll_to_cursor() {
+        let data = vec![0u8; 1 << 20];
+        let mut view = HexView::default();
+        view.move_to(0x1234, data.len());
+        let rows = rows(&mut view, &data, 2);
+        // 光标所在的行是最后一个可见行。
+        assert!(rows[0].starts_with("00001220"));
+        assert!(rows[1].starts_with("00001230"));
+        view.move_by(-view.page_bytes() * 1000, data.len());
+        assert_eq!(view.cursor(), 0);
+
+        view.toggle_selection();
+        view.move_by(20, data.len());
+        assert_eq!(view.selection(), Some((0, 20)));
+        view.toggle_selection();
+        assert_eq!(view.selection(), None);
+    }
+}
@@ -0,0 +1,229 @@
+//! 在后台线程中通过 IMAP 读取邮件。只使用 `EXAMINE` 和 `BODY.PEEK`，不会修改邮箱，
+//! 也不会把邮件标记为已读。
+//!
+//! 连接、读写都有超时，网络很慢或服务器没有响应时后台线程报告断开，而不是一直等待。
+
+use std::{
+    io,
+    net::{TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+use imap::types::{Flag, NameAttribute};
+use native_tls::{TlsConnector, TlsStream};
+
+use crate::mail::{self, Summary};
+
+/// 连接和每次读写的超时。
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+/// 每个文件夹列出的最新邮件数。
+const MESSAGES: u32 = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub password: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Folder(String),
+    Message { folder: String, uid: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Update {
+    /// 已经建立连接，正在登录。
+    Authenticating,
+    Folders(Vec<String>),
+    Messages {
+        folder: String,
+        messages: Vec<Summary>,
+    },
+    Body {
+        folder: String,
+        uid: u32,
+        text: Result<String, String>,
+    },
+    /// 服务器拒绝了用户名或密码，后台线程已经结束。
+    AuthFailed(String),
+    /// 服务器拒绝了一个命令，连接仍然可用。
+    Error(String),
+    /// 连接失败或断开，后台线程已经结束。
+    Disconnected(String),
+}
+
+/// 启动后台线程，返回发送命令的一端。
+pub fn spawn(account: Account, updates: Sender<Update>) -> Sender<Command> {
+    let (commands, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        if let Err(error) = serve(&account, &receiver, &updates) {
+            let _ = updates.send(Update::Disconnected(error.to_string()));
+        }
+    });
+    commands
+}
+
+type Client = imap::Client<TlsStream<TcpStream>>;
+type Session = imap::Session<TlsStream<TcpStream>>;
+
+fn connect(account: &Account) -> imap::error::Result<Client> {
+    let address = (account.host.as_str(), account.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "host has no address"))?;
+    let tcp = TcpStream::connect_timeout(&address, TIMEOUT)?;
+    tcp.set_read_timeout(Some(TIMEOUT))?;
+    tcp.set_write_timeout(Some(TIMEOUT))?;
+    let tls = TlsConnector::new()?.connect(&account.host, tcp)?;
+    let mut client = imap::Client::new(tls);
+    client.read_greeting()?;
+    Ok(client)
+}
+
+fn serve(
+    account: &Account,
+    commands: &Receiver<Command>,
+    updates: &Sender<Update>,
+) -> imap::error::Result<()> {
+    let client = connect(account)?;
+    let _ = updates.send(Update::Authenticating);
+    let mut session = match client.login(&account.user, &account.password) {
+        Ok(session) => session,
+        Err((imap::Error::No(message), _)) => {
+            let _ = updates.send(Update::AuthFailed(message));
+            return Ok(());
+        }
+        Err((error, _)) => return Err(error),
+    };
+    let _ = updates.send(Update::Folders(folders(&mut session)?));
+
+    let mut examined = None;
+    while let Ok(command) = commands.recv() {
+        let mut pending = vec![command];
+        pending.extend(commands.try_iter());
+        for command in latest(pending) {
+            let update = match command {
+                Command::Folder(folder) => examine(&mut session, &mut examined, &folder, true)
+                    .and_then(|exists| messages(&mut session, exists))
+                    .map(|messages| Update::Messages { folder, messages }),
+                Command::Message { folder, uid } => {
+                    examine(&mut session, &mut examined, &folder, false)
+                        .and_then(|_| body(&mut session, uid))
+                        .map(|text| Update::Body { folder, uid, text })
+                }
+            };
+            let update = match update {
+                Ok(update) => update,
+                // 服务器拒绝的命令不影响之后的命令，其他错误说明连接已经不可用。
+                Err(imap::Error::No(message) | imap::Error::Bad(message)) => Update::Error(message),
+                Err(error) => return Err(error),
+            };
+            if updates.send(update).is_err() {
+                break;
+            }
+        }
+    }
+    let _ = session.logout();
+    Ok(())
+}
+
+/// 网络慢时命令会积压。每种命令只需要最后一个：用户已经离开的文件夹和邮件不用再下载。
+fn latest(commands: Vec<Command>) -> Vec<Command> {
+    let last_folder = commands
+        .iter()
+        .rposition(|command| matches!(command, Command::Folder(_)));
+    let last_message = commands
+        .iter()
+        .rposition(|command| matches!(command, Command::Message { .. }));
+    commands
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| Some(*i) == last_folder || Some(*i) == last_message)
+        .map(|(_, command)| command)
+        .collect()
+}
+
+/// 可以打开的文件夹，`INBOX` 在最前面。
+fn folders(session: &mut Session) -> imap::error::Result<Vec<String>> {
+    let names = session.list(Some(""), Some("*"))?;
+    let mut folders: Vec<String> = names
+        .iter()
+        .filter(|name| !name.attributes().contains(&NameAttribute::NoSelect))
+        .map(|name| name.name().to_string())
+        .collect();
+    folders.sort_by_key(|folder| (!folder.eq_ignore_ascii_case("INBOX"), folder.clone()));
+    Ok(folders)
+}
+
+/// 以只读方式打开文件夹，返回其中的邮件数。列出邮件时总是重新打开，得到最新的邮件数。
+fn examine(
+    session: &mut Session,
+    examined: &mut Option<(String, u32)>,
+    folder: &str,
+    reopen: bool,
+) -> imap::error::Result<u32> {
+    match examined {
+        Some((name, exists)) if name == folder && !reopen => Ok(*exists),
+        _ => {
+            let mailbox = session.examine(folder)?;
+            *examined = Some((folder.to_string(), mailbox.exists));
+            Ok(mailbox.exists)
+        }
+    }
+}
+
+/// 当前文件夹中最新的邮件，最新的在前。
+fn messages(session: &mut Session, exists: u32) -> imap::error::Result<Vec<Summary>> {
+    if exists == 0 {
+        return Ok(Vec::new());
+    }
+    let first = exists.saturating_sub(MESSAGES - 1).max(1);
+    let fetches = session.fetch(format!("{first}:{exists}"), "(UID FLAGS BODY.PEEK[HEADER])")?;
+    let mut messages: Vec<Summary> = fetches
+        .iter()
+        .filter_map(|fetch| {
+            let seen = fetch.flags().contains(&Flag::Seen);
+            Some(mail::summary(fetch.uid?, fetch.header()?, seen))
+        })
+        .collect();
+    messages.sort_by_key(|message| std::cmp::Reverse(message.uid));
+    Ok(messages)
+}
+
+fn body(session: &mut Session, uid: u32) -> imap::error::Result<Result<String, String>> {
+    let fetches = session.uid_fetch(uid.to_string(), "BODY.PEEK[]")?;
+    Ok(match fetches.iter().find_map(|fetch| fetch.body()) {
+        Some(raw) => mail::plain_text(raw),
+        None => Err("message no longer exists".into()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keep_latest_commands() {
+        let message = |uid| Command::Message {
+            folder: "INBOX".into(),
+            uid,
+        };
+        let commands = vec![
+            message(1),
+            Command::Folder("INBOX".into()),
+            message(2),
+            Command::Folder("Sent".into()),
+            message(3),
+        ];
+        assert_eq!(
+            latest(commands),
+            [Command::Folder("Sent".into()), message(3)]
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! 解析邮件：从头部取出列表中显示的字段，从完整的邮件中找出纯文本正文。
+
+use mailparse::{DispositionType, MailAddr, MailHeaderMap, ParsedMail};
+
+/// 邮件列表中的一行。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Summary {
+    pub uid: u32,
+    /// 发件人的显示名称，没有时是地址。
+    pub from: String,
+    pub subject: String,
+    /// Unix 时间戳（秒），`Date` 头部缺失或无法解析时没有。
+    pub date: Option<i64>,
+    pub seen: bool,
+}
+
+/// 从 `BODY.PEEK[HEADER]` 的内容解析摘要。编码过的头部（`=?UTF-8?B?...?=`）会被解码。
+pub fn summary(uid: u32, header: &[u8], seen: bool) -> Summary {
+    let headers = mailparse::parse_headers(header)
+        .map(|(headers, _)| headers)
+        .unwrap_or_default();
+    let from = headers
+        .get_first_header("From")
+        .and_then(|header| {
+            let addrs = mailparse::addrparse_header(header).ok()?;
+            match addrs.first()? {
+                MailAddr::Single(info) => {
+                    Some(info.display_name.clone().unwrap_or(info.addr.clone()))
+                }
+                MailAddr::Group(group) => Some(group.group_name.clone()),
+            }
+        })
+        .or_else(|| headers.get_first_value("From"))
+        .unwrap_or_default();
+    Summary {
+        uid,
+        from,
+        subject: headers
+            .get_first_value("Subject")
+            .unwrap_or_else(|| "(no subject)".into()),
+        date: headers
+            .get_first_value("Date")
+            .and_then(|date| mailparse::dateparse(&date).ok()),
+        seen,
+    }
+}
+
+/// 完整邮件的纯文本正文。多部分邮件取第一个不是附件的 `text/plain` 部分。
+pub fn plain_text(raw: &[u8]) -> Result<String, String> {
+    let mail = mailparse::parse_mail(raw).map_err(|error| error.to_string())?;
+    match find_plain(&mail) {
+        Some(part) => part
+            .get_body()
+            .map(|body| body.replace("\r\n", "\n"))
+            .map_err(|error| error.to_string()),
+        None => Err(format!("no plain-text part ({})", mail.ctype.mimetype)),
+    }
+}
+
+fn find_plain<'a>(mail: &'a ParsedMail<'a>) -> Option<&'a ParsedMail<'a>> {
+    if mail.subparts.is_empty() {
+        let attachment = mail.get_content_disposition().disposition == DispositionType::Attachment;
+        return (mail.ctype.mimetype == "text/plain" && !attachment).then_some(mail);
+    }
+    mail.subparts.iter().find_map(find_plain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_summary() {
+        let header = b"From: =?UTF-8?B?5byg5LiJ?= <zhang@example.com>\r\n\
+            Subject: Weekly report\r\n\
+            Date: Tue, 02 Jan 2024 08:00:00 +0000\r\n\r\n";
+        let summary = super::summary(7, header, false);
+        assert_eq!(summary.from, "张三");
+        assert_eq!(summary.subject, "Weekly report");
+        assert_eq!(summary.date, Some(1704182400));
+
+        let summary = super::summary(8, b"From: bob@example.com\r\n\r\n", true);
+        assert_eq!(summary.from, "bob@example.com");
+        assert_eq!(summary.subject, "(no subject)");
+        assert_eq!(summary.date, None);
+    }
+
+    #[test]
+    fn find_plain_text() {
+        let raw = b"Content-Type: multipart/mixed; boundary=\"b\"\r\n\r\n\
+            --b\r\n\
+            Content-Type: text/html\r\n\r\n\
+            <p>Hello</p>\r\n\
+            --b\r\n\
+            Content-Type: text/plain; charset=utf-8\r\n\
+            Content-Transfer-Encoding: quoted-printable\r\n\r\n\
+            Hello,=20world\r\n\
+            --b--\r\n";
+        assert_eq!(plain_text(raw).unwrap().trim_end(), "Hello, world");
+
+        let html = b"Content-Type: text/html\r\n\r\n<p>Hi</p>\r\n";
+        assert_eq!(
+            plain_text(html),
+            Err("no plain-text part (text/html)".into())
+        );
+    }
+}
@@ -0,0 +1,569 @@
+//! IMAP 邮件客户端演示（只读）：连接到 IMAP 服务器，左侧列出文件夹，中间是所选文件夹中
+//! 最新的邮件，右侧显示所选邮件的纯文本正文。
+//!
+//! 网络操作都在后台线程中进行，见 `client` 模块，所以连接很慢时界面仍然可以操作，
+//! 并显示每个部分的加载状态。邮箱以只读方式打开，浏览不会把邮件标记为已读。
+//!
+//! 只支持 TLS 连接（默认端口 993）。密码从环境变量 `IMAP_PASSWORD` 读取，不放在命令行上，
+//! 以免出现在进程列表和 shell 历史中。
+//!
+//! 按键：`Tab` 切换焦点，`Up` / `Down` 选择文件夹和邮件或者滚动正文，`q` 退出。
+
+use std::{
+    collections::HashMap,
+    env,
+    sync::mpsc::{self, Receiver, Sender},
+    time::{Duration, Instant},
+};
+
+use clap::Parser;
+use color_eyre::{eyre::WrapErr, Result};
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, List, ListState, Paragraph, Wrap},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+};
+
+use crate::{
+    client::{Account, Command, Update},
+    mail::Summary,
+};
+
+mod client;
+mod mail;
+
+/// 等待按键的最长时间，之后处理后台送回的结果。
+const REFRESH: Duration = Duration::from_millis(100);
+
+/// 等待超过这么久之后在状态栏显示已经等待的时间。
+const SLOW: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// IMAP 服务器的主机名
+    #[arg(long)]
+    host: String,
+    #[arg(long, default_value_t = 993)]
+    port: u16,
+    /// 登录用户名，通常是邮件地址
+    #[arg(long)]
+    user: String,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let password = env::var("IMAP_PASSWORD").wrap_err("IMAP_PASSWORD is not set")?;
+    let account = Account {
+        host: cli.host,
+        port: cli.port,
+        user: cli.user,
+        password,
+    };
+    let (sender, updates) = mpsc::channel();
+    let host = account.host.clone();
+    let commands = client::spawn(account, sender);
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new(host, commands, updates).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Connection {
+    Connecting,
+    Authenticating,
+    Connected,
+    AuthFailed(String),
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Folders,
+    Messages,
+    Body,
+}
+
+struct App {
+    host: String,
+    commands: Sender<Command>,
+    updates: Receiver<Update>,
+    connection: Connection,
+    /// 连接状态最近一次变化或者最近一次发出请求的时间，用来显示等待了多久。
+    since: Instant,
+    focus: Focus,
+    folders: Vec<String>,
+    folder_list: ListState,
+    /// 每个文件夹的邮件，`None` 表示正在加载。
+    messages: HashMap<String, Option<Vec<Summary>>>,
+    message_list: ListState,
+    /// 按文件夹和 UID 缓存的正文，`None` 表示正在加载。
+    bodies: HashMap<(String, u32), Option<Result<String, String>>>,
+    scroll: u16,
+    /// 服务器最近一次拒绝的命令。
+    error: Option<String>,
+    exit: bool,
+}
+
+impl App {
+    fn new(host: String, commands: Sender<Command>, updates: Receiver<Update>) -> Self {
+        Self {
+            host,
+            commands,
+            updates,
+            connection: Connection::Connecting,
+            since: Instant::now(),
+            focus: Focus::Folders,
+            folders: Vec::new(),
+            folder_list: ListState::default(),
+            messages: HashMap::new(),
+            message_list: ListState::default(),
+            bodies: HashMap::new(),
+            scroll: 0,
+            error: None,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            while let Ok(update) = self.updates.try_recv() {
+                self.update(update);
+            }
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut()))?;
+            if events.poll(REFRESH)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key.code);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, update: Update) {
+        match update {
+            Update::Authenticating => self.set_connection(Connection::Authenticating),
+            Update::Folders(folders) => {
+                self.set_connection(Connection::Connected);
+                self.folders = folders;
+                self.folder_list
+                    .select((!self.folders.is_empty()).then_some(0));
+                self.load_folder();
+            }
+            Update::Messages { folder, messages } => {
+                let current = self.selected_folder() == Some(&folder);
+                let empty = messages.is_empty();
+                self.messages.insert(folder, Some(messages));
+                if current {
+                    self.message_list.select((!empty).then_some(0));
+                    self.load_message();
+                }
+            }
+            Update::Body { folder, uid, text } => {
+                self.bodies.insert((folder, uid), Some(text));
+            }
+            Update::AuthFailed(message) => self.set_connection(Connection::AuthFailed(message)),
+            Update::Error(message) => self.error = Some(message),
+            Update::Disconnected(message) => self.set_connection(Connection::Failed(message)),
+        }
+    }
+
+    fn set_connection(&mut self, connection: Connection) {
+        self.connection = connection;
+        self.since = Instant::now();
+    }
+
+    fn send(&mut self, command: Command) {
+        self.since = Instant::now();
+        let _ = self.commands.send(command);
+    }
+
+    fn selected_folder(&self) -> Option<&String> {
+        self.folders.get(self.folder_list.selected()?)
+    }
+
+    /// 所选文件夹的邮件，还在加载时是 `None`。
+    fn folder_messages(&self) -> Option<&Vec<Summary>> {
+        self.messages.get(self.selected_folder()?)?.as_ref()
+    }
+
+    fn selected_message(&self) -> Option<&Summary> {
+        self.folder_messages()?.get(self.message_list.selected()?)
+    }
+
+    /// 所选文件夹的邮件还没有加载过时，发出请求。
+    fn load_folder(&mut self) {
+        let Some(folder) = self.selected_folder().cloned() else {
+            return;
+        };
+        self.error = None;
+        match self.messages.get(&folder) {
+            Some(Some(messages)) => {
+                let empty = messages.is_empty();
+                self.message_list.select((!empty).then_some(0));
+                self.load_message();
+            }
+            Some(None) => self.message_list.select(None),
+            None => {
+                self.message_list.select(None);
+                // 后台只处理积压的最后一个请求，之前还在等待的文件夹下次选中时重新请求。
+                self.messages.retain(|_, messages| messages.is_some());
+                self.messages.insert(folder.clone(), None);
+                self.send(Command::Folder(folder));
+            }
+        }
+    }
+
+    /// 所选邮件的正文还没有加载过时，发出请求。
+    fn load_message(&mut self) {
+        self.scroll = 0;
+        let (Some(folder), Some(message)) = (self.selected_folder(), self.selected_message())
+        else {
+            return;
+        };
+        let key = (folder.clone(), message.uid);
+        if !self.bodies.contains_key(&key) {
+            self.bodies.retain(|_, body| body.is_some());
+            self.bodies.insert(key.clone(), None);
+            self.send(Command::Message {
+                folder: key.0,
+                uid: key.1,
+            });
+        }
+    }
+
+    fn handle_key(&mut self, code: KeyCode) {
+        match (self.focus, code) {
+            (_, KeyCode::Char('q') | KeyCode::Esc) => self.exit = true,
+            (Focus::Folders, KeyCode::Tab) | (Focus::Body, KeyCode::BackTab) => {
+                self.focus = Focus::Messages
+            }
+            (Focus::Messages, KeyCode::Tab) | (Focus::Folders, KeyCode::BackTab) => {
+                self.focus = Focus::Body
+            }
+            (_, KeyCode::Tab | KeyCode::BackTab) => self.focus = Focus::Folders,
+            (Focus::Folders, KeyCode::Up | KeyCode::Down) => {
+                let before = self.folder_list.selected();
+                select(&mut self.folder_list, self.folders.len(), code);
+                if self.folder_list.selected() != before {
+                    self.load_folder();
+                }
+            }
+            (Focus::Messages, KeyCode::Up | KeyCode::Down) => {
+                let len = self.folder_messages().map_or(0, Vec::len);
+                select(&mut self.message_list, len, code);
+                self.load_message();
+            }
+            (Focus::Body, KeyCode::Up) => self.scroll = self.scroll.saturating_sub(1),
+            (Focus::Body, KeyCode::Down) => self.scroll = self.scroll.saturating_add(1),
+            (Focus::Body, KeyCode::PageUp) => self.scroll = self.scroll.saturating_sub(10),
+            (Focus::Body, KeyCode::PageDown) => self.scroll = self.scroll.saturating_add(10),
+            _ => {}
+        }
+    }
+
+    fn border(&self, focus: Focus) -> Style {
+        if self.focus == focus {
+            Style::new().green()
+        } else {
+            Style::new()
+        }
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let [folders_area, messages_area, body_area] = Layout::horizontal([
+            Constraint::Length(20),
+            Constraint::Fill(2),
+            Constraint::Fill(3),
+        ])
+        .areas(main);
+
+        let loading = |text: &'static str| vec![Line::from(text.dim())];
+
+        let block = Block::bordered()
+            .title(" Folders ".bold())
+            .border_style(self.border(Focus::Folders));
+        if self.connection == Connection::Connected {
+            let items: Vec<Line> = self
+                .folders
+                .iter()
+                .map(|folder| Line::from(folder.clone()))
+                .collect();
+            let list = List::new(items)
+                .highlight_style(Style::new().reversed())
+                .block(block);
+            StatefulWidget::render(list, folders_area, buf, &mut self.folder_list);
+        } else {
+            Paragraph::new(loading("Loading…"))
+                .block(block)
+                .render(folders_area, buf);
+        }
+
+        let block = Block::bordered()
+            .title(" Messages ")
+            .border_style(self.border(Focus::Messages));
+        match self.folder_messages() {
+            Some(messages) => {
+                let items: Vec<Line> = messages.iter().map(message_line).collect();
+                let list = List::new(items)
+                    .highlight_style(Style::new().reversed())
+                    .block(block);
+                StatefulWidget::render(list, messages_area, buf, &mut self.message_list);
+            }
+            None if self.selected_folder().is_some() => Paragraph::new(loading("Loading…"))
+                .block(block)
+                .render(messages_area, buf),
+            None => block.render(messages_area, buf),
+        }
+
+        let block = Block::bordered()
+            .title(
+                self.selected_message()
+                    .map_or(" Message ".into(), |m| format!(" {} ", m.subject)),
+            )
+            .border_style(self.border(Focus::Body));
+        let body = self
+            .selected_folder()
+            .zip(self.selected_message())
+            .and_then(|(folder, message)| self.bodies.get(&(folder.clone(), message.uid)));
+        let lines = match body {
+            Some(Some(Ok(text))) => text
+                .lines()
+                .map(|line| Line::from(line.to_string()))
+                .collect(),
+            Some(Some(Err(error))) => vec![Line::from(error.clone().red())],
+            Some(None) => loading("Loading message…"),
+            None => Vec::new(),
+        };
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0))
+            .block(block)
+            .render(body_area, buf);
+
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+
+    /// 等待超过 [`SLOW`] 时加上已经等待的秒数。
+    fn waiting(&self, text: String) -> String {
+        let elapsed = self.since.elapsed();
+        if elapsed < SLOW {
+            text
+        } else {
+            format!("{text} ({}s)", elapsed.as_secs())
+        }
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        match &self.connection {
+            Connection::Connecting => Line::from(
+                self.waiting(format!("Connecting to {}…", self.host))
+                    .yellow(),
+            ),
+            Connection::Authenticating => Line::from(self.waiting("Logging in…".into()).yellow()),
+            Connection::AuthFailed(message) => {
+                Line::from(format!("Authentication failed: {message}").red())
+            }
+            Connection::Failed(message) => {
+                Line::from(format!("Disconnected from {}: {message}", self.host).red())
+            }
+            Connection::Connected => {
+                if let Some(error) = &self.error {
+                    return Line::from(error.clone().red());
+                }
+                let loading = self.messages.values().any(Option::is_none)
+                    || self.bodies.values().any(Option::is_none);
+                if loading {
+                    Line::from(self.waiting("Loading…".into()).yellow())
+                } else {
+                    Line::from(format!("{}  Focus <Tab> Quit <Q>", self.host))
+                }
+            }
+        }
+    }
+}
+
+fn message_line(message: &Summary) -> Line<'static> {
+    let date = message
+        .date
+        .and_then(|at| chrono::DateTime::from_timestamp(at, 0))
+        .map_or_else(
+            || " ".repeat(10),
+            |at| {
+                at.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d")
+                    .to_string()
+            },
+        );
+    let text = format!("{:<16.16} {}", message.from, message.subject);
+    let text = if message.seen {
+        Span::from(format!("  {text}"))
+    } else {
+        format!("● {text}").bold()
+    };
+    Line::from(vec![date.dim(), " ".into(), text])
+}
+
+/// 在有 `len` 项的列表中上下移动选择。
+fn select(state: &mut ListState, len: usize, code: KeyCode) {
+    if len == 0 {
+        return;
+    }
+    let selected = state.selected().unwrap_or(0);
+    let next = match code {
+        KeyCode::Up => selected.saturating_sub(1),
+        _ => (selected + 1).min(len - 1),
+    };
+    state.select(Some(next));
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn summary(uid: u32, from: &str, seen: bool) -> Summary {
+        Summary {
+            uid,
+            from: from.into(),
+            subject: format!("Subject {uid}"),
+            date: None,
+            seen,
+        }
+    }
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    fn app() -> (App, Receiver<Command>) {
+        let (commands, sent) = mpsc::channel();
+        let (_updates, receiver) = mpsc::channel();
+        (
+            App::new("imap.example.com".into(), commands, receiver),
+            sent,
+        )
+    }
+
+    #[test]
+    fn loading_states() {
+        let (mut app, sent) = app();
+        assert_eq!(
+            app.status_line().to_string(),
+            "Connecting to imap.example.com…"
+        );
+        app.update(Update::Authenticating);
+        assert_eq!(app.status_line().to_string(), "Logging in…");
+        app.update(Update::Folders(vec!["INBOX".into(), "Sent".into()]));
+        assert_eq!(sent.try_recv(), Ok(Command::Folder("INBOX".into())));
+        assert_eq!(app.status_line().to_string(), "Loading…");
+
+        // 后台可能跳过了被新请求取代的请求，所以回到还在加载的文件夹时重新请求。
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Up);
+        assert_eq!(sent.try_recv(), Ok(Command::Folder("Sent".into())));
+        assert_eq!(sent.try_recv(), Ok(Command::Folder("INBOX".into())));
+        assert!(sent.try_recv().is_err());
+
+        app.update(Update::Messages {
+            folder: "INBOX".into(),
+            messages: vec![summary(2, "alice", false), summary(1, "bob", true)],
+        });
+        let message = |uid| Command::Message {
+            folder: "INBOX".into(),
+            uid,
+        };
+        assert_eq!(sent.try_recv(), Ok(message(2)));
+        app.handle_key(KeyCode::Tab);
+        app.handle_key(KeyCode::Down);
+        assert_eq!(sent.try_recv(), Ok(message(1)));
+
+        // 其他文件夹的结果到达时不改变选择。
+        app.update(Update::Messages {
+            folder: "Sent".into(),
+            messages: Vec::new(),
+        });
+        assert_eq!(app.selected_message().unwrap().uid, 1);
+        app.update(Update::Body {
+            folder: "INBOX".into(),
+            uid: 1,
+            text: Ok("Hi".into()),
+        });
+        app.update(Update::Body {
+            folder: "INBOX".into(),
+            uid: 2,
+            text: Err("no plain-text part".into()),
+        });
+        assert!(app
+            .status_line()
+            .to_string()
+            .starts_with("imap.example.com"));
+
+        app.update(Update::Error("Mailbox does not exist".into()));
+        assert_eq!(app.status_line().to_string(), "Mailbox does not exist");
+        app.update(Update::Disconnected("timed out".into()));
+        assert_eq!(
+            app.status_line().to_string(),
+            "Disconnected from imap.example.com: timed out"
+        );
+    }
+
+    #[test]
+    fn auth_failure() {
+        let (mut app, _sent) = app();
+        app.update(Update::Authenticating);
+        app.update(Update::AuthFailed("Invalid credentials".into()));
+        let rows = rows(&mut app, 80, 6);
+        assert_eq!(
+            rows[1],
+            "│Loading…          ││                      ││                                  │"
+        );
+        assert_eq!(
+            rows[5].trim_end(),
+            "Authentication failed: Invalid credentials"
+        );
+    }
+
+    #[test]
+    fn render_messages() {
+        let (mut app, _sent) = app();
+        app.update(Update::Folders(vec!["INBOX".into()]));
+        app.update(Update::Messages {
+            folder: "INBOX".into(),
+            messages: vec![
+                summary(2, "Alice Example-Longname", false),
+                summary(1, "bob", true),
+            ],
+        });
+        app.update(Update::Body {
+            folder: "INBOX".into(),
+            uid: 2,
+            text: Ok("Hello\nWorld".into()),
+        });
+        let rows = rows(&mut app, 100, 6);
+        assert_eq!(
+            rows[1],
+            "│INBOX             ││           ● Alice Example-Lo ││Hello                                         │"
+        );
+        assert_eq!(
+            rows[2],
+            "│                  ││             bob              ││World                                         │"
+        );
+        assert!(rows[0].contains("┌ Subject 2 ─"));
+    }
+}
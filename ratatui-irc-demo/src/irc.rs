@@ -0,0 +1,131 @@
+//! IRC 协议的消息格式（RFC 1459 / 2812）：解析收到的行，构造要发送的行。
+//!
+//! 不支持 IRCv3 的消息标签之外的扩展，标签本身被忽略。
+
+/// 一条 IRC 消息，例如 `:nick!user@host PRIVMSG #rust :hello`。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    /// 发送者，服务器名或者 `nick!user@host`。
+    pub prefix: Option<String>,
+    /// 命令或三位数字的回复代码，统一为大写。
+    pub command: String,
+    /// 参数，最后一个参数可以包含空格。
+    pub params: Vec<String>,
+}
+
+impl Message {
+    pub fn new(command: &str, params: &[&str]) -> Self {
+        Self {
+            prefix: None,
+            command: command.to_string(),
+            params: params.iter().map(|param| param.to_string()).collect(),
+        }
+    }
+
+    /// 解析一行（不包括结尾的 `\r\n`）。空行和只有前缀的行返回 `None`。
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut rest = line.trim_end_matches(['\r', '\n']);
+        if let Some(tagged) = rest.strip_prefix('@') {
+            rest = tagged.split_once(' ')?.1;
+        }
+        let prefix = match rest.strip_prefix(':') {
+            Some(prefixed) => {
+                let (prefix, after) = prefixed.split_once(' ')?;
+                rest = after;
+                Some(prefix.to_string())
+            }
+            None => None,
+        };
+        let (middle, trailing) = match rest.split_once(" :") {
+            Some((middle, trailing)) => (middle, Some(trailing)),
+            None => (rest, None),
+        };
+        let mut words = middle.split(' ').filter(|word| !word.is_empty());
+        let command = words.next()?.to_ascii_uppercase();
+        let mut params: Vec<String> = words.map(str::to_string).collect();
+        params.extend(trailing.map(str::to_string));
+        Some(Self {
+            prefix,
+            command,
+            params,
+        })
+    }
+
+    /// 发送者的昵称，前缀是服务器名时就是服务器名。
+    pub fn nick(&self) -> Option<&str> {
+        let prefix = self.prefix.as_deref()?;
+        Some(prefix.split_once('!').map_or(prefix, |(nick, _)| nick))
+    }
+
+    pub fn param(&self, index: usize) -> &str {
+        self.params.get(index).map_or("", String::as_str)
+    }
+
+    /// 要发送的一行，包括结尾的 `\r\n`。最后一个参数为空、以 `:` 开头或包含空格时加上 `:`。
+    pub fn to_line(&self) -> String {
+        let mut line = self.command.clone();
+        for (index, param) in self.params.iter().enumerate() {
+            line.push(' ');
+            let last = index + 1 == self.params.len();
+            if last && (param.is_empty() || param.starts_with(':') || param.contains(' ')) {
+                line.push(':');
+            }
+            // 换行会被服务器当作另一条命令。
+            line.extend(param.chars().filter(|c| *c != '\r' && *c != '\n'));
+        }
+        line + "\r\n"
+    }
+}
+
+/// 频道名以 `#`、`&`、`+` 或 `!` 开头，其他目标是用户。
+pub fn is_channel(target: &str) -> bool {
+    target.starts_with(['#', '&', '+', '!'])
+}
+
+/// CTCP ACTION（`/me`）的内容。
+pub fn action(text: &str) -> Option<&str> {
+    text.strip_prefix("\x01ACTION ")
+        .map(|rest| rest.strip_suffix('\x01').unwrap_or(rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_messages() {
+        let message = Message::parse(":alice!a@host PRIVMSG #rust :hello there\r\n").unwrap();
+        assert_eq!(message.nick(), Some("alice"));
+        assert_eq!(message.command, "PRIVMSG");
+        assert_eq!(message.params, ["#rust", "hello there"]);
+
+        let message = Message::parse("PING :irc.example.com").unwrap();
+        assert_eq!(message.prefix, None);
+        assert_eq!(message.params, ["irc.example.com"]);
+
+        let message =
+            Message::parse("@time=2024-01-01T00:00:00Z :irc.example.com 353 me = #rust :a @b +c")
+                .unwrap();
+        assert_eq!(message.nick(), Some("irc.example.com"));
+        assert_eq!(message.params, ["me", "=", "#rust", "a @b +c"]);
+        assert_eq!(message.param(9), "");
+
+        assert_eq!(Message::parse(""), None);
+        assert_eq!(Message::parse(":prefix-only"), None);
+    }
+
+    #[test]
+    fn format_messages() {
+        assert_eq!(Message::new("JOIN", &["#rust"]).to_line(), "JOIN #rust\r\n");
+        assert_eq!(
+            Message::new("PRIVMSG", &["#rust", "hi all\r\nQUIT"]).to_line(),
+            "PRIVMSG #rust :hi allQUIT\r\n"
+        );
+        assert_eq!(
+            Message::new("USER", &["me", "0", "*", "Real Name"]).to_line(),
+            "USER me 0 * :Real Name\r\n"
+        );
+        assert_eq!(action("\x01ACTION waves\x01"), Some("waves"));
+        assert!(is_channel("#rust") && !is_channel("alice"));
+    }
+}
@@ -0,0 +1,775 @@
+//! IRC 客户端演示：每个频道和私聊一个标签页，中间是聊天记录，右侧是频道的昵称列表，
+//! 底部是带历史记录的输入行。连接断开后自动重连并重新加入频道，见 `network` 模块。
+//!
+//! 输入行支持常用的命令：`/join #频道`、`/part [原因]`、`/msg 昵称 内容`、`/me 动作`、
+//! `/nick 新昵称`、`/topic [主题]`、`/close`（关闭私聊）、`/quote 原始命令` 和 `/quit [原因]`。
+//! 其他以 `/` 开头的输入报错；要发送以 `/` 开头的消息，写成 `//`。
+//!
+//! 按键：`Enter` 发送，`Up` / `Down` 浏览输入历史，`Alt+Left` / `Alt+Right` 切换标签页，
+//! `PageUp` / `PageDown` 滚动聊天记录，`Ctrl+C` 退出。
+
+use std::{
+    collections::{BTreeSet, VecDeque},
+    sync::mpsc::{self, Receiver},
+    time::Duration,
+};
+
+use clap::Parser;
+use color_eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Paragraph, Tabs},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+    text_input::{History, Input},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    irc::Message,
+    network::{Server, Update},
+};
+
+mod irc;
+mod network;
+
+/// 等待按键的最长时间，之后处理后台送回的结果。
+const REFRESH: Duration = Duration::from_millis(100);
+
+/// 每个标签页保留的行数。
+const SCROLLBACK: usize = 1000;
+
+/// 服务器消息所在的标签页，总是第一个。
+const STATUS: &str = "*status*";
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 服务器的主机名，例如 `irc.libera.chat`
+    server: String,
+    #[arg(long, default_value_t = 6697)]
+    port: u16,
+    /// 不使用 TLS（通常配合 `--port 6667`）
+    #[arg(long)]
+    plain: bool,
+    #[arg(long, default_value = "ratatui-demo")]
+    nick: String,
+    /// 连接后加入的频道，可以用逗号分隔多个
+    #[arg(long, value_delimiter = ',')]
+    join: Vec<String>,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let server = Server {
+        host: cli.server,
+        port: cli.port,
+        tls: !cli.plain,
+        nick: cli.nick.clone(),
+        realname: "ratatui IRC demo".into(),
+    };
+    let (sender, updates) = mpsc::channel();
+    let messages = network::spawn(server, sender);
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result =
+        App::new(cli.nick, &cli.join, messages, updates).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Message,
+    Action,
+    /// 加入、离开、改名等。
+    Event,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    time: String,
+    kind: Kind,
+    nick: Option<String>,
+    text: String,
+}
+
+/// 一个标签页：服务器、频道或私聊。
+#[derive(Debug, Default)]
+struct Window {
+    name: String,
+    lines: VecDeque<Entry>,
+    /// 频道中的昵称，不包括 `@`、`+` 等权限前缀。
+    nicks: BTreeSet<String>,
+    topic: String,
+    /// 有还没有看过的消息。
+    unread: bool,
+    /// 从底部向上滚动的行数。
+    scroll: usize,
+}
+
+impl Window {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    fn push(&mut self, kind: Kind, nick: Option<&str>, text: impl Into<String>) {
+        if self.lines.len() == SCROLLBACK {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(Entry {
+            time: chrono::Local::now().format("%H:%M").to_string(),
+            kind,
+            nick: nick.map(str::to_string),
+            text: text.into(),
+        });
+        // 向上滚动时新消息不改变看到的位置。
+        if self.scroll > 0 {
+            self.scroll += 1;
+        }
+    }
+
+    fn is_channel(&self) -> bool {
+        irc::is_channel(&self.name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Connection {
+    Connecting(u32),
+    Registered,
+    Disconnected { error: String, retry_in: Duration },
+}
+
+struct App {
+    nick: String,
+    messages: UnboundedSender<Message>,
+    updates: Receiver<Update>,
+    connection: Connection,
+    windows: Vec<Window>,
+    current: usize,
+    input: Input,
+    history: History,
+    exit: bool,
+}
+
+impl App {
+    fn new(
+        nick: String,
+        channels: &[String],
+        messages: UnboundedSender<Message>,
+        updates: Receiver<Update>,
+    ) -> Self {
+        let mut windows = vec![Window::new(STATUS)];
+        // 频道的标签页在注册后加入，见 `Update::Registered`。
+        windows.extend(channels.iter().map(|channel| Window::new(channel)));
+        Self {
+            nick,
+            messages,
+            updates,
+            connection: Connection::Connecting(1),
+            windows,
+            current: 0,
+            input: Input::default(),
+            history: History::default(),
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            while let Ok(update) = self.updates.try_recv() {
+                self.update(update);
+            }
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut()))?;
+            if events.poll(REFRESH)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn send(&self, command: &str, params: &[&str]) {
+        let _ = self.messages.send(Message::new(command, params));
+    }
+
+    fn window(&mut self, name: &str) -> &mut Window {
+        let index = self.window_index(name);
+        &mut self.windows[index]
+    }
+
+    /// 名称对应的标签页，没有时新建一个。IRC 的名称不区分大小写。
+    fn window_index(&mut self, name: &str) -> usize {
+        match self
+            .windows
+            .iter()
+            .position(|window| window.name.eq_ignore_ascii_case(name))
+        {
+            Some(index) => index,
+            None => {
+                self.windows.push(Window::new(name));
+                self.windows.len() - 1
+            }
+        }
+    }
+
+    fn status(&mut self, kind: Kind, text: impl Into<String>) {
+        self.windows[0].push(kind, None, text);
+    }
+
+    fn update(&mut self, update: Update) {
+        match update {
+            Update::Connecting(attempt) => self.connection = Connection::Connecting(attempt),
+            Update::Registered(nick) => {
+                self.connection = Connection::Registered;
+                self.nick = nick;
+                // 重连后重新加入之前的频道。
+                for window in &mut self.windows {
+                    if window.is_channel() {
+                        window.nicks.clear();
+                        let _ = self.messages.send(Message::new("JOIN", &[&window.name]));
+                    }
+                }
+            }
+            Update::Message(message) => self.receive(message),
+            Update::Disconnected { error, retry_in } => {
+                self.status(Kind::Error, format!("Disconnected: {error}"));
+                self.connection = Connection::Disconnected { error, retry_in };
+            }
+        }
+        if let Some(window) = self.windows.get_mut(self.current) {
+            window.unread = false;
+        }
+    }
+
+    /// 处理服务器发来的消息。
+    fn receive(&mut self, message: Message) {
+        let nick = message.nick().unwrap_or_default().to_string();
+        let me = nick.eq_ignore_ascii_case(&self.nick);
+        match message.command.as_str() {
+            "PRIVMSG" | "NOTICE" => {
+                let target = message.param(0);
+                // 私聊显示在对方的标签页中，发给自己的通知显示在状态页中。
+                let name = if irc::is_channel(target) {
+                    target
+                } else if message.command == "NOTICE" || nick.is_empty() {
+                    STATUS
+                } else {
+                    &nick
+                };
+                let text = message.param(1);
+                let window = self.window(name);
+                match irc::action(text) {
+                    Some(action) => window.push(Kind::Action, Some(&nick), action),
+                    None => window.push(Kind::Message, Some(&nick), text),
+                }
+                window.unread = true;
+            }
+            "JOIN" => {
+                let channel = message.param(0);
+                let window = self.window(channel);
+                window.nicks.insert(nick.clone());
+                window.push(Kind::Event, None, format!("{nick} joined {channel}"));
+                if me {
+                    self.current = self.window_index(channel);
+                }
+            }
+            "PART" | "KICK" => {
+                let channel = message.param(0);
+                let (who, reason) = if message.command == "KICK" {
+                    (message.param(1).to_string(), message.param(2))
+                } else {
+                    (nick.clone(), message.param(1))
+                };
+                let left = who.eq_ignore_ascii_case(&self.nick);
+                let window = self.window(channel);
+                window.nicks.remove(&who);
+                let verb = if message.command == "KICK" {
+                    format!("was kicked by {nick}")
+                } else {
+                    "left".into()
+                };
+                window.push(Kind::Event, None, format!("{who} {verb} ({reason})"));
+                if left {
+                    window.nicks.clear();
+                }
+            }
+            "QUIT" => {
+                for window in &mut self.windows {
+                    if window.nicks.remove(&nick) {
+                        let reason = message.param(0);
+                        window.push(Kind::Event, None, format!("{nick} quit ({reason})"));
+                    }
+                }
+            }
+            "NICK" => {
+                let new = message.param(0).to_string();
+                if me {
+                    self.nick = new.clone();
+                    self.status(Kind::Event, format!("You are now known as {new}"));
+                }
+                for window in &mut self.windows {
+                    if window.nicks.remove(&nick) {
+                        window.nicks.insert(new.clone());
+                        window.push(Kind::Event, None, format!("{nick} is now {new}"));
+                    }
+                    if window.name.eq_ignore_ascii_case(&nick) {
+                        window.name = new.clone();
+                    }
+                }
+            }
+            // RPL_NAMREPLY：频道中的昵称列表。
+            "353" => {
+                let names = message.param(3).to_string();
+                let window = self.window(message.param(2));
+                window.nicks.extend(
+                    names
+                        .split_whitespace()
+                        .map(|name| name.trim_start_matches(['@', '+', '%', '&', '~']))
+                        .map(str::to_string),
+                );
+            }
+            // RPL_TOPIC
+            "332" => {
+                let topic = message.param(2).to_string();
+                self.window(message.param(1)).topic = topic;
+            }
+            "TOPIC" => {
+                let topic = message.param(1).to_string();
+                let window = self.window(message.param(0));
+                window.push(Kind::Event, None, format!("{nick} set the topic: {topic}"));
+                window.topic = topic;
+            }
+            // 不需要显示的回复：昵称列表结束、MOTD 的开始和结束等。
+            "366" | "375" | "376" | "PONG" => {}
+            command => {
+                // 4xx 和 5xx 是错误回复。
+                let kind = if command.starts_with(['4', '5']) {
+                    Kind::Error
+                } else {
+                    Kind::Event
+                };
+                // 数字回复的第一个参数是自己的昵称。
+                let skip = usize::from(command.chars().all(|c| c.is_ascii_digit()));
+                let text = message.params[skip.min(message.params.len())..].join(" ");
+                self.status(kind, text);
+            }
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.exit = true,
+            KeyCode::Left if alt => self.switch(self.current + self.windows.len() - 1),
+            KeyCode::Right if alt => self.switch(self.current + 1),
+            KeyCode::Enter => {
+                let line = self.input.value().to_string();
+                self.history.push(&line);
+                self.input = Input::default();
+                self.submit(&line);
+            }
+            KeyCode::Up => {
+                if let Some(entry) = self.history.older(self.input.value()) {
+                    self.input = Input::with_value(entry);
+                }
+            }
+            KeyCode::Down => {
+                if let Some(entry) = self.history.newer() {
+                    self.input = Input::with_value(entry);
+                }
+            }
+            KeyCode::PageUp => {
+                let window = &mut self.windows[self.current];
+                window.scroll = (window.scroll + 10).min(window.lines.len().saturating_sub(1));
+            }
+            KeyCode::PageDown => {
+                let window = &mut self.windows[self.current];
+                window.scroll = window.scroll.saturating_sub(10);
+            }
+            _ => {
+                self.input.handle_key_event(key);
+            }
+        }
+    }
+
+    fn switch(&mut self, index: usize) {
+        self.current = index % self.windows.len();
+        self.windows[self.current].unread = false;
+    }
+
+    /// 处理输入行：命令或者发送到当前标签页的消息。
+    fn submit(&mut self, line: &str) {
+        if line.is_empty() {
+            return;
+        }
+        let target = self.windows[self.current].name.clone();
+        let Some(command) = line.strip_prefix('/').filter(|rest| !rest.starts_with('/')) else {
+            // `//` 开头的消息去掉一个 `/` 后原样发送。
+            let text = line.strip_prefix('/').unwrap_or(line);
+            self.say(&target, text);
+            return;
+        };
+        let (name, args) = command.split_once(' ').unwrap_or((command, ""));
+        let args = args.trim();
+        match name.to_ascii_lowercase().as_str() {
+            "join" if !args.is_empty() => self.send("JOIN", &[args]),
+            "part" if self.windows[self.current].is_channel() => {
+                self.send("PART", &[&target, args]);
+            }
+            "msg" => match args.split_once(' ') {
+                Some((nick, text)) => {
+                    self.say(nick, text);
+                    self.current = self.window_index(nick);
+                }
+                None => self.error("Usage: /msg <nick> <text>"),
+            },
+            "me" if target != STATUS => {
+                let text = format!("\x01ACTION {args}\x01");
+                self.send("PRIVMSG", &[&target, &text]);
+                let nick = self.nick.clone();
+                self.windows[self.current].push(Kind::Action, Some(&nick), args);
+            }
+            "nick" if !args.is_empty() => self.send("NICK", &[args]),
+            "topic" if self.windows[self.current].is_channel() => {
+                if args.is_empty() {
+                    self.send("TOPIC", &[&target]);
+                } else {
+                    self.send("TOPIC", &[&target, args]);
+                }
+            }
+            "close" if self.current > 0 && !self.windows[self.current].is_channel() => {
+                self.windows.remove(self.current);
+                self.current -= 1;
+            }
+            "quote" if !args.is_empty() => match Message::parse(args) {
+                Some(message) => {
+                    let _ = self.messages.send(message);
+                }
+                None => self.error("Invalid command"),
+            },
+            "quit" => {
+                self.send("QUIT", &[if args.is_empty() { "Bye" } else { args }]);
+                self.exit = true;
+            }
+            _ => self.error(format!("Unknown command or missing argument: /{name}")),
+        }
+    }
+
+    fn say(&mut self, target: &str, text: &str) {
+        if target == STATUS {
+            self.error("Not in a channel; use /join or /msg");
+            return;
+        }
+        if self.connection != Connection::Registered {
+            self.error("Not connected");
+            return;
+        }
+        self.send("PRIVMSG", &[target, text]);
+        // 服务器不会把自己的消息发回来。
+        let nick = self.nick.clone();
+        self.window(target).push(Kind::Message, Some(&nick), text);
+    }
+
+    fn error(&mut self, text: impl Into<String>) {
+        self.windows[self.current].push(Kind::Error, None, text);
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let [tabs_area, main, input_area, status] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let titles: Vec<Line> = self
+            .windows
+            .iter()
+            .map(|window| {
+                if window.unread {
+                    Line::from(window.name.clone().bold().yellow())
+                } else {
+                    Line::from(window.name.clone())
+                }
+            })
+            .collect();
+        Tabs::new(titles)
+            .select(self.current)
+            .highlight_style(Style::new().reversed())
+            .render(tabs_area, buf);
+
+        let window = &self.windows[self.current];
+        let nicks_width = if window.is_channel() { 18 } else { 0 };
+        let [log_area, nicks_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(nicks_width)]).areas(main);
+
+        let mut block = Block::bordered().title(format!(" {} ", window.name).bold());
+        if !window.topic.is_empty() {
+            block = block.title(Line::from(format!(" {} ", window.topic).dim()));
+        }
+        if window.scroll > 0 {
+            block = block.title_bottom(
+                Line::from(format!(" ↑ {} more below ", window.scroll)).right_aligned(),
+            );
+        }
+        let height = log_area.height.saturating_sub(2) as usize;
+        let end = window.lines.len() - window.scroll.min(window.lines.len());
+        let lines: Vec<Line> = window
+            .lines
+            .range(end.saturating_sub(height)..end)
+            .map(entry_line)
+            .collect();
+        Paragraph::new(lines).block(block).render(log_area, buf);
+
+        if window.is_channel() {
+            let nicks: Vec<Line> = window
+                .nicks
+                .iter()
+                .map(|nick| Line::from(nick.clone().fg(nick_color(nick))))
+                .collect();
+            Paragraph::new(nicks)
+                .block(Block::bordered().title(format!(" {} ", window.nicks.len())))
+                .render(nicks_area, buf);
+        }
+
+        let prompt = format!("[{}] ", self.nick);
+        Paragraph::new(self.input.line(&prompt, Style::new().bold()))
+            .block(Block::bordered())
+            .render(input_area, buf);
+
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        match &self.connection {
+            Connection::Connecting(1) => Line::from("Connecting…".yellow()),
+            Connection::Connecting(attempt) => {
+                Line::from(format!("Reconnecting (attempt {attempt})…").yellow())
+            }
+            Connection::Registered => Line::from(format!(
+                "Connected as {}  Switch <Alt+←/→> Quit <Ctrl+C>",
+                self.nick
+            )),
+            Connection::Disconnected { error, retry_in } => Line::from(
+                format!("Disconnected: {error}, retrying in {}s", retry_in.as_secs()).red(),
+            ),
+        }
+    }
+}
+
+fn entry_line(entry: &Entry) -> Line<'static> {
+    let mut spans = vec![format!("{} ", entry.time).dim()];
+    let nick = entry.nick.clone().unwrap_or_default();
+    match entry.kind {
+        Kind::Message => {
+            spans.push(format!("<{nick}> ").fg(nick_color(&nick)));
+            spans.push(entry.text.clone().into());
+        }
+        Kind::Action => spans.push(format!("* {nick} {}", entry.text).italic()),
+        Kind::Event => spans.push(format!("-- {}", entry.text).dim()),
+        Kind::Error => spans.push(format!("!! {}", entry.text).red()),
+    }
+    Line::from(spans)
+}
+
+/// 同一个昵称总是使用同一种颜色。
+fn nick_color(nick: &str) -> Color {
+    const COLORS: [Color; 6] = [
+        Color::Cyan,
+        Color::Green,
+        Color::Magenta,
+        Color::Yellow,
+        Color::Blue,
+        Color::LightRed,
+    ];
+    let hash = nick
+        .bytes()
+        .fold(0usize, |hash, byte| hash.wrapping_mul(31) + byte as usize);
+    COLORS[hash % COLORS.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+    use super::*;
+
+    fn app() -> (App, UnboundedReceiver<Message>) {
+        let (messages, sent) = unbounded_channel();
+        let (_updates, receiver) = mpsc::channel();
+        let app = App::new("me".into(), &["#rust".into()], messages, receiver);
+        (app, sent)
+    }
+
+    fn receive(app: &mut App, line: &str) {
+        app.update(Update::Message(Message::parse(line).unwrap()));
+    }
+
+    fn type_line(app: &mut App, line: &str) {
+        for c in line.chars() {
+            app.handle_key(KeyCode::Char(c).into());
+        }
+        app.handle_key(KeyCode::Enter.into());
+    }
+
+    fn texts(window: &Window) -> Vec<String> {
+        window
+            .lines
+            .iter()
+            .map(|entry| entry.text.clone())
+            .collect()
+    }
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    #[test]
+    fn channels_and_nicks() {
+        let (mut app, mut sent) = app();
+        app.update(Update::Registered("me_".into()));
+        assert_eq!(app.nick, "me_");
+        assert_eq!(sent.try_recv().unwrap().to_line(), "JOIN #rust\r\n");
+
+        receive(&mut app, ":me_!u@h JOIN #rust");
+        assert_eq!(app.current, 1);
+        receive(&mut app, ":srv 353 me_ = #rust :me_ @alice +bob");
+        receive(&mut app, ":srv 332 me_ #rust :Rust talk");
+        receive(&mut app, ":alice!a@h PRIVMSG #rust :hello");
+        receive(&mut app, ":bob!b@h NICK robert");
+        receive(&mut app, ":alice!a@h QUIT :bye");
+        let window = &app.windows[1];
+        assert_eq!(window.topic, "Rust talk");
+        assert_eq!(window.nicks.iter().collect::<Vec<_>>(), ["me_", "robert"]);
+        assert_eq!(
+            texts(window),
+            [
+                "me_ joined #rust",
+                "hello",
+                "bob is now robert",
+                "alice quit (bye)"
+            ]
+        );
+
+        // 私聊打开新的标签页，标记为未读。
+        receive(&mut app, ":carol!c@h PRIVMSG me_ :\x01ACTION waves\x01");
+        assert_eq!(app.windows[2].name, "carol");
+        assert_eq!(app.windows[2].lines[0].kind, Kind::Action);
+        assert!(app.windows[2].unread);
+
+        receive(&mut app, ":srv 433 me_ robert :Nickname is already in use");
+        assert_eq!(
+            texts(&app.windows[0]),
+            ["robert Nickname is already in use"]
+        );
+        assert_eq!(app.windows[0].lines[0].kind, Kind::Error);
+    }
+
+    #[test]
+    fn input_commands_and_history() {
+        let (mut app, mut sent) = app();
+        app.handle_key(KeyEvent::new(KeyCode::Right, KeyModifiers::ALT));
+        type_line(&mut app, "hi");
+        assert_eq!(texts(&app.windows[1]), ["Not connected"]);
+
+        app.update(Update::Registered("me".into()));
+        sent.try_recv().unwrap();
+        type_line(&mut app, "hello");
+        type_line(&mut app, "//etc/hosts");
+        type_line(&mut app, "/me waves");
+        type_line(&mut app, "/msg alice psst");
+        type_line(&mut app, "/bogus");
+        let lines: Vec<String> = std::iter::from_fn(|| sent.try_recv().ok())
+            .map(|message| message.to_line())
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "PRIVMSG #rust hello\r\n",
+                "PRIVMSG #rust /etc/hosts\r\n",
+                "PRIVMSG #rust :\x01ACTION waves\x01\r\n",
+                "PRIVMSG alice psst\r\n",
+            ]
+        );
+        assert_eq!(app.windows[app.current].name, "alice");
+        assert_eq!(
+            texts(&app.windows[app.current]),
+            ["psst", "Unknown command or missing argument: /bogus"]
+        );
+
+        app.handle_key(KeyCode::Up.into());
+        app.handle_key(KeyCode::Up.into());
+        assert_eq!(app.input.value(), "/msg alice psst");
+        app.handle_key(KeyCode::Down.into());
+        app.handle_key(KeyCode::Down.into());
+        assert_eq!(app.input.value(), "");
+
+        type_line(&mut app, "/close");
+        assert_eq!(app.windows.len(), 2);
+        type_line(&mut app, "/quit");
+        assert_eq!(sent.try_recv().unwrap().to_line(), "QUIT Bye\r\n");
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn reconnect_rejoins() {
+        let (mut app, mut sent) = app();
+        app.update(Update::Registered("me".into()));
+        sent.try_recv().unwrap();
+        receive(&mut app, ":srv 353 me = #rust :me alice");
+        app.update(Update::Disconnected {
+            error: "connection reset".into(),
+            retry_in: Duration::from_secs(4),
+        });
+        assert_eq!(
+            app.status_line().to_string(),
+            "Disconnected: connection reset, retrying in 4s"
+        );
+        app.update(Update::Connecting(2));
+        assert_eq!(app.status_line().to_string(), "Reconnecting (attempt 2)…");
+        app.update(Update::Registered("me".into()));
+        assert_eq!(sent.try_recv().unwrap().to_line(), "JOIN #rust\r\n");
+        assert!(app.windows[1].nicks.is_empty());
+    }
+
+    #[test]
+    fn render_channel() {
+        let (mut app, _sent) = app();
+        app.update(Update::Registered("me".into()));
+        receive(&mut app, ":me!u@h JOIN #rust");
+        receive(&mut app, ":srv 353 me = #rust :me alice");
+        receive(&mut app, ":alice!a@h PRIVMSG #rust :hello");
+        let rows = rows(&mut app, 60, 9);
+        assert_eq!(
+            rows[0],
+            " *status* │ #rust                                           "
+        );
+        // 时间随运行时间变化，只比较其他部分。
+        assert!(rows[2].contains(" -- me joined #rust "));
+        assert!(rows[2].ends_with("││alice           │"));
+        assert!(rows[3].contains(" <alice> hello "));
+        assert!(rows[3].ends_with("││me              │"));
+        assert_eq!(
+            rows[6],
+            "│[me]                                                      │"
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! 与 IRC 服务器的长连接，在后台线程的 tokio 运行时中运行。
+//!
+//! 连接断开后按指数退避自动重连（1 秒、2 秒、4 秒……最多 60 秒），注册成功后退避重新开始。
+//! 服务器的 `PING` 在这里直接回复；长时间没有收到任何数据时主动发送 `PING`，
+//! 仍然没有回应就认为连接已经断开。
+
+use std::{io, sync::mpsc, thread, time::Duration};
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    net::TcpStream,
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    time::{sleep, timeout},
+};
+
+use crate::irc::Message;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// 这么久没有收到数据时发送 `PING`，再过这么久仍然没有数据就断开。
+const IDLE: Duration = Duration::from_secs(120);
+
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Server {
+    pub host: String,
+    pub port: u16,
+    pub tls: bool,
+    pub nick: String,
+    pub realname: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Update {
+    /// 开始第几次连接，从 1 开始。
+    Connecting(u32),
+    /// 注册成功，服务器确认的昵称（昵称被占用时可能加了下划线）。
+    Registered(String),
+    Message(Message),
+    Disconnected {
+        error: String,
+        retry_in: Duration,
+    },
+}
+
+/// 启动后台线程，返回发送消息的一端。发送端被丢弃后发送 `QUIT` 并结束线程。
+pub fn spawn(server: Server, updates: mpsc::Sender<Update>) -> UnboundedSender<Message> {
+    let (messages, receiver) = unbounded_channel();
+    thread::spawn(move || {
+        let runtime = match tokio::runtime::Runtime::new() {
+            Ok(runtime) => runtime,
+            Err(error) => {
+                let _ = updates.send(Update::Disconnected {
+                    error: error.to_string(),
+                    retry_in: Duration::MAX,
+                });
+                return;
+            }
+        };
+        runtime.block_on(serve(server, receiver, updates));
+    });
+    messages
+}
+
+/// 第 `attempt` 次连接失败后等待的时间。
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_secs(1 << attempt.saturating_sub(1).min(6)).min(MAX_BACKOFF)
+}
+
+async fn serve(
+    server: Server,
+    mut outgoing: UnboundedReceiver<Message>,
+    updates: mpsc::Sender<Update>,
+) {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        if updates.send(Update::Connecting(attempt)).is_err() {
+            return;
+        }
+        let mut registered = false;
+        let error = match session(&server, &mut outgoing, &updates, &mut registered).await {
+            // 用户退出了。
+            Ok(()) => return,
+            Err(error) => error,
+        };
+        if registered {
+            attempt = 1;
+        }
+        let retry_in = backoff(attempt);
+        let disconnected = Update::Disconnected {
+            error: error.to_string(),
+            retry_in,
+        };
+        if updates.send(disconnected).is_err() {
+            return;
+        }
+        // 断开期间输入的消息无法发送，丢弃。
+        let wait = sleep(retry_in);
+        tokio::pin!(wait);
+        loop {
+            tokio::select! {
+                () = &mut wait => break,
+                message = outgoing.recv() => if message.is_none() {
+                    return;
+                },
+            }
+        }
+    }
+}
+
+trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Stream for T {}
+
+async fn connect(server: &Server) -> io::Result<Box<dyn Stream>> {
+    let address = (server.host.as_str(), server.port);
+    let tcp = timeout(CONNECT_TIMEOUT, TcpStream::connect(address))
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "connection timed out"))??;
+    if !server.tls {
+        return Ok(Box::new(tcp));
+    }
+    let connector = native_tls::TlsConnector::new().map_err(io::Error::other)?;
+    let tls = tokio_native_tls::TlsConnector::from(connector)
+        .connect(&server.host, tcp)
+        .await
+        .map_err(io::Error::other)?;
+    Ok(Box::new(tls))
+}
+
+async fn send(writer: &mut (impl AsyncWrite + Unpin), message: &Message) -> io::Result<()> {
+    writer.write_all(message.to_line().as_bytes()).await?;
+    writer.flush().await
+}
+
+/// 一次连接。用户退出时返回 `Ok`，连接出错或断开时返回错误。
+async fn session(
+    server: &Server,
+    outgoing: &mut UnboundedReceiver<Message>,
+    updates: &mpsc::Sender<Update>,
+    registered: &mut bool,
+) -> io::Result<()> {
+    let (reader, mut writer) = tokio::io::split(connect(server).await?);
+    let mut reader = BufReader::new(reader);
+    let mut nick = server.nick.clone();
+    send(&mut writer, &Message::new("NICK", &[&nick])).await?;
+    send(
+        &mut writer,
+        &Message::new("USER", &[&server.nick, "0", "*", &server.realname]),
+    )
+    .await?;
+
+    // 在多次 `select!` 之间保留，读到一半被取消的行不会丢失。
+    let mut line = Vec::new();
+    let mut pinged = false;
+    loop {
+        tokio::select! {
+            read = timeout(IDLE, reader.read_until(b'\n', &mut line)) => {
+                match read {
+                    Err(_) if pinged => {
+                        return Err(io::Error::new(io::ErrorKind::TimedOut, "ping timeout"));
+                    }
+                    Err(_) => {
+                        pinged = true;
+                        send(&mut writer, &Message::new("PING", &[&server.host])).await?;
+                        continue;
+                    }
+                    Ok(Ok(0)) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "connection closed by server",
+                        ))
+                    }
+                    Ok(Ok(_)) => pinged = false,
+                    Ok(Err(error)) => return Err(error),
+                }
+                // 有些服务器和频道不使用 UTF-8。
+                let text = String::from_utf8_lossy(&line).into_owned();
+                line.clear();
+                let Some(message) = Message::parse(&text) else {
+                    continue;
+                };
+                match message.command.as_str() {
+                    "PING" => {
+                        let pong = Message::new("PONG", &[message.param(0)]);
+                        send(&mut writer, &pong).await?;
+                        continue;
+                    }
+                    // 昵称被占用，注册前换一个再试。
+                    "433" if !*registered => {
+                        nick.push('_');
+                        send(&mut writer, &Message::new("NICK", &[&nick])).await?;
+                    }
+                    "001" => {
+                        *registered = true;
+                        let _ = updates.send(Update::Registered(message.param(0).to_string()));
+                    }
+                    _ => {}
+                }
+                if updates.send(Update::Message(message)).is_err() {
+                    return Ok(());
+                }
+            }
+            message = outgoing.recv() => {
+                let Some(message) = message else {
+                    let _ = send(&mut writer, &Message::new("QUIT", &["Bye"])).await;
+                    return Ok(());
+                };
+                send(&mut writer, &message).await?;
+                if message.command == "QUIT" {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exponential_backoff() {
+        let delays: Vec<u64> = (1..=9).map(|n| backoff(n).as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16, 32, 60, 60, 60]);
+    }
+}
@@ -0,0 +1,236 @@
+//! 在后台线程的 tokio 运行时中访问 Kubernetes API。
+//!
+//! 和 Docker 演示一样，界面线程发送 [`Command`]，结果以 [`Update`] 的形式通过标准库的通道送回。
+//! 命名空间、Pod 列表和所选 Pod 的事件定时刷新，日志则是一条持续的流。
+
+use std::{sync::mpsc, thread, time::Duration};
+
+use futures_util::{AsyncBufReadExt, StreamExt};
+use k8s_openapi::api::core::v1::{Event, Namespace, Pod};
+use kube::{
+    api::{ListParams, LogParams},
+    Api, Client,
+};
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+};
+
+/// 列表的刷新间隔。
+const REFRESH: Duration = Duration::from_secs(3);
+
+/// 跟随日志时先显示的历史行数。
+const LOG_TAIL: i64 = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// 切换到这个命名空间，之后定时列出其中的 Pod。
+    Namespace(String),
+    /// 跟随这个 Pod 的日志并定时读取它的事件，之前跟随的日志流停止。
+    Follow { namespace: String, pod: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PodInfo {
+    pub name: String,
+    /// `Running`、`Pending` 等。
+    pub phase: String,
+    /// 就绪的容器数和容器总数。
+    pub ready: (usize, usize),
+    pub restarts: i32,
+    /// 创建时间，Unix 时间戳（秒）。
+    pub created: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PodEvent {
+    /// `Normal` 或 `Warning`。
+    pub kind: String,
+    pub reason: String,
+    pub message: String,
+    pub count: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Update {
+    Namespaces(Vec<String>),
+    Pods {
+        namespace: String,
+        pods: Vec<PodInfo>,
+    },
+    Log {
+        pod: String,
+        line: String,
+    },
+    /// 日志流结束，例如 Pod 被删除。
+    LogEnded(String),
+    Events {
+        pod: String,
+        events: Vec<PodEvent>,
+    },
+    Error(String),
+    /// 无法读取 kubeconfig 或集群配置，后台线程退出。
+    Disconnected(String),
+}
+
+/// 启动后台线程，返回发送命令的一端。
+pub fn spawn(namespace: String, updates: mpsc::Sender<Update>) -> UnboundedSender<Command> {
+    let (commands, receiver) = unbounded_channel();
+    thread::spawn(move || {
+        let runtime = match tokio::runtime::Runtime::new() {
+            Ok(runtime) => runtime,
+            Err(error) => {
+                let _ = updates.send(Update::Disconnected(error.to_string()));
+                return;
+            }
+        };
+        runtime.block_on(serve(namespace, receiver, updates));
+    });
+    commands
+}
+
+async fn serve(
+    mut namespace: String,
+    mut commands: UnboundedReceiver<Command>,
+    updates: mpsc::Sender<Update>,
+) {
+    let client = match Client::try_default().await {
+        Ok(client) => client,
+        Err(error) => {
+            let _ = updates.send(Update::Disconnected(error.to_string()));
+            return;
+        }
+    };
+    let mut refresh = tokio::time::interval(REFRESH);
+    let mut followed: Option<(String, JoinHandle<()>)> = None;
+    loop {
+        tokio::select! {
+            _ = refresh.tick() => {
+                let mut batch = vec![namespaces(&client).await, pods(&client, &namespace).await];
+                if let Some((pod, _)) = &followed {
+                    batch.push(events(&client, &namespace, pod).await);
+                }
+                for update in batch {
+                    let update = update.unwrap_or_else(|error| Update::Error(error.to_string()));
+                    if updates.send(update).is_err() {
+                        return;
+                    }
+                }
+            }
+            command = commands.recv() => match command {
+                Some(Command::Namespace(name)) => {
+                    namespace = name;
+                    if let Some((_, task)) = followed.take() {
+                        task.abort();
+                    }
+                    refresh.reset_immediately();
+                }
+                Some(Command::Follow { namespace, pod }) => {
+                    if let Some((_, task)) = followed.take() {
+                        task.abort();
+                    }
+                    let task = tokio::spawn(follow(client.clone(), namespace, pod.clone(), updates.clone()));
+                    followed = Some((pod, task));
+                    refresh.reset_immediately();
+                }
+                None => break,
+            },
+        }
+    }
+}
+
+async fn namespaces(client: &Client) -> kube::Result<Update> {
+    let list = Api::<Namespace>::all(client.clone())
+        .list(&ListParams::default())
+        .await?;
+    let mut names: Vec<String> = list
+        .into_iter()
+        .filter_map(|namespace| namespace.metadata.name)
+        .collect();
+    names.sort();
+    Ok(Update::Namespaces(names))
+}
+
+async fn pods(client: &Client, namespace: &str) -> kube::Result<Update> {
+    let list = Api::<Pod>::namespaced(client.clone(), namespace)
+        .list(&ListParams::default())
+        .await?;
+    let mut pods: Vec<PodInfo> = list.into_iter().map(pod_info).collect();
+    pods.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(Update::Pods {
+        namespace: namespace.to_string(),
+        pods,
+    })
+}
+
+async fn events(client: &Client, namespace: &str, pod: &str) -> kube::Result<Update> {
+    let params = ListParams::default().fields(&format!("involvedObject.name={pod}"));
+    let list = Api::<Event>::namespaced(client.clone(), namespace)
+        .list(&params)
+        .await?;
+    let mut list = list.items;
+    list.sort_by_key(|event| event.last_timestamp.as_ref().map(|time| time.0));
+    let events = list
+        .into_iter()
+        .map(|event| PodEvent {
+            kind: event.type_.unwrap_or_default(),
+            reason: event.reason.unwrap_or_default(),
+            message: event.message.unwrap_or_default(),
+            count: event.count.unwrap_or(1),
+        })
+        .collect();
+    Ok(Update::Events {
+        pod: pod.to_string(),
+        events,
+    })
+}
+
+async fn follow(client: Client, namespace: String, pod: String, updates: mpsc::Sender<Update>) {
+    let params = LogParams {
+        follow: true,
+        tail_lines: Some(LOG_TAIL),
+        ..Default::default()
+    };
+    let stream = match Api::<Pod>::namespaced(client, &namespace)
+        .log_stream(&pod, &params)
+        .await
+    {
+        Ok(stream) => stream,
+        Err(error) => {
+            let _ = updates.send(Update::Error(format!("logs of {pod}: {error}")));
+            return;
+        }
+    };
+    let mut lines = stream.lines();
+    while let Some(line) = lines.next().await {
+        let update = match line {
+            Ok(line) => Update::Log {
+                pod: pod.clone(),
+                line,
+            },
+            Err(error) => Update::Error(format!("logs of {pod}: {error}")),
+        };
+        if updates.send(update).is_err() {
+            return;
+        }
+    }
+    let _ = updates.send(Update::LogEnded(pod));
+}
+
+fn pod_info(pod: Pod) -> PodInfo {
+    let status = pod.status.unwrap_or_default();
+    let containers = status.container_statuses.unwrap_or_default();
+    PodInfo {
+        name: pod.metadata.name.unwrap_or_default(),
+        phase: status.phase.unwrap_or_default(),
+        ready: (
+            containers.iter().filter(|c| c.ready).count(),
+            containers.len(),
+        ),
+        restarts: containers.iter().map(|c| c.restart_count).sum(),
+        created: pod
+            .metadata
+            .creation_timestamp
+            .map(|time| time.0.as_second()),
+    }
+}
@@ -0,0 +1,474 @@
+//! Kubernetes Pod 查看演示：按命名空间列出 Pod，右侧实时显示所选 Pod 的日志和相关的事件。
+//!
+//! 集群的连接方式和 `kubectl` 相同（`KUBECONFIG`、`~/.kube/config` 或集群内的服务账号）。
+//! API 调用在后台线程的 tokio 运行时中进行，见 `cluster` 模块；连接中、连接失败和请求出错都显示在状态栏中。
+//!
+//! 按键：`Up` / `Down` 选择 Pod，`Left` / `Right` 切换命名空间，`q` 退出。
+
+use std::{
+    collections::VecDeque,
+    sync::mpsc::{self, Receiver},
+    time::{Duration, SystemTime},
+};
+
+use clap::Parser;
+use color_eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Paragraph, Row, Table, TableState, Wrap},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::cluster::{Command, PodEvent, PodInfo, Update};
+
+mod cluster;
+
+/// 等待按键的最长时间，之后处理后台送回的结果。
+const REFRESH: Duration = Duration::from_millis(100);
+
+/// 日志面板保留的行数。
+const LOG_LINES: usize = 1000;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// 开始时显示的命名空间。
+    #[arg(short, long, default_value = "default")]
+    namespace: String,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let (sender, updates) = mpsc::channel();
+    let commands = cluster::spawn(cli.namespace.clone(), sender);
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new(cli.namespace, commands, updates).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Connection {
+    /// 还没有收到第一次的结果。
+    Connecting,
+    Connected,
+    Failed(String),
+}
+
+struct App {
+    commands: UnboundedSender<Command>,
+    updates: Receiver<Update>,
+    connection: Connection,
+    namespaces: Vec<String>,
+    namespace: String,
+    pods: Vec<PodInfo>,
+    table: TableState,
+    /// 正在跟随日志的 Pod。
+    following: Option<String>,
+    logs: VecDeque<String>,
+    events: Vec<PodEvent>,
+    /// 最近一次请求的错误。
+    error: Option<String>,
+    /// 当前时间，Unix 时间戳（秒），用于计算 Pod 的存在时间。
+    now: i64,
+    exit: bool,
+}
+
+impl App {
+    fn new(
+        namespace: String,
+        commands: UnboundedSender<Command>,
+        updates: Receiver<Update>,
+    ) -> Self {
+        Self {
+            commands,
+            updates,
+            connection: Connection::Connecting,
+            namespaces: Vec::new(),
+            namespace,
+            pods: Vec::new(),
+            table: TableState::default().with_selected(Some(0)),
+            following: None,
+            logs: VecDeque::new(),
+            events: Vec::new(),
+            error: None,
+            now: 0,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            while let Ok(update) = self.updates.try_recv() {
+                self.update(update);
+            }
+            self.now = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs() as i64);
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut()))?;
+            if events.poll(REFRESH)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key.code);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, update: Update) {
+        match update {
+            Update::Namespaces(namespaces) => {
+                self.connection = Connection::Connected;
+                self.namespaces = namespaces;
+            }
+            Update::Pods { namespace, pods } => {
+                // 切换命名空间之前发出的请求，结果已经过时。
+                if namespace != self.namespace {
+                    return;
+                }
+                self.connection = Connection::Connected;
+                self.error = None;
+                let selected = self.selected().map(|pod| pod.name.clone());
+                self.pods = pods;
+                let position = selected
+                    .and_then(|name| self.pods.iter().position(|pod| pod.name == name))
+                    .unwrap_or(0);
+                self.table.select(Some(position));
+                self.follow_selected();
+            }
+            Update::Log { pod, line } => {
+                if self.following.as_ref() == Some(&pod) {
+                    if self.logs.len() == LOG_LINES {
+                        self.logs.pop_front();
+                    }
+                    self.logs.push_back(line);
+                }
+            }
+            Update::LogEnded(pod) => {
+                if self.following.as_ref() == Some(&pod) {
+                    self.following = None;
+                }
+            }
+            Update::Events { pod, events } => {
+                if self.following.as_ref() == Some(&pod) {
+                    self.events = events;
+                }
+            }
+            // 还没有连接成功时出错，多半是集群无法访问。后台会继续重试。
+            Update::Error(error) if self.connection != Connection::Connected => {
+                self.connection = Connection::Failed(error)
+            }
+            Update::Error(error) => self.error = Some(error),
+            Update::Disconnected(error) => self.connection = Connection::Failed(error),
+        }
+    }
+
+    fn selected(&self) -> Option<&PodInfo> {
+        self.pods.get(self.table.selected()?)
+    }
+
+    /// 跟随所选 Pod 的日志。还在等待调度的 Pod 没有日志。
+    fn follow_selected(&mut self) {
+        let Some(pod) = self.selected() else {
+            return;
+        };
+        if self.following.as_ref() == Some(&pod.name) || pod.phase == "Pending" {
+            return;
+        }
+        let name = pod.name.clone();
+        self.logs.clear();
+        self.events.clear();
+        self.following = Some(name.clone());
+        let _ = self.commands.send(Command::Follow {
+            namespace: self.namespace.clone(),
+            pod: name,
+        });
+    }
+
+    fn switch_namespace(&mut self, step: isize) {
+        if self.namespaces.is_empty() {
+            return;
+        }
+        let len = self.namespaces.len() as isize;
+        let current = self
+            .namespaces
+            .iter()
+            .position(|name| *name == self.namespace)
+            .map_or(0, |index| index as isize + step);
+        self.namespace = self.namespaces[current.rem_euclid(len) as usize].clone();
+        self.pods.clear();
+        self.logs.clear();
+        self.events.clear();
+        self.following = None;
+        self.table.select(Some(0));
+        let _ = self
+            .commands
+            .send(Command::Namespace(self.namespace.clone()));
+    }
+
+    fn handle_key(&mut self, code: KeyCode) {
+        let selected = self.table.selected().unwrap_or(0);
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
+            KeyCode::Up => {
+                self.table.select(Some(selected.saturating_sub(1)));
+                self.follow_selected();
+            }
+            KeyCode::Down => {
+                let last = self.pods.len().saturating_sub(1);
+                self.table.select(Some((selected + 1).min(last)));
+                self.follow_selected();
+            }
+            KeyCode::Left => self.switch_namespace(-1),
+            KeyCode::Right => self.switch_namespace(1),
+            _ => {}
+        }
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let [pods_area, right] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(main);
+        let [logs_area, events_area] =
+            Layout::vertical([Constraint::Fill(2), Constraint::Fill(1)]).areas(right);
+
+        let rows: Vec<Row> = self
+            .pods
+            .iter()
+            .map(|pod| {
+                let phase = match pod.phase.as_str() {
+                    "Running" | "Succeeded" => pod.phase.clone().green(),
+                    "Pending" => pod.phase.clone().yellow(),
+                    _ => pod.phase.clone().red(),
+                };
+                let age = pod
+                    .created
+                    .map_or_else(|| "-".into(), |created| format_age(self.now - created));
+                Row::new(vec![
+                    Line::from(pod.name.clone()),
+                    Line::from(format!("{}/{}", pod.ready.0, pod.ready.1)),
+                    Line::from(phase),
+                    Line::from(pod.restarts.to_string()),
+                    Line::from(age),
+                ])
+            })
+            .collect();
+        let widths = [
+            Constraint::Fill(1),
+            Constraint::Length(5),
+            Constraint::Length(9),
+            Constraint::Length(3),
+            Constraint::Length(4),
+        ];
+        let position = self
+            .namespaces
+            .iter()
+            .position(|name| *name == self.namespace)
+            .map_or_else(String::new, |index| {
+                format!(" ({}/{})", index + 1, self.namespaces.len())
+            });
+        let table = Table::new(rows, widths)
+            .header(Row::new(["Pod", "Ready", "Status", "⟳", "Age"]).bold())
+            .highlight_style(Style::new().reversed())
+            .block(
+                Block::bordered()
+                    .title(format!(" ◀ {}{position} ▶ ", self.namespace).bold())
+                    .title_bottom(Line::from(" Namespace <←>/<→> Quit <Q> ").centered()),
+            );
+        StatefulWidget::render(table, pods_area, buf, &mut self.table);
+
+        // 只显示最后几行，新日志出现在底部。
+        let height = logs_area.height.saturating_sub(2) as usize;
+        let logs: Vec<Line> = self
+            .logs
+            .iter()
+            .skip(self.logs.len().saturating_sub(height))
+            .map(|line| Line::from(line.clone()))
+            .collect();
+        let title = match &self.following {
+            Some(pod) => format!(" Logs: {pod} "),
+            None => " Logs ".into(),
+        };
+        Paragraph::new(logs)
+            .block(Block::bordered().title(title))
+            .render(logs_area, buf);
+
+        let events: Vec<Line> = self.events.iter().map(event_line).collect();
+        Paragraph::new(events)
+            .wrap(Wrap { trim: false })
+            .block(Block::bordered().title(" Events "))
+            .render(events_area, buf);
+
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        match &self.connection {
+            Connection::Connecting => Line::from("Connecting to the cluster…".yellow()),
+            Connection::Failed(error) => {
+                Line::from(format!("Cannot reach the cluster: {error}").red())
+            }
+            Connection::Connected => match &self.error {
+                Some(error) => Line::from(error.clone().red()),
+                None => Line::from(format!("{} pods in {}", self.pods.len(), self.namespace)),
+            },
+        }
+    }
+}
+
+fn event_line(event: &PodEvent) -> Line<'static> {
+    let kind = if event.kind == "Warning" {
+        event.kind.clone().yellow()
+    } else {
+        event.kind.clone().dim()
+    };
+    let mut spans = vec![kind, " ".into(), event.reason.clone().bold()];
+    if event.count > 1 {
+        spans.push(format!(" ×{}", event.count).dim());
+    }
+    spans.push(format!(" {}", event.message).into());
+    Line::from(spans)
+}
+
+/// 和 `kubectl get pods` 一样，用最大的一个单位显示存在时间。
+fn format_age(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    match seconds {
+        0..=59 => format!("{seconds}s"),
+        60..=3599 => format!("{}m", seconds / 60),
+        3600..=86_399 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86_400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    use super::*;
+
+    fn pod(name: &str, phase: &str) -> PodInfo {
+        PodInfo {
+            name: name.into(),
+            phase: phase.into(),
+            ready: (1, 1),
+            restarts: 0,
+            created: Some(1000),
+        }
+    }
+
+    fn app() -> (App, tokio::sync::mpsc::UnboundedReceiver<Command>) {
+        let (commands, sent) = unbounded_channel();
+        let (_updates, receiver) = mpsc::channel();
+        let mut app = App::new("default".into(), commands, receiver);
+        app.now = 1000 + 3 * 3600;
+        (app, sent)
+    }
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    #[test]
+    fn ages() {
+        assert_eq!(format_age(-5), "0s");
+        assert_eq!(format_age(59), "59s");
+        assert_eq!(format_age(120), "2m");
+        assert_eq!(format_age(7200), "2h");
+        assert_eq!(format_age(3 * 86_400), "3d");
+    }
+
+    #[test]
+    fn follow_and_switch_namespace() {
+        let (mut app, mut sent) = app();
+        app.update(Update::Namespaces(vec![
+            "default".into(),
+            "kube-system".into(),
+        ]));
+        app.update(Update::Pods {
+            namespace: "default".into(),
+            pods: vec![pod("api", "Running"), pod("job", "Pending")],
+        });
+        assert_eq!(
+            sent.try_recv(),
+            Ok(Command::Follow {
+                namespace: "default".into(),
+                pod: "api".into()
+            })
+        );
+        app.update(Update::Log {
+            pod: "api".into(),
+            line: "listening on :80".into(),
+        });
+        assert_eq!(app.logs, ["listening on :80"]);
+        // 等待调度的 Pod 没有日志可以跟随。
+        app.handle_key(KeyCode::Down);
+        assert!(sent.try_recv().is_err());
+
+        app.handle_key(KeyCode::Right);
+        assert_eq!(
+            sent.try_recv(),
+            Ok(Command::Namespace("kube-system".into()))
+        );
+        assert!(app.pods.is_empty() && app.logs.is_empty());
+        // 切换之前的命名空间的结果被忽略。
+        app.update(Update::Pods {
+            namespace: "default".into(),
+            pods: vec![pod("api", "Running")],
+        });
+        assert!(app.pods.is_empty());
+        app.handle_key(KeyCode::Right);
+        assert_eq!(app.namespace, "default");
+    }
+
+    #[test]
+    fn render_states() {
+        let (mut app, _sent) = app();
+        assert!(rows(&mut app, 80, 8)[7].starts_with("Connecting to the cluster…"));
+        app.update(Update::Error("connection refused".into()));
+        assert!(
+            rows(&mut app, 80, 8)[7].starts_with("Cannot reach the cluster: connection refused")
+        );
+
+        app.update(Update::Namespaces(vec!["default".into()]));
+        app.update(Update::Pods {
+            namespace: "default".into(),
+            pods: vec![pod("api", "Running")],
+        });
+        app.update(Update::Events {
+            pod: "api".into(),
+            events: vec![PodEvent {
+                kind: "Warning".into(),
+                reason: "BackOff".into(),
+                message: "restarting".into(),
+                count: 3,
+            }],
+        });
+        let rows = rows(&mut app, 80, 11);
+        assert_eq!(
+            rows[0],
+            "┌ ◀ default (1/1) ▶ ───────────────────┐┌ Logs: api ───────────────────────────┐"
+        );
+        assert_eq!(
+            rows[2],
+            "│api           1/1   Running   0   3h  ││                                      │"
+        );
+        assert!(rows[8].ends_with("││Warning BackOff ×3 restarting         │"));
+        assert!(rows[10].starts_with("1 pods in default"));
+    }
+}
@@ -0,0 +1,175 @@
+//! 生命游戏的世界：只记录活细胞的坐标，所以网格实际上是无限大的。
+//!
+//! 每一代统计每个活细胞周围的格子被多少个活细胞包围，再按规则决定哪些格子在下一代是活的。
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
+
+/// 细胞的坐标，`y` 向下增大。
+pub type Cell = (i64, i64);
+
+/// `B3/S23` 形式的规则：周围有几个活细胞时诞生，有几个时存活。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    pub birth: [bool; 9],
+    pub survival: [bool; 9],
+}
+
+impl Default for Rule {
+    /// 康威的规则 `B3/S23`。
+    fn default() -> Self {
+        "B3/S23".parse().unwrap()
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid rule {s:?}, expected something like B3/S23");
+        let (birth, survival) = s.split_once('/').ok_or_else(invalid)?;
+        let counts = |part: &str, prefix: char| -> Result<[bool; 9], String> {
+            let digits = part
+                .strip_prefix([prefix, prefix.to_ascii_lowercase()])
+                .ok_or_else(invalid)?;
+            let mut counts = [false; 9];
+            for digit in digits.chars() {
+                let count = digit.to_digit(10).filter(|&count| count <= 8);
+                counts[count.ok_or_else(invalid)? as usize] = true;
+            }
+            Ok(counts)
+        };
+        Ok(Self {
+            birth: counts(birth, 'B')?,
+            survival: counts(survival, 'S')?,
+        })
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = |counts: &[bool; 9]| -> String {
+            (0..9)
+                .filter(|&count| counts[count])
+                .map(|count| char::from(b'0' + count as u8))
+                .collect()
+        };
+        write!(f, "B{}/S{}", digits(&self.birth), digits(&self.survival))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct World {
+    pub rule: Rule,
+    alive: HashSet<Cell>,
+    generation: u64,
+}
+
+impl World {
+    pub fn new(rule: Rule, cells: impl IntoIterator<Item = Cell>) -> Self {
+        Self {
+            rule,
+            alive: cells.into_iter().collect(),
+            generation: 0,
+        }
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn population(&self) -> usize {
+        self.alive.len()
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = Cell> + '_ {
+        self.alive.iter().copied()
+    }
+
+    /// 包含所有活细胞的最小矩形的左上角和右下角，没有活细胞时为 `None`。
+    pub fn bounds(&self) -> Option<(Cell, Cell)> {
+        let mut cells = self.cells();
+        let first = cells.next()?;
+        Some(cells.fold((first, first), |((x0, y0), (x1, y1)), (x, y)| {
+            ((x0.min(x), y0.min(y)), (x1.max(x), y1.max(y)))
+        }))
+    }
+
+    pub fn step(&mut self) {
+        let mut neighbors: HashMap<Cell, u8> = HashMap::new();
+        for &(x, y) in &self.alive {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    if (dx, dy) != (0, 0) {
+                        *neighbors.entry((x + dx, y + dy)).or_default() += 1;
+                    }
+                }
+            }
+        }
+        // 活细胞周围一个邻居都没有时不在 `neighbors` 中，只有规则允许 0 个邻居存活时才需要单独处理。
+        let lonely_survive = self.rule.survival[0];
+        let next = neighbors
+            .iter()
+            .filter(|&(cell, &count)| {
+                let counts = if self.alive.contains(cell) {
+                    &self.rule.survival
+                } else {
+                    &self.rule.birth
+                };
+                counts[usize::from(count)]
+            })
+            .map(|(&cell, _)| cell)
+            .chain(
+                self.alive
+                    .iter()
+                    .filter(|cell| lonely_survive && !neighbors.contains_key(cell))
+                    .copied(),
+            )
+            .collect();
+        self.alive = next;
+        self.generation += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(world: &World) -> Vec<Cell> {
+        let mut cells: Vec<Cell> = world.cells().collect();
+        cells.sort_by_key(|&(x, y)| (y, x));
+        cells
+    }
+
+    #[test]
+    fn parse_rules() {
+        assert_eq!(Rule::default().to_string(), "B3/S23");
+        let highlife: Rule = "b36/s23".parse().unwrap();
+        assert_eq!(highlife.to_string(), "B36/S23");
+        assert!("B3S23".parse::<Rule>().is_err());
+        assert!("B9/S23".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn blinker_and_glider() {
+        let mut blinker = World::new(Rule::default(), [(0, 1), (1, 1), (2, 1)]);
+        blinker.step();
+        assert_eq!(sorted(&blinker), [(1, 0), (1, 1), (1, 2)]);
+        blinker.step();
+        assert_eq!(sorted(&blinker), [(0, 1), (1, 1), (2, 1)]);
+
+        // 滑翔机每 4 代向右下移动一格。
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let mut world = World::new(Rule::default(), glider);
+        for _ in 0..4 {
+            world.step();
+        }
+        let moved = World::new(Rule::default(), glider.map(|(x, y)| (x + 1, y + 1)));
+        assert_eq!(sorted(&world), sorted(&moved));
+        assert_eq!(world.generation(), 4);
+        assert_eq!(world.bounds(), Some(((1, 1), (3, 3))));
+    }
+}
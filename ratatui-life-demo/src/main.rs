@@ -0,0 +1,318 @@
+//! 生命游戏演示：在盲文画布上显示细胞，每个字符可以显示 2×4 个细胞。世界是无限大的，
+//! 用方向键移动视口。
+//!
+//! 图案从 RLE 文件读取（格式见 `rle` 模块），没有给出文件时使用 `patterns` 目录中的 Gosper 滑翔机枪。
+//! 运行时按计时器推进：主循环只等待到下一代的时间，速度由每秒的代数决定，与事件多少无关。
+//!
+//! 按键：`Space` 运行/暂停，`s` 单步，`+` / `-` 调整速度，方向键或 `h` / `j` / `k` / `l` 移动视口，
+//! `c` 让图案居中，`r` 重新开始，`q` 退出。
+
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use clap::Parser;
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    symbols::Marker,
+    widgets::{
+        canvas::{Canvas, Points},
+        Block, Paragraph,
+    },
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+};
+
+use crate::{
+    life::{Cell, World},
+    rle::Pattern,
+};
+
+mod life;
+mod rle;
+
+const DEFAULT_PATTERN: &str = include_str!("../patterns/gosper-glider-gun.rle");
+
+/// 可以选择的速度，每秒的代数。
+const SPEEDS: [u32; 7] = [1, 2, 5, 10, 20, 30, 60];
+const DEFAULT_SPEED: usize = 3;
+
+/// 暂停时等待按键的最长时间。
+const IDLE: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// RLE 格式的图案文件，默认是 Gosper 滑翔机枪
+    #[arg(value_name = "FILE")]
+    pattern: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let pattern = match &cli.pattern {
+        Some(path) => {
+            let text = fs::read_to_string(path)
+                .wrap_err_with(|| format!("reading {} failed", path.display()))?;
+            rle::parse(&text)
+                .map_err(|error| eyre!("parsing {} failed: {error}", path.display()))?
+        }
+        None => rle::parse(DEFAULT_PATTERN).map_err(|error| eyre!(error))?,
+    };
+
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new(pattern, Instant::now()).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+struct App {
+    pattern: Pattern,
+    world: World,
+    /// 视口左上角的细胞。还没有绘制过时为 `None`，第一次绘制时让图案居中。
+    origin: Option<Cell>,
+    /// 最近一次绘制时视口的大小（细胞数）。
+    view: (i64, i64),
+    playing: bool,
+    speed: usize,
+    next_step: Instant,
+    exit: bool,
+}
+
+impl App {
+    fn new(pattern: Pattern, now: Instant) -> Self {
+        Self {
+            world: World::new(pattern.rule, pattern.cells.iter().copied()),
+            pattern,
+            origin: None,
+            view: (0, 0),
+            playing: false,
+            speed: DEFAULT_SPEED,
+            next_step: now,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut()))?;
+            let timeout = if self.playing {
+                self.next_step.saturating_duration_since(Instant::now())
+            } else {
+                IDLE
+            };
+            if events.poll(timeout)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key.code, Instant::now());
+                    }
+                }
+            }
+            self.tick(Instant::now());
+        }
+        Ok(())
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(1) / SPEEDS[self.speed]
+    }
+
+    /// 运行时到了下一代的时间就推进一代。
+    fn tick(&mut self, now: Instant) {
+        if self.playing && now >= self.next_step {
+            self.world.step();
+            self.next_step = now + self.interval();
+        }
+    }
+
+    fn handle_key(&mut self, code: KeyCode, now: Instant) {
+        // 每次移动视口的四分之一。
+        let (dx, dy) = ((self.view.0 / 4).max(1), (self.view.1 / 4).max(1));
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
+            KeyCode::Char(' ') => {
+                self.playing = !self.playing;
+                self.next_step = now + self.interval();
+            }
+            KeyCode::Char('s') | KeyCode::Char('.') => {
+                self.playing = false;
+                self.world.step();
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => {
+                self.speed = (self.speed + 1).min(SPEEDS.len() - 1)
+            }
+            KeyCode::Char('-') => self.speed = self.speed.saturating_sub(1),
+            KeyCode::Left | KeyCode::Char('h') => self.pan(-dx, 0),
+            KeyCode::Right | KeyCode::Char('l') => self.pan(dx, 0),
+            KeyCode::Up | KeyCode::Char('k') => self.pan(0, -dy),
+            KeyCode::Down | KeyCode::Char('j') => self.pan(0, dy),
+            KeyCode::Char('c') => self.origin = None,
+            KeyCode::Char('r') => {
+                self.world = World::new(self.pattern.rule, self.pattern.cells.iter().copied());
+                self.playing = false;
+                self.origin = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn pan(&mut self, dx: i64, dy: i64) {
+        if let Some((x, y)) = &mut self.origin {
+            *x += dx;
+            *y += dy;
+        }
+    }
+
+    /// 让活细胞所在区域的中心位于视口中央时视口的左上角。
+    fn centered(&self, (width, height): (i64, i64)) -> Cell {
+        let ((x0, y0), (x1, y1)) = self.world.bounds().unwrap_or_default();
+        ((x0 + x1) / 2 - width / 2, (y0 + y1) / 2 - height / 2)
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        let state = if self.playing {
+            format!("Running at {} gen/s", SPEEDS[self.speed]).green()
+        } else {
+            "Paused".yellow()
+        };
+        let (x, y) = self.origin.unwrap_or_default();
+        Line::from(vec![
+            state,
+            format!("  Rule {}  View at ({x}, {y})", self.world.rule).into(),
+        ])
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let name = self.pattern.name.as_deref().unwrap_or("Life");
+        let counters = format!(
+            " Generation {}  Population {} ",
+            self.world.generation(),
+            self.world.population()
+        );
+        let block = Block::bordered()
+            .title(format!(" {name} ").bold())
+            .title(Line::from(counters).right_aligned())
+            .title_bottom(
+                Line::from(" Play <Space> Step <S> Speed <+/-> Center <C> Reset <R> ").centered(),
+            );
+        let inner = block.inner(main);
+        block.render(main, buf);
+
+        // 每个盲文字符是 2 列 4 行的点。
+        let view = (i64::from(inner.width) * 2, i64::from(inner.height) * 4);
+        self.view = view;
+        let (left, top) = match self.origin {
+            Some(origin) => origin,
+            None => *self.origin.insert(self.centered(view)),
+        };
+        let coords: Vec<(f64, f64)> = self
+            .world
+            .cells()
+            .filter(|&(x, y)| {
+                (left..left + view.0).contains(&x) && (top..top + view.1).contains(&y)
+            })
+            // 画布的 y 轴向上。
+            .map(|(x, y)| ((x - left) as f64, (view.1 - 1 - (y - top)) as f64))
+            .collect();
+        Canvas::default()
+            .marker(Marker::Braille)
+            .x_bounds([0.0, (view.0 - 1) as f64])
+            .y_bounds([0.0, (view.1 - 1) as f64])
+            .paint(|ctx| {
+                ctx.draw(&Points {
+                    coords: &coords,
+                    color: Color::Green,
+                })
+            })
+            .render(inner, buf);
+
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn glider() -> Pattern {
+        rle::parse("#N Glider\nx = 3, y = 3\nbob$2bo$3o!").unwrap()
+    }
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    #[test]
+    fn braille_viewport() {
+        let mut app = App::new(glider(), Instant::now());
+        app.origin = Some((0, 0));
+        let rows = rows(&mut app, 50, 5);
+        assert_eq!(
+            rows[0],
+            format!("┌ Glider {} Generation 0  Population 5 ┐", "─".repeat(12))
+        );
+        assert!(rows[1].starts_with("│⠬⠆ "));
+        assert_eq!(app.view, (96, 8));
+
+        // 向右移动视口后图案向左移出一部分。
+        app.origin = Some((1, 0));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 50, 5));
+        app.render(buf.area, &mut buf);
+        assert_eq!(buf.get(1, 1).symbol(), "⠵");
+    }
+
+    #[test]
+    fn play_step_and_speed() {
+        let start = Instant::now();
+        let mut app = App::new(glider(), start);
+        app.handle_key(KeyCode::Char('s'), start);
+        assert_eq!(app.world.generation(), 1);
+
+        app.handle_key(KeyCode::Char('+'), start);
+        assert_eq!(app.interval(), Duration::from_millis(50));
+        app.handle_key(KeyCode::Char(' '), start);
+        app.tick(start + Duration::from_millis(20));
+        assert_eq!(app.world.generation(), 1);
+        app.tick(start + Duration::from_millis(50));
+        app.tick(start + Duration::from_millis(100));
+        assert_eq!(app.world.generation(), 3);
+        assert_eq!(
+            app.status_line().to_string(),
+            "Running at 20 gen/s  Rule B3/S23  View at (0, 0)"
+        );
+
+        app.handle_key(KeyCode::Char('r'), start);
+        assert_eq!((app.world.generation(), app.playing), (0, false));
+    }
+
+    #[test]
+    fn center_pattern() {
+        let mut app = App::new(glider(), Instant::now());
+        rows(&mut app, 22, 5);
+        // 视口 40×8 个细胞，3×3 的滑翔机位于中央。
+        assert_eq!(app.origin, Some((-19, -3)));
+        app.handle_key(KeyCode::Right, Instant::now());
+        assert_eq!(app.origin, Some((-9, -3)));
+        app.handle_key(KeyCode::Char('c'), Instant::now());
+        assert_eq!(app.origin, None);
+        assert!(rle::parse(DEFAULT_PATTERN).is_ok());
+    }
+}
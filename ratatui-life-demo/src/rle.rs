@@ -0,0 +1,210 @@
+//! 读取 RLE 格式的图案，这是 LifeWiki 和 Golly 使用的格式：
+//!
+//! ```text
+//! #N Glider
+//! x = 3, y = 3, rule = B3/S23
+//! bob$2bo$3o!
+//! ```
+//!
+//! `b` 是死细胞，`o` 是活细胞，`$` 换行，前面的数字是重复次数，`!` 结束。以 `#` 开头的是注释，
+//! 其中 `#N` 是图案的名称。头部的 `x` 和 `y` 最大为 [`MAX_SIZE`]，超出这个范围的重复次数报错。
+
+use crate::life::{Cell, Rule};
+
+/// 图案最大的宽度和高度。
+pub const MAX_SIZE: i64 = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    pub name: Option<String>,
+    pub rule: Rule,
+    /// 相对于图案左上角的坐标。
+    pub cells: Vec<Cell>,
+    pub width: i64,
+    pub height: i64,
+}
+
+pub fn parse(text: &str) -> Result<Pattern, String> {
+    let mut name = None;
+    let mut header = None;
+    let mut body = String::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if let Some(comment) = line.strip_prefix('#') {
+            if let Some(title) = comment.strip_prefix('N') {
+                name = Some(title.trim().to_string());
+            }
+        } else if header.is_none() && line.starts_with('x') {
+            header =
+                Some(parse_header(line).map_err(|error| format!("line {}: {error}", index + 1))?);
+        } else {
+            body.push_str(line);
+        }
+    }
+    let (width, height, rule) = header.ok_or("missing `x = …, y = …` header")?;
+
+    let mut cells = Vec::new();
+    let (mut x, mut y): (i64, i64) = (0, 0);
+    let mut count = String::new();
+    for c in body.chars() {
+        if c.is_ascii_digit() {
+            count.push(c);
+            continue;
+        }
+        let run = if count.is_empty() {
+            1
+        } else {
+            count.parse::<i64>().map_err(|error| error.to_string())?
+        };
+        count.clear();
+        // 重复之后的位置，不能超出头部给出的大小。
+        let wider = |x: i64| {
+            x.checked_add(run)
+                .filter(|&end| end <= width)
+                .ok_or_else(|| format!("row {} is wider than x = {width}", y + 1))
+        };
+        match c {
+            'b' | '.' => x = wider(x)?,
+            '$' => {
+                x = 0;
+                y = y
+                    .checked_add(run)
+                    .filter(|&end| end <= height)
+                    .ok_or_else(|| format!("the pattern is taller than y = {height}"))?;
+            }
+            '!' => break,
+            c if c.is_ascii_alphabetic() => {
+                if y >= height {
+                    return Err(format!("the pattern is taller than y = {height}"));
+                }
+                let end = wider(x)?;
+                // 多状态规则中的其他字母都当作活细胞。
+                cells.extend((x..end).map(|x| (x, y)));
+                x = end;
+            }
+            c if c.is_whitespace() => {}
+            c => return Err(format!("unexpected character {c:?}")),
+        }
+    }
+    Ok(Pattern {
+        name,
+        rule,
+        cells,
+        width,
+        height,
+    })
+}
+
+/// `x = 3, y = 3, rule = B3/S23`，`rule` 可以省略。
+fn parse_header(line: &str) -> Result<(i64, i64, Rule), String> {
+    let (mut width, mut height, mut rule) = (None, None, Rule::default());
+    for field in line.split(',') {
+        let (key, value) = field
+            .split_once('=')
+            .ok_or_else(|| format!("invalid header field {:?}", field.trim()))?;
+        let value = value.trim();
+        let number = || match value.parse::<i64>() {
+            Ok(size) if (0..=MAX_SIZE).contains(&size) => Ok(size),
+            _ => Err(format!("invalid size {value:?}")),
+        };
+        match key.trim() {
+            "x" => width = Some(number()?),
+            "y" => height = Some(number()?),
+            "rule" => rule = value.parse()?,
+            _ => {}
+        }
+    }
+    match (width, height) {
+        (Some(width), Some(height)) => Ok((width, height, rule)),
+        _ => Err("the header needs both x and y".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_glider() {
+        let pattern =
+            parse("#N Glider\n#C A comment\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!\n").unwrap();
+        assert_eq!(pattern.name.as_deref(), Some("Glider"));
+        assert_eq!((pattern.width, pattern.height), (3, 3));
+        assert_eq!(pattern.cells, [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn runs_span_lines() {
+        // 空行用 `$` 的重复次数表示，图案数据可以分成多行。
+        let pattern = parse("x = 12, y = 3, rule = b36/s23\n2o10b\n2$\n12o!").unwrap();
+        assert_eq!(pattern.rule.to_string(), "B36/S23");
+        assert_eq!(pattern.cells.len(), 14);
+        assert_eq!(pattern.cells[2], (0, 2));
+        assert_eq!(pattern.cells[13], (11, 2));
+
+        assert_eq!(parse("bo!").unwrap_err(), "missing `x = …, y = …` header");
+        assert_eq!(
+            parse("x = 1, y = a\no!").unwrap_err(),
+            "line 1: invalid size \"a\""
+        );
+        assert_eq!(
+            parse("x = 1, y = 1\no?!").unwrap_err(),
+            "unexpected character '?'"
+        );
+    }
+
+    #[test]
+    fn runs_stay_inside_the_header() {
+        assert_eq!(
+            parse(
+                "x = 3, y = 3
+999999999999o!"
+            )
+            .unwrap_err(),
+            "row 1 is wider than x = 3"
+        );
+        assert_eq!(
+            parse(
+                "x = 3, y = 3
+9223372036854775807b!"
+            )
+            .unwrap_err(),
+            "row 1 is wider than x = 3"
+        );
+        assert_eq!(
+            parse(
+                "x = 3, y = 3
+o3$o!"
+            )
+            .unwrap_err(),
+            "the pattern is taller than y = 3"
+        );
+        assert_eq!(
+            parse(
+                "x = 3, y = 3
+o9223372036854775807$!"
+            )
+            .unwrap_err(),
+            "the pattern is taller than y = 3"
+        );
+        assert_eq!(
+            parse(
+                "x = 100000, y = 1
+o!"
+            )
+            .unwrap_err(),
+            "line 1: invalid size \"100000\""
+        );
+        // 结尾的换行可以正好到达底部。
+        assert_eq!(
+            parse(
+                "x = 3, y = 2
+3o2$!"
+            )
+            .unwrap()
+            .cells
+            .len(),
+            3
+        );
+    }
+}
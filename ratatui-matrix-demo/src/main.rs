@@ -0,0 +1,742 @@
+//! Matrix 客户端演示：登录界面，登录后左侧是加入的房间，右侧是房间的消息和输入行。
+//! 与服务器的交互在后台线程中进行，见 `matrix` 模块。
+//!
+//! 打开房间时加载最近的一页消息，在顶部继续按 `PageUp` 加载更早的消息。
+//! 新消息（包括自己发送的）通过同步收到，按事件 ID 去重。
+//!
+//! 按键：登录界面用 `Tab` 切换输入框、`Enter` 登录；聊天界面用 `Tab` 在房间列表和输入行之间切换，
+//! `Enter` 发送，`Up` / `Down` 选择房间或浏览输入历史，`PageUp` / `PageDown` 滚动消息，
+//! `Esc` 退出。
+
+use std::{
+    collections::HashSet,
+    sync::mpsc::{self, Receiver},
+    time::Duration,
+};
+
+use chrono::{DateTime, Local};
+use clap::Parser;
+use color_eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, List, ListState, Paragraph},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    layout::centered,
+    terminal,
+    text_input::{History, Input},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::matrix::{Command, Message, RoomInfo, Update};
+
+mod matrix;
+
+/// 等待按键的最长时间，之后处理后台送回的结果。
+const REFRESH: Duration = Duration::from_millis(100);
+
+/// `PageUp` / `PageDown` 滚动的行数。
+const PAGE_SCROLL: usize = 10;
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 服务器名或主服务器的 URL
+    #[arg(long, default_value = "matrix.org")]
+    homeserver: String,
+    /// 用户名，例如 `alice` 或 `@alice:matrix.org`
+    #[arg(long)]
+    user: Option<String>,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let (sender, updates) = mpsc::channel();
+    let commands = matrix::spawn(sender);
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new(&cli.homeserver, cli.user.as_deref(), commands, updates)
+        .run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Screen {
+    Login,
+    Chat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Rooms,
+    Input,
+}
+
+impl Focus {
+    fn border(self, focus: Focus) -> Style {
+        if self == focus {
+            Style::new().green()
+        } else {
+            Style::new()
+        }
+    }
+}
+
+/// 登录表单的输入框。
+const HOMESERVER: usize = 0;
+const USER: usize = 1;
+const PASSWORD: usize = 2;
+const LABELS: [&str; 3] = ["Homeserver: ", "User:       ", "Password:   "];
+
+#[derive(Debug, Default)]
+struct Room {
+    info: RoomInfo,
+    /// 按时间顺序。
+    messages: Vec<Message>,
+    ids: HashSet<String>,
+    /// 已经开始加载历史消息。只有同步收到的新消息时仍然需要加载。
+    requested: bool,
+    /// 正在加载更早的消息。
+    loading: bool,
+    /// 已经加载到房间的开头。
+    complete: bool,
+    /// 从底部向上滚动的行数。
+    scroll: usize,
+    unread: bool,
+}
+
+impl Room {
+    fn new(info: RoomInfo) -> Self {
+        Self {
+            info,
+            ..Self::default()
+        }
+    }
+
+    /// 更早的消息加在前面，已经有的（同步先收到的）跳过。
+    fn prepend(&mut self, messages: Vec<Message>) {
+        let older: Vec<Message> = messages
+            .into_iter()
+            .filter(|message| self.ids.insert(message.id.clone()))
+            .collect();
+        self.messages.splice(0..0, older);
+    }
+
+    /// 加入新消息，返回是否是没有见过的消息。
+    fn push(&mut self, message: Message) -> bool {
+        if !self.ids.insert(message.id.clone()) {
+            return false;
+        }
+        self.messages.push(message);
+        // 向上滚动时新消息不改变看到的位置。
+        if self.scroll > 0 {
+            self.scroll += 1;
+        }
+        true
+    }
+}
+
+struct App {
+    commands: UnboundedSender<Command>,
+    updates: Receiver<Update>,
+    screen: Screen,
+    fields: [Input; 3],
+    field: usize,
+    logging_in: bool,
+    user: String,
+    rooms: Vec<Room>,
+    list: ListState,
+    focus: Focus,
+    input: Input,
+    history: History,
+    /// 上次绘制时消息区域的高度，用来判断是否已经滚动到顶部。
+    height: usize,
+    message: Option<Result<String, String>>,
+    exit: bool,
+}
+
+impl App {
+    fn new(
+        homeserver: &str,
+        user: Option<&str>,
+        commands: UnboundedSender<Command>,
+        updates: Receiver<Update>,
+    ) -> Self {
+        let user = user.unwrap_or_default();
+        Self {
+            commands,
+            updates,
+            screen: Screen::Login,
+            fields: [
+                Input::with_value(homeserver),
+                Input::with_value(user),
+                Input::default(),
+            ],
+            field: if user.is_empty() { USER } else { PASSWORD },
+            logging_in: false,
+            user: String::new(),
+            rooms: Vec::new(),
+            list: ListState::default(),
+            focus: Focus::Input,
+            input: Input::default(),
+            history: History::default(),
+            height: 0,
+            message: None,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            while let Ok(update) = self.updates.try_recv() {
+                self.update(update);
+            }
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut()))?;
+            if events.poll(REFRESH)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, update: Update) {
+        match update {
+            Update::LoggedIn(user) => {
+                self.logging_in = false;
+                self.screen = Screen::Chat;
+                self.fields[PASSWORD] = Input::default();
+                self.message = Some(Ok(format!("Logged in as {user}, syncing…")));
+                self.user = user;
+            }
+            Update::LoginFailed(error) => {
+                self.logging_in = false;
+                self.message = Some(Err(error));
+            }
+            Update::Rooms(rooms) => {
+                self.rooms = rooms.into_iter().map(Room::new).collect();
+                self.list.select((!self.rooms.is_empty()).then_some(0));
+                self.message = Some(Ok(format!("Logged in as {}", self.user)));
+                self.open();
+            }
+            Update::Older {
+                room,
+                messages,
+                complete,
+            } => {
+                if let Some(room) = self.rooms.iter_mut().find(|r| r.info.id == room) {
+                    room.loading = false;
+                    room.complete = complete;
+                    room.prepend(messages);
+                }
+            }
+            Update::Live { room, message } => {
+                let index = match self.rooms.iter().position(|r| r.info.id == room) {
+                    Some(index) => index,
+                    // 登录后新加入的房间。
+                    None => {
+                        let name = room.clone();
+                        self.rooms.push(Room::new(RoomInfo { id: room, name }));
+                        self.rooms.len() - 1
+                    }
+                };
+                let current = self.list.selected() == Some(index);
+                let room = &mut self.rooms[index];
+                if room.push(message) && !current {
+                    room.unread = true;
+                }
+            }
+            Update::Sent(_) => self.message = None,
+            Update::Error(error) => {
+                if let Some(room) = self.rooms.iter_mut().find(|room| room.loading) {
+                    room.loading = false;
+                }
+                self.message = Some(Err(error));
+            }
+        }
+    }
+
+    fn current(&mut self) -> Option<&mut Room> {
+        self.list.selected().and_then(|i| self.rooms.get_mut(i))
+    }
+
+    /// 选中房间后，还没有加载过消息时加载最近的一页。
+    fn open(&mut self) {
+        let Some(room) = self.current() else {
+            return;
+        };
+        room.unread = false;
+        if !room.requested {
+            self.paginate();
+        }
+    }
+
+    fn paginate(&mut self) {
+        let Some(room) = self.current() else {
+            return;
+        };
+        if room.loading || room.complete {
+            return;
+        }
+        room.requested = true;
+        room.loading = true;
+        let id = room.info.id.clone();
+        let _ = self.commands.send(Command::Paginate(id));
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if key.code == KeyCode::Esc
+            || key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            self.exit = true;
+            return;
+        }
+        match self.screen {
+            Screen::Login => self.handle_login_key(key),
+            Screen::Chat => self.handle_chat_key(key),
+        }
+    }
+
+    fn handle_login_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Tab | KeyCode::Down => self.field = (self.field + 1) % self.fields.len(),
+            KeyCode::BackTab | KeyCode::Up => {
+                self.field = (self.field + self.fields.len() - 1) % self.fields.len();
+            }
+            KeyCode::Enter if !self.logging_in => {
+                if let Some(empty) = self.fields.iter().position(|f| f.value().is_empty()) {
+                    self.field = empty;
+                    self.message = Some(Err(format!(
+                        "{} is required",
+                        LABELS[empty].trim_end().trim_end_matches(':')
+                    )));
+                    return;
+                }
+                self.logging_in = true;
+                self.message = Some(Ok("Logging in…".into()));
+                let _ = self.commands.send(Command::Login {
+                    homeserver: self.fields[HOMESERVER].value().trim().to_string(),
+                    user: self.fields[USER].value().trim().to_string(),
+                    password: self.fields[PASSWORD].value().to_string(),
+                });
+            }
+            _ => {
+                self.fields[self.field].handle_key_event(key);
+            }
+        }
+    }
+
+    fn handle_chat_key(&mut self, key: KeyEvent) {
+        match (self.focus, key.code) {
+            (_, KeyCode::Tab) => {
+                self.focus = match self.focus {
+                    Focus::Rooms => Focus::Input,
+                    Focus::Input => Focus::Rooms,
+                };
+            }
+            (_, KeyCode::PageUp) => self.scroll_up(),
+            (_, KeyCode::PageDown) => {
+                if let Some(room) = self.current() {
+                    room.scroll = room.scroll.saturating_sub(PAGE_SCROLL);
+                }
+            }
+            (Focus::Rooms, code @ (KeyCode::Up | KeyCode::Down)) => {
+                select(&mut self.list, self.rooms.len(), code);
+                self.open();
+            }
+            (Focus::Rooms, KeyCode::Enter) => self.focus = Focus::Input,
+            (Focus::Input, KeyCode::Enter) => self.submit(),
+            (Focus::Input, KeyCode::Up) => {
+                if let Some(entry) = self.history.older(self.input.value()) {
+                    self.input = Input::with_value(entry);
+                }
+            }
+            (Focus::Input, KeyCode::Down) => {
+                if let Some(entry) = self.history.newer() {
+                    self.input = Input::with_value(entry);
+                }
+            }
+            (Focus::Input, _) => {
+                self.input.handle_key_event(key);
+            }
+            _ => {}
+        }
+    }
+
+    /// 向上滚动，到达顶部时加载更早的消息。
+    fn scroll_up(&mut self) {
+        let height = self.height;
+        let Some(room) = self.current() else {
+            return;
+        };
+        // 顶部有一行提示。
+        let top = (room.messages.len() + 1).saturating_sub(height);
+        room.scroll = (room.scroll + PAGE_SCROLL).min(top);
+        if room.scroll == top {
+            self.paginate();
+        }
+    }
+
+    fn submit(&mut self) {
+        let text = self.input.value().trim().to_string();
+        if text.is_empty() {
+            return;
+        }
+        let Some(room) = self.current() else {
+            self.message = Some(Err("No room selected".into()));
+            return;
+        };
+        room.scroll = 0;
+        let id = room.info.id.clone();
+        self.history.push(&text);
+        self.input = Input::default();
+        self.message = Some(Ok("Sending…".into()));
+        let _ = self.commands.send(Command::Send { room: id, text });
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        match self.screen {
+            Screen::Login => self.render_login(main, buf),
+            Screen::Chat => self.render_chat(main, buf),
+        }
+        let line = match &self.message {
+            Some(Ok(text)) => Line::from(text.clone()),
+            Some(Err(text)) => Line::from(text.clone().red()),
+            None if self.screen == Screen::Chat => {
+                Line::from(format!("Logged in as {}", self.user))
+            }
+            None => Line::default(),
+        };
+        Paragraph::new(line).render(status, buf);
+    }
+
+    fn render_login(&self, area: Rect, buf: &mut Buffer) {
+        let area = centered(area, 56, 7);
+        let block = Block::bordered()
+            .title(" Matrix login ".bold())
+            .title_bottom(Line::from(" Next <Tab> Log in <Enter> Quit <Esc> ").right_aligned());
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let rows = Layout::vertical([Constraint::Length(1); 3])
+            .margin(1)
+            .split(inner.inner(&Margin::new(1, 0)));
+        for (index, input) in self.fields.iter().enumerate() {
+            let style = if index == self.field {
+                Style::new().green().bold()
+            } else {
+                Style::new()
+            };
+            let line = if index == PASSWORD {
+                input.masked_line(LABELS[index], style, '*')
+            } else {
+                input.line(LABELS[index], style)
+            };
+            Paragraph::new(line).render(rows[index], buf);
+        }
+    }
+
+    fn render_chat(&mut self, area: Rect, buf: &mut Buffer) {
+        let [rooms_area, right] =
+            Layout::horizontal([Constraint::Length(24), Constraint::Fill(1)]).areas(area);
+        let [timeline_area, input_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(3)]).areas(right);
+
+        let items: Vec<Line> = self
+            .rooms
+            .iter()
+            .map(|room| {
+                if room.unread {
+                    Line::from(format!("• {}", room.info.name).bold().yellow())
+                } else {
+                    Line::from(format!("  {}", room.info.name))
+                }
+            })
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::bordered()
+                    .title(" Rooms ")
+                    .border_style(Focus::Rooms.border(self.focus)),
+            )
+            .highlight_style(Style::new().reversed());
+        StatefulWidget::render(list, rooms_area, buf, &mut self.list);
+
+        self.height = timeline_area.height.saturating_sub(2) as usize;
+        let selected = self.list.selected().and_then(|i| self.rooms.get(i));
+        let mut block = Block::bordered();
+        let mut lines = Vec::new();
+        if let Some(room) = selected {
+            block = block.title(format!(" {} ", room.info.name).bold());
+            if room.scroll > 0 {
+                block = block.title_bottom(
+                    Line::from(format!(" ↑ {} more below ", room.scroll)).right_aligned(),
+                );
+            }
+            let hint = if room.loading {
+                "Loading older messages…"
+            } else if room.complete {
+                "Beginning of room"
+            } else {
+                "Older messages <PageUp>"
+            };
+            lines.push(Line::from(format!("-- {hint} --").dim()));
+            lines.extend(room.messages.iter().map(message_line));
+            let end = lines.len() - room.scroll.min(lines.len());
+            lines = lines.drain(end.saturating_sub(self.height)..end).collect();
+        }
+        Paragraph::new(lines)
+            .block(block)
+            .render(timeline_area, buf);
+
+        let prompt = format!("[{}] ", localpart(&self.user));
+        Paragraph::new(self.input.line(&prompt, Style::new().bold()))
+            .block(Block::bordered().border_style(Focus::Input.border(self.focus)))
+            .render(input_area, buf);
+    }
+}
+
+/// 用户 ID `@alice:example.org` 中的 `alice`。
+fn localpart(user: &str) -> &str {
+    let user = user.strip_prefix('@').unwrap_or(user);
+    user.split(':').next().unwrap_or(user)
+}
+
+fn message_line(message: &Message) -> Line<'static> {
+    let time = DateTime::from_timestamp_millis(message.timestamp)
+        .map(|time| time.with_timezone(&Local).format("%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+    let sender = localpart(&message.sender);
+    let body = if message.encrypted {
+        message.body.clone().italic().dim()
+    } else {
+        message.body.clone().into()
+    };
+    Line::from(vec![
+        format!("{time} ").dim(),
+        format!("<{sender}> ").cyan(),
+        body,
+    ])
+}
+
+/// 在有 `len` 项的列表中上下移动选择。
+fn select(state: &mut ListState, len: usize, code: KeyCode) {
+    if len == 0 {
+        return;
+    }
+    let selected = state.selected().unwrap_or(0);
+    let next = match code {
+        KeyCode::Up => selected.saturating_sub(1),
+        _ => (selected + 1).min(len - 1),
+    };
+    state.select(Some(next));
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+    use super::*;
+
+    fn app() -> (App, UnboundedReceiver<Command>) {
+        let (commands, sent) = unbounded_channel();
+        let (_updates, receiver) = mpsc::channel();
+        let app = App::new("example.org", Some("alice"), commands, receiver);
+        (app, sent)
+    }
+
+    fn message(id: &str, body: &str) -> Message {
+        Message {
+            id: id.into(),
+            sender: "@bob:example.org".into(),
+            body: body.into(),
+            timestamp: 1704182400000,
+            encrypted: false,
+        }
+    }
+
+    fn rooms() -> Vec<RoomInfo> {
+        ["Rust", "Ratatui"]
+            .map(|name| RoomInfo {
+                id: format!("!{}:example.org", name.to_lowercase()),
+                name: name.into(),
+            })
+            .to_vec()
+    }
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    fn bodies(room: &Room) -> Vec<&str> {
+        room.messages.iter().map(|m| m.body.as_str()).collect()
+    }
+
+    #[test]
+    fn login() {
+        let (mut app, mut sent) = app();
+        assert_eq!(app.field, PASSWORD);
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(app.message, Some(Err("Password is required".into())));
+
+        testing::type_text("secret", |key| app.handle_key(key));
+        let rows = rows(&mut app, 60, 9);
+        assert_eq!(
+            rows[0],
+            "  ┌ Matrix login ────────────────────────────────────────┐  "
+        );
+        assert_eq!(
+            rows[4],
+            "  │  Password:   ******                                  │  "
+        );
+
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(
+            sent.try_recv().unwrap(),
+            Command::Login {
+                homeserver: "example.org".into(),
+                user: "alice".into(),
+                password: "secret".into(),
+            }
+        );
+        // 登录进行中时不重复发送。
+        app.handle_key(KeyCode::Enter.into());
+        assert!(sent.try_recv().is_err());
+
+        app.update(Update::LoginFailed("Invalid password".into()));
+        assert_eq!(app.screen, Screen::Login);
+        assert_eq!(app.message, Some(Err("Invalid password".into())));
+
+        app.handle_key(KeyCode::Enter.into());
+        sent.try_recv().unwrap();
+        app.update(Update::LoggedIn("@alice:example.org".into()));
+        assert_eq!(app.screen, Screen::Chat);
+        assert_eq!(app.fields[PASSWORD].value(), "");
+    }
+
+    #[test]
+    fn rooms_and_pagination() {
+        let (mut app, mut sent) = app();
+        app.update(Update::LoggedIn("@alice:example.org".into()));
+        app.update(Update::Rooms(rooms()));
+        assert_eq!(
+            sent.try_recv().unwrap(),
+            Command::Paginate("!rust:example.org".into())
+        );
+
+        // 分页结果回来之前同步先收到了最新的消息。
+        let live = Update::Live {
+            room: "!rust:example.org".into(),
+            message: message("$3", "three"),
+        };
+        app.update(live.clone());
+        app.update(Update::Older {
+            room: "!rust:example.org".into(),
+            messages: vec![message("$2", "two"), message("$3", "three")],
+            complete: false,
+        });
+        app.update(live);
+        assert_eq!(bodies(&app.rooms[0]), ["two", "three"]);
+
+        // 内容不满一屏时 `PageUp` 直接加载更早的消息。
+        rows(&mut app, 60, 12);
+        app.handle_key(KeyCode::PageUp.into());
+        assert_eq!(
+            sent.try_recv().unwrap(),
+            Command::Paginate("!rust:example.org".into())
+        );
+        app.handle_key(KeyCode::PageUp.into());
+        assert!(sent.try_recv().is_err());
+        app.update(Update::Older {
+            room: "!rust:example.org".into(),
+            messages: vec![message("$1", "one")],
+            complete: true,
+        });
+        assert_eq!(bodies(&app.rooms[0]), ["one", "two", "three"]);
+        app.handle_key(KeyCode::PageUp.into());
+        assert!(sent.try_recv().is_err());
+
+        // 其他房间的新消息标记为未读，打开时加载。
+        app.update(Update::Live {
+            room: "!ratatui:example.org".into(),
+            message: message("$9", "hi"),
+        });
+        assert!(app.rooms[1].unread);
+        app.handle_key(KeyCode::Tab.into());
+        app.handle_key(KeyCode::Down.into());
+        assert!(!app.rooms[1].unread);
+        assert_eq!(
+            sent.try_recv().unwrap(),
+            Command::Paginate("!ratatui:example.org".into())
+        );
+    }
+
+    #[test]
+    fn send_and_history() {
+        let (mut app, mut sent) = app();
+        app.update(Update::LoggedIn("@alice:example.org".into()));
+        app.update(Update::Rooms(rooms()));
+        sent.try_recv().unwrap();
+
+        testing::type_text("hello", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(
+            sent.try_recv().unwrap(),
+            Command::Send {
+                room: "!rust:example.org".into(),
+                text: "hello".into(),
+            }
+        );
+        assert_eq!(app.input.value(), "");
+        assert_eq!(app.message, Some(Ok("Sending…".into())));
+        app.update(Update::Sent("!rust:example.org".into()));
+        assert_eq!(app.message, None);
+
+        app.handle_key(KeyCode::Up.into());
+        assert_eq!(app.input.value(), "hello");
+
+        app.update(Update::Error("Sending failed: forbidden".into()));
+        assert_eq!(app.message, Some(Err("Sending failed: forbidden".into())));
+    }
+
+    #[test]
+    fn render_chat() {
+        let (mut app, _sent) = app();
+        app.update(Update::LoggedIn("@alice:example.org".into()));
+        app.update(Update::Rooms(rooms()));
+        app.update(Update::Older {
+            room: "!rust:example.org".into(),
+            messages: vec![message("$1", "hello")],
+            complete: true,
+        });
+        let rows = rows(&mut app, 64, 9);
+        assert_eq!(
+            rows[0],
+            "┌ Rooms ───────────────┐┌ Rust ────────────────────────────────┐"
+        );
+        assert_eq!(
+            rows[1],
+            "│  Rust                ││-- Beginning of room --               │"
+        );
+        // 时间随时区变化，只比较其他部分。
+        assert!(rows[2].starts_with("│  Ratatui             ││"));
+        assert!(rows[2].ends_with(" <bob> hello               │"));
+        assert_eq!(
+            rows[6],
+            "│                      ││[alice]                               │"
+        );
+        assert_eq!(rows[8].trim_end(), "Logged in as @alice:example.org");
+    }
+}
@@ -0,0 +1,328 @@
+//! 用 `matrix-sdk` 登录、同步和收发消息，在后台线程的 tokio 运行时中运行。
+//!
+//! 登录后先做一次同步得到房间列表，然后在后台持续同步，新消息通过事件处理器送回界面。
+//! 历史消息按需向前分页加载，每个房间的分页位置记录在这里。
+//!
+//! 没有启用端到端加密（`e2e-encryption` 功能），加密房间中的消息显示为占位文字。
+
+use std::{collections::HashMap, sync::mpsc, thread};
+
+use matrix_sdk::{
+    config::SyncSettings,
+    room::MessagesOptions,
+    ruma::{
+        events::{
+            room::message::{OriginalSyncRoomMessageEvent, RoomMessageEventContent},
+            AnySyncMessageLikeEvent, AnySyncTimelineEvent, SyncMessageLikeEvent,
+        },
+        serde::Raw,
+        OwnedRoomId, RoomId, UInt,
+    },
+    Client, Room,
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+/// 每次分页加载的事件数。
+const PAGE: u32 = 30;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Login {
+        homeserver: String,
+        user: String,
+        password: String,
+    },
+    /// 加载房间中更早的一页消息。
+    Paginate(String),
+    Send {
+        room: String,
+        text: String,
+    },
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RoomInfo {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub id: String,
+    pub sender: String,
+    pub body: String,
+    /// 服务器收到消息的时间，Unix 毫秒时间戳。
+    pub timestamp: i64,
+    /// 无法解密的消息，`body` 是占位文字。
+    pub encrypted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Update {
+    LoggedIn(String),
+    /// 登录失败，可以修改后重试。
+    LoginFailed(String),
+    Rooms(Vec<RoomInfo>),
+    /// 分页加载的更早的消息，按时间顺序。`complete` 表示已经到达房间的开头。
+    Older {
+        room: String,
+        messages: Vec<Message>,
+        complete: bool,
+    },
+    /// 同步收到的新消息，包括自己发送的消息。
+    Live {
+        room: String,
+        message: Message,
+    },
+    Sent(String),
+    Error(String),
+}
+
+/// 启动后台线程，返回发送命令的一端。发送端被丢弃后注销并结束线程。
+pub fn spawn(updates: mpsc::Sender<Update>) -> UnboundedSender<Command> {
+    let (commands, receiver) = unbounded_channel();
+    thread::spawn(move || {
+        let runtime = match tokio::runtime::Runtime::new() {
+            Ok(runtime) => runtime,
+            Err(error) => {
+                let _ = updates.send(Update::LoginFailed(error.to_string()));
+                return;
+            }
+        };
+        runtime.block_on(serve(receiver, updates));
+    });
+    commands
+}
+
+async fn serve(mut commands: UnboundedReceiver<Command>, updates: mpsc::Sender<Update>) {
+    // 登录成功之前只处理登录命令。
+    let client = loop {
+        let Some(command) = commands.recv().await else {
+            return;
+        };
+        let Command::Login {
+            homeserver,
+            user,
+            password,
+        } = command
+        else {
+            continue;
+        };
+        match login(&homeserver, &user, &password).await {
+            Ok(client) => break client,
+            Err(error) => {
+                let _ = updates.send(Update::LoginFailed(error));
+            }
+        }
+    };
+    let user = client
+        .user_id()
+        .map_or_else(String::new, |id| id.to_string());
+    let _ = updates.send(Update::LoggedIn(user));
+
+    let token = match client.sync_once(SyncSettings::default()).await {
+        Ok(response) => response.next_batch,
+        Err(error) => {
+            let _ = updates.send(Update::Error(format!("Sync failed: {error}")));
+            return;
+        }
+    };
+    let _ = updates.send(Update::Rooms(rooms(&client).await));
+
+    let live = updates.clone();
+    client.add_event_handler(move |event: OriginalSyncRoomMessageEvent, room: Room| {
+        let live = live.clone();
+        async move {
+            let _ = live.send(Update::Live {
+                room: room.room_id().to_string(),
+                message: message(&event),
+            });
+        }
+    });
+    let sync = client.clone();
+    let errors = updates.clone();
+    let syncing = tokio::spawn(async move {
+        if let Err(error) = sync.sync(SyncSettings::default().token(token)).await {
+            let _ = errors.send(Update::Error(format!("Sync stopped: {error}")));
+        }
+    });
+
+    // 每个房间下一次分页的起点，`None` 表示从最新的消息开始。
+    let mut tokens: HashMap<OwnedRoomId, Option<String>> = HashMap::new();
+    while let Some(command) = commands.recv().await {
+        let result = match command {
+            Command::Login { .. } => continue,
+            Command::Paginate(room) => paginate(&client, &room, &mut tokens).await,
+            Command::Send { room, text } => send(&client, &room, &text)
+                .await
+                .map(|()| Update::Sent(room)),
+        };
+        let update = result.unwrap_or_else(Update::Error);
+        if updates.send(update).is_err() {
+            break;
+        }
+    }
+    syncing.abort();
+    let _ = client.logout().await;
+}
+
+async fn login(homeserver: &str, user: &str, password: &str) -> Result<Client, String> {
+    let client = Client::builder()
+        .server_name_or_homeserver_url(homeserver)
+        .build()
+        .await
+        .map_err(|error| format!("Cannot reach {homeserver}: {error}"))?;
+    client
+        .matrix_auth()
+        .login_username(user, password)
+        .initial_device_display_name("ratatui-matrix-demo")
+        .await
+        .map_err(|error| error.to_string())?;
+    Ok(client)
+}
+
+/// 已经加入的房间，按名称排序。
+async fn rooms(client: &Client) -> Vec<RoomInfo> {
+    let mut rooms = Vec::new();
+    for room in client.joined_rooms() {
+        let name = match room.display_name().await {
+            Ok(name) => name.to_string(),
+            Err(_) => room.room_id().to_string(),
+        };
+        rooms.push(RoomInfo {
+            id: room.room_id().to_string(),
+            name,
+        });
+    }
+    rooms.sort_by_key(|room| room.name.to_lowercase());
+    rooms
+}
+
+fn room(client: &Client, id: &str) -> Result<Room, String> {
+    let id = RoomId::parse(id).map_err(|error| error.to_string())?;
+    client
+        .get_room(&id)
+        .ok_or_else(|| format!("Unknown room {id}"))
+}
+
+async fn paginate(
+    client: &Client,
+    id: &str,
+    tokens: &mut HashMap<OwnedRoomId, Option<String>>,
+) -> Result<Update, String> {
+    let room = room(client, id)?;
+    let token = tokens.entry(room.room_id().to_owned()).or_default();
+    let mut options = MessagesOptions::backward().from(token.as_deref());
+    options.limit = UInt::from(PAGE);
+    let response = room
+        .messages(options)
+        .await
+        .map_err(|error| format!("Loading messages failed: {error}"))?;
+    *token = response.end.clone();
+    // 向前分页时最新的在前。
+    let messages = response
+        .chunk
+        .iter()
+        .rev()
+        .filter_map(|event| convert(event.raw()))
+        .collect();
+    Ok(Update::Older {
+        room: id.to_string(),
+        messages,
+        complete: response.end.is_none(),
+    })
+}
+
+async fn send(client: &Client, id: &str, text: &str) -> Result<(), String> {
+    room(client, id)?
+        .send(RoomMessageEventContent::text_plain(text))
+        .await
+        .map(|_| ())
+        .map_err(|error| format!("Sending failed: {error}"))
+}
+
+fn message(event: &OriginalSyncRoomMessageEvent) -> Message {
+    Message {
+        id: event.event_id.to_string(),
+        sender: event.sender.to_string(),
+        body: event.content.body().to_string(),
+        timestamp: event.origin_server_ts.0.into(),
+        encrypted: false,
+    }
+}
+
+/// 时间线中要显示的事件：文本消息和无法解密的消息。状态事件等忽略。
+fn convert(raw: &Raw<AnySyncTimelineEvent>) -> Option<Message> {
+    match raw.deserialize().ok()? {
+        AnySyncTimelineEvent::MessageLike(AnySyncMessageLikeEvent::RoomMessage(
+            SyncMessageLikeEvent::Original(event),
+        )) => Some(message(&event)),
+        AnySyncTimelineEvent::MessageLike(AnySyncMessageLikeEvent::RoomEncrypted(
+            SyncMessageLikeEvent::Original(event),
+        )) => Some(Message {
+            id: event.event_id.to_string(),
+            sender: event.sender.to_string(),
+            body: "(encrypted message)".into(),
+            timestamp: event.origin_server_ts.0.into(),
+            encrypted: true,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(json: &str) -> Raw<AnySyncTimelineEvent> {
+        Raw::from_json_string(json.to_string()).unwrap()
+    }
+
+    #[test]
+    fn convert_events() {
+        let text = raw(r#"{
+            "type": "m.room.message",
+            "event_id": "$1",
+            "sender": "@alice:example.org",
+            "origin_server_ts": 1704182400000,
+            "content": { "msgtype": "m.text", "body": "hello" }
+        }"#);
+        assert_eq!(
+            convert(&text),
+            Some(Message {
+                id: "$1".into(),
+                sender: "@alice:example.org".into(),
+                body: "hello".into(),
+                timestamp: 1704182400000,
+                encrypted: false,
+            })
+        );
+
+        let encrypted = raw(r#"{
+            "type": "m.room.encrypted",
+            "event_id": "$2",
+            "sender": "@bob:example.org",
+            "origin_server_ts": 1704182400000,
+            "content": {
+                "algorithm": "m.megolm.v1.aes-sha2",
+                "ciphertext": "AwgAEnACgAkLmt6qF84IK++J7UDH2Za1YVchHyprqTqsg2yyOwAtHaZTwyNg37afzg8f3r9IsN9r4RNFg7MaZencUJe4qvELiDiopUjy5wYVDAtqdBzer5bWRD9ldxp1FLgbQvBcjkkywYjCsmsq6+hArLd9oAQZnGKn/qLsK+5uNX3PaWzDRC9wZPQvWYYPCTov3jCwXKTPsLKIiTrcCXDqMvnn8m+T3zF/I2zqxg158tnUwWWIw51UO",
+                "device_id": "RJYKSTBOIE",
+                "sender_key": "IlRMeOPX2e0MurIyfWEucYBRVOEEUMrOHqn/8mLqMjA",
+                "session_id": "X3lUlvLELLYxeTx4yOVu6UDpasGEVO0Jbu+QFnm0cKQ"
+            }
+        }"#);
+        let message = convert(&encrypted).unwrap();
+        assert!(message.encrypted);
+        assert_eq!(message.sender, "@bob:example.org");
+
+        let state = raw(r#"{
+            "type": "m.room.topic",
+            "event_id": "$3",
+            "sender": "@alice:example.org",
+            "origin_server_ts": 1704182400000,
+            "state_key": "",
+            "content": { "topic": "Rust" }
+        }"#);
+        assert_eq!(convert(&state), None);
+    }
+}
@@ -0,0 +1,279 @@
+//! 扫雷的规则：地雷在第一次翻开时才布置，第一下翻开的格子和它周围的格子都不会有雷；
+//! 翻开周围没有雷的格子时自动翻开相邻的格子，直到遇到数字为止。
+
+use clap::ValueEnum;
+use rand::{seq::index, Rng};
+
+/// 难度预设，与 Windows 扫雷相同。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Difficulty {
+    #[default]
+    Beginner,
+    Intermediate,
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Self; 3] = [Self::Beginner, Self::Intermediate, Self::Expert];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Beginner => "Beginner",
+            Self::Intermediate => "Intermediate",
+            Self::Expert => "Expert",
+        }
+    }
+
+    /// 宽、高和地雷数。
+    pub fn size(self) -> (u16, u16, usize) {
+        match self {
+            Self::Beginner => (9, 9, 10),
+            Self::Intermediate => (16, 16, 40),
+            Self::Expert => (30, 16, 99),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    /// 还没有翻开任何格子，地雷还没有布置。
+    Ready,
+    Playing,
+    Won,
+    Lost,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cell {
+    pub mine: bool,
+    pub revealed: bool,
+    pub flagged: bool,
+    /// 相邻的地雷数。
+    pub adjacent: u8,
+}
+
+#[derive(Debug, Clone)]
+pub struct Board {
+    width: u16,
+    height: u16,
+    mines: usize,
+    cells: Vec<Cell>,
+    state: State,
+}
+
+impl Board {
+    pub fn new(width: u16, height: u16, mines: usize) -> Self {
+        let len = usize::from(width) * usize::from(height);
+        Self {
+            width,
+            height,
+            // 至少留出第一下翻开的 3×3 区域。
+            mines: mines.min(len.saturating_sub(9)),
+            cells: vec![Cell::default(); len],
+            state: State::Ready,
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn mines(&self) -> usize {
+        self.mines
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn cell(&self, x: u16, y: u16) -> &Cell {
+        &self.cells[self.index(x, y)]
+    }
+
+    pub fn flags(&self) -> usize {
+        self.cells.iter().filter(|cell| cell.flagged).count()
+    }
+
+    fn index(&self, x: u16, y: u16) -> usize {
+        usize::from(y) * usize::from(self.width) + usize::from(x)
+    }
+
+    fn neighbors(&self, x: u16, y: u16) -> impl Iterator<Item = (u16, u16)> {
+        let (width, height) = (self.width, self.height);
+        (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+            .filter(|&offset| offset != (0, 0))
+            .filter_map(move |(dx, dy)| {
+                let x = x.checked_add_signed(dx).filter(|&x| x < width)?;
+                let y = y.checked_add_signed(dy).filter(|&y| y < height)?;
+                Some((x, y))
+            })
+    }
+
+    /// 在 `(x, y)` 及其周围以外的格子中随机布置地雷。
+    fn place_mines(&mut self, x: u16, y: u16, rng: &mut impl Rng) {
+        let safe: Vec<usize> = self
+            .neighbors(x, y)
+            .chain([(x, y)])
+            .map(|(x, y)| self.index(x, y))
+            .collect();
+        let candidates: Vec<usize> = (0..self.cells.len())
+            .filter(|index| !safe.contains(index))
+            .collect();
+        let mines = self.mines.min(candidates.len());
+        let indices = index::sample(rng, candidates.len(), mines).into_iter();
+        self.set_mines(indices.map(|index| candidates[index]));
+    }
+
+    fn set_mines(&mut self, indices: impl IntoIterator<Item = usize>) {
+        for index in indices {
+            self.cells[index].mine = true;
+        }
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let adjacent = self
+                    .neighbors(x, y)
+                    .filter(|&(x, y)| self.cell(x, y).mine)
+                    .count();
+                let index = self.index(x, y);
+                self.cells[index].adjacent = adjacent as u8;
+            }
+        }
+        self.state = State::Playing;
+    }
+
+    /// 翻开一个格子。插了旗的格子不会被翻开。
+    pub fn reveal(&mut self, x: u16, y: u16, rng: &mut impl Rng) {
+        match self.state {
+            State::Ready => self.place_mines(x, y, rng),
+            State::Playing => {}
+            State::Won | State::Lost => return,
+        }
+        let cell = *self.cell(x, y);
+        if cell.flagged || cell.revealed {
+            return;
+        }
+        if cell.mine {
+            self.state = State::Lost;
+            for cell in &mut self.cells {
+                cell.revealed |= cell.mine;
+            }
+            return;
+        }
+        // 用栈代替递归，大棋盘上也不会栈溢出。
+        let mut pending = vec![(x, y)];
+        while let Some((x, y)) = pending.pop() {
+            let index = self.index(x, y);
+            let cell = &mut self.cells[index];
+            if cell.revealed || cell.flagged {
+                continue;
+            }
+            cell.revealed = true;
+            if cell.adjacent == 0 {
+                pending.extend(self.neighbors(x, y));
+            }
+        }
+        if self.cells.iter().all(|cell| cell.mine || cell.revealed) {
+            self.state = State::Won;
+            for cell in &mut self.cells {
+                cell.flagged = cell.mine;
+            }
+        }
+    }
+
+    /// 切换旗子。只能在没有翻开的格子上插旗。
+    pub fn toggle_flag(&mut self, x: u16, y: u16) {
+        if !matches!(self.state, State::Ready | State::Playing) {
+            return;
+        }
+        let index = self.index(x, y);
+        let cell = &mut self.cells[index];
+        if !cell.revealed {
+            cell.flagged = !cell.flagged;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    /// 按 `*` 布置地雷的棋盘。
+    fn board(rows: &[&str]) -> Board {
+        let mut board = Board::new(rows[0].len() as u16, rows.len() as u16, 0);
+        let mines = rows
+            .concat()
+            .char_indices()
+            .filter(|&(_, c)| c == '*')
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        board.mines = mines.len();
+        board.set_mines(mines);
+        board
+    }
+
+    fn revealed(board: &Board) -> Vec<String> {
+        (0..board.height())
+            .map(|y| {
+                (0..board.width())
+                    .map(|x| match board.cell(x, y) {
+                        Cell { flagged: true, .. } => 'F',
+                        Cell {
+                            revealed: false, ..
+                        } => '#',
+                        Cell { mine: true, .. } => '*',
+                        Cell { adjacent, .. } => char::from(b'0' + adjacent),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn first_reveal_is_safe() {
+        for seed in 0..20 {
+            let mut board = Board::new(9, 9, 10);
+            board.reveal(4, 4, &mut StdRng::seed_from_u64(seed));
+            assert_eq!(board.state(), State::Playing);
+            assert_eq!(board.cells.iter().filter(|cell| cell.mine).count(), 10);
+            assert_eq!(board.cell(4, 4).adjacent, 0);
+        }
+    }
+
+    #[test]
+    fn flood_fill_stops_at_numbers() {
+        let mut board = board(&["....", "....", "...*", "..*."]);
+        let rng = &mut StdRng::seed_from_u64(0);
+        board.toggle_flag(0, 3);
+        board.reveal(0, 0, rng);
+        // 插旗的格子不会被自动翻开。
+        assert_eq!(revealed(&board), ["0000", "0011", "012#", "F1##"]);
+        assert_eq!(board.state(), State::Playing);
+
+        board.toggle_flag(0, 3);
+        board.reveal(0, 3, rng);
+        board.reveal(3, 3, rng);
+        assert_eq!(board.state(), State::Won);
+        assert_eq!(board.flags(), 2);
+    }
+
+    #[test]
+    fn lose_on_mine() {
+        let mut board = board(&["*..", "...", "..."]);
+        let rng = &mut StdRng::seed_from_u64(0);
+        board.reveal(1, 0, rng);
+        board.reveal(0, 0, rng);
+        assert_eq!(board.state(), State::Lost);
+        assert_eq!(revealed(&board), ["*1#", "###", "###"]);
+        // 结束后不能再操作。
+        board.reveal(2, 2, rng);
+        board.toggle_flag(1, 1);
+        assert_eq!(revealed(&board), ["*1#", "###", "###"]);
+    }
+}
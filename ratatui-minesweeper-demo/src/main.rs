@@ -0,0 +1,333 @@
+//! 扫雷演示：可以用键盘移动光标，也可以直接用鼠标点击格子。规则见 `board` 模块。
+//!
+//! 每个格子占两列，这样棋盘看起来接近正方形。鼠标点击时根据最近一次绘制的棋盘位置计算点中了
+//! 哪个格子。计时从第一次翻开格子开始，到胜利或踩雷为止。
+//!
+//! 按键：方向键或 `h` / `j` / `k` / `l` 移动，`Space` / `Enter` 翻开，`f` 插旗，
+//! `1`–`3` 选择难度，`n` 重新开始，`q` 退出。鼠标左键翻开，右键插旗。
+
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+use color_eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
+use rand::{rngs::StdRng, SeedableRng};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Paragraph},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+};
+
+use crate::board::{Board, Difficulty, State};
+
+mod board;
+
+/// 等待事件的最长时间，之后刷新计时器。
+const REFRESH: Duration = Duration::from_millis(250);
+
+/// 棋盘边框的最小宽度。
+const MIN_WIDTH: u16 = 36;
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 难度
+    #[arg(long, value_enum, default_value_t)]
+    difficulty: Difficulty,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init_with_mouse()?;
+    let result =
+        App::new(cli.difficulty, StdRng::from_entropy()).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+/// 数字的颜色，与经典扫雷相近。
+fn number_color(adjacent: u8) -> Color {
+    match adjacent {
+        1 => Color::LightBlue,
+        2 => Color::Green,
+        3 => Color::LightRed,
+        4 => Color::Magenta,
+        5 => Color::Yellow,
+        6 => Color::Cyan,
+        7 => Color::White,
+        _ => Color::Gray,
+    }
+}
+
+struct App {
+    difficulty: Difficulty,
+    board: Board,
+    cursor: (u16, u16),
+    rng: StdRng,
+    started: Option<Instant>,
+    /// 结束时用了多长时间。
+    finished: Option<Duration>,
+    /// 最近一次绘制时格子所在的区域，用于鼠标点击。
+    cells_area: Rect,
+    exit: bool,
+}
+
+impl App {
+    fn new(difficulty: Difficulty, rng: StdRng) -> Self {
+        let (width, height, mines) = difficulty.size();
+        Self {
+            difficulty,
+            board: Board::new(width, height, mines),
+            cursor: (width / 2, height / 2),
+            rng,
+            started: None,
+            finished: None,
+            cells_area: Rect::default(),
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut(), Instant::now()))?;
+            if events.poll(REFRESH)? {
+                self.handle_event(events.read()?, Instant::now());
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: Event, now: Instant) {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_key(key.code, now),
+            Event::Mouse(mouse) => self.handle_mouse(mouse, now),
+            _ => {}
+        }
+    }
+
+    fn handle_key(&mut self, code: KeyCode, now: Instant) {
+        let (x, y) = self.cursor;
+        let (width, height) = (self.board.width(), self.board.height());
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
+            KeyCode::Left | KeyCode::Char('h') => self.cursor.0 = x.saturating_sub(1),
+            KeyCode::Right | KeyCode::Char('l') => self.cursor.0 = (x + 1).min(width - 1),
+            KeyCode::Up | KeyCode::Char('k') => self.cursor.1 = y.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.cursor.1 = (y + 1).min(height - 1),
+            KeyCode::Char(' ') | KeyCode::Enter => self.reveal(x, y, now),
+            KeyCode::Char('f') => self.board.toggle_flag(x, y),
+            KeyCode::Char('n') => self.restart(self.difficulty),
+            KeyCode::Char(c @ '1'..='3') => {
+                self.restart(Difficulty::ALL[usize::from(c as u8 - b'1')])
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent, now: Instant) {
+        let MouseEventKind::Down(button) = mouse.kind else {
+            return;
+        };
+        let Some((x, y)) = self.hit_test(mouse.column, mouse.row) else {
+            return;
+        };
+        self.cursor = (x, y);
+        match button {
+            MouseButton::Left => self.reveal(x, y, now),
+            MouseButton::Right => self.board.toggle_flag(x, y),
+            MouseButton::Middle => {}
+        }
+    }
+
+    /// 屏幕位置对应的格子。
+    fn hit_test(&self, column: u16, row: u16) -> Option<(u16, u16)> {
+        let area = self.cells_area;
+        if !(area.left()..area.right()).contains(&column)
+            || !(area.top()..area.bottom()).contains(&row)
+        {
+            return None;
+        }
+        Some(((column - area.x) / 2, row - area.y))
+    }
+
+    fn reveal(&mut self, x: u16, y: u16, now: Instant) {
+        self.board.reveal(x, y, &mut self.rng);
+        let started = *self.started.get_or_insert(now);
+        if matches!(self.board.state(), State::Won | State::Lost) && self.finished.is_none() {
+            self.finished = Some(now - started);
+        }
+    }
+
+    fn restart(&mut self, difficulty: Difficulty) {
+        let rng = self.rng.clone();
+        *self = Self {
+            cells_area: self.cells_area,
+            ..Self::new(difficulty, rng)
+        };
+    }
+
+    fn elapsed(&self, now: Instant) -> Duration {
+        match (self.finished, self.started) {
+            (Some(elapsed), _) => elapsed,
+            (None, Some(started)) => now - started,
+            (None, None) => Duration::ZERO,
+        }
+    }
+
+    fn cell_span(&self, x: u16, y: u16) -> Span<'static> {
+        let cell = self.board.cell(x, y);
+        let lost = self.board.state() == State::Lost;
+        let span = match (cell.revealed, cell.flagged) {
+            // 踩雷后标出插错的旗子。
+            (false, true) if lost && !cell.mine => " ✗".yellow(),
+            (false, true) => " ⚑".light_red(),
+            (false, false) => " ■".dark_gray(),
+            (true, _) if cell.mine => " ✱".red().bold(),
+            (true, _) if cell.adjacent == 0 => "  ".into(),
+            (true, _) => Span::styled(
+                format!(" {}", cell.adjacent),
+                Style::new().fg(number_color(cell.adjacent)).bold(),
+            ),
+        };
+        if self.cursor == (x, y) && !matches!(self.board.state(), State::Won | State::Lost) {
+            span.reversed()
+        } else {
+            span
+        }
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        match self.board.state() {
+            State::Ready | State::Playing => {
+                Line::from("Left click reveals, right click flags  Difficulty <1-3>".dim())
+            }
+            State::Won => Line::from("You win!".green().bold()),
+            State::Lost => Line::from("Boom! Press n to play again".red().bold()),
+        }
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, now: Instant) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let cells_width = self.board.width() * 2;
+        // 初级的棋盘比标题窄，边框至少要放得下标题。
+        let width = (cells_width + 3).max(MIN_WIDTH);
+        let height = self.board.height() + 2;
+        let board_area = Rect {
+            x: main.x + main.width.saturating_sub(width) / 2,
+            y: main.y + main.height.saturating_sub(height) / 2,
+            width: width.min(main.width),
+            height: height.min(main.height),
+        };
+
+        let remaining = self.board.mines() as isize - self.board.flags() as isize;
+        let counters = format!(
+            " Mines {remaining}  Time {:03} ",
+            self.elapsed(now).as_secs()
+        );
+        let block = Block::bordered()
+            .title(format!(" {} ", self.difficulty.name()).bold())
+            .title(Line::from(counters).right_aligned())
+            .title_bottom(Line::from(" New <N> Quit <Q> ").centered());
+        let inner = block.inner(board_area);
+        block.render(board_area, buf);
+        // 每个格子的符号前有一个空格，右边再留一列，让最后一个格子和边框之间也有空隙。
+        let x = inner.x + inner.width.saturating_sub(cells_width + 1) / 2;
+        self.cells_area = Rect {
+            x,
+            width: cells_width.min(inner.right().saturating_sub(x)),
+            ..inner
+        };
+
+        let lines: Vec<Line> = (0..self.board.height())
+            .map(|y| {
+                Line::from(
+                    (0..self.board.width())
+                        .map(|x| self.cell_span(x, y))
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        Paragraph::new(lines).render(self.cells_area, buf);
+
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyModifiers;
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn rows(app: &mut App, width: u16, height: u16, now: Instant) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf, now))
+    }
+
+    fn click(app: &mut App, button: MouseButton, column: u16, row: u16, now: Instant) {
+        app.handle_event(
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(button),
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            }),
+            now,
+        );
+    }
+
+    #[test]
+    fn mouse_hit_testing() {
+        let mut app = App::new(Difficulty::Beginner, StdRng::seed_from_u64(1));
+        let start = Instant::now();
+        let before = rows(&mut app, 40, 14, start);
+        assert_eq!(before[1], "  ┌ Beginner ──── Mines 10  Time 000 ┐  ");
+        assert_eq!(app.cells_area, Rect::new(10, 2, 18, 9));
+
+        // 右键插旗，点到边框上没有作用。
+        click(&mut app, MouseButton::Right, 11, 2, start);
+        click(&mut app, MouseButton::Left, 9, 2, start);
+        assert!(app.board.cell(0, 0).flagged);
+        assert_eq!(app.board.state(), State::Ready);
+
+        // 左键翻开第 4 行第 5 列的格子（格子的两列都可以点）。
+        click(&mut app, MouseButton::Left, 18, 6, start);
+        assert_eq!(app.cursor, (4, 4));
+        assert!(app.board.cell(4, 4).revealed);
+        assert_eq!(app.board.cell(4, 4).adjacent, 0);
+        assert_eq!(app.started, Some(start));
+
+        let after = rows(&mut app, 40, 14, start + Duration::from_secs(7));
+        assert!(after[1].ends_with(" Mines 9  Time 007 ┐  "));
+        assert!(after[2].starts_with("  │        ⚑ ■"));
+    }
+
+    #[test]
+    fn keyboard_and_presets() {
+        let mut app = App::new(Difficulty::Beginner, StdRng::seed_from_u64(1));
+        let now = Instant::now();
+        for code in [KeyCode::Left, KeyCode::Char('k'), KeyCode::Char('f')] {
+            app.handle_key(code, now);
+        }
+        assert_eq!(app.cursor, (3, 3));
+        assert!(app.board.cell(3, 3).flagged);
+
+        app.handle_key(KeyCode::Char('3'), now);
+        assert_eq!(app.difficulty, Difficulty::Expert);
+        assert_eq!((app.board.width(), app.board.mines()), (30, 99));
+        assert_eq!(app.board.flags(), 0);
+        for _ in 0..40 {
+            app.handle_key(KeyCode::Right, now);
+        }
+        assert_eq!(app.cursor, (29, 8));
+    }
+}
@@ -0,0 +1,231 @@
+//! 窗口中面板的排列：一棵二叉树，叶子是面板，内部节点把区域分为上下或左右两部分。
+//!
+//! 面板按从左到右、从上到下的顺序（树的先序）编号，焦点和关闭都使用这个编号。
+
+use chrono::{DateTime, Local};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Widget},
+};
+
+use crate::panes::Pane;
+
+pub enum Node {
+    Pane(Box<dyn Pane>),
+    Split {
+        /// `Vertical` 表示上下排列，`Horizontal` 表示左右排列。
+        direction: Direction,
+        /// `first` 的大小，`second` 占据剩下的部分。`Fill(1)` 表示两部分一样大。
+        size: Constraint,
+        first: Box<Node>,
+        second: Box<Node>,
+    },
+}
+
+impl Node {
+    /// 面板的数量。
+    pub fn count(&self) -> usize {
+        match self {
+            Node::Pane(_) => 1,
+            Node::Split { first, second, .. } => first.count() + second.count(),
+        }
+    }
+
+    pub fn pane(&self, index: usize) -> &dyn Pane {
+        match self {
+            Node::Pane(pane) => pane.as_ref(),
+            Node::Split { first, second, .. } => {
+                let n = first.count();
+                if index < n {
+                    first.pane(index)
+                } else {
+                    second.pane(index - n)
+                }
+            }
+        }
+    }
+
+    pub fn pane_mut(&mut self, index: usize) -> &mut dyn Pane {
+        match self {
+            Node::Pane(pane) => pane.as_mut(),
+            Node::Split { first, second, .. } => {
+                let n = first.count();
+                if index < n {
+                    first.pane_mut(index)
+                } else {
+                    second.pane_mut(index - n)
+                }
+            }
+        }
+    }
+
+    /// 把第 `index` 个面板分成两半，新面板放在后一半，编号为 `index + 1`。
+    pub fn split(self, index: usize, direction: Direction, pane: Box<dyn Pane>) -> Node {
+        match self {
+            Node::Pane(_) => Node::Split {
+                direction,
+                size: Constraint::Fill(1),
+                first: Box::new(self),
+                second: Box::new(Node::Pane(pane)),
+            },
+            Node::Split {
+                direction: outer,
+                size,
+                first,
+                second,
+            } => {
+                let n = first.count();
+                if index < n {
+                    Node::Split {
+                        direction: outer,
+                        size,
+                        first: Box::new(first.split(index, direction, pane)),
+                        second,
+                    }
+                } else {
+                    Node::Split {
+                        direction: outer,
+                        size,
+                        first,
+                        second: Box::new(second.split(index - n, direction, pane)),
+                    }
+                }
+            }
+        }
+    }
+
+    /// 按编号顺序取出所有面板，重新读取布局时用来放进新的树。
+    pub fn into_panes(self) -> Vec<Box<dyn Pane>> {
+        match self {
+            Node::Pane(pane) => vec![pane],
+            Node::Split { first, second, .. } => {
+                let mut panes = first.into_panes();
+                panes.extend(second.into_panes());
+                panes
+            }
+        }
+    }
+
+    /// 删除第 `index` 个面板，另一半占据整个区域。删除的是最后一个面板时返回 `None`。
+    pub fn remove(self, index: usize) -> Option<Node> {
+        match self {
+            Node::Pane(_) => None,
+            Node::Split {
+                direction,
+                size,
+                first,
+                second,
+            } => {
+                let n = first.count();
+                if index < n {
+                    match first.remove(index) {
+                        Some(first) => Some(Node::Split {
+                            direction,
+                            size,
+                            first: Box::new(first),
+                            second,
+                        }),
+                        None => Some(*second),
+                    }
+                } else {
+                    match second.remove(index - n) {
+                        Some(second) => Some(Node::Split {
+                            direction,
+                            size,
+                            first,
+                            second: Box::new(second),
+                        }),
+                        None => Some(*first),
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn tick(&mut self, now: DateTime<Local>) {
+        match self {
+            Node::Pane(pane) => pane.tick(now),
+            Node::Split { first, second, .. } => {
+                first.tick(now);
+                second.tick(now);
+            }
+        }
+    }
+
+    /// 绘制所有面板，获得焦点的面板边框高亮。`index` 是这棵子树中第一个面板的编号。
+    pub fn render(&self, area: Rect, buf: &mut Buffer, focus: usize, index: usize) {
+        match self {
+            Node::Pane(pane) => {
+                let style = if index == focus {
+                    Style::new().green()
+                } else {
+                    Style::new().dark_gray()
+                };
+                let block = Block::bordered()
+                    .title(format!(" {} ", pane.title()))
+                    .border_style(style);
+                let inner = block.inner(area);
+                block.render(area, buf);
+                pane.render(inner, buf);
+            }
+            Node::Split {
+                direction,
+                size,
+                first,
+                second,
+            } => {
+                let [a, b] = Layout::new(*direction, [*size, Constraint::Fill(1)]).areas(area);
+                first.render(a, buf, focus, index);
+                second.render(b, buf, focus, index + first.count());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing::buffer_rows;
+
+    use super::*;
+    use crate::panes::Kind;
+
+    fn pane(kind: Kind) -> Box<dyn Pane> {
+        kind.create(Local::now())
+    }
+
+    fn kinds(node: &Node) -> Vec<Kind> {
+        (0..node.count()).map(|i| node.pane(i).kind()).collect()
+    }
+
+    #[test]
+    fn split_and_remove() {
+        let root = Node::Pane(pane(Kind::Counter))
+            .split(0, Direction::Horizontal, pane(Kind::Clock))
+            .split(0, Direction::Vertical, pane(Kind::Log));
+        assert_eq!(kinds(&root), [Kind::Counter, Kind::Log, Kind::Clock]);
+
+        let root = root.remove(0).unwrap();
+        assert_eq!(kinds(&root), [Kind::Log, Kind::Clock]);
+        let root = root.remove(1).unwrap();
+        assert_eq!(kinds(&root), [Kind::Log]);
+        assert!(root.remove(0).is_none());
+    }
+
+    #[test]
+    fn render_split() {
+        let root = Node::Pane(pane(Kind::Counter)).split(0, Direction::Horizontal, pane(Kind::Log));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 26, 3));
+        root.render(buf.area, &mut buf, 1, 0);
+        assert_eq!(
+            buffer_rows(&buf),
+            [
+                "┌ counter 0 ┐┌ log (0) ──┐",
+                "│     0     ││           │",
+                "└───────────┘└───────────┘",
+            ]
+        );
+        // 只有获得焦点的面板边框高亮。
+        assert_eq!(buf.get(0, 0).fg, Color::DarkGray);
+        assert_eq!(buf.get(13, 0).fg, Color::Green);
+    }
+}
@@ -0,0 +1,354 @@
+//! 从文件加载的窗口和面板排列：启动时用 `--layout <FILE>` 指定，不用重新编译就能调整布局。运行时
+//! 文件改变后会重新读取，已有的面板保留自己的状态（见 `App::apply_layout`）。
+//!
+//! 文件是 TOML，扩展名为 `.json` 时是同样结构的 JSON。每个 `[[windows]]` 是一个窗口，窗口和其中
+//! 的每一部分都是一个节点：有 `pane` 的是一个面板，有 `split` 的把区域分成 `first` 和 `second`
+//! 两部分。`size` 是 `first` 的大小，可以是百分比（`"60%"`）或者列数、行数（`20`），省略时两部分
+//! 一样大。
+//!
+//! ```toml
+//! # 左边 60% 是计数器，右边上面 4 行是时钟，下面是按键日志
+//! [[windows]]
+//! split = "horizontal"
+//! size = "60%"
+//! first = { pane = "counter" }
+//!
+//! [windows.second]
+//! split = "vertical"
+//! size = 4
+//! first = { pane = "clock" }
+//! second = { pane = "log" }
+//!
+//! # 第二个窗口只有一个日志面板
+//! [[windows]]
+//! pane = "log"
+//! ```
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use ratatui::layout::{Constraint, Direction};
+use serde::Deserialize;
+
+use crate::{
+    layout::Node,
+    panes::{Kind, Pane},
+    MAX_WINDOWS,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LayoutFile {
+    windows: Vec<NodeSpec>,
+}
+
+/// 文件中的一个节点。面板和分割写在同一种表里，由 [`NodeSpec::check`] 检查只用了其中一种的字段，
+/// 这样出错时能指出是哪个节点。
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NodeSpec {
+    pane: Option<Kind>,
+    split: Option<Split>,
+    size: Option<Size>,
+    first: Option<Box<NodeSpec>>,
+    second: Option<Box<NodeSpec>>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Split {
+    /// 左右排列。
+    Horizontal,
+    /// 上下排列。
+    Vertical,
+}
+
+impl Split {
+    fn direction(self) -> Direction {
+        match self {
+            Self::Horizontal => Direction::Horizontal,
+            Self::Vertical => Direction::Vertical,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Size {
+    Cells(u16),
+    Text(String),
+}
+
+impl Size {
+    fn constraint(&self) -> Result<Constraint, String> {
+        match self {
+            Self::Cells(cells) => Ok(Constraint::Length(*cells)),
+            Self::Text(text) => match text.trim().strip_suffix('%') {
+                Some(percent) => percent
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|percent| (1..=99).contains(percent))
+                    .map(Constraint::Percentage)
+                    .ok_or_else(|| format!("{text:?} is not a percentage between 1% and 99%")),
+                None => text
+                    .trim()
+                    .parse()
+                    .map(Constraint::Length)
+                    .map_err(|_| format!("{text:?} is not a size like \"60%\" or 20")),
+            },
+        }
+    }
+}
+
+/// 检查过的布局：每个窗口一棵树，还没有创建面板。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Arrangement {
+    pub windows: Vec<Spec>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Spec {
+    Pane(Kind),
+    Split {
+        direction: Direction,
+        size: Constraint,
+        first: Box<Spec>,
+        second: Box<Spec>,
+    },
+}
+
+impl Arrangement {
+    /// 创建所有窗口的面板树。`pane(window, kind)` 提供第 `window` 个窗口中的面板，可以是新建的，
+    /// 也可以是重新读取布局之前已有的。
+    pub fn build(self, mut pane: impl FnMut(usize, Kind) -> Box<dyn Pane>) -> Vec<Node> {
+        self.windows
+            .into_iter()
+            .enumerate()
+            .map(|(window, spec)| spec.build(&mut |kind| pane(window, kind)))
+            .collect()
+    }
+}
+
+impl Spec {
+    fn build(self, pane: &mut dyn FnMut(Kind) -> Box<dyn Pane>) -> Node {
+        match self {
+            Self::Pane(kind) => Node::Pane(pane(kind)),
+            Self::Split {
+                direction,
+                size,
+                first,
+                second,
+            } => Node::Split {
+                direction,
+                size,
+                first: Box::new(first.build(pane)),
+                second: Box::new(second.build(pane)),
+            },
+        }
+    }
+}
+
+impl NodeSpec {
+    /// 检查这个节点。`path` 是节点在文件中的位置，例如 `windows[0].second`，用在错误信息中。
+    fn check(&self, path: &str) -> Result<Spec, String> {
+        match (self.pane, self.split, &self.first, &self.second) {
+            (Some(kind), None, None, None) if self.size.is_none() => Ok(Spec::Pane(kind)),
+            (None, Some(split), Some(first), Some(second)) => {
+                let size = match &self.size {
+                    Some(size) => size
+                        .constraint()
+                        .map_err(|error| format!("{path}.size: {error}"))?,
+                    None => Constraint::Fill(1),
+                };
+                Ok(Spec::Split {
+                    direction: split.direction(),
+                    size,
+                    first: Box::new(first.check(&format!("{path}.first"))?),
+                    second: Box::new(second.check(&format!("{path}.second"))?),
+                })
+            }
+            (Some(_), ..) => Err(format!(
+                "{path}: a pane cannot also have split, size, first or second"
+            )),
+            (None, Some(_), ..) => Err(format!("{path}: a split needs both first and second")),
+            (None, None, ..) => Err(format!("{path}: needs either pane or split")),
+        }
+    }
+}
+
+/// 解析并检查布局文件的内容。
+pub fn parse(text: &str, json: bool) -> Result<Arrangement, String> {
+    let file: LayoutFile = if json {
+        serde_json::from_str(text).map_err(|error| error.to_string())?
+    } else {
+        toml::from_str(text).map_err(|error| error.to_string())?
+    };
+    if file.windows.is_empty() || file.windows.len() > MAX_WINDOWS {
+        return Err(format!("a layout needs 1 to {MAX_WINDOWS} windows"));
+    }
+    let windows = file
+        .windows
+        .iter()
+        .enumerate()
+        .map(|(index, window)| window.check(&format!("windows[{index}]")))
+        .collect::<Result<_, _>>()?;
+    Ok(Arrangement { windows })
+}
+
+/// 读取 `path` 中的布局，扩展名为 `.json` 时按 JSON 解析，否则按 TOML 解析。
+pub fn load(path: &Path) -> Result<Arrangement> {
+    let text = fs::read_to_string(path)
+        .wrap_err_with(|| format!("reading layout {} failed", path.display()))?;
+    let json = path
+        .extension()
+        .is_some_and(|extension| extension == "json");
+    parse(&text, json).map_err(|error| eyre!("invalid layout {}: {error}", path.display()))
+}
+
+/// 检查布局文件有没有改变：比较文件的修改时间，由主循环每个节拍调用一次。
+#[derive(Debug)]
+pub struct Watcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl Watcher {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            modified: modified(path),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 上次调用以来文件是否改变。文件暂时不存在时（一些编辑器保存时先删除再写入）不算改变。
+    pub fn changed(&mut self) -> bool {
+        match modified(&self.path) {
+            Some(modified) if Some(modified) != self.modified => {
+                self.modified = Some(modified);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, time::Duration};
+
+    use chrono::{DateTime, Local, TimeZone};
+    use ratatui::prelude::*;
+    use ratatui_common::testing::buffer_rows;
+
+    use super::*;
+
+    fn noon() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+    }
+
+    fn build(arrangement: Arrangement) -> Vec<Node> {
+        arrangement.build(|_, kind| kind.create(noon()))
+    }
+
+    const EXAMPLE: &str = r#"
+[[windows]]
+split = "horizontal"
+size = "60%"
+first = { pane = "counter" }
+
+[windows.second]
+split = "vertical"
+size = 4
+first = { pane = "clock" }
+second = { pane = "log" }
+
+[[windows]]
+pane = "log"
+"#;
+
+    #[test]
+    fn parse_toml_and_json() {
+        let windows = build(parse(EXAMPLE, false).unwrap());
+        assert_eq!(windows.len(), 2);
+        let kinds: Vec<Kind> = (0..windows[0].count())
+            .map(|index| windows[0].pane(index).kind())
+            .collect();
+        assert_eq!(kinds, [Kind::Counter, Kind::Clock, Kind::Log]);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 30, 7));
+        windows[0].render(buf.area, &mut buf, 0, 0);
+        assert_eq!(
+            buffer_rows(&buf),
+            [
+                "┌ counter 0 ─────┐┌ clock ───┐",
+                "│        0       ││ 12:00:00 │",
+                "│<Left> / <Right>││2024-05-01│",
+                "│                │└──────────┘",
+                "│                │┌ log (0) ─┐",
+                "│                ││          │",
+                "└────────────────┘└──────────┘",
+            ]
+        );
+
+        let json = r#"{"windows": [{"split": "vertical", "first": {"pane": "clock"}, "second": {"pane": "counter"}}]}"#;
+        let windows = build(parse(json, true).unwrap());
+        assert_eq!(windows[0].pane(1).kind(), Kind::Counter);
+    }
+
+    #[test]
+    fn errors_name_the_node() {
+        let error = |text: &str| parse(text, false).err().unwrap();
+        assert_eq!(
+            error("[[windows]]\nsplit = \"vertical\"\nfirst = { pane = \"log\" }"),
+            "windows[0]: a split needs both first and second"
+        );
+        assert_eq!(
+            error(
+                "[[windows]]\npane = \"log\"\n[[windows]]\nsplit = \"vertical\"\nsize = \"120%\"\n\
+                 first = { pane = \"log\" }\nsecond = { size = 3 }"
+            ),
+            "windows[1].size: \"120%\" is not a percentage between 1% and 99%"
+        );
+        assert_eq!(
+            error("[[windows]]\npane = \"log\"\nsize = 3"),
+            "windows[0]: a pane cannot also have split, size, first or second"
+        );
+        assert!(error("[[windows]]\npane = \"editor\"").contains("unknown variant `editor`"));
+        assert_eq!(error("windows = []"), "a layout needs 1 to 10 windows");
+    }
+
+    #[test]
+    fn watch_for_changes() {
+        let path = env::temp_dir().join(format!("multiplexer-layout-{}.toml", std::process::id()));
+        fs::write(&path, "[[windows]]\npane = \"log\"").unwrap();
+        let mut watcher = Watcher::new(&path);
+        assert!(!watcher.changed());
+
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5))
+            .unwrap();
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+
+        fs::remove_file(&path).unwrap();
+        assert!(!watcher.changed());
+    }
+}
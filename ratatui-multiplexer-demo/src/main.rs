@@ -0,0 +1,496 @@
+//! 类似 tmux 的多窗口演示：一个终端中有多个互相独立的窗口，每个窗口可以上下或左右分割为多个面板，
+//! 每个面板是一个小程序（计数器、时钟或按键日志）。
+//!
+//! 按键与 tmux 相同，先按前缀键 Ctrl-b，再按命令键：
+//!
+//! ```text
+//! c       新建窗口          n / p   下一个 / 上一个窗口     0-9   切换到指定窗口
+//! "       上下分割面板      %       左右分割面板            o     切换到下一个面板
+//! x       关闭面板          d       退出
+//! ```
+//!
+//! 没有前缀的按键交给获得焦点的面板处理。F12 打开调试控制台。
+//!
+//! 启动时的窗口和面板排列可以用 `--layout <FILE>` 从文件中读取，格式见 `layout_file` 模块。运行时
+//! 修改这个文件，排列会随之更新，面板中的状态（计数、按键日志）保持不变。
+
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+use clap::Parser;
+use color_eyre::{eyre::eyre, Result};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::prelude::*;
+use ratatui_common::{
+    console::{Console, ConsoleAction},
+    events::{EventSource, TerminalEvents},
+    terminal,
+};
+
+use crate::{
+    layout::Node,
+    layout_file::Watcher,
+    panes::{Kind, Pane},
+};
+
+mod layout;
+mod layout_file;
+mod panes;
+
+/// 所有面板每隔这么长时间更新一次。
+const TICK_RATE: Duration = Duration::from_secs(1);
+
+/// 最多可以用数字键直接切换的窗口数。
+const MAX_WINDOWS: usize = 10;
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 窗口和面板的排列（TOML，或者扩展名为 .json 的 JSON），修改后自动重新读取
+    #[arg(long, value_name = "FILE")]
+    layout: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let now = Local::now();
+    let mut app = App::new(now);
+    if let Some(path) = &cli.layout {
+        // 先记下修改时间再读取，读取过程中的修改也会被发现。
+        app.watcher = Some(Watcher::new(path));
+        app.apply_layout(layout_file::load(path)?);
+    }
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = app.run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+/// 一个窗口：面板树和获得焦点的面板编号。
+struct Window {
+    /// 只在关闭面板、重建树的过程中短暂为 `None`。
+    root: Option<Node>,
+    focus: usize,
+}
+
+impl From<Node> for Window {
+    fn from(root: Node) -> Self {
+        Self {
+            root: Some(root),
+            focus: 0,
+        }
+    }
+}
+
+impl Window {
+    fn new(kind: Kind, now: DateTime<Local>) -> Self {
+        Node::Pane(kind.create(now)).into()
+    }
+
+    fn root(&self) -> &Node {
+        self.root.as_ref().expect("window has panes")
+    }
+
+    fn root_mut(&mut self) -> &mut Node {
+        self.root.as_mut().expect("window has panes")
+    }
+
+    /// 窗口的名称是获得焦点的面板的名称。
+    fn name(&self) -> String {
+        self.root().pane(self.focus).title()
+    }
+
+    fn split(&mut self, direction: Direction, now: DateTime<Local>) {
+        let root = self.root.take().expect("window has panes");
+        let kind = root.pane(self.focus).kind().next();
+        self.root = Some(root.split(self.focus, direction, kind.create(now)));
+        self.focus += 1;
+    }
+
+    /// 关闭获得焦点的面板，窗口中没有面板时返回 `false`。
+    fn close_pane(&mut self) -> bool {
+        let root = self.root.take().expect("window has panes");
+        self.root = root.remove(self.focus);
+        match &self.root {
+            Some(root) => {
+                self.focus = self.focus.min(root.count() - 1);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+struct App {
+    windows: Vec<Window>,
+    active: usize,
+    /// 刚刚按下了前缀键，下一个按键是命令。
+    prefix: bool,
+    /// 用 `--layout` 启动时监视布局文件。
+    watcher: Option<Watcher>,
+    /// 显示在状态栏右边的消息，例如重新读取布局失败的原因，下一次按键时清除。
+    message: Option<String>,
+    console: Console,
+    /// 调试控制台中用 `error` 命令要求返回的错误。
+    forced_error: Option<String>,
+    now: DateTime<Local>,
+    exit: bool,
+}
+
+impl App {
+    fn new(now: DateTime<Local>) -> Self {
+        Self {
+            windows: vec![Window::new(Kind::Counter, now)],
+            active: 0,
+            prefix: false,
+            watcher: None,
+            message: None,
+            console: Console::default(),
+            forced_error: None,
+            now,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        let mut next_tick = Instant::now() + TICK_RATE;
+        while !self.exit {
+            terminal.draw(|frame| self.render(frame))?;
+            let timeout = next_tick.saturating_duration_since(Instant::now());
+            if events.poll(timeout)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.console.event(format!("{key:?}"));
+                        self.handle_key(key);
+                        self.console.watch(&(self.active, self.names()));
+                    }
+                }
+                if let Some(error) = self.forced_error.take() {
+                    return Err(eyre!(error));
+                }
+            }
+            if Instant::now() >= next_tick {
+                self.tick(Local::now());
+                self.reload_layout();
+                next_tick += TICK_RATE;
+            }
+        }
+        Ok(())
+    }
+
+    /// 所有窗口中的面板都会收到节拍，切换回来时时钟已经是最新的时间。
+    fn tick(&mut self, now: DateTime<Local>) {
+        self.now = now;
+        for window in &mut self.windows {
+            window.root_mut().tick(now);
+        }
+    }
+
+    /// 布局文件改变后重新读取。文件有错误时保留现在的排列，在状态栏中显示错误。
+    fn reload_layout(&mut self) {
+        let Some(watcher) = &mut self.watcher else {
+            return;
+        };
+        if !watcher.changed() {
+            return;
+        }
+        match layout_file::load(watcher.path()) {
+            Ok(arrangement) => {
+                self.apply_layout(arrangement);
+                self.message = None;
+            }
+            Err(error) => {
+                self.message = Some(format!("{error:#}"));
+            }
+        }
+    }
+
+    /// 按 `arrangement` 重建所有窗口。新排列中的面板优先使用同一个窗口中已有的同类面板（按编号顺序），
+    /// 这样计数和日志不会因为调整排列而丢失；没有可用的面板时新建，用不上的面板被丢弃。
+    fn apply_layout(&mut self, arrangement: layout_file::Arrangement) {
+        let now = self.now;
+        let focus: Vec<usize> = self.windows.iter().map(|window| window.focus).collect();
+        let mut panes: Vec<Vec<Box<dyn Pane>>> = self
+            .windows
+            .drain(..)
+            .filter_map(|mut window| window.root.take().map(Node::into_panes))
+            .collect();
+        let roots = arrangement.build(|window, kind| {
+            panes
+                .get_mut(window)
+                .and_then(|panes| {
+                    let index = panes.iter().position(|pane| pane.kind() == kind)?;
+                    Some(panes.remove(index))
+                })
+                .unwrap_or_else(|| kind.create(now))
+        });
+        self.windows = roots.into_iter().map(Window::from).collect();
+        for (window, focus) in self.windows.iter_mut().zip(focus) {
+            window.focus = focus.min(window.root().count() - 1);
+        }
+        self.active = self.active.min(self.windows.len() - 1);
+    }
+
+    fn window(&mut self) -> &mut Window {
+        &mut self.windows[self.active]
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        self.message = None;
+        match self.console.handle_key_event(key) {
+            ConsoleAction::Ignored => {}
+            ConsoleAction::Handled => return,
+            ConsoleAction::Error(message) => {
+                self.forced_error = Some(message);
+                return;
+            }
+        }
+        if key.code == KeyCode::Char('b') && key.modifiers == KeyModifiers::CONTROL {
+            self.prefix = true;
+            return;
+        }
+        if std::mem::take(&mut self.prefix) {
+            self.command(key.code);
+            return;
+        }
+        let (now, window) = (self.now, &mut self.windows[self.active]);
+        let focus = window.focus;
+        window.root_mut().pane_mut(focus).handle_key(key.code, now);
+    }
+
+    fn command(&mut self, code: KeyCode) {
+        self.console.message(format!("command {code:?}"));
+        let now = self.now;
+        match code {
+            KeyCode::Char('c') if self.windows.len() < MAX_WINDOWS => {
+                self.windows.push(Window::new(Kind::Counter, now));
+                self.active = self.windows.len() - 1;
+            }
+            KeyCode::Char('n') => self.active = (self.active + 1) % self.windows.len(),
+            KeyCode::Char('p') => {
+                self.active = (self.active + self.windows.len() - 1) % self.windows.len()
+            }
+            KeyCode::Char(c @ '0'..='9') => {
+                let index = c as usize - '0' as usize;
+                if index < self.windows.len() {
+                    self.active = index;
+                }
+            }
+            KeyCode::Char('"') => self.window().split(Direction::Vertical, now),
+            KeyCode::Char('%') => self.window().split(Direction::Horizontal, now),
+            KeyCode::Char('o') => {
+                let window = self.window();
+                window.focus = (window.focus + 1) % window.root().count();
+            }
+            KeyCode::Char('x') => self.close_pane(),
+            KeyCode::Char('d') => self.exit = true,
+            _ => {}
+        }
+    }
+
+    /// 关闭面板；窗口中没有面板时关闭窗口，没有窗口时退出。
+    fn close_pane(&mut self) {
+        if self.window().close_pane() {
+            return;
+        }
+        self.windows.remove(self.active);
+        if self.windows.is_empty() {
+            self.exit = true;
+        } else {
+            self.active = self.active.min(self.windows.len() - 1);
+        }
+    }
+
+    fn render(&self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.size());
+        if let Some(window) = self.windows.get(self.active) {
+            window
+                .root()
+                .render(main, frame.buffer_mut(), window.focus, 0);
+        }
+        self.render_status(status, frame.buffer_mut());
+        frame.render_widget(&self.console, frame.size());
+    }
+
+    /// 各个窗口的名称，调试控制台用来记录状态的变化。
+    fn names(&self) -> Vec<String> {
+        self.windows.iter().map(Window::name).collect()
+    }
+
+    /// 状态栏：左边是窗口列表，当前窗口带 `*`；右边是时间，按下前缀键后改为命令提示，有消息时
+    /// 显示消息。
+    fn render_status(&self, area: Rect, buf: &mut Buffer) {
+        let windows: Vec<Span> = self
+            .windows
+            .iter()
+            .enumerate()
+            .map(|(index, window)| {
+                if index == self.active {
+                    Span::raw(format!(" {index}:{}*", window.name())).bold()
+                } else {
+                    Span::raw(format!(" {index}:{} ", window.name()))
+                }
+            })
+            .collect();
+        let right = if self.prefix {
+            " c n p 0-9 \" % o x d ".to_string()
+        } else if let Some(message) = &self.message {
+            format!(" {message} ")
+        } else {
+            self.now.format(" %H:%M ").to_string()
+        };
+        let [left_area, right_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(right.len() as u16)])
+                .areas(area);
+        buf.set_style(area, Style::new().black().on_green());
+        Line::from(windows).render(left_area, buf);
+        Line::raw(right).render(right_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, time::SystemTime};
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn app() -> App {
+        App::new(Local.with_ymd_and_hms(2024, 5, 1, 12, 30, 0).unwrap())
+    }
+
+    fn press(app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            app.handle_key(KeyEvent::new(KeyCode::Char('b'), KeyModifiers::CONTROL));
+            app.handle_key(KeyCode::Char(c).into());
+        }
+    }
+
+    fn names(app: &App) -> Vec<String> {
+        app.windows.iter().map(Window::name).collect()
+    }
+
+    #[test]
+    fn keys_go_to_focused_pane() {
+        let mut app = app();
+        app.handle_key(KeyCode::Right.into());
+        press(&mut app, "%");
+        // 新面板是时钟，获得焦点；计数器不再收到按键。
+        app.handle_key(KeyCode::Right.into());
+        press(&mut app, "o");
+        assert_eq!(names(&app), ["counter 1"]);
+        app.handle_key(KeyCode::Right.into());
+        assert_eq!(names(&app), ["counter 2"]);
+    }
+
+    #[test]
+    fn windows() {
+        let mut app = app();
+        press(&mut app, "c\"");
+        assert_eq!(names(&app), ["counter 0", "clock"]);
+        assert_eq!(app.active, 1);
+        press(&mut app, "n");
+        assert_eq!(app.active, 0);
+        press(&mut app, "p1");
+        assert_eq!(app.active, 1);
+        // 9 号窗口不存在。
+        press(&mut app, "9");
+        assert_eq!(app.active, 1);
+
+        press(&mut app, "x");
+        assert_eq!(names(&app), ["counter 0", "counter 0"]);
+        press(&mut app, "x");
+        assert_eq!(app.windows.len(), 1);
+        assert_eq!(app.active, 0);
+        press(&mut app, "x");
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn debug_console() {
+        let mut app = app();
+        app.handle_key(KeyCode::F(12).into());
+        for c in "error boom".chars() {
+            app.handle_key(KeyCode::Char(c).into());
+        }
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(app.forced_error.as_deref(), Some("boom"));
+        // 控制台打开时前缀键也交给控制台。
+        press(&mut app, "c");
+        assert_eq!(app.windows.len(), 1);
+
+        app.handle_key(KeyCode::F(12).into());
+        press(&mut app, "c");
+        assert_eq!(app.windows.len(), 2);
+        assert!(app
+            .console
+            .entries()
+            .any(|entry| entry.text == "command Char('c')"));
+    }
+
+    #[test]
+    fn render_status_line() {
+        let mut app = app();
+        press(&mut app, "c");
+        let mut terminal = Terminal::new(backend::TestBackend::new(40, 4)).unwrap();
+        terminal.draw(|frame| app.render(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let status: String = (0..40).map(|x| buffer.get(x, 3).symbol()).collect();
+        assert_eq!(status, " 0:counter 0  1:counter 0*        12:30 ");
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('b'), KeyModifiers::CONTROL));
+        terminal.draw(|frame| app.render(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let status: String = (0..40).map(|x| buffer.get(x, 3).symbol()).collect();
+        assert!(status.ends_with(" c n p 0-9 \" % o x d "));
+    }
+
+    #[test]
+    fn reload_layout_keeps_pane_state() {
+        let path = env::temp_dir().join(format!("multiplexer-reload-{}.toml", std::process::id()));
+        let rewrite = |text: &str, seconds: u64| {
+            fs::write(&path, text).unwrap();
+            let file = fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+                .unwrap();
+        };
+        rewrite("[[windows]]\npane = \"counter\"", 1);
+        let mut app = app();
+        app.watcher = Some(Watcher::new(&path));
+        app.handle_key(KeyCode::Right.into());
+        app.handle_key(KeyCode::Right.into());
+
+        rewrite(
+            "[[windows]]\nsplit = \"vertical\"\nfirst = { pane = \"clock\" }\n\
+             second = { pane = \"counter\" }\n[[windows]]\npane = \"counter\"",
+            2,
+        );
+        app.reload_layout();
+        assert_eq!(names(&app), ["clock", "counter 0"]);
+        press(&mut app, "o");
+        assert_eq!(names(&app), ["counter 2", "counter 0"]);
+
+        // 有错误的文件不改变排列。
+        rewrite("[[windows]]\npane = \"editor\"", 3);
+        app.reload_layout();
+        assert_eq!(names(&app), ["counter 2", "counter 0"]);
+        assert!(app
+            .message
+            .as_ref()
+            .unwrap()
+            .contains("unknown variant `editor`"));
+        app.handle_key(KeyCode::Right.into());
+        assert_eq!(app.message, None);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,182 @@
+//! 可以放进窗口的小程序：计数器、时钟和按键日志。每个面板都有独立的状态。
+
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Local};
+use crossterm::event::KeyCode;
+use ratatui::{prelude::*, widgets::Paragraph};
+use serde::Deserialize;
+
+/// 日志面板最多保留的条目数。
+const LOG_LIMIT: usize = 200;
+
+/// 面板的种类，分割窗口时新面板使用当前面板的下一种。布局文件中写作小写的名称。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Counter,
+    Clock,
+    Log,
+}
+
+impl Kind {
+    pub fn next(self) -> Self {
+        match self {
+            Kind::Counter => Kind::Clock,
+            Kind::Clock => Kind::Log,
+            Kind::Log => Kind::Counter,
+        }
+    }
+
+    pub fn create(self, now: DateTime<Local>) -> Box<dyn Pane> {
+        match self {
+            Kind::Counter => Box::<Counter>::default(),
+            Kind::Clock => Box::new(Clock { now }),
+            Kind::Log => Box::<Log>::default(),
+        }
+    }
+}
+
+/// 窗口中的一个小程序。
+pub trait Pane {
+    fn kind(&self) -> Kind;
+
+    /// 显示在边框和状态栏中的名称。
+    fn title(&self) -> String;
+
+    /// 处理没有被前缀键截获的按键，只有获得焦点的面板会收到。
+    fn handle_key(&mut self, code: KeyCode, now: DateTime<Local>);
+
+    /// 每秒调用一次，所有窗口中的面板都会收到。
+    fn tick(&mut self, _now: DateTime<Local>) {}
+
+    fn render(&self, area: Rect, buf: &mut Buffer);
+}
+
+#[derive(Debug, Default)]
+pub struct Counter {
+    value: u32,
+}
+
+impl Pane for Counter {
+    fn kind(&self) -> Kind {
+        Kind::Counter
+    }
+
+    fn title(&self) -> String {
+        format!("counter {}", self.value)
+    }
+
+    fn handle_key(&mut self, code: KeyCode, _now: DateTime<Local>) {
+        match code {
+            KeyCode::Left => self.value = self.value.saturating_sub(1),
+            KeyCode::Right => self.value = self.value.saturating_add(1),
+            _ => {}
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let text = vec![
+            Line::from(self.value.to_string().yellow().bold()),
+            Line::from("<Left> / <Right>".dim()),
+        ];
+        Paragraph::new(text).centered().render(area, buf);
+    }
+}
+
+#[derive(Debug)]
+pub struct Clock {
+    now: DateTime<Local>,
+}
+
+impl Pane for Clock {
+    fn kind(&self) -> Kind {
+        Kind::Clock
+    }
+
+    fn title(&self) -> String {
+        "clock".into()
+    }
+
+    fn handle_key(&mut self, _code: KeyCode, _now: DateTime<Local>) {}
+
+    fn tick(&mut self, now: DateTime<Local>) {
+        self.now = now;
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let text = vec![
+            Line::from(self.now.format("%H:%M:%S").to_string().bold()),
+            Line::from(self.now.format("%Y-%m-%d %A").to_string().dim()),
+        ];
+        Paragraph::new(text).centered().render(area, buf);
+    }
+}
+
+/// 记录获得焦点时收到的按键。
+#[derive(Debug, Default)]
+pub struct Log {
+    entries: VecDeque<String>,
+}
+
+impl Pane for Log {
+    fn kind(&self) -> Kind {
+        Kind::Log
+    }
+
+    fn title(&self) -> String {
+        format!("log ({})", self.entries.len())
+    }
+
+    fn handle_key(&mut self, code: KeyCode, now: DateTime<Local>) {
+        if self.entries.len() == LOG_LIMIT {
+            self.entries.pop_front();
+        }
+        self.entries
+            .push_back(format!("{} {code:?}", now.format("%H:%M:%S")));
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        // 最新的条目在最下面，放不下时省略最旧的。
+        let skip = self.entries.len().saturating_sub(area.height.into());
+        let lines: Vec<Line> = self.entries.iter().skip(skip).map(Line::raw).collect();
+        Paragraph::new(lines).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn noon() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn counter_keys() {
+        let mut counter = Counter::default();
+        counter.handle_key(KeyCode::Left, noon());
+        counter.handle_key(KeyCode::Right, noon());
+        counter.handle_key(KeyCode::Right, noon());
+        assert_eq!(counter.title(), "counter 2");
+    }
+
+    #[test]
+    fn log_keeps_newest_entries() {
+        let mut log = Log::default();
+        for _ in 0..LOG_LIMIT {
+            log.handle_key(KeyCode::Char('a'), noon());
+        }
+        log.handle_key(KeyCode::Enter, noon());
+        assert_eq!(log.entries.len(), LOG_LIMIT);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 16, 2));
+        log.render(buf.area, &mut buf);
+        assert_eq!(
+            buf,
+            Buffer::with_lines(vec!["12:00:00 Char('a", "12:00:00 Enter  "])
+        );
+    }
+}
@@ -0,0 +1,490 @@
+//! 笔记演示：笔记列表、编辑框，以及旁边随输入实时更新的 Markdown 预览。
+//!
+//! 笔记保存在命令行指定的目录中，每篇笔记一个 `.md` 文件（见 `notebook` 模块），可以用其他编辑器
+//! 打开。切换笔记、离开编辑框和退出时自动保存，编辑时也可以随时按 `Ctrl+S` 保存。预览只支持常用的
+//! Markdown（见 `markdown` 模块），按编辑框滚动的比例跟着滚动。
+//!
+//! 按键：列表中 `Enter` 编辑，`n` 新建，`x` 删除，`q` 退出；编辑时 `Esc` 回到列表。
+
+use std::{path::PathBuf, time::Duration};
+
+use clap::Parser;
+use color_eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, List, ListState, Paragraph, Wrap},
+};
+use ratatui_common::{
+    editor::Editor,
+    events::{EventSource, TerminalEvents},
+    terminal,
+    text_input::Input,
+};
+
+use crate::notebook::Notebook;
+
+mod markdown;
+mod notebook;
+
+/// 等待按键的最长时间。
+const REFRESH: Duration = Duration::from_secs(1);
+
+/// 笔记列表的宽度。
+const LIST_WIDTH: u16 = 24;
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 保存笔记的目录，不存在时在新建第一篇笔记时创建
+    #[arg(default_value = "notes")]
+    dir: PathBuf,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let notebook = Notebook::new(cli.dir);
+    let names = notebook.names()?;
+
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new(notebook, names).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+fn count(count: usize) -> String {
+    match count {
+        1 => "1 note".into(),
+        count => format!("{count} notes"),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Notes,
+    Editor,
+}
+
+struct App {
+    notebook: Notebook,
+    /// 按名字排序。
+    names: Vec<String>,
+    list: ListState,
+    /// 编辑框中打开的笔记，以及它保存在文件中的内容，用来判断有没有修改。读取失败时为 `None`。
+    open: Option<(String, String)>,
+    editor: Editor,
+    /// 编辑框顶部的行，绘制时调整到能看到光标。
+    scroll: usize,
+    focus: Focus,
+    /// 新建笔记时输入的名字。
+    prompt: Option<Input>,
+    /// 等待确认删除的笔记。
+    confirm: Option<usize>,
+    /// 显示在状态栏中的提示或错误，下一次按键时清除。
+    message: Option<Result<String, String>>,
+    exit: bool,
+}
+
+impl App {
+    fn new(notebook: Notebook, names: Vec<String>) -> Self {
+        let mut app = Self {
+            notebook,
+            names,
+            list: ListState::default(),
+            open: None,
+            editor: Editor::new(""),
+            scroll: 0,
+            focus: Focus::Notes,
+            prompt: None,
+            confirm: None,
+            message: None,
+            exit: false,
+        };
+        app.select(0);
+        app
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| frame.render_widget(&mut *self, frame.size()))?;
+            if events.poll(REFRESH)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn dirty(&self) -> bool {
+        self.open
+            .as_ref()
+            .is_some_and(|(_, saved)| *saved != self.editor.text())
+    }
+
+    /// 保存修改过的笔记，失败时显示错误并返回 `false`。
+    fn save(&mut self) -> bool {
+        if !self.dirty() {
+            return true;
+        }
+        let Some((name, saved)) = &mut self.open else {
+            return true;
+        };
+        let text = self.editor.text();
+        match self.notebook.write(name, &text) {
+            Ok(()) => {
+                *saved = text;
+                self.message = Some(Ok(format!("Saved {name}")));
+                true
+            }
+            Err(error) => {
+                self.message = Some(Err(format!("{error:#}")));
+                false
+            }
+        }
+    }
+
+    /// 选中并打开一篇笔记。当前的笔记保存失败时留在当前的笔记。
+    fn select(&mut self, row: usize) {
+        if !self.save() {
+            return;
+        }
+        let row = self.names.len().checked_sub(1).map(|last| row.min(last));
+        self.list.select(row);
+        let name = row.map(|row| self.names[row].clone());
+        if self.open.as_ref().map(|(open, _)| open) == name.as_ref() {
+            return;
+        }
+        self.open = None;
+        self.editor = Editor::new("");
+        self.scroll = 0;
+        let Some(name) = name else {
+            return;
+        };
+        match self.notebook.read(&name) {
+            Ok(text) => {
+                self.editor = Editor::new(&text);
+                self.open = Some((name, text));
+            }
+            Err(error) => self.message = Some(Err(format!("{error:#}"))),
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        self.message = None;
+        if let Some(input) = &mut self.prompt {
+            match key.code {
+                KeyCode::Esc => self.prompt = None,
+                KeyCode::Enter => {
+                    let name = input.value().trim().to_string();
+                    match self.notebook.create(&name) {
+                        Ok(()) => {
+                            self.prompt = None;
+                            let row = self.names.partition_point(|other| {
+                                other.to_lowercase() <= name.to_lowercase()
+                            });
+                            self.names.insert(row, name);
+                            self.select(row);
+                            self.focus = Focus::Editor;
+                        }
+                        Err(error) => self.message = Some(Err(error)),
+                    }
+                }
+                _ => {
+                    input.handle_key_event(key);
+                }
+            }
+            return;
+        }
+        if let Some(row) = self.confirm.take() {
+            if key.code == KeyCode::Char('y') {
+                let name = self.names[row].clone();
+                match self.notebook.delete(&name) {
+                    Ok(()) => {
+                        self.names.remove(row);
+                        // 删除的笔记不再保存。
+                        self.open = None;
+                        self.select(row);
+                        self.message = Some(Ok(format!("Deleted {name}")));
+                    }
+                    Err(error) => self.message = Some(Err(format!("{error:#}"))),
+                }
+            }
+            return;
+        }
+        match self.focus {
+            Focus::Notes => self.handle_list_key(key),
+            Focus::Editor => self.handle_editor_key(key),
+        }
+    }
+
+    fn handle_list_key(&mut self, key: KeyEvent) {
+        let row = self.list.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.exit = self.save(),
+            KeyCode::Up | KeyCode::Char('k') => self.select(row.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.select(row + 1),
+            KeyCode::Enter | KeyCode::Tab | KeyCode::Right if self.open.is_some() => {
+                self.focus = Focus::Editor;
+            }
+            KeyCode::Char('n') => self.prompt = Some(Input::default()),
+            KeyCode::Char('x') | KeyCode::Delete => self.confirm = self.list.selected(),
+            _ => {}
+        }
+    }
+
+    fn handle_editor_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Tab => {
+                if self.save() {
+                    self.focus = Focus::Notes;
+                }
+            }
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if !self.dirty() {
+                    self.message = Some(Ok("No changes to save".into()));
+                }
+                self.save();
+            }
+            _ => {
+                self.editor.handle_key_event(key);
+            }
+        }
+    }
+
+    fn border(&self, focus: Focus) -> Style {
+        if self.focus == focus {
+            Style::new().green()
+        } else {
+            Style::new()
+        }
+    }
+
+    fn render_list(&mut self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(" Notes ")
+            .title_bottom(Line::from(" New <n> Delete <x> ").right_aligned())
+            .border_style(self.border(Focus::Notes));
+        if self.names.is_empty() {
+            Paragraph::new("No notes yet, press n to create one".dim())
+                .wrap(Wrap { trim: true })
+                .block(block)
+                .render(area, buf);
+            return;
+        }
+        let items: Vec<Line> = self
+            .names
+            .iter()
+            .map(|name| Line::from(name.as_str()))
+            .collect();
+        StatefulWidget::render(
+            List::new(items)
+                .highlight_style(Style::new().reversed())
+                .block(block),
+            area,
+            buf,
+            &mut self.list,
+        );
+    }
+
+    fn render_editor(&mut self, area: Rect, buf: &mut Buffer) {
+        let mut block = Block::bordered()
+            .title_bottom(Line::from(" Save <Ctrl+S> Back <Esc> ").right_aligned())
+            .border_style(self.border(Focus::Editor));
+        let Some((name, _)) = &self.open else {
+            block.render(area, buf);
+            return;
+        };
+        block = block.title(if self.dirty() {
+            Line::from(vec![format!(" {name} ").bold(), "● ".yellow()])
+        } else {
+            Line::from(format!(" {name} ").bold())
+        });
+        let height = usize::from(block.inner(area).height).max(1);
+        let (row, _) = self.editor.cursor();
+        self.scroll = self.scroll.clamp(row.saturating_sub(height - 1), row);
+        let lines = if self.focus == Focus::Editor {
+            self.editor.styled_lines()
+        } else {
+            self.editor
+                .lines()
+                .iter()
+                .map(|line| Line::from(line.as_str()))
+                .collect()
+        };
+        Paragraph::new(lines)
+            .scroll((u16::try_from(self.scroll).unwrap_or(u16::MAX), 0))
+            .block(block)
+            .render(area, buf);
+    }
+
+    fn render_preview(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered().title(" Preview ");
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if self.open.is_none() {
+            return;
+        }
+        let lines = markdown::render(&self.editor.text(), inner.width);
+        // 按编辑框滚动的比例滚动预览，换行后的行数不好算，用渲染出的行数近似。
+        let scroll = self.scroll * lines.len() / self.editor.lines().len();
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((u16::try_from(scroll).unwrap_or(u16::MAX), 0))
+            .render(inner, buf);
+    }
+
+    fn status_line(&self) -> Line<'_> {
+        if let Some(row) = self.confirm {
+            return Line::from(format!("Delete {}? <Y>/<N>", self.names[row]))
+                .red()
+                .bold();
+        }
+        if let Some(input) = &self.prompt {
+            return input.line("New note: ", Style::new().bold());
+        }
+        match &self.message {
+            Some(Ok(message)) => Line::from(message.as_str()),
+            Some(Err(error)) => Line::from(error.as_str().red()),
+            None if self.focus == Focus::Editor => {
+                let (row, column) = self.editor.cursor();
+                Line::from(format!("Line {}, column {}", row + 1, column + 1).dim())
+            }
+            None => Line::from(
+                format!(
+                    "{} in {}  Edit <Enter> Quit <q>",
+                    count(self.names.len()),
+                    self.notebook.dir().display()
+                )
+                .dim(),
+            ),
+        }
+    }
+}
+
+impl Widget for &mut App {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let [list_area, editor_area, preview_area] = Layout::horizontal([
+            Constraint::Length(LIST_WIDTH),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ])
+        .areas(main);
+        self.render_list(list_area, buf);
+        self.render_editor(editor_area, buf);
+        self.render_preview(preview_area, buf);
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    fn app(name: &str) -> (App, PathBuf) {
+        let dir = env::temp_dir().join(format!("notes-demo-{name}-{}", process::id()));
+        let notebook = Notebook::new(dir.clone());
+        notebook
+            .write("groceries", "# Groceries\n- milk\n- **eggs**")
+            .unwrap();
+        notebook.write("ideas", "Nothing yet").unwrap();
+        let names = notebook.names().unwrap();
+        (App::new(notebook, names), dir)
+    }
+
+    #[test]
+    fn edit_with_live_preview() {
+        let (mut app, dir) = app("edit");
+        let screen = rows(&mut app, 80, 8);
+        assert_eq!(
+            screen[0],
+            "┌ Notes ───────────────┐┌ groceries ───────────────┐┌ Preview ─────────────────┐"
+        );
+        assert_eq!(
+            screen[3],
+            "│                      ││- **eggs**                ││• milk                    │"
+        );
+        assert!(screen[7].starts_with("2 notes in "));
+
+        app.handle_key(KeyCode::Enter.into());
+        testing::type_text(" and *bread*", |key| app.handle_key(key));
+        let screen = rows(&mut app, 80, 8);
+        assert!(screen[0].starts_with("┌ Notes ───────────────┐┌ groceries ● ─"));
+        assert_eq!(
+            screen[4],
+            "│                      ││                          ││• eggs and bread          │"
+        );
+        assert_eq!(screen[7].trim_end(), "Line 3, column 23");
+        assert!(app.dirty());
+
+        // 切换笔记时自动保存。
+        app.handle_key(KeyCode::Esc.into());
+        assert_eq!(app.message, Some(Ok("Saved groceries".into())));
+        app.handle_key(KeyCode::Down.into());
+        assert_eq!(app.editor.text(), "Nothing yet");
+        assert_eq!(
+            fs::read_to_string(dir.join("groceries.md")).unwrap(),
+            "# Groceries\n- milk\n- **eggs** and *bread*"
+        );
+
+        app.handle_key(KeyCode::Enter.into());
+        app.handle_key(KeyCode::Char('!').into());
+        app.handle_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL));
+        assert_eq!(app.message, Some(Ok("Saved ideas".into())));
+        assert_eq!(
+            fs::read_to_string(dir.join("ideas.md")).unwrap(),
+            "Nothing yet!"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn create_and_delete() {
+        let (mut app, dir) = app("create");
+        app.handle_key(KeyCode::Char('n').into());
+        testing::type_text("ideas", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(app.message, Some(Err("ideas already exists".into())));
+        for _ in 0.."deas".len() {
+            app.handle_key(KeyCode::Backspace.into());
+        }
+        testing::type_text("ntro", |key| app.handle_key(key));
+        assert_eq!(rows(&mut app, 80, 8)[7].trim_end(), "New note: intro");
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(app.names, ["groceries", "ideas", "intro"]);
+        assert_eq!(app.list.selected(), Some(2));
+        assert_eq!(app.focus, Focus::Editor);
+        testing::type_text("Hello", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Esc.into());
+        assert_eq!(fs::read_to_string(dir.join("intro.md")).unwrap(), "Hello");
+
+        app.handle_key(KeyCode::Up.into());
+        app.handle_key(KeyCode::Char('x').into());
+        assert_eq!(rows(&mut app, 80, 8)[7].trim_end(), "Delete ideas? <Y>/<N>");
+        app.handle_key(KeyCode::Char('y').into());
+        assert_eq!(app.names, ["groceries", "intro"]);
+        assert_eq!(app.editor.text(), "Hello");
+        assert!(!dir.join("ideas.md").exists());
+
+        app.handle_key(KeyCode::Char('q').into());
+        assert!(app.exit);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,334 @@
+//! 把 Markdown 渲染成终端中显示的文本。
+//!
+//! 只支持笔记中常用的部分：标题、段落、列表（包括任务列表）、引用、代码块、分隔线，以及行内的
+//! 粗体、斜体、删除线、代码和链接。段落中相邻的行合成一行，由显示的控件自动换行。没有闭合的
+//! 行内标记按原样显示，这样输入到一半时预览不会整段都变成粗体。
+
+use ratatui::prelude::*;
+
+fn heading_style(level: usize) -> Style {
+    match level {
+        1 => Style::new().light_magenta().bold().underlined(),
+        2 => Style::new().light_cyan().bold(),
+        _ => Style::new().bold(),
+    }
+}
+
+fn code_style() -> Style {
+    Style::new().yellow()
+}
+
+/// `#` 到 `######` 开头的标题，返回级别和标题文字。
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim_end()))
+}
+
+/// 三个或更多 `-`、`*` 或 `_` 组成的分隔线，中间可以有空格。
+fn is_rule(line: &str) -> bool {
+    let chars: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    chars.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|&mark| chars.iter().all(|&c| c == mark))
+}
+
+/// 列表项，返回缩进、显示的记号和内容。
+fn list_item(line: &str) -> Option<(usize, String, &str)> {
+    let indent = line.len() - line.trim_start().len();
+    let rest = line.trim_start();
+    let (marker, content) = if let Some(content) = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| rest.strip_prefix(marker))
+    {
+        if let Some(content) = content.strip_prefix("[ ] ") {
+            ("☐ ".to_string(), content)
+        } else if let Some(content) = content
+            .strip_prefix("[x] ")
+            .or_else(|| content.strip_prefix("[X] "))
+        {
+            ("☑ ".to_string(), content)
+        } else {
+            ("• ".to_string(), content)
+        }
+    } else {
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        let content = rest[digits..].strip_prefix(". ").filter(|_| digits > 0)?;
+        (format!("{}. ", &rest[..digits]), content)
+    };
+    Some((indent, marker, content))
+}
+
+/// 行内的标记。
+pub fn inline(text: &str, base: Style) -> Vec<Span<'static>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut spans = Vec::new();
+    let mut current = String::new();
+    let (mut bold, mut italic, mut strike) = (false, false, false);
+    let style = |bold: bool, italic: bool, strike: bool| {
+        let mut style = base;
+        if bold {
+            style = style.bold();
+        }
+        if italic {
+            style = style.italic();
+        }
+        if strike {
+            style = style.crossed_out();
+        }
+        style
+    };
+    let rest_contains = |from: usize, pattern: &str| -> bool {
+        chars[from..].iter().collect::<String>().contains(pattern)
+    };
+    let mut at = 0;
+    while at < chars.len() {
+        let c = chars[at];
+        let next = chars.get(at + 1).copied();
+        let previous = at.checked_sub(1).map(|back| chars[back]);
+        let flush = |current: &mut String, spans: &mut Vec<Span<'static>>, style: Style| {
+            if !current.is_empty() {
+                spans.push(Span::styled(std::mem::take(current), style));
+            }
+        };
+        match c {
+            '\\' if next.is_some_and(|next| next.is_ascii_punctuation()) => {
+                current.push(next.unwrap());
+                at += 2;
+                continue;
+            }
+            '`' => {
+                if let Some(end) = (at + 1..chars.len()).find(|&end| chars[end] == '`') {
+                    flush(&mut current, &mut spans, style(bold, italic, strike));
+                    let code: String = chars[at + 1..end].iter().collect();
+                    spans.push(Span::styled(code, code_style()));
+                    at = end + 1;
+                    continue;
+                }
+            }
+            '[' => {
+                // `[文字](地址)` 只显示文字。
+                let close = (at + 1..chars.len()).find(|&end| chars[end] == ']');
+                if let Some(close) = close.filter(|&close| chars.get(close + 1) == Some(&'(')) {
+                    if let Some(end) = (close + 2..chars.len()).find(|&end| chars[end] == ')') {
+                        flush(&mut current, &mut spans, style(bold, italic, strike));
+                        let label: String = chars[at + 1..close].iter().collect();
+                        spans.extend(inline(
+                            &label,
+                            style(bold, italic, strike).light_blue().underlined(),
+                        ));
+                        at = end + 1;
+                        continue;
+                    }
+                }
+            }
+            '*' | '_' | '~' if next == Some(c) => {
+                let marker: String = [c, c].iter().collect();
+                let open = if c == '~' { strike } else { bold };
+                let valid = if open {
+                    previous.is_some_and(|c| !c.is_whitespace())
+                } else {
+                    chars.get(at + 2).is_some_and(|c| !c.is_whitespace())
+                        && rest_contains(at + 2, &marker)
+                };
+                if valid {
+                    flush(&mut current, &mut spans, style(bold, italic, strike));
+                    if c == '~' {
+                        strike = !strike;
+                    } else {
+                        bold = !bold;
+                    }
+                    at += 2;
+                    continue;
+                }
+            }
+            '*' | '_' => {
+                // 单词中间的 `_` 不是标记，例如 `snake_case`。
+                let inside_word = c == '_'
+                    && previous.is_some_and(char::is_alphanumeric)
+                    && next.is_some_and(char::is_alphanumeric);
+                let valid = if italic {
+                    previous.is_some_and(|c| !c.is_whitespace())
+                } else {
+                    next.is_some_and(|c| !c.is_whitespace())
+                        && rest_contains(at + 1, &c.to_string())
+                };
+                if !inside_word && valid {
+                    flush(&mut current, &mut spans, style(bold, italic, strike));
+                    italic = !italic;
+                    at += 1;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        current.push(c);
+        at += 1;
+    }
+    if !current.is_empty() {
+        spans.push(Span::styled(current, style(bold, italic, strike)));
+    }
+    spans
+}
+
+/// 渲染整篇笔记。`width` 是分隔线的长度。
+pub fn render(text: &str, width: u16) -> Vec<Line<'static>> {
+    let mut lines: Vec<Line<'static>> = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut in_code = false;
+    let flush = |paragraph: &mut Vec<&str>, lines: &mut Vec<Line<'static>>| {
+        if !paragraph.is_empty() {
+            let joined = paragraph
+                .iter()
+                .map(|line| line.trim())
+                .collect::<Vec<_>>()
+                .join(" ");
+            lines.push(Line::from(inline(&joined, Style::new())));
+            paragraph.clear();
+        }
+    };
+    // 块之间只留一个空行。
+    let blank = |lines: &mut Vec<Line<'static>>| {
+        if lines.last().is_some_and(|line| line.width() > 0) {
+            lines.push(Line::default());
+        }
+    };
+
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            flush(&mut paragraph, &mut lines);
+            blank(&mut lines);
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            lines.push(Line::from(Span::styled(format!("  {line}"), code_style())));
+            continue;
+        }
+        if line.trim().is_empty() {
+            flush(&mut paragraph, &mut lines);
+            blank(&mut lines);
+            continue;
+        }
+        if let Some((level, title)) = heading(line) {
+            flush(&mut paragraph, &mut lines);
+            blank(&mut lines);
+            let style = heading_style(level);
+            lines.push(Line::from(inline(title, style)));
+            lines.push(Line::default());
+            continue;
+        }
+        if is_rule(line) {
+            flush(&mut paragraph, &mut lines);
+            lines.push(Line::from("─".repeat(usize::from(width)).dark_gray()));
+            continue;
+        }
+        if let Some(quote) = line.trim_start().strip_prefix('>') {
+            flush(&mut paragraph, &mut lines);
+            let mut spans = vec![Span::styled("▌ ", Style::new().dark_gray())];
+            spans.extend(inline(quote.trim_start(), Style::new().italic().dim()));
+            lines.push(Line::from(spans));
+            continue;
+        }
+        if let Some((indent, marker, content)) = list_item(line) {
+            flush(&mut paragraph, &mut lines);
+            let mut spans = vec![
+                Span::raw(" ".repeat(indent)),
+                Span::styled(marker, Style::new().cyan()),
+            ];
+            spans.extend(inline(content, Style::new()));
+            lines.push(Line::from(spans));
+            continue;
+        }
+        paragraph.push(line);
+    }
+    flush(&mut paragraph, &mut lines);
+    if lines.last().is_some_and(|line| line.width() == 0) {
+        lines.pop();
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(lines: &[Line]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn blocks() {
+        let text = "# Groceries\nBuy these\nbefore *Friday*.\n\n\n- milk\n  - [x] oat\n1. eggs\n\
+                    > remember the bags\n***\n```rust\nlet x = 1;\n```\n## Done ##";
+        let lines = render(text, 5);
+        assert_eq!(
+            plain(&lines),
+            [
+                "Groceries",
+                "",
+                "Buy these before Friday.",
+                "",
+                "• milk",
+                "  ☑ oat",
+                "1. eggs",
+                "▌ remember the bags",
+                "─────",
+                "",
+                "  let x = 1;",
+                "",
+                "Done",
+            ]
+        );
+        assert_eq!(lines[0].spans[0].style, heading_style(1));
+        assert_eq!(lines[12].spans[0].style, heading_style(2));
+        assert_eq!(lines[10].spans[0].style, code_style());
+        // `#hashtag` 和 `1.5` 不是标题和列表。
+        assert_eq!(plain(&render("#hashtag\n1.5 kg", 5)), ["#hashtag 1.5 kg"]);
+    }
+
+    #[test]
+    fn inline_markup() {
+        let spans = inline(
+            "a **bold** _it_ ~~no~~ `x*y` [site](https://example.com)",
+            Style::new(),
+        );
+        let parts: Vec<(&str, Style)> = spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style))
+            .collect();
+        assert_eq!(
+            parts,
+            [
+                ("a ", Style::new()),
+                ("bold", Style::new().bold()),
+                (" ", Style::new()),
+                ("it", Style::new().italic()),
+                (" ", Style::new()),
+                ("no", Style::new().crossed_out()),
+                (" ", Style::new()),
+                ("x*y", code_style()),
+                (" ", Style::new()),
+                ("site", Style::new().light_blue().underlined()),
+            ]
+        );
+        // 没有闭合的标记、单词中的下划线和转义按原样显示。
+        let text = |text: &str| -> String {
+            inline(text, Style::new())
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect()
+        };
+        assert_eq!(text("2 * 3 and **half"), "2 * 3 and **half");
+        assert_eq!(text("snake_case_name"), "snake_case_name");
+        assert_eq!(text(r"\*not italic\*"), "*not italic*");
+        assert_eq!(inline("**a**", Style::new())[0].style, Style::new().bold());
+    }
+}
@@ -0,0 +1,120 @@
+//! 笔记保存在一个目录中，每篇笔记是一个 `.md` 文件，文件名（不含扩展名）就是笔记的名字。
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{eyre::WrapErr, Result};
+use ratatui_common::files;
+
+const EXTENSION: &str = "md";
+
+#[derive(Debug)]
+pub struct Notebook {
+    dir: PathBuf,
+}
+
+impl Notebook {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.{EXTENSION}"))
+    }
+
+    /// 目录中所有笔记的名字，按字母顺序。目录还不存在时为空。
+    pub fn names(&self) -> Result<Vec<String>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(err).wrap_err_with(|| format!("cannot list {}", self.dir.display()))
+            }
+        };
+        let mut names = Vec::new();
+        for entry in entries {
+            let path = entry
+                .wrap_err_with(|| format!("cannot list {}", self.dir.display()))?
+                .path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == EXTENSION)
+            {
+                if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names.sort_by_key(|name| name.to_lowercase());
+        Ok(names)
+    }
+
+    pub fn read(&self, name: &str) -> Result<String> {
+        let path = self.path(name);
+        fs::read_to_string(&path).wrap_err_with(|| format!("cannot read {}", path.display()))
+    }
+
+    /// 保存笔记的内容。
+    pub fn write(&self, name: &str, text: &str) -> Result<()> {
+        let path = self.path(name);
+        files::write_atomic(&path, text)
+            .wrap_err_with(|| format!("cannot write {}", path.display()))
+    }
+
+    /// 新建一篇空的笔记。名字不能为空、不能包含路径分隔符，也不能和已有的笔记重名。
+    pub fn create(&self, name: &str) -> Result<(), String> {
+        if name.is_empty() {
+            return Err("the note needs a name".into());
+        }
+        if name.starts_with('.') || name.contains(['/', '\\']) {
+            return Err(format!("{name:?} cannot be used as a file name"));
+        }
+        if self.path(name).exists() {
+            return Err(format!("{name} already exists"));
+        }
+        self.write(name, "").map_err(|error| format!("{error:#}"))
+    }
+
+    pub fn delete(&self, name: &str) -> Result<()> {
+        let path = self.path(name);
+        fs::remove_file(&path).wrap_err_with(|| format!("cannot delete {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    #[test]
+    fn notes_in_a_directory() {
+        let dir = env::temp_dir().join(format!("notes-demo-notebook-{}", process::id()));
+        let notebook = Notebook::new(dir.clone());
+        assert!(notebook.names().unwrap().is_empty());
+
+        notebook.create("todo").unwrap();
+        notebook.write("Ideas", "# Ideas\n").unwrap();
+        fs::write(dir.join("image.png"), "").unwrap();
+        assert_eq!(notebook.names().unwrap(), ["Ideas", "todo"]);
+        assert_eq!(notebook.read("Ideas").unwrap(), "# Ideas\n");
+        assert_eq!(notebook.read("todo").unwrap(), "");
+
+        assert_eq!(notebook.create("todo"), Err("todo already exists".into()));
+        assert_eq!(
+            notebook.create("../escape"),
+            Err("\"../escape\" cannot be used as a file name".into())
+        );
+        assert_eq!(notebook.create(""), Err("the note needs a name".into()));
+
+        notebook.delete("todo").unwrap();
+        assert_eq!(notebook.names().unwrap(), ["Ideas"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,218 @@
+//! 二维码演示：在输入框中输入的文字实时编码成二维码，按终端的大小选择最大的放大倍数。
+//! 二维码控件来自 `ratatui_common::qr`。
+//!
+//! 按键：`Tab` 切换纠错等级，`Esc` 退出。
+
+use color_eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Paragraph, Wrap},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    qr::{EcLevel, QrCode, QrError},
+    terminal,
+    text_input::Input,
+};
+
+const LEVELS: [EcLevel; 4] = [EcLevel::L, EcLevel::M, EcLevel::Q, EcLevel::H];
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new("https://ratatui.rs").run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+fn level_name(level: EcLevel) -> &'static str {
+    match level {
+        EcLevel::L => "L (7%)",
+        EcLevel::M => "M (15%)",
+        EcLevel::Q => "Q (25%)",
+        EcLevel::H => "H (30%)",
+    }
+}
+
+struct App {
+    text: Input,
+    level: usize,
+    /// 文字为空时是 `None`。
+    code: Option<Result<QrCode, QrError>>,
+    exit: bool,
+}
+
+impl App {
+    fn new(text: &str) -> Self {
+        let mut app = Self {
+            text: Input::with_value(text),
+            level: 1,
+            code: None,
+            exit: false,
+        };
+        app.encode();
+        app
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| frame.render_widget(&*self, frame.size()))?;
+            if let Event::Key(key) = events.read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn encode(&mut self) {
+        let text = self.text.value();
+        self.code = (!text.is_empty()).then(|| QrCode::new(text, LEVELS[self.level]));
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => self.exit = true,
+            KeyCode::Char('c') if key.modifiers == KeyModifiers::CONTROL => self.exit = true,
+            KeyCode::Tab => {
+                self.level = (self.level + 1) % LEVELS.len();
+                self.encode();
+            }
+            KeyCode::BackTab => {
+                self.level = (self.level + LEVELS.len() - 1) % LEVELS.len();
+                self.encode();
+            }
+            _ => {
+                if self.text.handle_key_event(key) {
+                    self.encode();
+                }
+            }
+        }
+    }
+
+    fn status_line(&self, area: Rect) -> Line<'static> {
+        let level = level_name(LEVELS[self.level]);
+        match &self.code {
+            None => Line::from(format!("Error correction {level}  Type some text")),
+            Some(Err(QrError::DataTooLong)) => Line::from(
+                format!(
+                    "{} bytes is too long for a QR code at level {level}",
+                    self.text.value().len()
+                )
+                .red(),
+            ),
+            Some(Err(error)) => Line::from(error.to_string().red()),
+            Some(Ok(code)) => {
+                let scale = code.scale(area);
+                let scale = if scale == 0 {
+                    let (width, height) = code.size(1);
+                    format!("needs {width}×{height} cells").red()
+                } else {
+                    format!("scale {scale}×").into()
+                };
+                Line::from(vec![
+                    format!(
+                        "Version {}  Error correction {level}  {width}×{width} modules  ",
+                        code.version(),
+                        width = code.width()
+                    )
+                    .into(),
+                    scale,
+                ])
+            }
+        }
+    }
+}
+
+impl Widget for &App {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [input_area, code_area, status_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        Paragraph::new(self.text.line(" ", Style::new()))
+            .block(
+                Block::bordered()
+                    .title(" Text ")
+                    .title_bottom(Line::from(" Error correction <Tab> Quit <Esc> ").right_aligned())
+                    .border_style(Style::new().green()),
+            )
+            .render(input_area, buf);
+
+        match &self.code {
+            Some(Ok(code)) if code.scale(code_area) > 0 => code.render(code_area, buf),
+            Some(Ok(_)) => Paragraph::new("Enlarge the terminal to show the code")
+                .centered()
+                .wrap(Wrap { trim: true })
+                .render(code_area, buf),
+            _ => {}
+        }
+        Paragraph::new(self.status_line(code_area)).render(status_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn rows(app: &App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    #[test]
+    fn encode_while_typing() {
+        let mut app = App::new("");
+        assert!(app.code.is_none());
+        for c in "hello".chars() {
+            app.handle_key(KeyCode::Char(c).into());
+        }
+        let code = app.code.as_ref().unwrap().as_ref().unwrap();
+        assert_eq!((code.version(), code.level()), (1, EcLevel::M));
+
+        app.handle_key(KeyCode::BackTab.into());
+        app.handle_key(KeyCode::BackTab.into());
+        let code = app.code.as_ref().unwrap().as_ref().unwrap();
+        assert_eq!(code.level(), EcLevel::H);
+
+        app.text = Input::with_value("x".repeat(2000));
+        app.encode();
+        assert_eq!(
+            app.status_line(Rect::new(0, 0, 80, 20)).to_string(),
+            "2000 bytes is too long for a QR code at level H (30%)"
+        );
+    }
+
+    #[test]
+    fn scale_to_terminal() {
+        let app = App::new("hello");
+        let small = rows(&app, 60, 18);
+        assert_eq!(
+            small[17].trim_end(),
+            "Version 1  Error correction M (15%)  21×21 modules  scale 1×"
+        );
+        assert!(small[4].contains("█▀▀▀▀▀█"));
+        assert!(small[14].starts_with("                   ▀▀▀▀▀▀▀ "));
+
+        let tiny = rows(&app, 80, 10);
+        assert_eq!(
+            tiny[9].trim_end(),
+            "Version 1  Error correction M (15%)  21×21 modules  needs 25×13 cells"
+        );
+        assert!(tiny[3].contains("Enlarge the terminal"));
+
+        let big = rows(&app, 120, 40);
+        assert!(big[39].trim_end().ends_with("scale 2×"));
+    }
+}
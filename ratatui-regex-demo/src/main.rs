@@ -0,0 +1,341 @@
+//! 正则表达式测试演示：编辑表达式和样例文本，实时高亮所有匹配，并列出每个匹配的捕获组。
+//! 表达式无效时显示 `regex` 给出的错误信息。
+//!
+//! 按键：`Tab` 在表达式和样例文本之间切换焦点，`Esc` 退出。
+//! 也是处理文本输入的参考：单行输入框和多行编辑框都来自 `ratatui_common`。
+
+use color_eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Paragraph, Wrap},
+};
+use ratatui_common::{
+    editor::Editor,
+    events::{EventSource, TerminalEvents},
+    terminal,
+    text_input::Input,
+};
+use regex::Regex;
+
+const SAMPLE: &str = "Contact alice@example.com or bob@example.org.\n\
+                      Dates: 2024-05-01, 2024-12-31\n\
+                      Phone: +1 555 0100";
+
+/// 相邻的匹配交替使用这两种样式，紧挨着的两个匹配也能区分开。
+const MATCH_STYLES: [Style; 2] = [
+    Style::new().fg(Color::Black).bg(Color::Yellow),
+    Style::new().fg(Color::Black).bg(Color::Cyan),
+];
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result =
+        App::new(r"(\w+)@(?P<domain>[\w.]+\w)", SAMPLE).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Pattern,
+    Sample,
+}
+
+struct App {
+    pattern: Input,
+    /// 表达式为空时是 `None`。
+    regex: Option<Result<Regex, regex::Error>>,
+    sample: Editor,
+    focus: Focus,
+    exit: bool,
+}
+
+impl App {
+    fn new(pattern: &str, sample: &str) -> Self {
+        let mut app = Self {
+            pattern: Input::with_value(pattern),
+            regex: None,
+            sample: Editor::new(sample),
+            focus: Focus::Pattern,
+            exit: false,
+        };
+        app.compile();
+        app
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| frame.render_widget(&*self, frame.size()))?;
+            if let Event::Key(key) = events.read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn compile(&mut self) {
+        let pattern = self.pattern.value();
+        self.regex = (!pattern.is_empty()).then(|| Regex::new(pattern));
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => self.exit = true,
+            KeyCode::Char('c') if key.modifiers == KeyModifiers::CONTROL => self.exit = true,
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.focus = match self.focus {
+                    Focus::Pattern => Focus::Sample,
+                    Focus::Sample => Focus::Pattern,
+                }
+            }
+            _ => match self.focus {
+                Focus::Pattern => {
+                    if self.pattern.handle_key_event(key) {
+                        self.compile();
+                    }
+                }
+                Focus::Sample => {
+                    self.sample.handle_key_event(key);
+                }
+            },
+        }
+    }
+
+    fn regex(&self) -> Option<&Regex> {
+        self.regex.as_ref()?.as_ref().ok()
+    }
+
+    fn border(&self, focus: Focus) -> Style {
+        if self.focus == focus {
+            Style::new().green()
+        } else {
+            Style::new()
+        }
+    }
+}
+
+impl Widget for &App {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [pattern_area, main_area] =
+            Layout::vertical([Constraint::Length(3), Constraint::Fill(1)]).areas(area);
+        let [sample_area, matches_area] =
+            Layout::horizontal([Constraint::Fill(3), Constraint::Fill(2)]).areas(main_area);
+
+        let text = self.sample.text();
+        let matches: Vec<regex::Captures> = self
+            .regex()
+            .map(|regex| regex.captures_iter(&text).collect())
+            .unwrap_or_default();
+
+        // 只有获得焦点的输入框显示光标。
+        let pattern_line = if self.focus == Focus::Pattern {
+            self.pattern.line(" ", Style::new())
+        } else {
+            Line::from(format!(" {}", self.pattern.value()))
+        };
+        let title = match &self.regex {
+            Some(Ok(_)) => format!(" Pattern ({} matches) ", matches.len()),
+            Some(Err(_)) => " Pattern (invalid) ".into(),
+            None => " Pattern ".into(),
+        };
+        Paragraph::new(pattern_line)
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .border_style(self.border(Focus::Pattern)),
+            )
+            .render(pattern_area, buf);
+
+        let ranges: Vec<(usize, usize)> = matches
+            .iter()
+            .map(|captures| {
+                let whole = captures.get(0).expect("group 0 always matches");
+                (whole.start(), whole.end())
+            })
+            .collect();
+        let cursor = (self.focus == Focus::Sample).then(|| self.sample.cursor());
+        let mut offset = 0;
+        let lines: Vec<Line> = self
+            .sample
+            .lines()
+            .iter()
+            .enumerate()
+            .map(|(row, line)| {
+                let cursor = cursor.filter(|(cursor_row, _)| *cursor_row == row);
+                let rendered = highlight(line, offset, &ranges, cursor.map(|(_, column)| column));
+                offset += line.len() + 1;
+                rendered
+            })
+            .collect();
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title(" Sample ")
+                    .title_bottom(Line::from(" Focus <Tab> Quit <Esc> ").centered())
+                    .border_style(self.border(Focus::Sample)),
+            )
+            .render(sample_area, buf);
+
+        match &self.regex {
+            Some(Err(error)) => Paragraph::new(error.to_string())
+                .red()
+                .block(Block::bordered().title(" Error "))
+                .render(matches_area, buf),
+            _ => Paragraph::new(capture_lines(&matches, self.regex()))
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(" Captures "))
+                .render(matches_area, buf),
+        }
+    }
+}
+
+/// 一行样例文本，匹配的部分高亮。`offset` 是这一行在整个文本中的字节位置，`cursor` 是光标之前的字符数。
+fn highlight(
+    line: &str,
+    offset: usize,
+    ranges: &[(usize, usize)],
+    cursor: Option<usize>,
+) -> Line<'static> {
+    let mut styles: Vec<(char, Style)> = line
+        .char_indices()
+        .map(|(at, c)| {
+            let at = offset + at;
+            let style = ranges
+                .iter()
+                .position(|&(start, end)| start <= at && at < end)
+                .map_or(Style::new(), |index| MATCH_STYLES[index % 2]);
+            (c, style)
+        })
+        .collect();
+    if let Some(column) = cursor {
+        if column == styles.len() {
+            styles.push((' ', Style::new()));
+        }
+        styles[column].1 = styles[column].1.reversed();
+    }
+    // 样式相同的相邻字符合并为一个片段。
+    let mut spans: Vec<Span> = Vec::new();
+    let mut text = String::new();
+    let mut current = None;
+    for (c, style) in styles {
+        if let Some(previous) = current.filter(|&previous| previous != style) {
+            spans.push(Span::styled(std::mem::take(&mut text), previous));
+        }
+        current = Some(style);
+        text.push(c);
+    }
+    if let Some(style) = current {
+        spans.push(Span::styled(text, style));
+    }
+    Line::from(spans)
+}
+
+/// 每个匹配一行，后面是各个捕获组，命名的组同时显示名称。没有参与匹配的组显示为 `-`。
+fn capture_lines(matches: &[regex::Captures], regex: Option<&Regex>) -> Vec<Line<'static>> {
+    let Some(regex) = regex else {
+        return vec![Line::raw("Type a pattern to start.").dim()];
+    };
+    if matches.is_empty() {
+        return vec![Line::raw("No matches.").dim()];
+    }
+    let names: Vec<Option<&str>> = regex.capture_names().collect();
+    let mut lines = Vec::new();
+    for (index, captures) in matches.iter().enumerate() {
+        let whole = captures.get(0).expect("group 0 always matches");
+        lines.push(Line::from(vec![
+            format!("#{} ", index + 1).bold(),
+            Span::styled(format!("{:?}", whole.as_str()), MATCH_STYLES[index % 2]),
+            format!(" {}..{}", whole.start(), whole.end()).dim(),
+        ]));
+        for (group, name) in names.iter().enumerate().skip(1) {
+            let label = match name {
+                Some(name) => format!("  {group} {name}: "),
+                None => format!("  {group}: "),
+            };
+            let value = captures
+                .get(group)
+                .map_or_else(|| "-".into(), |m| format!("{:?}", m.as_str()));
+            lines.push(Line::from(vec![label.dim(), value.into()]));
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn rows(app: &App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    #[test]
+    fn highlight_matches() {
+        let line = highlight("a1b22", 10, &[(11, 12), (13, 15)], None);
+        let spans: Vec<(&str, Style)> = line
+            .spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style))
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                ("a", Style::new()),
+                ("1", MATCH_STYLES[0]),
+                ("b", Style::new()),
+                ("22", MATCH_STYLES[1]),
+            ]
+        );
+        // 光标在行尾时显示为一个反色的空格。
+        let line = highlight("ab", 0, &[], Some(2));
+        assert_eq!(line.spans[1].content, " ");
+        assert_eq!(line.spans[1].style, Style::new().reversed());
+    }
+
+    #[test]
+    fn render_captures() {
+        let app = App::new(r"(\w)@(?P<host>\w)(x)?", "a@b c@d");
+        assert_eq!(
+            rows(&app, 60, 9),
+            [
+                "┌ Pattern (2 matches) ─────────────────────────────────────┐",
+                "│ (\\w)@(?P<host>\\w)(x)?                                    │",
+                "└──────────────────────────────────────────────────────────┘",
+                "┌ Sample ──────────────────────────┐┌ Captures ────────────┐",
+                "│a@b c@d                           ││#1 \"a@b\" 0..3         │",
+                "│                                  ││  1: \"a\"              │",
+                "│                                  ││  2 host: \"b\"         │",
+                "│                                  ││  3: -                │",
+                "└───── Focus <Tab> Quit <Esc> ─────┘└──────────────────────┘",
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        let mut app = App::new("a", "aaa");
+        app.handle_key(KeyCode::Char('(').into());
+        assert!(matches!(app.regex, Some(Err(_))));
+        let rows = rows(&app, 60, 8);
+        assert!(rows[0].starts_with("┌ Pattern (invalid) "));
+        assert!(rows[3].contains("┐┌ Error ─"));
+
+        // 切换焦点后按键编辑样例文本，表达式不变。
+        app.handle_key(KeyCode::Backspace.into());
+        app.handle_key(KeyCode::Tab.into());
+        app.handle_key(KeyCode::Char('b').into());
+        assert_eq!(app.pattern.value(), "a");
+        assert_eq!(app.sample.text(), "aaab");
+    }
+}
@@ -0,0 +1,82 @@
+//! 从 TOML 文件加载订阅列表。
+//!
+//! ```toml
+//! # 刷新间隔，单位为秒，默认 900
+//! interval_secs = 600
+//!
+//! [[feed]]
+//! name = "This Week in Rust"
+//! url = "https://this-week-in-rust.org/rss.xml"
+//!
+//! [[feed]]
+//! url = "https://blog.rust-lang.org/feed.xml"
+//! ```
+
+use std::{fs, path::Path, time::Duration};
+
+use color_eyre::{eyre::WrapErr, Result};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub interval_secs: u64,
+    #[serde(rename = "feed")]
+    pub feeds: Vec<FeedConfig>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            interval_secs: 900,
+            feeds: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeedConfig {
+    /// 侧栏中显示的名称，没有设置时使用订阅源自己的标题。
+    pub name: Option<String>,
+    pub url: String,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .wrap_err_with(|| format!("reading config file {} failed", path.display()))?;
+        toml::from_str(&text)
+            .wrap_err_with(|| format!("parsing config file {} failed", path.display()))
+    }
+
+    pub fn interval(&self) -> Duration {
+        // 太短的间隔对订阅源不友好。
+        Duration::from_secs(self.interval_secs.max(60))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_feeds() {
+        let config: Config = toml::from_str(
+            r#"
+            [[feed]]
+            name = "Rust"
+            url = "https://blog.rust-lang.org/feed.xml"
+
+            [[feed]]
+            url = "https://example.com/atom.xml"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.interval(), Duration::from_secs(900));
+        assert_eq!(config.feeds.len(), 2);
+        assert_eq!(config.feeds[0].name.as_deref(), Some("Rust"));
+        assert_eq!(config.feeds[1].name, None);
+        assert!(toml::from_str::<Config>("feeds = []").is_err());
+    }
+}
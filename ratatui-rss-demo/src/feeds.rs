@@ -0,0 +1,154 @@
+//! 在后台线程中定期下载和解析订阅源。RSS 和 Atom 都由 `feed-rs` 解析。
+
+use std::{
+    sync::mpsc::{self, RecvTimeoutError, Sender},
+    thread,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+
+const USER_AGENT: &str = concat!("ratatui-rss-demo/", env!("CARGO_PKG_VERSION"));
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Article {
+    /// RSS 的 `guid` 或 Atom 的 `id`；都没有时 `feed-rs` 根据链接和标题生成，刷新后保持不变。
+    pub id: String,
+    pub title: String,
+    pub link: Option<String>,
+    pub published: Option<DateTime<Utc>>,
+    /// 正文的 HTML，没有正文时是摘要。
+    pub html: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feed {
+    pub title: Option<String>,
+    /// 最新的在前。
+    pub articles: Vec<Article>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Update {
+    /// 开始下载第几个订阅源。
+    Fetching(usize),
+    Fetched {
+        feed: usize,
+        result: Result<Feed, String>,
+    },
+}
+
+pub fn parse(xml: &[u8]) -> Result<Feed, feed_rs::parser::ParseFeedError> {
+    let feed = feed_rs::parser::parse(xml)?;
+    let mut articles: Vec<Article> = feed
+        .entries
+        .into_iter()
+        .map(|entry| Article {
+            id: entry.id,
+            title: entry
+                .title
+                .map_or_else(|| "(untitled)".into(), |title| title.content),
+            link: entry.links.into_iter().next().map(|link| link.href),
+            published: entry.published.or(entry.updated),
+            html: entry
+                .content
+                .and_then(|content| content.body)
+                .or(entry.summary.map(|summary| summary.content))
+                .unwrap_or_default(),
+        })
+        .collect();
+    // 没有日期的文章保持原来的顺序，排在最后。
+    articles.sort_by_key(|article| std::cmp::Reverse(article.published));
+    Ok(Feed {
+        title: feed.title.map(|title| title.content),
+        articles,
+    })
+}
+
+fn fetch(url: &str) -> Result<Feed, Box<dyn std::error::Error>> {
+    let xml = ureq::get(url)
+        .header("User-Agent", USER_AGENT)
+        .call()?
+        .body_mut()
+        .read_to_vec()?;
+    Ok(parse(&xml)?)
+}
+
+/// 启动后台线程，依次下载 `urls`，然后等待 `interval` 或者刷新请求。
+/// 返回发送刷新请求的一端，界面退出（接收端被丢弃）后线程结束。
+pub fn spawn(urls: Vec<String>, interval: Duration, updates: Sender<Update>) -> Sender<()> {
+    let (refresh, requests) = mpsc::channel();
+    thread::spawn(move || loop {
+        for (feed, url) in urls.iter().enumerate() {
+            if updates.send(Update::Fetching(feed)).is_err() {
+                return;
+            }
+            let result = fetch(url).map_err(|error| error.to_string());
+            if updates.send(Update::Fetched { feed, result }).is_err() {
+                return;
+            }
+        }
+        match requests.recv_timeout(interval) {
+            Ok(()) | Err(RecvTimeoutError::Timeout) => {
+                // 连续按了几次刷新时只刷新一次。
+                while requests.try_recv().is_ok() {}
+            }
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+    });
+    refresh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rss_and_atom() {
+        let rss = br#"<?xml version="1.0"?>
+            <rss version="2.0"><channel>
+              <title>Example</title>
+              <item>
+                <title>Older</title>
+                <guid>older</guid>
+                <link>https://example.com/older</link>
+                <pubDate>Mon, 01 Jan 2024 00:00:00 GMT</pubDate>
+                <description>&lt;p&gt;Hello&lt;/p&gt;</description>
+              </item>
+              <item>
+                <title>Newer</title>
+                <guid>newer</guid>
+                <pubDate>Tue, 02 Jan 2024 00:00:00 GMT</pubDate>
+              </item>
+            </channel></rss>"#;
+        let feed = parse(rss).unwrap();
+        assert_eq!(feed.title.as_deref(), Some("Example"));
+        let titles: Vec<&str> = feed.articles.iter().map(|a| a.title.as_str()).collect();
+        assert_eq!(titles, ["Newer", "Older"]);
+        assert_eq!(feed.articles[1].id, "older");
+        assert_eq!(feed.articles[1].html, "<p>Hello</p>");
+        assert_eq!(
+            feed.articles[1].link.as_deref(),
+            Some("https://example.com/older")
+        );
+
+        let atom = br#"<?xml version="1.0"?>
+            <feed xmlns="http://www.w3.org/2005/Atom">
+              <title>Atom</title>
+              <id>urn:feed</id>
+              <updated>2024-01-01T00:00:00Z</updated>
+              <entry>
+                <id>urn:entry</id>
+                <title>Entry</title>
+                <updated>2024-01-01T00:00:00Z</updated>
+                <content type="html">&lt;b&gt;Body&lt;/b&gt;</content>
+              </entry>
+            </feed>"#;
+        let feed = parse(atom).unwrap();
+        assert_eq!(feed.articles[0].id, "urn:entry");
+        assert_eq!(feed.articles[0].html, "<b>Body</b>");
+        assert!(feed.articles[0].published.is_some());
+
+        assert!(parse(b"not a feed").is_err());
+    }
+}
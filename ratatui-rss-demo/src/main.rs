@@ -0,0 +1,581 @@
+//! RSS / Atom 阅读器演示：定期下载配置的订阅源，左侧是订阅列表和未读数，中间是文章列表，
+//! 右侧阅读所选文章（HTML 转成纯文本）。
+//!
+//! 订阅列表默认从配置目录中的 `feeds.toml` 读取，格式见 `config` 模块；也可以直接在命令行上
+//! 给出 URL。已读记录保存在状态目录中的 `read.json`，下次启动时恢复。
+//!
+//! 按键：`Tab` 切换焦点，`Up` / `Down` 选择或滚动，`Enter` 打开文章（同时标记为已读），
+//! `m` 切换已读状态，`r` 立即刷新，`q` 退出。
+
+use std::{
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, Sender},
+    time::Duration,
+};
+
+use clap::Parser;
+use color_eyre::{eyre::eyre, Result};
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use directories::ProjectDirs;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, List, ListState, Paragraph, Wrap},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+};
+
+use crate::{
+    config::{Config, FeedConfig},
+    feeds::{Article, Update},
+    read::ReadSet,
+};
+
+mod config;
+mod feeds;
+mod read;
+
+/// 等待按键的最长时间，之后处理后台送回的结果。
+const REFRESH: Duration = Duration::from_millis(100);
+
+const APPLICATION: &str = "ratatui-rss-demo";
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 订阅列表，默认是配置目录中的 `feeds.toml`
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+    /// 已读记录，默认是状态目录中的 `read.json`
+    #[arg(long, value_name = "FILE")]
+    state: Option<PathBuf>,
+    /// 订阅源的 URL，给出时不读取配置文件
+    #[arg(value_name = "URL")]
+    urls: Vec<String>,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let project = ProjectDirs::from("", "", APPLICATION);
+    let config = if cli.urls.is_empty() {
+        let path = cli
+            .config
+            .or_else(|| Some(project.as_ref()?.config_dir().join("feeds.toml")))
+            .ok_or_else(|| eyre!("no config directory, pass --config"))?;
+        Config::load(&path)?
+    } else {
+        Config {
+            feeds: cli
+                .urls
+                .into_iter()
+                .map(|url| FeedConfig { name: None, url })
+                .collect(),
+            ..Config::default()
+        }
+    };
+    let state = cli
+        .state
+        .or_else(|| {
+            let project = project.as_ref()?;
+            let dir = project.state_dir().unwrap_or(project.data_local_dir());
+            Some(dir.join("read.json"))
+        })
+        .ok_or_else(|| eyre!("no state directory, pass --state"))?;
+    let read = ReadSet::load(state)?;
+
+    let (sender, updates) = mpsc::channel();
+    let urls = config.feeds.iter().map(|feed| feed.url.clone()).collect();
+    let refresh = feeds::spawn(urls, config.interval(), sender);
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new(&config, read, refresh, updates).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Feeds,
+    Articles,
+    Reader,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Status {
+    /// 还没有开始下载。
+    Pending,
+    Fetching,
+    Fetched,
+    Failed(String),
+}
+
+struct FeedState {
+    /// 配置中的名称，没有时是订阅源的标题，还没有下载时是 URL。
+    name: String,
+    named: bool,
+    status: Status,
+    articles: Vec<Article>,
+}
+
+struct App {
+    feeds: Vec<FeedState>,
+    read: ReadSet,
+    refresh: Sender<()>,
+    updates: Receiver<Update>,
+    focus: Focus,
+    feed_list: ListState,
+    article_list: ListState,
+    /// 正在阅读的文章，按 ID 记录，刷新后文章的位置可能变化。
+    reading: Option<String>,
+    /// 正在阅读的文章按宽度换行后的文本，宽度变化时重新转换。
+    text: Option<(u16, String)>,
+    scroll: u16,
+    /// 最近一次操作的结果，`Err` 显示为红色。
+    message: Option<Result<String, String>>,
+    exit: bool,
+}
+
+impl App {
+    fn new(config: &Config, read: ReadSet, refresh: Sender<()>, updates: Receiver<Update>) -> Self {
+        let feeds = config
+            .feeds
+            .iter()
+            .map(|feed| FeedState {
+                name: feed.name.clone().unwrap_or_else(|| feed.url.clone()),
+                named: feed.name.is_some(),
+                status: Status::Pending,
+                articles: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+        Self {
+            feed_list: ListState::default().with_selected((!feeds.is_empty()).then_some(0)),
+            feeds,
+            read,
+            refresh,
+            updates,
+            focus: Focus::Feeds,
+            article_list: ListState::default(),
+            reading: None,
+            text: None,
+            scroll: 0,
+            message: None,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            while let Ok(update) = self.updates.try_recv() {
+                self.update(update);
+            }
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut()))?;
+            if events.poll(REFRESH)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key.code);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, update: Update) {
+        match update {
+            Update::Fetching(index) => self.feeds[index].status = Status::Fetching,
+            Update::Fetched {
+                feed: index,
+                result,
+            } => {
+                let feed = &mut self.feeds[index];
+                match result {
+                    Ok(fetched) => {
+                        if let (false, Some(title)) = (feed.named, fetched.title) {
+                            feed.name = title;
+                        }
+                        feed.status = Status::Fetched;
+                        // 刷新后仍然选中同一篇文章。
+                        let selected = self.selected_article().map(|article| article.id.clone());
+                        let feed = &mut self.feeds[index];
+                        feed.articles = fetched.articles;
+                        if self.feed_list.selected() == Some(index) {
+                            let position = selected
+                                .and_then(|id| {
+                                    feed.articles.iter().position(|article| article.id == id)
+                                })
+                                .or((!feed.articles.is_empty()).then_some(0));
+                            self.article_list.select(position);
+                        }
+                    }
+                    // 下载失败时保留之前的文章。
+                    Err(error) => feed.status = Status::Failed(error),
+                }
+            }
+        }
+    }
+
+    fn selected_feed(&self) -> Option<&FeedState> {
+        self.feeds.get(self.feed_list.selected()?)
+    }
+
+    fn selected_article(&self) -> Option<&Article> {
+        self.selected_feed()?
+            .articles
+            .get(self.article_list.selected()?)
+    }
+
+    /// 正在阅读的文章，可能在任何一个订阅源中。
+    fn article(&self) -> Option<&Article> {
+        let id = self.reading.as_ref()?;
+        self.feeds
+            .iter()
+            .flat_map(|feed| &feed.articles)
+            .find(|article| &article.id == id)
+    }
+
+    fn unread(&self, feed: &FeedState) -> usize {
+        feed.articles
+            .iter()
+            .filter(|article| !self.read.contains(&article.id))
+            .count()
+    }
+
+    fn handle_key(&mut self, code: KeyCode) {
+        match (self.focus, code) {
+            (_, KeyCode::Char('q')) => self.exit = true,
+            (_, KeyCode::Char('r')) => {
+                let _ = self.refresh.send(());
+                self.message = Some(Ok("Refreshing…".into()));
+            }
+            (_, KeyCode::Char('m')) => self.toggle_read(),
+            (Focus::Feeds, KeyCode::Tab) | (Focus::Reader, KeyCode::BackTab) => {
+                self.focus = Focus::Articles
+            }
+            (Focus::Articles, KeyCode::Tab) if self.reading.is_some() => self.focus = Focus::Reader,
+            (Focus::Articles | Focus::Reader, KeyCode::Tab | KeyCode::BackTab) => {
+                self.focus = Focus::Feeds
+            }
+            (Focus::Feeds, KeyCode::Up | KeyCode::Down) => {
+                select(&mut self.feed_list, self.feeds.len(), code);
+                let empty = self.selected_feed().is_none_or(|f| f.articles.is_empty());
+                self.article_list.select((!empty).then_some(0));
+            }
+            (Focus::Feeds, KeyCode::Enter) => self.focus = Focus::Articles,
+            (Focus::Articles, KeyCode::Up | KeyCode::Down) => {
+                let len = self.selected_feed().map_or(0, |feed| feed.articles.len());
+                select(&mut self.article_list, len, code);
+            }
+            (Focus::Articles, KeyCode::Enter) => self.open(),
+            (Focus::Reader, KeyCode::Up) => self.scroll = self.scroll.saturating_sub(1),
+            (Focus::Reader, KeyCode::Down) => self.scroll = self.scroll.saturating_add(1),
+            (Focus::Reader, KeyCode::PageUp) => self.scroll = self.scroll.saturating_sub(10),
+            (Focus::Reader, KeyCode::PageDown) => self.scroll = self.scroll.saturating_add(10),
+            (Focus::Reader, KeyCode::Esc) => self.focus = Focus::Articles,
+            _ => {}
+        }
+    }
+
+    fn open(&mut self) {
+        let Some(id) = self.selected_article().map(|article| article.id.clone()) else {
+            return;
+        };
+        self.set_read(&id, true);
+        self.reading = Some(id);
+        self.text = None;
+        self.scroll = 0;
+        self.focus = Focus::Reader;
+    }
+
+    /// 切换的是阅读窗格中的文章，还没有打开文章时是列表中选中的文章。
+    fn toggle_read(&mut self) {
+        let article = match self.focus {
+            Focus::Reader => self.article(),
+            _ => self.selected_article(),
+        };
+        let Some(id) = article.map(|article| article.id.clone()) else {
+            return;
+        };
+        let read = !self.read.contains(&id);
+        self.set_read(&id, read);
+    }
+
+    fn set_read(&mut self, id: &str, read: bool) {
+        if let Err(error) = self.read.set(id, read) {
+            self.message = Some(Err(format!("{error:#}")));
+        }
+    }
+
+    fn border(&self, focus: Focus) -> Style {
+        if self.focus == focus {
+            Style::new().green()
+        } else {
+            Style::new()
+        }
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let [feeds_area, articles_area, reader_area] = Layout::horizontal([
+            Constraint::Length(24),
+            Constraint::Fill(2),
+            Constraint::Fill(3),
+        ])
+        .areas(main);
+
+        let items: Vec<Line> = self
+            .feeds
+            .iter()
+            .map(|feed| {
+                let marker = match feed.status {
+                    Status::Pending => " ".into(),
+                    Status::Fetching => "…".yellow(),
+                    Status::Fetched => " ".into(),
+                    Status::Failed(_) => "!".red(),
+                };
+                let unread = self.unread(feed);
+                let count = if unread > 0 {
+                    format!(" {unread}").bold()
+                } else {
+                    "".into()
+                };
+                Line::from(vec![marker, feed.name.clone().into(), count])
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(Style::new().reversed())
+            .block(
+                Block::bordered()
+                    .title(" Feeds ".bold())
+                    .title_bottom(Line::from(" Refresh <R> ").centered())
+                    .border_style(self.border(Focus::Feeds)),
+            );
+        StatefulWidget::render(list, feeds_area, buf, &mut self.feed_list);
+
+        let items: Vec<Line> = self
+            .selected_feed()
+            .map(|feed| feed.articles.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|article| {
+                let date = article
+                    .published
+                    .map_or_else(|| " ".repeat(10), |at| at.format("%Y-%m-%d").to_string());
+                let title = if self.read.contains(&article.id) {
+                    Span::from(format!("  {}", article.title))
+                } else {
+                    format!("● {}", article.title).bold()
+                };
+                Line::from(vec![date.dim(), " ".into(), title])
+            })
+            .collect();
+        let mut block = Block::bordered()
+            .title(" Articles ")
+            .title_bottom(Line::from(" Open <Enter> Read <M> ").centered())
+            .border_style(self.border(Focus::Articles));
+        if let Some(Status::Failed(error)) = self.selected_feed().map(|feed| &feed.status) {
+            block = block.title(Line::from(format!(" {error} ").red()).right_aligned());
+        }
+        let list = List::new(items)
+            .highlight_style(Style::new().reversed())
+            .block(block);
+        StatefulWidget::render(list, articles_area, buf, &mut self.article_list);
+
+        self.render_reader(reader_area, buf);
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+
+    fn render_reader(&mut self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(" Reader ")
+            .border_style(self.border(Focus::Reader));
+        let width = area.width.saturating_sub(2);
+        let Some(article) = self.article() else {
+            block.render(area, buf);
+            return;
+        };
+        let mut lines = vec![Line::from(article.title.clone().bold())];
+        let mut meta = Vec::new();
+        if let Some(at) = article.published {
+            meta.push(at.format("%Y-%m-%d %H:%M").to_string());
+        }
+        meta.extend(article.link.clone());
+        lines.push(Line::from(meta.join("  ").dim()));
+        lines.push(Line::default());
+        if self.text.as_ref().map(|(cached, _)| *cached) != Some(width) {
+            let text = html2text::from_read(article.html.as_bytes(), width.max(1).into())
+                .unwrap_or_else(|error| format!("Cannot render article: {error}"));
+            self.text = Some((width, text));
+        }
+        let (_, text) = self.text.as_ref().unwrap();
+        lines.extend(text.lines().map(|line| Line::from(line.to_string())));
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0))
+            .block(block)
+            .render(area, buf);
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        if let Some(feed) = self.feeds.iter().find(|f| f.status == Status::Fetching) {
+            return Line::from(format!("Fetching {}…", feed.name).yellow());
+        }
+        match &self.message {
+            Some(Err(message)) => Line::from(message.clone().red()),
+            _ => {
+                let unread: usize = self.feeds.iter().map(|feed| self.unread(feed)).sum();
+                let failed = self
+                    .feeds
+                    .iter()
+                    .filter(|feed| matches!(feed.status, Status::Failed(_)))
+                    .count();
+                let mut line = format!("{} feeds, {unread} unread", self.feeds.len());
+                if failed > 0 {
+                    line += &format!(", {failed} failed");
+                }
+                Line::from(line + "  Focus <Tab> Quit <Q>")
+            }
+        }
+    }
+}
+
+/// 在有 `len` 项的列表中上下移动选择。
+fn select(state: &mut ListState, len: usize, code: KeyCode) {
+    if len == 0 {
+        return;
+    }
+    let selected = state.selected().unwrap_or(0);
+    let next = match code {
+        KeyCode::Up => selected.saturating_sub(1),
+        _ => (selected + 1).min(len - 1),
+    };
+    state.select(Some(next));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use chrono::TimeZone;
+    use ratatui_common::testing;
+
+    use super::*;
+    use crate::feeds::Feed;
+
+    fn article(id: &str, day: u32) -> Article {
+        Article {
+            id: id.into(),
+            title: format!("Post {id}"),
+            link: Some(format!("https://example.com/{id}")),
+            published: chrono::Utc.with_ymd_and_hms(2024, 1, day, 8, 0, 0).single(),
+            html: format!("<p>Body of {id}</p>"),
+        }
+    }
+
+    fn app(name: &str) -> (App, PathBuf, Receiver<()>) {
+        let dir = env::temp_dir().join(format!("rss-demo-{name}-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let config = Config {
+            feeds: vec![
+                FeedConfig {
+                    name: Some("Blog".into()),
+                    url: "https://example.com/feed.xml".into(),
+                },
+                FeedConfig {
+                    name: None,
+                    url: "https://example.org/atom.xml".into(),
+                },
+            ],
+            ..Config::default()
+        };
+        let read = ReadSet::load(dir.join("read.json")).unwrap();
+        let (refresh, requests) = mpsc::channel();
+        let (_updates, receiver) = mpsc::channel();
+        (App::new(&config, read, refresh, receiver), dir, requests)
+    }
+
+    fn fetched(app: &mut App, feed: usize, articles: Vec<Article>) {
+        app.update(Update::Fetched {
+            feed,
+            result: Ok(Feed {
+                title: Some("Other".into()),
+                articles,
+            }),
+        });
+    }
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    #[test]
+    fn read_tracking() {
+        let (mut app, dir, requests) = app("read");
+        app.update(Update::Fetching(0));
+        assert_eq!(app.status_line().to_string(), "Fetching Blog…");
+        fetched(&mut app, 0, vec![article("b", 2), article("a", 1)]);
+        fetched(&mut app, 1, vec![article("c", 3)]);
+        // 配置中的名称优先于订阅源的标题。
+        assert_eq!(app.feeds[0].name, "Blog");
+        assert_eq!(app.feeds[1].name, "Other");
+        assert_eq!(app.unread(&app.feeds[0]), 2);
+
+        app.handle_key(KeyCode::Tab);
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.focus, Focus::Reader);
+        assert_eq!(app.article().unwrap().id, "a");
+        assert_eq!(app.unread(&app.feeds[0]), 1);
+        app.handle_key(KeyCode::Char('m'));
+        assert_eq!(app.unread(&app.feeds[0]), 2);
+        app.handle_key(KeyCode::Char('m'));
+
+        // 刷新后仍然选中同一篇文章。
+        fetched(
+            &mut app,
+            0,
+            vec![article("new", 4), article("b", 2), article("a", 1)],
+        );
+        assert_eq!(app.selected_article().unwrap().id, "a");
+        app.handle_key(KeyCode::Char('r'));
+        assert_eq!(requests.try_recv(), Ok(()));
+
+        // 已读记录保存到了磁盘。
+        let read = ReadSet::load(dir.join("read.json")).unwrap();
+        assert!(read.contains("a"));
+        assert!(!read.contains("b"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn render_panes() {
+        let (mut app, dir, _requests) = app("render");
+        fetched(&mut app, 0, vec![article("b", 2), article("a", 1)]);
+        app.update(Update::Fetched {
+            feed: 1,
+            result: Err("connection refused".into()),
+        });
+        app.handle_key(KeyCode::Tab);
+        app.handle_key(KeyCode::Enter);
+        let rows = rows(&mut app, 100, 8);
+        assert_eq!(
+            rows[1],
+            "│ Blog 1               ││2024-01-02   Post b         ││Post b                                      │"
+        );
+        assert_eq!(
+            rows[2],
+            "│!https://example.org/a││2024-01-01 ● Post a         ││2024-01-02 08:00  https://example.com/b     │"
+        );
+        assert_eq!(
+            rows[4],
+            "│                      ││                            ││Body of b                                   │"
+        );
+        assert!(rows[7].starts_with("2 feeds, 1 unread, 1 failed"));
+        let _ = fs::remove_dir_all(dir);
+    }
+}
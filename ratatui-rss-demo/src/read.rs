@@ -0,0 +1,77 @@
+//! 已读文章的记录，保存为 JSON，每次改变时立即写入，下次启动时恢复。
+
+use std::{collections::BTreeSet, fs, io, path::PathBuf};
+
+use color_eyre::{eyre::WrapErr, Result};
+use ratatui_common::files;
+
+#[derive(Debug)]
+pub struct ReadSet {
+    path: PathBuf,
+    /// 已读文章的 ID，见 [`crate::feeds::Article::id`]。
+    ids: BTreeSet<String>,
+}
+
+impl ReadSet {
+    /// 读取记录文件。文件还不存在时没有已读文章。
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let ids = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .wrap_err_with(|| format!("parsing {} failed", path.display()))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => BTreeSet::new(),
+            Err(err) => {
+                return Err(err).wrap_err_with(|| format!("reading {} failed", path.display()))
+            }
+        };
+        Ok(Self { path, ids })
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.ids.contains(id)
+    }
+
+    /// 标记为已读或未读，有变化时写入文件。
+    pub fn set(&mut self, id: &str, read: bool) -> Result<()> {
+        let changed = if read {
+            self.ids.insert(id.to_string())
+        } else {
+            self.ids.remove(id)
+        };
+        if changed {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// 保存已读文章的编号。
+    fn save(&self) -> Result<()> {
+        serde_json::to_vec_pretty(&self.ids)
+            .map_err(io::Error::from)
+            .and_then(|bytes| files::write_atomic(&self.path, bytes))
+            .wrap_err_with(|| format!("writing {} failed", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    #[test]
+    fn persist_read_articles() {
+        let dir = env::temp_dir().join(format!("rss-demo-read-{}", process::id()));
+        let path = dir.join("read.json");
+
+        let mut read = ReadSet::load(path.clone()).unwrap();
+        assert!(!read.contains("a"));
+        read.set("a", true).unwrap();
+        read.set("b", true).unwrap();
+        read.set("b", false).unwrap();
+
+        let read = ReadSet::load(path).unwrap();
+        assert!(read.contains("a"));
+        assert!(!read.contains("b"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
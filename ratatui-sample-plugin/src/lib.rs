@@ -0,0 +1,95 @@
+//! 示例插件：按键记录面板。
+//!
+//! 面板显示期间记录最近按下的按键，按 Backspace 清空记录。
+//! 构建后把生成的共享库（例如 `libratatui_sample_plugin.so`）复制到数据目录下的 `plugins` 目录中，
+//! 计数器程序启动时就会加载它，按 Tab 切换到这个面板。
+
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{List, Paragraph},
+};
+use ratatui_common::plugin::Plugin;
+
+/// 最多保留的按键数量。
+const MAX_KEYS: usize = 100;
+
+#[derive(Debug, Default)]
+pub struct KeyLog {
+    /// 按下的按键，最新的在最后。
+    keys: Vec<KeyCode>,
+}
+
+impl Plugin for KeyLog {
+    fn name(&self) -> &str {
+        "Key log"
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        if self.keys.is_empty() {
+            Paragraph::new("press any key, Backspace clears")
+                .centered()
+                .render(area, buf);
+            return;
+        }
+        // 最新的按键在最上面。
+        let items = self
+            .keys
+            .iter()
+            .rev()
+            .take(usize::from(area.height))
+            .map(|code| format!("{code:?}"));
+        Widget::render(List::new(items), area, buf);
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        let Event::Key(key) = event else {
+            return false;
+        };
+        if key.kind == KeyEventKind::Release {
+            return false;
+        }
+        if key.code == KeyCode::Backspace {
+            self.keys.clear();
+            return true;
+        }
+        if self.keys.len() == MAX_KEYS {
+            self.keys.remove(0);
+        }
+        self.keys.push(key.code);
+        // 其他按键只是记录下来，仍然交给宿主处理（例如 Tab 切换面板、q 退出）。
+        false
+    }
+}
+
+ratatui_common::declare_plugin!(KeyLog::default());
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_and_clear() {
+        let mut log = KeyLog::default();
+        assert!(!log.handle_event(&Event::Key(KeyCode::Char('a').into())));
+        assert!(!log.handle_event(&Event::Key(KeyCode::Tab.into())));
+        assert_eq!(log.keys, [KeyCode::Char('a'), KeyCode::Tab]);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 2));
+        log.render(buf.area, &mut buf);
+        assert_eq!(buf, Buffer::with_lines(vec!["Tab       ", "Char('a') "]));
+
+        assert!(log.handle_event(&Event::Key(KeyCode::Backspace.into())));
+        assert!(log.keys.is_empty());
+    }
+
+    #[test]
+    fn export_entry_point() {
+        assert_eq!(
+            RATATUI_PLUGIN_API_VERSION,
+            ratatui_common::plugin::API_VERSION
+        );
+        let plugin = unsafe { Box::from_raw(ratatui_plugin_create()) };
+        assert_eq!(plugin.name(), "Key log");
+    }
+}
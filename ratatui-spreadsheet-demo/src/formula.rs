@@ -0,0 +1,391 @@
+//! 单元格公式：`=` 之后的部分，例如 `A1+B2*2` 或 `SUM(A1:A10)/2`。
+//!
+//! 支持小数、单元格引用、`+ - * /`、一元负号、括号，以及对矩形范围求值的
+//! `SUM`、`MIN`、`MAX` 和 `AVERAGE`（范围最多包含 10000 个单元格，更大时显示 `#REF!`）。解析得到的 [`Expr`] 可以列出引用的单元格，
+//! 表格据此在单元格改变时重新计算依赖它的公式。
+//!
+//! ```text
+//! expr   = term (("+" | "-") term)*
+//! term   = unary (("*" | "/") unary)*
+//! unary  = "-" unary | atom
+//! atom   = number | cell | name "(" cell ":" cell ")" | "(" expr ")"
+//! ```
+
+use std::{fmt, iter::Peekable, str::CharIndices};
+
+/// 单元格地址，行和列都从 0 开始。显示为 `A1` 的形式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Addr {
+    pub col: usize,
+    pub row: usize,
+}
+
+impl Addr {
+    pub const fn new(col: usize, row: usize) -> Self {
+        Self { col, row }
+    }
+
+    /// 解析 `A1`、`ab12` 这样的地址，不区分大小写。
+    pub fn parse(text: &str) -> Option<Self> {
+        let digits = text.find(|c: char| c.is_ascii_digit())?;
+        let (letters, digits) = text.split_at(digits);
+        if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        let col = letters.bytes().try_fold(0usize, |col, byte| {
+            col.checked_mul(26)?
+                .checked_add(usize::from(byte.to_ascii_uppercase() - b'A') + 1)
+        })?;
+        let row: usize = digits.parse().ok()?;
+        Some(Self::new(col - 1, row.checked_sub(1)?))
+    }
+}
+
+/// 列名：`A` … `Z`、`AA` ….
+pub fn column_name(col: usize) -> String {
+    let mut name = Vec::new();
+    let mut n = col + 1;
+    while n > 0 {
+        n -= 1;
+        name.push(b'A' + (n % 26) as u8);
+        n /= 26;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+impl fmt::Display for Addr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", column_name(self.col), self.row + 1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// 在给定的字符位置遇到了意外的字符。
+    Unexpected(char, usize),
+    UnexpectedEnd,
+    UnknownFunction(String),
+    DivisionByZero,
+    /// 公式直接或间接地引用了自己。
+    Cycle,
+    /// 范围中的单元格超过了 [`MAX_RANGE_CELLS`]。
+    RangeTooLarge,
+}
+
+/// 一个范围最多包含的单元格数。重新计算时范围会展开成其中的每个单元格，太大的范围会耗尽内存。
+pub const MAX_RANGE_CELLS: usize = 10_000;
+
+impl Error {
+    /// 单元格中显示的简短错误。
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::Unexpected(..) | Error::UnexpectedEnd => "#PARSE!",
+            Error::UnknownFunction(_) => "#NAME?",
+            Error::DivisionByZero => "#DIV/0!",
+            Error::Cycle => "#CYCLE!",
+            Error::RangeTooLarge => "#REF!",
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Unexpected(c, at) => write!(f, "unexpected {c:?} at column {}", at + 1),
+            Error::UnexpectedEnd => write!(f, "unexpected end of formula"),
+            Error::UnknownFunction(name) => write!(f, "unknown function {name}"),
+            Error::DivisionByZero => write!(f, "division by zero"),
+            Error::Cycle => write!(f, "circular reference"),
+            Error::RangeTooLarge => write!(f, "range covers more than {MAX_RANGE_CELLS} cells"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Sum,
+    Min,
+    Max,
+    Average,
+}
+
+impl Function {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "SUM" => Some(Function::Sum),
+            "MIN" => Some(Function::Min),
+            "MAX" => Some(Function::Max),
+            "AVERAGE" => Some(Function::Average),
+            _ => None,
+        }
+    }
+
+    fn apply(self, values: &[f64]) -> f64 {
+        if values.is_empty() {
+            return 0.0;
+        }
+        match self {
+            Function::Sum => values.iter().sum(),
+            Function::Min => values.iter().copied().fold(f64::INFINITY, f64::min),
+            Function::Max => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            Function::Average => values.iter().sum::<f64>() / values.len() as f64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Cell(Addr),
+    Neg(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+    /// 对矩形范围内的单元格求值，两个角可以按任意顺序给出。
+    Call(Function, Addr, Addr),
+}
+
+impl Expr {
+    /// 解析 `=` 之后的部分。
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let mut parser = Parser {
+            chars: input.char_indices().peekable(),
+        };
+        let expr = parser.expr()?;
+        match parser.next_token() {
+            Some((at, c)) => Err(Error::Unexpected(c, at)),
+            None => Ok(expr),
+        }
+    }
+
+    /// 引用的所有单元格，范围展开成其中的每个单元格。
+    pub fn references(&self, out: &mut Vec<Addr>) {
+        match self {
+            Expr::Number(_) => {}
+            Expr::Cell(addr) => out.push(*addr),
+            Expr::Neg(expr) => expr.references(out),
+            Expr::Binary(_, lhs, rhs) => {
+                lhs.references(out);
+                rhs.references(out);
+            }
+            Expr::Call(_, from, to) => out.extend(range(*from, *to)),
+        }
+    }
+
+    /// 计算公式的值。`cell` 返回单元格的数值，空单元格和文字返回 `None`：
+    /// 直接引用时按 0 计算，范围函数忽略它们。
+    pub fn eval(
+        &self,
+        cell: &mut impl FnMut(Addr) -> Result<Option<f64>, Error>,
+    ) -> Result<f64, Error> {
+        match self {
+            Expr::Number(value) => Ok(*value),
+            Expr::Cell(addr) => Ok(cell(*addr)?.unwrap_or(0.0)),
+            Expr::Neg(expr) => Ok(-expr.eval(cell)?),
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = lhs.eval(cell)?;
+                let rhs = rhs.eval(cell)?;
+                match op {
+                    '+' => Ok(lhs + rhs),
+                    '-' => Ok(lhs - rhs),
+                    '*' => Ok(lhs * rhs),
+                    _ if rhs == 0.0 => Err(Error::DivisionByZero),
+                    _ => Ok(lhs / rhs),
+                }
+            }
+            Expr::Call(function, from, to) => {
+                let mut values = Vec::new();
+                for addr in range(*from, *to) {
+                    values.extend(cell(addr)?);
+                }
+                Ok(function.apply(&values))
+            }
+        }
+    }
+}
+
+/// 以 `a` 和 `b` 为对角的矩形中的单元格，逐行排列。
+fn range(a: Addr, b: Addr) -> impl Iterator<Item = Addr> {
+    let cols = a.col.min(b.col)..=a.col.max(b.col);
+    (a.row.min(b.row)..=a.row.max(b.row))
+        .flat_map(move |row| cols.clone().map(move |col| Addr::new(col, row)))
+}
+
+struct Parser<'a> {
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+
+    fn peek_token(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.peek().map(|&(_, c)| c)
+    }
+
+    fn next_token(&mut self) -> Option<(usize, char)> {
+        self.skip_whitespace();
+        self.chars.next()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), Error> {
+        match self.next_token() {
+            Some((_, c)) if c == expected => Ok(()),
+            Some((at, c)) => Err(Error::Unexpected(c, at)),
+            None => Err(Error::UnexpectedEnd),
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.term()?;
+        while let Some(op @ ('+' | '-')) = self.peek_token() {
+            self.chars.next();
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.term()?));
+        }
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.unary()?;
+        while let Some(op @ ('*' | '/')) = self.peek_token() {
+            self.chars.next();
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, Error> {
+        if self.peek_token() == Some('-') {
+            self.chars.next();
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.atom()
+    }
+
+    /// 连续的字母和数字，例如 `A1` 或 `SUM`。
+    fn word(&mut self, first: (usize, char)) -> (usize, String) {
+        let mut word = first.1.to_string();
+        while let Some((_, c)) = self.chars.next_if(|(_, c)| c.is_ascii_alphanumeric()) {
+            word.push(c);
+        }
+        (first.0, word)
+    }
+
+    fn cell(&mut self) -> Result<Addr, Error> {
+        match self.next_token() {
+            Some(first @ (_, c)) if c.is_ascii_alphabetic() => {
+                let (at, word) = self.word(first);
+                Addr::parse(&word).ok_or(Error::Unexpected(c, at))
+            }
+            Some((at, c)) => Err(Error::Unexpected(c, at)),
+            None => Err(Error::UnexpectedEnd),
+        }
+    }
+
+    fn atom(&mut self) -> Result<Expr, Error> {
+        match self.next_token() {
+            Some((_, '(')) => {
+                let expr = self.expr()?;
+                self.expect(')')?;
+                Ok(expr)
+            }
+            Some((at, c)) if c.is_ascii_digit() || c == '.' => {
+                let mut text = c.to_string();
+                while let Some((_, c)) =
+                    self.chars.next_if(|(_, c)| c.is_ascii_digit() || *c == '.')
+                {
+                    text.push(c);
+                }
+                text.parse()
+                    .map(Expr::Number)
+                    .map_err(|_| Error::Unexpected(c, at))
+            }
+            Some(first @ (at, c)) if c.is_ascii_alphabetic() => {
+                let (_, word) = self.word(first);
+                if let Some(addr) = Addr::parse(&word) {
+                    return Ok(Expr::Cell(addr));
+                }
+                if self.peek_token() != Some('(') {
+                    return Err(Error::Unexpected(c, at));
+                }
+                let function =
+                    Function::parse(&word).ok_or_else(|| Error::UnknownFunction(word.clone()))?;
+                self.expect('(')?;
+                let from = self.cell()?;
+                self.expect(':')?;
+                let to = self.cell()?;
+                self.expect(')')?;
+                let cells =
+                    (from.col.abs_diff(to.col) + 1).checked_mul(from.row.abs_diff(to.row) + 1);
+                if cells.is_none_or(|cells| cells > MAX_RANGE_CELLS) {
+                    return Err(Error::RangeTooLarge);
+                }
+                Ok(Expr::Call(function, from, to))
+            }
+            Some((at, c)) => Err(Error::Unexpected(c, at)),
+            None => Err(Error::UnexpectedEnd),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(input: &str) -> Result<f64, Error> {
+        // A1 = 1, B1 = 2, … 第一行之外都是空的。
+        Expr::parse(input)?
+            .eval(&mut |addr: Addr| Ok((addr.row == 0).then_some(addr.col as f64 + 1.0)))
+    }
+
+    #[test]
+    fn addresses() {
+        assert_eq!(Addr::parse("A1"), Some(Addr::new(0, 0)));
+        assert_eq!(Addr::parse("b12"), Some(Addr::new(1, 11)));
+        assert_eq!(Addr::parse("AA3"), Some(Addr::new(26, 2)));
+        assert_eq!(Addr::parse("A0"), None);
+        assert_eq!(Addr::parse("1A"), None);
+        assert_eq!(Addr::parse("A1B"), None);
+        assert_eq!(Addr::new(27, 9).to_string(), "AB10");
+        assert_eq!(column_name(25), "Z");
+    }
+
+    #[test]
+    fn evaluate() {
+        assert_eq!(eval("A1+B1*2"), Ok(5.0));
+        assert_eq!(eval("(a1 + b1) * 2"), Ok(6.0));
+        assert_eq!(eval("-C1 / 2"), Ok(-1.5));
+        assert_eq!(eval("1.5 + A9"), Ok(1.5));
+        assert_eq!(eval("SUM(A1:C2)"), Ok(6.0));
+        assert_eq!(eval("average(C1:A1)"), Ok(2.0));
+        assert_eq!(eval("MAX(A1:B1) - MIN(A2:B2)"), Ok(2.0));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(eval(""), Err(Error::UnexpectedEnd));
+        assert_eq!(eval("A1 +"), Err(Error::UnexpectedEnd));
+        assert_eq!(eval("A1 B1"), Err(Error::Unexpected('B', 3)));
+        assert_eq!(eval("1.2.3"), Err(Error::Unexpected('1', 0)));
+        assert_eq!(eval("FOO"), Err(Error::Unexpected('F', 0)));
+        assert_eq!(
+            eval("FOO(A1:A2)"),
+            Err(Error::UnknownFunction("FOO".into()))
+        );
+        assert_eq!(eval("SUM(A1)"), Err(Error::Unexpected(')', 6)));
+        assert_eq!(eval("A1 / A2"), Err(Error::DivisionByZero));
+        assert_eq!(eval("SUM(A1:ZZZ999999)"), Err(Error::RangeTooLarge));
+        assert_eq!(eval("SUM(A1:CV100)"), Ok(5050.0));
+    }
+
+    #[test]
+    fn references() {
+        let mut out = Vec::new();
+        Expr::parse("A1 + SUM(B1:C2)").unwrap().references(&mut out);
+        let names: Vec<String> = out.iter().map(Addr::to_string).collect();
+        assert_eq!(names, ["A1", "B1", "C1", "B2", "C2"]);
+    }
+}
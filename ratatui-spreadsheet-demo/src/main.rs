@@ -0,0 +1,444 @@
+//! 电子表格演示：方向键在单元格之间移动，输入内容或以 `=` 开头的公式，例如 `=A1+B2`
+//! 或 `=SUM(A1:A10)`。修改单元格后依赖它的公式自动重新计算，见 `sheet` 模块。
+//!
+//! 可以打开一个 CSV 文件，保存时公式保持为公式；也可以导出计算结果。
+//!
+//! 按键：`Enter` 或 `F2` 编辑单元格，直接输入也会开始编辑；编辑时 `Enter` 确认并下移，
+//! `Tab` 确认并右移，`Esc` 取消。`Delete` 清空单元格，`Ctrl+S` 保存，
+//! `Ctrl+E` 导出计算结果，`Esc` 退出。
+
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use color_eyre::{eyre::WrapErr, Result};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{prelude::*, widgets::Paragraph};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+    text_input::Input,
+};
+
+use crate::{
+    formula::{column_name, Addr},
+    sheet::{Contents, Sheet, Value},
+};
+
+mod formula;
+mod sheet;
+
+/// 至少可以移动到的列数和行数，文件更大时可以移动到文件的范围。
+const COLUMNS: usize = 26;
+const ROWS: usize = 100;
+
+const CELL_WIDTH: u16 = 10;
+/// 行号所在列的宽度。
+const HEADER_WIDTH: u16 = 4;
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 打开的 CSV 文件，不存在时在保存时创建
+    #[arg(default_value = "sheet.csv")]
+    file: PathBuf,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let sheet = if cli.file.exists() {
+        load(&cli.file)?
+    } else {
+        Sheet::default()
+    };
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new(sheet, cli.file).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+fn load(path: &Path) -> Result<Sheet> {
+    let file = File::open(path).wrap_err_with(|| format!("cannot open {}", path.display()))?;
+    Sheet::read_csv(BufReader::new(file))
+        .wrap_err_with(|| format!("cannot read {}", path.display()))
+}
+
+fn save(sheet: &Sheet, path: &Path, contents: Contents) -> Result<()> {
+    let file = File::create(path).wrap_err_with(|| format!("cannot create {}", path.display()))?;
+    sheet
+        .write_csv(BufWriter::new(file), contents)
+        .wrap_err_with(|| format!("cannot write {}", path.display()))
+}
+
+/// 导出计算结果的文件：`sheet.csv` 导出到 `sheet.values.csv`。
+fn values_path(path: &Path) -> PathBuf {
+    path.with_extension("values.csv")
+}
+
+struct App {
+    sheet: Sheet,
+    path: PathBuf,
+    cursor: Addr,
+    /// 左上角显示的单元格。
+    offset: Addr,
+    /// 正在编辑的内容。
+    editing: Option<Input>,
+    /// 有没有保存的修改。
+    modified: bool,
+    message: Option<Result<String, String>>,
+    exit: bool,
+}
+
+impl App {
+    fn new(sheet: Sheet, path: PathBuf) -> Self {
+        Self {
+            sheet,
+            path,
+            cursor: Addr::default(),
+            offset: Addr::default(),
+            editing: None,
+            modified: false,
+            message: None,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut()))?;
+            if let Event::Key(key) = events.read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 可以移动到的列数和行数。
+    fn limits(&self) -> (usize, usize) {
+        let (cols, rows) = self.sheet.extent();
+        (cols.max(COLUMNS), rows.max(ROWS))
+    }
+
+    fn move_cursor(&mut self, dx: isize, dy: isize) {
+        let (cols, rows) = self.limits();
+        self.cursor.col = self.cursor.col.saturating_add_signed(dx).min(cols - 1);
+        self.cursor.row = self.cursor.row.saturating_add_signed(dy).min(rows - 1);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if self.editing.is_some() {
+            self.handle_edit_key(key);
+            return;
+        }
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.exit = true,
+            KeyCode::Char('s') if control => self.save(Contents::Inputs),
+            KeyCode::Char('e') if control => self.save(Contents::Values),
+            KeyCode::Left => self.move_cursor(-1, 0),
+            KeyCode::Right | KeyCode::Tab => self.move_cursor(1, 0),
+            KeyCode::BackTab => self.move_cursor(-1, 0),
+            KeyCode::Up => self.move_cursor(0, -1),
+            KeyCode::Down => self.move_cursor(0, 1),
+            KeyCode::PageUp => self.move_cursor(0, -20),
+            KeyCode::PageDown => self.move_cursor(0, 20),
+            KeyCode::Home => self.cursor.col = 0,
+            KeyCode::Enter | KeyCode::F(2) => {
+                self.editing = Some(Input::with_value(self.sheet.input(self.cursor)));
+            }
+            KeyCode::Delete | KeyCode::Backspace => self.commit(""),
+            KeyCode::Char(c) if !control => self.editing = Some(Input::with_value(c)),
+            _ => {}
+        }
+    }
+
+    fn handle_edit_key(&mut self, key: KeyEvent) {
+        let Some(input) = &mut self.editing else {
+            return;
+        };
+        let (dx, dy) = match key.code {
+            KeyCode::Esc => {
+                self.editing = None;
+                return;
+            }
+            KeyCode::Enter => (0, 1),
+            KeyCode::Tab => (1, 0),
+            _ => {
+                input.handle_key_event(key);
+                return;
+            }
+        };
+        let value = input.value().to_string();
+        self.editing = None;
+        self.commit(&value);
+        self.move_cursor(dx, dy);
+    }
+
+    /// 修改当前单元格。
+    fn commit(&mut self, input: &str) {
+        if self.sheet.input(self.cursor) == input {
+            return;
+        }
+        let count = self.sheet.set(self.cursor, input);
+        self.modified = true;
+        self.message = match self.sheet.value(self.cursor) {
+            Value::Error(error) => Some(Err(format!("{}: {error}", self.cursor))),
+            _ if count > 1 => Some(Ok(format!("Recalculated {} cells", count - 1))),
+            _ => None,
+        };
+    }
+
+    fn save(&mut self, contents: Contents) {
+        let path = match contents {
+            Contents::Inputs => self.path.clone(),
+            Contents::Values => values_path(&self.path),
+        };
+        self.message = Some(match save(&self.sheet, &path, contents) {
+            Ok(()) => {
+                if contents == Contents::Inputs {
+                    self.modified = false;
+                }
+                Ok(format!("Saved {}", path.display()))
+            }
+            Err(error) => Err(format!("{error:#}")),
+        });
+    }
+
+    /// 调整左上角，让光标所在的单元格可见。
+    fn scroll_to_cursor(&mut self, cols: usize, rows: usize) {
+        let cols = cols.max(1);
+        let rows = rows.max(1);
+        if self.cursor.col < self.offset.col {
+            self.offset.col = self.cursor.col;
+        } else if self.cursor.col >= self.offset.col + cols {
+            self.offset.col = self.cursor.col + 1 - cols;
+        }
+        if self.cursor.row < self.offset.row {
+            self.offset.row = self.cursor.row;
+        } else if self.cursor.row >= self.offset.row + rows {
+            self.offset.row = self.cursor.row + 1 - rows;
+        }
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let [bar, grid, status] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+        self.render_bar(bar, buf);
+        self.render_grid(grid, buf);
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+
+    /// 编辑栏：当前单元格的地址和内容。
+    fn render_bar(&self, area: Rect, buf: &mut Buffer) {
+        let prompt = format!("{:<5}│ ", self.cursor.to_string());
+        let line = match &self.editing {
+            Some(input) => input.line(&prompt, Style::new().bold()),
+            None => Line::from(vec![
+                prompt.clone().bold(),
+                self.sheet.input(self.cursor).into(),
+            ]),
+        };
+        Paragraph::new(line).render(area, buf);
+    }
+
+    fn render_grid(&mut self, area: Rect, buf: &mut Buffer) {
+        let cols = (area.width.saturating_sub(HEADER_WIDTH) / CELL_WIDTH) as usize;
+        let rows = area.height.saturating_sub(1) as usize;
+        self.scroll_to_cursor(cols, rows);
+
+        let header = Style::new().fg(Color::Black).bg(Color::Gray);
+        buf.set_style(Rect { height: 1, ..area }, header);
+        buf.set_style(
+            Rect {
+                width: HEADER_WIDTH.min(area.width),
+                ..area
+            },
+            header,
+        );
+        let references = self.sheet.references(self.cursor);
+        for i in 0..cols {
+            let col = self.offset.col + i;
+            let x = area.x + HEADER_WIDTH + i as u16 * CELL_WIDTH;
+            let name = format!("{:^width$}", column_name(col), width = CELL_WIDTH as usize);
+            let style = if col == self.cursor.col {
+                header.bold()
+            } else {
+                header
+            };
+            buf.set_stringn(x, area.y, name, CELL_WIDTH as usize, style);
+        }
+        for j in 0..rows {
+            let row = self.offset.row + j;
+            let y = area.y + 1 + j as u16;
+            let number = format!("{:>3} ", row + 1);
+            buf.set_stringn(area.x, y, number, HEADER_WIDTH as usize, header);
+            for i in 0..cols {
+                let addr = Addr::new(self.offset.col + i, row);
+                let x = area.x + HEADER_WIDTH + i as u16 * CELL_WIDTH;
+                let style = if addr == self.cursor {
+                    Style::new().reversed()
+                } else if references.contains(&addr) {
+                    Style::new().bg(Color::Blue)
+                } else {
+                    Style::new()
+                };
+                let value = self.sheet.value(addr);
+                buf.set_stringn(x, y, cell_text(value), CELL_WIDTH as usize, style);
+            }
+        }
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        match &self.message {
+            Some(Ok(text)) => Line::from(text.clone()),
+            Some(Err(text)) => Line::from(text.clone().red()),
+            None => {
+                let modified = if self.modified { " [modified]" } else { "" };
+                Line::from(format!(
+                    "{}{modified}  Edit <Enter> Save <Ctrl+S> Export <Ctrl+E> Quit <Esc>",
+                    self.path.display()
+                ))
+            }
+        }
+    }
+}
+
+/// 单元格中显示的文字，占满单元格的宽度：数字靠右，其他靠左，太长时截断。
+fn cell_text(value: &Value) -> String {
+    let width = CELL_WIDTH as usize - 1;
+    let text: String = value.display().chars().take(width).collect();
+    match value {
+        Value::Number(_) | Value::Error(_) => format!("{text:>width$} "),
+        _ => format!("{text:<width$} "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    #[test]
+    fn edit_cells() {
+        let mut app = App::new(Sheet::default(), "sheet.csv".into());
+        testing::type_text("2", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Tab.into());
+        testing::type_text("=A1*3", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(app.cursor, Addr::new(1, 1));
+        assert_eq!(app.sheet.value(Addr::new(1, 0)), &Value::Number(6.0));
+
+        // 修改 A1 后 B1 重新计算。
+        app.handle_key(KeyCode::Up.into());
+        app.handle_key(KeyCode::Left.into());
+        app.handle_key(KeyCode::Enter.into());
+        app.handle_key(KeyCode::Backspace.into());
+        testing::type_text("5", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(app.sheet.value(Addr::new(1, 0)), &Value::Number(15.0));
+        assert_eq!(app.message, Some(Ok("Recalculated 1 cells".into())));
+
+        // 取消编辑不修改单元格。
+        app.handle_key(KeyCode::Up.into());
+        testing::type_text("9", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Esc.into());
+        assert_eq!(app.sheet.input(Addr::new(0, 0)), "5");
+        assert!(!app.exit);
+
+        app.handle_key(KeyCode::Right.into());
+        testing::type_text("=A1/0", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(app.message, Some(Err("B1: division by zero".into())));
+        app.handle_key(KeyCode::Up.into());
+        app.handle_key(KeyCode::Delete.into());
+        assert_eq!(app.sheet.value(Addr::new(1, 0)), &Value::Empty);
+
+        app.handle_key(KeyCode::Esc.into());
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn navigation_and_scrolling() {
+        let mut app = App::new(Sheet::default(), "sheet.csv".into());
+        app.handle_key(KeyCode::Left.into());
+        app.handle_key(KeyCode::Up.into());
+        assert_eq!(app.cursor, Addr::new(0, 0));
+        for _ in 0..30 {
+            app.handle_key(KeyCode::Right.into());
+        }
+        assert_eq!(app.cursor.col, COLUMNS - 1);
+
+        app.cursor = Addr::new(5, 10);
+        rows(&mut app, 34, 8);
+        // 三列五行可见，光标在右下角。
+        assert_eq!(app.offset, Addr::new(3, 6));
+        app.handle_key(KeyCode::Home.into());
+        rows(&mut app, 34, 8);
+        assert_eq!(app.offset, Addr::new(0, 6));
+    }
+
+    #[test]
+    fn render_grid() {
+        let csv = "Tea,2.5,4,=B1*C1\nMilk,1.2,2,=B2*C2\n";
+        let mut app = App::new(Sheet::read_csv(csv.as_bytes()).unwrap(), "shop.csv".into());
+        app.cursor = Addr::new(3, 1);
+        let rows = rows(&mut app, 44, 5);
+        assert_eq!(
+            rows,
+            [
+                "D2   │ =B2*C2                               ",
+                "        A         B         C         D     ",
+                "  1 Tea             2.5         4        10 ",
+                "  2 Milk            1.2         2       2.4 ",
+                "shop.csv  Edit <Enter> Save <Ctrl+S> Export ",
+            ]
+        );
+    }
+
+    #[test]
+    fn save_and_export() {
+        let dir = env::temp_dir().join(format!("spreadsheet-demo-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sheet.csv");
+        let mut app = App::new(Sheet::default(), path.clone());
+        testing::type_text("3", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        testing::type_text("=A1*A1", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert!(app.modified);
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL));
+        assert!(!app.modified);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "3\n=A1*A1\n");
+        app.handle_key(KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL));
+        assert_eq!(
+            fs::read_to_string(dir.join("sheet.values.csv")).unwrap(),
+            "3\n9\n"
+        );
+
+        let sheet = load(&path).unwrap();
+        assert_eq!(sheet.value(Addr::new(0, 1)), &Value::Number(9.0));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,331 @@
+//! 单元格的内容、计算结果和依赖关系，以及 CSV 导入导出。
+//!
+//! 每个单元格保存输入的原文；以 `=` 开头的是公式，其他的是数字或文字。
+//! 修改一个单元格时只重新计算它和直接或间接依赖它的公式，循环引用的单元格显示 `#CYCLE!`。
+
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    io,
+};
+
+use crate::formula::{Addr, Error, Expr};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Value {
+    #[default]
+    Empty,
+    Number(f64),
+    Text(String),
+    Error(Error),
+}
+
+impl Value {
+    /// 单元格中显示的文字。
+    pub fn display(&self) -> String {
+        match self {
+            Value::Empty => String::new(),
+            Value::Number(value) => format_number(*value),
+            Value::Text(text) => text.clone(),
+            Value::Error(error) => error.code().to_string(),
+        }
+    }
+}
+
+/// 整数不带小数点，其他的最多保留六位小数。
+pub fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        return format!("{value:.0}");
+    }
+    let text = format!("{value:.6}");
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[derive(Debug, Clone, Default)]
+struct Cell {
+    input: String,
+    formula: Option<Result<Expr, Error>>,
+    value: Value,
+}
+
+impl Cell {
+    fn new(input: &str) -> Self {
+        let formula = input.strip_prefix('=').map(Expr::parse);
+        let value = match input.trim() {
+            _ if formula.is_some() => Value::Empty,
+            "" => Value::Empty,
+            text => text
+                .parse()
+                .map_or_else(|_| Value::Text(input.to_string()), Value::Number),
+        };
+        Self {
+            input: input.to_string(),
+            formula,
+            value,
+        }
+    }
+
+    fn references(&self) -> Vec<Addr> {
+        let mut out = Vec::new();
+        if let Some(Ok(expr)) = &self.formula {
+            expr.references(&mut out);
+        }
+        out
+    }
+}
+
+/// 导出 CSV 时写入的内容。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Contents {
+    /// 输入的原文，公式保持为公式，可以再导入。
+    Inputs,
+    /// 计算结果。
+    Values,
+}
+
+#[derive(Debug, Default)]
+pub struct Sheet {
+    cells: HashMap<Addr, Cell>,
+    /// 引用了某个单元格的公式所在的单元格。
+    dependents: HashMap<Addr, HashSet<Addr>>,
+}
+
+impl Sheet {
+    pub fn input(&self, addr: Addr) -> &str {
+        self.cells.get(&addr).map_or("", |cell| &cell.input)
+    }
+
+    pub fn value(&self, addr: Addr) -> &Value {
+        const EMPTY: &Value = &Value::Empty;
+        self.cells.get(&addr).map_or(EMPTY, |cell| &cell.value)
+    }
+
+    /// 单元格中的公式引用的单元格。
+    pub fn references(&self, addr: Addr) -> Vec<Addr> {
+        self.cells
+            .get(&addr)
+            .map_or_else(Vec::new, Cell::references)
+    }
+
+    /// 用到的列数和行数。
+    pub fn extent(&self) -> (usize, usize) {
+        self.cells.keys().fold((0, 0), |(cols, rows), addr| {
+            (cols.max(addr.col + 1), rows.max(addr.row + 1))
+        })
+    }
+
+    /// 修改单元格的内容，返回重新计算了的单元格数（包括它自己）。
+    pub fn set(&mut self, addr: Addr, input: &str) -> usize {
+        self.unlink(addr);
+        if input.is_empty() {
+            self.cells.remove(&addr);
+        } else {
+            self.cells.insert(addr, Cell::new(input));
+            self.link(addr);
+        }
+        self.recalculate([addr].into())
+    }
+
+    fn link(&mut self, addr: Addr) {
+        for reference in self.references(addr) {
+            self.dependents.entry(reference).or_default().insert(addr);
+        }
+    }
+
+    fn unlink(&mut self, addr: Addr) {
+        for reference in self.references(addr) {
+            if let Some(dependents) = self.dependents.get_mut(&reference) {
+                dependents.remove(&addr);
+                if dependents.is_empty() {
+                    self.dependents.remove(&reference);
+                }
+            }
+        }
+    }
+
+    /// 重新计算 `changed` 和所有依赖它们的公式，返回计算的单元格数。
+    fn recalculate(&mut self, changed: BTreeSet<Addr>) -> usize {
+        let mut dirty = changed.clone();
+        let mut queue: Vec<Addr> = changed.into_iter().collect();
+        while let Some(addr) = queue.pop() {
+            for &dependent in self.dependents.get(&addr).into_iter().flatten() {
+                if dirty.insert(dependent) {
+                    queue.push(dependent);
+                }
+            }
+        }
+        let count = dirty.len();
+        let mut visiting = HashSet::new();
+        // 按依赖顺序计算：计算一个公式时先计算它引用的还没有计算的单元格。
+        for addr in dirty.clone() {
+            let _ = self.compute(addr, &mut dirty, &mut visiting);
+        }
+        count
+    }
+
+    /// 计算单元格的值，作为公式中的数值返回。
+    fn compute(
+        &mut self,
+        addr: Addr,
+        dirty: &mut BTreeSet<Addr>,
+        visiting: &mut HashSet<Addr>,
+    ) -> Result<Option<f64>, Error> {
+        if dirty.contains(&addr) {
+            if !visiting.insert(addr) {
+                return Err(Error::Cycle);
+            }
+            let formula = self.cells.get(&addr).and_then(|cell| cell.formula.clone());
+            if let Some(formula) = formula {
+                let value = formula
+                    .and_then(|expr| expr.eval(&mut |a| self.compute(a, dirty, visiting)))
+                    .map_or_else(Value::Error, Value::Number);
+                if let Some(cell) = self.cells.get_mut(&addr) {
+                    cell.value = value;
+                }
+            }
+            visiting.remove(&addr);
+            dirty.remove(&addr);
+        }
+        match self.value(addr) {
+            Value::Number(value) => Ok(Some(*value)),
+            Value::Error(error) => Err(error.clone()),
+            Value::Empty | Value::Text(_) => Ok(None),
+        }
+    }
+
+    /// 从 CSV 导入，每一行对应表格的一行，行的长度可以不同。
+    pub fn read_csv(reader: impl io::Read) -> csv::Result<Self> {
+        let mut sheet = Self::default();
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(reader);
+        for (row, record) in reader.records().enumerate() {
+            for (col, field) in record?.iter().enumerate() {
+                if !field.is_empty() {
+                    sheet.cells.insert(Addr::new(col, row), Cell::new(field));
+                }
+            }
+        }
+        let addrs: BTreeSet<Addr> = sheet.cells.keys().copied().collect();
+        for &addr in &addrs {
+            sheet.link(addr);
+        }
+        sheet.recalculate(addrs);
+        Ok(sheet)
+    }
+
+    /// 导出 CSV，每一行都写满用到的列数。
+    pub fn write_csv(&self, writer: impl io::Write, contents: Contents) -> csv::Result<()> {
+        let (cols, rows) = self.extent();
+        let mut writer = csv::Writer::from_writer(writer);
+        for row in 0..rows {
+            let record = (0..cols).map(|col| {
+                let addr = Addr::new(col, row);
+                match contents {
+                    Contents::Inputs => self.input(addr).to_string(),
+                    Contents::Values => self.value(addr).display(),
+                }
+            });
+            writer.write_record(record)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(name: &str) -> Addr {
+        Addr::parse(name).unwrap()
+    }
+
+    fn shown(sheet: &Sheet, name: &str) -> String {
+        sheet.value(addr(name)).display()
+    }
+
+    #[test]
+    fn values() {
+        let mut sheet = Sheet::default();
+        sheet.set(addr("A1"), "12");
+        sheet.set(addr("A2"), "0.5");
+        sheet.set(addr("A3"), "apples");
+        sheet.set(addr("A4"), "=A1*A2+A3");
+        assert_eq!(sheet.value(addr("A1")), &Value::Number(12.0));
+        assert_eq!(sheet.value(addr("A3")), &Value::Text("apples".into()));
+        assert_eq!(shown(&sheet, "A4"), "6");
+        sheet.set(addr("A5"), "=1/3");
+        assert_eq!(shown(&sheet, "A5"), "0.333333");
+        sheet.set(addr("A6"), "=1+");
+        assert_eq!(shown(&sheet, "A6"), "#PARSE!");
+        assert_eq!(sheet.extent(), (1, 6));
+    }
+
+    #[test]
+    fn dependencies() {
+        let mut sheet = Sheet::default();
+        sheet.set(addr("A1"), "1");
+        sheet.set(addr("B1"), "=A1*10");
+        sheet.set(addr("C1"), "=B1+A1");
+        sheet.set(addr("D1"), "=SUM(A1:C1)");
+        sheet.set(addr("A2"), "7");
+        assert_eq!(shown(&sheet, "D1"), "22");
+
+        // 只重新计算 A1 和依赖它的三个公式。
+        assert_eq!(sheet.set(addr("A1"), "2"), 4);
+        assert_eq!(shown(&sheet, "C1"), "22");
+        assert_eq!(shown(&sheet, "D1"), "44");
+
+        // 公式改为引用别的单元格后，不再依赖原来的单元格。
+        sheet.set(addr("B1"), "=A2");
+        assert_eq!(shown(&sheet, "D1"), "18");
+        assert_eq!(sheet.set(addr("A1"), "3"), 3);
+        assert_eq!(shown(&sheet, "D1"), "20");
+
+        sheet.set(addr("A2"), "0");
+        sheet.set(addr("E1"), "=A1/B1");
+        assert_eq!(shown(&sheet, "E1"), "#DIV/0!");
+        // 太大的范围直接报错，不会展开成依赖。
+        sheet.set(addr("F1"), "=SUM(A1:ZZZ999999)");
+        assert_eq!(shown(&sheet, "F1"), "#REF!");
+        // 删除单元格后引用它的公式按 0 计算。
+        sheet.set(addr("A1"), "");
+        assert_eq!(shown(&sheet, "C1"), "0");
+    }
+
+    #[test]
+    fn cycles() {
+        let mut sheet = Sheet::default();
+        sheet.set(addr("A1"), "=B1+1");
+        sheet.set(addr("B1"), "=A1+1");
+        sheet.set(addr("C1"), "=A1");
+        assert_eq!(shown(&sheet, "A1"), "#CYCLE!");
+        assert_eq!(shown(&sheet, "B1"), "#CYCLE!");
+        assert_eq!(shown(&sheet, "C1"), "#CYCLE!");
+
+        sheet.set(addr("B1"), "5");
+        assert_eq!(shown(&sheet, "A1"), "6");
+        assert_eq!(shown(&sheet, "C1"), "6");
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        let csv =
+            "Item,Price,Count,Total\nTea,2.5,4,=B2*C2\n\"Milk, 1l\",1.2,2,=B3*C3\n,,,=SUM(D2:D3)\n";
+        let sheet = Sheet::read_csv(csv.as_bytes()).unwrap();
+        assert_eq!(shown(&sheet, "A3"), "Milk, 1l");
+        assert_eq!(shown(&sheet, "D4"), "12.4");
+
+        let mut inputs = Vec::new();
+        sheet.write_csv(&mut inputs, Contents::Inputs).unwrap();
+        assert_eq!(String::from_utf8(inputs).unwrap(), csv);
+
+        let mut values = Vec::new();
+        sheet.write_csv(&mut values, Contents::Values).unwrap();
+        assert_eq!(
+            String::from_utf8(values).unwrap(),
+            "Item,Price,Count,Total\nTea,2.5,4,10\n\"Milk, 1l\",1.2,2,2.4\n,,,12.4\n"
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! 一局数独：题目给出的数字不能修改，其他格子可以填数字或者记下候选数字（铅笔标记）。
+
+use clap::ValueEnum;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::grid::{Digits, Grid, Pos};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Self; 3] = [Self::Easy, Self::Medium, Self::Hard];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Easy => "Easy",
+            Self::Medium => "Medium",
+            Self::Hard => "Hard",
+        }
+    }
+
+    /// 题目给出的数字个数。困难的题目挖到答案不再唯一时会多留几个。
+    pub fn givens(self) -> usize {
+        match self {
+            Self::Easy => 40,
+            Self::Medium => 32,
+            Self::Hard => 24,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Game {
+    pub difficulty: Difficulty,
+    givens: Grid,
+    solution: Grid,
+    /// 题目给出的数字和玩家填的数字。
+    board: Grid,
+    marks: [[Digits; 9]; 9],
+    hints: u32,
+}
+
+impl Game {
+    pub fn new(difficulty: Difficulty, rng: &mut impl Rng) -> Self {
+        let (givens, solution) = Grid::generate(difficulty.givens(), rng);
+        Self {
+            difficulty,
+            givens,
+            solution,
+            board: givens,
+            marks: Default::default(),
+            hints: 0,
+        }
+    }
+
+    /// 恢复保存的进度。答案由题目重新求出来。
+    pub fn restore(
+        difficulty: Difficulty,
+        givens: Grid,
+        board: Grid,
+        marks: [[Digits; 9]; 9],
+        hints: u32,
+    ) -> Result<Self, String> {
+        let solution = givens.solve().ok_or("the saved puzzle has no solution")?;
+        if Grid::positions().any(|pos| givens.get(pos) != 0 && board.get(pos) != givens.get(pos)) {
+            return Err("the saved board changes a given digit".into());
+        }
+        Ok(Self {
+            difficulty,
+            givens,
+            solution,
+            board,
+            marks,
+            hints,
+        })
+    }
+
+    pub fn givens(&self) -> &Grid {
+        &self.givens
+    }
+
+    pub fn board(&self) -> &Grid {
+        &self.board
+    }
+
+    pub fn marks(&self) -> &[[Digits; 9]; 9] {
+        &self.marks
+    }
+
+    pub fn hints(&self) -> u32 {
+        self.hints
+    }
+
+    pub fn is_given(&self, pos: Pos) -> bool {
+        self.givens.get(pos) != 0
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.board == self.solution
+    }
+
+    pub fn conflicts(&self) -> Vec<Pos> {
+        self.board.conflicts()
+    }
+
+    /// 在 `pos` 填上数字，同时擦掉同一行、列和宫中这个数字的铅笔标记。
+    pub fn enter(&mut self, pos: Pos, digit: u8) {
+        if self.is_given(pos) || self.is_solved() {
+            return;
+        }
+        self.board.set(pos, digit);
+        self.marks[pos.0][pos.1] = 0;
+        for (row, col) in Grid::peers(pos) {
+            self.marks[row][col] &= !(1 << digit);
+        }
+    }
+
+    pub fn clear(&mut self, pos: Pos) {
+        if !self.is_given(pos) && !self.is_solved() {
+            self.board.set(pos, 0);
+            self.marks[pos.0][pos.1] = 0;
+        }
+    }
+
+    /// 只有空格可以做铅笔标记。
+    pub fn toggle_mark(&mut self, pos: Pos, digit: u8) {
+        if self.board.get(pos) == 0 {
+            self.marks[pos.0][pos.1] ^= 1 << digit;
+        }
+    }
+
+    /// 填对一个格子并返回它的位置：`at` 是空格或填错了时填 `at`，否则先改正填错的格子，
+    /// 再填候选数字最少的空格。已经解完时为 `None`。
+    pub fn hint(&mut self, at: Pos) -> Option<Pos> {
+        let wrong = |pos: Pos| self.board.get(pos) != self.solution.get(pos);
+        let pos = if wrong(at) {
+            at
+        } else {
+            Grid::positions()
+                .filter(|&pos| self.board.get(pos) != 0)
+                .find(|&pos| wrong(pos))
+                .or_else(|| {
+                    Grid::positions()
+                        .filter(|&pos| self.board.get(pos) == 0)
+                        .min_by_key(|&pos| self.board.candidates(pos).count_ones())
+                })?
+        };
+        self.enter(pos, self.solution.get(pos));
+        self.hints += 1;
+        Some(pos)
+    }
+
+    /// 直接填上答案。
+    pub fn solve(&mut self) {
+        self.board = self.solution;
+        self.marks = Default::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    fn game() -> Game {
+        let givens: Grid = PUZZLE.parse().unwrap();
+        Game::restore(Difficulty::Easy, givens, givens, Default::default(), 0).unwrap()
+    }
+
+    #[test]
+    fn entries_and_marks() {
+        let mut game = game();
+        game.enter((0, 0), 1);
+        assert_eq!(game.board().get((0, 0)), 5);
+
+        game.toggle_mark((0, 2), 4);
+        game.toggle_mark((0, 2), 1);
+        game.toggle_mark((8, 2), 4);
+        assert_eq!(game.marks()[0][2], 1 << 1 | 1 << 4);
+        // 填上 4 后同一列的标记 4 被擦掉。
+        game.enter((1, 2), 4);
+        assert_eq!(game.marks()[0][2], 1 << 1);
+        assert_eq!(game.marks()[8][2], 0);
+        game.toggle_mark((1, 2), 2);
+        assert_eq!(game.marks()[1][2], 0);
+
+        game.enter((0, 2), 5);
+        assert_eq!(game.conflicts(), [(0, 0), (0, 2)]);
+        game.clear((0, 2));
+        assert!(game.conflicts().is_empty());
+    }
+
+    #[test]
+    fn hints_fix_mistakes_first() {
+        let mut game = game();
+        // (1, 2) 的答案是 2，填成 4 是错的，但现在还没有冲突。
+        game.enter((1, 2), 4);
+        assert!(game.conflicts().is_empty());
+        assert_eq!(game.hint((0, 0)), Some((1, 2)));
+        assert_eq!(game.board().get((1, 2)), 2);
+        // 光标所在的空格直接填上答案。
+        assert_eq!(game.hint((0, 2)), Some((0, 2)));
+        assert_eq!(game.board().get((0, 2)), 4);
+        assert_eq!(game.hints(), 2);
+
+        game.solve();
+        assert!(game.is_solved());
+        assert_eq!(game.hint((0, 0)), None);
+    }
+
+    #[test]
+    fn restore_checks_the_board() {
+        let givens: Grid = PUZZLE.parse().unwrap();
+        let mut board = givens;
+        board.set((0, 0), 1);
+        let error = Game::restore(Difficulty::Easy, givens, board, Default::default(), 0);
+        assert_eq!(error.unwrap_err(), "the saved board changes a given digit");
+    }
+}
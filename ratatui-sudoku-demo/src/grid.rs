@@ -0,0 +1,223 @@
+//! 9×9 的数独网格，以及求解和出题。
+//!
+//! 求解用回溯：每次选候选数字最少的空格尝试，这对人出的题目基本不需要回溯几次。出题时先用
+//! 打乱顺序的求解填满一个网格，再按随机顺序挖空，每挖一个都检查答案是否仍然唯一。
+
+use std::{fmt, str::FromStr};
+
+use rand::{seq::SliceRandom, Rng, RngCore};
+
+pub const SIZE: usize = 9;
+
+/// 数字的集合，第 `d` 位表示数字 `d`。
+pub type Digits = u16;
+
+const ALL_DIGITS: Digits = 0b11_1111_1110;
+
+/// 格子的行和列。
+pub type Pos = (usize, usize);
+
+/// 0 表示空格。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Grid([[u8; SIZE]; SIZE]);
+
+impl Grid {
+    pub fn get(&self, (row, col): Pos) -> u8 {
+        self.0[row][col]
+    }
+
+    pub fn set(&mut self, (row, col): Pos, digit: u8) {
+        self.0[row][col] = digit;
+    }
+
+    /// 所有格子，按行排列。
+    pub fn positions() -> impl Iterator<Item = Pos> {
+        (0..SIZE).flat_map(|row| (0..SIZE).map(move |col| (row, col)))
+    }
+
+    /// 与 `pos` 在同一行、同一列或同一宫的其他 20 个格子。
+    pub fn peers((row, col): Pos) -> impl Iterator<Item = Pos> {
+        let (box_row, box_col) = (row / 3 * 3, col / 3 * 3);
+        Self::positions().filter(move |&(r, c)| {
+            (r, c) != (row, col)
+                && (r == row || c == col || (r / 3 * 3, c / 3 * 3) == (box_row, box_col))
+        })
+    }
+
+    /// 填在 `pos` 不会和其他格子冲突的数字。
+    pub fn candidates(&self, pos: Pos) -> Digits {
+        Self::peers(pos).fold(ALL_DIGITS, |digits, peer| digits & !(1 << self.get(peer)))
+    }
+
+    /// 和同一行、列或宫中其他格子数字相同的格子。
+    pub fn conflicts(&self) -> Vec<Pos> {
+        Self::positions()
+            .filter(|&pos| {
+                let digit = self.get(pos);
+                digit != 0 && Self::peers(pos).any(|peer| self.get(peer) == digit)
+            })
+            .collect()
+    }
+
+    /// 任意一个解。已经有冲突或者无解时为 `None`。
+    pub fn solve(&self) -> Option<Self> {
+        self.solutions(1, None).pop()
+    }
+
+    /// 最多找 `limit` 个解，用来判断答案是否唯一。
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.solutions(limit, None).len()
+    }
+
+    fn solutions(&self, limit: usize, rng: Option<&mut dyn RngCore>) -> Vec<Self> {
+        let mut found = Vec::new();
+        if self.conflicts().is_empty() {
+            let mut grid = *self;
+            grid.search(limit, rng, &mut found);
+        }
+        found
+    }
+
+    fn search<'a>(
+        &mut self,
+        limit: usize,
+        mut rng: Option<&mut (dyn RngCore + 'a)>,
+        found: &mut Vec<Self>,
+    ) {
+        let mut best: Option<(Pos, Digits)> = None;
+        for pos in Self::positions().filter(|&pos| self.get(pos) == 0) {
+            let digits = self.candidates(pos);
+            if best.is_none_or(|(_, best)| digits.count_ones() < best.count_ones()) {
+                best = Some((pos, digits));
+                if digits.count_ones() <= 1 {
+                    break;
+                }
+            }
+        }
+        let Some((pos, digits)) = best else {
+            found.push(*self);
+            return;
+        };
+        let mut order: Vec<u8> = (1..=9).filter(|&digit| digits & 1 << digit != 0).collect();
+        if let Some(rng) = rng.as_deref_mut() {
+            order.shuffle(rng);
+        }
+        for digit in order {
+            self.set(pos, digit);
+            self.search(limit, rng.as_deref_mut(), found);
+            if found.len() >= limit {
+                break;
+            }
+        }
+        self.set(pos, 0);
+    }
+
+    /// 出一道答案唯一的题目，挖空到只剩 `givens` 个数字或者再挖就不唯一为止。
+    /// 返回题目和答案。
+    pub fn generate(givens: usize, rng: &mut impl Rng) -> (Self, Self) {
+        let solution = Self::default()
+            .solutions(1, Some(&mut *rng))
+            .pop()
+            .expect("an empty grid always has a solution");
+        let mut puzzle = solution;
+        let mut positions: Vec<Pos> = Self::positions().collect();
+        positions.shuffle(rng);
+        let mut filled = SIZE * SIZE;
+        for pos in positions {
+            if filled <= givens {
+                break;
+            }
+            puzzle.set(pos, 0);
+            if puzzle.count_solutions(2) == 1 {
+                filled -= 1;
+            } else {
+                puzzle.set(pos, solution.get(pos));
+            }
+        }
+        (puzzle, solution)
+    }
+}
+
+/// 按行排列的 81 个字符，空格写作 `.`。
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for pos in Self::positions() {
+            match self.get(pos) {
+                0 => f.write_str(".")?,
+                digit => write!(f, "{digit}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Grid {
+    type Err = String;
+
+    /// 空格可以写作 `.` 或 `0`，其他空白字符被忽略。
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut grid = Self::default();
+        let mut positions = Self::positions();
+        for c in s.chars().filter(|c| !c.is_whitespace()) {
+            let digit = match c {
+                '.' => 0,
+                c => c
+                    .to_digit(10)
+                    .ok_or_else(|| format!("unexpected character {c:?} in grid"))?
+                    as u8,
+            };
+            let pos = positions.next().ok_or("a grid has only 81 cells")?;
+            grid.set(pos, digit);
+        }
+        if positions.next().is_some() {
+            return Err("a grid needs 81 cells".into());
+        }
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn filled(grid: &Grid) -> usize {
+        Grid::positions().filter(|&pos| grid.get(pos) != 0).count()
+    }
+
+    const PUZZLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    #[test]
+    fn solve_and_check() {
+        let puzzle: Grid = PUZZLE.parse().unwrap();
+        assert_eq!(puzzle.to_string(), PUZZLE);
+        assert_eq!(filled(&puzzle), 30);
+        assert_eq!(puzzle.candidates((0, 2)), 1 << 1 | 1 << 2 | 1 << 4);
+
+        let solution = puzzle.solve().unwrap();
+        assert_eq!(solution.to_string()[..18], *"534678912672195348");
+        assert!(solution.conflicts().is_empty());
+        assert_eq!(puzzle.count_solutions(2), 1);
+
+        let mut wrong = puzzle;
+        wrong.set((0, 2), 5);
+        assert_eq!(wrong.conflicts(), [(0, 0), (0, 2)]);
+        assert_eq!(wrong.solve(), None);
+
+        assert!("12".parse::<Grid>().is_err());
+        assert!(format!("{PUZZLE}1").parse::<Grid>().is_err());
+    }
+
+    #[test]
+    fn generate_unique_puzzles() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let (puzzle, solution) = Grid::generate(30, &mut rng);
+        assert_eq!(filled(&puzzle), 30);
+        assert_eq!(puzzle.count_solutions(2), 1);
+        assert_eq!(puzzle.solve(), Some(solution));
+        assert!(Grid::positions()
+            .all(|pos| puzzle.get(pos) == 0 || puzzle.get(pos) == solution.get(pos)));
+    }
+}
@@ -0,0 +1,465 @@
+//! 数独演示：随机出题，可以填数字、做铅笔标记，冲突的数字标成红色，卡住时可以要提示。
+//!
+//! 题目的生成和求解见 `grid` 模块。每次修改后进度立即保存到数据目录中的 `puzzle.json`，
+//! 包括已经用掉的时间，下次启动时继续。终端够高时每个格子占 3 行，空格中显示 3×3 的铅笔标记；
+//! 否则每个格子占 1 行，光标所在格子的标记显示在状态栏。
+//!
+//! 按键：方向键或 `h` / `j` / `k` / `l` 移动，`1`–`9` 填数字，`0` / `Backspace` 清除，
+//! `p` 切换铅笔模式，`?` 提示，`s` 显示答案，`n` 出新题，`d` 换难度并出新题，`q` 保存并退出。
+
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use clap::Parser;
+use color_eyre::{eyre::eyre, Result};
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use directories::ProjectDirs;
+use rand::{rngs::StdRng, SeedableRng};
+use ratatui::{prelude::*, widgets::Paragraph};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+};
+
+use crate::{
+    game::{Difficulty, Game},
+    grid::{Pos, SIZE},
+    save::SaveFile,
+};
+
+mod game;
+mod grid;
+mod save;
+
+const APPLICATION: &str = "ratatui-sudoku-demo";
+
+/// 等待按键的最长时间，之后刷新计时器。
+const REFRESH: Duration = Duration::from_millis(250);
+
+/// 棋盘的宽度：9 个 3 列宽的格子和 4 条竖线。
+const BOARD_WIDTH: u16 = 31;
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 出新题时的难度
+    #[arg(long, value_enum, default_value_t)]
+    difficulty: Difficulty,
+    /// 保存进度的文件，默认是数据目录中的 `puzzle.json`
+    #[arg(long, value_name = "FILE")]
+    save: Option<PathBuf>,
+    /// 不继续保存的进度，直接出新题
+    #[arg(long)]
+    new: bool,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let path = cli
+        .save
+        .or_else(|| {
+            let project = ProjectDirs::from("", "", APPLICATION)?;
+            Some(project.data_dir().join("puzzle.json"))
+        })
+        .ok_or_else(|| eyre!("no data directory, pass --save"))?;
+    let save = SaveFile::new(path);
+    let mut rng = StdRng::from_entropy();
+    let saved = if cli.new { None } else { save.load()? };
+    let (game, elapsed) =
+        saved.unwrap_or_else(|| (Game::new(cli.difficulty, &mut rng), Duration::ZERO));
+
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result =
+        App::new(game, save, rng, elapsed, Instant::now()).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+fn format_time(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+struct App {
+    game: Game,
+    save: SaveFile,
+    rng: StdRng,
+    cursor: Pos,
+    /// 铅笔模式下数字键切换标记而不是填数字。
+    pencil: bool,
+    /// 本次启动之前已经用掉的时间。
+    clock: Duration,
+    started: Instant,
+    /// 解完时用了多长时间。
+    finished: Option<Duration>,
+    /// 是不是直接显示了答案。
+    revealed: bool,
+    /// 最近一次保存的错误。
+    error: Option<String>,
+    exit: bool,
+}
+
+impl App {
+    fn new(game: Game, save: SaveFile, rng: StdRng, elapsed: Duration, now: Instant) -> Self {
+        Self {
+            game,
+            save,
+            rng,
+            cursor: (0, 0),
+            pencil: false,
+            clock: elapsed,
+            started: now,
+            finished: None,
+            revealed: false,
+            error: None,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut(), Instant::now()))?;
+            if events.poll(REFRESH)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key.code, Instant::now());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_key(&mut self, code: KeyCode, now: Instant) {
+        let (row, col) = self.cursor;
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                // 保存用掉的时间。
+                self.changed(now);
+                self.exit = true;
+            }
+            KeyCode::Left | KeyCode::Char('h') => self.cursor.1 = col.saturating_sub(1),
+            KeyCode::Right | KeyCode::Char('l') => self.cursor.1 = (col + 1).min(SIZE - 1),
+            KeyCode::Up | KeyCode::Char('k') => self.cursor.0 = row.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.cursor.0 = (row + 1).min(SIZE - 1),
+            KeyCode::Char(c @ '1'..='9') => {
+                let digit = c as u8 - b'0';
+                if self.pencil {
+                    self.game.toggle_mark(self.cursor, digit);
+                } else {
+                    self.game.enter(self.cursor, digit);
+                }
+                self.changed(now);
+            }
+            KeyCode::Char('0') | KeyCode::Backspace | KeyCode::Delete => {
+                self.game.clear(self.cursor);
+                self.changed(now);
+            }
+            KeyCode::Char('p') => self.pencil = !self.pencil,
+            KeyCode::Char('?') => {
+                if let Some(pos) = self.game.hint(self.cursor) {
+                    self.cursor = pos;
+                    self.changed(now);
+                }
+            }
+            KeyCode::Char('s') if !self.game.is_solved() => {
+                self.game.solve();
+                self.revealed = true;
+                self.changed(now);
+            }
+            KeyCode::Char('n') => self.restart(self.game.difficulty, now),
+            KeyCode::Char('d') => self.restart(self.game.difficulty.next(), now),
+            _ => {}
+        }
+    }
+
+    /// 修改后保存进度。解完时停止计时并删除进度。
+    fn changed(&mut self, now: Instant) {
+        let result = if self.game.is_solved() {
+            self.finished.get_or_insert(self.elapsed(now));
+            self.save.remove()
+        } else {
+            self.save.save(&self.game, self.elapsed(now))
+        };
+        self.error = result.err().map(|error| format!("{error:#}"));
+    }
+
+    fn restart(&mut self, difficulty: Difficulty, now: Instant) {
+        self.game = Game::new(difficulty, &mut self.rng);
+        self.clock = Duration::ZERO;
+        self.started = now;
+        self.finished = None;
+        self.revealed = false;
+        self.changed(now);
+    }
+
+    fn elapsed(&self, now: Instant) -> Duration {
+        self.finished
+            .unwrap_or_else(|| self.clock + now.saturating_duration_since(self.started))
+    }
+
+    /// 格子的第 `line` 行。`height` 为 1 时空格只显示一个点，有标记时显示星号。
+    fn cell_span(&self, pos: Pos, line: u16, height: u16, conflicts: &[Pos]) -> Span<'static> {
+        let digit = self.game.board().get(pos);
+        let span = if digit != 0 {
+            let text = if height == 1 || line == 1 {
+                format!(" {digit} ")
+            } else {
+                "   ".into()
+            };
+            let style = if conflicts.contains(&pos) {
+                Style::new().light_red().bold()
+            } else if self.game.is_given(pos) {
+                Style::new().bold()
+            } else {
+                Style::new().light_blue()
+            };
+            Span::styled(text, style)
+        } else {
+            let marks = self.game.marks()[pos.0][pos.1];
+            let text: String = if height == 1 {
+                if marks == 0 { " · " } else { " * " }.into()
+            } else {
+                (1..=3)
+                    .map(|digit| digit + line as u8 * 3)
+                    .map(|digit| {
+                        if marks & 1 << digit != 0 {
+                            char::from(b'0' + digit)
+                        } else {
+                            ' '
+                        }
+                    })
+                    .collect()
+            };
+            Span::styled(text, Style::new().dark_gray())
+        };
+        if pos == self.cursor && !self.game.is_solved() {
+            span.reversed()
+        } else {
+            span
+        }
+    }
+
+    fn board_lines(&self, height: u16) -> Vec<Line<'static>> {
+        let border = |left: &str, middle: &str, right: &str| {
+            let segment = "─".repeat(9);
+            Line::from(format!(
+                "{left}{segment}{middle}{segment}{middle}{segment}{right}"
+            ))
+        };
+        let conflicts = self.game.conflicts();
+        let mut lines = vec![border("┌", "┬", "┐")];
+        for row in 0..SIZE {
+            for line in 0..height {
+                let mut spans = vec![Span::raw("│")];
+                for col in 0..SIZE {
+                    spans.push(self.cell_span((row, col), line, height, &conflicts));
+                    if col % 3 == 2 {
+                        spans.push("│".into());
+                    }
+                }
+                lines.push(Line::from(spans));
+            }
+            if row == SIZE - 1 {
+                lines.push(border("└", "┴", "┘"));
+            } else if row % 3 == 2 {
+                lines.push(border("├", "┼", "┤"));
+            }
+        }
+        lines
+    }
+
+    fn status_line(&self, compact: bool, now: Instant) -> Line<'static> {
+        let marks = self.game.marks()[self.cursor.0][self.cursor.1];
+        if let Some(error) = &self.error {
+            Line::from(error.clone().red())
+        } else if self.revealed {
+            Line::from("Solution revealed. Press n for a new puzzle".yellow())
+        } else if self.game.is_solved() {
+            let hints = match self.game.hints() {
+                0 => "no hints".to_string(),
+                1 => "1 hint".to_string(),
+                hints => format!("{hints} hints"),
+            };
+            Line::from(
+                format!("Solved in {} with {hints}!", format_time(self.elapsed(now)))
+                    .green()
+                    .bold(),
+            )
+        } else if compact && marks != 0 {
+            let digits: Vec<String> = (1..=9)
+                .filter(|digit| marks & 1 << digit != 0)
+                .map(|digit| digit.to_string())
+                .collect();
+            Line::from(format!("Pencil marks {}", digits.join(" ")))
+        } else {
+            Line::from(
+                "Digits <1-9> Clear <0> Pencil <P> Hint <?> Solve <S> New <N> Level <D>".dim(),
+            )
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, now: Instant) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        // 棋盘上面还有一行标题。
+        let cell_height = if main.height > 4 + 9 * 3 { 3 } else { 1 };
+        let height = 1 + 4 + 9 * cell_height;
+        let board_area = Rect {
+            x: main.x + main.width.saturating_sub(BOARD_WIDTH) / 2,
+            y: main.y + main.height.saturating_sub(height) / 2,
+            width: BOARD_WIDTH.min(main.width),
+            height: height.min(main.height),
+        };
+        let [title, board] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(board_area);
+
+        let mut name = vec![self.game.difficulty.name().bold()];
+        if self.pencil {
+            name.push(" · Pencil".yellow());
+        }
+        Paragraph::new(Line::from(name)).render(title, buf);
+        let counters = format!(
+            "Hints {}  {}",
+            self.game.hints(),
+            format_time(self.elapsed(now))
+        );
+        Paragraph::new(Line::from(counters).right_aligned()).render(title, buf);
+
+        Paragraph::new(self.board_lines(cell_height)).render(board, buf);
+        Paragraph::new(self.status_line(cell_height == 1, now)).render(status, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use ratatui_common::testing;
+
+    use super::*;
+    use crate::grid::Grid;
+
+    const PUZZLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    fn dir(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("sudoku-demo-{name}-{}", process::id()))
+    }
+
+    fn app(name: &str, now: Instant) -> App {
+        let givens: Grid = PUZZLE.parse().unwrap();
+        let game = Game::restore(Difficulty::Easy, givens, givens, Default::default(), 0).unwrap();
+        let save = SaveFile::new(dir(name).join("puzzle.json"));
+        App::new(
+            game,
+            save,
+            StdRng::seed_from_u64(1),
+            Duration::from_secs(60),
+            now,
+        )
+    }
+
+    fn rows(app: &App, width: u16, height: u16, now: Instant) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf, now))
+    }
+
+    #[test]
+    fn compact_board() {
+        let now = Instant::now();
+        let mut app = app("compact", now);
+        app.handle_key(KeyCode::Right, now);
+        app.handle_key(KeyCode::Right, now);
+        app.handle_key(KeyCode::Char('p'), now);
+        app.handle_key(KeyCode::Char('4'), now);
+        app.handle_key(KeyCode::Char('1'), now);
+
+        let rows = rows(&app, 31, 16, now + Duration::from_secs(5));
+        assert_eq!(rows[0], "Easy · Pencil    Hints 0  01:05");
+        assert_eq!(rows[1], "┌─────────┬─────────┬─────────┐");
+        assert_eq!(rows[2], "│ 5  3  * │ ·  7  · │ ·  ·  · │");
+        assert_eq!(rows[13], "└─────────┴─────────┴─────────┘");
+        assert_eq!(rows[15].trim_end(), "Pencil marks 1 4");
+        fs::remove_dir_all(dir("compact")).unwrap();
+    }
+
+    #[test]
+    fn pencil_marks_and_conflicts() {
+        let now = Instant::now();
+        let mut app = app("large", now);
+        app.cursor = (0, 2);
+        for c in ['p', '1', '2', '4', '8', 'p'] {
+            app.handle_key(KeyCode::Char(c), now);
+        }
+        app.handle_key(KeyCode::Right, now);
+        app.handle_key(KeyCode::Char('5'), now);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 31, 34));
+        app.render(buf.area, &mut buf, now);
+        // 每个格子 3 行，标记按 3×3 排列。
+        assert_eq!(
+            testing::buffer_rows(&buf)[2..5],
+            [
+                "│      12 │         │         │",
+                "│ 5  3 4  │ 5  7    │         │",
+                "│       8 │         │         │",
+            ]
+        );
+        // 和第一格冲突的 5 是红色的，题目给出的 3 不是。
+        assert_eq!(buf.get(11, 3).fg, Color::LightRed);
+        assert_eq!(buf.get(1, 3).fg, Color::LightRed);
+        assert_eq!(buf.get(5, 3).fg, Color::Reset);
+        fs::remove_dir_all(dir("large")).unwrap();
+    }
+
+    #[test]
+    fn hints_timer_and_progress() {
+        let start = Instant::now();
+        let mut app = app("progress", start);
+        app.cursor = (4, 4);
+        app.handle_key(KeyCode::Char('?'), start);
+        assert_eq!(app.game.board().get((4, 4)), 5);
+        assert_eq!(app.game.hints(), 1);
+
+        // 退出时保存用掉的时间，下次从这里继续。
+        app.handle_key(KeyCode::Char('q'), start + Duration::from_secs(30));
+        let (game, elapsed) = app.save.load().unwrap().unwrap();
+        assert_eq!(
+            (game.board(), elapsed),
+            (app.game.board(), Duration::from_secs(90))
+        );
+
+        app.handle_key(KeyCode::Char('s'), start + Duration::from_secs(40));
+        assert!(app.game.is_solved());
+        assert!(app.save.load().unwrap().is_none());
+        assert_eq!(
+            app.elapsed(start + Duration::from_secs(500)),
+            Duration::from_secs(100)
+        );
+        assert_eq!(
+            app.status_line(false, start).to_string(),
+            "Solution revealed. Press n for a new puzzle"
+        );
+
+        app.handle_key(KeyCode::Char('d'), start);
+        assert_eq!(app.game.difficulty, Difficulty::Medium);
+        let givens = app.game.givens();
+        let filled = Grid::positions()
+            .filter(|&pos| givens.get(pos) != 0)
+            .count();
+        assert_eq!(filled, Difficulty::Medium.givens());
+        assert_eq!(
+            app.elapsed(start + Duration::from_secs(3)),
+            Duration::from_secs(3)
+        );
+        assert!(app.save.load().unwrap().is_some());
+        fs::remove_dir_all(dir("progress")).unwrap();
+    }
+}
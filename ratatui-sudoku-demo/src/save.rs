@@ -0,0 +1,137 @@
+//! 保存未完成的题目，保存为 JSON，每次修改后立即写入，下次启动时继续。解完后删除。
+//!
+//! 网格保存为按行排列的 81 个字符，空格写作 `.`，这样文件可以直接看出题目。
+
+use std::{fs, io, path::PathBuf, time::Duration};
+
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use ratatui_common::files;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{Difficulty, Game},
+    grid::{Digits, Grid, Pos},
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Saved {
+    difficulty: Difficulty,
+    givens: String,
+    board: String,
+    /// 按行排列的 81 个铅笔标记，第 `d` 位表示数字 `d`。
+    marks: Vec<Digits>,
+    hints: u32,
+    elapsed_secs: u64,
+}
+
+#[derive(Debug)]
+pub struct SaveFile {
+    path: PathBuf,
+}
+
+impl SaveFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// 读取保存的进度和已经用掉的时间。文件还不存在时为 `None`。
+    pub fn load(&self) -> Result<Option<(Game, Duration)>> {
+        let json = match fs::read_to_string(&self.path) {
+            Ok(json) => json,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err).wrap_err_with(|| format!("reading {} failed", self.path.display()))
+            }
+        };
+        let parse = || -> Result<(Game, Duration)> {
+            let saved: Saved = serde_json::from_str(&json)?;
+            let grid = |text: &str| text.parse::<Grid>().map_err(|error| eyre!(error));
+            if saved.marks.len() != 81 {
+                return Err(eyre!("expected 81 pencil marks"));
+            }
+            let mut marks = [[0; 9]; 9];
+            for (pos, digits) in Grid::positions().zip(saved.marks) {
+                marks[pos.0][pos.1] = digits;
+            }
+            let game = Game::restore(
+                saved.difficulty,
+                grid(&saved.givens)?,
+                grid(&saved.board)?,
+                marks,
+                saved.hints,
+            )
+            .map_err(|error| eyre!(error))?;
+            Ok((game, Duration::from_secs(saved.elapsed_secs)))
+        };
+        parse()
+            .map(Some)
+            .wrap_err_with(|| format!("parsing {} failed", self.path.display()))
+    }
+
+    /// 保存当前的游戏和用时。
+    pub fn save(&self, game: &Game, elapsed: Duration) -> Result<()> {
+        let saved = Saved {
+            difficulty: game.difficulty,
+            givens: game.givens().to_string(),
+            board: game.board().to_string(),
+            marks: Grid::positions()
+                .map(|(row, col): Pos| game.marks()[row][col])
+                .collect(),
+            hints: game.hints(),
+            elapsed_secs: elapsed.as_secs(),
+        };
+        serde_json::to_vec_pretty(&saved)
+            .map_err(io::Error::from)
+            .and_then(|bytes| files::write_atomic(&self.path, bytes))
+            .wrap_err_with(|| format!("writing {} failed", self.path.display()))
+    }
+
+    /// 解完后删除进度，下次启动时出新题。
+    pub fn remove(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                Err(err).wrap_err_with(|| format!("removing {} failed", self.path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn persist_progress() {
+        let dir = env::temp_dir().join(format!("sudoku-demo-save-{}", process::id()));
+        let file = SaveFile::new(dir.join("puzzle.json"));
+        assert!(file.load().unwrap().is_none());
+
+        let mut game = Game::new(Difficulty::Easy, &mut StdRng::seed_from_u64(3));
+        let empty = Grid::positions().find(|&pos| !game.is_given(pos)).unwrap();
+        game.toggle_mark(empty, 6);
+        game.hint((8, 8));
+        file.save(&game, Duration::from_secs(95)).unwrap();
+
+        let (restored, elapsed) = file.load().unwrap().unwrap();
+        assert_eq!(elapsed, Duration::from_secs(95));
+        assert_eq!(restored.board(), game.board());
+        assert_eq!(restored.marks(), game.marks());
+        assert_eq!(
+            (restored.difficulty, restored.hints()),
+            (Difficulty::Easy, 1)
+        );
+
+        file.remove().unwrap();
+        file.remove().unwrap();
+        assert!(file.load().unwrap().is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
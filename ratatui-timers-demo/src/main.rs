@@ -0,0 +1,548 @@
+//! 多个计时器演示：同时运行几个有名字的倒计时，每个都有自己的进度条，可以暂停和重置，到期时
+//! 响铃并闪烁提醒。
+//!
+//! 所有计时器共用一个调度（见 `timer` 模块）：主循环只等待到最早需要更新的时刻，没有计时器在走
+//! 时不会定时重绘。重置计时器时进度条平滑地退回去。减少动态效果时到期的计时器不闪烁，只用红色
+//! 标出，进度条也直接跳到新的位置。
+//!
+//! 启动时可以在命令行中给出计时器，例如 `ratatui-timers-demo tea=3m pasta=9m`。
+//!
+//! 按键：`↑` / `↓` 选择，`Space` 开始或暂停，`r` 重置，`a` 添加，`x` 删除，`q` 退出。
+
+use std::{
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use clap::Parser;
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Clear, Gauge, Paragraph},
+};
+use ratatui_common::{
+    animation::{Animation, Easing},
+    countdown::{self, Countdown, Format},
+    events::{EventSource, TerminalEvents},
+    layout::centered,
+    motion::Motion,
+    terminal,
+    text_input::Input,
+};
+
+use crate::timer::{parse_duration, State, Timer};
+
+mod timer;
+
+/// 没有计时器在走时等待按键的最长时间。
+const IDLE: Duration = Duration::from_secs(60);
+
+/// 每个计时器占的行数：边框和进度条。
+const TIMER_HEIGHT: u16 = 3;
+
+/// 进度条退回去的时长。
+const GAUGE_DURATION: Duration = Duration::from_millis(300);
+
+/// 进度条左边剩余时间的宽度，放得下 `1:02:03` 和后面的空格。
+const COUNTDOWN_WIDTH: u16 = 8;
+
+const NAME: usize = 0;
+const DURATION: usize = 1;
+const LABELS: [&str; 2] = ["Name:     ", "Duration: "];
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 启动时添加的计时器，写作 NAME=DURATION，例如 tea=3m
+    timers: Vec<String>,
+    /// 减少动态效果：到期时不闪烁
+    #[arg(long)]
+    reduced_motion: bool,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let timers = cli
+        .timers
+        .iter()
+        .map(|spec| {
+            let (name, duration) = spec
+                .split_once('=')
+                .ok_or_else(|| eyre!("{spec:?} is not a timer like tea=3m"))?;
+            let duration = parse_duration(duration).map_err(|error| eyre!(error))?;
+            Ok(Timer::new(name.trim(), duration))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let motion = Motion {
+        reduced: cli.reduced_motion,
+    };
+
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new(timers, motion).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+/// 添加计时器的表单。
+struct Form {
+    fields: [Input; 2],
+    field: usize,
+}
+
+impl Form {
+    fn new() -> Self {
+        Self {
+            fields: [Input::default(), Input::with_value("5m")],
+            field: NAME,
+        }
+    }
+
+    /// 检查每一项，出错时返回出错的那一项和错误。
+    fn timer(&self) -> Result<Timer, (usize, String)> {
+        let name = self.fields[NAME].value().trim();
+        if name.is_empty() {
+            return Err((NAME, "the timer needs a name".into()));
+        }
+        let duration =
+            parse_duration(self.fields[DURATION].value()).map_err(|error| (DURATION, error))?;
+        Ok(Timer::new(name, duration))
+    }
+}
+
+struct App {
+    timers: Vec<Timer>,
+    /// 每个计时器的进度条，只在退回去时播放动画，其余时候直接显示计时器的进度。
+    gauges: Vec<Animation<f64>>,
+    selected: usize,
+    motion: Motion,
+    form: Option<Form>,
+    /// 有计时器刚刚到期，下一次绘制前响铃。
+    bell: bool,
+    /// 显示在状态栏中的提示或错误，下一次按键时清除。
+    message: Option<Result<String, String>>,
+    exit: bool,
+}
+
+impl App {
+    fn new(timers: Vec<Timer>, motion: Motion) -> Self {
+        Self {
+            gauges: timers.iter().map(|_| gauge()).collect(),
+            timers,
+            selected: 0,
+            motion,
+            form: None,
+            bell: false,
+            message: None,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            let now = Instant::now();
+            self.tick(now);
+            if self.bell {
+                self.bell = false;
+                let mut stdout = io::stdout();
+                stdout
+                    .write_all(b"\x07")
+                    .and_then(|()| stdout.flush())
+                    .wrap_err("failed to ring the bell")?;
+            }
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut(), now))?;
+            let timeout = self
+                .next_deadline(now)
+                .map_or(IDLE, |deadline| deadline.saturating_duration_since(now));
+            if events.poll(timeout)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key, Instant::now());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn next_deadline(&self, now: Instant) -> Option<Instant> {
+        let frames = self.gauges.iter().filter_map(|gauge| gauge.next_frame(now));
+        timer::next_deadline(&self.timers, now, !self.motion.reduced)
+            .into_iter()
+            .chain(frames)
+            .min()
+    }
+
+    /// 更新所有计时器和进度条的动画，有计时器到期时响铃。
+    fn tick(&mut self, now: Instant) {
+        for gauge in &mut self.gauges {
+            gauge.tick(now);
+        }
+        for timer in &mut self.timers {
+            if timer.tick(now) {
+                self.bell = true;
+                self.message = Some(Ok(format!("{} is done", timer.name)));
+            }
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, now: Instant) {
+        self.message = None;
+        self.tick(now);
+        if self.form.is_some() {
+            self.handle_form_key(key);
+            return;
+        }
+        let last = self.timers.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.selected = (self.selected + 1).min(last),
+            KeyCode::Char('a') => self.form = Some(Form::new()),
+            KeyCode::Char(' ') | KeyCode::Enter => {
+                if let Some(timer) = self.timers.get_mut(self.selected) {
+                    timer.toggle(now);
+                }
+            }
+            KeyCode::Char('r') => {
+                if let Some(timer) = self.timers.get_mut(self.selected) {
+                    // 从当前显示的位置退回去，连续重置时接着上一段动画。
+                    let gauge = &mut self.gauges[self.selected];
+                    if !gauge.is_running() {
+                        gauge.set(timer.ratio(now));
+                    }
+                    timer.reset();
+                    gauge.animate_to(timer.ratio(now), self.motion, now);
+                }
+            }
+            KeyCode::Char('x') | KeyCode::Delete if !self.timers.is_empty() => {
+                self.gauges.remove(self.selected);
+                let timer = self.timers.remove(self.selected);
+                self.selected = self.selected.min(self.timers.len().saturating_sub(1));
+                self.message = Some(Ok(format!("Deleted {}", timer.name)));
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_form_key(&mut self, key: KeyEvent) {
+        let Some(form) = &mut self.form else {
+            return;
+        };
+        let len = form.fields.len();
+        match key.code {
+            KeyCode::Esc => self.form = None,
+            KeyCode::Tab | KeyCode::Down => form.field = (form.field + 1) % len,
+            KeyCode::BackTab | KeyCode::Up => form.field = (form.field + len - 1) % len,
+            KeyCode::Enter => match form.timer() {
+                Ok(timer) => {
+                    self.form = None;
+                    self.message =
+                        Some(Ok(format!("Added {}, press Space to start it", timer.name)));
+                    self.timers.push(timer);
+                    self.gauges.push(gauge());
+                    self.selected = self.timers.len() - 1;
+                }
+                Err((field, error)) => {
+                    form.field = field;
+                    self.message = Some(Err(error));
+                }
+            },
+            _ => {
+                form.fields[form.field].handle_key_event(key);
+            }
+        }
+    }
+
+    fn render_timer(
+        &self,
+        timer: &Timer,
+        gauge: &Animation<f64>,
+        selected: bool,
+        area: Rect,
+        buf: &mut Buffer,
+        now: Instant,
+    ) {
+        let (state, mut style) = match timer.state() {
+            State::Idle => ("Ready", Style::new().dark_gray()),
+            State::Running { .. } => ("Running", Style::new().green()),
+            State::Paused { .. } => ("Paused", Style::new().yellow()),
+            State::Expired { .. } => ("Done", Style::new().red()),
+        };
+        // 闪烁时亮暗交替；减少动态效果时保持不变。
+        if !self.motion.reduced && timer.blink(now) == Some(true) {
+            style = style.reversed();
+        }
+        let block = Block::bordered()
+            .title(Line::from(vec![
+                format!(" {} ", timer.name).bold(),
+                format!("{} ", countdown::format(timer.duration, Format::Compact)).dim(),
+            ]))
+            .title(Line::from(format!(" {state} ")).right_aligned())
+            .border_style(if selected { style.bold() } else { Style::new() });
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let [countdown_area, gauge_area] =
+            Layout::horizontal([Constraint::Length(COUNTDOWN_WIDTH), Constraint::Fill(1)])
+                .areas(inner);
+        Countdown::new(timer.remaining(now))
+            .style(Style::new().bold())
+            .threshold(Duration::from_secs(60), Style::new().yellow())
+            .threshold(Duration::from_secs(10), Style::new().red())
+            .render(countdown_area, buf);
+        let ratio = if gauge.is_running() {
+            gauge.value()
+        } else {
+            timer.ratio(now)
+        };
+        Gauge::default()
+            .ratio(ratio.clamp(0.0, 1.0))
+            .label("")
+            .gauge_style(style)
+            .use_unicode(true)
+            .render(gauge_area, buf);
+    }
+
+    fn render_form(form: &Form, area: Rect, buf: &mut Buffer) {
+        let popup = centered(area, 40, 6);
+        Clear.render(popup, buf);
+        let block = Block::bordered()
+            .title(" New timer ".bold())
+            .title_bottom(Line::from(" Add <Enter> Cancel <Esc> ").right_aligned())
+            .border_style(Style::new().yellow());
+        let inner = block.inner(popup);
+        block.render(popup, buf);
+        let rows = Layout::vertical([Constraint::Length(1); 2])
+            .margin(1)
+            .split(inner.inner(&Margin::new(1, 0)));
+        for (index, input) in form.fields.iter().enumerate() {
+            let style = if index == form.field {
+                Style::new().green().bold()
+            } else {
+                Style::new()
+            };
+            Paragraph::new(input.line(LABELS[index], style)).render(rows[index], buf);
+        }
+    }
+
+    fn status_line(&self, now: Instant) -> Line<'_> {
+        match &self.message {
+            Some(Ok(message)) => Line::from(message.as_str()),
+            Some(Err(error)) => Line::from(error.as_str().red()),
+            None => {
+                let running = self
+                    .timers
+                    .iter()
+                    .filter(|timer| matches!(timer.state(), State::Running { .. }))
+                    .count();
+                let next = self
+                    .timers
+                    .iter()
+                    .filter(|timer| matches!(timer.state(), State::Running { .. }))
+                    .min_by_key(|timer| timer.remaining(now));
+                let text = match next {
+                    Some(timer) => format!(
+                        "{running} running, {} is next in {}",
+                        timer.name,
+                        countdown::format(timer.remaining(now), Format::Clock)
+                    ),
+                    None => "Press a to add a timer".into(),
+                };
+                Line::from(text.dim())
+            }
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, now: Instant) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let block = Block::bordered()
+            .title(" Timers ")
+            .title_bottom(
+                Line::from(" Start <Space> Reset <r> Add <a> Delete <x> Quit <q> ").right_aligned(),
+            )
+            .border_style(Style::new().green());
+        let inner = block.inner(main);
+        block.render(main, buf);
+        if self.timers.is_empty() {
+            Paragraph::new("No timers yet".dim()).render(inner, buf);
+        }
+
+        // 放不下所有计时器时滚动，让选中的那个可见。
+        let fits = usize::from(inner.height / TIMER_HEIGHT).max(1);
+        let first = self.selected.saturating_sub(fits - 1);
+        for (row, (index, (timer, gauge))) in self
+            .timers
+            .iter()
+            .zip(&self.gauges)
+            .enumerate()
+            .skip(first)
+            .take(fits)
+            .enumerate()
+        {
+            let area = Rect {
+                y: inner.y + row as u16 * TIMER_HEIGHT,
+                height: TIMER_HEIGHT.min(inner.height),
+                ..inner
+            };
+            self.render_timer(timer, gauge, index == self.selected, area, buf, now);
+        }
+
+        if let Some(form) = &self.form {
+            App::render_form(form, main, buf);
+        }
+        Paragraph::new(self.status_line(now)).render(status, buf);
+    }
+}
+
+fn gauge() -> Animation<f64> {
+    Animation::new(0.0, GAUGE_DURATION, Easing::EaseInOut)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn rows(app: &App, width: u16, height: u16, now: Instant) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf, now))
+    }
+
+    fn app(motion: Motion) -> App {
+        App::new(
+            vec![
+                Timer::new("tea", Duration::from_secs(180)),
+                Timer::new("pasta", Duration::from_secs(540)),
+            ],
+            motion,
+        )
+    }
+
+    #[test]
+    fn run_timers_together() {
+        let start = Instant::now();
+        let at = |seconds: u64| start + Duration::from_secs(seconds);
+        let mut app = app(Motion::default());
+        app.handle_key(KeyCode::Char(' ').into(), start);
+        app.handle_key(KeyCode::Down.into(), at(60));
+        app.handle_key(KeyCode::Char(' ').into(), at(60));
+        assert_eq!(app.next_deadline(at(60)), Some(at(61)));
+
+        let screen = rows(&app, 60, 9, at(90));
+        assert_eq!(
+            screen[1],
+            "│┌ tea 3m ─────────────────────────────────────── Running ┐│"
+        );
+        assert_eq!(
+            screen[5],
+            "││08:30   ██▋                                             ││"
+        );
+        assert_eq!(screen[8].trim_end(), "2 running, tea is next in 01:30");
+
+        // 暂停后下一次更新只取决于另一个计时器。
+        app.handle_key(KeyCode::Char(' ').into(), at(120));
+        app.tick(at(180));
+        assert!(app.bell);
+        assert_eq!(app.message, Some(Ok("tea is done".into())));
+        let screen = rows(&app, 60, 9, at(180));
+        assert!(screen[1].ends_with("─ Done ┐│"));
+        assert!(screen[4].ends_with("─ Paused ┐│"));
+        assert_eq!(app.next_deadline(at(180)), Some(at(180) + timer::BLINK));
+
+        app.handle_key(KeyCode::Char('r').into(), at(200));
+        assert_eq!(app.timers[1].state(), State::Idle);
+        app.handle_key(KeyCode::Up.into(), at(200));
+        app.handle_key(KeyCode::Char('x').into(), at(200));
+        assert_eq!(app.message, Some(Ok("Deleted tea".into())));
+        // 只剩重置后退回去的进度条在播放动画。
+        assert_eq!(
+            app.next_deadline(at(200)),
+            Some(at(200) + ratatui_common::animation::FRAME)
+        );
+        app.tick(at(201));
+        assert_eq!(app.next_deadline(at(201)), None);
+    }
+
+    #[test]
+    fn gauge_moves_back_on_reset() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut app = app(Motion::default());
+        app.handle_key(KeyCode::Char(' ').into(), start);
+        app.handle_key(KeyCode::Char('r').into(), at(90_000));
+        assert_eq!(
+            rows(&app, 60, 9, at(90_000))[2],
+            "││03:00   ████████████████████████                        ││"
+        );
+        app.tick(at(90_150));
+        let halfway = rows(&app, 60, 9, at(90_150));
+        assert_eq!(
+            halfway[2],
+            "││03:00   ████████████                                    ││"
+        );
+        app.tick(at(90_300));
+        assert_eq!(
+            rows(&app, 60, 9, at(90_300))[2],
+            "││03:00                                                   ││"
+        );
+
+        // 减少动态效果时直接退回去。
+        let mut reduced = self::app(Motion::reduced());
+        reduced.handle_key(KeyCode::Char(' ').into(), start);
+        reduced.handle_key(KeyCode::Char('r').into(), at(90_000));
+        assert_eq!(reduced.next_deadline(at(90_000)), None);
+    }
+
+    #[test]
+    fn reduced_motion_does_not_blink() {
+        let start = Instant::now();
+        let mut app = app(Motion::reduced());
+        app.handle_key(KeyCode::Enter.into(), start);
+        app.tick(start + Duration::from_secs(180));
+        assert!(app.bell);
+        assert_eq!(app.next_deadline(start + Duration::from_secs(180)), None);
+    }
+
+    #[test]
+    fn add_a_timer() {
+        let now = Instant::now();
+        let mut app = App::new(Vec::new(), Motion::default());
+        assert!(rows(&app, 60, 9, now)[1].starts_with("│No timers yet"));
+        app.handle_key(KeyCode::Char('a').into(), now);
+        app.handle_key(KeyCode::Enter.into(), now);
+        assert_eq!(app.message, Some(Err("the timer needs a name".into())));
+        testing::type_text("eggs", |key| app.handle_key(key, now));
+        app.handle_key(KeyCode::Tab.into(), now);
+        testing::type_text("x", |key| app.handle_key(key, now));
+        app.handle_key(KeyCode::Enter.into(), now);
+        assert_eq!(
+            app.message,
+            Some(Err(
+                "\"5mx\" is not a duration like 5m, 1h30m or 25:00".into()
+            ))
+        );
+        assert_eq!(
+            rows(&app, 60, 9, now)[4],
+            "│         │  Duration: 5mx                       │         │"
+        );
+        for _ in 0.."5mx".len() {
+            app.handle_key(KeyCode::Backspace.into(), now);
+        }
+        testing::type_text("7m", |key| app.handle_key(key, now));
+        app.handle_key(KeyCode::Enter.into(), now);
+        assert_eq!(
+            app.message,
+            Some(Ok("Added eggs, press Space to start it".into()))
+        );
+        assert_eq!(app.timers[0].duration, Duration::from_secs(420));
+    }
+}
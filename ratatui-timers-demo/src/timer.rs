@@ -0,0 +1,259 @@
+//! 倒计时器，以及所有计时器共用的调度。
+//!
+//! 计时器本身不开线程也不定时轮询：每个计时器根据当前时间给出下一次需要更新显示的时刻
+//! （剩余的整秒数变化、到期、提醒闪烁切换），主循环只等待到所有计时器中最早的那个时刻。
+//! 时间都由调用方传入，测试中可以直接给出任意时刻。
+
+use std::time::{Duration, Instant};
+
+/// 到期后闪烁提醒的时长。
+pub const ALERT: Duration = Duration::from_secs(5);
+
+/// 闪烁时亮和暗各持续的时间。
+pub const BLINK: Duration = Duration::from_millis(500);
+
+const SECOND: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Idle,
+    Running {
+        deadline: Instant,
+    },
+    Paused {
+        remaining: Duration,
+    },
+    /// 到期的时刻。
+    Expired {
+        at: Instant,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timer {
+    pub name: String,
+    pub duration: Duration,
+    state: State,
+}
+
+impl Timer {
+    pub fn new(name: impl Into<String>, duration: Duration) -> Self {
+        Self {
+            name: name.into(),
+            duration,
+            state: State::Idle,
+        }
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn remaining(&self, now: Instant) -> Duration {
+        match self.state {
+            State::Idle => self.duration,
+            State::Running { deadline } => deadline.saturating_duration_since(now),
+            State::Paused { remaining } => remaining,
+            State::Expired { .. } => Duration::ZERO,
+        }
+    }
+
+    /// 已经走过的比例，用于进度条。
+    pub fn ratio(&self, now: Instant) -> f64 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        1.0 - self.remaining(now).as_secs_f64() / self.duration.as_secs_f64()
+    }
+
+    /// 开始、暂停或继续；到期后重新开始。
+    pub fn toggle(&mut self, now: Instant) {
+        self.state = match self.state {
+            State::Idle | State::Expired { .. } => State::Running {
+                deadline: now + self.duration,
+            },
+            State::Running { deadline } => State::Paused {
+                remaining: deadline.saturating_duration_since(now),
+            },
+            State::Paused { remaining } => State::Running {
+                deadline: now + remaining,
+            },
+        };
+    }
+
+    pub fn reset(&mut self) {
+        self.state = State::Idle;
+    }
+
+    /// 更新状态，刚刚到期时返回 `true`。
+    pub fn tick(&mut self, now: Instant) -> bool {
+        match self.state {
+            State::Running { deadline } if deadline <= now => {
+                self.state = State::Expired { at: deadline };
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 到期后的提醒是否正在闪烁，以及这一刻是亮还是暗。
+    pub fn blink(&self, now: Instant) -> Option<bool> {
+        let State::Expired { at } = self.state else {
+            return None;
+        };
+        let since = now.saturating_duration_since(at);
+        (since < ALERT).then(|| (since.as_millis() / BLINK.as_millis()).is_multiple_of(2))
+    }
+
+    /// 下一次需要更新显示的时刻。`blink` 为 `false`（减少动态效果）时到期后不再更新。
+    pub fn next_deadline(&self, now: Instant, blink: bool) -> Option<Instant> {
+        match self.state {
+            State::Running { deadline } => {
+                // 显示的秒数向上取整，剩余时间跨过整秒时更新。
+                let remaining = deadline.saturating_duration_since(now);
+                let to_next =
+                    Duration::from_nanos((remaining.as_nanos() % SECOND.as_nanos()) as u64);
+                Some(if to_next.is_zero() {
+                    (now + SECOND).min(deadline)
+                } else {
+                    now + to_next
+                })
+            }
+            State::Expired { at } if blink && now < at + ALERT => {
+                let since = now.saturating_duration_since(at);
+                let blinks = since.as_millis() / BLINK.as_millis() + 1;
+                Some(at + BLINK * blinks as u32)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// 所有计时器中最早需要更新的时刻。
+pub fn next_deadline<'a>(
+    timers: impl IntoIterator<Item = &'a Timer>,
+    now: Instant,
+    blink: bool,
+) -> Option<Instant> {
+    timers
+        .into_iter()
+        .filter_map(|timer| timer.next_deadline(now, blink))
+        .min()
+}
+
+/// 解析时长，例如 `90s`、`5m`、`1h30m`，或者 `25:00`、`1:02:03`。只写数字时表示分钟。
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let error = || format!("{text:?} is not a duration like 5m, 1h30m or 25:00");
+    if text.is_empty() {
+        return Err("the timer needs a duration".into());
+    }
+    let seconds = if text.contains(':') {
+        let parts: Vec<&str> = text.split(':').collect();
+        if parts.len() > 3 {
+            return Err(error());
+        }
+        let mut seconds = 0u64;
+        for (index, part) in parts.iter().enumerate() {
+            let value: u64 = part.parse().map_err(|_| error())?;
+            if index > 0 && value >= 60 {
+                return Err(error());
+            }
+            seconds = seconds * 60 + value;
+        }
+        seconds
+    } else if let Ok(minutes) = text.parse::<u64>() {
+        minutes * 60
+    } else {
+        let mut seconds = 0;
+        let mut number = String::new();
+        for c in text.chars() {
+            match c {
+                '0'..='9' => number.push(c),
+                'h' | 'm' | 's' if !number.is_empty() => {
+                    let value: u64 = number.parse().map_err(|_| error())?;
+                    seconds += value
+                        * match c {
+                            'h' => 3600,
+                            'm' => 60,
+                            _ => 1,
+                        };
+                    number.clear();
+                }
+                _ => return Err(error()),
+            }
+        }
+        if !number.is_empty() {
+            return Err(error());
+        }
+        seconds
+    };
+    if seconds == 0 {
+        return Err("the duration must be longer than zero".into());
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_down() {
+        let start = Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+        let mut timer = Timer::new("tea", Duration::from_secs(3));
+        assert_eq!(timer.next_deadline(start, true), None);
+
+        timer.toggle(start);
+        assert_eq!(timer.remaining(at(200)), Duration::from_millis(2800));
+        assert_eq!(timer.next_deadline(at(200), true), Some(at(1000)));
+        assert_eq!(timer.next_deadline(at(1000), true), Some(at(2000)));
+
+        // 暂停的时间不算在内。
+        timer.toggle(at(1500));
+        assert_eq!(timer.remaining(at(9000)), Duration::from_millis(1500));
+        assert_eq!(timer.next_deadline(at(9000), true), None);
+        timer.toggle(at(9000));
+        assert_eq!(timer.ratio(at(9000)), 0.5);
+        assert!(!timer.tick(at(10_000)));
+        assert!(timer.tick(at(10_600)));
+        assert!(!timer.tick(at(10_700)));
+        assert_eq!(timer.state(), State::Expired { at: at(10_500) });
+
+        assert_eq!(timer.blink(at(10_600)), Some(true));
+        assert_eq!(timer.blink(at(11_100)), Some(false));
+        assert_eq!(timer.next_deadline(at(10_600), true), Some(at(11_000)));
+        assert_eq!(timer.next_deadline(at(10_600), false), None);
+        assert_eq!(timer.blink(at(15_500)), None);
+        assert_eq!(timer.next_deadline(at(15_500), true), None);
+
+        let mut other = Timer::new("pasta", Duration::from_millis(2500));
+        other.toggle(start);
+        let timers = [timer, other];
+        assert_eq!(next_deadline(&timers, at(100), true), Some(at(500)));
+        // 过了截止时间还没有更新的计时器要立即更新。
+        assert_eq!(next_deadline(&timers, at(12_000), false), Some(at(2500)));
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration(" 25:00 "), Ok(Duration::from_secs(1500)));
+        assert_eq!(parse_duration("1:02:03"), Ok(Duration::from_secs(3723)));
+        assert_eq!(parse_duration("5"), Ok(Duration::from_secs(300)));
+        for text in ["5x", "m5", "1:60", "1h30", "1:2:3:4"] {
+            assert_eq!(
+                parse_duration(text),
+                Err(format!(
+                    "{text:?} is not a duration like 5m, 1h30m or 25:00"
+                ))
+            );
+        }
+        assert_eq!(
+            parse_duration("0s"),
+            Err("the duration must be longer than zero".into())
+        );
+    }
+}
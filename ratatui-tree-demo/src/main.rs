@@ -0,0 +1,263 @@
+//! 树形控件演示：浏览嵌套的 JSON 文档。
+//!
+//! 使用共享库中的 `tree` 模块。每个节点的值是 JSON Pointer（例如 `/authors/0/name`），对象
+//! 和数组的子节点在第一次展开时才从文档中取出，大文件也不需要一开始就建好整棵树。右边显示选中的
+//! 节点的路径和值。没有指定文件时浏览内置的示例文档。
+//!
+//! 按键：`↑` / `↓` 选择，`→` 展开，`←` 折叠或回到上一层，`Enter` 展开或折叠，`q` 退出。
+
+use std::{fs, path::PathBuf, time::Duration};
+
+use clap::Parser;
+use color_eyre::{eyre::WrapErr, Result};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Paragraph, Wrap},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+    tree::{Item, Tree, TreeState, TreeView},
+};
+use serde_json::Value;
+
+/// 等待按键的最长时间。
+const REFRESH: Duration = Duration::from_secs(1);
+
+const SAMPLE: &str = r#"{
+  "name": "ratatui-demo",
+  "version": "0.1.0",
+  "private": true,
+  "authors": [{ "name": "Ada", "email": "ada@example.com" }, { "name": "Linus", "email": null }],
+  "dependencies": {
+    "ratatui": { "version": "0.26.3" },
+    "crossterm": { "version": "0.27.0", "features": ["event-stream"] },
+    "clap": { "version": "4.5.4", "features": ["derive"] }
+  },
+  "settings": { "tick_rate": 250, "theme": { "accent": "green", "borders": "rounded" } }
+}"#;
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 要浏览的 JSON 文件，不指定时使用内置的示例
+    file: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let (name, document) = match &cli.file {
+        Some(path) => {
+            let text = fs::read_to_string(path)
+                .wrap_err_with(|| format!("cannot open {}", path.display()))?;
+            let document = serde_json::from_str(&text)
+                .wrap_err_with(|| format!("{} is not valid JSON", path.display()))?;
+            (path.display().to_string(), document)
+        }
+        None => ("sample".to_string(), serde_json::from_str(SAMPLE)?),
+    };
+
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new(name, document).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+/// JSON Pointer 中的一段，`~` 和 `/` 需要转义。
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// 值的简短表示：对象和数组只显示有多少项。
+fn summary(value: &Value) -> Span<'static> {
+    match value {
+        Value::Object(object) => format!("{{{}}}", object.len()).dim(),
+        Value::Array(array) => format!("[{}]", array.len()).dim(),
+        Value::String(string) => format!("{string:?}").green(),
+        Value::Number(number) => number.to_string().yellow(),
+        Value::Bool(_) | Value::Null => value.to_string().magenta(),
+    }
+}
+
+fn item(key: String, pointer: String, value: &Value) -> Item<String> {
+    let label = Line::from(vec![key.cyan(), ": ".into(), summary(value)]);
+    match value {
+        Value::Object(object) if !object.is_empty() => Item::branch(pointer, label),
+        Value::Array(array) if !array.is_empty() => Item::branch(pointer, label),
+        _ => Item::leaf(pointer, label),
+    }
+}
+
+/// 取出 `pointer` 指向的对象或数组的子节点。
+fn children(document: &Value, pointer: &str) -> Vec<Item<String>> {
+    match document.pointer(pointer) {
+        Some(Value::Object(object)) => object
+            .iter()
+            .map(|(key, value)| item(key.clone(), format!("{pointer}/{}", escape(key)), value))
+            .collect(),
+        Some(Value::Array(array)) => array
+            .iter()
+            .enumerate()
+            .map(|(index, value)| item(index.to_string(), format!("{pointer}/{index}"), value))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+struct App {
+    name: String,
+    document: Value,
+    tree: Tree<String>,
+    state: TreeState,
+    exit: bool,
+}
+
+impl App {
+    fn new(name: String, document: Value) -> Self {
+        let mut tree = Tree::new();
+        let root = tree.push_root(item(name.clone(), String::new(), &document));
+        let mut state = TreeState::default();
+        state.expand(&mut tree, root, |pointer| children(&document, pointer));
+        state.select(Some(root));
+        Self {
+            name,
+            document,
+            tree,
+            state,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| frame.render_widget(&mut *self, frame.size()))?;
+            if events.poll(REFRESH)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        let document = &self.document;
+        if self
+            .state
+            .handle_key_event(key, &mut self.tree, |pointer| children(document, pointer))
+        {
+            return;
+        }
+        if let KeyCode::Char('q') | KeyCode::Esc = key.code {
+            self.exit = true;
+        }
+    }
+
+    fn selected(&self) -> Option<(&str, &Value)> {
+        let pointer = self.tree.node(self.state.selected()?).value.as_str();
+        Some((pointer, self.document.pointer(pointer)?))
+    }
+
+    fn render_value(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered().title(" Value ");
+        let Some((pointer, value)) = self.selected() else {
+            block.render(area, buf);
+            return;
+        };
+        let pointer = if pointer.is_empty() { "/" } else { pointer };
+        let mut lines = vec![Line::from(pointer.bold()), Line::default()];
+        let text = serde_json::to_string_pretty(value).unwrap_or_default();
+        lines.extend(text.lines().map(|line| Line::from(line.to_string())));
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(area, buf);
+    }
+}
+
+impl Widget for &mut App {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let [tree_area, value_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(main);
+        let tree = TreeView::new(&self.tree).block(
+            Block::bordered()
+                .title(format!(" {} ", self.name))
+                .title_bottom(Line::from(" Expand <→> Collapse <←> ").right_aligned()),
+        );
+        StatefulWidget::render(tree, tree_area, buf, &mut self.state);
+        self.render_value(value_area, buf);
+        Paragraph::new("Toggle <Enter> First <Home> Last <End> Quit <q>".dim()).render(status, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    #[test]
+    fn browse_the_sample() {
+        let mut app = App::new("sample".into(), serde_json::from_str(SAMPLE).unwrap());
+        assert_eq!(
+            rows(&mut app, 60, 12)[1..7],
+            [
+                "│▾ sample: {6}               ││/                           │",
+                "│  ▸ authors: [2]            ││                            │",
+                "│  ▸ dependencies: {3}       ││{                           │",
+                "│    name: \"ratatui-demo\"    ││  \"authors\": [              │",
+                "│    private: true           ││    {                       │",
+                "│  ▸ settings: {2}           ││      \"email\":              │",
+            ]
+        );
+
+        app.handle_key(KeyCode::Down.into());
+        app.handle_key(KeyCode::Right.into());
+        app.handle_key(KeyCode::Right.into());
+        assert_eq!(
+            rows(&mut app, 60, 12)[1..5],
+            [
+                "│▾ sample: {6}               ││/authors/0                  │",
+                "│  ▾ authors: [2]            ││                            │",
+                "│    ▸ 0: {2}                ││{                           │",
+                "│    ▸ 1: {2}                ││  \"email\":                  │",
+            ]
+        );
+
+        // 回到上一层再折叠。
+        app.handle_key(KeyCode::Left.into());
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(app.selected().unwrap().0, "/authors");
+        assert_eq!(
+            rows(&mut app, 60, 12)[3],
+            "│  ▸ dependencies: {3}       ││[                           │"
+        );
+        app.handle_key(KeyCode::Char('q').into());
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn pointers() {
+        let document: Value = serde_json::json!({ "a/b": { "~c": [1] } });
+        let items = children(&document, "");
+        assert_eq!(items[0].value, "/a~1b");
+        let items = children(&document, &items[0].value);
+        assert_eq!(items[0].value, "/a~1b/~0c");
+        assert_eq!(children(&document, &items[0].value)[0].value, "/a~1b/~0c/0");
+        assert!(children(&document, "/a~1b/~0c/0").is_empty());
+    }
+}
@@ -0,0 +1,96 @@
+//! 过去每一轮的成绩，保存为 JSON，每轮结束时立即写入，下次启动时恢复。
+
+use std::{fs, io, path::PathBuf};
+
+use color_eyre::{eyre::WrapErr, Result};
+use ratatui_common::files;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Run {
+    /// 结束时间，Unix 时间戳（秒）。
+    pub finished: i64,
+    pub wpm: f64,
+    /// 0–100%。
+    pub accuracy: f64,
+    pub seconds: f64,
+    pub characters: usize,
+}
+
+#[derive(Debug)]
+pub struct History {
+    path: PathBuf,
+    /// 按时间从早到晚排列。
+    runs: Vec<Run>,
+}
+
+impl History {
+    /// 读取记录文件。文件还不存在时没有记录。
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let runs = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .wrap_err_with(|| format!("parsing {} failed", path.display()))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => {
+                return Err(err).wrap_err_with(|| format!("reading {} failed", path.display()))
+            }
+        };
+        Ok(Self { path, runs })
+    }
+
+    pub fn runs(&self) -> &[Run] {
+        &self.runs
+    }
+
+    pub fn best(&self) -> Option<&Run> {
+        self.runs.iter().max_by(|a, b| a.wpm.total_cmp(&b.wpm))
+    }
+
+    /// 添加一轮成绩并写入文件。写入失败时成绩仍然保留在内存中。
+    pub fn push(&mut self, run: Run) -> Result<()> {
+        self.runs.push(run);
+        self.save()
+    }
+
+    /// 保存所有练习记录。
+    fn save(&self) -> Result<()> {
+        serde_json::to_vec_pretty(&self.runs)
+            .map_err(io::Error::from)
+            .and_then(|bytes| files::write_atomic(&self.path, bytes))
+            .wrap_err_with(|| format!("writing {} failed", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    fn run(wpm: f64) -> Run {
+        Run {
+            finished: 1704182400,
+            wpm,
+            accuracy: 95.0,
+            seconds: 12.5,
+            characters: 44,
+        }
+    }
+
+    #[test]
+    fn persist_runs() {
+        let dir = env::temp_dir().join(format!("typing-demo-history-{}", process::id()));
+        let path = dir.join("history.json");
+
+        let mut history = History::load(path.clone()).unwrap();
+        assert!(history.best().is_none());
+        history.push(run(42.0)).unwrap();
+        history.push(run(61.5)).unwrap();
+        history.push(run(50.0)).unwrap();
+
+        let history = History::load(path).unwrap();
+        assert_eq!(history.runs().len(), 3);
+        assert_eq!(history.best(), Some(&run(61.5)));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
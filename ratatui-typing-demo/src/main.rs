@@ -0,0 +1,443 @@
+//! 打字速度测试演示：照着显示的句子输入，逐字符标出输入正确与否，结束时计算每分钟字数（WPM）
+//! 和准确率。每一轮的成绩保存在数据目录中的 `history.json`，下次启动时恢复。
+//!
+//! 计时从第一次按键开始，输入的字符数等于句子长度时结束。WPM 按每 5 个正确字符算一个词；
+//! 准确率是输入时正确的按键占全部按键的比例，改正过的错误也计算在内。
+//!
+//! 按键：输入字符，`Backspace` 删除，`Tab` 换下一句重新开始，`Esc` 退出。
+
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local, Utc};
+use clap::Parser;
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Result,
+};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use directories::ProjectDirs;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, List, Padding, Paragraph, Wrap},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+};
+
+use crate::history::{History, Run};
+
+mod history;
+
+/// 等待按键的最长时间，之后刷新正在进行的这一轮的成绩。
+const REFRESH: Duration = Duration::from_millis(100);
+
+const APPLICATION: &str = "ratatui-typing-demo";
+
+const SENTENCES: &[&str] = &[
+    "The quick brown fox jumps over the lazy dog.",
+    "Pack my box with five dozen liquor jugs.",
+    "A terminal user interface draws everything with characters.",
+    "Rust makes it easy to write fast and reliable software.",
+    "Every widget renders itself into a buffer of cells.",
+];
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// 练习用的句子，每行一句，默认使用内置的句子
+    #[arg(long, value_name = "FILE")]
+    text: Option<PathBuf>,
+    /// 成绩记录，默认是数据目录中的 `history.json`
+    #[arg(long, value_name = "FILE")]
+    history: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    let sentences = match &cli.text {
+        Some(path) => {
+            let text = fs::read_to_string(path)
+                .wrap_err_with(|| format!("reading {} failed", path.display()))?;
+            let sentences: Vec<String> = text
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(String::from)
+                .collect();
+            if sentences.is_empty() {
+                bail!("{} contains no sentences", path.display());
+            }
+            sentences
+        }
+        None => SENTENCES
+            .iter()
+            .map(|sentence| sentence.to_string())
+            .collect(),
+    };
+    let path = cli
+        .history
+        .or_else(|| {
+            let project = ProjectDirs::from("", "", APPLICATION)?;
+            Some(project.data_dir().join("history.json"))
+        })
+        .ok_or_else(|| eyre!("no data directory, pass --history"))?;
+    let history = History::load(path)?;
+
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new(sentences, history).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+/// 一轮输入。
+struct Attempt {
+    target: Vec<char>,
+    typed: Vec<char>,
+    keystrokes: usize,
+    /// 输入时与句子不符的按键数，之后删除改正也不减少。
+    mistakes: usize,
+    started: Option<Instant>,
+    /// 结束时用了多长时间。
+    finished: Option<Duration>,
+}
+
+impl Attempt {
+    fn new(target: &str) -> Self {
+        Self {
+            target: target.chars().collect(),
+            typed: Vec::new(),
+            keystrokes: 0,
+            mistakes: 0,
+            started: None,
+            finished: None,
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.finished.is_some()
+    }
+
+    /// 输入一个字符，这一轮因此结束时返回 `true`。
+    fn type_char(&mut self, c: char, now: Instant) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+        let started = *self.started.get_or_insert(now);
+        self.keystrokes += 1;
+        if self.target.get(self.typed.len()) != Some(&c) {
+            self.mistakes += 1;
+        }
+        self.typed.push(c);
+        if self.typed.len() == self.target.len() {
+            self.finished = Some(now - started);
+            return true;
+        }
+        false
+    }
+
+    fn backspace(&mut self) {
+        if !self.is_finished() {
+            self.typed.pop();
+        }
+    }
+
+    fn elapsed(&self, now: Instant) -> Duration {
+        match (self.finished, self.started) {
+            (Some(elapsed), _) => elapsed,
+            (None, Some(started)) => now - started,
+            (None, None) => Duration::ZERO,
+        }
+    }
+
+    /// 当前与句子相符的字符数。
+    fn correct(&self) -> usize {
+        self.typed
+            .iter()
+            .zip(&self.target)
+            .filter(|(typed, target)| typed == target)
+            .count()
+    }
+
+    fn wpm(&self, now: Instant) -> f64 {
+        let seconds = self.elapsed(now).as_secs_f64();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+        // 每 5 个字符一个词，再换算成每分钟：`correct / 5 / (seconds / 60)`。
+        self.correct() as f64 * 12.0 / seconds
+    }
+
+    fn accuracy(&self) -> f64 {
+        if self.keystrokes == 0 {
+            return 100.0;
+        }
+        (self.keystrokes - self.mistakes) as f64 * 100.0 / self.keystrokes as f64
+    }
+
+    fn spans(&self) -> Vec<Span<'static>> {
+        self.target
+            .iter()
+            .enumerate()
+            .map(|(index, &target)| {
+                let span = Span::from(target.to_string());
+                match self.typed.get(index) {
+                    Some(&typed) if typed == target => span.green(),
+                    // 输入错误的空格只能靠背景色看出来。
+                    Some(_) => span.white().on_red(),
+                    None if index == self.typed.len() => span.reversed(),
+                    None => span.dim(),
+                }
+            })
+            .collect()
+    }
+}
+
+struct App {
+    sentences: Vec<String>,
+    sentence: usize,
+    attempt: Attempt,
+    history: History,
+    /// 最近一次操作的结果，`Err` 显示为红色。
+    message: Option<Result<String, String>>,
+    exit: bool,
+}
+
+impl App {
+    fn new(sentences: Vec<String>, history: History) -> Self {
+        Self {
+            attempt: Attempt::new(&sentences[0]),
+            sentences,
+            sentence: 0,
+            history,
+            message: None,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| self.render(frame.size(), frame.buffer_mut(), Instant::now()))?;
+            if events.poll(REFRESH)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key, Instant::now());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, now: Instant) {
+        match key.code {
+            KeyCode::Esc => self.exit = true,
+            KeyCode::Char('c') if key.modifiers == KeyModifiers::CONTROL => self.exit = true,
+            KeyCode::Tab => self.next(),
+            KeyCode::Backspace => self.attempt.backspace(),
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.type_char(c, now)
+            }
+            _ => {}
+        }
+    }
+
+    fn type_char(&mut self, c: char, now: Instant) {
+        if self.attempt.type_char(c, now) {
+            self.finish(now);
+        }
+    }
+
+    fn next(&mut self) {
+        self.sentence = (self.sentence + 1) % self.sentences.len();
+        self.attempt = Attempt::new(&self.sentences[self.sentence]);
+        self.message = None;
+    }
+
+    fn finish(&mut self, now: Instant) {
+        let run = Run {
+            finished: Utc::now().timestamp(),
+            wpm: self.attempt.wpm(now),
+            accuracy: self.attempt.accuracy(),
+            seconds: self.attempt.elapsed(now).as_secs_f64(),
+            characters: self.attempt.target.len(),
+        };
+        let best = self.history.best().map_or(0.0, |best| best.wpm);
+        let text = if run.wpm > best {
+            format!("New best: {:.0} WPM", run.wpm)
+        } else {
+            format!("Finished: {:.0} WPM", run.wpm)
+        };
+        self.message = Some(match self.history.push(run) {
+            Ok(()) => Ok(text),
+            Err(error) => Err(format!("{error:#}")),
+        });
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        match &self.message {
+            Some(Ok(message)) => Line::from(message.clone()),
+            Some(Err(error)) => Line::from(error.clone().red()),
+            None if self.attempt.started.is_none() => Line::from("Start typing to begin".dim()),
+            None => Line::default(),
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, now: Instant) {
+        let [text_area, stats_area, history_area, status_area] = Layout::vertical([
+            Constraint::Length(6),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let title = format!(" Sentence {}/{} ", self.sentence + 1, self.sentences.len());
+        let border = if self.attempt.is_finished() {
+            Style::new().green()
+        } else {
+            Style::new()
+        };
+        Paragraph::new(Line::from(self.attempt.spans()))
+            .wrap(Wrap { trim: false })
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .title_bottom(Line::from(" Next <Tab> Quit <Esc> ").right_aligned())
+                    .border_style(border)
+                    .padding(Padding::horizontal(1)),
+            )
+            .render(text_area, buf);
+
+        let attempt = &self.attempt;
+        Line::from(format!(
+            " WPM {:.0}  Accuracy {:.0}%  Time {:.1}s",
+            attempt.wpm(now),
+            attempt.accuracy(),
+            attempt.elapsed(now).as_secs_f64()
+        ))
+        .bold()
+        .render(stats_area, buf);
+
+        let items: Vec<Line> = self
+            .history
+            .runs()
+            .iter()
+            .rev()
+            .map(|run| {
+                let time = DateTime::from_timestamp(run.finished, 0)
+                    .map(|time| {
+                        time.with_timezone(&Local)
+                            .format("%Y-%m-%d %H:%M")
+                            .to_string()
+                    })
+                    .unwrap_or_default();
+                Line::from(vec![
+                    time.dim(),
+                    format!(
+                        "  {:>4.0} WPM  {:>3.0}%  {:>5.1}s",
+                        run.wpm, run.accuracy, run.seconds
+                    )
+                    .into(),
+                ])
+            })
+            .collect();
+        let mut block = Block::bordered().title(" History ");
+        if let Some(best) = self.history.best() {
+            block = block.title(Line::from(format!(" Best {:.0} WPM ", best.wpm)).right_aligned());
+        }
+        Widget::render(List::new(items).block(block), history_area, buf);
+
+        Paragraph::new(self.status_line()).render(status_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn app(name: &str) -> (App, PathBuf) {
+        let dir = env::temp_dir().join(format!("typing-demo-{name}-{}", process::id()));
+        let history = History::load(dir.join("history.json")).unwrap();
+        let sentences = vec!["abc def".to_string(), "xyz".to_string()];
+        (App::new(sentences, history), dir)
+    }
+
+    fn type_str(app: &mut App, text: &str, now: Instant) {
+        for c in text.chars() {
+            let code = if c == '\u{8}' {
+                KeyCode::Backspace
+            } else {
+                KeyCode::Char(c)
+            };
+            app.handle_key(code.into(), now);
+        }
+    }
+
+    fn rows(app: &App, width: u16, height: u16, now: Instant) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf, now))
+    }
+
+    #[test]
+    fn score_and_persist() {
+        let (mut app, dir) = app("score");
+        let start = Instant::now();
+        // 第一个字符错了，删除后改正。
+        type_str(&mut app, "x\u{8}abc", start);
+        type_str(&mut app, " def", start + Duration::from_secs(6));
+        assert!(app.attempt.is_finished());
+        assert_eq!((app.attempt.keystrokes, app.attempt.mistakes), (8, 1));
+        let run = &app.history.runs()[0];
+        // 7 个正确字符用了 6 秒：1.4 个词 / 0.1 分钟。
+        assert_eq!((run.wpm, run.accuracy, run.seconds), (14.0, 87.5, 6.0));
+        assert_eq!(app.message, Some(Ok("New best: 14 WPM".into())));
+
+        // 结束后不再接受输入，`Tab` 换下一句。
+        type_str(&mut app, "zz", start);
+        assert_eq!(app.attempt.typed.len(), 7);
+        app.handle_key(KeyCode::Tab.into(), start);
+        assert_eq!(app.attempt.target, vec!['x', 'y', 'z']);
+        assert_eq!(app.attempt.started, None);
+
+        let history = History::load(dir.join("history.json")).unwrap();
+        assert_eq!(history.runs().len(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn color_each_character() {
+        let (mut app, dir) = app("color");
+        let start = Instant::now();
+        type_str(&mut app, "abx", start);
+        let spans = app.attempt.spans();
+        assert_eq!(spans[0].style, Style::new().green());
+        assert_eq!(spans[2].style, Style::new().white().on_red());
+        assert_eq!(spans[3].style, Style::new().reversed());
+        assert_eq!(spans[4].style, Style::new().dim());
+
+        let rows = rows(&app, 50, 10, start + Duration::from_secs(3));
+        assert_eq!(
+            rows[0],
+            "┌ Sentence 1/2 ──────────────────────────────────┐"
+        );
+        assert_eq!(
+            rows[1],
+            "│ abc def                                        │"
+        );
+        assert_eq!(rows[6].trim_end(), " WPM 8  Accuracy 67%  Time 3.0s");
+        assert!(!dir.exists());
+    }
+}
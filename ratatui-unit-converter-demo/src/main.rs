@@ -0,0 +1,223 @@
+//! 单位换算演示：选择类别（长度、质量、温度、数据），输入数值，表格中实时显示换算为所有单位的结果。
+//!
+//! 按键：`Tab` / `Shift-Tab` 切换类别，`Up` / `Down` 选择输入的单位，其他按键编辑数值，`Esc` 退出。
+
+use color_eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Paragraph, Row, Table, Tabs},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+    text_input::Input,
+};
+
+use crate::units::Category;
+
+mod units;
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::default().run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+struct App {
+    /// 当前类别在 `Category::ALL` 中的位置。
+    category: usize,
+    /// 输入的数值使用的单位。
+    from: usize,
+    input: Input,
+    exit: bool,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self {
+            category: 0,
+            from: 0,
+            input: Input::with_value("1"),
+            exit: false,
+        }
+    }
+}
+
+impl App {
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| frame.render_widget(&*self, frame.size()))?;
+            if let Event::Key(key) = events.read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn category(&self) -> Category {
+        Category::ALL[self.category]
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        let count = Category::ALL.len();
+        match key.code {
+            KeyCode::Esc => self.exit = true,
+            KeyCode::Char('c') if key.modifiers == KeyModifiers::CONTROL => self.exit = true,
+            KeyCode::Tab => self.select_category((self.category + 1) % count),
+            KeyCode::BackTab => self.select_category((self.category + count - 1) % count),
+            KeyCode::Up => self.from = self.from.saturating_sub(1),
+            KeyCode::Down => self.from = (self.from + 1).min(self.category().units().len() - 1),
+            _ => {
+                self.input.handle_key_event(key);
+            }
+        }
+    }
+
+    /// 切换类别时输入的数值保持不变，单位回到该类别的第一个。
+    fn select_category(&mut self, index: usize) {
+        self.category = index;
+        self.from = 0;
+    }
+
+    /// 输入的数值，无法解析时返回 `None`。允许 `1e3` 这样的写法和逗号作为千位分隔符。
+    fn value(&self) -> Option<f64> {
+        self.input
+            .value()
+            .trim()
+            .replace(',', "")
+            .parse()
+            .ok()
+            .filter(|value: &f64| value.is_finite())
+    }
+}
+
+impl Widget for &App {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [tabs_area, input_area, results_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+
+        Tabs::new(Category::ALL.map(Category::name))
+            .select(self.category)
+            .highlight_style(Style::new().bold().reversed())
+            .render(tabs_area, buf);
+
+        let units = self.category().units();
+        let from = &units[self.from];
+        let mut line = self.input.line(" ", Style::new());
+        line.push_span(format!(" {}", from.symbol).bold());
+        let value = self.value();
+        if value.is_none() && !self.input.value().is_empty() {
+            line.push_span("  not a number".red());
+        }
+        Paragraph::new(line)
+            .block(Block::bordered().title(" Value "))
+            .render(input_area, buf);
+
+        let rows = units.iter().enumerate().map(|(index, unit)| {
+            let result = value.map_or_else(String::new, |value| {
+                units::format(units::convert(value, from, unit))
+            });
+            let row = Row::new([unit.name.to_string(), unit.symbol.to_string(), result]);
+            if index == self.from {
+                row.bold().yellow()
+            } else {
+                row
+            }
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(12),
+                Constraint::Length(4),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(Row::new(["Unit", "", "Value"]).dim())
+        .block(
+            Block::bordered()
+                .title(" Results ")
+                .title_bottom(Line::from(" Category <Tab> Unit <↑↓> Quit <Esc> ").centered()),
+        );
+        Widget::render(table, results_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn rows(app: &App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    #[test]
+    fn convert_temperature() {
+        let mut app = App::default();
+        app.handle_key(KeyCode::Tab.into());
+        app.handle_key(KeyCode::Tab.into());
+        app.handle_key(KeyCode::Backspace.into());
+        testing::type_text("100", |key| app.handle_key(key));
+        assert_eq!(
+            rows(&app, 40, 11),
+            [
+                " Length │ Mass │ Temperature │ Data     ",
+                "┌ Value ───────────────────────────────┐",
+                "│ 100  °C                              │",
+                "└──────────────────────────────────────┘",
+                "┌ Results ─────────────────────────────┐",
+                "│Unit              Value               │",
+                "│celsius      °C   100                 │",
+                "│fahrenheit   °F   212                 │",
+                "│kelvin       K    373.15              │",
+                "│                                      │",
+                "└ Category <Tab> Unit <↑↓> Quit <Esc> ─┘",
+            ]
+        );
+    }
+
+    #[test]
+    fn change_source_unit() {
+        let mut app = App::default();
+        app.handle_key(KeyCode::Down.into());
+        // 1 km = 1000 m。
+        assert_eq!(
+            rows(&app, 40, 9)[6],
+            "│metre        m    1000                │"
+        );
+        app.handle_key(KeyCode::BackTab.into());
+        assert_eq!(app.category(), Category::Data);
+        assert_eq!(app.from, 0);
+    }
+
+    #[test]
+    fn invalid_number() {
+        let mut app = App::default();
+        testing::type_text("x", |key| app.handle_key(key));
+        assert_eq!(app.value(), None);
+        assert_eq!(
+            rows(&app, 40, 9)[2],
+            "│ 1x  m  not a number                  │"
+        );
+        app.handle_key(KeyCode::Backspace.into());
+        testing::type_text(",000", |key| app.handle_key(key));
+        assert_eq!(app.value(), Some(1000.0));
+        app.handle_key(KeyCode::Esc.into());
+        assert!(app.exit);
+    }
+}
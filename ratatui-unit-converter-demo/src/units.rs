@@ -0,0 +1,176 @@
+//! 单位和换算。每个类别有一个基准单位，其他单位用 `基准值 = 值 × factor + offset` 换算，
+//! 温度这样带偏移的换算也能用同一个公式。
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Length,
+    Mass,
+    Temperature,
+    Data,
+}
+
+impl Category {
+    pub const ALL: [Category; 4] = [
+        Category::Length,
+        Category::Mass,
+        Category::Temperature,
+        Category::Data,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::Length => "Length",
+            Category::Mass => "Mass",
+            Category::Temperature => "Temperature",
+            Category::Data => "Data",
+        }
+    }
+
+    pub fn units(self) -> &'static [Unit] {
+        match self {
+            Category::Length => &LENGTH,
+            Category::Mass => &MASS,
+            Category::Temperature => &TEMPERATURE,
+            Category::Data => &DATA,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Unit {
+    pub name: &'static str,
+    pub symbol: &'static str,
+    factor: f64,
+    offset: f64,
+}
+
+const fn unit(name: &'static str, symbol: &'static str, factor: f64) -> Unit {
+    Unit {
+        name,
+        symbol,
+        factor,
+        offset: 0.0,
+    }
+}
+
+/// 基准单位是米。
+const LENGTH: [Unit; 8] = [
+    unit("metre", "m", 1.0),
+    unit("kilometre", "km", 1000.0),
+    unit("centimetre", "cm", 0.01),
+    unit("millimetre", "mm", 0.001),
+    unit("mile", "mi", 1609.344),
+    unit("yard", "yd", 0.9144),
+    unit("foot", "ft", 0.3048),
+    unit("inch", "in", 0.0254),
+];
+
+/// 基准单位是千克。
+const MASS: [Unit; 6] = [
+    unit("kilogram", "kg", 1.0),
+    unit("gram", "g", 0.001),
+    unit("milligram", "mg", 0.000_001),
+    unit("tonne", "t", 1000.0),
+    unit("pound", "lb", 0.453_592_37),
+    unit("ounce", "oz", 0.028_349_523_125),
+];
+
+/// 基准单位是开尔文。
+const TEMPERATURE: [Unit; 3] = [
+    Unit {
+        name: "celsius",
+        symbol: "°C",
+        factor: 1.0,
+        offset: 273.15,
+    },
+    Unit {
+        name: "fahrenheit",
+        symbol: "°F",
+        factor: 5.0 / 9.0,
+        offset: 459.67 * 5.0 / 9.0,
+    },
+    unit("kelvin", "K", 1.0),
+];
+
+/// 基准单位是字节。十进制和二进制前缀都有。
+const DATA: [Unit; 9] = [
+    unit("byte", "B", 1.0),
+    unit("kilobyte", "kB", 1e3),
+    unit("megabyte", "MB", 1e6),
+    unit("gigabyte", "GB", 1e9),
+    unit("terabyte", "TB", 1e12),
+    unit("kibibyte", "KiB", 1024.0),
+    unit("mebibyte", "MiB", 1024.0 * 1024.0),
+    unit("gibibyte", "GiB", 1024.0 * 1024.0 * 1024.0),
+    unit("tebibyte", "TiB", 1024.0 * 1024.0 * 1024.0 * 1024.0),
+];
+
+/// 把 `value` 从 `from` 换算为 `to`。
+pub fn convert(value: f64, from: &Unit, to: &Unit) -> f64 {
+    let base = value * from.factor + from.offset;
+    (base - to.offset) / to.factor
+}
+
+/// 显示换算结果：一般保留六位小数并去掉末尾的零，很大或很小的数使用科学计数法。
+pub fn format(value: f64) -> String {
+    let magnitude = value.abs();
+    if magnitude != 0.0 && !(1e-4..1e12).contains(&magnitude) {
+        return format!("{value:.6e}");
+    }
+    let text = format!("{value:.6}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".into()
+    } else {
+        text.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(category: Category, symbol: &str) -> Unit {
+        *category
+            .units()
+            .iter()
+            .find(|unit| unit.symbol == symbol)
+            .unwrap()
+    }
+
+    fn approx(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{a} != {b}");
+    }
+
+    #[test]
+    fn conversions() {
+        let length = |v, a, b| convert(v, &find(Category::Length, a), &find(Category::Length, b));
+        approx(length(1.0, "mi", "km"), 1.609344);
+        approx(length(12.0, "in", "ft"), 1.0);
+
+        let temperature = |v, a, b| {
+            convert(
+                v,
+                &find(Category::Temperature, a),
+                &find(Category::Temperature, b),
+            )
+        };
+        approx(temperature(100.0, "°C", "°F"), 212.0);
+        approx(temperature(-40.0, "°F", "°C"), -40.0);
+        approx(temperature(0.0, "K", "°C"), -273.15);
+
+        let data = |v, a, b| convert(v, &find(Category::Data, a), &find(Category::Data, b));
+        approx(data(1.0, "GiB", "MiB"), 1024.0);
+        approx(data(1.0, "MB", "kB"), 1000.0);
+    }
+
+    #[test]
+    fn format_numbers() {
+        assert_eq!(format(1.5), "1.5");
+        assert_eq!(format(100.0), "100");
+        assert_eq!(format(1.0 / 3.0), "0.333333");
+        assert_eq!(format(-0.0000001), "-1.000000e-7");
+        assert_eq!(format(1e15), "1.000000e15");
+        assert_eq!(format(0.0), "0");
+    }
+}
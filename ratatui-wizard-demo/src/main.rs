@@ -0,0 +1,436 @@
+//! 向导演示：分四步注册账号，每一步校验通过后才能前进，最后一步确认填写的内容。
+//! 步骤和进度来自 `ratatui_common::wizard`，这里只负责每一步的输入和校验。
+//!
+//! 按键：`Tab` / `↓` 下一项，`Shift+Tab` / `↑` 上一项，`Enter` 下一步，`Esc` 上一步，
+//! 在第一步按 `Esc` 退出。
+
+use std::{ops::Range, time::Duration};
+
+use color_eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Paragraph},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+    text_input::Input,
+    wizard::Wizard,
+};
+
+/// 等待按键的最长时间。
+const REFRESH: Duration = Duration::from_secs(1);
+
+const NAME: usize = 0;
+const EMAIL: usize = 1;
+const PASSWORD: usize = 2;
+const CONFIRM: usize = 3;
+
+const LABELS: [&str; 4] = ["Name:     ", "Email:    ", "Password: ", "Confirm:  "];
+
+const PLANS: [(&str, &str); 3] = [
+    ("Free", "1 project, community support"),
+    ("Pro", "unlimited projects, $8 a month"),
+    ("Team", "shared workspaces, $20 a user"),
+];
+
+/// 密码的最短长度。
+const MIN_PASSWORD: usize = 8;
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    terminal::install_panic_hook();
+    let mut terminal = terminal::init()?;
+    let result = App::new().run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Account,
+    Password,
+    Plan,
+    Review,
+}
+
+impl Step {
+    const ALL: [Self; 4] = [Self::Account, Self::Password, Self::Plan, Self::Review];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Account => "Account",
+            Self::Password => "Password",
+            Self::Plan => "Plan",
+            Self::Review => "Review",
+        }
+    }
+
+    /// 这一步的输入框。
+    fn fields(self) -> Range<usize> {
+        match self {
+            Self::Account => NAME..EMAIL + 1,
+            Self::Password => PASSWORD..CONFIRM + 1,
+            Self::Plan | Self::Review => 0..0,
+        }
+    }
+}
+
+/// 像 `sam@example.com` 这样，域名中至少有一个点。
+fn check_email(email: &str) -> Result<(), String> {
+    if email.is_empty() {
+        return Err("the email cannot be empty".into());
+    }
+    let valid = email.split_once('@').is_some_and(|(local, domain)| {
+        !local.is_empty()
+            && !domain.contains('@')
+            && domain.split('.').count() > 1
+            && domain.split('.').all(|part| !part.is_empty())
+    }) && !email.chars().any(char::is_whitespace);
+    if !valid {
+        return Err(format!(
+            "{email:?} is not an email address like sam@example.com"
+        ));
+    }
+    Ok(())
+}
+
+struct App {
+    wizard: Wizard,
+    fields: [Input; 4],
+    /// 当前步骤中正在输入的一项。
+    field: usize,
+    plan: usize,
+    exit: bool,
+}
+
+impl App {
+    fn new() -> Self {
+        Self {
+            wizard: Wizard::new(Step::ALL.map(Step::name)),
+            fields: Default::default(),
+            field: NAME,
+            plan: 0,
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| frame.render_widget(&*self, frame.size()))?;
+            if events.poll(REFRESH)? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn step(&self) -> Step {
+        Step::ALL[self.wizard.current()]
+    }
+
+    fn value(&self, field: usize) -> &str {
+        self.fields[field].value().trim()
+    }
+
+    /// 检查当前步骤，出错时返回出错的那一项和错误。
+    fn validate(&self) -> Result<(), (usize, String)> {
+        match self.step() {
+            Step::Account => {
+                if self.value(NAME).is_empty() {
+                    return Err((NAME, "the name cannot be empty".into()));
+                }
+                check_email(self.value(EMAIL)).map_err(|error| (EMAIL, error))
+            }
+            Step::Password => {
+                let password = self.fields[PASSWORD].value();
+                if password.chars().count() < MIN_PASSWORD {
+                    return Err((
+                        PASSWORD,
+                        format!("the password needs at least {MIN_PASSWORD} characters"),
+                    ));
+                }
+                if self.fields[CONFIRM].value() != password {
+                    return Err((CONFIRM, "the passwords do not match".into()));
+                }
+                Ok(())
+            }
+            Step::Plan | Step::Review => Ok(()),
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if self.wizard.is_finished() {
+            if let KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter = key.code {
+                self.exit = true;
+            }
+            return;
+        }
+        self.wizard.clear_error();
+        let fields = self.step().fields();
+        match key.code {
+            KeyCode::Enter => {
+                let validation = self.validate();
+                if let Err((field, _)) = &validation {
+                    self.field = *field;
+                }
+                if self.wizard.next(validation.map_err(|(_, error)| error)) {
+                    self.field = self.step().fields().start;
+                }
+            }
+            KeyCode::Esc => {
+                if self.wizard.back() {
+                    self.field = self.step().fields().start;
+                } else {
+                    self.exit = true;
+                }
+            }
+            KeyCode::Up if self.step() == Step::Plan => self.plan = self.plan.saturating_sub(1),
+            KeyCode::Down if self.step() == Step::Plan => {
+                self.plan = (self.plan + 1).min(PLANS.len() - 1);
+            }
+            KeyCode::Tab | KeyCode::Down if !fields.is_empty() => {
+                self.field = fields.start + (self.field - fields.start + 1) % fields.len();
+            }
+            KeyCode::BackTab | KeyCode::Up if !fields.is_empty() => {
+                self.field =
+                    fields.start + (self.field - fields.start + fields.len() - 1) % fields.len();
+            }
+            _ if !fields.is_empty() => {
+                self.fields[self.field].handle_key_event(key);
+            }
+            _ => {}
+        }
+    }
+
+    fn render_fields(&self, area: Rect, buf: &mut Buffer) {
+        let fields = self.step().fields();
+        let rows = Layout::vertical([Constraint::Length(1); 2]).split(area);
+        for (row, field) in rows.iter().zip(fields) {
+            let style = if field == self.field {
+                Style::new().green().bold()
+            } else {
+                Style::new()
+            };
+            let input = &self.fields[field];
+            let line = if field == NAME || field == EMAIL {
+                input.line(LABELS[field], style)
+            } else {
+                input.masked_line(LABELS[field], style, '•')
+            };
+            Paragraph::new(line).render(*row, buf);
+        }
+    }
+
+    fn render_plans(&self, area: Rect, buf: &mut Buffer) {
+        let lines: Vec<Line> = PLANS
+            .iter()
+            .enumerate()
+            .map(|(index, (name, description))| {
+                let line = Line::from(vec![
+                    format!("{name:<6}").bold(),
+                    description.to_string().into(),
+                ]);
+                if index == self.plan {
+                    line.reversed()
+                } else {
+                    line
+                }
+            })
+            .collect();
+        Paragraph::new(lines).render(area, buf);
+    }
+
+    fn render_review(&self, area: Rect, buf: &mut Buffer) {
+        let password = "•".repeat(self.fields[PASSWORD].value().chars().count());
+        let lines = vec![
+            Line::from(vec![LABELS[NAME].dim(), self.value(NAME).into()]),
+            Line::from(vec![LABELS[EMAIL].dim(), self.value(EMAIL).into()]),
+            Line::from(vec![LABELS[PASSWORD].dim(), password.into()]),
+            Line::from(vec!["Plan:     ".dim(), PLANS[self.plan].0.into()]),
+        ];
+        Paragraph::new(lines).render(area, buf);
+    }
+
+    fn render_finished(&self, area: Rect, buf: &mut Buffer) {
+        let lines = vec![
+            Line::from("Account created".green().bold()),
+            Line::default(),
+            Line::from(format!(
+                "Welcome, {}! You are on the {} plan.",
+                self.value(NAME),
+                PLANS[self.plan].0
+            )),
+        ];
+        Paragraph::new(lines).render(area, buf);
+    }
+
+    fn status_line(&self) -> Line<'_> {
+        if let Some(error) = self.wizard.error() {
+            return Line::from(error.red());
+        }
+        let hint = match self.step() {
+            _ if self.wizard.is_finished() => "Quit <q>",
+            Step::Account | Step::Password => "Next field <Tab> Previous field <Shift+Tab>",
+            Step::Plan => "Choose a plan <↑>/<↓>",
+            Step::Review => "Check the details, then press Enter to create the account",
+        };
+        Line::from(hint.dim())
+    }
+}
+
+impl Widget for &App {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [steps, main, status] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+        self.wizard.render(steps, buf);
+
+        let step = self.step();
+        let hint = if self.wizard.is_finished() {
+            ""
+        } else if step == Step::Review {
+            " Submit <Enter> Back <Esc> "
+        } else if self.wizard.is_first() {
+            " Next <Enter> Quit <Esc> "
+        } else {
+            " Next <Enter> Back <Esc> "
+        };
+        let title = if self.wizard.is_finished() {
+            " Done ".to_string()
+        } else {
+            format!(
+                " Step {} of {}: {} ",
+                self.wizard.current() + 1,
+                Step::ALL.len(),
+                step.name()
+            )
+        };
+        let block = Block::bordered()
+            .title(title.bold())
+            .title_bottom(Line::from(hint).right_aligned());
+        let inner = block.inner(main).inner(&Margin::new(2, 1));
+        block.render(main, buf);
+        match step {
+            _ if self.wizard.is_finished() => self.render_finished(inner, buf),
+            Step::Account | Step::Password => self.render_fields(inner, buf),
+            Step::Plan => self.render_plans(inner, buf),
+            Step::Review => self.render_review(inner, buf),
+        }
+        Paragraph::new(self.status_line()).render(status, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn rows(app: &App, width: u16, height: u16) -> Vec<String> {
+        testing::render_rows(width, height, |area, buf| app.render(area, buf))
+    }
+
+    #[test]
+    fn sign_up() {
+        let mut app = App::new();
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(
+            rows(&app, 52, 9)[..4],
+            [
+                "1 Account › 2 Password › 3 Plan › 4 Review          ",
+                "┌ Step 1 of 4: Account ────────────────────────────┐",
+                "│                                                  │",
+                "│  Name:                                           │",
+            ]
+        );
+        assert_eq!(rows(&app, 52, 9)[8].trim_end(), "the name cannot be empty");
+
+        testing::type_text("Ada", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Tab.into());
+        testing::type_text("ada@example", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(
+            app.wizard.error(),
+            Some("\"ada@example\" is not an email address like sam@example.com")
+        );
+        testing::type_text(".com", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+
+        // 密码太短时留在这一步，改正后校验确认的密码。
+        testing::type_text("secret", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(
+            app.wizard.error(),
+            Some("the password needs at least 8 characters")
+        );
+        testing::type_text("12", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Tab.into());
+        testing::type_text("secret21", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(
+            (app.wizard.error(), app.field),
+            (Some("the passwords do not match"), CONFIRM)
+        );
+        for _ in 0..2 {
+            app.handle_key(KeyCode::Backspace.into());
+        }
+        testing::type_text("12", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(
+            rows(&app, 52, 9)[..5],
+            [
+                "✓ Account › ✓ Password › 3 Plan › 4 Review          ",
+                "┌ Step 3 of 4: Plan ───────────────────────────────┐",
+                "│                                                  │",
+                "│  Free  1 project, community support              │",
+                "│  Pro   unlimited projects, $8 a month            │",
+            ]
+        );
+
+        app.handle_key(KeyCode::Down.into());
+        app.handle_key(KeyCode::Enter.into());
+        assert_eq!(
+            rows(&app, 52, 12)[3..7],
+            [
+                "│  Name:     Ada                                   │",
+                "│  Email:    ada@example.com                       │",
+                "│  Password: ••••••••                              │",
+                "│  Plan:     Pro                                   │",
+            ]
+        );
+
+        // 回到上一步时保留填写的内容。
+        app.handle_key(KeyCode::Esc.into());
+        assert_eq!(app.plan, 1);
+        app.handle_key(KeyCode::Enter.into());
+        app.handle_key(KeyCode::Enter.into());
+        assert!(app.wizard.is_finished());
+        assert_eq!(
+            rows(&app, 52, 9)[5],
+            "│  Welcome, Ada! You are on the Pro plan.          │"
+        );
+        app.handle_key(KeyCode::Char('q').into());
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn back_from_the_first_step_quits() {
+        let mut app = App::new();
+        testing::type_text("Ada", |key| app.handle_key(key));
+        app.handle_key(KeyCode::Esc.into());
+        assert!(app.exit);
+    }
+}
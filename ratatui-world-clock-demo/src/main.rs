@@ -0,0 +1,238 @@
+//! 世界时钟演示：在表格中显示多个时区的当前时间，每秒更新一次。
+//!
+//! 时区在命令行中用 IANA 名称指定，例如 `ratatui-world-clock-demo Asia/Tokyo America/New_York`。
+//! 每一行用 ☀ 和 ☾ 表示当地是白天还是夜晚，夏令时由 `chrono-tz` 的时区数据处理。
+//!
+//! 按键：`Up` / `Down` 选择行，`q` 退出。
+
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Offset, TimeZone, Timelike, Utc};
+use chrono_tz::{OffsetComponents, Tz};
+use clap::Parser;
+use color_eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Row, Table, TableState},
+};
+use ratatui_common::{
+    events::{EventSource, TerminalEvents},
+    terminal,
+};
+
+/// 没有指定时区时显示的时区。
+const DEFAULT_ZONES: [Tz; 6] = [
+    Tz::America__Los_Angeles,
+    Tz::America__New_York,
+    Tz::Europe__London,
+    Tz::Europe__Berlin,
+    Tz::Asia__Shanghai,
+    Tz::Australia__Sydney,
+];
+
+/// 当地时间在这个范围内（小时）算作白天。
+const DAYTIME: std::ops::Range<u32> = 6..18;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// 要显示的时区（IANA 名称，例如 `Europe/Paris`）。不指定时显示几个常见的时区。
+    #[arg(value_name = "TIMEZONE", value_parser = parse_zone)]
+    zones: Vec<Tz>,
+}
+
+fn parse_zone(name: &str) -> Result<Tz, String> {
+    name.parse()
+        .map_err(|_| format!("unknown timezone {name:?}"))
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    color_eyre::install()?;
+    terminal::install_panic_hook();
+    let zones = if cli.zones.is_empty() {
+        DEFAULT_ZONES.to_vec()
+    } else {
+        cli.zones
+    };
+    let mut terminal = terminal::init()?;
+    let result = App::new(zones, Utc::now()).run(&mut terminal, &mut TerminalEvents);
+    terminal::restore()?;
+    result
+}
+
+struct App {
+    zones: Vec<Tz>,
+    now: DateTime<Utc>,
+    table: TableState,
+    exit: bool,
+}
+
+impl App {
+    fn new(zones: Vec<Tz>, now: DateTime<Utc>) -> Self {
+        Self {
+            zones,
+            now,
+            table: TableState::default().with_selected(Some(0)),
+            exit: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut terminal::Tui,
+        events: &mut dyn EventSource<Event>,
+    ) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| self.render(frame))?;
+            // 等到下一个整秒再更新，秒数和系统时钟同时跳动。
+            let to_next_second = 1_000_000_000 - u64::from(self.now.timestamp_subsec_nanos());
+            let deadline = Instant::now() + Duration::from_nanos(to_next_second);
+            if events.poll(deadline.saturating_duration_since(Instant::now()))? {
+                if let Event::Key(key) = events.read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key.code);
+                    }
+                }
+            }
+            self.now = Utc::now();
+        }
+        Ok(())
+    }
+
+    fn handle_key(&mut self, code: KeyCode) {
+        let selected = self.table.selected().unwrap_or(0);
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
+            KeyCode::Up => self.table.select(Some(selected.saturating_sub(1))),
+            KeyCode::Down => {
+                self.table
+                    .select(Some((selected + 1).min(self.zones.len().saturating_sub(1))));
+            }
+            _ => {}
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let rows: Vec<Row> = self
+            .zones
+            .iter()
+            .map(|zone| zone_row(*zone, self.now))
+            .collect();
+        let header = Row::new(["", "Zone", "Time", "Date", "UTC", ""]).bold();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(1),
+                Constraint::Fill(1),
+                Constraint::Length(8),
+                Constraint::Length(10),
+                Constraint::Length(6),
+                Constraint::Length(4),
+            ],
+        )
+        .header(header)
+        .highlight_style(Style::new().reversed())
+        .block(
+            Block::bordered()
+                .title(" World Clock ".bold())
+                .title_bottom(Line::from(" Select <Up>/<Down> Quit <Q> ").centered()),
+        );
+        frame.render_stateful_widget(table, frame.size(), &mut self.table);
+    }
+}
+
+/// 一个时区的一行：白天或夜晚、名称、当地时间、日期、UTC 偏移，以及是否处于夏令时。
+fn zone_row(zone: Tz, now: DateTime<Utc>) -> Row<'static> {
+    let local = now.with_timezone(&zone);
+    let offset = zone.offset_from_utc_datetime(&now.naive_utc());
+    let indicator = if DAYTIME.contains(&local.hour()) {
+        "☀".yellow()
+    } else {
+        "☾".blue()
+    };
+    let dst = if offset.dst_offset().is_zero() {
+        ""
+    } else {
+        "DST"
+    };
+    Row::new([
+        Line::from(indicator),
+        Line::raw(zone.name()),
+        Line::raw(local.format("%H:%M:%S").to_string()),
+        Line::raw(local.format("%a %d %b").to_string()),
+        Line::raw(format_offset(offset.fix().local_minus_utc())),
+        Line::raw(dst).dim(),
+    ])
+}
+
+/// 例如 `+8`、`-3:30`。
+fn format_offset(seconds: i32) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.abs() / 60;
+    match minutes % 60 {
+        0 => format!("{sign}{}", minutes / 60),
+        rest => format!("{sign}{}:{rest:02}", minutes / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui_common::testing;
+
+    use super::*;
+
+    fn rows(app: &mut App, width: u16) -> Vec<String> {
+        let height = app.zones.len() as u16 + 3;
+        let mut terminal = Terminal::new(backend::TestBackend::new(width, height)).unwrap();
+        terminal.draw(|frame| app.render(frame)).unwrap();
+        testing::buffer_rows(terminal.backend().buffer())
+    }
+
+    #[test]
+    fn offsets() {
+        assert_eq!(format_offset(8 * 3600), "+8");
+        assert_eq!(format_offset(-(3 * 3600 + 1800)), "-3:30");
+        assert_eq!(format_offset(0), "+0");
+    }
+
+    #[test]
+    fn render_table() {
+        let now = Utc.with_ymd_and_hms(2024, 7, 1, 20, 30, 5).unwrap();
+        let mut app = App::new(
+            vec![Tz::Europe__Berlin, Tz::Asia__Kolkata, Tz::America__Denver],
+            now,
+        );
+        assert_eq!(
+            rows(&mut app, 50),
+            [
+                "┌ World Clock ───────────────────────────────────┐",
+                "│  Zone           Time     Date       UTC        │",
+                "│☾ Europe/Berlin  22:30:05 Mon 01 Jul +2     DST │",
+                "│☾ Asia/Kolkata   02:00:05 Tue 02 Jul +5:30      │",
+                "│☀ America/Denver 14:30:05 Mon 01 Jul -6     DST │",
+                "└───────── Select <Up>/<Down> Quit <Q> ──────────┘",
+            ]
+        );
+
+        // 冬季柏林没有夏令时，偏移变为 +1。
+        app.now = Utc.with_ymd_and_hms(2024, 1, 1, 8, 0, 0).unwrap();
+        assert_eq!(
+            rows(&mut app, 50)[2],
+            "│☀ Europe/Berlin  09:00:00 Mon 01 Jan +1         │"
+        );
+    }
+
+    #[test]
+    fn select_and_quit() {
+        let mut app = App::new(vec![Tz::UTC, Tz::Asia__Tokyo], Utc::now());
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Down);
+        assert_eq!(app.table.selected(), Some(1));
+        app.handle_key(KeyCode::Up);
+        assert_eq!(app.table.selected(), Some(0));
+        app.handle_key(KeyCode::Char('q'));
+        assert!(app.exit);
+    }
+}